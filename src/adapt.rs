@@ -0,0 +1,987 @@
+//! Rewrites chat requests to fit the target provider instead of failing.
+//!
+//! Each request mapper supports a different subset of [`ChatRequest`]: Anthropic only takes
+//! base64 images, Bedrock has no JSON mode, Ollama cannot force a specific tool. A
+//! [`RequestAdapter`] rewrites the request against the target's capabilities and mapper
+//! quirks and records every change as an [`Adaptation`], which makes falling back from one
+//! provider to another practical. Use it through
+//! [`crate::client::LLMClient::adapt_request`] or
+//! [`crate::client::LLMClient::chat_adapted`].
+
+use std::fmt;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use futures_util::StreamExt;
+
+use crate::http::{DynHttpTransport, HttpMethod, HttpRequest};
+use crate::types::{
+    CapabilityDescriptor, ChatRequest, ContentPart, ImageContent, ImageSource, Message,
+    ResponseFormat, Role, TextContent, ToolChoice,
+};
+
+/// One change made by [`RequestAdapter::adapt`].
+///
+/// Message and part indices refer to the request as it was passed in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Adaptation {
+    /// An image URL was fetched (or a `data:` URL decoded) into an inline base64 image.
+    ImageInlined {
+        /// Index of the message in [`ChatRequest::messages`].
+        message_index: usize,
+        /// Index of the part in the message content.
+        part_index: usize,
+        /// Original image URL, truncated for `data:` URLs.
+        url: String,
+    },
+    /// A content part was removed.
+    PartDropped {
+        /// Index of the message in [`ChatRequest::messages`].
+        message_index: usize,
+        /// Index of the part in the message content.
+        part_index: usize,
+        /// Part kind, such as `audio`.
+        kind: &'static str,
+        /// Why the part could not be kept.
+        reason: String,
+    },
+    /// System or developer messages after the conversation start were moved into the
+    /// leading system prompt, where the provider places them anyway.
+    SystemMessagesFolded {
+        /// Number of messages moved.
+        count: usize,
+    },
+    /// Tool definitions and the tool choice were removed because the model has no tool
+    /// support.
+    ToolsDropped {
+        /// Number of tool definitions removed.
+        count: usize,
+    },
+    /// A JSON response format was replaced by an instruction in the system prompt.
+    ResponseFormatAsInstruction {
+        /// `json_object` or `json_schema`.
+        format: &'static str,
+    },
+    /// A tool choice the provider cannot express was emulated by narrowing the tool list
+    /// and instructing the model.
+    ToolChoiceEmulated {
+        /// Name of the forced tool, or `None` for [`ToolChoice::Any`].
+        tool: Option<String>,
+    },
+    /// A forced tool choice naming no tool definition was removed, since emulating it would
+    /// leave the request without tools.
+    ToolChoiceDropped {
+        /// Name of the missing tool.
+        tool: String,
+    },
+}
+
+impl fmt::Display for Adaptation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ImageInlined {
+                message_index,
+                part_index,
+                url,
+            } => write!(
+                f,
+                "messages[{message_index}].content[{part_index}]: inlined image from {url}"
+            ),
+            Self::PartDropped {
+                message_index,
+                part_index,
+                kind,
+                reason,
+            } => write!(
+                f,
+                "messages[{message_index}].content[{part_index}]: dropped {kind} part ({reason})"
+            ),
+            Self::SystemMessagesFolded { count } => {
+                write!(f, "moved {count} system message(s) into the system prompt")
+            }
+            Self::ToolsDropped { count } => write!(f, "dropped {count} tool definition(s)"),
+            Self::ResponseFormatAsInstruction { format } => {
+                write!(f, "replaced {format} response format with an instruction")
+            }
+            Self::ToolChoiceEmulated { tool: Some(tool) } => {
+                write!(f, "emulated forced choice of tool `{tool}`")
+            }
+            Self::ToolChoiceEmulated { tool: None } => f.write_str("emulated required tool choice"),
+            Self::ToolChoiceDropped { tool } => {
+                write!(f, "dropped forced choice of unknown tool `{tool}`")
+            }
+        }
+    }
+}
+
+/// Request rewritten by [`RequestAdapter::adapt`] together with the changes made.
+#[derive(Debug, Clone)]
+pub struct AdaptedRequest {
+    /// Request ready to send to the target provider.
+    pub request: ChatRequest,
+    /// Every change made, in the order applied; empty when the request already fit.
+    pub changes: Vec<Adaptation>,
+}
+
+/// Rewrites requests to fit a provider's mapper and a model's capabilities.
+///
+/// Without a transport, image URLs that the provider cannot reference are dropped rather
+/// than fetched; `data:` URLs are always decoded locally. Fetches are bounded by
+/// [`RequestAdapter::DEFAULT_MAX_IMAGE_BYTES`] and
+/// [`RequestAdapter::DEFAULT_IMAGE_FETCH_TIMEOUT`] unless changed with
+/// [`RequestAdapter::with_image_fetch_limits`]; images over either limit are dropped.
+///
+/// # Examples
+///
+/// ```
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// use kotoba_llm::adapt::{Adaptation, RequestAdapter};
+/// use kotoba_llm::types::{CapabilityDescriptor, ChatRequest, ResponseFormat, Role};
+///
+/// let request = ChatRequest {
+///     messages: Vec::new(),
+///     options: Default::default(),
+///     tools: Vec::new(),
+///     tool_choice: None,
+///     response_format: Some(ResponseFormat::JsonObject),
+///     metadata: None,
+/// };
+/// let capabilities = CapabilityDescriptor { supports_structured_output: true, ..Default::default() };
+/// let adapted = RequestAdapter::new()
+///     .adapt(request, "anthropic_messages", &capabilities)
+///     .await;
+/// assert_eq!(
+///     adapted.changes,
+///     vec![Adaptation::ResponseFormatAsInstruction { format: "json_object" }]
+/// );
+/// assert!(adapted.request.response_format.is_none());
+/// assert_eq!(adapted.request.messages[0].role, Role::system());
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct RequestAdapter {
+    transport: Option<DynHttpTransport>,
+    max_image_bytes: usize,
+    image_fetch_timeout: Duration,
+}
+
+impl Default for RequestAdapter {
+    fn default() -> Self {
+        Self {
+            transport: None,
+            max_image_bytes: Self::DEFAULT_MAX_IMAGE_BYTES,
+            image_fetch_timeout: Self::DEFAULT_IMAGE_FETCH_TIMEOUT,
+        }
+    }
+}
+
+impl RequestAdapter {
+    /// Largest image fetched by default, matching the OpenAI per-image limit.
+    pub const DEFAULT_MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+    /// Default time allowed for fetching one image, including its body.
+    pub const DEFAULT_IMAGE_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Creates an adapter that never performs network calls.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetches image URLs through `transport` for providers that only accept inline
+    /// images.
+    pub fn with_transport(mut self, transport: DynHttpTransport) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Drops fetched images larger than `max_bytes` or taking longer than `timeout`.
+    pub fn with_image_fetch_limits(mut self, max_bytes: usize, timeout: Duration) -> Self {
+        self.max_image_bytes = max_bytes;
+        self.image_fetch_timeout = timeout;
+        self
+    }
+
+    /// Rewrites `request` for the provider named `provider` whose model offers
+    /// `capabilities`.
+    ///
+    /// Adaptation never fails: whatever cannot be converted is dropped and reported.
+    pub async fn adapt(
+        &self,
+        mut request: ChatRequest,
+        provider: &str,
+        capabilities: &CapabilityDescriptor,
+    ) -> AdaptedRequest {
+        let profile = Profile::for_provider(provider);
+        let mut changes = Vec::new();
+        let mut instructions = Vec::new();
+
+        for (message_index, message) in request.messages.iter_mut().enumerate() {
+            let is_system = is_system_role(&message.role);
+            let changes_before = changes.len();
+            let parts = std::mem::take(&mut message.content);
+            for (part_index, part) in parts.into_iter().enumerate() {
+                let mut note = |change: PartChange| {
+                    changes.push(change.into_adaptation(message_index, part_index))
+                };
+                if let Some(part) = self
+                    .adapt_part(part, is_system, &profile, capabilities, &mut note)
+                    .await
+                {
+                    message.content.push(part);
+                }
+            }
+            // Mappers reject empty messages; keep the turn so roles still alternate.
+            if message.content.is_empty() && changes.len() > changes_before {
+                message.content.push(ContentPart::Text(TextContent {
+                    text: "[content omitted]".to_string(),
+                }));
+            }
+        }
+
+        if !request.tools.is_empty() && !capabilities.supports_tools {
+            changes.push(Adaptation::ToolsDropped {
+                count: request.tools.len(),
+            });
+            request.tools.clear();
+            request.tool_choice = None;
+        }
+
+        match request.response_format.take() {
+            Some(ResponseFormat::JsonObject) if !profile.json_object => {
+                instructions.push("Respond only with a valid JSON object.".to_string());
+                changes.push(Adaptation::ResponseFormatAsInstruction {
+                    format: "json_object",
+                });
+            }
+            Some(ResponseFormat::JsonSchema { schema })
+                if !profile.json_schema || !capabilities.supports_structured_output =>
+            {
+                instructions.push(format!(
+                    "Respond only with JSON that conforms to this JSON Schema:\n{schema}"
+                ));
+                changes.push(Adaptation::ResponseFormatAsInstruction {
+                    format: "json_schema",
+                });
+            }
+            other => request.response_format = other,
+        }
+
+        match request.tool_choice.take() {
+            Some(ToolChoice::Tool { name })
+                if !profile.tool_choice_tool
+                    && !request.tools.iter().any(|tool| tool.name == name) =>
+            {
+                changes.push(Adaptation::ToolChoiceDropped { tool: name });
+            }
+            Some(ToolChoice::Tool { name }) if !profile.tool_choice_tool => {
+                request.tools.retain(|tool| tool.name == name);
+                if profile.tool_choice_any {
+                    request.tool_choice = Some(ToolChoice::Any);
+                }
+                instructions.push(format!("Call the `{name}` tool."));
+                changes.push(Adaptation::ToolChoiceEmulated { tool: Some(name) });
+            }
+            Some(ToolChoice::Any) if !profile.tool_choice_any => {
+                instructions.push("Call one of the provided tools.".to_string());
+                changes.push(Adaptation::ToolChoiceEmulated { tool: None });
+            }
+            other => request.tool_choice = other,
+        }
+
+        if profile.leading_system {
+            if let Some(change) = fold_system_messages(&mut request.messages) {
+                changes.push(change);
+            }
+        }
+        if !instructions.is_empty() {
+            append_system_instructions(&mut request.messages, instructions.join("\n\n"));
+        }
+
+        AdaptedRequest { request, changes }
+    }
+
+    async fn adapt_part(
+        &self,
+        part: ContentPart,
+        in_system: bool,
+        profile: &Profile,
+        capabilities: &CapabilityDescriptor,
+        note: &mut impl FnMut(PartChange),
+    ) -> Option<ContentPart> {
+        let kind = part_kind(&part);
+        // Providers that hoist system messages into a text-only system prompt lose the rest;
+        // cache breakpoints stay because they mark positions within that prompt.
+        if in_system
+            && profile.leading_system
+            && !matches!(part, ContentPart::Text(_) | ContentPart::CacheBreakpoint(_))
+        {
+            note(PartChange::Dropped(
+                kind,
+                "the system prompt only accepts text".to_string(),
+            ));
+            return None;
+        }
+
+        match part {
+            ContentPart::Image(_) if !capabilities.supports_image_input => {
+                note(PartChange::Dropped(
+                    kind,
+                    "the model does not accept image input".to_string(),
+                ));
+                None
+            }
+            ContentPart::Audio(_) if !capabilities.supports_audio_input => {
+                note(PartChange::Dropped(
+                    kind,
+                    "the model does not accept audio input".to_string(),
+                ));
+                None
+            }
+            ContentPart::Video(_) if !capabilities.supports_video_input => {
+                note(PartChange::Dropped(
+                    kind,
+                    "the model does not accept video input".to_string(),
+                ));
+                None
+            }
+            ContentPart::Audio(_) | ContentPart::Video(_) | ContentPart::File(_)
+                if !profile.media =>
+            {
+                note(PartChange::Dropped(
+                    kind,
+                    "the provider does not accept this content type".to_string(),
+                ));
+                None
+            }
+            ContentPart::Image(image) => self.adapt_image(image, profile, note).await,
+            other => Some(other),
+        }
+    }
+
+    async fn adapt_image(
+        &self,
+        image: ImageContent,
+        profile: &Profile,
+        note: &mut impl FnMut(PartChange),
+    ) -> Option<ContentPart> {
+        match &image.source {
+            ImageSource::Url { url } if !profile.image_url => match self.inline_image(url).await {
+                Ok((data, mime_type)) => {
+                    note(PartChange::Inlined(display_url(url)));
+                    Some(ContentPart::Image(ImageContent {
+                        source: ImageSource::Base64 {
+                            data,
+                            mime_type: Some(mime_type),
+                        },
+                        ..image
+                    }))
+                }
+                Err(reason) => {
+                    note(PartChange::Dropped("image", reason));
+                    None
+                }
+            },
+            ImageSource::FileId { .. } if !profile.image_file_id => {
+                note(PartChange::Dropped(
+                    "image",
+                    "the provider cannot reference uploaded files".to_string(),
+                ));
+                None
+            }
+            _ => Some(ContentPart::Image(image)),
+        }
+    }
+
+    /// Returns the base64 payload and MIME type of the image at `url`.
+    async fn inline_image(&self, url: &str) -> Result<(String, String), String> {
+        if let Some(rest) = url.strip_prefix("data:") {
+            return match rest.split_once(";base64,") {
+                Some((mime, data)) if mime.starts_with("image/") => {
+                    Ok((data.to_string(), mime.to_string()))
+                }
+                _ => Err("unsupported data URL".to_string()),
+            };
+        }
+        let transport = self
+            .transport
+            .as_ref()
+            .ok_or_else(|| "no transport configured to fetch image URLs".to_string())?;
+        let request = HttpRequest {
+            method: HttpMethod::Get,
+            url: url.to_string(),
+            headers: Default::default(),
+            body: None,
+            timeout: Some(self.image_fetch_timeout),
+        };
+        // The transport timeout may not cover the body, so bound the whole fetch as well.
+        tokio::time::timeout(self.image_fetch_timeout, async {
+            let response = transport
+                .send_stream(request)
+                .await
+                .map_err(|err| format!("failed to fetch image: {err}"))?;
+            if !(200..300).contains(&response.status) {
+                return Err(format!("fetching image returned HTTP {}", response.status));
+            }
+            let header = |wanted: &str| {
+                response
+                    .headers
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(wanted))
+                    .map(|(_, value)| value.as_str())
+            };
+            let mime_type = header("content-type")
+                .map(|value| value.split(';').next().unwrap_or_default().trim())
+                .filter(|mime| mime.starts_with("image/"))
+                .ok_or_else(|| "fetched resource is not an image".to_string())?
+                .to_string();
+            let too_large = || format!("image exceeds the {}-byte limit", self.max_image_bytes);
+            if header("content-length")
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .is_some_and(|length| length > self.max_image_bytes as u64)
+            {
+                return Err(too_large());
+            }
+            let mut body = Vec::new();
+            let mut chunks = response.body;
+            while let Some(chunk) = chunks.next().await {
+                let chunk = chunk.map_err(|err| format!("failed to fetch image: {err}"))?;
+                if body.len() + chunk.len() > self.max_image_bytes {
+                    return Err(too_large());
+                }
+                body.extend_from_slice(&chunk);
+            }
+            Ok((STANDARD.encode(&body), mime_type))
+        })
+        .await
+        .map_err(|_| {
+            format!(
+                "fetching image timed out after {}s",
+                self.image_fetch_timeout.as_secs_f64()
+            )
+        })?
+    }
+}
+
+/// What a provider's request mapper accepts.
+struct Profile {
+    /// `ImageSource::Url` is forwarded.
+    image_url: bool,
+    /// `ImageSource::FileId` is forwarded.
+    image_file_id: bool,
+    /// Audio, video and file parts are forwarded.
+    media: bool,
+    /// System messages are hoisted into a single text-only system prompt.
+    leading_system: bool,
+    json_object: bool,
+    json_schema: bool,
+    tool_choice_tool: bool,
+    tool_choice_any: bool,
+}
+
+impl Profile {
+    fn for_provider(provider: &str) -> Self {
+        let native = Self {
+            image_url: true,
+            image_file_id: true,
+            media: true,
+            leading_system: false,
+            json_object: true,
+            json_schema: true,
+            tool_choice_tool: true,
+            tool_choice_any: true,
+        };
+        match provider {
+            "anthropic_messages" => Self {
+                image_url: false,
+                image_file_id: false,
+                media: false,
+                leading_system: true,
+                json_object: false,
+                json_schema: false,
+                ..native
+            },
+            "bedrock_converse" => Self {
+                image_url: false,
+                image_file_id: false,
+                media: false,
+                leading_system: true,
+                json_object: false,
+                json_schema: false,
+                ..native
+            },
+            "google_gemini" => Self {
+                leading_system: true,
+                ..native
+            },
+            "cohere_chat" => Self {
+                image_file_id: false,
+                media: false,
+                ..native
+            },
+            "ollama" => Self {
+                image_url: false,
+                image_file_id: false,
+                media: false,
+                tool_choice_tool: false,
+                tool_choice_any: false,
+                ..native
+            },
+            // OpenAI-style mappers and unknown custom providers get the request untouched.
+            _ => native,
+        }
+    }
+}
+
+/// Part-level change recorded before its indices are known.
+enum PartChange {
+    Inlined(String),
+    Dropped(&'static str, String),
+}
+
+impl PartChange {
+    fn into_adaptation(self, message_index: usize, part_index: usize) -> Adaptation {
+        match self {
+            Self::Inlined(url) => Adaptation::ImageInlined {
+                message_index,
+                part_index,
+                url,
+            },
+            Self::Dropped(kind, reason) => Adaptation::PartDropped {
+                message_index,
+                part_index,
+                kind,
+                reason,
+            },
+        }
+    }
+}
+
+fn part_kind(part: &ContentPart) -> &'static str {
+    match part {
+        ContentPart::Text(_) => "text",
+        ContentPart::Image(_) => "image",
+        ContentPart::Audio(_) => "audio",
+        ContentPart::Video(_) => "video",
+        ContentPart::File(_) => "file",
+        ContentPart::ToolCall(_) => "tool_call",
+        ContentPart::ToolResult(_) => "tool_result",
+        ContentPart::Data { .. } => "data",
+        ContentPart::CacheBreakpoint(_) => "cache_breakpoint",
+    }
+}
+
+fn is_system_role(role: &Role) -> bool {
+    matches!(role.0.as_str(), "system" | "developer")
+}
+
+/// Keeps reports readable when the URL embeds the image itself.
+fn display_url(url: &str) -> String {
+    match url.split_once(',') {
+        Some((prefix, _)) if url.starts_with("data:") => format!("{prefix},..."),
+        _ => url.to_string(),
+    }
+}
+
+/// Moves system messages that follow the first conversational message to the front,
+/// keeping their relative order.
+fn fold_system_messages(messages: &mut Vec<Message>) -> Option<Adaptation> {
+    let first_turn = messages
+        .iter()
+        .position(|message| !is_system_role(&message.role))?;
+    let tail = messages.split_off(first_turn);
+    let (late, conversation): (Vec<Message>, Vec<Message>) = tail
+        .into_iter()
+        .partition(|message| is_system_role(&message.role));
+    let count = late.len();
+    messages.extend(late);
+    messages.extend(conversation);
+    (count > 0).then_some(Adaptation::SystemMessagesFolded { count })
+}
+
+/// Appends `text` to the leading system message, creating one when missing.
+fn append_system_instructions(messages: &mut Vec<Message>, text: String) {
+    let part = ContentPart::Text(TextContent { text });
+    match messages.first_mut() {
+        Some(message) if is_system_role(&message.role) => message.content.push(part),
+        _ => messages.insert(
+            0,
+            Message {
+                role: Role::system(),
+                name: None,
+                content: vec![part],
+                metadata: None,
+            },
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use futures_util::stream;
+    use serde_json::json;
+
+    use super::*;
+    use crate::error::LLMError;
+    use crate::http::{HttpResponse, HttpStreamResponse, HttpTransport};
+    use crate::types::{
+        AudioContent, MediaSource, ToolCall, ToolCallKind, ToolDefinition, ToolKind,
+    };
+
+    /// Serves a PNG payload for every GET, optionally stalling before the body ends.
+    struct ImageTransport {
+        chunks: Vec<Vec<u8>>,
+        content_length: Option<usize>,
+        stall: bool,
+    }
+
+    impl ImageTransport {
+        fn png() -> Self {
+            Self {
+                chunks: vec![b"pn".to_vec(), b"g".to_vec()],
+                content_length: Some(3),
+                stall: false,
+            }
+        }
+
+        fn headers(&self) -> HashMap<String, String> {
+            let mut headers = HashMap::from([(
+                "Content-Type".to_string(),
+                "image/png; charset=binary".to_string(),
+            )]);
+            if let Some(length) = self.content_length {
+                headers.insert("Content-Length".to_string(), length.to_string());
+            }
+            headers
+        }
+    }
+
+    #[async_trait]
+    impl HttpTransport for ImageTransport {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, LLMError> {
+            assert!(matches!(request.method, HttpMethod::Get));
+            Ok(HttpResponse {
+                status: 200,
+                headers: self.headers(),
+                body: self.chunks.concat(),
+            })
+        }
+
+        async fn send_stream(&self, request: HttpRequest) -> Result<HttpStreamResponse, LLMError> {
+            assert!(matches!(request.method, HttpMethod::Get));
+            assert!(request.timeout.is_some());
+            let chunks = stream::iter(self.chunks.clone().into_iter().map(Ok));
+            let body = if self.stall {
+                chunks.chain(stream::pending()).boxed()
+            } else {
+                chunks.boxed()
+            };
+            Ok(HttpStreamResponse {
+                status: 200,
+                headers: self.headers(),
+                body,
+            })
+        }
+    }
+
+    fn message(role: Role, content: Vec<ContentPart>) -> Message {
+        Message {
+            role,
+            name: None,
+            content,
+            metadata: None,
+        }
+    }
+
+    fn text(text: &str) -> ContentPart {
+        ContentPart::Text(TextContent {
+            text: text.to_string(),
+        })
+    }
+
+    fn image_url(url: &str) -> ContentPart {
+        ContentPart::Image(ImageContent {
+            source: ImageSource::Url {
+                url: url.to_string(),
+            },
+            detail: None,
+            metadata: None,
+        })
+    }
+
+    fn tool(name: &str) -> ToolDefinition {
+        ToolDefinition {
+            name: name.to_string(),
+            description: None,
+            input_schema: None,
+            kind: ToolKind::Function,
+            metadata: None,
+            cache_control: None,
+        }
+    }
+
+    fn request(messages: Vec<Message>) -> ChatRequest {
+        ChatRequest {
+            messages,
+            options: Default::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        }
+    }
+
+    fn multimodal() -> CapabilityDescriptor {
+        CapabilityDescriptor {
+            supports_tools: true,
+            supports_image_input: true,
+            supports_audio_input: true,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn rewrites_request_for_anthropic() {
+        let mut request = request(vec![
+            message(Role::system(), vec![text("Be brief.")]),
+            message(
+                Role::user(),
+                vec![
+                    text("Describe these."),
+                    image_url("data:image/png;base64,cG5n"),
+                    ContentPart::Audio(AudioContent {
+                        source: MediaSource::Url {
+                            url: "https://example.com/a.wav".to_string(),
+                        },
+                        mime_type: None,
+                        metadata: None,
+                    }),
+                ],
+            ),
+            message(
+                Role::assistant(),
+                vec![ContentPart::ToolCall(ToolCall {
+                    id: Some("call_1".to_string()),
+                    name: "lookup".to_string(),
+                    arguments: json!({"q": "cat"}),
+                    kind: ToolCallKind::Function,
+                })],
+            ),
+            message(
+                Role("developer".to_string()),
+                vec![text("Answer in French.")],
+            ),
+        ]);
+        request.response_format = Some(ResponseFormat::JsonSchema {
+            schema: json!({"type": "object"}),
+        });
+
+        let adapted = RequestAdapter::new()
+            .adapt(request, "anthropic_messages", &multimodal())
+            .await;
+
+        assert_eq!(
+            adapted.changes,
+            vec![
+                Adaptation::ImageInlined {
+                    message_index: 1,
+                    part_index: 1,
+                    url: "data:image/png;base64,...".to_string(),
+                },
+                Adaptation::PartDropped {
+                    message_index: 1,
+                    part_index: 2,
+                    kind: "audio",
+                    reason: "the provider does not accept this content type".to_string(),
+                },
+                Adaptation::ResponseFormatAsInstruction {
+                    format: "json_schema",
+                },
+                Adaptation::SystemMessagesFolded { count: 1 },
+            ]
+        );
+
+        let messages = &adapted.request.messages;
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0].role, Role::system());
+        assert_eq!(messages[1].role, Role("developer".to_string()));
+        assert!(matches!(
+            &messages[0].content[1],
+            ContentPart::Text(TextContent { text }) if text.contains("JSON Schema")
+        ));
+        assert!(matches!(
+            &messages[2].content[1],
+            ContentPart::Image(ImageContent {
+                source: ImageSource::Base64 { data, mime_type: Some(mime) },
+                ..
+            }) if data == "cG5n" && mime == "image/png"
+        ));
+        // The Anthropic mapper replays typed tool calls as `tool_use` itself.
+        assert!(matches!(
+            &messages[3].content[0],
+            ContentPart::ToolCall(ToolCall { id: Some(id), kind: ToolCallKind::Function, .. })
+                if id == "call_1"
+        ));
+        assert!(adapted.request.response_format.is_none());
+    }
+
+    #[tokio::test]
+    async fn fetches_image_urls_through_transport() {
+        let request = request(vec![message(
+            Role::user(),
+            vec![image_url("https://example.com/cat.png")],
+        )]);
+        let adapter = RequestAdapter::new().with_transport(Arc::new(ImageTransport::png()));
+
+        let adapted = adapter
+            .adapt(request.clone(), "ollama", &multimodal())
+            .await;
+        assert_eq!(
+            adapted.changes,
+            vec![Adaptation::ImageInlined {
+                message_index: 0,
+                part_index: 0,
+                url: "https://example.com/cat.png".to_string(),
+            }]
+        );
+        assert!(matches!(
+            &adapted.request.messages[0].content[0],
+            ContentPart::Image(ImageContent {
+                source: ImageSource::Base64 { data, mime_type: Some(mime) },
+                ..
+            }) if data == "cG5n" && mime == "image/png"
+        ));
+
+        // Without a transport the image is dropped and a placeholder keeps the turn.
+        let adapted = RequestAdapter::new()
+            .adapt(request, "ollama", &multimodal())
+            .await;
+        assert!(matches!(
+            adapted.changes[0],
+            Adaptation::PartDropped { kind: "image", .. }
+        ));
+        assert!(matches!(
+            &adapted.request.messages[0].content[..],
+            [ContentPart::Text(TextContent { text })] if text == "[content omitted]"
+        ));
+    }
+
+    #[tokio::test]
+    async fn emulates_tool_choice_and_drops_unsupported_tools() {
+        let mut forced = request(vec![message(Role::user(), vec![text("hi")])]);
+        forced.tools = vec![tool("lookup"), tool("search")];
+        forced.tool_choice = Some(ToolChoice::Tool {
+            name: "search".to_string(),
+        });
+
+        let adapted = RequestAdapter::new()
+            .adapt(forced.clone(), "ollama", &multimodal())
+            .await;
+        assert_eq!(
+            adapted.changes,
+            vec![Adaptation::ToolChoiceEmulated {
+                tool: Some("search".to_string()),
+            }]
+        );
+        assert_eq!(adapted.request.tools.len(), 1);
+        assert_eq!(adapted.request.tools[0].name, "search");
+        assert!(adapted.request.tool_choice.is_none());
+        assert_eq!(adapted.request.messages[0].role, Role::system());
+
+        let adapted = RequestAdapter::new()
+            .adapt(forced.clone(), "openai_chat", &multimodal())
+            .await;
+        assert!(adapted.changes.is_empty());
+        assert_eq!(adapted.request.messages.len(), 1);
+
+        let adapted = RequestAdapter::new()
+            .adapt(forced, "openai_chat", &CapabilityDescriptor::default())
+            .await;
+        assert_eq!(adapted.changes, vec![Adaptation::ToolsDropped { count: 2 }]);
+        assert!(adapted.request.tools.is_empty());
+        assert!(adapted.request.tool_choice.is_none());
+    }
+
+    #[tokio::test]
+    async fn drops_images_over_the_fetch_limits() {
+        let request = request(vec![message(
+            Role::user(),
+            vec![image_url("https://example.com/cat.png")],
+        )]);
+        let dropped = |transport: ImageTransport, max_bytes: usize| {
+            let adapter = RequestAdapter::new()
+                .with_transport(Arc::new(transport))
+                .with_image_fetch_limits(max_bytes, Duration::from_millis(50));
+            let request = request.clone();
+            async move {
+                let adapted = adapter.adapt(request, "ollama", &multimodal()).await;
+                match adapted.changes.as_slice() {
+                    [
+                        Adaptation::PartDropped {
+                            kind: "image",
+                            reason,
+                            ..
+                        },
+                    ] => reason.clone(),
+                    other => panic!("unexpected changes: {other:?}"),
+                }
+            }
+        };
+
+        // A declared length over the limit is rejected before reading the body.
+        let reason = dropped(ImageTransport::png(), 2).await;
+        assert_eq!(reason, "image exceeds the 2-byte limit");
+
+        // Without a declared length the body itself is counted.
+        let undeclared = ImageTransport {
+            content_length: None,
+            ..ImageTransport::png()
+        };
+        assert_eq!(
+            dropped(undeclared, 2).await,
+            "image exceeds the 2-byte limit"
+        );
+
+        // An understated length does not get past the body check either.
+        let understated = ImageTransport {
+            content_length: Some(1),
+            ..ImageTransport::png()
+        };
+        assert_eq!(
+            dropped(understated, 2).await,
+            "image exceeds the 2-byte limit"
+        );
+
+        let stalled = ImageTransport {
+            stall: true,
+            ..ImageTransport::png()
+        };
+        let reason = dropped(stalled, 1024).await;
+        assert!(reason.starts_with("fetching image timed out"), "{reason}");
+    }
+
+    #[tokio::test]
+    async fn drops_forced_choice_of_unknown_tool() {
+        let mut forced = request(vec![message(Role::user(), vec![text("hi")])]);
+        forced.tools = vec![tool("lookup"), tool("search")];
+        forced.tool_choice = Some(ToolChoice::Tool {
+            name: "missing".to_string(),
+        });
+
+        let adapted = RequestAdapter::new()
+            .adapt(forced, "ollama", &multimodal())
+            .await;
+        assert_eq!(
+            adapted.changes,
+            vec![Adaptation::ToolChoiceDropped {
+                tool: "missing".to_string(),
+            }]
+        );
+        assert_eq!(adapted.request.tools.len(), 2);
+        assert!(adapted.request.tool_choice.is_none());
+        assert_eq!(adapted.request.messages.len(), 1);
+    }
+}
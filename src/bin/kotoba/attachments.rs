@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::Path;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use kotoba_llm::types::{AudioContent, ContentPart, ImageContent, ImageSource, MediaSource};
+
+/// Media families the CLI knows how to attach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttachmentKind {
+    Image,
+    Audio,
+}
+
+/// Maps a file extension to its attachment family and MIME type.
+fn classify(path: &Path) -> Option<(AttachmentKind, &'static str)> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let classified = match extension.as_str() {
+        "png" => (AttachmentKind::Image, "image/png"),
+        "jpg" | "jpeg" => (AttachmentKind::Image, "image/jpeg"),
+        "gif" => (AttachmentKind::Image, "image/gif"),
+        "webp" => (AttachmentKind::Image, "image/webp"),
+        "wav" => (AttachmentKind::Audio, "audio/wav"),
+        "mp3" => (AttachmentKind::Audio, "audio/mpeg"),
+        "ogg" => (AttachmentKind::Audio, "audio/ogg"),
+        "flac" => (AttachmentKind::Audio, "audio/flac"),
+        "m4a" => (AttachmentKind::Audio, "audio/mp4"),
+        _ => return None,
+    };
+    Some(classified)
+}
+
+/// Reads `path` and wraps its bytes in the matching inline [`ContentPart`].
+pub(crate) fn load_attachment(path: &Path) -> Result<ContentPart, String> {
+    let (kind, mime) = classify(path).ok_or_else(|| {
+        format!(
+            "unsupported attachment {}: expected an image (png/jpg/gif/webp) or audio (wav/mp3/ogg/flac/m4a) file",
+            path.display()
+        )
+    })?;
+    let bytes =
+        fs::read(path).map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+    Ok(encode_attachment(kind, mime, &bytes))
+}
+
+fn encode_attachment(kind: AttachmentKind, mime: &str, bytes: &[u8]) -> ContentPart {
+    let data = STANDARD.encode(bytes);
+    match kind {
+        AttachmentKind::Image => ContentPart::Image(ImageContent {
+            source: ImageSource::Base64 {
+                data,
+                mime_type: Some(mime.to_string()),
+            },
+            detail: None,
+            metadata: None,
+        }),
+        AttachmentKind::Audio => ContentPart::Audio(AudioContent {
+            source: MediaSource::Inline { data },
+            mime_type: Some(mime.to_string()),
+            metadata: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_maps_common_extensions() {
+        assert_eq!(
+            classify(Path::new("photo.JPG")),
+            Some((AttachmentKind::Image, "image/jpeg"))
+        );
+        assert_eq!(
+            classify(Path::new("clip.mp3")),
+            Some((AttachmentKind::Audio, "audio/mpeg"))
+        );
+        assert_eq!(classify(Path::new("notes.txt")), None);
+        assert_eq!(classify(Path::new("no_extension")), None);
+    }
+
+    #[test]
+    fn encode_attachment_builds_inline_parts() {
+        match encode_attachment(AttachmentKind::Image, "image/png", b"png") {
+            ContentPart::Image(ImageContent {
+                source: ImageSource::Base64 { data, mime_type },
+                ..
+            }) => {
+                assert_eq!(data, "cG5n");
+                assert_eq!(mime_type.as_deref(), Some("image/png"));
+            }
+            other => panic!("unexpected part: {other:?}"),
+        }
+
+        match encode_attachment(AttachmentKind::Audio, "audio/wav", b"wav") {
+            ContentPart::Audio(AudioContent {
+                source: MediaSource::Inline { data },
+                mime_type,
+                ..
+            }) => {
+                assert_eq!(data, "d2F2");
+                assert_eq!(mime_type.as_deref(), Some("audio/wav"));
+            }
+            other => panic!("unexpected part: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn load_attachment_rejects_unknown_types() {
+        let err = load_attachment(Path::new("document.pdf")).unwrap_err();
+        assert!(err.contains("unsupported attachment"));
+    }
+}
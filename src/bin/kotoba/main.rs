@@ -0,0 +1,293 @@
+//! `kotoba` command-line tool for ad-hoc chats against configured handles.
+//!
+//! Build with `cargo run --features cli --bin kotoba -- --config models.toml list`.
+
+mod attachments;
+mod render;
+
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use futures_util::StreamExt;
+use kotoba_llm::client::LLMClient;
+use kotoba_llm::config::{ModelConfig, build_client_from_configs, load_configs_from_path};
+use kotoba_llm::http::reqwest::default_dyn_transport;
+use kotoba_llm::types::{
+    ChatEvent, ChatOptions, ChatRequest, ContentDelta, ContentPart, Message, Role, TextContent,
+    TokenUsage,
+};
+
+use crate::render::Pricing;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "kotoba",
+    version,
+    about = "Ad-hoc chats against configured LLM handles"
+)]
+struct Cli {
+    /// TOML, YAML or JSON file holding the `ModelConfig` list.
+    #[arg(short, long, env = "KOTOBA_CONFIG")]
+    config: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List configured handles with their provider, default model and capabilities.
+    List,
+    /// Send a one-shot or interactive chat to a handle.
+    Chat(ChatArgs),
+}
+
+#[derive(Debug, Args)]
+struct ChatArgs {
+    /// Handle to chat with.
+    handle: String,
+    /// Prompt for a one-shot chat; read from stdin when omitted outside interactive mode.
+    prompt: Option<String>,
+    /// System prompt prepended to the conversation.
+    #[arg(long)]
+    system: Option<String>,
+    /// Model override; defaults to the handle's configured model.
+    #[arg(long)]
+    model: Option<String>,
+    /// Image or audio file attached to the first user message (repeatable).
+    #[arg(long = "attach", value_name = "PATH")]
+    attachments: Vec<PathBuf>,
+    /// Maximum number of output tokens.
+    #[arg(long)]
+    max_tokens: Option<u32>,
+    /// Sampling temperature.
+    #[arg(long)]
+    temperature: Option<f32>,
+    /// Keep the conversation open and read follow-up prompts from stdin.
+    #[arg(short, long)]
+    interactive: bool,
+    /// Wait for the full response instead of streaming it.
+    #[arg(long)]
+    no_stream: bool,
+    /// Print the vendor request (after `RequestPatch`) instead of sending it.
+    #[arg(long)]
+    dump_request: bool,
+    /// Input price in USD per million tokens, used for the cost estimate.
+    #[arg(long, value_name = "USD")]
+    input_price: Option<f64>,
+    /// Output price in USD per million tokens, used for the cost estimate.
+    #[arg(long, value_name = "USD")]
+    output_price: Option<f64>,
+}
+
+type CliResult<T> = Result<T, String>;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> CliResult<()> {
+    let configs = load_configs_from_path(&cli.config).map_err(|err| err.to_string())?;
+    let transport = default_dyn_transport().map_err(|err| err.to_string())?;
+    let client = build_client_from_configs(&configs, transport).map_err(|err| err.to_string())?;
+    match cli.command {
+        Command::List => list_handles(&client, &configs),
+        Command::Chat(args) => {
+            let secret_headers = configs
+                .iter()
+                .find(|config| config.handle == args.handle)
+                .map(render::config_secret_headers)
+                .unwrap_or_default();
+            chat(&client, args, &secret_headers).await
+        }
+    }
+}
+
+fn list_handles(client: &LLMClient, configs: &[ModelConfig]) -> CliResult<()> {
+    let mut sorted: Vec<&ModelConfig> = configs.iter().collect();
+    sorted.sort_by(|a, b| a.handle.cmp(&b.handle));
+    for config in sorted {
+        let caps = client
+            .capabilities(&config.handle)
+            .map_err(|err| err.to_string())?;
+        println!(
+            "{}\t{}\t{}\t{}",
+            config.handle,
+            config.provider.as_str(),
+            config.default_model.as_deref().unwrap_or("-"),
+            render::capability_flags(&caps)
+        );
+    }
+    Ok(())
+}
+
+async fn chat(client: &LLMClient, args: ChatArgs, secret_headers: &[String]) -> CliResult<()> {
+    let pricing = Pricing {
+        input_per_million: args.input_price,
+        output_per_million: args.output_price,
+    };
+    let mut messages = Vec::new();
+    if let Some(system) = &args.system {
+        messages.push(text_message(Role::system(), system.clone()));
+    }
+    let mut pending_attachments = args
+        .attachments
+        .iter()
+        .map(|path| attachments::load_attachment(path))
+        .collect::<CliResult<Vec<ContentPart>>>()?;
+
+    let first_prompt = match &args.prompt {
+        Some(prompt) => Some(prompt.clone()),
+        None if !args.interactive => Some(read_stdin()?),
+        None => None,
+    };
+    let mut next_prompt = first_prompt;
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        let prompt = match next_prompt.take() {
+            Some(prompt) => prompt,
+            None => {
+                eprint!("> ");
+                io::stderr().flush().ok();
+                match lines.next() {
+                    Some(line) => line.map_err(|err| err.to_string())?,
+                    None => break,
+                }
+            }
+        };
+        match prompt.trim() {
+            "" => continue,
+            "/exit" | "/quit" => break,
+            "/reset" if args.interactive => {
+                messages.retain(|message: &Message| message.role == Role::system());
+                eprintln!("(conversation reset)");
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut user = text_message(Role::user(), prompt);
+        user.content.append(&mut pending_attachments);
+        messages.push(user);
+        let request = ChatRequest {
+            messages: messages.clone(),
+            options: ChatOptions {
+                model: args.model.clone(),
+                temperature: args.temperature,
+                max_output_tokens: args.max_tokens,
+                ..ChatOptions::default()
+            },
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        };
+
+        if args.dump_request {
+            let prepared = client
+                .prepare_request(&args.handle, &request, !args.no_stream)
+                .await
+                .map_err(|err| err.to_string())?;
+            let rendered = serde_json::to_string_pretty(&render::prepared_request_json(
+                &prepared,
+                secret_headers,
+            ))
+            .map_err(|err| err.to_string())?;
+            println!("{rendered}");
+            messages.pop();
+        } else {
+            let (reply, usage) = if args.no_stream {
+                send_once(client, &args.handle, request).await?
+            } else {
+                send_streaming(client, &args.handle, request).await?
+            };
+            if let Some(usage) = usage {
+                eprintln!("{}", render::usage_line(&usage, &pricing));
+            }
+            messages.push(text_message(Role::assistant(), reply));
+        }
+
+        if !args.interactive {
+            break;
+        }
+    }
+    Ok(())
+}
+
+async fn send_once(
+    client: &LLMClient,
+    handle: &str,
+    request: ChatRequest,
+) -> CliResult<(String, Option<TokenUsage>)> {
+    let response = client
+        .chat(handle, request)
+        .await
+        .map_err(|err| err.to_string())?;
+    let text = render::response_text(&response);
+    println!("{}", text.trim_end());
+    Ok((text, response.usage))
+}
+
+async fn send_streaming(
+    client: &LLMClient,
+    handle: &str,
+    request: ChatRequest,
+) -> CliResult<(String, Option<TokenUsage>)> {
+    let mut stream = client
+        .stream_chat(handle, request)
+        .await
+        .map_err(|err| err.to_string())?;
+    let mut text = String::new();
+    let mut usage = None;
+    let mut stdout = io::stdout();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|err| err.to_string())?;
+        for event in &chunk.events {
+            if let ChatEvent::MessageDelta(delta) = event {
+                for content in &delta.content {
+                    if let ContentDelta::Text { text: piece }
+                    | ContentDelta::Refusal { text: piece } = content
+                    {
+                        text.push_str(piece);
+                        print!("{piece}");
+                        stdout.flush().ok();
+                    }
+                }
+            }
+        }
+        if let Some(update) = chunk.usage {
+            render::merge_usage(&mut usage, update);
+        }
+        if chunk.is_terminal {
+            break;
+        }
+    }
+    println!();
+    Ok((text, usage))
+}
+
+fn text_message(role: Role, text: String) -> Message {
+    Message {
+        role,
+        name: None,
+        content: vec![ContentPart::Text(TextContent { text })],
+        metadata: None,
+    }
+}
+
+fn read_stdin() -> CliResult<String> {
+    let mut buffer = String::new();
+    io::Read::read_to_string(&mut io::stdin(), &mut buffer).map_err(|err| err.to_string())?;
+    Ok(buffer)
+}
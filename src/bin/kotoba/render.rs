@@ -0,0 +1,299 @@
+use std::collections::{BTreeMap, HashMap};
+
+use kotoba_llm::config::{Credential, ModelConfig};
+use kotoba_llm::provider::PreparedRequest;
+use kotoba_llm::types::{
+    CapabilityDescriptor, ChatResponse, ContentPart, OutputItem, TextContent, TokenUsage,
+};
+use serde_json::{Value, json};
+
+/// Header names whose values carry credentials and must never be printed.
+const SECRET_HEADERS: [&str; 5] = [
+    "authorization",
+    "x-api-key",
+    "x-goog-api-key",
+    "api-key",
+    "x-amz-security-token",
+];
+
+/// Per-million-token prices used to turn usage into an approximate cost.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Pricing {
+    pub(crate) input_per_million: Option<f64>,
+    pub(crate) output_per_million: Option<f64>,
+}
+
+impl Pricing {
+    /// Returns the USD cost for `usage`, or `None` when no price was configured.
+    pub(crate) fn cost(&self, usage: &TokenUsage) -> Option<f64> {
+        if self.input_per_million.is_none() && self.output_per_million.is_none() {
+            return None;
+        }
+        let input = usage.prompt_tokens.unwrap_or(0) as f64 * self.input_per_million.unwrap_or(0.0)
+            / 1_000_000.0;
+        let output = usage.completion_tokens.unwrap_or(0) as f64
+            * self.output_per_million.unwrap_or(0.0)
+            / 1_000_000.0;
+        Some(input + output)
+    }
+}
+
+/// Headers `config` may fill with credentials besides [`SECRET_HEADERS`]: a custom
+/// `Credential::ApiKey` header and every header set through `patch.headers` or
+/// `extra.headers`.
+pub(crate) fn config_secret_headers(config: &ModelConfig) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Credential::ApiKey {
+        header: Some(header),
+        ..
+    } = &config.credential
+    {
+        names.push(header.clone());
+    }
+    if let Some(headers) = config
+        .patch
+        .as_ref()
+        .and_then(|patch| patch.headers.as_ref())
+    {
+        names.extend(
+            headers
+                .iter()
+                .filter(|(_, value)| value.is_some())
+                .map(|(name, _)| name.clone()),
+        );
+    }
+    if let Some(Value::Object(headers)) = config.extra.get("headers") {
+        names.extend(headers.keys().cloned());
+    }
+    names
+}
+
+/// Renders a prepared request as JSON with credential headers and `secret_headers` masked.
+pub(crate) fn prepared_request_json(
+    prepared: &PreparedRequest,
+    secret_headers: &[String],
+) -> Value {
+    json!({
+        "url": prepared.url,
+        "headers": redact_headers(&prepared.headers, secret_headers),
+        "body": prepared.body,
+    })
+}
+
+fn redact_headers(
+    headers: &HashMap<String, String>,
+    secret_headers: &[String],
+) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let secret = SECRET_HEADERS
+                .iter()
+                .any(|secret| name.eq_ignore_ascii_case(secret))
+                || secret_headers
+                    .iter()
+                    .any(|secret| name.eq_ignore_ascii_case(secret));
+            let value = if secret {
+                "<redacted>".to_string()
+            } else {
+                value.clone()
+            };
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+/// Produces the space-separated capability flags shown by `kotoba list`.
+pub(crate) fn capability_flags(caps: &CapabilityDescriptor) -> String {
+    let flags = [
+        (caps.supports_stream, "stream"),
+        (caps.supports_tools, "tools"),
+        (caps.supports_parallel_tool_calls, "parallel-tools"),
+        (caps.supports_structured_output, "structured-output"),
+        (caps.supports_image_input, "image"),
+        (caps.supports_audio_input, "audio"),
+        (caps.supports_video_input, "video"),
+    ];
+    let enabled: Vec<&str> = flags
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| *name)
+        .collect();
+    if enabled.is_empty() {
+        "-".to_string()
+    } else {
+        enabled.join(" ")
+    }
+}
+
+/// Formats token usage and, when prices are known, the resulting cost.
+pub(crate) fn usage_line(usage: &TokenUsage, pricing: &Pricing) -> String {
+    let field = |value: Option<u64>| value.map_or_else(|| "?".to_string(), |v| v.to_string());
+    let mut line = format!(
+        "usage: prompt={} completion={} total={}",
+        field(usage.prompt_tokens),
+        field(usage.completion_tokens),
+        field(usage.total_tokens)
+    );
+    if let Some(reasoning) = usage.reasoning_tokens {
+        line.push_str(&format!(" reasoning={reasoning}"));
+    }
+    if let Some(cached) = usage.cached_input_tokens {
+        line.push_str(&format!(" cached={cached}"));
+    }
+    if let Some(cost) = pricing.cost(usage) {
+        line.push_str(&format!(" cost=${cost:.6}"));
+    }
+    line
+}
+
+/// Folds a streaming usage update into the running total, keeping known values.
+pub(crate) fn merge_usage(acc: &mut Option<TokenUsage>, update: TokenUsage) {
+    let current = acc.get_or_insert_with(TokenUsage::default);
+    current.prompt_tokens = update.prompt_tokens.or(current.prompt_tokens);
+    current.completion_tokens = update.completion_tokens.or(current.completion_tokens);
+    current.reasoning_tokens = update.reasoning_tokens.or(current.reasoning_tokens);
+    current.total_tokens = update.total_tokens.or(current.total_tokens);
+    current.cached_input_tokens = update.cached_input_tokens.or(current.cached_input_tokens);
+    current.cache_creation_tokens = update
+        .cache_creation_tokens
+        .or(current.cache_creation_tokens);
+    if update.details.is_some() {
+        current.details = update.details;
+    }
+}
+
+/// Collects the assistant text of a non-streaming response, one line per tool call.
+pub(crate) fn response_text(response: &ChatResponse) -> String {
+    let mut buffer = String::new();
+    for output in &response.outputs {
+        match output {
+            OutputItem::Message { message, .. } => {
+                for part in &message.content {
+                    if let ContentPart::Text(TextContent { text }) = part {
+                        buffer.push_str(text);
+                    }
+                }
+            }
+            OutputItem::Refusal { text, .. } => buffer.push_str(text),
+            OutputItem::ToolCall { call, .. } => {
+                if !buffer.is_empty() && !buffer.ends_with('\n') {
+                    buffer.push('\n');
+                }
+                buffer.push_str(&format!("[tool call] {}({})\n", call.name, call.arguments));
+            }
+            _ => {}
+        }
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prepared_request_json_masks_credentials() {
+        let prepared = PreparedRequest {
+            url: "https://api.example.com/v1/chat/completions".to_string(),
+            headers: HashMap::from([
+                ("Authorization".to_string(), "Bearer sk-secret".to_string()),
+                ("x-goog-api-key".to_string(), "g-secret".to_string()),
+                ("X-Trace".to_string(), "abc".to_string()),
+            ]),
+            body: json!({ "model": "gpt-4.1-mini" }),
+        };
+
+        let rendered = prepared_request_json(&prepared, &[]);
+        assert_eq!(rendered["headers"]["Authorization"], json!("<redacted>"));
+        assert_eq!(rendered["headers"]["x-goog-api-key"], json!("<redacted>"));
+        assert_eq!(rendered["headers"]["X-Trace"], json!("abc"));
+        assert_eq!(rendered["body"]["model"], json!("gpt-4.1-mini"));
+        assert!(!rendered.to_string().contains("secret"));
+    }
+
+    #[test]
+    fn prepared_request_json_masks_config_defined_headers() {
+        let config: ModelConfig = serde_json::from_value(json!({
+            "handle": "gateway",
+            "provider": "openai_chat",
+            "credential": { "type": "api_key", "header": "X-Gateway-Key", "key": "k-secret" },
+            "patch": { "headers": { "X-Tenant-Token": "t-secret", "X-Removed": null } },
+            "extra": { "headers": { "X-Extra-Auth": "e-secret" } }
+        }))
+        .expect("config");
+        let secret_headers = config_secret_headers(&config);
+        let prepared = PreparedRequest {
+            url: "https://bedrock-runtime.us-east-1.amazonaws.com/model/m/converse".to_string(),
+            headers: HashMap::from([
+                ("x-gateway-key".to_string(), "k-secret".to_string()),
+                ("X-Tenant-Token".to_string(), "t-secret".to_string()),
+                ("X-Extra-Auth".to_string(), "e-secret".to_string()),
+                (
+                    "X-Amz-Security-Token".to_string(),
+                    "session-secret".to_string(),
+                ),
+                ("X-Amz-Date".to_string(), "20250101T000000Z".to_string()),
+            ]),
+            body: json!({}),
+        };
+
+        let rendered = prepared_request_json(&prepared, &secret_headers);
+        assert!(!secret_headers.contains(&"X-Removed".to_string()));
+        assert_eq!(rendered["headers"]["X-Amz-Date"], json!("20250101T000000Z"));
+        assert!(!rendered.to_string().contains("secret"));
+    }
+
+    #[test]
+    fn usage_line_includes_cost_when_priced() {
+        let usage = TokenUsage {
+            prompt_tokens: Some(1_000),
+            completion_tokens: Some(500),
+            total_tokens: Some(1_500),
+            ..TokenUsage::default()
+        };
+        let unpriced = usage_line(&usage, &Pricing::default());
+        assert_eq!(unpriced, "usage: prompt=1000 completion=500 total=1500");
+
+        let pricing = Pricing {
+            input_per_million: Some(2.0),
+            output_per_million: Some(8.0),
+        };
+        let priced = usage_line(&usage, &pricing);
+        assert!(priced.ends_with("cost=$0.006000"), "{priced}");
+    }
+
+    #[test]
+    fn merge_usage_keeps_earlier_fields() {
+        let mut acc = None;
+        merge_usage(
+            &mut acc,
+            TokenUsage {
+                prompt_tokens: Some(10),
+                ..TokenUsage::default()
+            },
+        );
+        merge_usage(
+            &mut acc,
+            TokenUsage {
+                completion_tokens: Some(4),
+                ..TokenUsage::default()
+            },
+        );
+        let usage = acc.expect("usage");
+        assert_eq!(usage.prompt_tokens, Some(10));
+        assert_eq!(usage.completion_tokens, Some(4));
+    }
+
+    #[test]
+    fn capability_flags_lists_enabled_features() {
+        let caps = CapabilityDescriptor {
+            supports_stream: true,
+            supports_tools: true,
+            supports_image_input: true,
+            ..CapabilityDescriptor::default()
+        };
+        assert_eq!(capability_flags(&caps), "stream tools image");
+        assert_eq!(capability_flags(&CapabilityDescriptor::default()), "-");
+    }
+}
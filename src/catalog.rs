@@ -0,0 +1,663 @@
+//! Per-model capabilities and limits.
+//!
+//! [`crate::provider::LLMProvider::capabilities`] describes what a provider mapper can
+//! express, but the model behind it decides what actually works: `gpt-4o-audio-preview`
+//! accepts audio while `o3-mini` rejects images, and output limits range from 4K to 100K
+//! tokens. A [`ModelCatalog`] records those per-model facts keyed by provider name so
+//! [`crate::client::LLMClient::capabilities_for_request`] can narrow the provider
+//! descriptor to the model a request targets.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::CapabilityDescriptor;
+
+/// Reasoning controls accepted by a model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningMode {
+    /// Discrete effort levels such as OpenAI `reasoning_effort`.
+    Effort,
+    /// Explicit thinking budgets such as Anthropic `budget_tokens` or Gemini `thinkingBudget`.
+    BudgetTokens,
+}
+
+/// Facts about one model, or a family of models sharing an id prefix.
+///
+/// Every field except `model` is optional; `None` means "unknown", which leaves the
+/// provider-level value untouched.
+///
+/// # Examples
+///
+/// ```
+/// use kotoba_llm::catalog::{ModelInfo, ReasoningMode};
+///
+/// let info = ModelInfo::new("acme-large")
+///     .with_context_window(64_000)
+///     .with_max_output_tokens(8_192)
+///     .with_reasoning(vec![ReasoningMode::Effort]);
+/// assert_eq!(info.context_window, Some(64_000));
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// Model id, or an id prefix matched at a `-`, `:` or `@` boundary.
+    pub model: String,
+    /// Capabilities of the model; combined with the provider descriptor by logical AND.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<CapabilityDescriptor>,
+    /// Maximum number of input plus output tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    /// Maximum number of tokens the model can generate in one response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+    /// Reasoning controls; an empty list marks a model without reasoning support.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<Vec<ReasoningMode>>,
+    /// Training data cutoff, formatted as `YYYY-MM`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub knowledge_cutoff: Option<String>,
+}
+
+impl ModelInfo {
+    /// Creates an entry for `model` with every fact unknown.
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the model capabilities.
+    pub fn with_capabilities(mut self, capabilities: CapabilityDescriptor) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// Sets the context window in tokens.
+    pub fn with_context_window(mut self, tokens: u64) -> Self {
+        self.context_window = Some(tokens);
+        self
+    }
+
+    /// Sets the maximum output tokens.
+    pub fn with_max_output_tokens(mut self, tokens: u64) -> Self {
+        self.max_output_tokens = Some(tokens);
+        self
+    }
+
+    /// Sets the supported reasoning controls.
+    pub fn with_reasoning(mut self, modes: Vec<ReasoningMode>) -> Self {
+        self.reasoning = Some(modes);
+        self
+    }
+
+    /// Sets the knowledge cutoff (`YYYY-MM`).
+    pub fn with_knowledge_cutoff(mut self, cutoff: impl Into<String>) -> Self {
+        self.knowledge_cutoff = Some(cutoff.into());
+        self
+    }
+
+    /// Returns `true` when the model accepts `mode`.
+    pub fn supports_reasoning(&self, mode: ReasoningMode) -> bool {
+        self.reasoning
+            .as_ref()
+            .is_some_and(|modes| modes.contains(&mode))
+    }
+
+    /// Overwrites the fields that `other` knows, keeping the rest.
+    fn merge(&mut self, other: ModelInfo) {
+        if other.capabilities.is_some() {
+            self.capabilities = other.capabilities;
+        }
+        if other.context_window.is_some() {
+            self.context_window = other.context_window;
+        }
+        if other.max_output_tokens.is_some() {
+            self.max_output_tokens = other.max_output_tokens;
+        }
+        if other.reasoning.is_some() {
+            self.reasoning = other.reasoning;
+        }
+        if other.knowledge_cutoff.is_some() {
+            self.knowledge_cutoff = other.knowledge_cutoff;
+        }
+    }
+
+    /// Returns `true` when `model` is this entry's id or starts with it at a version
+    /// boundary, so `gpt-4o` covers `gpt-4o-2024-08-06` but not `gpt-4o1`.
+    fn matches(&self, model: &str) -> bool {
+        match model.strip_prefix(self.model.as_str()) {
+            Some("") => true,
+            Some(rest) => rest.starts_with(['-', ':', '@']),
+            None => false,
+        }
+    }
+}
+
+/// Model facts keyed by provider name ([`crate::provider::LLMProvider::name`]).
+///
+/// Lookups pick the exact id when present and otherwise the longest matching prefix, so
+/// dated snapshots such as `claude-3-5-haiku-20241022` resolve to the family entry. Ids
+/// with a path such as Gemini's `models/gemini-2.0-flash` are also matched by their last
+/// segment.
+///
+/// # Examples
+///
+/// ```
+/// use kotoba_llm::catalog::{ModelCatalog, ModelInfo};
+///
+/// let mut catalog = ModelCatalog::builtin();
+/// let info = catalog.lookup("openai_chat", "gpt-4o-mini-2024-07-18").expect("known model");
+/// assert_eq!(info.model, "gpt-4o-mini");
+///
+/// // Overrides only replace the fields they set.
+/// catalog.insert("openai_chat", ModelInfo::new("gpt-4o-mini").with_max_output_tokens(4_096));
+/// let info = catalog.lookup("openai_chat", "gpt-4o-mini").expect("known model");
+/// assert_eq!(info.max_output_tokens, Some(4_096));
+/// assert_eq!(info.context_window, Some(128_000));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ModelCatalog {
+    entries: HashMap<String, Vec<ModelInfo>>,
+}
+
+impl ModelCatalog {
+    /// Creates an empty catalog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a catalog pre-populated with the models this crate knows about.
+    ///
+    /// The built-in data covers the OpenAI, Anthropic, Gemini, Bedrock (Claude) and Cohere
+    /// families; self-hosted providers such as `ollama` and `openai_compatible` have no
+    /// built-in entries.
+    pub fn builtin() -> Self {
+        let mut catalog = Self::new();
+        let openai = openai_models();
+        for provider in ["openai_chat", "openai_responses", "azure_openai"] {
+            for info in &openai {
+                catalog.insert(provider, info.clone());
+            }
+        }
+        for info in anthropic_models() {
+            // Bedrock ids carry a vendor prefix and, for inference profiles, a region.
+            for prefix in ["", "us.", "eu.", "apac.", "global."] {
+                let mut bedrock = info.clone();
+                bedrock.model = format!("{prefix}anthropic.{}", info.model);
+                catalog.insert("bedrock_converse", bedrock);
+            }
+            catalog.insert("anthropic_messages", info);
+        }
+        for info in gemini_models() {
+            catalog.insert("google_gemini", info);
+        }
+        for info in cohere_models() {
+            catalog.insert("cohere_chat", info);
+        }
+        catalog
+    }
+
+    /// Adds `info` for `provider`, merging it onto the entry it overrides.
+    ///
+    /// An entry with the same id is updated in place. A new id starts from the entry its
+    /// id currently resolves to, so overriding one field of `gpt-4o-2024-08-06` keeps the
+    /// remaining `gpt-4o` facts.
+    pub fn insert(&mut self, provider: impl Into<String>, info: ModelInfo) {
+        let provider = provider.into();
+        let base = self.lookup(&provider, &info.model).cloned();
+        let entries = self.entries.entry(provider).or_default();
+        if let Some(existing) = entries.iter_mut().find(|entry| entry.model == info.model) {
+            existing.merge(info);
+            return;
+        }
+        let entry = match base {
+            Some(mut base) => {
+                base.model = info.model.clone();
+                base.merge(info);
+                base
+            }
+            None => info,
+        };
+        entries.push(entry);
+    }
+
+    /// Builder-style variant of [`ModelCatalog::insert`].
+    pub fn with_model(mut self, provider: impl Into<String>, info: ModelInfo) -> Self {
+        self.insert(provider, info);
+        self
+    }
+
+    /// Returns the entry describing `model` on `provider`, if any.
+    pub fn lookup(&self, provider: &str, model: &str) -> Option<&ModelInfo> {
+        let entries = self.entries.get(provider)?;
+        let find = |model: &str| {
+            entries
+                .iter()
+                .filter(|entry| entry.matches(model))
+                .max_by_key(|entry| entry.model.len())
+        };
+        find(model).or_else(|| model.rsplit_once('/').and_then(|(_, last)| find(last)))
+    }
+
+    /// Narrows `base` (the provider descriptor) to what `model` supports.
+    ///
+    /// Returns `base` unchanged when the model is unknown or has no capability entry.
+    pub fn capabilities(
+        &self,
+        provider: &str,
+        model: Option<&str>,
+        base: CapabilityDescriptor,
+    ) -> CapabilityDescriptor {
+        match model
+            .and_then(|model| self.lookup(provider, model))
+            .and_then(|info| info.capabilities.as_ref())
+        {
+            Some(caps) => base.intersect(caps),
+            None => base,
+        }
+    }
+
+    /// Returns the provider names that have at least one entry, sorted.
+    pub fn providers(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Capability flags used by the built-in tables; streaming is available everywhere.
+fn caps(
+    image: bool,
+    audio: bool,
+    video: bool,
+    tools: bool,
+    parallel: bool,
+) -> CapabilityDescriptor {
+    CapabilityDescriptor {
+        supports_stream: true,
+        supports_image_input: image,
+        supports_audio_input: audio,
+        supports_video_input: video,
+        supports_tools: tools,
+        supports_structured_output: tools,
+        supports_parallel_tool_calls: parallel,
+    }
+}
+
+fn model(
+    id: &str,
+    capabilities: CapabilityDescriptor,
+    context_window: u64,
+    max_output_tokens: u64,
+    reasoning: &[ReasoningMode],
+    knowledge_cutoff: Option<&str>,
+) -> ModelInfo {
+    ModelInfo {
+        model: id.to_string(),
+        capabilities: Some(capabilities),
+        context_window: Some(context_window),
+        max_output_tokens: Some(max_output_tokens),
+        reasoning: Some(reasoning.to_vec()),
+        knowledge_cutoff: knowledge_cutoff.map(str::to_string),
+    }
+}
+
+fn openai_models() -> Vec<ModelInfo> {
+    use ReasoningMode::Effort;
+    let vision = caps(true, false, false, true, true);
+    // Reasoning models reject `parallel_tool_calls`.
+    let reasoning_vision = caps(true, false, false, true, false);
+    let mut audio = caps(false, true, false, true, true);
+    audio.supports_structured_output = false;
+    vec![
+        model(
+            "gpt-4o",
+            vision.clone(),
+            128_000,
+            16_384,
+            &[],
+            Some("2023-10"),
+        ),
+        model(
+            "gpt-4o-mini",
+            vision.clone(),
+            128_000,
+            16_384,
+            &[],
+            Some("2023-10"),
+        ),
+        model(
+            "gpt-4o-audio-preview",
+            audio,
+            128_000,
+            16_384,
+            &[],
+            Some("2023-10"),
+        ),
+        model(
+            "gpt-4.1",
+            vision.clone(),
+            1_047_576,
+            32_768,
+            &[],
+            Some("2024-06"),
+        ),
+        model(
+            "gpt-4.1-mini",
+            vision.clone(),
+            1_047_576,
+            32_768,
+            &[],
+            Some("2024-06"),
+        ),
+        model(
+            "gpt-4.1-nano",
+            vision,
+            1_047_576,
+            32_768,
+            &[],
+            Some("2024-06"),
+        ),
+        // The first o1 releases are text-only, without tools or `reasoning_effort`; the
+        // longer ids keep them from resolving to the bare `o1` entry.
+        model(
+            "o1-mini",
+            caps(false, false, false, false, false),
+            128_000,
+            65_536,
+            &[],
+            Some("2023-10"),
+        ),
+        model(
+            "o1-preview",
+            caps(false, false, false, false, false),
+            128_000,
+            32_768,
+            &[],
+            Some("2023-10"),
+        ),
+        model(
+            "o1",
+            reasoning_vision.clone(),
+            200_000,
+            100_000,
+            &[Effort],
+            Some("2023-10"),
+        ),
+        model(
+            "o3",
+            reasoning_vision.clone(),
+            200_000,
+            100_000,
+            &[Effort],
+            Some("2024-06"),
+        ),
+        model(
+            "o3-mini",
+            caps(false, false, false, true, false),
+            200_000,
+            100_000,
+            &[Effort],
+            Some("2023-10"),
+        ),
+        model(
+            "o4-mini",
+            reasoning_vision,
+            200_000,
+            100_000,
+            &[Effort],
+            Some("2024-06"),
+        ),
+    ]
+}
+
+fn anthropic_models() -> Vec<ModelInfo> {
+    use ReasoningMode::BudgetTokens;
+    let claude = caps(true, false, false, true, true);
+    vec![
+        model(
+            "claude-3-haiku",
+            claude.clone(),
+            200_000,
+            4_096,
+            &[],
+            Some("2023-08"),
+        ),
+        model(
+            "claude-3-opus",
+            claude.clone(),
+            200_000,
+            4_096,
+            &[],
+            Some("2023-08"),
+        ),
+        model(
+            "claude-3-5-haiku",
+            claude.clone(),
+            200_000,
+            8_192,
+            &[],
+            Some("2024-07"),
+        ),
+        model(
+            "claude-3-5-sonnet",
+            claude.clone(),
+            200_000,
+            8_192,
+            &[],
+            Some("2024-04"),
+        ),
+        model(
+            "claude-3-7-sonnet",
+            claude.clone(),
+            200_000,
+            64_000,
+            &[BudgetTokens],
+            Some("2024-10"),
+        ),
+        model(
+            "claude-sonnet-4",
+            claude.clone(),
+            200_000,
+            64_000,
+            &[BudgetTokens],
+            Some("2025-03"),
+        ),
+        model(
+            "claude-opus-4",
+            claude,
+            200_000,
+            32_000,
+            &[BudgetTokens],
+            Some("2025-03"),
+        ),
+    ]
+}
+
+fn gemini_models() -> Vec<ModelInfo> {
+    use ReasoningMode::BudgetTokens;
+    let gemini = caps(true, true, true, true, true);
+    vec![
+        model(
+            "gemini-2.5-pro",
+            gemini.clone(),
+            1_048_576,
+            65_536,
+            &[BudgetTokens],
+            Some("2025-01"),
+        ),
+        model(
+            "gemini-2.5-flash",
+            gemini.clone(),
+            1_048_576,
+            65_536,
+            &[BudgetTokens],
+            Some("2025-01"),
+        ),
+        model(
+            "gemini-2.0-flash",
+            gemini.clone(),
+            1_048_576,
+            8_192,
+            &[],
+            Some("2024-08"),
+        ),
+        model(
+            "gemini-2.0-flash-lite",
+            gemini,
+            1_048_576,
+            8_192,
+            &[],
+            Some("2024-08"),
+        ),
+    ]
+}
+
+fn cohere_models() -> Vec<ModelInfo> {
+    use ReasoningMode::BudgetTokens;
+    let command = caps(false, false, false, true, true);
+    vec![
+        model(
+            "command-a-03-2025",
+            command.clone(),
+            256_000,
+            8_000,
+            &[],
+            None,
+        ),
+        model(
+            "command-a-vision",
+            caps(true, false, false, false, false),
+            128_000,
+            8_000,
+            &[],
+            None,
+        ),
+        model(
+            "command-a-reasoning",
+            command.clone(),
+            256_000,
+            32_000,
+            &[BudgetTokens],
+            None,
+        ),
+        model("command-r-plus", command.clone(), 128_000, 4_000, &[], None),
+        model("command-r", command, 128_000, 4_000, &[], None),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_prefers_exact_then_longest_prefix() {
+        let catalog = ModelCatalog::builtin();
+        let info = catalog.lookup("openai_chat", "gpt-4o").expect("gpt-4o");
+        assert_eq!(info.model, "gpt-4o");
+        let info = catalog
+            .lookup("openai_chat", "gpt-4o-audio-preview-2024-12-17")
+            .expect("audio snapshot");
+        assert_eq!(info.model, "gpt-4o-audio-preview");
+        let info = catalog
+            .lookup("openai_chat", "o3-mini-2025-01-31")
+            .expect("o3-mini");
+        assert_eq!(info.model, "o3-mini");
+        let info = catalog
+            .lookup("openai_chat", "o1-mini-2024-09-12")
+            .expect("o1-mini");
+        assert_eq!(info.model, "o1-mini");
+        assert_eq!(info.max_output_tokens, Some(65_536));
+        assert!(!info.capabilities.as_ref().unwrap().supports_image_input);
+        let info = catalog
+            .lookup("openai_chat", "o1-preview")
+            .expect("o1-preview");
+        assert_eq!(info.model, "o1-preview");
+        assert_eq!(info.context_window, Some(128_000));
+        let info = catalog.lookup("openai_chat", "o1-2024-12-17").expect("o1");
+        assert_eq!(info.model, "o1");
+        assert!(catalog.lookup("openai_chat", "gpt-4").is_none());
+        assert!(catalog.lookup("openai_chat", "o30").is_none());
+    }
+
+    #[test]
+    fn lookup_handles_path_and_vendor_prefixed_ids() {
+        let catalog = ModelCatalog::builtin();
+        let info = catalog
+            .lookup("google_gemini", "models/gemini-2.5-flash")
+            .expect("gemini path");
+        assert_eq!(info.model, "gemini-2.5-flash");
+        assert!(info.supports_reasoning(ReasoningMode::BudgetTokens));
+
+        let info = catalog
+            .lookup(
+                "bedrock_converse",
+                "us.anthropic.claude-3-5-haiku-20241022-v1:0",
+            )
+            .expect("bedrock profile");
+        assert_eq!(info.max_output_tokens, Some(8_192));
+        assert!(
+            catalog
+                .lookup("anthropic_messages", "gemini-2.5-pro")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn capabilities_intersect_with_provider_descriptor() {
+        let catalog = ModelCatalog::builtin();
+        let provider = caps(true, true, false, true, true);
+
+        let mini = catalog.capabilities("openai_chat", Some("o3-mini"), provider.clone());
+        assert!(!mini.supports_image_input);
+        assert!(!mini.supports_parallel_tool_calls);
+        assert!(mini.supports_tools);
+
+        let audio = catalog.capabilities(
+            "openai_chat",
+            Some("gpt-4o-audio-preview"),
+            provider.clone(),
+        );
+        assert!(audio.supports_audio_input);
+        assert!(!audio.supports_image_input);
+
+        let unknown = catalog.capabilities("openai_chat", Some("ft:custom"), provider.clone());
+        assert!(unknown.supports_image_input && unknown.supports_audio_input);
+        let no_model = catalog.capabilities("openai_chat", None, provider);
+        assert!(no_model.supports_audio_input);
+    }
+
+    #[test]
+    fn insert_merges_overrides_onto_resolved_entry() {
+        let mut catalog = ModelCatalog::builtin();
+        catalog.insert(
+            "anthropic_messages",
+            ModelInfo::new("claude-opus-4-20250514").with_max_output_tokens(16_000),
+        );
+        let snapshot = catalog
+            .lookup("anthropic_messages", "claude-opus-4-20250514")
+            .expect("override");
+        assert_eq!(snapshot.max_output_tokens, Some(16_000));
+        assert_eq!(snapshot.context_window, Some(200_000));
+        assert_eq!(snapshot.knowledge_cutoff.as_deref(), Some("2025-03"));
+        let family = catalog
+            .lookup("anthropic_messages", "claude-opus-4-1")
+            .expect("family");
+        assert_eq!(family.max_output_tokens, Some(32_000));
+
+        catalog.insert(
+            "ollama",
+            ModelInfo::new("llama3.2").with_context_window(131_072),
+        );
+        assert_eq!(
+            catalog
+                .lookup("ollama", "llama3.2:3b")
+                .and_then(|info| info.context_window),
+            Some(131_072)
+        );
+        assert!(catalog.providers().contains(&"ollama"));
+    }
+}
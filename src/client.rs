@@ -1,17 +1,32 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 
+use crate::adapt::{Adaptation, AdaptedRequest, RequestAdapter};
+use crate::catalog::{ModelCatalog, ModelInfo};
 use crate::error::LLMError;
-use crate::provider::{ChatStream, DynProvider, RetryConfig, RetryableLLMProvider};
-use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
+use crate::preflight;
+use crate::provider::{
+    ChatStream, DynProvider, PreparedRequest, RetryConfig, RetryableLLMProvider,
+};
+use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse, TokenEstimator};
+
+mod reloadable;
+
+pub use reloadable::{ConfigWatcher, ReloadableClient};
 
 /// Routes chat requests through the set of registered providers.
 ///
 /// The client stores provider handles in-memory, making it trivial to share one instance
-/// across the application and pick the right backend per request.
+/// across the application and pick the right backend per request. Use
+/// [`ReloadableClient`] when handles must change without rebuilding the client.
+#[derive(Clone)]
 pub struct LLMClient {
     providers: HashMap<String, DynProvider>,
+    catalog: Arc<ModelCatalog>,
+    preflight: bool,
+    adapter: RequestAdapter,
 }
 
 impl LLMClient {
@@ -53,6 +68,9 @@ impl LLMClient {
     pub fn builder() -> LLMClientBuilder {
         LLMClientBuilder {
             providers: HashMap::new(),
+            catalog: ModelCatalog::builtin(),
+            preflight: false,
+            adapter: RequestAdapter::new(),
         }
     }
 
@@ -106,10 +124,11 @@ impl LLMClient {
     ///
     /// # Errors
     ///
-    /// Returns [`LLMError::Validation`] when `handle` is not registered, or propagates any
-    /// error raised by the underlying provider implementation.
+    /// Returns [`LLMError::Validation`] when `handle` is not registered,
+    /// [`LLMError::Preflight`] when pre-flight validation is enabled and rejects the request,
+    /// or propagates any error raised by the underlying provider implementation.
     pub async fn chat(&self, handle: &str, request: ChatRequest) -> Result<ChatResponse, LLMError> {
-        let provider = self.get_provider(handle)?;
+        let provider = self.resolve(handle, &request)?;
         provider.chat(request).await
     }
 
@@ -141,6 +160,8 @@ impl LLMClient {
     /// #             outputs: Vec::new(),
     /// #             usage: None,
     /// #             finish_reason: None,
+    /// #             candidate_finish_reasons: Vec::new(),
+    /// #             prompt_safety: None,
     /// #             model: None,
     /// #             provider: Default::default(),
     /// #         })
@@ -177,7 +198,7 @@ impl LLMClient {
         request: ChatRequest,
         config: RetryConfig,
     ) -> Result<ChatResponse, LLMError> {
-        let provider = self.get_provider(handle)?;
+        let provider = self.resolve(handle, &request)?;
         provider.chat_with_retry(request, config).await
     }
 
@@ -234,17 +255,180 @@ impl LLMClient {
     ///
     /// # Errors
     ///
-    /// Returns [`LLMError::Validation`] when the handle is missing or any transport/provider
-    /// level error bubbled up during streaming setup.
+    /// Returns [`LLMError::Validation`] when the handle is missing, [`LLMError::Preflight`]
+    /// when pre-flight validation is enabled and rejects the request, or any
+    /// transport/provider level error bubbled up during streaming setup.
     pub async fn stream_chat(
         &self,
         handle: &str,
         request: ChatRequest,
     ) -> Result<ChatStream, LLMError> {
-        let provider = self.get_provider(handle)?;
+        let provider = self.resolve(handle, &request)?;
         provider.stream_chat(request).await
     }
 
+    /// Rewrites `request` to fit the provider and model behind `handle`.
+    ///
+    /// Unsupported content parts are converted or dropped, JSON response formats and tool
+    /// choices the provider cannot express are turned into instructions, and late system
+    /// messages are folded into the system prompt. See [`crate::adapt`] for the rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use async_trait::async_trait;
+    /// # use kotoba_llm::adapt::Adaptation;
+    /// # use kotoba_llm::client::LLMClient;
+    /// # use kotoba_llm::error::LLMError;
+    /// # use kotoba_llm::provider::{LLMProvider, ChatStream};
+    /// # use kotoba_llm::types::{
+    /// #     CapabilityDescriptor, ChatRequest, ChatResponse, ContentPart, ImageContent,
+    /// #     ImageSource, Message, Role, TextContent,
+    /// # };
+    /// # use futures_util::stream;
+    /// # struct TextOnly;
+    /// # #[async_trait]
+    /// # impl LLMProvider for TextOnly {
+    /// #     async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LLMError> { unreachable!() }
+    /// #     async fn stream_chat(&self, _request: ChatRequest) -> Result<ChatStream, LLMError> { Ok(Box::pin(stream::empty())) }
+    /// #     fn capabilities(&self) -> CapabilityDescriptor { CapabilityDescriptor::default() }
+    /// #     fn name(&self) -> &'static str { "text_only" }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let client = LLMClient::builder()
+    ///     .register_handle("text", Arc::new(TextOnly))
+    ///     .expect("unique handle")
+    ///     .build();
+    /// let request = ChatRequest {
+    ///     messages: vec![Message {
+    ///         role: Role::user(),
+    ///         name: None,
+    ///         content: vec![
+    ///             ContentPart::Text(TextContent { text: "What is this?".into() }),
+    ///             ContentPart::Image(ImageContent {
+    ///                 source: ImageSource::Url { url: "https://example.com/cat.png".into() },
+    ///                 detail: None,
+    ///                 metadata: None,
+    ///             }),
+    ///         ],
+    ///         metadata: None,
+    ///     }],
+    ///     options: Default::default(),
+    ///     tools: Vec::new(),
+    ///     tool_choice: None,
+    ///     response_format: None,
+    ///     metadata: None,
+    /// };
+    /// let adapted = client.adapt_request("text", request).await.expect("known handle");
+    /// assert_eq!(adapted.request.messages[0].content.len(), 1);
+    /// assert!(matches!(adapted.changes[0], Adaptation::PartDropped { kind: "image", .. }));
+    /// # });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] if the handle is unknown. Adaptation itself never
+    /// fails.
+    pub async fn adapt_request(
+        &self,
+        handle: &str,
+        request: ChatRequest,
+    ) -> Result<AdaptedRequest, LLMError> {
+        let provider = self.get_provider(handle)?;
+        let capabilities = self.model_capabilities(&provider, request_model(&provider, &request));
+        Ok(self
+            .adapter
+            .adapt(request, provider.name(), &capabilities)
+            .await)
+    }
+
+    /// Adapts `request` with [`LLMClient::adapt_request`] and sends the result through
+    /// [`LLMClient::chat`], returning the response with the changes that were made.
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::chat`].
+    pub async fn chat_adapted(
+        &self,
+        handle: &str,
+        request: ChatRequest,
+    ) -> Result<(ChatResponse, Vec<Adaptation>), LLMError> {
+        let adapted = self.adapt_request(handle, request).await?;
+        let response = self.chat(handle, adapted.request).await?;
+        Ok((response, adapted.changes))
+    }
+
+    /// Streaming counterpart of [`LLMClient::chat_adapted`].
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::stream_chat`].
+    pub async fn stream_chat_adapted(
+        &self,
+        handle: &str,
+        request: ChatRequest,
+    ) -> Result<(ChatStream, Vec<Adaptation>), LLMError> {
+        let adapted = self.adapt_request(handle, request).await?;
+        let stream = self.stream_chat(handle, adapted.request).await?;
+        Ok((stream, adapted.changes))
+    }
+
+    /// Builds the vendor HTTP request that `handle` would send for `request`.
+    ///
+    /// Nothing is dispatched; the returned [`PreparedRequest`] reflects the provider's
+    /// request mapping with any configured [`crate::config::RequestPatch`] applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(all(feature = "openai", feature = "reqwest-transport"))] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::client::LLMClient;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// # use kotoba_llm::provider::openai_chat::OpenAiChatProvider;
+    /// # use kotoba_llm::types::{ChatRequest, ContentPart, Message, Role, TextContent};
+    /// let provider = OpenAiChatProvider::new(default_dyn_transport().unwrap(), "sk-test")
+    ///     .with_default_model("gpt-4.1-mini");
+    /// let client = LLMClient::builder()
+    ///     .register_handle("openai", Arc::new(provider))
+    ///     .expect("unique handle")
+    ///     .build();
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let request = ChatRequest {
+    ///     messages: vec![Message {
+    ///         role: Role::user(),
+    ///         name: None,
+    ///         content: vec![ContentPart::Text(TextContent { text: "ping".into() })],
+    ///         metadata: None,
+    ///     }],
+    ///     options: Default::default(),
+    ///     tools: Vec::new(),
+    ///     tool_choice: None,
+    ///     response_format: None,
+    ///     metadata: None,
+    /// };
+    /// let prepared = client.prepare_request("openai", &request, false).await.unwrap();
+    /// assert!(prepared.url.ends_with("/v1/chat/completions"));
+    /// assert_eq!(prepared.body["model"], "gpt-4.1-mini");
+    /// # });
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] when the handle is missing, or any error raised by
+    /// [`crate::provider::LLMProvider::prepare_request`].
+    pub async fn prepare_request(
+        &self,
+        handle: &str,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let provider = self.get_provider(handle)?;
+        provider.prepare_request(request, stream).await
+    }
+
     /// Lists every handle currently registered on the client.
     ///
     /// The returned vector is independent from the internal storage, so callers can sort or
@@ -282,7 +466,9 @@ impl LLMClient {
     /// Returns the capability descriptor associated with `handle`.
     ///
     /// Capability descriptors make it easy to filter providers based on streaming support
-    /// or multimodal input availability before placing a request.
+    /// or multimodal input availability before placing a request. The provider descriptor
+    /// is narrowed to the handle's default model when the [`ModelCatalog`] knows it; use
+    /// [`LLMClient::capabilities_for_request`] when a request overrides the model.
     ///
     /// # Examples
     ///
@@ -318,7 +504,163 @@ impl LLMClient {
     /// Returns [`LLMError::Validation`] if the handle is unknown.
     pub fn capabilities(&self, handle: &str) -> Result<CapabilityDescriptor, LLMError> {
         let provider = self.get_provider(handle)?;
-        Ok(provider.capabilities())
+        Ok(self.model_capabilities(&provider, provider.default_model()))
+    }
+
+    /// Returns the capabilities of the model `request` would be sent to on `handle`.
+    ///
+    /// The model is [`crate::types::ChatOptions::model`] when set and the handle's default
+    /// model otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use async_trait::async_trait;
+    /// # use kotoba_llm::client::LLMClient;
+    /// # use kotoba_llm::error::LLMError;
+    /// # use kotoba_llm::provider::{LLMProvider, ChatStream};
+    /// # use kotoba_llm::types::{CapabilityDescriptor, ChatOptions, ChatRequest, ChatResponse};
+    /// # use futures_util::stream;
+    /// # struct OpenAiLike;
+    /// # #[async_trait]
+    /// # impl LLMProvider for OpenAiLike {
+    /// #     async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LLMError> { unreachable!() }
+    /// #     async fn stream_chat(&self, _request: ChatRequest) -> Result<ChatStream, LLMError> { Ok(Box::pin(stream::empty())) }
+    /// #     fn capabilities(&self) -> CapabilityDescriptor {
+    /// #         CapabilityDescriptor { supports_stream: true, supports_image_input: true, ..Default::default() }
+    /// #     }
+    /// #     fn name(&self) -> &'static str { "openai_chat" }
+    /// #     fn default_model(&self) -> Option<&str> { Some("gpt-4o") }
+    /// # }
+    /// let client = LLMClient::builder()
+    ///     .register_handle("openai", Arc::new(OpenAiLike))
+    ///     .expect("unique handle")
+    ///     .build();
+    /// assert!(client.capabilities("openai").expect("handle").supports_image_input);
+    ///
+    /// let request = ChatRequest {
+    ///     messages: Vec::new(),
+    ///     options: ChatOptions { model: Some("o3-mini".into()), ..Default::default() },
+    ///     tools: Vec::new(),
+    ///     tool_choice: None,
+    ///     response_format: None,
+    ///     metadata: None,
+    /// };
+    /// let caps = client.capabilities_for_request("openai", &request).expect("handle");
+    /// assert!(!caps.supports_image_input);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] if the handle is unknown.
+    pub fn capabilities_for_request(
+        &self,
+        handle: &str,
+        request: &ChatRequest,
+    ) -> Result<CapabilityDescriptor, LLMError> {
+        let provider = self.get_provider(handle)?;
+        Ok(self.model_capabilities(&provider, request_model(&provider, request)))
+    }
+
+    /// Checks `request` against the capabilities and limits of the model it targets on
+    /// `handle`, without sending anything.
+    ///
+    /// Flags image, audio and video parts the model does not accept, tools without tool
+    /// support, [`crate::types::ResponseFormat::JsonSchema`] without structured output, a
+    /// prompt whose [`TokenEstimator`] estimate exceeds the context window, and
+    /// `max_output_tokens` above the output limit. Limits come from the [`ModelCatalog`]
+    /// and are skipped for models it does not know. Enable
+    /// [`LLMClientBuilder::with_preflight_validation`] to run the check on every call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use async_trait::async_trait;
+    /// # use kotoba_llm::client::LLMClient;
+    /// # use kotoba_llm::error::LLMError;
+    /// # use kotoba_llm::preflight::{InputModality, RequestViolation};
+    /// # use kotoba_llm::provider::{LLMProvider, ChatStream};
+    /// # use kotoba_llm::types::{
+    /// #     CapabilityDescriptor, ChatRequest, ChatResponse, ContentPart, ImageContent,
+    /// #     ImageSource, Message, Role,
+    /// # };
+    /// # use futures_util::stream;
+    /// # struct TextOnly;
+    /// # #[async_trait]
+    /// # impl LLMProvider for TextOnly {
+    /// #     async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LLMError> { unreachable!() }
+    /// #     async fn stream_chat(&self, _request: ChatRequest) -> Result<ChatStream, LLMError> { Ok(Box::pin(stream::empty())) }
+    /// #     fn capabilities(&self) -> CapabilityDescriptor { CapabilityDescriptor::default() }
+    /// #     fn name(&self) -> &'static str { "text_only" }
+    /// # }
+    /// let client = LLMClient::builder()
+    ///     .register_handle("text", Arc::new(TextOnly))
+    ///     .expect("unique handle")
+    ///     .build();
+    /// let request = ChatRequest {
+    ///     messages: vec![Message {
+    ///         role: Role::user(),
+    ///         name: None,
+    ///         content: vec![ContentPart::Image(ImageContent {
+    ///             source: ImageSource::Url { url: "https://example.com/cat.png".into() },
+    ///             detail: None,
+    ///             metadata: None,
+    ///         })],
+    ///         metadata: None,
+    ///     }],
+    ///     options: Default::default(),
+    ///     tools: Vec::new(),
+    ///     tool_choice: None,
+    ///     response_format: None,
+    ///     metadata: None,
+    /// };
+    /// match client.validate_request("text", &request) {
+    ///     Err(LLMError::Preflight { violations }) => assert_eq!(
+    ///         violations,
+    ///         vec![RequestViolation::UnsupportedInput {
+    ///             modality: InputModality::Image,
+    ///             message_index: 0,
+    ///             part_index: 0,
+    ///         }]
+    ///     ),
+    ///     other => panic!("unexpected result: {other:?}"),
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] if the handle is unknown and [`LLMError::Preflight`]
+    /// listing every violation otherwise.
+    pub fn validate_request(&self, handle: &str, request: &ChatRequest) -> Result<(), LLMError> {
+        let provider = self.get_provider(handle)?;
+        self.check_request(&provider, request)
+    }
+
+    /// Returns the catalog entry for `model` on `handle`, or for the handle's default model
+    /// when `model` is `None`.
+    ///
+    /// `Ok(None)` means the catalog has no entry for the model.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] if the handle is unknown.
+    pub fn model_info(
+        &self,
+        handle: &str,
+        model: Option<&str>,
+    ) -> Result<Option<ModelInfo>, LLMError> {
+        let provider = self.get_provider(handle)?;
+        Ok(model
+            .or(provider.default_model())
+            .and_then(|model| self.catalog.lookup(provider.name(), model))
+            .cloned())
+    }
+
+    /// Returns the model catalog used for capability lookups.
+    pub fn catalog(&self) -> &ModelCatalog {
+        &self.catalog
     }
 
     /// Lists handles whose providers declare tool-calling support.
@@ -361,7 +703,10 @@ impl LLMClient {
         self.providers
             .iter()
             .filter_map(|(handle, provider)| {
-                if provider.capabilities().supports_tools {
+                if self
+                    .model_capabilities(provider, provider.default_model())
+                    .supports_tools
+                {
                     Some(handle.clone())
                 } else {
                     None
@@ -410,7 +755,10 @@ impl LLMClient {
         self.providers
             .iter()
             .filter_map(|(handle, provider)| {
-                if provider.capabilities().supports_stream {
+                if self
+                    .model_capabilities(provider, provider.default_model())
+                    .supports_stream
+                {
                     Some(handle.clone())
                 } else {
                     None
@@ -419,6 +767,37 @@ impl LLMClient {
             .collect()
     }
 
+    fn model_capabilities(
+        &self,
+        provider: &DynProvider,
+        model: Option<&str>,
+    ) -> CapabilityDescriptor {
+        self.catalog
+            .capabilities(provider.name(), model, provider.capabilities())
+    }
+
+    fn check_request(&self, provider: &DynProvider, request: &ChatRequest) -> Result<(), LLMError> {
+        let model = request_model(provider, request);
+        let capabilities = self.model_capabilities(provider, model);
+        let info = model.and_then(|model| self.catalog.lookup(provider.name(), model));
+        let estimator = TokenEstimator::new(preflight::provider_type(provider.name()));
+        let violations = preflight::validate_request(request, &capabilities, info, &estimator);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(LLMError::Preflight { violations })
+        }
+    }
+
+    /// Resolves `handle` and, when enabled, runs pre-flight validation.
+    fn resolve(&self, handle: &str, request: &ChatRequest) -> Result<DynProvider, LLMError> {
+        let provider = self.get_provider(handle)?;
+        if self.preflight {
+            self.check_request(&provider, request)?;
+        }
+        Ok(provider)
+    }
+
     fn get_provider(&self, handle: &str) -> Result<DynProvider, LLMError> {
         self.providers
             .get(handle)
@@ -429,6 +808,15 @@ impl LLMClient {
     }
 }
 
+/// Returns the model `request` targets: its explicit override, else the provider default.
+fn request_model<'a>(provider: &'a DynProvider, request: &'a ChatRequest) -> Option<&'a str> {
+    request
+        .options
+        .model
+        .as_deref()
+        .or(provider.default_model())
+}
+
 /// Lightweight client abstraction used for dependency injection.
 ///
 /// The trait can be mocked in tests or implemented by alternative clients that still
@@ -556,6 +944,9 @@ impl LLMClientLike for LLMClient {
 /// Builder used to register providers and construct an [`LLMClient`].
 pub struct LLMClientBuilder {
     providers: HashMap<String, DynProvider>,
+    catalog: ModelCatalog,
+    preflight: bool,
+    adapter: RequestAdapter,
 }
 
 impl LLMClientBuilder {
@@ -607,6 +998,38 @@ impl LLMClientBuilder {
         Ok(self)
     }
 
+    /// Replaces the model catalog, which defaults to [`ModelCatalog::builtin`].
+    pub fn with_catalog(mut self, catalog: ModelCatalog) -> Self {
+        self.catalog = catalog;
+        self
+    }
+
+    /// Adds or overrides one catalog entry for the provider named `provider`.
+    ///
+    /// See [`ModelCatalog::insert`] for how overrides merge with existing entries.
+    pub fn register_model(mut self, provider: impl Into<String>, info: ModelInfo) -> Self {
+        self.catalog.insert(provider, info);
+        self
+    }
+
+    /// Sets the adapter used by [`LLMClient::adapt_request`] and the `*_adapted` calls.
+    ///
+    /// The default adapter performs no network calls; pass one built with
+    /// [`RequestAdapter::with_transport`] to inline image URLs for providers that only
+    /// accept base64 images.
+    pub fn with_request_adapter(mut self, adapter: RequestAdapter) -> Self {
+        self.adapter = adapter;
+        self
+    }
+
+    /// Runs [`LLMClient::validate_request`] before every `chat`, `chat_with_retry` and
+    /// `stream_chat` call, failing with [`LLMError::Preflight`] instead of sending requests
+    /// the target model cannot serve.
+    pub fn with_preflight_validation(mut self) -> Self {
+        self.preflight = true;
+        self
+    }
+
     /// Consumes the builder and returns the configured [`LLMClient`].
     ///
     /// This method finalizes registration, moving the provider map into the client.
@@ -638,6 +1061,9 @@ impl LLMClientBuilder {
     pub fn build(self) -> LLMClient {
         LLMClient {
             providers: self.providers,
+            catalog: Arc::new(self.catalog),
+            preflight: self.preflight,
+            adapter: self.adapter,
         }
     }
 }
@@ -697,6 +1123,9 @@ mod tests {
 
         let client = LLMClient {
             providers: HashMap::from([("p1".to_string(), Arc::new(provider) as DynProvider)]),
+            catalog: Arc::default(),
+            preflight: false,
+            adapter: RequestAdapter::new(),
         };
 
         let caps = client.capabilities("p1").expect("capabilities");
@@ -705,10 +1134,80 @@ mod tests {
         assert!(!caps.supports_image_input);
     }
 
+    #[tokio::test]
+    async fn preflight_validation_rejects_before_dispatch_when_enabled() {
+        use crate::preflight::RequestViolation;
+        use crate::types::{ContentPart, ImageContent, ImageSource, Message, ResponseFormat, Role};
+
+        let provider: DynProvider = Arc::new(DummyProvider {
+            name: "dummy",
+            caps: CapabilityDescriptor {
+                supports_stream: true,
+                ..Default::default()
+            },
+        });
+        let request = ChatRequest {
+            messages: vec![Message {
+                role: Role::user(),
+                name: None,
+                content: vec![ContentPart::Image(ImageContent {
+                    source: ImageSource::Url {
+                        url: "https://example.com/cat.png".to_string(),
+                    },
+                    detail: None,
+                    metadata: None,
+                })],
+                metadata: None,
+            }],
+            options: Default::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: Some(ResponseFormat::JsonSchema {
+                schema: serde_json::json!({"type": "object"}),
+            }),
+            metadata: None,
+        };
+
+        let lenient = LLMClient::builder()
+            .register_handle("dummy", provider.clone())
+            .expect("handle")
+            .build();
+        assert!(matches!(
+            lenient.chat("dummy", request.clone()).await,
+            Err(LLMError::NotImplemented { .. })
+        ));
+
+        let strict = LLMClient::builder()
+            .register_handle("dummy", provider)
+            .expect("handle")
+            .with_preflight_validation()
+            .build();
+        for result in [
+            strict.chat("dummy", request.clone()).await.map(|_| ()),
+            strict
+                .stream_chat("dummy", request.clone())
+                .await
+                .map(|_| ()),
+            strict.validate_request("dummy", &request),
+        ] {
+            match result {
+                Err(LLMError::Preflight { violations }) => {
+                    assert_eq!(violations.len(), 2);
+                    assert_eq!(violations[1], RequestViolation::StructuredOutputUnsupported);
+                }
+                Err(other) => panic!("unexpected error: {other:?}"),
+                Ok(()) => panic!("expected pre-flight rejection"),
+            }
+        }
+    }
+
     #[test]
     fn capabilities_returns_error_for_unknown_handle() {
         let client = LLMClient {
             providers: HashMap::new(),
+            catalog: Arc::default(),
+            preflight: false,
+            adapter: RequestAdapter::new(),
         };
 
         let err = client.capabilities("missing").expect_err("should fail");
@@ -758,7 +1257,12 @@ mod tests {
             ),
         ]);
 
-        let client = LLMClient { providers };
+        let client = LLMClient {
+            providers,
+            catalog: Arc::default(),
+            preflight: false,
+            adapter: RequestAdapter::new(),
+        };
         let mut handles = client.handles_supporting_tools();
         handles.sort();
 
@@ -800,7 +1304,12 @@ mod tests {
             ),
         ]);
 
-        let client = LLMClient { providers };
+        let client = LLMClient {
+            providers,
+            catalog: Arc::default(),
+            preflight: false,
+            adapter: RequestAdapter::new(),
+        };
         let mut handles = client.handles_supporting_stream();
         handles.sort();
 
@@ -852,6 +1361,9 @@ mod tests {
 
         let client = LLMClient {
             providers: HashMap::from([("handle".to_string(), provider)]),
+            catalog: Arc::default(),
+            preflight: false,
+            adapter: RequestAdapter::new(),
         };
 
         // Invoke chat through the trait object to ensure compilation and behavior.
@@ -898,6 +1410,8 @@ mod tests {
                 outputs: Vec::new(),
                 usage: None,
                 finish_reason: None,
+                candidate_finish_reasons: Vec::new(),
+                prompt_safety: None,
                 model: Some("mock".to_string()),
                 provider: ProviderMetadata {
                     provider: "retry".to_string(),
@@ -939,6 +1453,9 @@ mod tests {
 
         let client = LLMClient {
             providers: HashMap::from([("retry".to_string(), provider)]),
+            catalog: Arc::default(),
+            preflight: false,
+            adapter: RequestAdapter::new(),
         };
 
         let response = client
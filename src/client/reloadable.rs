@@ -0,0 +1,902 @@
+//! Client whose handle table can be swapped at runtime.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use tokio::task::JoinHandle;
+
+use crate::adapt::{Adaptation, AdaptedRequest};
+use crate::catalog::{ModelCatalog, ModelInfo};
+use crate::config::{
+    ModelConfig, ProviderRegistry, build_client_from_configs_with_registry,
+    load_configs_from_path_with_registry,
+};
+use crate::error::LLMError;
+use crate::http::DynHttpTransport;
+use crate::provider::{
+    ChatStream, DynProvider, PreparedRequest, RetryConfig, RetryableLLMProvider,
+};
+use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
+
+use super::{LLMClient, LLMClientLike};
+
+/// [`LLMClient`] wrapper whose handle table can be replaced while requests are running.
+///
+/// Every request resolves its provider from the snapshot that is current when the call
+/// starts, so in-flight chats and streams keep using the provider they began with even if
+/// the handle is removed or replaced concurrently. Updates are copy-on-write: the table is
+/// cloned, modified and swapped in a single step, so readers never observe a partial
+/// update.
+///
+/// # Examples
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use async_trait::async_trait;
+/// # use kotoba_llm::client::{LLMClient, ReloadableClient};
+/// # use kotoba_llm::error::LLMError;
+/// # use kotoba_llm::provider::{LLMProvider, ChatStream};
+/// # use kotoba_llm::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
+/// # use futures_util::stream;
+/// # struct DummyProvider;
+/// # #[async_trait]
+/// # impl LLMProvider for DummyProvider {
+/// #     async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LLMError> { unreachable!() }
+/// #     async fn stream_chat(&self, _request: ChatRequest) -> Result<ChatStream, LLMError> { Ok(Box::pin(stream::empty())) }
+/// #     fn capabilities(&self) -> CapabilityDescriptor { CapabilityDescriptor::default() }
+/// #     fn name(&self) -> &'static str { "dummy" }
+/// # }
+/// let client = ReloadableClient::new(LLMClient::builder().build());
+/// client.insert_handle("primary", Arc::new(DummyProvider)).expect("new handle");
+/// client.replace_handle("primary", Arc::new(DummyProvider));
+/// assert_eq!(client.handles(), vec!["primary".to_string()]);
+/// assert!(client.remove_handle("primary").is_some());
+/// assert!(client.handles().is_empty());
+/// ```
+pub struct ReloadableClient {
+    current: RwLock<Arc<LLMClient>>,
+    /// Catalog that config reloads start from, without any config-declared entries.
+    base_catalog: RwLock<Arc<ModelCatalog>>,
+    registry: ProviderRegistry,
+}
+
+impl ReloadableClient {
+    /// Wraps an existing client as the initial handle table.
+    ///
+    /// The client's catalog becomes the base that config reloads add their entries to.
+    pub fn new(client: LLMClient) -> Self {
+        Self {
+            base_catalog: RwLock::new(client.catalog.clone()),
+            current: RwLock::new(Arc::new(client)),
+            registry: ProviderRegistry::new(),
+        }
+    }
+
+    /// Builds the initial handle table from model configurations.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by [`crate::config::build_client_from_configs`].
+    pub fn from_configs(
+        configs: &[ModelConfig],
+        transport: DynHttpTransport,
+    ) -> Result<Self, LLMError> {
+        Self::from_configs_with_registry(configs, transport, ProviderRegistry::new())
+    }
+
+    /// Builds the initial handle table from model configurations, resolving provider names
+    /// through `registry`.
+    ///
+    /// The registry is kept for later reloads, including those of
+    /// [`ReloadableClient::watch_config_file`].
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by [`build_client_from_configs_with_registry`].
+    pub fn from_configs_with_registry(
+        configs: &[ModelConfig],
+        transport: DynHttpTransport,
+        registry: ProviderRegistry,
+    ) -> Result<Self, LLMError> {
+        let client = build_client_from_configs_with_registry(configs, transport, &registry)?;
+        Ok(Self {
+            current: RwLock::new(Arc::new(client)),
+            base_catalog: RwLock::new(Arc::new(ModelCatalog::builtin())),
+            registry,
+        })
+    }
+
+    /// Resolves provider names through `registry` in later reloads.
+    pub fn with_registry(mut self, registry: ProviderRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Returns the handle table currently in effect.
+    ///
+    /// The snapshot stays valid after later updates, which makes it suitable for issuing
+    /// several related calls against a consistent set of providers.
+    pub fn snapshot(&self) -> Arc<LLMClient> {
+        self.current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Atomically replaces the whole handle table.
+    ///
+    /// The client's catalog becomes the base that later config reloads start from.
+    pub fn replace_all(&self, client: LLMClient) {
+        *self
+            .base_catalog
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = client.catalog.clone();
+        *self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(client);
+    }
+
+    /// Rebuilds every handle from `configs` and swaps them in.
+    ///
+    /// The new table is built before anything is swapped, so a failing configuration leaves
+    /// the current handles untouched. Provider names resolve through the registry given
+    /// at construction. Preflight validation and the request adapter carry over from the
+    /// current table. The catalog is rebuilt from the base catalog plus the entries declared
+    /// in `configs`, so entries of configs that were edited or removed do not linger.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error raised by [`build_client_from_configs_with_registry`].
+    pub fn reload_from_configs(
+        &self,
+        configs: &[ModelConfig],
+        transport: DynHttpTransport,
+    ) -> Result<(), LLMError> {
+        let rebuilt = build_client_from_configs_with_registry(configs, transport, &self.registry)?;
+        let mut current = self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut catalog = ModelCatalog::clone(
+            &self
+                .base_catalog
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+        for config in configs {
+            if let Some(provider) = rebuilt.providers.get(&config.handle) {
+                for info in &config.catalog {
+                    catalog.insert(provider.name(), info.clone());
+                }
+            }
+        }
+        *current = Arc::new(LLMClient {
+            providers: rebuilt.providers,
+            catalog: Arc::new(catalog),
+            preflight: current.preflight,
+            adapter: current.adapter.clone(),
+        });
+        Ok(())
+    }
+
+    /// Loads `path` with [`load_configs_from_path_with_registry`] and swaps in the resulting
+    /// handles.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::InvalidConfig`] when the file fails to load or validate, or any
+    /// error raised while building providers. The current handles stay in place on error.
+    pub fn reload_from_path(
+        &self,
+        path: impl AsRef<Path>,
+        transport: DynHttpTransport,
+    ) -> Result<(), LLMError> {
+        let configs = load_configs_from_path_with_registry(path, &self.registry)?;
+        self.reload_from_configs(&configs, transport)
+    }
+
+    /// Registers a new handle.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::InvalidConfig`] if the handle already exists.
+    pub fn insert_handle<S: Into<String>>(
+        &self,
+        handle: S,
+        provider: DynProvider,
+    ) -> Result<(), LLMError> {
+        let handle = handle.into();
+        self.update(|providers| {
+            if providers.contains_key(&handle) {
+                return Err(LLMError::InvalidConfig {
+                    field: "handle".to_string(),
+                    reason: format!("duplicate model handle: {handle}"),
+                });
+            }
+            providers.insert(handle, provider);
+            Ok(())
+        })
+    }
+
+    /// Registers or replaces a handle, returning the provider it previously pointed to.
+    pub fn replace_handle<S: Into<String>>(
+        &self,
+        handle: S,
+        provider: DynProvider,
+    ) -> Option<DynProvider> {
+        let handle = handle.into();
+        self.update(|providers| Ok(providers.insert(handle, provider)))
+            .unwrap_or_default()
+    }
+
+    /// Removes a handle, returning its provider when it was registered.
+    pub fn remove_handle(&self, handle: &str) -> Option<DynProvider> {
+        self.update(|providers| Ok(providers.remove(handle)))
+            .unwrap_or_default()
+    }
+
+    /// Polls `path` every `poll_interval` and reloads the handles whenever it changes.
+    ///
+    /// Changes are detected through the file's modification time and size. A reload that
+    /// fails keeps the previous handles and is retried on every poll until it succeeds, so a
+    /// file caught halfway through a write is picked up once the write completes; the error
+    /// is exposed through [`ConfigWatcher::last_error`] until then. The watcher runs on the
+    /// current Tokio runtime and stops when the returned [`ConfigWatcher`] or the client is
+    /// dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside of a Tokio runtime.
+    pub fn watch_config_file(
+        self: &Arc<Self>,
+        path: impl Into<PathBuf>,
+        transport: DynHttpTransport,
+        poll_interval: Duration,
+    ) -> ConfigWatcher {
+        let path = path.into();
+        let client = Arc::downgrade(self);
+        let last_error = Arc::new(Mutex::new(None));
+        let task_error = Arc::clone(&last_error);
+        let mut seen = std::fs::metadata(&path)
+            .ok()
+            .and_then(|metadata| file_stamp(&metadata));
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(poll_interval).await;
+                if client.strong_count() == 0 {
+                    break;
+                }
+                let stat_path = path.clone();
+                let stamp = tokio::task::spawn_blocking(move || std::fs::metadata(stat_path))
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+                    .and_then(|metadata| file_stamp(&metadata));
+                if stamp.is_none() || stamp == seen {
+                    continue;
+                }
+                let Some(client) = Weak::upgrade(&client) else {
+                    break;
+                };
+                // Reading the file and building providers block, so keep them off the runtime.
+                let reload_path = path.clone();
+                let reload_transport = transport.clone();
+                let outcome = match tokio::task::spawn_blocking(move || {
+                    client.reload_from_path(&reload_path, reload_transport)
+                })
+                .await
+                {
+                    Ok(result) => result.err().map(|err| err.to_string()),
+                    Err(err) => Some(format!("config reload task failed: {err}")),
+                };
+                if outcome.is_none() {
+                    seen = stamp;
+                }
+                *task_error
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()) = outcome;
+            }
+        });
+        ConfigWatcher { task, last_error }
+    }
+
+    /// Lists every handle in the current table.
+    pub fn handles(&self) -> Vec<String> {
+        self.snapshot().handles()
+    }
+
+    /// Returns the capabilities of `handle` in the current table.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] when the handle is not registered.
+    pub fn capabilities(&self, handle: &str) -> Result<CapabilityDescriptor, LLMError> {
+        self.snapshot().capabilities(handle)
+    }
+
+    /// Returns the capabilities of the model `request` would be sent to on `handle`.
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::capabilities_for_request`].
+    pub fn capabilities_for_request(
+        &self,
+        handle: &str,
+        request: &ChatRequest,
+    ) -> Result<CapabilityDescriptor, LLMError> {
+        self.snapshot().capabilities_for_request(handle, request)
+    }
+
+    /// Checks `request` against the model `handle` currently targets.
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::validate_request`].
+    pub fn validate_request(&self, handle: &str, request: &ChatRequest) -> Result<(), LLMError> {
+        self.snapshot().validate_request(handle, request)
+    }
+
+    /// Returns the catalog entry for `model` on `handle` in the current table.
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::model_info`].
+    pub fn model_info(
+        &self,
+        handle: &str,
+        model: Option<&str>,
+    ) -> Result<Option<ModelInfo>, LLMError> {
+        self.snapshot().model_info(handle, model)
+    }
+
+    /// Sends a chat request through the provider currently registered for `handle`.
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::chat`].
+    pub async fn chat(&self, handle: &str, request: ChatRequest) -> Result<ChatResponse, LLMError> {
+        let provider = self.snapshot().resolve(handle, &request)?;
+        provider.chat(request).await
+    }
+
+    /// Retries [`ReloadableClient::chat`] on retryable errors.
+    ///
+    /// All attempts go to the provider resolved when the call started.
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::chat_with_retry`].
+    pub async fn chat_with_retry(
+        &self,
+        handle: &str,
+        request: ChatRequest,
+        config: RetryConfig,
+    ) -> Result<ChatResponse, LLMError> {
+        let provider = self.snapshot().resolve(handle, &request)?;
+        provider.chat_with_retry(request, config).await
+    }
+
+    /// Starts a streaming chat request through the provider currently registered for `handle`.
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::stream_chat`].
+    pub async fn stream_chat(
+        &self,
+        handle: &str,
+        request: ChatRequest,
+    ) -> Result<ChatStream, LLMError> {
+        let provider = self.snapshot().resolve(handle, &request)?;
+        provider.stream_chat(request).await
+    }
+
+    /// Rewrites `request` to fit the provider currently registered for `handle`.
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::adapt_request`].
+    pub async fn adapt_request(
+        &self,
+        handle: &str,
+        request: ChatRequest,
+    ) -> Result<AdaptedRequest, LLMError> {
+        self.snapshot().adapt_request(handle, request).await
+    }
+
+    /// Adapts and sends `request`; both steps use the same handle table snapshot.
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::chat_adapted`].
+    pub async fn chat_adapted(
+        &self,
+        handle: &str,
+        request: ChatRequest,
+    ) -> Result<(ChatResponse, Vec<Adaptation>), LLMError> {
+        self.snapshot().chat_adapted(handle, request).await
+    }
+
+    /// Streaming counterpart of [`ReloadableClient::chat_adapted`].
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::stream_chat_adapted`].
+    pub async fn stream_chat_adapted(
+        &self,
+        handle: &str,
+        request: ChatRequest,
+    ) -> Result<(ChatStream, Vec<Adaptation>), LLMError> {
+        self.snapshot().stream_chat_adapted(handle, request).await
+    }
+
+    /// Builds the vendor HTTP request that `handle` would currently send for `request`.
+    ///
+    /// # Errors
+    ///
+    /// See [`LLMClient::prepare_request`].
+    pub async fn prepare_request(
+        &self,
+        handle: &str,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let provider = self.snapshot().get_provider(handle)?;
+        provider.prepare_request(request, stream).await
+    }
+
+    fn update<T>(
+        &self,
+        apply: impl FnOnce(&mut HashMap<String, DynProvider>) -> Result<T, LLMError>,
+    ) -> Result<T, LLMError> {
+        let mut current = self
+            .current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut providers = current.providers.clone();
+        let output = apply(&mut providers)?;
+        *current = Arc::new(LLMClient {
+            providers,
+            catalog: current.catalog.clone(),
+            preflight: current.preflight,
+            adapter: current.adapter.clone(),
+        });
+        Ok(output)
+    }
+}
+
+#[async_trait]
+impl LLMClientLike for ReloadableClient {
+    async fn chat(&self, handle: &str, request: ChatRequest) -> Result<ChatResponse, LLMError> {
+        ReloadableClient::chat(self, handle, request).await
+    }
+
+    async fn stream_chat(
+        &self,
+        handle: &str,
+        request: ChatRequest,
+    ) -> Result<ChatStream, LLMError> {
+        ReloadableClient::stream_chat(self, handle, request).await
+    }
+}
+
+/// Background task started by [`ReloadableClient::watch_config_file`].
+///
+/// Dropping the watcher stops polling.
+pub struct ConfigWatcher {
+    task: JoinHandle<()>,
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl ConfigWatcher {
+    /// Returns the error of the most recent reload, or `None` if it succeeded.
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Stops polling the config file; dropping the watcher has the same effect.
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn file_stamp(metadata: &std::fs::Metadata) -> Option<(SystemTime, u64)> {
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(all(test, feature = "openai", feature = "reqwest-transport"))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures_util::stream;
+
+    use super::*;
+    use crate::http::reqwest::default_dyn_transport;
+    use crate::provider::LLMProvider;
+    use crate::types::ProviderMetadata;
+
+    /// Provider that reports a fixed model name so tests can tell instances apart.
+    struct NamedProvider {
+        model: &'static str,
+        calls: AtomicUsize,
+    }
+
+    impl NamedProvider {
+        fn shared(model: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                model,
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl LLMProvider for NamedProvider {
+        async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse, LLMError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(ChatResponse {
+                outputs: Vec::new(),
+                usage: None,
+                finish_reason: None,
+                candidate_finish_reasons: Vec::new(),
+                prompt_safety: None,
+                model: Some(self.model.to_string()),
+                provider: ProviderMetadata::default(),
+            })
+        }
+
+        async fn stream_chat(&self, _request: ChatRequest) -> Result<ChatStream, LLMError> {
+            Ok(Box::pin(stream::empty()))
+        }
+
+        fn capabilities(&self) -> CapabilityDescriptor {
+            CapabilityDescriptor::default()
+        }
+
+        fn name(&self) -> &'static str {
+            "named"
+        }
+    }
+
+    fn empty_request() -> ChatRequest {
+        ChatRequest {
+            messages: Vec::new(),
+            options: Default::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        }
+    }
+
+    async fn model_of(client: &ReloadableClient, handle: &str) -> Option<String> {
+        client
+            .chat(handle, empty_request())
+            .await
+            .expect("chat")
+            .model
+    }
+
+    #[tokio::test]
+    async fn insert_replace_and_remove_handles() {
+        let client = ReloadableClient::new(LLMClient::builder().build());
+        client
+            .insert_handle("primary", NamedProvider::shared("v1"))
+            .expect("insert");
+        assert!(matches!(
+            client.insert_handle("primary", NamedProvider::shared("dup")),
+            Err(LLMError::InvalidConfig { .. })
+        ));
+        assert_eq!(model_of(&client, "primary").await.as_deref(), Some("v1"));
+
+        let previous = client.replace_handle("primary", NamedProvider::shared("v2"));
+        assert!(previous.is_some());
+        assert_eq!(model_of(&client, "primary").await.as_deref(), Some("v2"));
+
+        assert!(client.remove_handle("primary").is_some());
+        assert!(client.remove_handle("primary").is_none());
+        assert!(matches!(
+            client.chat("primary", empty_request()).await,
+            Err(LLMError::Validation { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn snapshots_keep_the_provider_they_started_with() {
+        let old = NamedProvider::shared("old");
+        let client = ReloadableClient::new(LLMClient::builder().build());
+        client.replace_handle("primary", old.clone());
+
+        let snapshot = client.snapshot();
+        client.replace_handle("primary", NamedProvider::shared("new"));
+
+        let response = snapshot
+            .chat("primary", empty_request())
+            .await
+            .expect("chat");
+        assert_eq!(response.model.as_deref(), Some("old"));
+        assert_eq!(old.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(model_of(&client, "primary").await.as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn failed_reload_keeps_current_handles() {
+        let transport = default_dyn_transport().expect("transport");
+        let client = ReloadableClient::new(LLMClient::builder().build());
+        client.replace_handle("primary", NamedProvider::shared("v1"));
+
+        let invalid = vec![ModelConfig {
+            handle: "broken".to_string(),
+            provider: crate::config::ProviderKind::OpenAiChat,
+            credential: crate::config::Credential::None,
+            default_model: None,
+            base_url: None,
+            extra: HashMap::new(),
+            patch: None,
+            catalog: Vec::new(),
+        }];
+        assert!(client.reload_from_configs(&invalid, transport).is_err());
+        assert_eq!(client.handles(), vec!["primary".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn reload_keeps_preflight_adapter_and_catalog() {
+        let transport = default_dyn_transport().expect("transport");
+        let catalog = ModelCatalog::new().with_model(
+            "openai_chat",
+            ModelInfo::new("house-model").with_max_output_tokens(100),
+        );
+        let client = ReloadableClient::new(
+            LLMClient::builder()
+                .with_catalog(catalog)
+                .with_preflight_validation()
+                .build(),
+        );
+        let configs = vec![ModelConfig {
+            handle: "primary".to_string(),
+            provider: crate::config::ProviderKind::OpenAiChat,
+            credential: crate::config::Credential::ApiKey {
+                header: None,
+                key: "sk-test".to_string(),
+            },
+            default_model: Some("house-model".to_string()),
+            base_url: None,
+            extra: HashMap::new(),
+            patch: None,
+            catalog: Vec::new(),
+        }];
+        client
+            .reload_from_configs(&configs, transport)
+            .expect("reload");
+
+        let mut request = empty_request();
+        request.options.max_output_tokens = Some(1_000);
+        assert!(matches!(
+            client.chat("primary", request).await,
+            Err(LLMError::Preflight { .. })
+        ));
+        let info = client
+            .model_info("primary", None)
+            .expect("handle")
+            .expect("catalog entry");
+        assert_eq!(info.max_output_tokens, Some(100));
+    }
+
+    fn openai_config(handle: &str, catalog: Vec<ModelInfo>) -> ModelConfig {
+        ModelConfig {
+            handle: handle.to_string(),
+            provider: crate::config::ProviderKind::OpenAiChat,
+            credential: crate::config::Credential::ApiKey {
+                header: None,
+                key: "sk-test".to_string(),
+            },
+            default_model: Some("house-model".to_string()),
+            base_url: None,
+            extra: HashMap::new(),
+            patch: None,
+            catalog,
+        }
+    }
+
+    #[test]
+    fn reload_rebuilds_the_catalog_from_the_base() {
+        let transport = default_dyn_transport().expect("transport");
+        let client = ReloadableClient::new(
+            LLMClient::builder()
+                .register_model("openai_chat", ModelInfo::new("builder-model"))
+                .build(),
+        );
+        let declared = vec![openai_config(
+            "primary",
+            vec![ModelInfo::new("house-model").with_max_output_tokens(100)],
+        )];
+        client
+            .reload_from_configs(&declared, transport.clone())
+            .expect("reload");
+        let catalog = client.snapshot().catalog().clone();
+        assert!(catalog.lookup("openai_chat", "house-model").is_some());
+        assert!(catalog.lookup("openai_chat", "builder-model").is_some());
+
+        client
+            .reload_from_configs(&[openai_config("primary", Vec::new())], transport)
+            .expect("reload");
+        let catalog = client.snapshot().catalog().clone();
+        assert!(catalog.lookup("openai_chat", "house-model").is_none());
+        assert!(catalog.lookup("openai_chat", "builder-model").is_some());
+        assert!(catalog.lookup("openai_chat", "gpt-4.1-mini").is_some());
+    }
+
+    fn build_gateway(
+        _config: &ModelConfig,
+        _transport: DynHttpTransport,
+    ) -> Result<DynProvider, LLMError> {
+        Ok(NamedProvider::shared("gateway"))
+    }
+
+    #[tokio::test]
+    async fn reloads_resolve_providers_through_the_registry() {
+        let dir = std::env::temp_dir().join(format!("kotoba-registry-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let path = dir.join("models.json");
+        std::fs::write(
+            &path,
+            r#"[{"handle": "reloaded", "provider": "acme_gateway", "default_model": "acme-large",
+                 "credential": {"type": "bearer", "token": "secret"}}]"#,
+        )
+        .expect("write config");
+
+        let registry = ProviderRegistry::new()
+            .register("acme_gateway", build_gateway)
+            .expect("unique name");
+        let mut initial = openai_config("initial", Vec::new());
+        initial.provider = crate::config::ProviderKind::Custom("acme_gateway".to_string());
+        let transport = default_dyn_transport().expect("transport");
+        let client =
+            ReloadableClient::from_configs_with_registry(&[initial], transport.clone(), registry)
+                .expect("client");
+        assert_eq!(
+            model_of(&client, "initial").await.as_deref(),
+            Some("gateway")
+        );
+
+        let reloaded = client.reload_from_path(&path, transport);
+        std::fs::remove_dir_all(&dir).ok();
+        reloaded.expect("reload");
+        assert_eq!(client.handles(), vec!["reloaded".to_string()]);
+        assert_eq!(
+            model_of(&client, "reloaded").await.as_deref(),
+            Some("gateway")
+        );
+    }
+
+    #[tokio::test]
+    async fn watcher_stops_once_the_client_is_dropped() {
+        let path = std::env::temp_dir().join(format!("kotoba-dropped-{}.json", std::process::id()));
+        let client = Arc::new(ReloadableClient::new(LLMClient::builder().build()));
+        let transport = default_dyn_transport().expect("transport");
+        let watcher = client.watch_config_file(&path, transport, Duration::from_millis(10));
+
+        drop(client);
+        for _ in 0..200 {
+            if watcher.task.is_finished() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(watcher.task.is_finished());
+    }
+
+    #[tokio::test]
+    async fn watcher_retries_a_failed_reload_until_it_succeeds() {
+        let dir = std::env::temp_dir().join(format!("kotoba-retry-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let path = dir.join("models.json");
+        let config = |handle: &str| {
+            format!(
+                r#"[{{"handle": "{handle}", "provider": "openai_chat", "default_model": "gpt-4.1-mini",
+                    "credential": {{"type": "api_key", "key": "sk-test"}}}}]"#
+            )
+        };
+        std::fs::write(&path, config("first")).expect("write config");
+        let transport = default_dyn_transport().expect("transport");
+        let client = Arc::new(ReloadableClient::new(LLMClient::builder().build()));
+        let watcher = client.watch_config_file(&path, transport, Duration::from_millis(10));
+
+        // A half-written file with the same size and modification time as the finished one.
+        let finished = config("second");
+        std::fs::write(&path, "x".repeat(finished.len())).expect("partial config");
+        let mut last_error = None;
+        for _ in 0..200 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            last_error = watcher.last_error();
+            if last_error.is_some() {
+                break;
+            }
+        }
+        assert!(last_error.is_some());
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .expect("mtime");
+        std::fs::write(&path, &finished).expect("finish config");
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(modified))
+            .expect("restore mtime");
+
+        let mut handles = Vec::new();
+        for _ in 0..200 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            handles = client.handles();
+            if handles == ["second"] {
+                break;
+            }
+        }
+        watcher.stop();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(handles, vec!["second".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn watcher_reloads_when_the_file_changes() {
+        let dir = std::env::temp_dir().join(format!("kotoba-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        let path = dir.join("models.json");
+        let config = |handle: &str| {
+            format!(
+                r#"[{{"handle": "{handle}", "provider": "openai_chat", "default_model": "gpt-4.1-mini",
+                    "credential": {{"type": "api_key", "key": "sk-test"}}}}]"#
+            )
+        };
+        std::fs::write(&path, config("first")).expect("write config");
+
+        let transport = default_dyn_transport().expect("transport");
+        let configs = crate::config::load_configs_from_path(&path).expect("configs");
+        let client =
+            Arc::new(ReloadableClient::from_configs(&configs, transport.clone()).expect("client"));
+        let watcher = client.watch_config_file(&path, transport, Duration::from_millis(10));
+
+        std::fs::write(&path, config("second-handle")).expect("rewrite config");
+        let mut handles = Vec::new();
+        for _ in 0..200 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            handles = client.handles();
+            if handles == ["second-handle"] {
+                break;
+            }
+        }
+        assert_eq!(handles, vec!["second-handle".to_string()]);
+
+        std::fs::write(&path, "not json at all").expect("break config");
+        let mut last_error = None;
+        for _ in 0..200 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            last_error = watcher.last_error();
+            if last_error.is_some() {
+                break;
+            }
+        }
+        watcher.stop();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(
+            last_error
+                .expect("reload error")
+                .contains("failed to parse JSON")
+        );
+        assert_eq!(client.handles(), vec!["second-handle".to_string()]);
+    }
+}
@@ -0,0 +1,1144 @@
+//! Computer-use agents driven through the unified chat API.
+//!
+//! Models with a computer tool (OpenAI Responses `computer_use_preview`, Anthropic
+//! `computer_20250124`) reply with [`ToolCallKind::ComputerUse`] calls.
+//! [`ComputerCall::from_tool_call`] decodes either provider's payload into a typed
+//! [`ComputerAction`], a [`ComputerEnvironment`] performs it, and [`ComputerUseAgent`]
+//! repeats the act-then-screenshot loop until the model stops asking for actions.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::client::LLMClientLike;
+use crate::error::LLMError;
+use crate::types::{
+    ChatRequest, ChatResponse, ContentPart, ImageContent, Message, OutputItem, Role, ToolCall,
+    ToolCallKind, ToolDefinition, ToolKind, ToolResult,
+};
+
+/// Name of the computer tool; Anthropic requires exactly this name.
+pub const COMPUTER_TOOL_NAME: &str = "computer";
+
+/// Screen the model operates on, used to declare the computer tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComputerDisplay {
+    /// Screenshot width in pixels.
+    pub width: u32,
+    /// Screenshot height in pixels.
+    pub height: u32,
+    /// Responses `environment` (`browser`, `mac`, `windows`, `ubuntu`); Anthropic ignores it.
+    pub environment: String,
+}
+
+impl ComputerDisplay {
+    /// Builds the [`ToolKind::ComputerUse`] definition for this display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use kotoba_llm::computer::ComputerDisplay;
+    /// let display = ComputerDisplay {
+    ///     width: 1280,
+    ///     height: 800,
+    ///     environment: "browser".to_string(),
+    /// };
+    /// let tool = display.tool_definition();
+    /// assert_eq!(tool.name, "computer");
+    /// assert_eq!(tool.metadata.unwrap()["display_width"], 1280);
+    /// ```
+    pub fn tool_definition(&self) -> ToolDefinition {
+        let metadata = HashMap::from([
+            ("display_width".to_string(), json!(self.width)),
+            ("display_height".to_string(), json!(self.height)),
+            ("environment".to_string(), json!(self.environment)),
+        ]);
+        ToolDefinition {
+            name: COMPUTER_TOOL_NAME.to_string(),
+            description: None,
+            input_schema: None,
+            kind: ToolKind::ComputerUse,
+            metadata: Some(metadata),
+            cache_control: None,
+        }
+    }
+}
+
+/// Mouse button of a [`ComputerAction::Click`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+}
+
+/// Unit of the scroll distance in [`ComputerAction::Scroll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollUnit {
+    /// Pixels, as reported by OpenAI Responses.
+    Pixels,
+    /// Mouse wheel clicks, as reported by Anthropic.
+    Clicks,
+}
+
+/// Screen coordinate in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+/// Action requested by the model, independent of the provider that issued it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ComputerAction {
+    /// Single click at a position.
+    Click { at: Point, button: MouseButton },
+    /// Double left click.
+    DoubleClick { at: Point },
+    /// Triple left click (Anthropic only).
+    TripleClick { at: Point },
+    /// Moves the pointer without clicking.
+    Move { to: Point },
+    /// Drags the left button along `path`, starting at its first point.
+    Drag { path: Vec<Point> },
+    /// Scrolls at `at`; positive values scroll right and down.
+    Scroll {
+        at: Option<Point>,
+        scroll_x: i64,
+        scroll_y: i64,
+        unit: ScrollUnit,
+    },
+    /// Types text.
+    Type { text: String },
+    /// Presses a key combination, such as `["ctrl", "s"]`, using the provider's key names.
+    Key { keys: Vec<String> },
+    /// Waits before the next screenshot.
+    Wait { duration_ms: Option<u64> },
+    /// Only takes a screenshot.
+    Screenshot,
+}
+
+/// Safety check attached to an OpenAI computer call; it must be acknowledged with the
+/// result before the model continues.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComputerSafetyCheck {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Computer tool call decoded from a [`ToolCall`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComputerCall {
+    /// Call identifier echoed by the result.
+    pub id: Option<String>,
+    /// Requested action.
+    pub action: ComputerAction,
+    /// Safety checks to confirm before acting (OpenAI only).
+    pub pending_safety_checks: Vec<ComputerSafetyCheck>,
+}
+
+impl ComputerCall {
+    /// Decodes a [`ToolCallKind::ComputerUse`] call from OpenAI Responses (`action` object)
+    /// or Anthropic (`action` name plus `coordinate`, `text`, ...).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use kotoba_llm::computer::{ComputerAction, ComputerCall, MouseButton, Point};
+    /// # use kotoba_llm::types::{ToolCall, ToolCallKind};
+    /// let call = ToolCall {
+    ///     id: Some("toolu_1".to_string()),
+    ///     name: "computer".to_string(),
+    ///     arguments: serde_json::json!({ "action": "left_click", "coordinate": [10, 20] }),
+    ///     kind: ToolCallKind::ComputerUse,
+    /// };
+    /// let call = ComputerCall::from_tool_call(&call).unwrap();
+    /// assert_eq!(
+    ///     call.action,
+    ///     ComputerAction::Click { at: Point { x: 10, y: 20 }, button: MouseButton::Left }
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] for other call kinds, unknown actions and
+    /// missing fields.
+    pub fn from_tool_call(call: &ToolCall) -> Result<Self, LLMError> {
+        if call.kind != ToolCallKind::ComputerUse {
+            return Err(invalid(format!("`{}` is not a computer call", call.name)));
+        }
+        let (action, pending_safety_checks) = match call.arguments.get("action") {
+            Some(Value::Object(_)) => {
+                let checks = match call.arguments.get("pending_safety_checks") {
+                    Some(checks) => serde_json::from_value(checks.clone())
+                        .map_err(|err| invalid(format!("invalid pending_safety_checks: {err}")))?,
+                    None => Vec::new(),
+                };
+                (decode_responses_action(&call.arguments["action"])?, checks)
+            }
+            Some(Value::String(_)) => (decode_anthropic_action(&call.arguments)?, Vec::new()),
+            _ => return Err(invalid("computer call has no action".to_string())),
+        };
+        Ok(Self {
+            id: call.id.clone(),
+            action,
+            pending_safety_checks,
+        })
+    }
+
+    /// Builds the result answering this call with a screenshot taken after the action.
+    ///
+    /// `acknowledged` lists the safety checks the user confirmed; OpenAI Responses sends
+    /// them as `acknowledged_safety_checks`.
+    pub fn result(
+        &self,
+        screenshot: ImageContent,
+        acknowledged: &[ComputerSafetyCheck],
+    ) -> ToolResult {
+        let metadata = (!acknowledged.is_empty()).then(|| {
+            HashMap::from([(
+                "acknowledged_safety_checks".to_string(),
+                json!(acknowledged),
+            )])
+        });
+        ToolResult {
+            call_id: self.id.clone(),
+            output: Value::Null,
+            is_error: false,
+            content: vec![ContentPart::Image(screenshot)],
+            metadata,
+        }
+    }
+}
+
+fn invalid(message: String) -> LLMError {
+    LLMError::Validation { message }
+}
+
+fn decode_responses_action(action: &Value) -> Result<ComputerAction, LLMError> {
+    let int = |key: &str| {
+        action
+            .get(key)
+            .and_then(Value::as_i64)
+            .ok_or_else(|| invalid(format!("computer action is missing `{key}`")))
+    };
+    let point = || {
+        Ok::<_, LLMError>(Point {
+            x: int("x")?,
+            y: int("y")?,
+        })
+    };
+    let kind = action
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    Ok(match kind {
+        "click" => ComputerAction::Click {
+            at: point()?,
+            button: match action.get("button").and_then(Value::as_str) {
+                Some("right") => MouseButton::Right,
+                Some("wheel") => MouseButton::Middle,
+                Some("back") => MouseButton::Back,
+                Some("forward") => MouseButton::Forward,
+                _ => MouseButton::Left,
+            },
+        },
+        "double_click" => ComputerAction::DoubleClick { at: point()? },
+        "move" => ComputerAction::Move { to: point()? },
+        "drag" => ComputerAction::Drag {
+            path: serde_json::from_value(action.get("path").cloned().unwrap_or_default())
+                .map_err(|err| invalid(format!("invalid drag path: {err}")))?,
+        },
+        "scroll" => ComputerAction::Scroll {
+            at: point().ok(),
+            scroll_x: action.get("scroll_x").and_then(Value::as_i64).unwrap_or(0),
+            scroll_y: action.get("scroll_y").and_then(Value::as_i64).unwrap_or(0),
+            unit: ScrollUnit::Pixels,
+        },
+        "type" => ComputerAction::Type {
+            text: string_field(action, "text")?,
+        },
+        "keypress" => ComputerAction::Key {
+            keys: action
+                .get("keys")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect(),
+        },
+        "wait" => ComputerAction::Wait { duration_ms: None },
+        "screenshot" => ComputerAction::Screenshot,
+        other => return Err(invalid(format!("unsupported computer action `{other}`"))),
+    })
+}
+
+fn decode_anthropic_action(input: &Value) -> Result<ComputerAction, LLMError> {
+    let coordinate = |key: &str| match input.get(key).and_then(Value::as_array) {
+        Some(pair) if pair.len() == 2 => Ok(Point {
+            x: pair[0].as_i64().unwrap_or_default(),
+            y: pair[1].as_i64().unwrap_or_default(),
+        }),
+        _ => Err(invalid(format!("computer action is missing `{key}`"))),
+    };
+    let click = |button| {
+        Ok::<_, LLMError>(ComputerAction::Click {
+            at: coordinate("coordinate")?,
+            button,
+        })
+    };
+    let kind = input
+        .get("action")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    Ok(match kind {
+        "left_click" => click(MouseButton::Left)?,
+        "right_click" => click(MouseButton::Right)?,
+        "middle_click" => click(MouseButton::Middle)?,
+        "double_click" => ComputerAction::DoubleClick {
+            at: coordinate("coordinate")?,
+        },
+        "triple_click" => ComputerAction::TripleClick {
+            at: coordinate("coordinate")?,
+        },
+        "mouse_move" => ComputerAction::Move {
+            to: coordinate("coordinate")?,
+        },
+        "left_click_drag" => ComputerAction::Drag {
+            path: vec![coordinate("start_coordinate")?, coordinate("coordinate")?],
+        },
+        "scroll" => {
+            let amount = input
+                .get("scroll_amount")
+                .and_then(Value::as_i64)
+                .unwrap_or(1);
+            let (scroll_x, scroll_y) = match input.get("scroll_direction").and_then(Value::as_str) {
+                Some("up") => (0, -amount),
+                Some("down") => (0, amount),
+                Some("left") => (-amount, 0),
+                Some("right") => (amount, 0),
+                _ => return Err(invalid("scroll is missing `scroll_direction`".to_string())),
+            };
+            ComputerAction::Scroll {
+                at: coordinate("coordinate").ok(),
+                scroll_x,
+                scroll_y,
+                unit: ScrollUnit::Clicks,
+            }
+        }
+        "type" => ComputerAction::Type {
+            text: string_field(input, "text")?,
+        },
+        "key" => ComputerAction::Key {
+            keys: string_field(input, "text")?
+                .split('+')
+                .map(str::to_string)
+                .collect(),
+        },
+        "wait" => ComputerAction::Wait {
+            duration_ms: input
+                .get("duration")
+                .and_then(Value::as_f64)
+                .map(|seconds| (seconds * 1000.0) as u64),
+        },
+        "screenshot" => ComputerAction::Screenshot,
+        other => return Err(invalid(format!("unsupported computer action `{other}`"))),
+    })
+}
+
+fn string_field(value: &Value, key: &str) -> Result<String, LLMError> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| invalid(format!("computer action is missing `{key}`")))
+}
+
+/// Machine, VM or browser that performs computer actions.
+#[async_trait]
+pub trait ComputerEnvironment: Send {
+    /// Display declared to the model.
+    fn display(&self) -> ComputerDisplay;
+
+    /// Performs `action`; [`ComputerAction::Screenshot`] is never passed here.
+    async fn execute(&mut self, action: &ComputerAction) -> Result<(), LLMError>;
+
+    /// Captures the current screen.
+    async fn screenshot(&mut self) -> Result<ImageContent, LLMError>;
+
+    /// Asks whether to proceed despite `checks`; declining stops the run.
+    ///
+    /// The default declines, so OpenAI calls that carry safety checks need an explicit
+    /// confirmation, typically from a human.
+    async fn confirm_safety_checks(
+        &mut self,
+        checks: &[ComputerSafetyCheck],
+    ) -> Result<bool, LLMError> {
+        let _ = checks;
+        Ok(false)
+    }
+}
+
+/// Why [`ComputerUseAgent::run`] returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputerUseStop {
+    /// The model answered without requesting another action.
+    Completed,
+    /// The model called other tools, which the caller has to execute.
+    ToolCalls,
+    /// The step limit was reached while the model still requested actions.
+    MaxSteps,
+}
+
+/// Outcome of a computer-use run.
+#[derive(Debug, Clone)]
+pub struct ComputerUseRun {
+    /// Last model response.
+    pub response: ChatResponse,
+    /// Conversation including every assistant turn and tool result, ready to continue.
+    pub messages: Vec<Message>,
+    /// Number of computer actions performed.
+    pub steps: usize,
+    /// Why the run stopped.
+    pub stop: ComputerUseStop,
+}
+
+/// Runs the act-then-screenshot loop against one client handle.
+///
+/// Each round sends the conversation, executes every computer call of the response in
+/// the [`ComputerEnvironment`], and answers it with a fresh screenshot as
+/// [`ImageContent`] in [`ToolResult::content`]. Calls that cannot be decoded are answered
+/// with an error result so the model can correct itself.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use kotoba_llm::computer::{ComputerEnvironment, ComputerUseAgent};
+/// # use kotoba_llm::client::LLMClient;
+/// # use kotoba_llm::types::ChatRequest;
+/// # async fn run(client: LLMClient, request: ChatRequest, env: &mut dyn ComputerEnvironment) {
+/// let run = ComputerUseAgent::new()
+///     .with_max_steps(20)
+///     .run(&client, "claude", request, env)
+///     .await
+///     .expect("computer use");
+/// println!("{} actions: {:?}", run.steps, run.stop);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ComputerUseAgent {
+    max_steps: usize,
+}
+
+impl Default for ComputerUseAgent {
+    fn default() -> Self {
+        Self { max_steps: 50 }
+    }
+}
+
+impl ComputerUseAgent {
+    /// Creates an agent allowing 50 actions per run.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the number of actions performed in one run.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Runs `request` until the model stops requesting computer actions.
+    ///
+    /// The computer tool from [`ComputerEnvironment::display`] is added when `request`
+    /// declares none.
+    ///
+    /// # Errors
+    ///
+    /// Forwards client and environment errors, and returns [`LLMError::Aborted`] when the
+    /// environment declines pending safety checks.
+    pub async fn run<C, E>(
+        &self,
+        client: &C,
+        handle: &str,
+        mut request: ChatRequest,
+        env: &mut E,
+    ) -> Result<ComputerUseRun, LLMError>
+    where
+        C: LLMClientLike + ?Sized,
+        E: ComputerEnvironment + ?Sized,
+    {
+        if !request
+            .tools
+            .iter()
+            .any(|tool| matches!(tool.kind, ToolKind::ComputerUse))
+        {
+            request.tools.push(env.display().tool_definition());
+        }
+
+        let mut steps = 0;
+        loop {
+            let response = client.chat(handle, request.clone()).await?;
+            let assistant = assistant_turn(&response);
+            let calls: Vec<&ToolCall> = response
+                .outputs
+                .iter()
+                .filter_map(|item| match item {
+                    OutputItem::ToolCall { call, .. } => Some(call),
+                    _ => None,
+                })
+                .collect();
+            let stop = if calls.is_empty() {
+                Some(ComputerUseStop::Completed)
+            } else if calls
+                .iter()
+                .any(|call| call.kind != ToolCallKind::ComputerUse)
+            {
+                Some(ComputerUseStop::ToolCalls)
+            } else if steps + calls.len() > self.max_steps {
+                Some(ComputerUseStop::MaxSteps)
+            } else {
+                None
+            };
+
+            let mut results = Vec::new();
+            if stop.is_none() {
+                for call in &calls {
+                    results.push(ContentPart::ToolResult(self.act(call, env).await?));
+                    steps += 1;
+                }
+            }
+            request.messages.extend(assistant);
+            if let Some(stop) = stop {
+                return Ok(ComputerUseRun {
+                    response,
+                    messages: request.messages,
+                    steps,
+                    stop,
+                });
+            }
+            request.messages.push(Message {
+                role: Role("tool".to_string()),
+                name: None,
+                content: results,
+                metadata: None,
+            });
+        }
+    }
+
+    async fn act<E>(&self, call: &ToolCall, env: &mut E) -> Result<ToolResult, LLMError>
+    where
+        E: ComputerEnvironment + ?Sized,
+    {
+        let computer_call = match ComputerCall::from_tool_call(call) {
+            Ok(computer_call) => computer_call,
+            Err(err) => {
+                return Ok(ToolResult {
+                    call_id: call.id.clone(),
+                    output: Value::String(err.to_string()),
+                    is_error: true,
+                    content: vec![ContentPart::Image(env.screenshot().await?)],
+                    metadata: None,
+                });
+            }
+        };
+        let checks = &computer_call.pending_safety_checks;
+        if !checks.is_empty() && !env.confirm_safety_checks(checks).await? {
+            let codes: Vec<&str> = checks
+                .iter()
+                .map(|check| check.code.as_deref().unwrap_or(&check.id))
+                .collect();
+            return Err(LLMError::Aborted {
+                message: format!(
+                    "computer use stopped at unconfirmed safety checks: {}",
+                    codes.join(", ")
+                ),
+            });
+        }
+        if computer_call.action != ComputerAction::Screenshot {
+            env.execute(&computer_call.action).await?;
+        }
+        Ok(computer_call.result(env.screenshot().await?, checks))
+    }
+}
+
+/// Replays a response as the assistant message: its content followed by its tool calls.
+fn assistant_turn(response: &ChatResponse) -> Option<Message> {
+    let mut content = Vec::new();
+    for item in &response.outputs {
+        match item {
+            OutputItem::Message { message, .. } => content.extend(message.content.clone()),
+            OutputItem::ToolCall { call, .. } => content.push(ContentPart::ToolCall(call.clone())),
+            _ => {}
+        }
+    }
+    (!content.is_empty()).then(|| Message {
+        role: Role::assistant(),
+        name: None,
+        content,
+        metadata: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::provider::ChatStream;
+    use crate::types::{ChatOptions, ImageSource, ProviderMetadata, TextContent};
+
+    /// Client replaying canned responses and recording every request.
+    struct ScriptedClient {
+        responses: Mutex<VecDeque<ChatResponse>>,
+        requests: Mutex<Vec<ChatRequest>>,
+    }
+
+    impl ScriptedClient {
+        fn new(responses: Vec<ChatResponse>) -> Self {
+            Self {
+                responses: Mutex::new(responses.into()),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LLMClientLike for ScriptedClient {
+        async fn chat(
+            &self,
+            _handle: &str,
+            request: ChatRequest,
+        ) -> Result<ChatResponse, LLMError> {
+            self.requests.lock().unwrap().push(request);
+            Ok(self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("scripted response"))
+        }
+
+        async fn stream_chat(
+            &self,
+            _handle: &str,
+            _request: ChatRequest,
+        ) -> Result<ChatStream, LLMError> {
+            Err(LLMError::NotImplemented { feature: "stream" })
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeEnvironment {
+        actions: Vec<ComputerAction>,
+        screenshots: usize,
+        confirm: bool,
+    }
+
+    #[async_trait]
+    impl ComputerEnvironment for FakeEnvironment {
+        fn display(&self) -> ComputerDisplay {
+            ComputerDisplay {
+                width: 1024,
+                height: 768,
+                environment: "browser".to_string(),
+            }
+        }
+
+        async fn execute(&mut self, action: &ComputerAction) -> Result<(), LLMError> {
+            self.actions.push(action.clone());
+            Ok(())
+        }
+
+        async fn screenshot(&mut self) -> Result<ImageContent, LLMError> {
+            self.screenshots += 1;
+            Ok(ImageContent {
+                source: ImageSource::Base64 {
+                    data: format!("shot-{}", self.screenshots),
+                    mime_type: Some("image/png".to_string()),
+                },
+                detail: None,
+                metadata: None,
+            })
+        }
+
+        async fn confirm_safety_checks(
+            &mut self,
+            _checks: &[ComputerSafetyCheck],
+        ) -> Result<bool, LLMError> {
+            Ok(self.confirm)
+        }
+    }
+
+    fn response(outputs: Vec<OutputItem>) -> ChatResponse {
+        ChatResponse {
+            outputs,
+            usage: None,
+            finish_reason: None,
+            candidate_finish_reasons: Vec::new(),
+            prompt_safety: None,
+            model: None,
+            provider: ProviderMetadata::default(),
+        }
+    }
+
+    fn computer_call(id: &str, arguments: Value) -> OutputItem {
+        OutputItem::ToolCall {
+            call: ToolCall {
+                id: Some(id.to_string()),
+                name: COMPUTER_TOOL_NAME.to_string(),
+                arguments,
+                kind: ToolCallKind::ComputerUse,
+            },
+            index: 0,
+        }
+    }
+
+    fn text(text: &str) -> OutputItem {
+        OutputItem::Message {
+            message: Message {
+                role: Role::assistant(),
+                name: None,
+                content: vec![ContentPart::Text(TextContent {
+                    text: text.to_string(),
+                })],
+                metadata: None,
+            },
+            index: 0,
+            logprobs: Vec::new(),
+            citations: Vec::new(),
+        }
+    }
+
+    fn request() -> ChatRequest {
+        ChatRequest {
+            messages: vec![Message {
+                role: Role::user(),
+                name: None,
+                content: vec![ContentPart::Text(TextContent {
+                    text: "Open the settings page".to_string(),
+                })],
+                metadata: None,
+            }],
+            options: ChatOptions::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        }
+    }
+
+    fn decode(arguments: Value) -> Result<ComputerAction, LLMError> {
+        let call = ToolCall {
+            id: None,
+            name: COMPUTER_TOOL_NAME.to_string(),
+            arguments,
+            kind: ToolCallKind::ComputerUse,
+        };
+        ComputerCall::from_tool_call(&call).map(|call| call.action)
+    }
+
+    #[test]
+    fn decodes_responses_actions() {
+        assert_eq!(
+            decode(json!({ "action": { "type": "click", "button": "right", "x": 5, "y": 7 } }))
+                .unwrap(),
+            ComputerAction::Click {
+                at: Point { x: 5, y: 7 },
+                button: MouseButton::Right,
+            }
+        );
+        assert_eq!(
+            decode(json!({ "action": { "type": "scroll", "x": 1, "y": 2, "scroll_x": 0, "scroll_y": 300 } }))
+                .unwrap(),
+            ComputerAction::Scroll {
+                at: Some(Point { x: 1, y: 2 }),
+                scroll_x: 0,
+                scroll_y: 300,
+                unit: ScrollUnit::Pixels,
+            }
+        );
+        assert_eq!(
+            decode(json!({ "action": { "type": "keypress", "keys": ["CTRL", "L"] } })).unwrap(),
+            ComputerAction::Key {
+                keys: vec!["CTRL".to_string(), "L".to_string()],
+            }
+        );
+        assert_eq!(
+            decode(json!({ "action": { "type": "drag", "path": [{ "x": 0, "y": 0 }, { "x": 9, "y": 9 }] } }))
+                .unwrap(),
+            ComputerAction::Drag {
+                path: vec![Point { x: 0, y: 0 }, Point { x: 9, y: 9 }],
+            }
+        );
+    }
+
+    #[test]
+    fn decodes_anthropic_actions() {
+        assert_eq!(
+            decode(json!({ "action": "type", "text": "hello" })).unwrap(),
+            ComputerAction::Type {
+                text: "hello".to_string(),
+            }
+        );
+        assert_eq!(
+            decode(json!({ "action": "key", "text": "ctrl+s" })).unwrap(),
+            ComputerAction::Key {
+                keys: vec!["ctrl".to_string(), "s".to_string()],
+            }
+        );
+        assert_eq!(
+            decode(json!({
+                "action": "scroll",
+                "coordinate": [100, 200],
+                "scroll_direction": "up",
+                "scroll_amount": 3
+            }))
+            .unwrap(),
+            ComputerAction::Scroll {
+                at: Some(Point { x: 100, y: 200 }),
+                scroll_x: 0,
+                scroll_y: -3,
+                unit: ScrollUnit::Clicks,
+            }
+        );
+        assert_eq!(
+            decode(json!({ "action": "screenshot" })).unwrap(),
+            ComputerAction::Screenshot
+        );
+        assert!(matches!(
+            decode(json!({ "action": "hold_key", "text": "shift" })),
+            Err(LLMError::Validation { message }) if message.contains("hold_key")
+        ));
+        assert!(matches!(
+            decode(json!({ "action": "left_click" })),
+            Err(LLMError::Validation { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn runs_actions_and_feeds_back_screenshots() {
+        let client = ScriptedClient::new(vec![
+            response(vec![
+                text("Opening the menu."),
+                computer_call(
+                    "call_1",
+                    json!({ "action": "left_click", "coordinate": [40, 12] }),
+                ),
+            ]),
+            response(vec![computer_call(
+                "call_2",
+                json!({ "action": "type", "text": "settings" }),
+            )]),
+            response(vec![text("The settings page is open.")]),
+        ]);
+        let mut env = FakeEnvironment::default();
+
+        let run = ComputerUseAgent::new()
+            .run(&client, "claude", request(), &mut env)
+            .await
+            .expect("run");
+
+        assert_eq!(run.stop, ComputerUseStop::Completed);
+        assert_eq!(run.steps, 2);
+        assert_eq!(
+            env.actions,
+            vec![
+                ComputerAction::Click {
+                    at: Point { x: 40, y: 12 },
+                    button: MouseButton::Left,
+                },
+                ComputerAction::Type {
+                    text: "settings".to_string(),
+                },
+            ]
+        );
+        // user, assistant, tool result, assistant, tool result, final assistant
+        assert_eq!(run.messages.len(), 6);
+
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].tools.len(), 1);
+        assert!(matches!(requests[0].tools[0].kind, ToolKind::ComputerUse));
+        let ContentPart::ToolResult(result) = &requests[1].messages[2].content[0] else {
+            panic!("unexpected message: {:?}", requests[1].messages[2]);
+        };
+        assert_eq!(result.call_id.as_deref(), Some("call_1"));
+        assert!(matches!(
+            &result.content[0],
+            ContentPart::Image(ImageContent { source: ImageSource::Base64 { data, .. }, .. })
+                if data == "shot-1"
+        ));
+    }
+
+    #[tokio::test]
+    async fn screenshot_actions_only_capture_the_screen() {
+        let client = ScriptedClient::new(vec![
+            response(vec![computer_call(
+                "call_1",
+                json!({ "action": { "type": "screenshot" } }),
+            )]),
+            response(vec![text("done")]),
+        ]);
+        let mut env = FakeEnvironment::default();
+
+        let run = ComputerUseAgent::new()
+            .run(&client, "openai", request(), &mut env)
+            .await
+            .expect("run");
+
+        assert_eq!(run.steps, 1);
+        assert!(env.actions.is_empty());
+        assert_eq!(env.screenshots, 1);
+    }
+
+    #[tokio::test]
+    async fn undecodable_calls_are_answered_with_errors() {
+        let client = ScriptedClient::new(vec![
+            response(vec![computer_call(
+                "call_1",
+                json!({ "action": "hold_key", "text": "shift", "duration": 1 }),
+            )]),
+            response(vec![text("ok")]),
+        ]);
+        let mut env = FakeEnvironment::default();
+
+        ComputerUseAgent::new()
+            .run(&client, "claude", request(), &mut env)
+            .await
+            .expect("run");
+
+        let requests = client.requests.lock().unwrap();
+        let ContentPart::ToolResult(result) = &requests[1].messages[2].content[0] else {
+            panic!("unexpected message: {:?}", requests[1].messages[2]);
+        };
+        assert!(result.is_error);
+        assert!(env.actions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn safety_checks_need_confirmation() {
+        let call = computer_call(
+            "call_1",
+            json!({
+                "id": "cu_1",
+                "action": { "type": "click", "button": "left", "x": 1, "y": 1 },
+                "pending_safety_checks": [{ "id": "sc_1", "code": "malicious_instructions", "message": "..." }]
+            }),
+        );
+
+        let client = ScriptedClient::new(vec![response(vec![call.clone()])]);
+        let err = ComputerUseAgent::new()
+            .run(
+                &client,
+                "openai",
+                request(),
+                &mut FakeEnvironment::default(),
+            )
+            .await
+            .expect_err("declined");
+        assert!(matches!(
+            err,
+            LLMError::Aborted { message } if message.contains("malicious_instructions")
+        ));
+
+        let client = ScriptedClient::new(vec![response(vec![call]), response(vec![text("ok")])]);
+        let mut env = FakeEnvironment {
+            confirm: true,
+            ..FakeEnvironment::default()
+        };
+        ComputerUseAgent::new()
+            .run(&client, "openai", request(), &mut env)
+            .await
+            .expect("confirmed");
+        let requests = client.requests.lock().unwrap();
+        let ContentPart::ToolResult(result) = &requests[1].messages[2].content[0] else {
+            panic!("unexpected message: {:?}", requests[1].messages[2]);
+        };
+        assert_eq!(
+            result.metadata.as_ref().unwrap()["acknowledged_safety_checks"][0]["id"],
+            json!("sc_1")
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_step_limit_and_on_other_tool_calls() {
+        let click = || {
+            computer_call(
+                "call",
+                json!({ "action": "left_click", "coordinate": [1, 1] }),
+            )
+        };
+        let client = ScriptedClient::new(vec![response(vec![click()]), response(vec![click()])]);
+        let run = ComputerUseAgent::new()
+            .with_max_steps(1)
+            .run(
+                &client,
+                "claude",
+                request(),
+                &mut FakeEnvironment::default(),
+            )
+            .await
+            .expect("run");
+        assert_eq!(run.stop, ComputerUseStop::MaxSteps);
+        assert_eq!(run.steps, 1);
+
+        let lookup = OutputItem::ToolCall {
+            call: ToolCall {
+                id: Some("call_2".to_string()),
+                name: "lookup".to_string(),
+                arguments: json!({}),
+                kind: ToolCallKind::Function,
+            },
+            index: 0,
+        };
+        let client = ScriptedClient::new(vec![response(vec![lookup])]);
+        let run = ComputerUseAgent::new()
+            .run(
+                &client,
+                "claude",
+                request(),
+                &mut FakeEnvironment::default(),
+            )
+            .await
+            .expect("run");
+        assert_eq!(run.stop, ComputerUseStop::ToolCalls);
+        assert_eq!(run.steps, 0);
+    }
+
+    /// Answers Responses API calls in order and records every request body.
+    #[cfg(feature = "openai")]
+    struct ResponsesTransport {
+        responses: Mutex<VecDeque<Value>>,
+        bodies: Mutex<Vec<Value>>,
+    }
+
+    #[cfg(feature = "openai")]
+    #[async_trait]
+    impl crate::http::HttpTransport for ResponsesTransport {
+        async fn send(
+            &self,
+            request: crate::http::HttpRequest,
+        ) -> Result<crate::http::HttpResponse, LLMError> {
+            let body = serde_json::from_slice(&request.body.unwrap_or_default()).expect("json");
+            self.bodies.lock().unwrap().push(body);
+            let response = self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("response");
+            Ok(crate::http::HttpResponse {
+                status: 200,
+                headers: HashMap::new(),
+                body: serde_json::to_vec(&response).expect("serialize"),
+            })
+        }
+
+        async fn send_stream(
+            &self,
+            _request: crate::http::HttpRequest,
+        ) -> Result<crate::http::HttpStreamResponse, LLMError> {
+            panic!("send_stream should not be called");
+        }
+    }
+
+    /// The second turn replays the reasoning item that came with the computer call.
+    #[cfg(feature = "openai")]
+    #[tokio::test]
+    async fn responses_turns_replay_reasoning_with_computer_calls() {
+        use std::sync::Arc;
+
+        use crate::client::LLMClient;
+        use crate::provider::openai_responses::OpenAiResponsesProvider;
+
+        let transport = Arc::new(ResponsesTransport {
+            responses: Mutex::new(VecDeque::from([
+                json!({
+                    "id": "resp_1",
+                    "object": "response",
+                    "model": "computer-use-preview",
+                    "status": "completed",
+                    "output": [
+                        { "type": "reasoning", "id": "rs_1", "summary": [] },
+                        {
+                            "type": "computer_call",
+                            "id": "cu_1",
+                            "call_id": "call_1",
+                            "status": "completed",
+                            "action": { "type": "click", "button": "left", "x": 40, "y": 12 },
+                            "pending_safety_checks": []
+                        }
+                    ]
+                }),
+                json!({
+                    "id": "resp_2",
+                    "object": "response",
+                    "model": "computer-use-preview",
+                    "status": "completed",
+                    "output": [{
+                        "type": "message",
+                        "id": "msg_1",
+                        "role": "assistant",
+                        "content": [{ "type": "output_text", "text": "Done.", "annotations": [] }]
+                    }]
+                }),
+            ])),
+            bodies: Mutex::new(Vec::new()),
+        });
+        let provider = OpenAiResponsesProvider::new(transport.clone(), "sk-test")
+            .with_default_model("computer-use-preview");
+        let client = LLMClient::builder()
+            .register_handle("openai", Arc::new(provider))
+            .expect("unique handle")
+            .build();
+        let mut env = FakeEnvironment::default();
+
+        let run = ComputerUseAgent::new()
+            .run(&client, "openai", request(), &mut env)
+            .await
+            .expect("run");
+
+        assert_eq!(run.stop, ComputerUseStop::Completed);
+        assert_eq!(run.steps, 1);
+        let bodies = transport.bodies.lock().unwrap();
+        assert_eq!(bodies.len(), 2);
+        let input = bodies[1]["input"].as_array().expect("input");
+        let types: Vec<&str> = input
+            .iter()
+            .map(|item| item["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "message",
+                "reasoning",
+                "computer_call",
+                "computer_call_output"
+            ]
+        );
+        assert_eq!(
+            input[1],
+            json!({ "type": "reasoning", "id": "rs_1", "summary": [] })
+        );
+        assert_eq!(input[2]["id"], "cu_1");
+        assert_eq!(input[2]["call_id"], "call_1");
+        assert!(input[2].get("reasoning").is_none());
+        assert_eq!(input[3]["call_id"], "call_1");
+        assert_eq!(
+            input[3]["output"]["image_url"],
+            "data:image/png;base64,shot-1"
+        );
+    }
+}
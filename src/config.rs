@@ -1,35 +1,86 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::catalog::ModelInfo;
 use crate::client::LLMClient;
+use crate::credential::{CredentialSource, DynCredentialProvider, StaticCredential};
 use crate::error::LLMError;
 use crate::http::DynHttpTransport;
 
 // Import the macro to register providers
 use crate::register_providers;
 
+mod loader;
+mod registry;
+
+pub use loader::{
+    ConfigFormat, load_configs_from_path, load_configs_from_path_with_registry,
+    load_configs_from_str, load_configs_from_str_with_registry,
+};
+pub use registry::{ProviderFactory, ProviderRegistry};
+
 // Register all providers using the macro
 register_providers!(
-    (openai_chat, "openai_chat", OpenAiChatProvider, OpenAiChat),
+    (
+        openai_chat,
+        "openai_chat",
+        OpenAiChatProvider,
+        OpenAiChat,
+        "openai"
+    ),
     (
         openai_responses,
         "openai_responses",
         OpenAiResponsesProvider,
-        OpenAiResponses
+        OpenAiResponses,
+        "openai"
     ),
     (
         anthropic_messages,
         "anthropic_messages",
         AnthropicMessagesProvider,
-        AnthropicMessages
+        AnthropicMessages,
+        "anthropic"
     ),
     (
         google_gemini,
         "google_gemini",
         GoogleGeminiProvider,
-        GoogleGemini
+        GoogleGemini,
+        "gemini"
+    ),
+    (
+        azure_openai,
+        "azure_openai",
+        AzureOpenAiProvider,
+        AzureOpenAi,
+        "azure"
+    ),
+    (
+        bedrock_converse,
+        "bedrock_converse",
+        BedrockConverseProvider,
+        BedrockConverse,
+        "bedrock"
+    ),
+    (
+        cohere_chat,
+        "cohere_chat",
+        CohereChatProvider,
+        CohereChat,
+        "cohere"
+    ),
+    (ollama, "ollama", OllamaProvider, Ollama, "ollama"),
+    (
+        openai_compatible,
+        "openai_compatible",
+        OpenAiCompatibleProvider,
+        OpenAiCompatible,
+        "openai-compatible"
     ),
 );
 
@@ -42,6 +93,7 @@ register_providers!(
 /// # Examples
 ///
 /// ```
+/// # #[cfg(feature = "openai")] {
 /// # use std::collections::HashMap;
 /// # use kotoba_llm::config::{ModelConfig, ProviderKind, Credential};
 /// let cfg = ModelConfig {
@@ -52,8 +104,10 @@ register_providers!(
 ///     base_url: Some("https://api.openai.com".into()),
 ///     extra: HashMap::from([("service_tier".into(), serde_json::json!("default"))]),
 ///     patch: None,
+///     catalog: Vec::new(),
 /// };
 /// assert_eq!(cfg.handle, "default-openai");
+/// # }
 /// ```
 ///
 /// Each configuration declares the provider kind, credentials, optional defaults, and
@@ -71,6 +125,13 @@ pub struct ModelConfig {
     pub extra: HashMap<String, Value>,
     /// Runtime request patch applied before dispatching the HTTP call.
     pub patch: Option<RequestPatch>,
+    /// Model catalog overrides for this provider, merged onto
+    /// [`crate::catalog::ModelCatalog::builtin`].
+    ///
+    /// Entries are keyed by provider name, so they also apply to other handles of the same
+    /// provider.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub catalog: Vec<ModelInfo>,
 }
 
 /// Credential variants understood by the configuration loader.
@@ -88,10 +149,87 @@ pub enum Credential {
     Bearer { token: String },
     /// Google or GCP Service Account JSON blob.
     ServiceAccount { json: Value },
-    /// Dummy variant for providers that do not require credentials.
+    /// AWS access key used to sign requests with Signature Version 4.
+    #[serde(rename = "aws_sigv4")]
+    AwsSigV4 {
+        access_key_id: String,
+        secret_access_key: String,
+        /// Session token of temporary STS credentials.
+        session_token: Option<String>,
+    },
+    /// AWS access key whose parts are fetched on demand, for temporary STS credentials
+    /// that rotate while the process runs.
+    ///
+    /// Each part is resolved like [`Credential::Source`], and cached values are dropped
+    /// when AWS reports the session token as expired.
+    #[serde(rename = "aws_sigv4_source")]
+    AwsSigV4Source {
+        access_key_id: CredentialSource,
+        secret_access_key: CredentialSource,
+        session_token: Option<CredentialSource>,
+        /// Seconds file or command values are reused before being fetched again.
+        cache_ttl_secs: Option<u64>,
+    },
+    /// Secret fetched from an environment variable, file or command on demand.
+    ///
+    /// Unlike the static variants, the secret is resolved per request, so rotated keys are
+    /// picked up without rebuilding the client.
+    Source {
+        /// Where the secret comes from.
+        source: CredentialSource,
+        /// Seconds a file or command secret is reused before being fetched again.
+        cache_ttl_secs: Option<u64>,
+    },
+    /// No credential, for servers that do not authenticate requests such as a local Ollama.
     None,
 }
 
+impl Credential {
+    /// Builds the [`DynCredentialProvider`] consulted by `provider` on every request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use kotoba_llm::config::Credential;
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let credential = Credential::Bearer { token: "sk-test".into() };
+    /// let provider = credential.credential_provider("openai_chat").expect("supported");
+    /// assert_eq!(provider.token().await.unwrap().value, "sk-test");
+    /// # });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Auth`] for [`Credential::None`] and for credential kinds the
+    /// provider cannot use.
+    pub fn credential_provider(
+        &self,
+        provider: &'static str,
+    ) -> Result<DynCredentialProvider, LLMError> {
+        match self {
+            Credential::ApiKey { key, .. } => Ok(Arc::new(StaticCredential::new(key.clone()))),
+            Credential::Bearer { token } => Ok(Arc::new(StaticCredential::new(token.clone()))),
+            Credential::Source {
+                source,
+                cache_ttl_secs,
+            } => Ok(source
+                .clone()
+                .into_provider(cache_ttl_secs.map(Duration::from_secs))),
+            Credential::ServiceAccount { .. } => Err(LLMError::Auth {
+                message: format!("provider {provider} does not support service account credential"),
+            }),
+            Credential::AwsSigV4 { .. } | Credential::AwsSigV4Source { .. } => {
+                Err(LLMError::Auth {
+                    message: format!("provider {provider} does not support AWS SigV4 credential"),
+                })
+            }
+            Credential::None => Err(LLMError::Auth {
+                message: format!("provider {provider} requires credential"),
+            }),
+        }
+    }
+}
+
 /// Declarative patch that can rewrite the URL, headers, or body at runtime.
 ///
 /// Typical use cases include injecting organization-wide headers, routing a
@@ -184,7 +322,7 @@ fn merge_json(target: &mut Value, patch: &Value) {
     }
 }
 
-fn remove_json_field(value: &mut Value, path: &str) {
+pub(crate) fn remove_json_field(value: &mut Value, path: &str) {
     let segments: Vec<&str> = path
         .split('.')
         .filter(|segment| !segment.is_empty())
@@ -233,6 +371,7 @@ fn remove_json_field_inner(value: &mut Value, segments: &[&str]) {
 /// # Examples
 ///
 /// ```
+/// # #[cfg(all(feature = "openai", feature = "reqwest-transport"))] {
 /// # use std::collections::HashMap;
 /// # use kotoba_llm::config::{ModelConfig, ProviderKind, Credential, build_client_from_configs};
 /// # use kotoba_llm::http::reqwest::default_dyn_transport;
@@ -244,10 +383,12 @@ fn remove_json_field_inner(value: &mut Value, segments: &[&str]) {
 ///     base_url: None,
 ///     extra: HashMap::new(),
 ///     patch: None,
+///     catalog: Vec::new(),
 /// }];
 /// let transport = default_dyn_transport().expect("transport");
 /// let client = build_client_from_configs(&configs, transport).expect("client");
 /// assert_eq!(client.handles(), vec!["default-openai".to_string()]);
+/// # }
 /// ```
 ///
 /// # Errors
@@ -258,27 +399,54 @@ fn remove_json_field_inner(value: &mut Value, segments: &[&str]) {
 pub fn build_client_from_configs(
     configs: &[ModelConfig],
     transport: DynHttpTransport,
+) -> Result<LLMClient, LLMError> {
+    build_client_from_configs_with_registry(configs, transport, &ProviderRegistry::new())
+}
+
+/// Builds an [`LLMClient`] like [`build_client_from_configs`], resolving provider names
+/// through `registry`.
+///
+/// This lets config files reference providers defined outside this crate, and lets a
+/// registered factory replace a built-in provider of the same name. See
+/// [`ProviderRegistry`] for an example.
+///
+/// # Errors
+///
+/// Returns the errors of [`build_client_from_configs`], and [`LLMError::InvalidConfig`]
+/// when a provider name is neither built in nor registered.
+pub fn build_client_from_configs_with_registry(
+    configs: &[ModelConfig],
+    transport: DynHttpTransport,
+    registry: &ProviderRegistry,
 ) -> Result<LLMClient, LLMError> {
     let mut builder = LLMClient::builder();
 
     for config in configs {
-        let provider = build_provider_from_config(config, transport.clone())?;
+        let provider = registry.build(config, transport.clone())?;
+        for info in &config.catalog {
+            builder = builder.register_model(provider.name(), info.clone());
+        }
         builder = builder.register_handle(config.handle.clone(), provider)?;
     }
 
     Ok(builder.build())
 }
 
+// Provider behaviour is tested next to each provider; these tests cover the config wiring.
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::http::reqwest::default_dyn_transport;
+    #[cfg(any(feature = "openai", feature = "anthropic", feature = "gemini"))]
+    use crate::http::test_support::PanicTransport;
+    #[cfg(any(feature = "openai", feature = "anthropic"))]
+    use crate::types::test_support::text_request;
     use serde_json::json;
 
     /// Ensures every [`ProviderKind`] variant can be registered on [`LLMClient`].
+    #[cfg(all(feature = "openai", feature = "anthropic", feature = "gemini"))]
     #[test]
     fn build_client_from_configs_supports_all_providers() {
-        let transport = default_dyn_transport().expect("transport");
+        let transport = Arc::new(PanicTransport);
 
         let configs = vec![
             ModelConfig {
@@ -292,6 +460,7 @@ mod tests {
                 base_url: None,
                 extra: HashMap::new(),
                 patch: None,
+                catalog: Vec::new(),
             },
             ModelConfig {
                 handle: "openai-responses".to_string(),
@@ -304,6 +473,7 @@ mod tests {
                 base_url: None,
                 extra: HashMap::new(),
                 patch: None,
+                catalog: Vec::new(),
             },
             ModelConfig {
                 handle: "anthropic-messages".to_string(),
@@ -316,6 +486,7 @@ mod tests {
                 base_url: None,
                 extra: HashMap::new(),
                 patch: None,
+                catalog: Vec::new(),
             },
             ModelConfig {
                 handle: "gemini-generate".to_string(),
@@ -328,6 +499,7 @@ mod tests {
                 base_url: None,
                 extra: HashMap::new(),
                 patch: None,
+                catalog: Vec::new(),
             },
         ];
 
@@ -346,9 +518,10 @@ mod tests {
         );
     }
 
+    #[cfg(all(feature = "openai", feature = "gemini"))]
     #[test]
     fn build_client_from_configs_builds_handles() {
-        let transport = default_dyn_transport().expect("transport");
+        let transport = Arc::new(PanicTransport);
 
         let configs = vec![
             ModelConfig {
@@ -362,6 +535,7 @@ mod tests {
                 base_url: None,
                 extra: HashMap::new(),
                 patch: None,
+                catalog: Vec::new(),
             },
             ModelConfig {
                 handle: "gemini-default".to_string(),
@@ -374,6 +548,7 @@ mod tests {
                 base_url: None,
                 extra: HashMap::new(),
                 patch: None,
+                catalog: Vec::new(),
             },
         ];
 
@@ -387,9 +562,10 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "openai")]
     #[test]
     fn build_client_from_configs_requires_credential() {
-        let transport = default_dyn_transport().expect("transport");
+        let transport = Arc::new(PanicTransport);
 
         let configs = vec![ModelConfig {
             handle: "openai-no-cred".to_string(),
@@ -399,6 +575,7 @@ mod tests {
             base_url: None,
             extra: HashMap::new(),
             patch: None,
+            catalog: Vec::new(),
         }];
 
         let result = build_client_from_configs(&configs, transport);
@@ -417,10 +594,11 @@ mod tests {
         }
     }
 
-    /// Rejects unsupported service account credentials.
+    /// Rejects incomplete service account credentials.
+    #[cfg(feature = "gemini")]
     #[test]
     fn build_client_from_configs_rejects_service_account() {
-        let transport = default_dyn_transport().expect("transport");
+        let transport = Arc::new(PanicTransport);
 
         let configs = vec![ModelConfig {
             handle: "gemini-service-account".to_string(),
@@ -432,6 +610,7 @@ mod tests {
             base_url: None,
             extra: HashMap::new(),
             patch: None,
+            catalog: Vec::new(),
         }];
 
         let result = build_client_from_configs(&configs, transport);
@@ -452,9 +631,10 @@ mod tests {
     }
 
     /// Accepts bearer-token credentials when building the client.
+    #[cfg(feature = "openai")]
     #[test]
     fn build_client_from_configs_accepts_bearer_token() {
-        let transport = default_dyn_transport().expect("transport");
+        let transport = Arc::new(PanicTransport);
 
         let configs = vec![ModelConfig {
             handle: "openai-responses-bearer".to_string(),
@@ -466,6 +646,7 @@ mod tests {
             base_url: None,
             extra: HashMap::new(),
             patch: None,
+            catalog: Vec::new(),
         }];
 
         let result = build_client_from_configs(&configs, transport);
@@ -475,9 +656,10 @@ mod tests {
     }
 
     /// Surfaces a validation error when duplicate handles exist in the input configs.
+    #[cfg(all(feature = "openai", feature = "gemini"))]
     #[test]
     fn build_client_from_configs_rejects_duplicate_handles() {
-        let transport = default_dyn_transport().expect("transport");
+        let transport = Arc::new(PanicTransport);
 
         let cfg1 = ModelConfig {
             handle: "dup-handle".to_string(),
@@ -490,6 +672,7 @@ mod tests {
             base_url: None,
             extra: HashMap::new(),
             patch: None,
+            catalog: Vec::new(),
         };
 
         let cfg2 = ModelConfig {
@@ -503,6 +686,7 @@ mod tests {
             base_url: None,
             extra: HashMap::new(),
             patch: None,
+            catalog: Vec::new(),
         };
 
         let configs = vec![cfg1, cfg2];
@@ -524,6 +708,114 @@ mod tests {
         }
     }
 
+    /// Every built-in provider maps to the cargo feature that compiles it.
+    #[cfg(all(
+        feature = "openai",
+        feature = "anthropic",
+        feature = "gemini",
+        feature = "azure",
+        feature = "bedrock",
+        feature = "cohere",
+        feature = "ollama",
+        feature = "openai-compatible"
+    ))]
+    #[test]
+    fn provider_kinds_report_their_cargo_features() {
+        let names = ProviderKind::list_provider_types();
+        assert_eq!(names.len(), 9);
+        assert!(
+            names
+                .iter()
+                .all(|name| ProviderKind::required_feature(name).is_some())
+        );
+        assert_eq!(
+            ProviderKind::required_feature("azure_openai"),
+            Some("azure")
+        );
+        assert_eq!(
+            ProviderKind::required_feature("openai_compatible"),
+            Some("openai-compatible")
+        );
+        assert_eq!(ProviderKind::required_feature("acme_gateway"), None);
+    }
+
+    /// Registered factories resolve custom provider names and may replace built-in ones.
+    #[cfg(all(feature = "openai", feature = "bedrock", feature = "ollama"))]
+    #[tokio::test]
+    async fn provider_registry_resolves_custom_and_overridden_providers() {
+        fn gateway(
+            config: &ModelConfig,
+            transport: DynHttpTransport,
+        ) -> Result<crate::provider::DynProvider, LLMError> {
+            Ok(Arc::new(
+                crate::provider::ollama::OllamaProvider::from_model_config(config, transport)?,
+            ))
+        }
+
+        let configs: Vec<ModelConfig> = serde_json::from_value(json!([
+            {
+                "handle": "gateway",
+                "provider": "acme_gateway",
+                "credential": { "type": "none" },
+                "default_model": "acme-large",
+                "base_url": null,
+                "patch": null
+            },
+            {
+                "handle": "openai",
+                "provider": "openai_chat",
+                "credential": { "type": "none" },
+                "default_model": "gpt-4.1-mini",
+                "base_url": null,
+                "patch": null
+            }
+        ]))
+        .expect("configs");
+        assert!(
+            matches!(&configs[0].provider, ProviderKind::Custom(name) if name == "acme_gateway")
+        );
+        assert_eq!(
+            serde_json::to_value(&configs[0].provider).unwrap(),
+            json!("acme_gateway")
+        );
+
+        match build_client_from_configs(&configs, Arc::new(PanicTransport)) {
+            Err(LLMError::InvalidConfig { field, reason }) => {
+                assert_eq!(field, "provider");
+                assert!(
+                    reason.contains("unknown provider `acme_gateway`"),
+                    "{reason}"
+                );
+            }
+            Err(other) => panic!("unexpected error: {other:?}"),
+            Ok(_) => panic!("expected unknown provider"),
+        }
+
+        // `openai_chat` rejects `Credential::None`, so building it proves the override ran.
+        let registry = ProviderRegistry::new()
+            .register("acme_gateway", gateway)
+            .and_then(|registry| registry.register("openai_chat", gateway))
+            .expect("registry");
+        assert!(registry.contains("acme_gateway") && registry.contains("bedrock_converse"));
+        assert!(matches!(
+            registry.clone().register("acme_gateway", gateway),
+            Err(LLMError::InvalidConfig { .. })
+        ));
+
+        let client =
+            build_client_from_configs_with_registry(&configs, Arc::new(PanicTransport), &registry)
+                .expect("client");
+        let mut handles = client.handles();
+        handles.sort();
+        assert_eq!(handles, vec!["gateway".to_string(), "openai".to_string()]);
+        let request = text_request("ping");
+        let prepared = client
+            .prepare_request("openai", &request, false)
+            .await
+            .expect("prepared request");
+        assert_eq!(prepared.url, "http://localhost:11434/api/chat");
+    }
+
     #[test]
     fn request_patch_merges_and_removes_fields() {
         let patch = RequestPatch {
@@ -585,4 +877,141 @@ mod tests {
         assert!(headers.is_empty());
         assert_eq!(url, "https://example.com");
     }
+
+    /// `prepare_request` reflects the configured patch without dispatching anything.
+    #[cfg(feature = "anthropic")]
+    #[tokio::test]
+    async fn prepare_request_applies_request_patch() {
+        let transport = Arc::new(PanicTransport);
+        let configs = vec![ModelConfig {
+            handle: "anthropic-patched".to_string(),
+            provider: ProviderKind::AnthropicMessages,
+            credential: Credential::ApiKey {
+                header: None,
+                key: "test-key".to_string(),
+            },
+            default_model: Some("claude-3-5-sonnet".to_string()),
+            base_url: None,
+            extra: HashMap::new(),
+            patch: Some(RequestPatch {
+                url: Some("https://proxy.local/v1/messages".to_string()),
+                body: Some(json!({ "metadata": { "user_id": "cli" } })),
+                headers: Some(HashMap::from([(
+                    "x-trace".to_string(),
+                    Some("abc".to_string()),
+                )])),
+                remove_fields: Some(vec!["max_tokens".to_string()]),
+            }),
+            catalog: Vec::new(),
+        }];
+        let client = build_client_from_configs(&configs, transport).expect("client");
+        let mut request = text_request("ping");
+        request.options.max_output_tokens = Some(64);
+
+        let prepared = client
+            .prepare_request("anthropic-patched", &request, true)
+            .await
+            .expect("prepared request");
+
+        assert_eq!(prepared.url, "https://proxy.local/v1/messages");
+        assert_eq!(prepared.headers.get("x-trace"), Some(&"abc".to_string()));
+        assert_eq!(prepared.body["model"], json!("claude-3-5-sonnet"));
+        assert_eq!(prepared.body["stream"], json!(true));
+        assert_eq!(prepared.body["metadata"]["user_id"], json!("cli"));
+        assert!(prepared.body.get("max_tokens").is_none());
+    }
+
+    /// A `Source` credential is resolved per request, so rotated secrets show up immediately.
+    #[cfg(feature = "openai")]
+    #[tokio::test]
+    async fn source_credential_picks_up_rotated_secrets() {
+        let path = std::env::temp_dir().join(format!("kotoba-rotating-{}.key", std::process::id()));
+        std::fs::write(&path, "sk-first\n").expect("write key");
+
+        let transport = Arc::new(PanicTransport);
+        let configs = vec![ModelConfig {
+            handle: "rotating".to_string(),
+            provider: ProviderKind::OpenAiChat,
+            credential: Credential::Source {
+                source: CredentialSource::File { path: path.clone() },
+                cache_ttl_secs: Some(0),
+            },
+            default_model: Some("gpt-4.1-mini".to_string()),
+            base_url: None,
+            extra: HashMap::new(),
+            patch: None,
+            catalog: Vec::new(),
+        }];
+        let client = build_client_from_configs(&configs, transport).expect("client");
+        let request = text_request("ping");
+
+        let first = client
+            .prepare_request("rotating", &request, false)
+            .await
+            .map(|prepared| prepared.headers["Authorization"].clone());
+        std::fs::write(&path, "sk-second\n").expect("rotate key");
+        let second = client
+            .prepare_request("rotating", &request, false)
+            .await
+            .map(|prepared| prepared.headers["Authorization"].clone());
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(first.expect("first request"), "Bearer sk-first");
+        assert_eq!(second.expect("second request"), "Bearer sk-second");
+    }
+
+    /// Catalog entries in the config narrow capabilities for every handle of the provider.
+    #[cfg(feature = "openai")]
+    #[test]
+    fn catalog_overrides_apply_to_client_capabilities() {
+        let configs: Vec<ModelConfig> = serde_json::from_value(json!([
+            {
+                "handle": "openai",
+                "provider": "openai_chat",
+                "credential": { "type": "api_key", "key": "sk-test" },
+                "default_model": "gpt-4o",
+                "base_url": null,
+                "patch": null,
+                "catalog": [
+                    {
+                        "model": "ft:gpt-4o-mini:acme",
+                        "capabilities": { "supports_stream": true, "supports_tools": true },
+                        "context_window": 64000
+                    }
+                ]
+            },
+            {
+                "handle": "openai-mini",
+                "provider": "openai_chat",
+                "credential": { "type": "api_key", "key": "sk-test" },
+                "default_model": "ft:gpt-4o-mini:acme",
+                "base_url": null,
+                "patch": null
+            }
+        ]))
+        .expect("configs");
+        let client = build_client_from_configs(&configs, Arc::new(PanicTransport)).expect("client");
+
+        assert!(client.capabilities("openai").unwrap().supports_image_input);
+        let tuned = client.capabilities("openai-mini").expect("capabilities");
+        assert!(tuned.supports_tools && !tuned.supports_image_input);
+
+        let info = client
+            .model_info("openai-mini", None)
+            .expect("handle")
+            .expect("catalog entry");
+        assert_eq!(info.context_window, Some(64_000));
+        let info = client
+            .model_info("openai", Some("o3-mini-2025-01-31"))
+            .expect("handle")
+            .expect("catalog entry");
+        assert_eq!(info.max_output_tokens, Some(100_000));
+
+        let mut request = text_request("ping");
+        request.options.model = Some("o3-mini".to_string());
+        let caps = client
+            .capabilities_for_request("openai", &request)
+            .expect("capabilities");
+        assert!(!caps.supports_image_input && !caps.supports_parallel_tool_calls);
+    }
 }
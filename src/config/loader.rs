@@ -0,0 +1,696 @@
+//! Loads [`ModelConfig`] lists from TOML, YAML or JSON documents.
+//!
+//! Every string in the document may reference secrets instead of embedding them:
+//!
+//! - `${NAME}` anywhere inside a string is replaced by the environment variable `NAME`
+//!   (write `$${` for a literal `${`);
+//! - a string of the form `env:NAME` is replaced by the environment variable `NAME`;
+//! - a string of the form `file:PATH` is replaced by the file contents without the trailing
+//!   newline. Relative paths resolve against the config file's directory.
+//!
+//! Interpolation runs before deserialization, and the resulting configs are validated up
+//! front so that typos surface at startup instead of on the first request.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::error::LLMError;
+
+use super::{ModelConfig, ProviderKind, ProviderRegistry};
+
+/// Serialization formats accepted by the loader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// TOML document with a `[[models]]` array of tables; requires the `toml` feature.
+    Toml,
+    /// YAML document holding either a list of models or a `models` key; requires the
+    /// `yaml` feature.
+    Yaml,
+    /// JSON document holding either an array of models or a `models` key.
+    Json,
+}
+
+impl ConfigFormat {
+    /// Infers the format from a file extension (`toml`, `yaml`/`yml`, `json`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use kotoba_llm::config::ConfigFormat;
+    /// assert_eq!(ConfigFormat::from_path(Path::new("models.yml")), Some(ConfigFormat::Yaml));
+    /// assert_eq!(ConfigFormat::from_path(Path::new("models.ini")), None);
+    /// ```
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Toml => "TOML",
+            Self::Yaml => "YAML",
+            Self::Json => "JSON",
+        }
+    }
+}
+
+/// Reads, interpolates and validates the model configs stored at `path`.
+///
+/// The format is inferred from the file extension, and `file:` references resolve relative
+/// to the file's directory.
+///
+/// # Errors
+///
+/// Returns [`LLMError::InvalidConfig`] when the file cannot be read or parsed, or when any
+/// entry fails interpolation or validation. Entry-level problems are aggregated into a
+/// single error that names every offending handle.
+pub fn load_configs_from_path(path: impl AsRef<Path>) -> Result<Vec<ModelConfig>, LLMError> {
+    load_configs_from_path_with_registry(path, &ProviderRegistry::new())
+}
+
+/// Like [`load_configs_from_path`], but also accepts the provider names known to `registry`.
+///
+/// # Errors
+///
+/// See [`load_configs_from_path`].
+pub fn load_configs_from_path_with_registry(
+    path: impl AsRef<Path>,
+    registry: &ProviderRegistry,
+) -> Result<Vec<ModelConfig>, LLMError> {
+    let path = path.as_ref();
+    let format = ConfigFormat::from_path(path).ok_or_else(|| LLMError::InvalidConfig {
+        field: path.display().to_string(),
+        reason: "unknown config format; expected a .toml, .yaml, .yml or .json file".to_string(),
+    })?;
+    let input = fs::read_to_string(path).map_err(|err| LLMError::InvalidConfig {
+        field: path.display().to_string(),
+        reason: format!("failed to read config file: {err}"),
+    })?;
+    let interpolator = Interpolator {
+        env: &|name| std::env::var(name).ok(),
+        base_dir: path.parent().map(Path::to_path_buf),
+    };
+    load_configs(&input, format, &interpolator, registry)
+}
+
+/// Parses, interpolates and validates model configs from an in-memory document.
+///
+/// `file:` references resolve relative to the current working directory.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "openai", feature = "toml"))] {
+/// # use kotoba_llm::config::{ConfigFormat, load_configs_from_str};
+/// let input = r#"
+/// [[models]]
+/// handle = "openai"
+/// provider = "openai_chat"
+/// default_model = "gpt-4.1-mini"
+/// credential = { type = "api_key", key = "sk-test" }
+/// extra = { organization = "org_123" }
+/// "#;
+/// let configs = load_configs_from_str(input, ConfigFormat::Toml).expect("valid config");
+/// assert_eq!(configs[0].handle, "openai");
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns [`LLMError::InvalidConfig`] when the document cannot be parsed, or when any entry
+/// fails interpolation or validation.
+pub fn load_configs_from_str(
+    input: &str,
+    format: ConfigFormat,
+) -> Result<Vec<ModelConfig>, LLMError> {
+    load_configs_from_str_with_registry(input, format, &ProviderRegistry::new())
+}
+
+/// Like [`load_configs_from_str`], but also accepts the provider names known to `registry`.
+///
+/// Without a registry, a `provider` that names no built-in provider is rejected so that
+/// typos surface at load time.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "openai", feature = "toml"))] {
+/// # use std::sync::Arc;
+/// # use kotoba_llm::config::{
+/// #     ConfigFormat, ModelConfig, ProviderRegistry, load_configs_from_str,
+/// #     load_configs_from_str_with_registry,
+/// # };
+/// # use kotoba_llm::error::LLMError;
+/// # use kotoba_llm::http::DynHttpTransport;
+/// # use kotoba_llm::provider::{DynProvider, openai_chat::OpenAiChatProvider};
+/// # fn build_gateway(config: &ModelConfig, transport: DynHttpTransport) -> Result<DynProvider, LLMError> {
+/// #     Ok(Arc::new(OpenAiChatProvider::from_model_config(config, transport)?))
+/// # }
+/// let input = r#"
+/// [[models]]
+/// handle = "gateway"
+/// provider = "acme_gateway"
+/// default_model = "acme-large"
+/// credential = { type = "bearer", token = "secret" }
+/// "#;
+/// assert!(load_configs_from_str(input, ConfigFormat::Toml).is_err());
+///
+/// let registry = ProviderRegistry::new()
+///     .register("acme_gateway", build_gateway)
+///     .expect("unique name");
+/// let configs = load_configs_from_str_with_registry(input, ConfigFormat::Toml, &registry)
+///     .expect("valid config");
+/// assert_eq!(configs[0].provider.as_str(), "acme_gateway");
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// See [`load_configs_from_str`].
+pub fn load_configs_from_str_with_registry(
+    input: &str,
+    format: ConfigFormat,
+    registry: &ProviderRegistry,
+) -> Result<Vec<ModelConfig>, LLMError> {
+    let interpolator = Interpolator {
+        env: &|name| std::env::var(name).ok(),
+        base_dir: None,
+    };
+    load_configs(input, format, &interpolator, registry)
+}
+
+/// Resolves `${VAR}`, `env:` and `file:` references inside string values.
+struct Interpolator<'a> {
+    env: &'a dyn Fn(&str) -> Option<String>,
+    base_dir: Option<PathBuf>,
+}
+
+impl Interpolator<'_> {
+    /// Rewrites every string in `value`, pushing one message per failed reference.
+    fn apply(&self, value: &mut Value, path: &str, errors: &mut Vec<String>) {
+        match value {
+            Value::String(text) => match self.resolve(text) {
+                Ok(resolved) => *text = resolved,
+                Err(reason) => errors.push(format!("{path}: {reason}")),
+            },
+            Value::Array(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    self.apply(item, &format!("{path}[{index}]"), errors);
+                }
+            }
+            Value::Object(map) => {
+                for (key, item) in map.iter_mut() {
+                    let child = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    self.apply(item, &child, errors);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn resolve(&self, text: &str) -> Result<String, String> {
+        if let Some(name) = text.strip_prefix("env:") {
+            return self.lookup(name.trim());
+        }
+        if let Some(file) = text.strip_prefix("file:") {
+            return self.read_file(file.trim());
+        }
+        self.substitute(text)
+    }
+
+    fn lookup(&self, name: &str) -> Result<String, String> {
+        if name.is_empty() {
+            return Err("empty environment variable name".to_string());
+        }
+        (self.env)(name).ok_or_else(|| format!("environment variable `{name}` is not set"))
+    }
+
+    fn read_file(&self, file: &str) -> Result<String, String> {
+        let mut path = PathBuf::from(file);
+        if path.is_relative() {
+            if let Some(base) = &self.base_dir {
+                path = base.join(path);
+            }
+        }
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read `{}`: {err}", path.display()))?;
+        Ok(contents.trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    fn substitute(&self, text: &str) -> Result<String, String> {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            output.push_str(&rest[..start]);
+            let tail = &rest[start..];
+            if let Some(escaped) = tail.strip_prefix("$${") {
+                output.push_str("${");
+                rest = escaped;
+            } else if let Some(reference) = tail.strip_prefix("${") {
+                let end = reference
+                    .find('}')
+                    .ok_or_else(|| format!("unterminated `${{` in `{text}`"))?;
+                output.push_str(&self.lookup(reference[..end].trim())?);
+                rest = &reference[end + 1..];
+            } else {
+                output.push('$');
+                rest = &tail[1..];
+            }
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+fn load_configs(
+    input: &str,
+    format: ConfigFormat,
+    interpolator: &Interpolator<'_>,
+    registry: &ProviderRegistry,
+) -> Result<Vec<ModelConfig>, LLMError> {
+    let document = parse_document(input, format)?;
+    let entries = match document {
+        Value::Array(entries) => entries,
+        Value::Object(mut map) => match map.remove("models") {
+            Some(Value::Array(entries)) => entries,
+            _ => {
+                return Err(LLMError::InvalidConfig {
+                    field: "models".to_string(),
+                    reason: "expected a `models` list of model configs".to_string(),
+                });
+            }
+        },
+        _ => {
+            return Err(LLMError::InvalidConfig {
+                field: "models".to_string(),
+                reason: "expected a list of model configs or a `models` key".to_string(),
+            });
+        }
+    };
+
+    let mut configs = Vec::with_capacity(entries.len());
+    let mut errors = Vec::new();
+    let mut seen = HashSet::new();
+    for (index, mut entry) in entries.into_iter().enumerate() {
+        let label = match entry.get("handle").and_then(Value::as_str) {
+            Some(handle) => format!("handle `{handle}`"),
+            None => format!("models[{index}]"),
+        };
+        let mut entry_errors = Vec::new();
+        interpolator.apply(&mut entry, "", &mut entry_errors);
+        if entry_errors.is_empty() {
+            match serde_json::from_value::<ModelConfig>(entry) {
+                Ok(config) => {
+                    if !seen.insert(config.handle.clone()) {
+                        entry_errors.push("duplicate handle".to_string());
+                    }
+                    validate_config(&config, registry, &mut entry_errors);
+                    configs.push(config);
+                }
+                Err(err) => entry_errors.push(err.to_string()),
+            }
+        }
+        errors.extend(
+            entry_errors
+                .into_iter()
+                .map(|reason| format!("{label}: {reason}")),
+        );
+    }
+
+    if errors.is_empty() {
+        Ok(configs)
+    } else {
+        Err(LLMError::InvalidConfig {
+            field: "models".to_string(),
+            reason: errors.join("; "),
+        })
+    }
+}
+
+fn parse_document(input: &str, format: ConfigFormat) -> Result<Value, LLMError> {
+    let parsed = match format {
+        #[cfg(feature = "toml")]
+        ConfigFormat::Toml => toml::from_str::<Value>(input).map_err(|err| err.to_string()),
+        #[cfg(feature = "yaml")]
+        ConfigFormat::Yaml => serde_norway::from_str::<Value>(input).map_err(|err| err.to_string()),
+        ConfigFormat::Json => serde_json::from_str::<Value>(input).map_err(|err| err.to_string()),
+        #[cfg(not(all(feature = "toml", feature = "yaml")))]
+        disabled => {
+            let feature = match disabled {
+                ConfigFormat::Toml => "toml",
+                _ => "yaml",
+            };
+            return Err(LLMError::InvalidConfig {
+                field: "models".to_string(),
+                reason: format!(
+                    "{} configs require the `{feature}` cargo feature of kotoba-llm",
+                    disabled.label()
+                ),
+            });
+        }
+    };
+    parsed.map_err(|reason| LLMError::InvalidConfig {
+        field: "models".to_string(),
+        reason: format!("failed to parse {}: {reason}", format.label()),
+    })
+}
+
+fn validate_config(config: &ModelConfig, registry: &ProviderRegistry, errors: &mut Vec<String>) {
+    let name = config.provider.as_str();
+    if config.provider.is_custom() && !registry.contains(name) {
+        errors.push(match ProviderKind::required_feature(name) {
+            Some(feature) => {
+                format!("provider `{name}` requires the `{feature}` cargo feature of kotoba-llm")
+            }
+            None => format!(
+                "unknown variant `{name}`, expected one of {}",
+                registry.provider_names().join(", ")
+            ),
+        });
+    }
+
+    // Custom providers own their `extra` keys; their factories validate them.
+    let supported = config.provider.supported_extra_keys();
+    let mut unknown: Vec<&str> = config
+        .extra
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !config.provider.is_custom() && !supported.contains(key))
+        .collect();
+    unknown.sort_unstable();
+    for key in unknown {
+        let expected = if supported.is_empty() {
+            "it accepts no extra keys".to_string()
+        } else {
+            format!("expected one of: {}", supported.join(", "))
+        };
+        errors.push(format!(
+            "extra key `{key}` is not supported by {name} ({expected})"
+        ));
+    }
+
+    match config.default_model.as_deref().map(str::trim) {
+        None | Some("") => errors.push("default_model is required".to_string()),
+        Some(_) => {}
+    }
+
+    if let Some(base_url) = &config.base_url {
+        if let Err(reason) = validate_base_url(base_url) {
+            errors.push(format!("base_url `{base_url}` {reason}"));
+        }
+    }
+
+    for (index, info) in config.catalog.iter().enumerate() {
+        if info.model.trim().is_empty() {
+            errors.push(format!("catalog[{index}].model is required"));
+        }
+    }
+}
+
+fn validate_base_url(base_url: &str) -> Result<(), String> {
+    let parsed = url::Url::parse(base_url).map_err(|err| format!("is not a valid URL: {err}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("must use the http or https scheme".to_string());
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err("must include a host".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(all(
+    test,
+    feature = "openai",
+    feature = "anthropic",
+    feature = "gemini",
+    feature = "toml",
+    feature = "yaml"
+))]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::config::{Credential, ProviderKind};
+
+    fn load_with_env(
+        input: &str,
+        format: ConfigFormat,
+        vars: &[(&str, &str)],
+    ) -> Result<Vec<ModelConfig>, LLMError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let lookup = move |name: &str| vars.get(name).cloned();
+        let interpolator = Interpolator {
+            env: &lookup,
+            base_dir: None,
+        };
+        load_configs(input, format, &interpolator, &ProviderRegistry::new())
+    }
+
+    fn invalid_reason(result: Result<Vec<ModelConfig>, LLMError>) -> String {
+        match result {
+            Err(LLMError::InvalidConfig { reason, .. }) => reason,
+            Err(other) => panic!("unexpected error: {other:?}"),
+            Ok(configs) => panic!("expected invalid config, got {configs:?}"),
+        }
+    }
+
+    #[test]
+    fn loads_the_same_models_from_every_format() {
+        let toml = r#"
+            [[models]]
+            handle = "openai"
+            provider = "openai_chat"
+            default_model = "gpt-4.1-mini"
+            credential = { type = "api_key", key = "${OPENAI_KEY}" }
+        "#;
+        let yaml = r#"
+models:
+  - handle: openai
+    provider: openai_chat
+    default_model: gpt-4.1-mini
+    credential:
+      type: api_key
+      key: env:OPENAI_KEY
+"#;
+        let json = r#"[{
+            "handle": "openai",
+            "provider": "openai_chat",
+            "default_model": "gpt-4.1-mini",
+            "credential": { "type": "api_key", "key": "${OPENAI_KEY}" }
+        }]"#;
+        let env = [("OPENAI_KEY", "sk-from-env")];
+
+        for (input, format) in [
+            (toml, ConfigFormat::Toml),
+            (yaml, ConfigFormat::Yaml),
+            (json, ConfigFormat::Json),
+        ] {
+            let configs = load_with_env(input, format, &env).expect("valid config");
+            assert_eq!(configs.len(), 1, "{format:?}");
+            assert_eq!(configs[0].handle, "openai");
+            assert!(matches!(configs[0].provider, ProviderKind::OpenAiChat));
+            match &configs[0].credential {
+                Credential::ApiKey { key, .. } => assert_eq!(key, "sk-from-env"),
+                other => panic!("unexpected credential: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn substitutes_embedded_references_and_escapes() {
+        let interpolator = Interpolator {
+            env: &|name| (name == "HOST").then(|| "proxy.local".to_string()),
+            base_dir: None,
+        };
+        assert_eq!(
+            interpolator
+                .resolve("https://${HOST}/v1?price=$5&tpl=$${HOST}")
+                .unwrap(),
+            "https://proxy.local/v1?price=$5&tpl=${HOST}"
+        );
+        assert!(interpolator.resolve("${MISSING}").is_err());
+        assert!(interpolator.resolve("${HOST").is_err());
+    }
+
+    #[test]
+    fn reads_file_references_relative_to_base_dir() {
+        let dir = std::env::temp_dir().join(format!("kotoba-loader-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("temp dir");
+        fs::write(dir.join("key.txt"), "sk-from-file\n").expect("write key");
+
+        let interpolator = Interpolator {
+            env: &|_| None,
+            base_dir: Some(dir.clone()),
+        };
+        let resolved = interpolator.resolve("file:key.txt");
+        fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(resolved.unwrap(), "sk-from-file");
+    }
+
+    #[test]
+    fn aggregates_validation_errors_per_handle() {
+        let yaml = r#"
+- handle: openai
+  provider: openai_chat
+  credential: { type: api_key, key: sk-test }
+  extra: { orgnization: org_123 }
+- handle: gemini
+  provider: google_gemini
+  default_model: gemini-2.0-flash
+  base_url: ftp://example.com
+  credential: { type: api_key, key: "${GEMINI_KEY}" }
+- handle: openai
+  provider: openai_chat
+  default_model: gpt-4.1-mini
+  credential: { type: api_key, key: sk-test }
+"#;
+        let reason = invalid_reason(load_with_env(yaml, ConfigFormat::Yaml, &[]));
+
+        assert!(
+            reason.contains(
+                "handle `openai`: extra key `orgnization` is not supported by openai_chat"
+            ),
+            "{reason}"
+        );
+        assert!(
+            reason.contains("handle `openai`: default_model is required"),
+            "{reason}"
+        );
+        assert!(
+            reason.contains("handle `gemini`: credential.key: environment variable `GEMINI_KEY`"),
+            "{reason}"
+        );
+        assert!(
+            reason.contains("handle `openai`: duplicate handle"),
+            "{reason}"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_base_urls_and_unknown_providers() {
+        let json = r#"{"models": [
+            {
+                "handle": "proxy",
+                "provider": "anthropic_messages",
+                "default_model": "claude-3-5-sonnet",
+                "base_url": "not a url",
+                "credential": { "type": "api_key", "key": "sk-test" }
+            },
+            {
+                "handle": "mystery",
+                "provider": "unknown_vendor",
+                "default_model": "x",
+                "credential": { "type": "none" }
+            }
+        ]}"#;
+        let reason = invalid_reason(load_with_env(json, ConfigFormat::Json, &[]));
+
+        assert!(
+            reason.contains("handle `proxy`: base_url `not a url` is not a valid URL"),
+            "{reason}"
+        );
+        assert!(
+            reason.contains("handle `mystery`: unknown variant `unknown_vendor`"),
+            "{reason}"
+        );
+    }
+
+    #[test]
+    fn loads_catalog_overrides_and_rejects_unnamed_entries() {
+        let toml = r#"
+            [[models]]
+            handle = "anthropic"
+            provider = "anthropic_messages"
+            default_model = "claude-opus-4-20250514"
+            credential = { type = "api_key", key = "sk-test" }
+
+            [[models.catalog]]
+            model = "claude-opus-4-20250514"
+            max_output_tokens = 16000
+            capabilities = { supports_stream = true, supports_tools = true }
+
+            [[models.catalog]]
+            model = ""
+            context_window = 1000
+        "#;
+        let reason = invalid_reason(load_with_env(toml, ConfigFormat::Toml, &[]));
+        assert!(
+            reason.contains("handle `anthropic`: catalog[1].model is required"),
+            "{reason}"
+        );
+
+        let valid = toml
+            .split("[[models.catalog]]\n            model = \"\"")
+            .next();
+        let configs =
+            load_with_env(valid.expect("prefix"), ConfigFormat::Toml, &[]).expect("valid config");
+        let catalog = &configs[0].catalog;
+        assert_eq!(catalog.len(), 1);
+        assert_eq!(catalog[0].max_output_tokens, Some(16_000));
+        let caps = catalog[0].capabilities.as_ref().expect("capabilities");
+        assert!(caps.supports_tools && !caps.supports_image_input);
+    }
+
+    #[test]
+    fn accepts_registered_custom_providers_and_their_extra_keys() {
+        fn gateway(
+            _config: &ModelConfig,
+            _transport: crate::http::DynHttpTransport,
+        ) -> Result<crate::provider::DynProvider, LLMError> {
+            unreachable!("the loader never builds providers")
+        }
+
+        let yaml = r#"
+models:
+  - handle: gateway
+    provider: acme_gateway
+    default_model: acme-large
+    credential:
+      type: none
+    extra:
+      tenant: research
+"#;
+        let registry = ProviderRegistry::new()
+            .register("acme_gateway", gateway)
+            .expect("registry");
+        let interpolator = Interpolator {
+            env: &|_| None,
+            base_dir: None,
+        };
+        let configs =
+            load_configs(yaml, ConfigFormat::Yaml, &interpolator, &registry).expect("valid config");
+        assert!(
+            matches!(&configs[0].provider, ProviderKind::Custom(name) if name == "acme_gateway")
+        );
+
+        let reason = invalid_reason(load_with_env(yaml, ConfigFormat::Yaml, &[]));
+        assert!(
+            reason.contains("handle `gateway`: unknown variant `acme_gateway`"),
+            "{reason}"
+        );
+        assert!(!reason.contains("tenant"), "{reason}");
+    }
+
+    #[test]
+    fn reports_parse_errors_with_format() {
+        let reason = invalid_reason(load_with_env("[[models]", ConfigFormat::Toml, &[]));
+        assert!(reason.starts_with("failed to parse TOML"), "{reason}");
+    }
+}
@@ -0,0 +1,144 @@
+//! Runtime registry of provider factories keyed by provider name.
+//!
+//! [`ProviderKind`] is generated from the built-in providers and cannot be extended by
+//! downstream crates. A [`ProviderRegistry`] maps additional names to factory functions so
+//! that config files can reference providers defined outside this crate: any `provider`
+//! string that matches no built-in kind deserializes into [`ProviderKind::Custom`] and is
+//! resolved here when the client is built.
+
+use std::collections::HashMap;
+
+use crate::error::LLMError;
+use crate::http::DynHttpTransport;
+use crate::provider::DynProvider;
+
+use super::{ModelConfig, ProviderKind, build_provider_from_config, unknown_provider_reason};
+
+/// Constructs a provider from a [`ModelConfig`], like the built-in `from_model_config`
+/// constructors.
+pub type ProviderFactory = fn(&ModelConfig, DynHttpTransport) -> Result<DynProvider, LLMError>;
+
+/// Provider factories keyed by the name used in [`ModelConfig::provider`].
+///
+/// Lookups consult the registered factories first and fall back to the built-in
+/// providers, so a factory registered under a built-in name such as `openai_chat` replaces
+/// it. An empty registry therefore behaves exactly like [`super::build_client_from_configs`].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "openai", feature = "reqwest-transport"))] {
+/// # use std::sync::Arc;
+/// # use kotoba_llm::config::{
+/// #     ModelConfig, ProviderKind, ProviderRegistry, build_client_from_configs_with_registry,
+/// # };
+/// # use kotoba_llm::error::LLMError;
+/// # use kotoba_llm::http::DynHttpTransport;
+/// # use kotoba_llm::http::reqwest::default_dyn_transport;
+/// # use kotoba_llm::provider::{DynProvider, openai_chat::OpenAiChatProvider};
+/// fn build_gateway(
+///     config: &ModelConfig,
+///     transport: DynHttpTransport,
+/// ) -> Result<DynProvider, LLMError> {
+///     // Reuse a built-in mapper against an in-house gateway.
+///     Ok(Arc::new(OpenAiChatProvider::from_model_config(config, transport)?))
+/// }
+///
+/// let registry = ProviderRegistry::new()
+///     .register("acme_gateway", build_gateway)
+///     .expect("unique name");
+/// let configs: Vec<ModelConfig> = serde_json::from_value(serde_json::json!([{
+///     "handle": "gateway",
+///     "provider": "acme_gateway",
+///     "credential": { "type": "bearer", "token": "secret" },
+///     "default_model": "acme-large",
+///     "base_url": "https://llm.acme.internal",
+/// }]))
+/// .expect("configs");
+/// assert!(matches!(&configs[0].provider, ProviderKind::Custom(name) if name == "acme_gateway"));
+///
+/// let transport = default_dyn_transport().expect("transport");
+/// let client = build_client_from_configs_with_registry(&configs, transport, &registry)
+///     .expect("client");
+/// assert_eq!(client.handles(), vec!["gateway".to_string()]);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProviderRegistry {
+    factories: HashMap<String, ProviderFactory>,
+}
+
+impl ProviderRegistry {
+    /// Creates a registry that only knows the built-in providers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `factory` under `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::InvalidConfig`] if a factory is already registered under `name`.
+    pub fn register(
+        mut self,
+        name: impl Into<String>,
+        factory: ProviderFactory,
+    ) -> Result<Self, LLMError> {
+        let name = name.into();
+        if self.factories.contains_key(&name) {
+            return Err(LLMError::InvalidConfig {
+                field: "provider".to_string(),
+                reason: format!("duplicate provider factory: {name}"),
+            });
+        }
+        self.factories.insert(name, factory);
+        Ok(self)
+    }
+
+    /// Returns `true` when `name` resolves to a registered factory or a built-in provider.
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name) || ProviderKind::list_provider_types().contains(&name)
+    }
+
+    /// Lists the built-in provider names followed by the other registered names in sorted
+    /// order.
+    pub fn provider_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = ProviderKind::list_provider_types()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let mut registered: Vec<String> = self
+            .factories
+            .keys()
+            .filter(|name| !names.contains(name))
+            .cloned()
+            .collect();
+        registered.sort_unstable();
+        names.extend(registered);
+        names
+    }
+
+    /// Builds the provider described by `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::InvalidConfig`] when `config.provider` names neither a registered
+    /// factory nor a built-in provider, or any error raised by the factory.
+    pub fn build(
+        &self,
+        config: &ModelConfig,
+        transport: DynHttpTransport,
+    ) -> Result<DynProvider, LLMError> {
+        let name = config.provider.as_str();
+        if let Some(factory) = self.factories.get(name) {
+            return factory(config, transport);
+        }
+        if config.provider.is_custom() {
+            return Err(LLMError::InvalidConfig {
+                field: "provider".to_string(),
+                reason: unknown_provider_reason(name, &self.provider_names().join(", ")),
+            });
+        }
+        build_provider_from_config(config, transport)
+    }
+}
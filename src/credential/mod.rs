@@ -0,0 +1,614 @@
+//! Credential providers consulted by every request.
+//!
+//! Providers no longer copy a secret into their own state. Instead they hold a
+//! [`DynCredentialProvider`] and ask it for an [`AccessToken`] right before sending each
+//! HTTP call, so rotated keys and short-lived OAuth tokens are picked up without rebuilding
+//! the client. [`CachedCredential`] adds caching and refresh-before-expiry on top of any
+//! source that is expensive to query.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "process")]
+use std::time::UNIX_EPOCH;
+use std::time::{Duration, Instant, SystemTime};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "process")]
+use serde_json::Value;
+
+use crate::error::LLMError;
+
+#[cfg(feature = "gemini-service-account")]
+mod service_account;
+#[cfg(feature = "bedrock")]
+mod sigv4;
+
+#[cfg(feature = "gemini-service-account")]
+pub use service_account::ServiceAccountCredential;
+#[cfg(feature = "bedrock")]
+pub(crate) use sigv4::uri_encode;
+#[cfg(feature = "bedrock")]
+pub use sigv4::{
+    AwsCredentials, AwsCredentialsProvider, DynAwsCredentialsProvider, SigV4Signer,
+    SourcedAwsCredentials,
+};
+
+/// Secret value handed to a provider, with its expiry when known.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AccessToken {
+    /// API key or bearer token placed in the provider's auth header.
+    pub value: String,
+    /// Wall-clock instant after which the token is no longer accepted.
+    pub expires_at: Option<SystemTime>,
+}
+
+impl AccessToken {
+    /// Creates a token that never expires.
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            expires_at: None,
+        }
+    }
+
+    /// Creates a token that expires at `expires_at`.
+    pub fn expiring(value: impl Into<String>, expires_at: SystemTime) -> Self {
+        Self {
+            value: value.into(),
+            expires_at: Some(expires_at),
+        }
+    }
+}
+
+impl std::fmt::Debug for AccessToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccessToken")
+            .field("value", &"<redacted>")
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+/// Source of the secret a provider authenticates with.
+///
+/// Implementations are queried once per request, so they should be cheap or wrapped in
+/// [`CachedCredential`].
+///
+/// # Examples
+///
+/// ```
+/// # use async_trait::async_trait;
+/// # use kotoba_llm::credential::{AccessToken, CredentialProvider};
+/// # use kotoba_llm::error::LLMError;
+/// struct VaultCredential;
+///
+/// #[async_trait]
+/// impl CredentialProvider for VaultCredential {
+///     async fn token(&self) -> Result<AccessToken, LLMError> {
+///         Ok(AccessToken::new("sk-from-vault"))
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait CredentialProvider: Send + Sync {
+    /// Returns the secret to use for the next request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Auth`] when the secret cannot be obtained.
+    async fn token(&self) -> Result<AccessToken, LLMError>;
+
+    /// Discards any cached secret, typically after the upstream API rejected it.
+    fn invalidate(&self) {}
+}
+
+/// Thread-safe handle to a credential provider.
+pub type DynCredentialProvider = Arc<dyn CredentialProvider>;
+
+/// Drops cached secrets after the upstream API answered `401 Unauthorized`, so the next
+/// request fetches a fresh one.
+#[cfg(any(
+    feature = "openai",
+    feature = "anthropic",
+    feature = "gemini",
+    feature = "bedrock",
+    feature = "cohere",
+    feature = "ollama"
+))]
+pub(crate) fn invalidate_on_unauthorized(credential: &DynCredentialProvider, status: u16) {
+    if status == 401 {
+        credential.invalidate();
+    }
+}
+
+/// Fixed secret known at construction time.
+#[derive(Debug, Clone)]
+pub struct StaticCredential {
+    token: AccessToken,
+}
+
+impl StaticCredential {
+    /// Wraps a secret that never changes.
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            token: AccessToken::new(value),
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for StaticCredential {
+    async fn token(&self) -> Result<AccessToken, LLMError> {
+        Ok(self.token.clone())
+    }
+}
+
+/// Reads the secret from an environment variable on every request.
+#[derive(Debug, Clone)]
+pub struct EnvCredential {
+    name: String,
+}
+
+impl EnvCredential {
+    /// Reads the variable called `name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for EnvCredential {
+    async fn token(&self) -> Result<AccessToken, LLMError> {
+        match std::env::var(&self.name) {
+            Ok(value) if !value.trim().is_empty() => Ok(AccessToken::new(value.trim())),
+            _ => Err(LLMError::Auth {
+                message: format!("environment variable {} is not set", self.name),
+            }),
+        }
+    }
+}
+
+/// Reads the secret from a file, trimming surrounding whitespace.
+#[derive(Debug, Clone)]
+pub struct FileCredential {
+    path: PathBuf,
+}
+
+impl FileCredential {
+    /// Reads the secret stored at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for FileCredential {
+    async fn token(&self) -> Result<AccessToken, LLMError> {
+        let path = self.path.clone();
+        let contents = tokio::task::spawn_blocking(move || std::fs::read_to_string(path))
+            .await
+            .map_err(|err| LLMError::Auth {
+                message: format!("credential file task failed: {err}"),
+            })?
+            .map_err(|err| LLMError::Auth {
+                message: format!(
+                    "failed to read credential file {}: {err}",
+                    self.path.display()
+                ),
+            })?;
+        let value = contents.trim();
+        if value.is_empty() {
+            return Err(LLMError::Auth {
+                message: format!("credential file {} is empty", self.path.display()),
+            });
+        }
+        Ok(AccessToken::new(value))
+    }
+}
+
+/// Runs an external command and uses its standard output as the secret.
+///
+/// Requires the `process` feature.
+///
+/// The output is either the bare secret or a JSON object with a `token` (or `access_token`)
+/// field plus an optional `expires_in` (seconds from now) or `expires_at` (Unix seconds).
+#[cfg(feature = "process")]
+#[derive(Debug, Clone)]
+pub struct CommandCredential {
+    program: String,
+    args: Vec<String>,
+}
+
+#[cfg(feature = "process")]
+impl CommandCredential {
+    /// Runs `program` without arguments.
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Sets the arguments passed to the program.
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+#[cfg(feature = "process")]
+#[async_trait]
+impl CredentialProvider for CommandCredential {
+    async fn token(&self) -> Result<AccessToken, LLMError> {
+        let output = tokio::process::Command::new(&self.program)
+            .args(&self.args)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|err| LLMError::Auth {
+                message: format!("failed to run credential command {}: {err}", self.program),
+            })?;
+        if !output.status.success() {
+            return Err(LLMError::Auth {
+                message: format!(
+                    "credential command {} exited with {}: {}",
+                    self.program,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            });
+        }
+        let stdout = String::from_utf8(output.stdout).map_err(|err| LLMError::Auth {
+            message: format!(
+                "credential command {} printed invalid UTF-8: {err}",
+                self.program
+            ),
+        })?;
+        parse_command_output(stdout.trim(), SystemTime::now()).ok_or_else(|| LLMError::Auth {
+            message: format!("credential command {} printed no token", self.program),
+        })
+    }
+}
+
+#[cfg(feature = "process")]
+fn parse_command_output(output: &str, now: SystemTime) -> Option<AccessToken> {
+    if output.is_empty() {
+        return None;
+    }
+    let Ok(Value::Object(object)) = serde_json::from_str::<Value>(output) else {
+        return Some(AccessToken::new(output));
+    };
+    let value = object
+        .get("token")
+        .or_else(|| object.get("access_token"))
+        .and_then(Value::as_str)?;
+    let expires_at = match (
+        object.get("expires_in").and_then(Value::as_u64),
+        object.get("expires_at").and_then(Value::as_u64),
+    ) {
+        (Some(seconds), _) => Some(now + Duration::from_secs(seconds)),
+        (None, Some(unix)) => Some(UNIX_EPOCH + Duration::from_secs(unix)),
+        (None, None) => None,
+    };
+    Some(AccessToken {
+        value: value.to_string(),
+        expires_at,
+    })
+}
+
+/// Caches the tokens of another provider and refreshes them before they expire.
+///
+/// A token is refreshed once it is within the refresh margin of its `expires_at`, or once
+/// the TTL has elapsed for tokens without an expiry. While one caller refreshes, concurrent
+/// callers keep receiving the cached token as long as it has not expired yet, so a slow
+/// refresh never stalls traffic.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use kotoba_llm::credential::{CachedCredential, CredentialProvider, FileCredential};
+/// let credential = CachedCredential::new(FileCredential::new("/run/secrets/api-key"))
+///     .with_ttl(Duration::from_secs(3600))
+///     .with_refresh_margin(Duration::from_secs(120));
+/// # let _ = credential;
+/// ```
+pub struct CachedCredential<P> {
+    inner: P,
+    ttl: Option<Duration>,
+    refresh_margin: Duration,
+    cached: Mutex<Option<CachedToken>>,
+    refresh: tokio::sync::Mutex<()>,
+}
+
+#[derive(Clone)]
+struct CachedToken {
+    token: AccessToken,
+    refresh_at: Option<Instant>,
+    expires_at: Option<Instant>,
+}
+
+impl<P: CredentialProvider> CachedCredential<P> {
+    /// Default time before expiry at which tokens are refreshed.
+    pub const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+    /// Wraps `inner`; tokens without an expiry are cached until invalidated.
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            ttl: None,
+            refresh_margin: Self::DEFAULT_REFRESH_MARGIN,
+            cached: Mutex::new(None),
+            refresh: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Re-fetches tokens that carry no expiry after `ttl`.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Refreshes expiring tokens this long before they expire.
+    pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    fn current(&self) -> Option<CachedToken> {
+        self.cached
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    fn store(&self, token: AccessToken) {
+        let now = Instant::now();
+        let remaining = token
+            .expires_at
+            .map(|at| at.duration_since(SystemTime::now()).unwrap_or_default());
+        let expires_at = remaining.map(|remaining| now + remaining);
+        let refresh_at = match remaining {
+            Some(remaining) => Some(now + remaining.saturating_sub(self.refresh_margin)),
+            None => self.ttl.map(|ttl| now + ttl),
+        };
+        *self
+            .cached
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(CachedToken {
+            token,
+            refresh_at,
+            expires_at,
+        });
+    }
+}
+
+impl CachedToken {
+    fn is_fresh(&self, now: Instant) -> bool {
+        self.refresh_at.is_none_or(|at| now < at)
+    }
+
+    fn is_usable(&self, now: Instant) -> bool {
+        self.expires_at.is_none_or(|at| now < at)
+    }
+}
+
+#[async_trait]
+impl<P: CredentialProvider> CredentialProvider for CachedCredential<P> {
+    async fn token(&self) -> Result<AccessToken, LLMError> {
+        if let Some(cached) = self.current() {
+            if cached.is_fresh(Instant::now()) {
+                return Ok(cached.token);
+            }
+        }
+        let _guard = match self.refresh.try_lock() {
+            Ok(guard) => guard,
+            Err(_) => {
+                if let Some(cached) = self.current() {
+                    if cached.is_usable(Instant::now()) {
+                        return Ok(cached.token);
+                    }
+                }
+                self.refresh.lock().await
+            }
+        };
+        if let Some(cached) = self.current() {
+            if cached.is_fresh(Instant::now()) {
+                return Ok(cached.token);
+            }
+        }
+        let token = self.inner.token().await?;
+        self.store(token.clone());
+        Ok(token)
+    }
+
+    fn invalidate(&self) {
+        self.cached
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        self.inner.invalidate();
+    }
+}
+
+/// Declarative description of where a rotating secret comes from.
+///
+/// Used by [`crate::config::Credential::Source`] so configuration files can reference
+/// secrets that change while the process is running.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CredentialSource {
+    /// Environment variable read on every request.
+    Env { name: String },
+    /// File read when the cached value is stale.
+    File { path: PathBuf },
+    /// Command whose standard output is the secret (see [`CommandCredential`]).
+    #[cfg(feature = "process")]
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl CredentialSource {
+    /// Default TTL applied to file and command sources when none is configured.
+    pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(300);
+
+    /// Builds the credential provider described by this source.
+    ///
+    /// File and command sources are wrapped in [`CachedCredential`] using `cache_ttl`, or
+    /// [`Self::DEFAULT_CACHE_TTL`] when it is `None`.
+    pub fn into_provider(self, cache_ttl: Option<Duration>) -> DynCredentialProvider {
+        let ttl = cache_ttl.unwrap_or(Self::DEFAULT_CACHE_TTL);
+        match self {
+            Self::Env { name } => Arc::new(EnvCredential::new(name)),
+            Self::File { path } => {
+                Arc::new(CachedCredential::new(FileCredential::new(path)).with_ttl(ttl))
+            }
+            #[cfg(feature = "process")]
+            Self::Command { program, args } => Arc::new(
+                CachedCredential::new(CommandCredential::new(program).with_args(args))
+                    .with_ttl(ttl),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Issues numbered tokens that expire after a fixed lifetime.
+    struct CountingCredential {
+        calls: AtomicUsize,
+        lifetime: Option<Duration>,
+    }
+
+    impl CountingCredential {
+        fn new(lifetime: Option<Duration>) -> Self {
+            Self {
+                calls: AtomicUsize::new(0),
+                lifetime,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl CredentialProvider for CountingCredential {
+        async fn token(&self) -> Result<AccessToken, LLMError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(AccessToken {
+                value: format!("token-{call}"),
+                expires_at: self.lifetime.map(|lifetime| SystemTime::now() + lifetime),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_credential_reuses_fresh_tokens() {
+        let cached =
+            CachedCredential::new(CountingCredential::new(Some(Duration::from_secs(3600))));
+        assert_eq!(cached.token().await.unwrap().value, "token-1");
+        assert_eq!(cached.token().await.unwrap().value, "token-1");
+        assert_eq!(cached.inner.calls.load(Ordering::SeqCst), 1);
+
+        cached.invalidate();
+        assert_eq!(cached.token().await.unwrap().value, "token-2");
+    }
+
+    #[tokio::test]
+    async fn cached_credential_refreshes_inside_the_margin() {
+        let cached = CachedCredential::new(CountingCredential::new(Some(Duration::from_secs(30))))
+            .with_refresh_margin(Duration::from_secs(60));
+        assert_eq!(cached.token().await.unwrap().value, "token-1");
+        assert_eq!(cached.token().await.unwrap().value, "token-2");
+    }
+
+    #[tokio::test]
+    async fn cached_credential_honours_ttl_without_expiry() {
+        let cached = CachedCredential::new(CountingCredential::new(None))
+            .with_ttl(Duration::from_millis(20));
+        assert_eq!(cached.token().await.unwrap().value, "token-1");
+        assert_eq!(cached.token().await.unwrap().value, "token-1");
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(cached.token().await.unwrap().value, "token-2");
+    }
+
+    #[cfg(feature = "process")]
+    #[test]
+    fn parse_command_output_accepts_plain_and_json() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        assert_eq!(
+            parse_command_output("sk-plain", now),
+            Some(AccessToken::new("sk-plain"))
+        );
+        assert_eq!(
+            parse_command_output(r#"{"access_token": "ya29", "expires_in": 60}"#, now),
+            Some(AccessToken::expiring(
+                "ya29",
+                UNIX_EPOCH + Duration::from_secs(1_060)
+            ))
+        );
+        assert_eq!(
+            parse_command_output(r#"{"token": "sk", "expires_at": 5000}"#, now),
+            Some(AccessToken::expiring(
+                "sk",
+                UNIX_EPOCH + Duration::from_secs(5_000)
+            ))
+        );
+        assert_eq!(parse_command_output(r#"{"unrelated": 1}"#, now), None);
+        assert_eq!(parse_command_output("", now), None);
+    }
+
+    #[tokio::test]
+    async fn file_credential_reads_trimmed_contents() {
+        let path = std::env::temp_dir().join(format!("kotoba-cred-{}.txt", std::process::id()));
+        std::fs::write(&path, "  sk-file\n").expect("write secret");
+        let token = FileCredential::new(&path).token().await;
+        std::fs::remove_file(&path).ok();
+        assert_eq!(token.unwrap().value, "sk-file");
+
+        let missing = FileCredential::new("/nonexistent/kotoba/secret")
+            .token()
+            .await;
+        assert!(matches!(missing, Err(LLMError::Auth { .. })));
+    }
+
+    #[cfg(all(unix, feature = "process"))]
+    #[tokio::test]
+    async fn command_credential_uses_stdout() {
+        let credential = CommandCredential::new("sh").with_args(["-c", "echo sk-command"]);
+        assert_eq!(credential.token().await.unwrap().value, "sk-command");
+
+        let failing = CommandCredential::new("sh").with_args(["-c", "echo denied >&2; exit 3"]);
+        match failing.token().await {
+            Err(LLMError::Auth { message }) => assert!(message.contains("denied"), "{message}"),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[cfg(feature = "process")]
+    #[test]
+    fn credential_source_deserializes_tagged_variants() {
+        let source: CredentialSource = serde_json::from_value(serde_json::json!({
+            "type": "command",
+            "program": "vault",
+            "args": ["read", "-field=key", "secret/openai"]
+        }))
+        .expect("command source");
+        assert!(matches!(source, CredentialSource::Command { ref args, .. } if args.len() == 3));
+
+        let source: CredentialSource =
+            serde_json::from_value(serde_json::json!({ "type": "env", "name": "OPENAI_KEY" }))
+                .expect("env source");
+        assert!(matches!(source, CredentialSource::Env { ref name } if name == "OPENAI_KEY"));
+    }
+}
@@ -0,0 +1,489 @@
+//! AWS Signature Version 4 request signing.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use ring::{digest, hmac};
+use url::Url;
+
+use super::DynCredentialProvider;
+use crate::error::LLMError;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Static AWS access key, optionally with an STS session token.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AwsCredentials {
+    /// Access key ID, e.g. `AKIA...`.
+    pub access_key_id: String,
+    /// Secret access key used to derive the signing key.
+    pub secret_access_key: String,
+    /// Session token for temporary credentials.
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// Creates long-lived credentials.
+    pub fn new(access_key_id: impl Into<String>, secret_access_key: impl Into<String>) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token: None,
+        }
+    }
+
+    /// Attaches the session token of temporary credentials.
+    pub fn with_session_token(mut self, session_token: impl Into<String>) -> Self {
+        self.session_token = Some(session_token.into());
+        self
+    }
+}
+
+impl std::fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"<redacted>")
+            .field(
+                "session_token",
+                &self.session_token.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+/// Source of the AWS credentials a request is signed with.
+///
+/// Like [`super::CredentialProvider`], it is queried before every request, so temporary
+/// STS credentials can rotate without rebuilding the client. [`AwsCredentials`] itself is
+/// a provider of fixed credentials.
+///
+/// # Examples
+///
+/// ```
+/// # use async_trait::async_trait;
+/// # use kotoba_llm::credential::{AwsCredentials, AwsCredentialsProvider};
+/// # use kotoba_llm::error::LLMError;
+/// struct AssumedRole;
+///
+/// #[async_trait]
+/// impl AwsCredentialsProvider for AssumedRole {
+///     async fn credentials(&self) -> Result<AwsCredentials, LLMError> {
+///         Ok(AwsCredentials::new("ASIAEXAMPLE", "secret").with_session_token("session"))
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait AwsCredentialsProvider: Send + Sync {
+    /// Returns the credentials to sign the next request with.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Auth`] when the credentials cannot be obtained.
+    async fn credentials(&self) -> Result<AwsCredentials, LLMError>;
+
+    /// Discards any cached credentials, typically after AWS reported them expired.
+    fn invalidate(&self) {}
+}
+
+/// Thread-safe handle to an AWS credentials provider.
+pub type DynAwsCredentialsProvider = Arc<dyn AwsCredentialsProvider>;
+
+#[async_trait]
+impl AwsCredentialsProvider for AwsCredentials {
+    async fn credentials(&self) -> Result<AwsCredentials, LLMError> {
+        Ok(self.clone())
+    }
+}
+
+/// AWS credentials whose parts are each read from a [`super::CredentialProvider`].
+///
+/// Lets the access key, secret key and session token come from environment variables,
+/// files or commands that are refreshed externally, e.g. a mounted STS credentials file.
+/// Invalidating drops the cached value of every part.
+///
+/// # Examples
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use kotoba_llm::credential::{EnvCredential, SourcedAwsCredentials};
+/// let credentials = SourcedAwsCredentials::new(
+///     Arc::new(EnvCredential::new("AWS_ACCESS_KEY_ID")),
+///     Arc::new(EnvCredential::new("AWS_SECRET_ACCESS_KEY")),
+/// )
+/// .with_session_token(Arc::new(EnvCredential::new("AWS_SESSION_TOKEN")));
+/// # let _ = credentials;
+/// ```
+pub struct SourcedAwsCredentials {
+    access_key_id: DynCredentialProvider,
+    secret_access_key: DynCredentialProvider,
+    session_token: Option<DynCredentialProvider>,
+}
+
+impl SourcedAwsCredentials {
+    /// Reads long-lived credentials from `access_key_id` and `secret_access_key`.
+    pub fn new(
+        access_key_id: DynCredentialProvider,
+        secret_access_key: DynCredentialProvider,
+    ) -> Self {
+        Self {
+            access_key_id,
+            secret_access_key,
+            session_token: None,
+        }
+    }
+
+    /// Also reads the session token of temporary credentials from `session_token`.
+    pub fn with_session_token(mut self, session_token: DynCredentialProvider) -> Self {
+        self.session_token = Some(session_token);
+        self
+    }
+}
+
+#[async_trait]
+impl AwsCredentialsProvider for SourcedAwsCredentials {
+    async fn credentials(&self) -> Result<AwsCredentials, LLMError> {
+        let mut credentials = AwsCredentials::new(
+            self.access_key_id.token().await?.value,
+            self.secret_access_key.token().await?.value,
+        );
+        if let Some(session_token) = &self.session_token {
+            credentials.session_token = Some(session_token.token().await?.value);
+        }
+        Ok(credentials)
+    }
+
+    fn invalidate(&self) {
+        self.access_key_id.invalidate();
+        self.secret_access_key.invalidate();
+        if let Some(session_token) = &self.session_token {
+            session_token.invalidate();
+        }
+    }
+}
+
+/// Signs HTTP requests for one AWS region and service.
+///
+/// The signed headers are `host` plus every `x-amz-*` header, so transports may add
+/// other headers (e.g. `content-length`) without invalidating the signature.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use std::time::SystemTime;
+/// # use kotoba_llm::credential::{AwsCredentials, SigV4Signer};
+/// let signer = SigV4Signer::new("us-east-1", "bedrock");
+/// let credentials = AwsCredentials::new("AKIDEXAMPLE", "secret");
+/// let mut headers = HashMap::new();
+/// signer
+///     .sign(
+///         &credentials,
+///         "POST",
+///         "https://bedrock-runtime.us-east-1.amazonaws.com/model/m/converse",
+///         &mut headers,
+///         b"{}",
+///         SystemTime::now(),
+///     )
+///     .expect("signed");
+/// assert!(headers["Authorization"].starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SigV4Signer {
+    region: String,
+    service: String,
+}
+
+impl SigV4Signer {
+    /// Creates a signer for `service` (e.g. `bedrock`) in `region`.
+    pub fn new(region: impl Into<String>, service: impl Into<String>) -> Self {
+        Self {
+            region: region.into(),
+            service: service.into(),
+        }
+    }
+
+    /// Region whose signing key is derived.
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    /// Adds `X-Amz-Date`, `X-Amz-Security-Token` (for temporary credentials) and
+    /// `Authorization` to `headers`.
+    ///
+    /// `url` must be exactly the URL that is sent, with its path already percent-encoded,
+    /// and `body` exactly the bytes that are sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] when `url` cannot be parsed or has no host.
+    pub fn sign(
+        &self,
+        credentials: &AwsCredentials,
+        method: &str,
+        url: &str,
+        headers: &mut HashMap<String, String>,
+        body: &[u8],
+        time: SystemTime,
+    ) -> Result<(), LLMError> {
+        let parsed = Url::parse(url).map_err(|err| LLMError::Validation {
+            message: format!("cannot sign invalid URL {url}: {err}"),
+        })?;
+        let host = match (parsed.host_str(), parsed.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => {
+                return Err(LLMError::Validation {
+                    message: format!("cannot sign URL without host: {url}"),
+                });
+            }
+        };
+        let (date, timestamp) = format_time(time);
+
+        headers.retain(|name, _| {
+            !name.eq_ignore_ascii_case("authorization")
+                && !name.eq_ignore_ascii_case("x-amz-date")
+                && !name.eq_ignore_ascii_case("x-amz-security-token")
+        });
+        headers.insert("X-Amz-Date".to_string(), timestamp.clone());
+        if let Some(token) = &credentials.session_token {
+            headers.insert("X-Amz-Security-Token".to_string(), token.clone());
+        }
+
+        let mut signed: Vec<(String, String)> = headers
+            .iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.trim().to_string()))
+            .filter(|(name, _)| name.starts_with("x-amz-"))
+            .collect();
+        signed.push(("host".to_string(), host));
+        signed.sort();
+        let canonical_headers: String = signed
+            .iter()
+            .map(|(name, value)| format!("{name}:{value}\n"))
+            .collect();
+        let signed_headers = signed
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let canonical_request = format!(
+            "{method}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{}",
+            canonical_uri(parsed.path()),
+            canonical_query(&parsed),
+            hex(digest::digest(&digest::SHA256, body).as_ref())
+        );
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+        let string_to_sign = format!(
+            "{ALGORITHM}\n{timestamp}\n{scope}\n{}",
+            hex(digest::digest(&digest::SHA256, canonical_request.as_bytes()).as_ref())
+        );
+
+        let mut key = hmac_sha256(
+            format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.region.as_str(), self.service.as_str(), "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        headers.insert(
+            "Authorization".to_string(),
+            format!(
+                "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                credentials.access_key_id
+            ),
+        );
+        Ok(())
+    }
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+pub(crate) fn uri_encode(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Encodes each path segment once more, as SigV4 requires for every service except S3.
+fn canonical_uri(path: &str) -> String {
+    if path.is_empty() || path == "/" {
+        return "/".to_string();
+    }
+    path.split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn canonical_query(url: &Url) -> String {
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| (uri_encode(&key), uri_encode(&value)))
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data)
+        .as_ref()
+        .to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Formats `time` as the `YYYYMMDD` scope date and `YYYYMMDDTHHMMSSZ` timestamp.
+fn format_time(time: SystemTime) -> (String, String) {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, rem) = (seconds / 86_400, seconds % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+    let date = format!("{year:04}{month:02}{day:02}");
+    let timestamp = format!(
+        "{date}T{:02}{:02}{:02}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    );
+    (date, timestamp)
+}
+
+/// Converts days since 1970-01-01 into a proleptic Gregorian date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// 2015-08-30T12:36:00Z, the instant used by the AWS SigV4 test suite.
+    fn suite_time() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_440_938_160)
+    }
+
+    fn suite_credentials() -> AwsCredentials {
+        AwsCredentials::new("AKIDEXAMPLE", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY")
+    }
+
+    fn sign(method: &str, url: &str) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        SigV4Signer::new("us-east-1", "service")
+            .sign(
+                &suite_credentials(),
+                method,
+                url,
+                &mut headers,
+                b"",
+                suite_time(),
+            )
+            .expect("signed");
+        headers
+    }
+
+    #[test]
+    fn matches_aws_suite_get_vanilla() {
+        let headers = sign("GET", "https://example.amazonaws.com/");
+        assert_eq!(headers["X-Amz-Date"], "20150830T123600Z");
+        assert_eq!(
+            headers["Authorization"],
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn matches_aws_suite_post_vanilla() {
+        let headers = sign("POST", "https://example.amazonaws.com/");
+        assert!(
+            headers["Authorization"].ends_with(
+                "Signature=5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"
+            ),
+            "{}",
+            headers["Authorization"]
+        );
+    }
+
+    #[test]
+    fn matches_aws_suite_query_order() {
+        let headers = sign(
+            "GET",
+            "https://example.amazonaws.com/?Param2=value2&Param1=value1",
+        );
+        assert!(
+            headers["Authorization"].ends_with(
+                "Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+            ),
+            "{}",
+            headers["Authorization"]
+        );
+    }
+
+    #[test]
+    fn session_tokens_are_signed() {
+        let mut headers = HashMap::new();
+        SigV4Signer::new("us-west-2", "bedrock")
+            .sign(
+                &suite_credentials().with_session_token("session"),
+                "POST",
+                "https://bedrock-runtime.us-west-2.amazonaws.com/model/x/converse",
+                &mut headers,
+                b"{}",
+                suite_time(),
+            )
+            .expect("signed");
+        assert_eq!(headers["X-Amz-Security-Token"], "session");
+        assert!(
+            headers["Authorization"]
+                .contains("SignedHeaders=host;x-amz-date;x-amz-security-token,"),
+            "{}",
+            headers["Authorization"]
+        );
+    }
+
+    #[test]
+    fn canonical_uri_double_encodes_segments() {
+        assert_eq!(
+            canonical_uri("/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse"),
+            "/model/anthropic.claude-3-haiku-20240307-v1%253A0/converse"
+        );
+        assert_eq!(canonical_uri(""), "/");
+    }
+
+    #[test]
+    fn civil_dates_cover_leap_years() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(16_677), (2015, 8, 30));
+    }
+}
@@ -2,6 +2,9 @@ use std::time::Duration;
 
 use thiserror::Error;
 
+use crate::preflight::RequestViolation;
+use crate::types::SafetyInfo;
+
 /// Aggregates every failure mode exposed by the unified LLM client.
 ///
 /// Callers can downcast on the specific variant to decide whether to retry, fall back
@@ -35,6 +38,21 @@ pub enum LLMError {
     /// Signals validation failures in the request payload.
     #[error("invalid request: {message}")]
     Validation { message: String },
+    /// Lists every problem found by pre-flight validation; nothing was sent upstream.
+    #[error("request failed pre-flight validation: {}", join_violations(.violations))]
+    Preflight {
+        /// Violations in the order they were detected.
+        violations: Vec<RequestViolation>,
+    },
+    /// Reports a prompt the provider refused to process before generating anything,
+    /// such as a Gemini `promptFeedback.blockReason` or an Azure content filter hit.
+    #[error("prompt blocked by {provider}: {}", describe_block(.safety))]
+    PromptBlocked {
+        /// Name of the provider, such as `google_gemini`.
+        provider: &'static str,
+        /// Block reason and the ratings that triggered it.
+        safety: SafetyInfo,
+    },
     /// Declares that a capability is not supported by the selected provider.
     #[error("feature unsupported: {feature}")]
     UnsupportedFeature { feature: &'static str },
@@ -82,6 +100,35 @@ pub enum LLMError {
     Unknown { message: String },
 }
 
+fn join_violations(violations: &[RequestViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn describe_block(safety: &SafetyInfo) -> String {
+    let mut text = safety
+        .message
+        .clone()
+        .or_else(|| safety.block_reason.clone())
+        .unwrap_or_else(|| "content policy violation".to_string());
+    let categories: Vec<String> = safety
+        .ratings
+        .iter()
+        .filter(|rating| rating.blocked)
+        .map(|rating| match &rating.severity {
+            Some(severity) => format!("{}: {}", rating.category, severity.as_str()),
+            None => rating.category.clone(),
+        })
+        .collect();
+    if !categories.is_empty() {
+        text.push_str(&format!(" [{}]", categories.join(", ")));
+    }
+    text
+}
+
 impl LLMError {
     /// Creates an [`LLMError::Transport`] from a textual description.
     ///
@@ -129,6 +176,14 @@ impl LLMError {
 }
 
 /// Returns `true` when an error code or message suggests a context/window overflow.
+#[cfg(any(
+    feature = "openai",
+    feature = "anthropic",
+    feature = "gemini",
+    feature = "bedrock",
+    feature = "cohere",
+    feature = "ollama"
+))]
 pub(crate) fn looks_like_token_limit_error(code_hint: Option<&str>, message: &str) -> bool {
     if let Some(code) = code_hint {
         let lower = code.to_ascii_lowercase();
@@ -145,18 +200,28 @@ pub(crate) fn looks_like_token_limit_error(code_hint: Option<&str>, message: &st
     }
 
     let lower_message = message.to_ascii_lowercase();
-    const HINTS: [&str; 6] = [
+    const HINTS: [&str; 8] = [
         "context length",
         "context window",
         "token limit",
         "maximum output tokens",
         "max output tokens",
         "prompt is too long",
+        "input is too long",
+        "too many tokens",
     ];
     HINTS.iter().any(|needle| lower_message.contains(needle))
 }
 
 /// Attempts to extract a model identifier from an error payload.
+#[cfg(any(
+    feature = "openai",
+    feature = "anthropic",
+    feature = "gemini",
+    feature = "bedrock",
+    feature = "cohere",
+    feature = "ollama"
+))]
 pub(crate) fn extract_model_identifier(message: &str) -> Option<String> {
     for delimiter in ['`', '"', '\''] {
         if let Some(value) = between_delimiters(message, delimiter) {
@@ -168,6 +233,14 @@ pub(crate) fn extract_model_identifier(message: &str) -> Option<String> {
     None
 }
 
+#[cfg(any(
+    feature = "openai",
+    feature = "anthropic",
+    feature = "gemini",
+    feature = "bedrock",
+    feature = "cohere",
+    feature = "ollama"
+))]
 fn between_delimiters(message: &str, delimiter: char) -> Option<String> {
     for (start, ch) in message.char_indices() {
         if ch == delimiter {
@@ -199,64 +199,6 @@ pub trait HttpTransport: Send + Sync {
 /// Thread-safe handle to a transport implementation.
 pub type DynHttpTransport = Arc<dyn HttpTransport>;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use async_trait::async_trait;
-    use serde::ser;
-
-    /// Transport that panics if `send` or `send_stream` are invoked.
-    ///
-    /// The helper ensures serialization failures are surfaced before issuing real
-    /// network requests.
-    struct PanicTransport;
-
-    #[async_trait]
-    impl HttpTransport for PanicTransport {
-        async fn send(&self, _request: HttpRequest) -> Result<HttpResponse, LLMError> {
-            panic!("send should not be called");
-        }
-
-        async fn send_stream(&self, _request: HttpRequest) -> Result<HttpStreamResponse, LLMError> {
-            panic!("send_stream should not be called");
-        }
-    }
-
-    /// Body type that intentionally fails serialization to trigger validation errors.
-    struct NonSerializableBody;
-
-    impl Serialize for NonSerializableBody {
-        fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
-        where
-            S: serde::Serializer,
-        {
-            Err(ser::Error::custom(
-                "intentional serialization failure for test",
-            ))
-        }
-    }
-
-    #[tokio::test]
-    async fn post_json_with_headers_returns_validation_on_serde_error() {
-        let transport = PanicTransport;
-        let body = NonSerializableBody;
-        let headers = HashMap::new();
-
-        let result = post_json_with_headers(&transport, "http://example.com", headers, &body).await;
-
-        match result {
-            Err(LLMError::Validation { message }) => {
-                assert!(
-                    message.contains("failed to serialize request"),
-                    "unexpected validation message: {message}"
-                );
-            }
-            Ok(_) => panic!("expected validation error for non serializable body"),
-            other => panic!("unexpected error type: {other:?}"),
-        }
-    }
-}
-
 /// Serializes a body to JSON, attaches headers, and issues a POST request.
 ///
 /// This helper centralizes JSON serialization so each provider can reuse the same logic
@@ -377,4 +319,49 @@ pub async fn post_json_stream_with_headers<T: Serialize>(
     transport.send_stream(request).await
 }
 
+#[cfg(feature = "reqwest-transport")]
 pub mod reqwest;
+
+#[cfg(test)]
+pub(crate) mod test_support;
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::PanicTransport;
+    use super::*;
+    use serde::ser;
+
+    /// Body type that intentionally fails serialization to trigger validation errors.
+    struct NonSerializableBody;
+
+    impl Serialize for NonSerializableBody {
+        fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            Err(ser::Error::custom(
+                "intentional serialization failure for test",
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn post_json_with_headers_returns_validation_on_serde_error() {
+        let transport = PanicTransport;
+        let body = NonSerializableBody;
+        let headers = HashMap::new();
+
+        let result = post_json_with_headers(&transport, "http://example.com", headers, &body).await;
+
+        match result {
+            Err(LLMError::Validation { message }) => {
+                assert!(
+                    message.contains("failed to serialize request"),
+                    "unexpected validation message: {message}"
+                );
+            }
+            Ok(_) => panic!("expected validation error for non serializable body"),
+            other => panic!("unexpected error type: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! Transports shared by tests that must not reach the network.
+
+use async_trait::async_trait;
+
+use super::{HttpRequest, HttpResponse, HttpStreamResponse, HttpTransport};
+use crate::error::LLMError;
+
+/// Transport that panics if `send` or `send_stream` are invoked.
+///
+/// Tests that only build or prepare requests use it to prove nothing is dispatched.
+pub(crate) struct PanicTransport;
+
+#[async_trait]
+impl HttpTransport for PanicTransport {
+    async fn send(&self, _request: HttpRequest) -> Result<HttpResponse, LLMError> {
+        panic!("send should not be called");
+    }
+
+    async fn send_stream(&self, _request: HttpRequest) -> Result<HttpStreamResponse, LLMError> {
+        panic!("send_stream should not be called");
+    }
+}
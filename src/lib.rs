@@ -3,16 +3,28 @@
 //! This crate defines shared types, transport abstractions, and provider implementations
 //! so downstream applications can route chat requests to OpenAI, Anthropic, Google Gemini,
 //! or any additional vendor through one cohesive API surface.
+//!
+//! Each provider module sits behind a cargo feature (`openai`, `anthropic`, `gemini`,
+//! `azure`, `bedrock`, `cohere`, `ollama`, `openai-compatible`), all enabled by default. The
+//! reqwest-based transport in `http::reqwest` is behind `reqwest-transport`; crates that
+//! implement [`http::HttpTransport`] themselves can disable it.
 
+pub mod adapt;
+pub mod catalog;
 pub mod client;
+pub mod computer;
 pub mod config;
+pub mod credential;
 pub mod error;
 pub mod http;
+pub mod mcp;
+pub mod preflight;
 pub mod provider;
 pub mod stream;
 pub mod types;
 
-pub use client::{LLMClient, LLMClientLike};
+pub use catalog::{ModelCatalog, ModelInfo, ReasoningMode};
+pub use client::{LLMClient, LLMClientLike, ReloadableClient};
 pub use error::LLMError;
-pub use provider::{ChatStream, LLMProvider, RetryConfig, RetryableLLMProvider};
+pub use provider::{ChatStream, LLMProvider, PreparedRequest, RetryConfig, RetryableLLMProvider};
 pub use types::*;
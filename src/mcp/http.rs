@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use serde_json::Value;
+
+use super::{McpTransport, PROVIDER, reply_to_server};
+use crate::error::LLMError;
+use crate::http::{DynHttpTransport, HttpBodyStream, HttpRequest};
+use crate::stream::{StreamDecoder, StreamEvent};
+
+/// MCP streamable HTTP transport: every message is POSTed to one endpoint URL.
+///
+/// The server answers with a JSON body or a `text/event-stream` that ends with the
+/// response. The `Mcp-Session-Id` assigned during `initialize` and the negotiated
+/// `MCP-Protocol-Version` are sent with every later message.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use kotoba_llm::http::DynHttpTransport;
+/// # use kotoba_llm::mcp::{McpClient, StreamableHttpTransport};
+/// # async fn run(http: DynHttpTransport) -> Result<(), kotoba_llm::LLMError> {
+/// let transport = StreamableHttpTransport::new(http, "https://mcp.example.com/mcp")
+///     .with_header("Authorization", "Bearer token");
+/// let client = McpClient::connect("docs", Arc::new(transport)).await?;
+/// let tools = client.list_tools().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct StreamableHttpTransport {
+    http: DynHttpTransport,
+    url: String,
+    headers: HashMap<String, String>,
+    session: Mutex<HttpSession>,
+}
+
+#[derive(Default)]
+struct HttpSession {
+    id: Option<String>,
+    protocol_version: Option<String>,
+}
+
+impl StreamableHttpTransport {
+    /// Creates a transport posting to the MCP endpoint at `url`.
+    pub fn new(http: DynHttpTransport, url: impl Into<String>) -> Self {
+        Self {
+            http,
+            url: url.into(),
+            headers: HashMap::new(),
+            session: Mutex::new(HttpSession::default()),
+        }
+    }
+
+    /// Adds a header, such as `Authorization`, to every request.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Session identifier assigned by the server, if any.
+    pub fn session_id(&self) -> Option<String> {
+        self.session().id.clone()
+    }
+
+    fn session(&self) -> std::sync::MutexGuard<'_, HttpSession> {
+        self.session
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn build(&self, message: &Value) -> HttpRequest {
+        let mut headers = self.headers.clone();
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        headers.insert(
+            "Accept".to_string(),
+            "application/json, text/event-stream".to_string(),
+        );
+        let session = self.session();
+        if let Some(id) = &session.id {
+            headers.insert("Mcp-Session-Id".to_string(), id.clone());
+        }
+        if let Some(version) = &session.protocol_version {
+            headers.insert("MCP-Protocol-Version".to_string(), version.clone());
+        }
+        HttpRequest::post_json(self.url.clone(), message.to_string().into_bytes())
+            .with_headers(headers)
+    }
+
+    /// Answers a server request that arrived on a response stream with a separate POST.
+    async fn reply(&self, id: &Value, method: &Value) -> Result<(), LLMError> {
+        self.notify(reply_to_server(id, method)).await
+    }
+}
+
+fn header<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn parse(body: &str) -> Result<Value, LLMError> {
+    serde_json::from_str(body)
+        .map_err(|err| LLMError::provider(PROVIDER, format!("failed to parse MCP message: {err}")))
+}
+
+async fn collect(mut body: HttpBodyStream) -> Result<Vec<u8>, LLMError> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.next().await {
+        bytes.extend_from_slice(&chunk?);
+    }
+    Ok(bytes)
+}
+
+fn status_error(status: u16, body: &[u8]) -> LLMError {
+    LLMError::transport(format!(
+        "MCP server returned HTTP {status}: {}",
+        String::from_utf8_lossy(body)
+    ))
+}
+
+#[async_trait]
+impl McpTransport for StreamableHttpTransport {
+    async fn request(&self, request: Value) -> Result<Value, LLMError> {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let response = self.http.send_stream(self.build(&request)).await?;
+        if !(200..300).contains(&response.status) {
+            return Err(status_error(
+                response.status,
+                &collect(response.body).await?,
+            ));
+        }
+        if let Some(session_id) = header(&response.headers, "mcp-session-id") {
+            self.session().id = Some(session_id.to_string());
+        }
+        let is_event_stream = header(&response.headers, "content-type")
+            .is_some_and(|content_type| content_type.starts_with("text/event-stream"));
+
+        let message = if is_event_stream {
+            let mut events = StreamDecoder::new(response.body, PROVIDER);
+            loop {
+                let data = match events.next().await {
+                    Some(Ok(StreamEvent::Data(data))) => data,
+                    Some(Ok(StreamEvent::Done)) => continue,
+                    Some(Err(err)) => return Err(err),
+                    None => {
+                        return Err(LLMError::transport(
+                            "MCP event stream ended before the response",
+                        ));
+                    }
+                };
+                let message = parse(&data)?;
+                match (message.get("id"), message.get("method")) {
+                    (Some(server_id), Some(method)) => self.reply(server_id, method).await?,
+                    (Some(response_id), None) if *response_id == id => break message,
+                    _ => {}
+                }
+            }
+        } else {
+            parse(&String::from_utf8_lossy(&collect(response.body).await?))?
+        };
+
+        if request.get("method").and_then(Value::as_str) == Some("initialize") {
+            self.session().protocol_version = message
+                .pointer("/result/protocolVersion")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+        }
+        Ok(message)
+    }
+
+    async fn notify(&self, notification: Value) -> Result<(), LLMError> {
+        let response = self.http.send(self.build(&notification)).await?;
+        if !(200..300).contains(&response.status) {
+            return Err(status_error(response.status, &response.body));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use futures_util::stream;
+    use serde_json::json;
+
+    use crate::http::{HttpResponse, HttpStreamResponse, HttpTransport};
+
+    struct RecordingTransport {
+        requests: Mutex<Vec<HttpRequest>>,
+        responses: Mutex<Vec<(HashMap<String, String>, String)>>,
+    }
+
+    impl RecordingTransport {
+        fn new(responses: Vec<(&str, &str)>) -> Arc<Self> {
+            Arc::new(Self {
+                requests: Mutex::new(Vec::new()),
+                responses: Mutex::new(
+                    responses
+                        .into_iter()
+                        .map(|(content_type, body)| {
+                            let mut headers = HashMap::from([(
+                                "content-type".to_string(),
+                                content_type.to_string(),
+                            )]);
+                            headers.insert("mcp-session-id".to_string(), "session-1".to_string());
+                            (headers, body.to_string())
+                        })
+                        .collect(),
+                ),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl HttpTransport for RecordingTransport {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, LLMError> {
+            self.requests.lock().unwrap().push(request);
+            Ok(HttpResponse {
+                status: 202,
+                headers: HashMap::new(),
+                body: Vec::new(),
+            })
+        }
+
+        async fn send_stream(&self, request: HttpRequest) -> Result<HttpStreamResponse, LLMError> {
+            self.requests.lock().unwrap().push(request);
+            let (headers, body) = self.responses.lock().unwrap().remove(0);
+            Ok(HttpStreamResponse {
+                status: 200,
+                headers,
+                body: Box::pin(stream::iter(vec![Ok(body.into_bytes())])),
+            })
+        }
+    }
+
+    fn body(request: &HttpRequest) -> Value {
+        serde_json::from_slice(request.body.as_deref().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn reads_json_response_and_keeps_session() {
+        let http = RecordingTransport::new(vec![
+            (
+                "application/json",
+                r#"{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-06-18"}}"#,
+            ),
+            (
+                "application/json",
+                r#"{"jsonrpc":"2.0","id":2,"result":{"tools":[]}}"#,
+            ),
+        ]);
+        let transport = StreamableHttpTransport::new(http.clone(), "https://mcp.test/mcp")
+            .with_header("Authorization", "Bearer secret");
+
+        let initialize = transport
+            .request(json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }))
+            .await
+            .expect("initialize");
+        assert_eq!(initialize["result"]["protocolVersion"], "2025-06-18");
+        transport
+            .request(json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list", "params": {} }))
+            .await
+            .expect("tools/list");
+
+        assert_eq!(transport.session_id().as_deref(), Some("session-1"));
+        let requests = http.requests.lock().unwrap();
+        assert_eq!(
+            requests[0].headers.get("Accept").map(String::as_str),
+            Some("application/json, text/event-stream")
+        );
+        assert!(!requests[0].headers.contains_key("Mcp-Session-Id"));
+        assert_eq!(
+            requests[1]
+                .headers
+                .get("Mcp-Session-Id")
+                .map(String::as_str),
+            Some("session-1")
+        );
+        assert_eq!(
+            requests[1]
+                .headers
+                .get("MCP-Protocol-Version")
+                .map(String::as_str),
+            Some("2025-06-18")
+        );
+        assert_eq!(
+            requests[1].headers.get("Authorization").map(String::as_str),
+            Some("Bearer secret")
+        );
+    }
+
+    #[tokio::test]
+    async fn reads_response_from_event_stream_and_answers_ping() {
+        let http = RecordingTransport::new(vec![(
+            "text/event-stream",
+            concat!(
+                "data: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\",\"params\":{}}\n\n",
+                "data: {\"jsonrpc\":\"2.0\",\"id\":\"srv-1\",\"method\":\"ping\"}\n\n",
+                "data: {\"jsonrpc\":\"2.0\",\"id\":7,\"result\":{\"content\":[]}}\n\n",
+            ),
+        )]);
+        let transport = StreamableHttpTransport::new(http.clone(), "https://mcp.test/mcp");
+
+        let response = transport
+            .request(json!({ "jsonrpc": "2.0", "id": 7, "method": "tools/call", "params": {} }))
+            .await
+            .expect("tools/call");
+
+        assert_eq!(response["result"], json!({ "content": [] }));
+        let requests = http.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            body(&requests[1]),
+            json!({ "jsonrpc": "2.0", "id": "srv-1", "result": {} })
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Model Context Protocol client for exposing MCP server tools to models.
+//!
+//! [`McpClient`] performs the `initialize` handshake with one server over a
+//! [`McpTransport`] ([`StdioTransport`] for local processes, [`StreamableHttpTransport`]
+//! for remote servers), lists its tools and calls them. [`McpToolRouter`] aggregates
+//! several clients: its [`McpToolRouter::tools`] go into [`crate::types::ChatRequest::tools`]
+//! and [`McpToolRouter::call`] answers the model's [`crate::types::ToolCall`]s with
+//! [`crate::types::ToolResult`]s.
+
+mod http;
+mod router;
+#[cfg(feature = "process")]
+mod stdio;
+mod types;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::error::LLMError;
+use crate::http::DynHttpTransport;
+
+pub use http::StreamableHttpTransport;
+pub use router::McpToolRouter;
+#[cfg(feature = "process")]
+pub use stdio::StdioTransport;
+pub use types::{
+    CallToolResult, McpContent, McpImplementation, McpResourceContents, McpServerInfo, McpTool,
+};
+
+/// Protocol revision requested during `initialize`.
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+const PROVIDER: &str = "mcp";
+
+/// Carries JSON-RPC messages between the client and one MCP server.
+#[async_trait]
+pub trait McpTransport: Send + Sync {
+    /// Sends a JSON-RPC request and resolves with the response carrying the same `id`.
+    ///
+    /// The response is returned as-is; a JSON-RPC `error` member is not an `Err`.
+    async fn request(&self, request: Value) -> Result<Value, LLMError>;
+
+    /// Sends a JSON-RPC notification, which has no response.
+    async fn notify(&self, notification: Value) -> Result<(), LLMError>;
+}
+
+/// Answers a request the server sent to the client: `ping` succeeds, the rest are
+/// rejected because the client offers no capabilities.
+fn reply_to_server(id: &Value, method: &Value) -> Value {
+    if method == "ping" {
+        json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+    } else {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32601, "message": format!("method not supported by client: {method}") }
+        })
+    }
+}
+
+/// Thread-safe handle to an MCP transport.
+pub type DynMcpTransport = Arc<dyn McpTransport>;
+
+/// Connection to one initialized MCP server.
+pub struct McpClient {
+    name: String,
+    transport: DynMcpTransport,
+    next_id: AtomicU64,
+    server: McpServerInfo,
+}
+
+impl McpClient {
+    /// Initializes the server behind `transport`; `name` labels it in errors and routing.
+    ///
+    /// # Errors
+    ///
+    /// Returns the transport error, or [`LLMError::Provider`] when the server rejects
+    /// `initialize`.
+    pub async fn connect(
+        name: impl Into<String>,
+        transport: DynMcpTransport,
+    ) -> Result<Self, LLMError> {
+        let mut client = Self {
+            name: name.into(),
+            transport,
+            next_id: AtomicU64::new(1),
+            server: McpServerInfo::default(),
+        };
+        let result = client
+            .call(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                }),
+            )
+            .await?;
+        client.server = client.decode("initialize", result)?;
+        client
+            .transport
+            .notify(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .await?;
+        Ok(client)
+    }
+
+    /// Spawns `command` as a stdio MCP server and initializes it.
+    ///
+    /// The process is killed when the client is dropped. Requires the `process` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Transport`] when the process cannot be started, plus the
+    /// errors of [`McpClient::connect`].
+    #[cfg(feature = "process")]
+    pub async fn spawn(
+        name: impl Into<String>,
+        command: tokio::process::Command,
+    ) -> Result<Self, LLMError> {
+        let transport = StdioTransport::spawn(command)?;
+        Self::connect(name, Arc::new(transport)).await
+    }
+
+    /// Connects to the server described by `config`; `http` carries streamable HTTP traffic.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`McpClient::spawn`] and [`McpClient::connect`].
+    pub async fn connect_config(
+        config: &McpServerConfig,
+        http: DynHttpTransport,
+    ) -> Result<Self, LLMError> {
+        match &config.transport {
+            #[cfg(feature = "process")]
+            McpServerTransport::Stdio { command, args, env } => {
+                let mut process = tokio::process::Command::new(command);
+                process.args(args).envs(env);
+                Self::spawn(config.name.clone(), process).await
+            }
+            #[cfg(not(feature = "process"))]
+            McpServerTransport::Stdio { .. } => Err(LLMError::InvalidConfig {
+                field: format!("mcp_servers.{}", config.name),
+                reason: "stdio MCP servers require the `process` cargo feature of kotoba-llm"
+                    .to_string(),
+            }),
+            McpServerTransport::Http { url, headers } => {
+                let transport = headers.iter().fold(
+                    StreamableHttpTransport::new(http, url.clone()),
+                    |transport, (name, value)| transport.with_header(name.clone(), value.clone()),
+                );
+                Self::connect(config.name.clone(), Arc::new(transport)).await
+            }
+        }
+    }
+
+    /// Name given to the server when connecting.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Details reported by the server during `initialize`.
+    pub fn server_info(&self) -> &McpServerInfo {
+        &self.server
+    }
+
+    /// Lists every tool the server offers, following `nextCursor` pagination.
+    ///
+    /// # Errors
+    ///
+    /// Returns the transport error, or [`LLMError::Provider`] for JSON-RPC errors and
+    /// malformed results.
+    pub async fn list_tools(&self) -> Result<Vec<McpTool>, LLMError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ListToolsResult {
+            tools: Vec<McpTool>,
+            next_cursor: Option<String>,
+        }
+
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let result = self.call("tools/list", params).await?;
+            let page: ListToolsResult = self.decode("tools/list", result)?;
+            tools.extend(page.tools);
+            match page.next_cursor {
+                Some(next) if !next.is_empty() => cursor = Some(next),
+                _ => return Ok(tools),
+            }
+        }
+    }
+
+    /// Calls the tool `name` with a JSON object of arguments.
+    ///
+    /// A tool that fails reports [`CallToolResult::is_error`]; `Err` is reserved for
+    /// protocol failures such as unknown tools.
+    ///
+    /// # Errors
+    ///
+    /// Returns the transport error, or [`LLMError::Provider`] for JSON-RPC errors and
+    /// malformed results.
+    pub async fn call_tool(
+        &self,
+        name: &str,
+        arguments: Value,
+    ) -> Result<CallToolResult, LLMError> {
+        let result = self
+            .call(
+                "tools/call",
+                json!({ "name": name, "arguments": arguments }),
+            )
+            .await?;
+        self.decode("tools/call", result)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, LLMError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let response = self
+            .transport
+            .request(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await?;
+        if let Some(error) = response.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error");
+            let code = error
+                .get("code")
+                .and_then(Value::as_i64)
+                .unwrap_or_default();
+            return Err(LLMError::provider(
+                PROVIDER,
+                format!("{}: {method} failed: {message} (code {code})", self.name),
+            ));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    fn decode<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        result: Value,
+    ) -> Result<T, LLMError> {
+        serde_json::from_value(result).map_err(|err| {
+            LLMError::provider(
+                PROVIDER,
+                format!("{}: invalid {method} result: {err}", self.name),
+            )
+        })
+    }
+}
+
+/// MCP server entry, typically loaded from configuration.
+///
+/// # Examples
+///
+/// ```
+/// # use kotoba_llm::mcp::{McpServerConfig, McpServerTransport};
+/// let config: McpServerConfig = serde_json::from_value(serde_json::json!({
+///     "name": "files",
+///     "command": "npx",
+///     "args": ["-y", "@modelcontextprotocol/server-filesystem", "/tmp"]
+/// }))
+/// .unwrap();
+/// assert!(matches!(config.transport, McpServerTransport::Stdio { .. }));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// Server name, used as the tool prefix by [`McpToolRouter::with_prefixed_names`].
+    pub name: String,
+    #[serde(flatten)]
+    pub transport: McpServerTransport,
+}
+
+/// How to reach an MCP server: a `command` to spawn or a streamable HTTP `url`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum McpServerTransport {
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        env: HashMap<String, String>,
+    },
+    Http {
+        url: String,
+        /// Extra headers such as `Authorization`.
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
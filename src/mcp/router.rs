@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use futures_util::future::try_join_all;
+use serde_json::{Value, json};
+
+use super::{McpClient, McpServerConfig};
+use crate::error::LLMError;
+use crate::http::DynHttpTransport;
+use crate::types::{ContentPart, Message, Role, ToolCall, ToolDefinition, ToolResult};
+
+/// Routes model tool calls to the MCP servers that own the tools.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use kotoba_llm::http::DynHttpTransport;
+/// # use kotoba_llm::mcp::{McpServerConfig, McpToolRouter};
+/// # use kotoba_llm::types::{ChatRequest, OutputItem};
+/// # use kotoba_llm::LLMClient;
+/// # async fn run(client: LLMClient, mut request: ChatRequest, configs: Vec<McpServerConfig>, http: DynHttpTransport) -> Result<(), kotoba_llm::LLMError> {
+/// let router = McpToolRouter::connect(&configs, http).await?;
+/// request.tools.extend(router.tools().iter().cloned());
+/// let response = client.chat("default", request.clone()).await?;
+/// let calls: Vec<_> = response
+///     .outputs
+///     .iter()
+///     .filter_map(|item| match item {
+///         OutputItem::ToolCall { call, .. } => Some(call.clone()),
+///         _ => None,
+///     })
+///     .collect();
+/// request.messages.push(router.tool_message(&calls).await?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct McpToolRouter {
+    servers: Vec<McpClient>,
+    /// Exposed tool name to (server index, tool name on that server).
+    routes: HashMap<String, (usize, String)>,
+    tools: Vec<ToolDefinition>,
+    prefix_names: bool,
+}
+
+impl McpToolRouter {
+    /// Creates an empty router exposing tools under their MCP names.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exposes tools as `{server}__{tool}` so servers may reuse tool names.
+    ///
+    /// Applies to servers added afterwards.
+    pub fn with_prefixed_names(mut self) -> Self {
+        self.prefix_names = true;
+        self
+    }
+
+    /// Connects to every configured server and registers its tools.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first connection or registration error.
+    pub async fn connect(
+        configs: &[McpServerConfig],
+        http: DynHttpTransport,
+    ) -> Result<Self, LLMError> {
+        let mut router = Self::new();
+        for config in configs {
+            let client = McpClient::connect_config(config, http.clone()).await?;
+            router.add_server(client).await?;
+        }
+        Ok(router)
+    }
+
+    /// Lists the server's tools and registers them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] when an exposed tool name is already taken, and
+    /// the errors of [`McpClient::list_tools`].
+    pub async fn add_server(&mut self, client: McpClient) -> Result<(), LLMError> {
+        let index = self.servers.len();
+        let mut routes = Vec::new();
+        for tool in client.list_tools().await? {
+            let name = if self.prefix_names {
+                format!("{}__{}", client.name(), tool.name)
+            } else {
+                tool.name.clone()
+            };
+            if self.routes.contains_key(&name) || routes.iter().any(|(taken, _)| *taken == name) {
+                return Err(LLMError::Validation {
+                    message: format!(
+                        "MCP tool `{name}` from server `{}` is already registered",
+                        client.name()
+                    ),
+                });
+            }
+            self.tools.push(tool.to_tool_definition(name.clone()));
+            routes.push((name, tool.name));
+        }
+        self.routes
+            .extend(routes.into_iter().map(|(name, tool)| (name, (index, tool))));
+        self.servers.push(client);
+        Ok(())
+    }
+
+    /// Connected servers in registration order.
+    pub fn servers(&self) -> &[McpClient] {
+        &self.servers
+    }
+
+    /// Definitions of every registered tool, ready for [`crate::types::ChatRequest::tools`].
+    pub fn tools(&self) -> &[ToolDefinition] {
+        &self.tools
+    }
+
+    /// Whether `call` names a registered tool.
+    pub fn handles(&self, call: &ToolCall) -> bool {
+        self.routes.contains_key(&call.name)
+    }
+
+    /// Runs `call` on its server and converts the outcome into a [`ToolResult`].
+    ///
+    /// Arguments sent as a JSON string are parsed first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] for unknown tools and arguments that are not a
+    /// JSON object, and the errors of [`McpClient::call_tool`].
+    pub async fn call(&self, call: &ToolCall) -> Result<ToolResult, LLMError> {
+        let Some((index, tool)) = self.routes.get(&call.name) else {
+            return Err(LLMError::Validation {
+                message: format!("no MCP server provides tool `{}`", call.name),
+            });
+        };
+        let arguments = match &call.arguments {
+            Value::Null => json!({}),
+            Value::String(raw) if raw.trim().is_empty() => json!({}),
+            Value::String(raw) => {
+                serde_json::from_str(raw).map_err(|err| LLMError::Validation {
+                    message: format!("invalid arguments for MCP tool `{}`: {err}", call.name),
+                })?
+            }
+            arguments => arguments.clone(),
+        };
+        if !arguments.is_object() {
+            return Err(LLMError::Validation {
+                message: format!("arguments for MCP tool `{}` must be an object", call.name),
+            });
+        }
+        let result = self.servers[*index].call_tool(tool, arguments).await?;
+        Ok(result.into_tool_result(call.id.clone()))
+    }
+
+    /// Runs `calls` concurrently; results keep the order of the calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of [`McpToolRouter::call`].
+    pub async fn call_all(&self, calls: &[ToolCall]) -> Result<Vec<ToolResult>, LLMError> {
+        try_join_all(calls.iter().map(|call| self.call(call))).await
+    }
+
+    /// Runs `calls` and wraps the results in the tool message that answers them.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`McpToolRouter::call_all`].
+    pub async fn tool_message(&self, calls: &[ToolCall]) -> Result<Message, LLMError> {
+        let results = self.call_all(calls).await?;
+        Ok(Message {
+            role: Role("tool".to_string()),
+            name: None,
+            content: results.into_iter().map(ContentPart::ToolResult).collect(),
+            metadata: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::mcp::McpTransport;
+    use crate::types::ToolCallKind;
+
+    /// In-memory server answering with canned tools and echoing `tools/call` arguments.
+    struct FakeServer {
+        tools: Value,
+        calls: Mutex<Vec<Value>>,
+    }
+
+    #[async_trait]
+    impl McpTransport for FakeServer {
+        async fn request(&self, request: Value) -> Result<Value, LLMError> {
+            let result = match request["method"].as_str().unwrap() {
+                "initialize" => json!({
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "fake", "version": "1.0.0" }
+                }),
+                "tools/list" => json!({ "tools": self.tools }),
+                "tools/call" => {
+                    self.calls.lock().unwrap().push(request["params"].clone());
+                    if request["params"]["name"] == "missing" {
+                        return Ok(json!({
+                            "jsonrpc": "2.0",
+                            "id": request["id"],
+                            "error": { "code": -32602, "message": "Unknown tool: missing" }
+                        }));
+                    }
+                    json!({
+                        "content": [{ "type": "text", "text": request["params"]["arguments"].to_string() }]
+                    })
+                }
+                other => panic!("unexpected method {other}"),
+            };
+            Ok(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+        }
+
+        async fn notify(&self, _notification: Value) -> Result<(), LLMError> {
+            Ok(())
+        }
+    }
+
+    async fn server(name: &str, tools: &[&str]) -> (McpClient, Arc<FakeServer>) {
+        let fake = Arc::new(FakeServer {
+            tools: tools
+                .iter()
+                .map(|tool| json!({ "name": tool, "description": format!("{tool} tool"), "inputSchema": { "type": "object" } }))
+                .collect(),
+            calls: Mutex::new(Vec::new()),
+        });
+        let client = McpClient::connect(name, fake.clone())
+            .await
+            .expect("connect");
+        (client, fake)
+    }
+
+    fn tool_call(id: &str, name: &str, arguments: Value) -> ToolCall {
+        ToolCall {
+            id: Some(id.to_string()),
+            name: name.to_string(),
+            arguments,
+            kind: ToolCallKind::Function,
+        }
+    }
+
+    #[tokio::test]
+    async fn routes_calls_to_the_owning_server() {
+        let (files, files_server) = server("files", &["read_file"]).await;
+        let (web, web_server) = server("web", &["fetch", "missing"]).await;
+        assert_eq!(files.server_info().server_info.name, "fake");
+        let mut router = McpToolRouter::new();
+        router.add_server(files).await.expect("files");
+        router.add_server(web).await.expect("web");
+
+        let names: Vec<&str> = router
+            .tools()
+            .iter()
+            .map(|tool| tool.name.as_str())
+            .collect();
+        assert_eq!(names, ["read_file", "fetch", "missing"]);
+        assert_eq!(
+            router.tools()[0].description.as_deref(),
+            Some("read_file tool")
+        );
+
+        let message = router
+            .tool_message(&[
+                tool_call(
+                    "call_1",
+                    "fetch",
+                    json!("{\"url\":\"https://example.com\"}"),
+                ),
+                tool_call("call_2", "read_file", Value::Null),
+            ])
+            .await
+            .expect("tool message");
+
+        assert_eq!(message.role, Role("tool".to_string()));
+        let ContentPart::ToolResult(first) = &message.content[0] else {
+            panic!("unexpected part: {:?}", message.content[0]);
+        };
+        assert_eq!(first.call_id.as_deref(), Some("call_1"));
+        assert_eq!(first.output, json!("{\"url\":\"https://example.com\"}"));
+        assert_eq!(
+            web_server.calls.lock().unwrap()[0],
+            json!({ "name": "fetch", "arguments": { "url": "https://example.com" } })
+        );
+        assert_eq!(
+            files_server.calls.lock().unwrap()[0],
+            json!({ "name": "read_file", "arguments": {} })
+        );
+
+        let err = router
+            .call(&tool_call("call_3", "missing", json!({})))
+            .await
+            .expect_err("json-rpc error");
+        assert!(
+            err.to_string()
+                .contains("web: tools/call failed: Unknown tool: missing")
+        );
+        assert!(matches!(
+            router.call(&tool_call("call_4", "nope", json!({}))).await,
+            Err(LLMError::Validation { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn prefixes_names_to_avoid_collisions() {
+        let (first, _) = server("a", &["search"]).await;
+        let (second, second_server) = server("b", &["search"]).await;
+        let mut router = McpToolRouter::new();
+        router.add_server(first).await.expect("a");
+        let (duplicate, _) = server("c", &["search"]).await;
+        assert!(matches!(
+            router.add_server(duplicate).await,
+            Err(LLMError::Validation { .. })
+        ));
+
+        let mut router = McpToolRouter::new().with_prefixed_names();
+        let (first, _) = server("a", &["search"]).await;
+        router.add_server(first).await.expect("a");
+        router.add_server(second).await.expect("b");
+        let names: Vec<&str> = router
+            .tools()
+            .iter()
+            .map(|tool| tool.name.as_str())
+            .collect();
+        assert_eq!(names, ["a__search", "b__search"]);
+
+        let call = tool_call("call_1", "b__search", json!({ "q": "rust" }));
+        assert!(router.handles(&call));
+        router.call(&call).await.expect("call");
+        assert_eq!(second_server.calls.lock().unwrap()[0]["name"], "search");
+    }
+}
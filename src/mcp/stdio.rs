@@ -0,0 +1,109 @@
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+
+use super::{McpTransport, reply_to_server};
+use crate::error::LLMError;
+
+/// MCP transport over the stdin/stdout of a child process, one JSON message per line.
+///
+/// Requests are serialized: each waits for its response before the next is written.
+/// Pings from the server are answered while waiting; other server requests are
+/// rejected and notifications are ignored.
+pub struct StdioTransport {
+    io: Mutex<StdioIo>,
+}
+
+struct StdioIo {
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    // Held so the process is killed together with the transport.
+    _child: Child,
+}
+
+impl StdioTransport {
+    /// Spawns `command` with piped stdin and stdout; stderr is inherited.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Transport`] when the process cannot be started.
+    pub fn spawn(mut command: Command) -> Result<Self, LLMError> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| LLMError::transport(format!("failed to spawn MCP server: {err}")))?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(LLMError::transport("MCP server stdio is not piped"));
+        };
+        Ok(Self {
+            io: Mutex::new(StdioIo {
+                stdin,
+                stdout: BufReader::new(stdout),
+                _child: child,
+            }),
+        })
+    }
+}
+
+impl StdioIo {
+    async fn write(&mut self, message: &Value) -> Result<(), LLMError> {
+        let mut line = message.to_string();
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|err| LLMError::transport(format!("failed to write to MCP server: {err}")))?;
+        self.stdin
+            .flush()
+            .await
+            .map_err(|err| LLMError::transport(format!("failed to write to MCP server: {err}")))
+    }
+
+    async fn read(&mut self) -> Result<Value, LLMError> {
+        loop {
+            let mut line = String::new();
+            let read = self.stdout.read_line(&mut line).await.map_err(|err| {
+                LLMError::transport(format!("failed to read from MCP server: {err}"))
+            })?;
+            if read == 0 {
+                return Err(LLMError::transport("MCP server closed its stdout"));
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            return serde_json::from_str(&line).map_err(|err| {
+                LLMError::transport(format!("MCP server sent invalid JSON: {err}"))
+            });
+        }
+    }
+}
+
+#[async_trait]
+impl McpTransport for StdioTransport {
+    async fn request(&self, request: Value) -> Result<Value, LLMError> {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let mut io = self.io.lock().await;
+        io.write(&request).await?;
+        loop {
+            let message = io.read().await?;
+            match (message.get("id"), message.get("method")) {
+                // Server-to-client request.
+                (Some(server_id), Some(method)) => {
+                    io.write(&reply_to_server(server_id, method)).await?;
+                }
+                (Some(response_id), None) if *response_id == id => return Ok(message),
+                _ => {}
+            }
+        }
+    }
+
+    async fn notify(&self, notification: Value) -> Result<(), LLMError> {
+        self.io.lock().await.write(&notification).await
+    }
+}
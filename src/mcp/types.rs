@@ -0,0 +1,288 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::types::{
+    AudioContent, ContentPart, ImageContent, ImageSource, MediaSource, ToolDefinition, ToolKind,
+    ToolResult,
+};
+
+/// Server details negotiated during `initialize`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerInfo {
+    /// Protocol revision chosen by the server.
+    pub protocol_version: String,
+    /// Capabilities advertised by the server, such as `tools`.
+    #[serde(default)]
+    pub capabilities: Value,
+    /// Server name and version.
+    #[serde(default)]
+    pub server_info: McpImplementation,
+    /// Usage hints the server offers for the model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+}
+
+/// Name and version of an MCP client or server implementation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpImplementation {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// Tool listed by `tools/list`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpTool {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON Schema of the arguments.
+    #[serde(default = "empty_object_schema")]
+    pub input_schema: Value,
+    /// JSON Schema of `structuredContent`, when the tool returns structured results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    /// Behaviour hints such as `readOnlyHint`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Value>,
+}
+
+fn empty_object_schema() -> Value {
+    json!({ "type": "object" })
+}
+
+impl McpTool {
+    /// Converts the tool into a function [`ToolDefinition`] exposed under `name`.
+    ///
+    /// The description falls back to the tool title.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use kotoba_llm::mcp::McpTool;
+    /// let tool: McpTool = serde_json::from_value(serde_json::json!({
+    ///     "name": "read_file",
+    ///     "description": "Reads a file",
+    ///     "inputSchema": { "type": "object", "properties": { "path": { "type": "string" } } }
+    /// }))
+    /// .unwrap();
+    /// let definition = tool.to_tool_definition("files__read_file");
+    /// assert_eq!(definition.name, "files__read_file");
+    /// assert_eq!(definition.description.as_deref(), Some("Reads a file"));
+    /// ```
+    pub fn to_tool_definition(&self, name: impl Into<String>) -> ToolDefinition {
+        ToolDefinition {
+            name: name.into(),
+            description: self.description.clone().or_else(|| self.title.clone()),
+            input_schema: Some(self.input_schema.clone()),
+            kind: ToolKind::Function,
+            metadata: None,
+            cache_control: None,
+        }
+    }
+}
+
+/// Result of `tools/call`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallToolResult {
+    #[serde(default)]
+    pub content: Vec<McpContent>,
+    /// Structured result matching the tool's output schema.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+    /// Set when the tool itself failed; protocol errors are returned as `Err` instead.
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+/// Content block of a [`CallToolResult`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum McpContent {
+    Text {
+        text: String,
+    },
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    Audio {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    /// Link to a resource the client may read separately.
+    ResourceLink {
+        uri: String,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default, rename = "mimeType")]
+        mime_type: Option<String>,
+    },
+    /// Resource embedded in the result.
+    Resource {
+        resource: McpResourceContents,
+    },
+    /// Block type introduced by a newer protocol revision.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Contents of an embedded resource: `text` for text resources, base64 `blob` otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResourceContents {
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+}
+
+impl CallToolResult {
+    /// Converts the result into a [`ToolResult`] answering `call_id`.
+    ///
+    /// `output` is the structured content when present, otherwise the text blocks
+    /// (including text resources and resource links) joined by newlines. Images, audio
+    /// and binary resources become [`ToolResult::content`] parts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use kotoba_llm::mcp::{CallToolResult, McpContent};
+    /// let result = CallToolResult {
+    ///     content: vec![McpContent::Text { text: "42 files".to_string() }],
+    ///     structured_content: None,
+    ///     is_error: false,
+    /// };
+    /// let result = result.into_tool_result(Some("call_1".to_string()));
+    /// assert_eq!(result.output, serde_json::json!("42 files"));
+    /// assert!(result.content.is_empty());
+    /// ```
+    pub fn into_tool_result(self, call_id: Option<String>) -> ToolResult {
+        let mut texts = Vec::new();
+        let mut content = Vec::new();
+        for block in self.content {
+            match block {
+                McpContent::Text { text } => texts.push(text),
+                McpContent::Image { data, mime_type } => {
+                    content.push(ContentPart::Image(image(data, mime_type)))
+                }
+                McpContent::Audio { data, mime_type } => {
+                    content.push(ContentPart::Audio(AudioContent {
+                        source: MediaSource::Inline { data },
+                        mime_type: Some(mime_type),
+                        metadata: None,
+                    }))
+                }
+                McpContent::ResourceLink { uri, name, .. } => texts.push(match name {
+                    Some(name) => format!("{name}: {uri}"),
+                    None => uri,
+                }),
+                McpContent::Resource { resource } => match resource {
+                    McpResourceContents {
+                        text: Some(text), ..
+                    } => texts.push(text),
+                    McpResourceContents {
+                        blob: Some(blob),
+                        mime_type: Some(mime_type),
+                        ..
+                    } if mime_type.starts_with("image/") => {
+                        content.push(ContentPart::Image(image(blob, mime_type)))
+                    }
+                    other => content.push(ContentPart::Data {
+                        data: json!({ "type": "resource", "resource": other }),
+                    }),
+                },
+                McpContent::Unknown => {}
+            }
+        }
+        let output = match self.structured_content {
+            Some(structured) => structured,
+            None if texts.is_empty() => Value::Null,
+            None => Value::String(texts.join("\n")),
+        };
+        ToolResult {
+            call_id,
+            output,
+            is_error: self.is_error,
+            content,
+            metadata: None,
+        }
+    }
+}
+
+fn image(data: String, mime_type: String) -> ImageContent {
+    ImageContent {
+        source: ImageSource::Base64 {
+            data,
+            mime_type: Some(mime_type),
+        },
+        detail: None,
+        metadata: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_mixed_content_into_output_and_parts() {
+        let result: CallToolResult = serde_json::from_value(json!({
+            "content": [
+                { "type": "text", "text": "Found 2 matches" },
+                { "type": "image", "data": "iVBORw0", "mimeType": "image/png" },
+                { "type": "resource_link", "uri": "file:///notes.md", "name": "notes.md" },
+                { "type": "resource", "resource": { "uri": "file:///a.txt", "mimeType": "text/plain", "text": "alpha" } },
+                { "type": "resource", "resource": { "uri": "file:///a.bin", "blob": "AAEC" } },
+                { "type": "hologram" }
+            ]
+        }))
+        .expect("result");
+
+        let tool_result = result.into_tool_result(Some("call_1".to_string()));
+
+        assert_eq!(tool_result.call_id.as_deref(), Some("call_1"));
+        assert_eq!(
+            tool_result.output,
+            json!("Found 2 matches\nnotes.md: file:///notes.md\nalpha")
+        );
+        assert!(!tool_result.is_error);
+        assert_eq!(tool_result.content.len(), 2);
+        assert!(matches!(
+            &tool_result.content[0],
+            ContentPart::Image(ImageContent { source: ImageSource::Base64 { data, mime_type: Some(mime) }, .. })
+                if data == "iVBORw0" && mime == "image/png"
+        ));
+        assert!(matches!(
+            &tool_result.content[1],
+            ContentPart::Data { data } if data["resource"]["blob"] == "AAEC"
+        ));
+    }
+
+    #[test]
+    fn structured_content_becomes_the_output() {
+        let result: CallToolResult = serde_json::from_value(json!({
+            "content": [{ "type": "text", "text": "{\"temperature\":21}" }],
+            "structuredContent": { "temperature": 21 },
+            "isError": false
+        }))
+        .expect("result");
+
+        let tool_result = result.into_tool_result(None);
+
+        assert_eq!(tool_result.output, json!({ "temperature": 21 }));
+        assert!(tool_result.content.is_empty());
+    }
+}
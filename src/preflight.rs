@@ -0,0 +1,302 @@
+//! Pre-flight validation of chat requests against model capabilities and limits.
+//!
+//! Unsupported inputs otherwise fail deep inside the provider request mappers (for example
+//! as `UnsupportedFeature { feature: "anthropic_messages_content_type" }`) or upstream after
+//! a paid round trip. [`validate_request`] runs before any HTTP call and reports every
+//! problem at once; [`crate::client::LLMClient::validate_request`] applies it to a handle.
+
+use std::fmt;
+
+use crate::catalog::ModelInfo;
+use crate::types::{
+    CapabilityDescriptor, ChatRequest, ContentPart, ProviderType, ResponseFormat, TokenEstimator,
+};
+
+/// Input modalities gated by [`CapabilityDescriptor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputModality {
+    /// [`ContentPart::Image`].
+    Image,
+    /// [`ContentPart::Audio`].
+    Audio,
+    /// [`ContentPart::Video`].
+    Video,
+}
+
+impl fmt::Display for InputModality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Image => "image",
+            Self::Audio => "audio",
+            Self::Video => "video",
+        })
+    }
+}
+
+/// One reason the target model cannot serve a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestViolation {
+    /// A content part uses an input modality the model does not accept.
+    UnsupportedInput {
+        /// Modality of the rejected part.
+        modality: InputModality,
+        /// Index of the message in [`ChatRequest::messages`].
+        message_index: usize,
+        /// Index of the part in the message content.
+        part_index: usize,
+    },
+    /// The request declares tools but the model does not support tool calling.
+    ToolsUnsupported,
+    /// The request asks for [`ResponseFormat::JsonSchema`] without structured output support.
+    StructuredOutputUnsupported,
+    /// The estimated prompt size exceeds the model's context window.
+    ContextWindowExceeded {
+        /// Prompt tokens estimated by [`TokenEstimator`].
+        estimated: usize,
+        /// Context window of the model.
+        limit: u64,
+    },
+    /// `max_output_tokens` exceeds what the model can generate.
+    OutputLimitExceeded {
+        /// Requested [`crate::types::ChatOptions::max_output_tokens`].
+        requested: u32,
+        /// Output limit of the model.
+        limit: u64,
+    },
+}
+
+impl fmt::Display for RequestViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedInput {
+                modality,
+                message_index,
+                part_index,
+            } => write!(
+                f,
+                "messages[{message_index}].content[{part_index}]: {modality} input is not supported"
+            ),
+            Self::ToolsUnsupported => f.write_str("tools are not supported"),
+            Self::StructuredOutputUnsupported => {
+                f.write_str("JSON schema response format is not supported")
+            }
+            Self::ContextWindowExceeded { estimated, limit } => write!(
+                f,
+                "estimated prompt of {estimated} tokens exceeds the context window of {limit}"
+            ),
+            Self::OutputLimitExceeded { requested, limit } => write!(
+                f,
+                "max_output_tokens {requested} exceeds the output limit of {limit}"
+            ),
+        }
+    }
+}
+
+/// Checks `request` against the capabilities and catalog entry of its target model.
+///
+/// `model` supplies the context window and output limit; without it only the capability
+/// checks run. Returns every violation found, in message order, or an empty list when the
+/// request looks servable.
+///
+/// # Examples
+///
+/// ```
+/// use kotoba_llm::catalog::ModelInfo;
+/// use kotoba_llm::preflight::{RequestViolation, validate_request};
+/// use kotoba_llm::types::{
+///     CapabilityDescriptor, ChatOptions, ChatRequest, ProviderType, ResponseFormat,
+///     TokenEstimator,
+/// };
+///
+/// let request = ChatRequest {
+///     messages: Vec::new(),
+///     options: ChatOptions { max_output_tokens: Some(64_000), ..Default::default() },
+///     tools: Vec::new(),
+///     tool_choice: None,
+///     response_format: Some(ResponseFormat::JsonSchema { schema: serde_json::json!({}) }),
+///     metadata: None,
+/// };
+/// let violations = validate_request(
+///     &request,
+///     &CapabilityDescriptor::default(),
+///     Some(&ModelInfo::new("small").with_max_output_tokens(4_096)),
+///     &TokenEstimator::new(ProviderType::OpenAI),
+/// );
+/// assert_eq!(
+///     violations,
+///     vec![
+///         RequestViolation::StructuredOutputUnsupported,
+///         RequestViolation::OutputLimitExceeded { requested: 64_000, limit: 4_096 },
+///     ]
+/// );
+/// ```
+pub fn validate_request(
+    request: &ChatRequest,
+    capabilities: &CapabilityDescriptor,
+    model: Option<&ModelInfo>,
+    estimator: &TokenEstimator,
+) -> Vec<RequestViolation> {
+    let mut violations = Vec::new();
+
+    for (message_index, message) in request.messages.iter().enumerate() {
+        for (part_index, part) in message.content.iter().enumerate() {
+            let (modality, supported) = match part {
+                ContentPart::Image(_) => (InputModality::Image, capabilities.supports_image_input),
+                ContentPart::Audio(_) => (InputModality::Audio, capabilities.supports_audio_input),
+                ContentPart::Video(_) => (InputModality::Video, capabilities.supports_video_input),
+                _ => continue,
+            };
+            if !supported {
+                violations.push(RequestViolation::UnsupportedInput {
+                    modality,
+                    message_index,
+                    part_index,
+                });
+            }
+        }
+    }
+
+    if !request.tools.is_empty() && !capabilities.supports_tools {
+        violations.push(RequestViolation::ToolsUnsupported);
+    }
+
+    if matches!(
+        request.response_format,
+        Some(ResponseFormat::JsonSchema { .. })
+    ) && !capabilities.supports_structured_output
+    {
+        violations.push(RequestViolation::StructuredOutputUnsupported);
+    }
+
+    if let Some(limit) = model.and_then(|info| info.context_window) {
+        let estimated = estimator.estimate_request(request).total;
+        if estimated as u64 > limit {
+            violations.push(RequestViolation::ContextWindowExceeded { estimated, limit });
+        }
+    }
+
+    if let (Some(requested), Some(limit)) = (
+        request.options.max_output_tokens,
+        model.and_then(|info| info.max_output_tokens),
+    ) {
+        if u64::from(requested) > limit {
+            violations.push(RequestViolation::OutputLimitExceeded { requested, limit });
+        }
+    }
+
+    violations
+}
+
+/// Picks the tokenizer heuristics closest to the provider named `provider`.
+pub(crate) fn provider_type(provider: &str) -> ProviderType {
+    match provider {
+        "anthropic_messages" | "bedrock_converse" => ProviderType::Anthropic,
+        "google_gemini" => ProviderType::GoogleGemini,
+        _ => ProviderType::OpenAI,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        AudioContent, ImageContent, ImageSource, MediaSource, Message, Role, TextContent,
+        ToolDefinition, ToolKind,
+    };
+
+    fn request(content: Vec<ContentPart>) -> ChatRequest {
+        ChatRequest {
+            messages: vec![Message {
+                role: Role::user(),
+                name: None,
+                content,
+                metadata: None,
+            }],
+            options: Default::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn collects_every_violation() {
+        let mut request = request(vec![
+            ContentPart::Text(TextContent {
+                text: "word ".repeat(400),
+            }),
+            ContentPart::Image(ImageContent {
+                source: ImageSource::Url {
+                    url: "https://example.com/cat.png".to_string(),
+                },
+                detail: None,
+                metadata: None,
+            }),
+            ContentPart::Audio(AudioContent {
+                source: MediaSource::Url {
+                    url: "https://example.com/a.wav".to_string(),
+                },
+                mime_type: None,
+                metadata: None,
+            }),
+        ]);
+        request.tools.push(ToolDefinition {
+            name: "lookup".to_string(),
+            description: None,
+            input_schema: None,
+            kind: ToolKind::Function,
+            metadata: None,
+            cache_control: None,
+        });
+        request.response_format = Some(ResponseFormat::JsonSchema {
+            schema: serde_json::json!({"type": "object"}),
+        });
+        let caps = CapabilityDescriptor {
+            supports_audio_input: true,
+            ..Default::default()
+        };
+        let info = ModelInfo::new("tiny").with_context_window(100);
+
+        let violations = validate_request(
+            &request,
+            &caps,
+            Some(&info),
+            &TokenEstimator::new(ProviderType::OpenAI),
+        );
+        assert_eq!(violations.len(), 4, "{violations:?}");
+        assert_eq!(
+            violations[0],
+            RequestViolation::UnsupportedInput {
+                modality: InputModality::Image,
+                message_index: 0,
+                part_index: 1,
+            }
+        );
+        assert_eq!(violations[1], RequestViolation::ToolsUnsupported);
+        assert_eq!(violations[2], RequestViolation::StructuredOutputUnsupported);
+        assert!(matches!(
+            violations[3],
+            RequestViolation::ContextWindowExceeded { limit: 100, estimated } if estimated > 100
+        ));
+        assert_eq!(
+            violations[0].to_string(),
+            "messages[0].content[1]: image input is not supported"
+        );
+    }
+
+    #[test]
+    fn accepts_supported_requests_and_skips_unknown_limits() {
+        let mut request = request(vec![ContentPart::Text(TextContent {
+            text: "word ".repeat(400),
+        })]);
+        request.options.max_output_tokens = Some(1_000_000);
+        let violations = validate_request(
+            &request,
+            &CapabilityDescriptor::default(),
+            None,
+            &TokenEstimator::new(ProviderType::Anthropic),
+        );
+        assert!(violations.is_empty(), "{violations:?}");
+    }
+}
@@ -1,17 +1,20 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures_util::future::{try_join, try_join_all};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 
 use crate::config::RequestPatch;
+use crate::credential::{DynCredentialProvider, StaticCredential, invalidate_on_unauthorized};
 use crate::error::LLMError;
 use crate::http::{
     DynHttpTransport, HttpResponse, HttpStreamResponse, post_json_stream_with_headers,
     post_json_with_headers,
 };
-use crate::provider::{ChatStream, LLMProvider, retry::retry_after_from_headers};
-use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
+use crate::provider::{ChatStream, LLMProvider, PreparedRequest, retry::retry_after_from_headers};
+use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse, ToolKind};
 
 use super::error::parse_anthropic_error;
 use super::request::build_anthropic_body;
@@ -21,6 +24,8 @@ use super::types::AnthropicMessageResponse;
 
 const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
 const DEFAULT_VERSION: &str = "2023-06-01";
+/// Beta flag required by the `computer_20250124` tool.
+const COMPUTER_USE_BETA: &str = "computer-use-2025-01-24";
 
 /// Anthropic Messages provider compatible with the Claude 3.x Messages API.
 ///
@@ -29,7 +34,7 @@ const DEFAULT_VERSION: &str = "2023-06-01";
 pub struct AnthropicMessagesProvider {
     pub(crate) transport: DynHttpTransport,
     pub(crate) base_url: String,
-    pub(crate) api_key: String,
+    pub(crate) credential: DynCredentialProvider,
     pub(crate) version: String,
     pub(crate) beta: Option<String>,
     pub(crate) default_model: Option<String>,
@@ -37,23 +42,60 @@ pub struct AnthropicMessagesProvider {
 }
 
 impl AnthropicMessagesProvider {
+    /// `ModelConfig.extra` keys read by [`Self::from_model_config`].
+    pub const SUPPORTED_EXTRA_KEYS: &'static [&'static str] = &["version", "beta"];
+
+    /// Largest `candidate_count` accepted by [`LLMProvider::chat`].
+    ///
+    /// Messages has no `n`, so every candidate is a separate, billed request; larger
+    /// counts are rejected rather than fanned out.
+    pub const MAX_EMULATED_CANDIDATES: u32 = 8;
+
     /// Creates a provider with the default base URL and `anthropic-version` header.
     ///
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
     /// # use kotoba_llm::provider::anthropic_messages::AnthropicMessagesProvider;
     /// # use kotoba_llm::provider::LLMProvider;
     /// # use kotoba_llm::http::reqwest::default_dyn_transport;
     /// let transport = default_dyn_transport().expect("transport");
     /// let provider = AnthropicMessagesProvider::new(transport, "test-key");
     /// assert_eq!(provider.name(), "anthropic_messages");
+    /// # }
     /// ```
     pub fn new(transport: DynHttpTransport, api_key: impl Into<String>) -> Self {
+        Self::from_credential_provider(transport, Arc::new(StaticCredential::new(api_key)))
+    }
+
+    /// Creates a provider that asks `credential` for its API key before every request.
+    ///
+    /// Use this with rotating secrets, e.g. a [`crate::credential::CachedCredential`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::credential::EnvCredential;
+    /// # use kotoba_llm::provider::anthropic_messages::AnthropicMessagesProvider;
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let credential = Arc::new(EnvCredential::new("API_KEY"));
+    /// let provider = AnthropicMessagesProvider::from_credential_provider(transport, credential);
+    /// assert_eq!(provider.name(), "anthropic_messages");
+    /// # }
+    /// ```
+    pub fn from_credential_provider(
+        transport: DynHttpTransport,
+        credential: DynCredentialProvider,
+    ) -> Self {
         Self {
             transport,
             base_url: DEFAULT_BASE_URL.to_string(),
-            api_key: api_key.into(),
+            credential,
             version: DEFAULT_VERSION.to_string(),
             beta: None,
             default_model: None,
@@ -66,6 +108,7 @@ impl AnthropicMessagesProvider {
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
     /// # use kotoba_llm::provider::anthropic_messages::AnthropicMessagesProvider;
     /// # use kotoba_llm::provider::LLMProvider;
     /// # use kotoba_llm::http::reqwest::default_dyn_transport;
@@ -73,6 +116,7 @@ impl AnthropicMessagesProvider {
     /// let provider = AnthropicMessagesProvider::new(transport, "key")
     ///     .with_base_url("https://anthropic-proxy.local");
     /// assert_eq!(provider.name(), "anthropic_messages");
+    /// # }
     /// ```
     pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
         self.base_url = base_url.into();
@@ -84,6 +128,7 @@ impl AnthropicMessagesProvider {
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
     /// # use kotoba_llm::provider::anthropic_messages::AnthropicMessagesProvider;
     /// # use kotoba_llm::provider::LLMProvider;
     /// # use kotoba_llm::http::reqwest::default_dyn_transport;
@@ -91,6 +136,7 @@ impl AnthropicMessagesProvider {
     /// let provider = AnthropicMessagesProvider::new(transport, "key")
     ///     .with_version("2023-11-01");
     /// assert_eq!(provider.name(), "anthropic_messages");
+    /// # }
     /// ```
     pub fn with_version(mut self, version: impl Into<String>) -> Self {
         self.version = version.into();
@@ -102,6 +148,7 @@ impl AnthropicMessagesProvider {
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
     /// # use kotoba_llm::provider::anthropic_messages::AnthropicMessagesProvider;
     /// # use kotoba_llm::provider::LLMProvider;
     /// # use kotoba_llm::http::reqwest::default_dyn_transport;
@@ -109,6 +156,7 @@ impl AnthropicMessagesProvider {
     /// let provider = AnthropicMessagesProvider::new(transport, "key")
     ///     .with_beta("beta1,beta2");
     /// assert_eq!(provider.name(), "anthropic_messages");
+    /// # }
     /// ```
     pub fn with_beta(mut self, beta: impl Into<String>) -> Self {
         self.beta = Some(beta.into());
@@ -120,6 +168,7 @@ impl AnthropicMessagesProvider {
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
     /// # use kotoba_llm::provider::anthropic_messages::AnthropicMessagesProvider;
     /// # use kotoba_llm::provider::LLMProvider;
     /// # use kotoba_llm::http::reqwest::default_dyn_transport;
@@ -127,6 +176,7 @@ impl AnthropicMessagesProvider {
     /// let provider = AnthropicMessagesProvider::new(transport, "key")
     ///     .with_default_model("claude-3-sonnet");
     /// assert!(provider.capabilities().supports_tools);
+    /// # }
     /// ```
     pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
         self.default_model = Some(model.into());
@@ -150,25 +200,10 @@ impl AnthropicMessagesProvider {
         config: &crate::config::ModelConfig,
         transport: DynHttpTransport,
     ) -> Result<Self, LLMError> {
-        use crate::config::Credential;
-
-        let api_key =
-            match &config.credential {
-                Credential::ApiKey { key, .. } => key.clone(),
-                Credential::Bearer { token } => token.clone(),
-                Credential::ServiceAccount { .. } => return Err(LLMError::Auth {
-                    message:
-                        "provider anthropic_messages does not support service account credential"
-                            .to_string(),
-                }),
-                Credential::None => {
-                    return Err(LLMError::Auth {
-                        message: "provider anthropic_messages requires credential".to_string(),
-                    });
-                }
-            };
-
-        let mut provider = Self::new(transport, api_key);
+        let credential = config
+            .credential
+            .credential_provider("anthropic_messages")?;
+        let mut provider = Self::from_credential_provider(transport, credential);
 
         if let Some(base_url) = &config.base_url {
             provider = provider.with_base_url(base_url.clone());
@@ -200,9 +235,9 @@ impl AnthropicMessagesProvider {
         }
     }
 
-    fn build_headers(&self) -> HashMap<String, String> {
+    fn build_headers(&self, api_key: &str) -> HashMap<String, String> {
         let mut headers = HashMap::new();
-        headers.insert("x-api-key".to_string(), self.api_key.clone());
+        headers.insert("x-api-key".to_string(), api_key.to_string());
         headers.insert("Content-Type".to_string(), "application/json".to_string());
         headers.insert("Accept".to_string(), "application/json".to_string());
         headers.insert("anthropic-version".to_string(), self.version.clone());
@@ -228,19 +263,47 @@ impl AnthropicMessagesProvider {
         build_anthropic_body(request, &model, stream)
     }
 
-    async fn send_request(&self, body: Value) -> Result<HttpResponse, LLMError> {
-        let mut url = self.endpoint();
-        let mut headers = self.build_headers();
-        let mut body = body;
-        self.apply_patch(&mut url, &mut headers, &mut body);
+    async fn prepare(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let body = self.build_request_body(request, stream)?;
+        let token = self.credential.token().await?;
+        let mut headers = self.build_headers(&token.value);
+        // The computer tool is still a beta; opt in unless the caller already did.
+        if request
+            .tools
+            .iter()
+            .any(|tool| matches!(tool.kind, ToolKind::ComputerUse))
+        {
+            let beta = headers.entry("anthropic-beta".to_string()).or_default();
+            if !beta.split(',').any(|flag| flag.trim() == COMPUTER_USE_BETA) {
+                if !beta.is_empty() {
+                    beta.push(',');
+                }
+                beta.push_str(COMPUTER_USE_BETA);
+            }
+        }
+        let mut prepared = PreparedRequest {
+            url: self.endpoint(),
+            headers,
+            body,
+        };
+        self.apply_patch(&mut prepared.url, &mut prepared.headers, &mut prepared.body);
+        Ok(prepared)
+    }
+
+    async fn send_request(&self, prepared: PreparedRequest) -> Result<HttpResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
         post_json_with_headers(self.transport.as_ref(), url, headers, &body).await
     }
 
-    async fn send_stream_request(&self, body: Value) -> Result<HttpStreamResponse, LLMError> {
-        let mut url = self.endpoint();
-        let mut headers = self.build_headers();
-        let mut body = body;
-        self.apply_patch(&mut url, &mut headers, &mut body);
+    async fn send_stream_request(
+        &self,
+        prepared: PreparedRequest,
+    ) -> Result<HttpStreamResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
         post_json_stream_with_headers(self.transport.as_ref(), url, headers, &body).await
     }
 
@@ -254,6 +317,7 @@ impl AnthropicMessagesProvider {
         if (200..300).contains(&status) {
             Ok(text)
         } else {
+            invalidate_on_unauthorized(&self.credential, status);
             Err(parse_anthropic_error(
                 status,
                 &text,
@@ -262,6 +326,14 @@ impl AnthropicMessagesProvider {
         }
     }
 
+    async fn chat_once(&self, request: &ChatRequest) -> Result<ChatResponse, LLMError> {
+        let prepared = self.prepare(request, false).await?;
+        let response = self.send_request(prepared).await?;
+        let text = self.ensure_success(response)?;
+        let parsed: AnthropicMessageResponse = self.try_parse(&text)?;
+        map_response(parsed, self.name(), self.endpoint())
+    }
+
     fn try_parse<T: DeserializeOwned>(&self, text: &str) -> Result<T, LLMError> {
         serde_json::from_str(text).map_err(|err| LLMError::Provider {
             provider: self.name(),
@@ -284,22 +356,35 @@ impl AnthropicMessagesProvider {
 #[async_trait]
 impl LLMProvider for AnthropicMessagesProvider {
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LLMError> {
-        let body = self.build_request_body(&request, false)?;
-        let response = self.send_request(body).await?;
-        let text = self.ensure_success(response)?;
-        let parsed: AnthropicMessageResponse = self.try_parse(&text)?;
-        map_response(parsed, self.name(), self.endpoint())
+        match request.options.candidate_count {
+            // Messages has no `n`; emulate it with one concurrent call per candidate.
+            Some(count) if count > Self::MAX_EMULATED_CANDIDATES => Err(LLMError::Validation {
+                message: format!(
+                    "anthropic_messages sends one request per candidate and accepts at most {} candidates, got {count}",
+                    Self::MAX_EMULATED_CANDIDATES
+                ),
+            }),
+            Some(count) if count > 1 => {
+                let mut single = request;
+                single.options.candidate_count = None;
+                let rest = (1..count).map(|_| self.chat_once(&single));
+                let (first, rest) = try_join(self.chat_once(&single), try_join_all(rest)).await?;
+                Ok(ChatResponse::merge_candidates(first, rest))
+            }
+            _ => self.chat_once(&request).await,
+        }
     }
 
     async fn stream_chat(&self, request: ChatRequest) -> Result<ChatStream, LLMError> {
-        let body = self.build_request_body(&request, true)?;
-        let response = self.send_stream_request(body).await?;
+        let prepared = self.prepare(&request, true).await?;
+        let response = self.send_stream_request(prepared).await?;
         let HttpStreamResponse {
             status,
             headers,
             body,
         } = response;
         if !(200..300).contains(&status) {
+            invalidate_on_unauthorized(&self.credential, status);
             let text = collect_stream_text(body, self.name()).await?;
             return Err(parse_anthropic_error(
                 status,
@@ -310,6 +395,14 @@ impl LLMProvider for AnthropicMessagesProvider {
         Ok(create_stream(body, self.name(), self.endpoint()))
     }
 
+    async fn prepare_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        self.prepare(request, stream).await
+    }
+
     fn capabilities(&self) -> CapabilityDescriptor {
         CapabilityDescriptor {
             supports_stream: true,
@@ -326,4 +419,112 @@ impl LLMProvider for AnthropicMessagesProvider {
     fn name(&self) -> &'static str {
         "anthropic_messages"
     }
+
+    fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::http::{HttpRequest, HttpTransport};
+    use crate::types::FinishReason;
+    use crate::types::test_support::text_request;
+
+    /// Answers every call with a numbered message and counts the calls.
+    struct CannedTransport {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpTransport for CannedTransport {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, LLMError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let body: Value =
+                serde_json::from_slice(&request.body.unwrap_or_default()).expect("json body");
+            assert!(body.get("n").is_none());
+            let body = json!({
+                "id": format!("msg_{call}"),
+                "type": "message",
+                "role": "assistant",
+                "model": "claude-sonnet-4-20250514",
+                "content": [{ "type": "text", "text": format!("answer {call}") }],
+                "stop_reason": if call == 0 { "end_turn" } else { "max_tokens" },
+                "usage": { "input_tokens": 10, "output_tokens": 4 }
+            });
+            Ok(HttpResponse {
+                status: 200,
+                headers: HashMap::new(),
+                body: serde_json::to_vec(&body).expect("serialize"),
+            })
+        }
+
+        async fn send_stream(&self, _request: HttpRequest) -> Result<HttpStreamResponse, LLMError> {
+            panic!("send_stream should not be called");
+        }
+    }
+
+    fn provider() -> (AnthropicMessagesProvider, Arc<CannedTransport>) {
+        let transport = Arc::new(CannedTransport {
+            calls: AtomicUsize::new(0),
+        });
+        let provider = AnthropicMessagesProvider::new(transport.clone(), "key")
+            .with_default_model("claude-sonnet-4-20250514");
+        (provider, transport)
+    }
+
+    fn request(candidate_count: u32) -> ChatRequest {
+        let mut request = text_request("Name a colour.");
+        request.options.max_output_tokens = Some(16);
+        request.options.candidate_count = Some(candidate_count);
+        request
+    }
+
+    /// Anthropic has no `n`, so several candidates are requested with concurrent calls.
+    #[tokio::test]
+    async fn emulates_candidates_with_parallel_calls() {
+        let (provider, transport) = provider();
+
+        let response = provider.chat(request(3)).await.expect("response");
+        assert_eq!(transport.calls.load(Ordering::SeqCst), 3);
+        let usage = response.usage.clone().expect("usage");
+        assert_eq!(usage.prompt_tokens, Some(30));
+        assert_eq!(usage.completion_tokens, Some(12));
+
+        let candidates = response.into_candidates();
+        assert_eq!(candidates.len(), 3);
+        for (index, candidate) in candidates.iter().enumerate() {
+            assert_eq!(candidate.index, index);
+            assert!(candidate.text().starts_with("answer "));
+        }
+        assert!(matches!(
+            candidates[0].finish_reason,
+            Some(FinishReason::Stop)
+        ));
+        assert!(matches!(
+            candidates[2].finish_reason,
+            Some(FinishReason::Length)
+        ));
+    }
+
+    /// Counts above the cap are rejected before any request is sent.
+    #[tokio::test]
+    async fn rejects_candidate_counts_above_the_cap() {
+        let (provider, transport) = provider();
+
+        let cap = AnthropicMessagesProvider::MAX_EMULATED_CANDIDATES;
+        assert!(matches!(
+            provider.chat(request(cap + 1)).await,
+            Err(LLMError::Validation { .. })
+        ));
+        assert_eq!(transport.calls.load(Ordering::SeqCst), 0);
+
+        provider.chat(request(cap)).await.expect("response");
+        assert_eq!(transport.calls.load(Ordering::SeqCst), cap as usize);
+    }
 }
@@ -2,10 +2,13 @@ use serde_json::{Map, Value, json};
 
 use crate::error::LLMError;
 use crate::types::{
-    ChatRequest, ContentPart, ImageContent, ImageSource, Message, ReasoningOptions, TextContent,
-    ToolChoice, ToolDefinition, ToolKind, ToolResult,
+    CacheControl, CacheTtl, ChatRequest, ContentPart, ImageContent, ImageSource, Message,
+    ReasoningOptions, TextContent, ToolCall, ToolChoice, ToolDefinition, ToolKind, ToolResult,
 };
 
+/// Tool type of the Anthropic-defined computer tool.
+pub(crate) const COMPUTER_TOOL_TYPE: &str = "computer_20250124";
+
 /// Builds the request body for Anthropic Messages.
 pub(crate) fn build_anthropic_body(
     request: &ChatRequest,
@@ -16,7 +19,11 @@ pub(crate) fn build_anthropic_body(
     body.insert("model".to_string(), Value::String(model.to_string()));
 
     // 1. Fold system/developer roles into the top-level `system`; push others into `messages`.
+    // Cache breakpoints need block-level `cache_control`, so cached system prompts are sent
+    // as text blocks instead of a single string.
     let mut system_texts = Vec::new();
+    let mut system_blocks = Vec::new();
+    let mut system_cached = false;
     let mut messages = Vec::new();
     for message in &request.messages {
         match message.role.0.as_str() {
@@ -24,6 +31,18 @@ pub(crate) fn build_anthropic_body(
                 if let Some(text) = extract_text_from_message(message) {
                     system_texts.push(text);
                 }
+                for part in &message.content {
+                    match part {
+                        ContentPart::Text(TextContent { text }) => {
+                            system_blocks.push(json!({ "type": "text", "text": text }));
+                        }
+                        ContentPart::CacheBreakpoint(cache) => {
+                            mark_cached(&mut system_blocks, cache)?;
+                            system_cached = true;
+                        }
+                        _ => {}
+                    }
+                }
             }
             _ => {
                 messages.push(convert_message(message)?);
@@ -39,7 +58,9 @@ pub(crate) fn build_anthropic_body(
     }
     body.insert("messages".to_string(), Value::Array(messages));
 
-    if !system_texts.is_empty() {
+    if system_cached {
+        body.insert("system".to_string(), Value::Array(system_blocks));
+    } else if !system_texts.is_empty() {
         let system = system_texts.join("\n\n");
         body.insert("system".to_string(), Value::String(system));
     }
@@ -60,6 +81,32 @@ pub(crate) fn build_anthropic_body(
     if let Some(top_p) = request.options.top_p {
         body.insert("top_p".to_string(), Value::from(top_p));
     }
+    if let Some(top_k) = request.options.top_k {
+        body.insert("top_k".to_string(), Value::from(top_k));
+    }
+    if let Some(stop) = &request.options.stop_sequences {
+        body.insert("stop_sequences".to_string(), json!(stop));
+    }
+    if request.options.seed.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "anthropic_messages_seed",
+        });
+    }
+    if request.options.candidate_count.is_some_and(|n| n > 1) {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "anthropic_messages_candidate_count",
+        });
+    }
+    if request.options.logprobs == Some(true) || request.options.top_logprobs.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "anthropic_messages_logprobs",
+        });
+    }
+    if request.options.cached_content.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "anthropic_messages_cached_content",
+        });
+    }
 
     // 3. Thinking/reasoning configuration.
     if let Some(reasoning) = &request.options.reasoning {
@@ -83,13 +130,21 @@ pub(crate) fn build_anthropic_body(
         }
     }
 
-    // 5. Metadata passthrough.
-    if let Some(metadata) = &request.metadata {
-        let meta: Map<String, Value> = metadata.clone().into_iter().collect();
+    // 5. Metadata passthrough; the end-user id travels as `metadata.user_id`.
+    let mut meta: Map<String, Value> = request
+        .metadata
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    if let Some(user) = &request.options.user {
+        meta.insert("user_id".to_string(), Value::String(user.clone()));
+    }
+    if request.metadata.is_some() || !meta.is_empty() {
         body.insert("metadata".to_string(), Value::Object(meta));
     }
 
-    // 6. Provider-specific extras such as `stop_sequences`.
+    // 6. Provider-specific extras.
     for (k, v) in &request.options.extra {
         body.insert(k.clone(), v.clone());
     }
@@ -128,7 +183,11 @@ fn convert_message(message: &Message) -> Result<Value, LLMError> {
 
     let mut content_blocks = Vec::new();
     for part in &message.content {
-        content_blocks.push(convert_content_part(part)?);
+        if let ContentPart::CacheBreakpoint(cache) = part {
+            mark_cached(&mut content_blocks, cache)?;
+        } else {
+            content_blocks.push(convert_content_part(part)?);
+        }
     }
 
     if content_blocks.is_empty() {
@@ -163,18 +222,57 @@ fn convert_content_part(part: &ContentPart) -> Result<Value, LLMError> {
                 feature: "image_source_non_base64",
             }),
         },
+        ContentPart::ToolCall(ToolCall {
+            id,
+            name,
+            arguments,
+            ..
+        }) => {
+            let id = id.clone().ok_or_else(|| LLMError::Validation {
+                message: "tool_call content requires id (mapped to tool_use id)".to_string(),
+            })?;
+            Ok(json!({
+                "type": "tool_use",
+                "id": id,
+                "name": name,
+                "input": arguments
+            }))
+        }
         ContentPart::ToolResult(ToolResult {
             call_id,
             output,
             is_error,
+            content,
             ..
         }) => {
             let tool_use_id = call_id.clone().ok_or_else(|| LLMError::Validation {
                 message: "tool_result content requires call_id (mapped to tool_use_id)".to_string(),
             })?;
-            let content_value = match output {
-                Value::String(text) => Value::String(text.clone()),
-                other => Value::String(other.to_string()),
+            let output_text = match output {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            let content_value = if content.is_empty() {
+                Value::String(output_text)
+            } else {
+                // Rich results (e.g. screenshots) become a block list; a null output adds no text.
+                let mut blocks = Vec::new();
+                if !output.is_null() {
+                    blocks.push(json!({ "type": "text", "text": output_text }));
+                }
+                for part in content {
+                    match part {
+                        ContentPart::Text(_) | ContentPart::Image(_) => {
+                            blocks.push(convert_content_part(part)?)
+                        }
+                        _ => {
+                            return Err(LLMError::UnsupportedFeature {
+                                feature: "anthropic_messages_tool_result_content",
+                            });
+                        }
+                    }
+                }
+                Value::Array(blocks)
             };
             Ok(json!({
                 "type": "tool_result",
@@ -183,17 +281,44 @@ fn convert_content_part(part: &ContentPart) -> Result<Value, LLMError> {
                 "is_error": is_error
             }))
         }
-        // Other multimodal inputs or tool calls are not handled directly; let callers use Data.
-        ContentPart::Audio(_)
-        | ContentPart::Video(_)
-        | ContentPart::File(_)
-        | ContentPart::ToolCall(_) => Err(LLMError::UnsupportedFeature {
-            feature: "anthropic_messages_content_type",
-        }),
+        // Other multimodal inputs are not handled directly; let callers use Data.
+        ContentPart::Audio(_) | ContentPart::Video(_) | ContentPart::File(_) => {
+            Err(LLMError::UnsupportedFeature {
+                feature: "anthropic_messages_content_type",
+            })
+        }
         ContentPart::Data { data } => Ok(data.clone()),
+        ContentPart::CacheBreakpoint(_) => Err(LLMError::Validation {
+            message: "cache breakpoints are attached to the preceding block".to_string(),
+        }),
     }
 }
 
+/// Sets `cache_control` on the last block, which ends the cached prefix.
+fn mark_cached(blocks: &mut [Value], cache: &CacheControl) -> Result<(), LLMError> {
+    let Some(Value::Object(block)) = blocks.last_mut() else {
+        return Err(LLMError::Validation {
+            message: "cache breakpoint must follow a content block".to_string(),
+        });
+    };
+    block.insert("cache_control".to_string(), cache_control(cache));
+    Ok(())
+}
+
+fn cache_control(cache: &CacheControl) -> Value {
+    let mut obj = json!({ "type": "ephemeral" });
+    if let Some(ttl) = cache.ttl {
+        obj["ttl"] = Value::String(
+            match ttl {
+                CacheTtl::FiveMinutes => "5m",
+                CacheTtl::OneHour => "1h",
+            }
+            .to_string(),
+        );
+    }
+    obj
+}
+
 fn build_thinking(reasoning: &ReasoningOptions) -> Result<Option<Value>, LLMError> {
     // If the caller already provides a full thinking payload via `extra`, forward it.
     if let Some(explicit) = reasoning.extra.get("thinking") {
@@ -235,18 +360,43 @@ fn convert_tools(tools: &[ToolDefinition]) -> Result<Vec<Value>, LLMError> {
                     result.push(json!({ "type": name, "name": tool.name }));
                 }
             }
+            ToolKind::ComputerUse => result.push(convert_computer_tool(tool)),
             _ => {
                 return Err(LLMError::Validation {
-                    message:
-                        "Anthropic tools currently only support function or custom tool configs"
-                            .to_string(),
+                    message: "Anthropic tools currently only support function, computer or custom tool configs"
+                        .to_string(),
                 });
             }
         }
+        if let Some(cache) = &tool.cache_control {
+            mark_cached(&mut result, cache)?;
+        }
     }
     Ok(result)
 }
 
+/// Builds the computer tool; the Responses-style `display_width` / `display_height`
+/// metadata keys are accepted alongside Anthropic's `*_px` names.
+fn convert_computer_tool(tool: &ToolDefinition) -> Value {
+    let mut obj = Map::new();
+    obj.insert(
+        "type".to_string(),
+        Value::String(COMPUTER_TOOL_TYPE.to_string()),
+    );
+    obj.insert("name".to_string(), Value::String(tool.name.clone()));
+    for (key, value) in tool.metadata.iter().flatten() {
+        let key = match key.as_str() {
+            "display_width" => "display_width_px",
+            "display_height" => "display_height_px",
+            // Only the Responses API takes an environment.
+            "environment" => continue,
+            other => other,
+        };
+        obj.insert(key.to_string(), value.clone());
+    }
+    Value::Object(obj)
+}
+
 fn convert_tool_choice(
     choice: &ToolChoice,
     parallel_tool_calls: bool,
@@ -377,4 +527,329 @@ mod tests {
         assert_eq!(messages.len(), 1);
         assert_eq!(messages[0]["role"], json!("user"));
     }
+
+    /// Maps top-k, stop sequences and the end-user id; rejects what Anthropic lacks.
+    #[test]
+    fn map_sampling_controls_and_end_user_id() {
+        let mut request = ChatRequest {
+            messages: vec![Message {
+                role: Role::user(),
+                name: None,
+                content: vec![ContentPart::Text(TextContent {
+                    text: "Hello!".to_string(),
+                })],
+                metadata: None,
+            }],
+            options: ChatOptions {
+                max_output_tokens: Some(128),
+                top_k: Some(40),
+                stop_sequences: Some(vec!["\n\nHuman:".to_string()]),
+                candidate_count: Some(1),
+                user: Some("user-42".to_string()),
+                ..ChatOptions::default()
+            },
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: Some([("trace".to_string(), json!("abc"))].into_iter().collect()),
+        };
+
+        let body =
+            build_anthropic_body(&request, "claude-3-5-sonnet-20241022", false).expect("build");
+        assert_eq!(body["top_k"], json!(40));
+        assert_eq!(body["stop_sequences"], json!(["\n\nHuman:"]));
+        assert_eq!(
+            body["metadata"],
+            json!({ "trace": "abc", "user_id": "user-42" })
+        );
+
+        request.options.seed = Some(1);
+        let err = build_anthropic_body(&request, "claude-3-5-sonnet-20241022", false)
+            .expect_err("seed is unsupported");
+        assert!(matches!(
+            err,
+            LLMError::UnsupportedFeature {
+                feature: "anthropic_messages_seed"
+            }
+        ));
+
+        request.options.seed = None;
+        request.options.candidate_count = Some(2);
+        let err = build_anthropic_body(&request, "claude-3-5-sonnet-20241022", false)
+            .expect_err("several candidates need several calls");
+        assert!(matches!(
+            err,
+            LLMError::UnsupportedFeature {
+                feature: "anthropic_messages_candidate_count"
+            }
+        ));
+
+        request.options.candidate_count = None;
+        request.options.logprobs = Some(true);
+        let err = build_anthropic_body(&request, "claude-3-5-sonnet-20241022", false)
+            .expect_err("logprobs are unsupported");
+        assert!(matches!(
+            err,
+            LLMError::UnsupportedFeature {
+                feature: "anthropic_messages_logprobs"
+            }
+        ));
+
+        request.options.logprobs = None;
+        request.options.top_logprobs = Some(3);
+        let err = build_anthropic_body(&request, "claude-3-5-sonnet-20241022", false)
+            .expect_err("top logprobs imply logprobs");
+        assert!(matches!(
+            err,
+            LLMError::UnsupportedFeature {
+                feature: "anthropic_messages_logprobs"
+            }
+        ));
+    }
+
+    /// Cache breakpoints set `cache_control` on the block before them, switching a
+    /// cached system prompt to block form.
+    #[test]
+    fn map_cache_breakpoints_to_cache_control() {
+        let request = ChatRequest {
+            messages: vec![
+                Message {
+                    role: Role::system(),
+                    name: None,
+                    content: vec![
+                        ContentPart::Text(TextContent {
+                            text: "Reference manual".to_string(),
+                        }),
+                        ContentPart::CacheBreakpoint(CacheControl {
+                            ttl: Some(CacheTtl::OneHour),
+                        }),
+                    ],
+                    metadata: None,
+                },
+                Message {
+                    role: Role::user(),
+                    name: None,
+                    content: vec![
+                        ContentPart::Text(TextContent {
+                            text: "Question".to_string(),
+                        }),
+                        ContentPart::CacheBreakpoint(CacheControl::default()),
+                    ],
+                    metadata: None,
+                },
+            ],
+            options: ChatOptions {
+                max_output_tokens: Some(128),
+                ..ChatOptions::default()
+            },
+            tools: vec![ToolDefinition {
+                name: "lookup".to_string(),
+                description: None,
+                input_schema: None,
+                kind: ToolKind::Function,
+                metadata: None,
+                cache_control: Some(CacheControl::default()),
+            }],
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        };
+
+        let body =
+            build_anthropic_body(&request, "claude-3-5-sonnet-20241022", false).expect("build");
+        assert_eq!(
+            body["system"],
+            json!([{
+                "type": "text",
+                "text": "Reference manual",
+                "cache_control": { "type": "ephemeral", "ttl": "1h" }
+            }])
+        );
+        assert_eq!(
+            body["messages"][0]["content"],
+            json!([{
+                "type": "text",
+                "text": "Question",
+                "cache_control": { "type": "ephemeral" }
+            }])
+        );
+        assert_eq!(
+            body["tools"][0]["cache_control"],
+            json!({ "type": "ephemeral" })
+        );
+
+        let mut request = request;
+        request.messages[1].content.remove(0);
+        let err = build_anthropic_body(&request, "claude-3-5-sonnet-20241022", false)
+            .expect_err("a breakpoint needs a preceding block");
+        assert!(matches!(err, LLMError::Validation { .. }));
+    }
+
+    /// Only the block right before a breakpoint is marked; uncached prompts and tools keep
+    /// their plain form.
+    #[test]
+    fn cache_control_lands_on_the_block_before_each_breakpoint() {
+        let text = |text: &str| {
+            ContentPart::Text(TextContent {
+                text: text.to_string(),
+            })
+        };
+        let tool = |name: &str, cache_control: Option<CacheControl>| ToolDefinition {
+            name: name.to_string(),
+            description: None,
+            input_schema: None,
+            kind: ToolKind::Function,
+            metadata: None,
+            cache_control,
+        };
+        let request = ChatRequest {
+            messages: vec![
+                Message {
+                    role: Role::system(),
+                    name: None,
+                    content: vec![text("Be terse.")],
+                    metadata: None,
+                },
+                Message {
+                    role: Role::user(),
+                    name: None,
+                    content: vec![
+                        text("Long document"),
+                        ContentPart::CacheBreakpoint(CacheControl::default()),
+                        text("Question"),
+                    ],
+                    metadata: None,
+                },
+            ],
+            options: ChatOptions {
+                max_output_tokens: Some(128),
+                ..ChatOptions::default()
+            },
+            tools: vec![
+                tool("search", None),
+                tool(
+                    "lookup",
+                    Some(CacheControl {
+                        ttl: Some(CacheTtl::OneHour),
+                    }),
+                ),
+            ],
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        };
+
+        let body =
+            build_anthropic_body(&request, "claude-3-5-sonnet-20241022", false).expect("build");
+
+        assert_eq!(body["system"], json!("Be terse."));
+        assert_eq!(
+            body["messages"][0]["content"],
+            json!([
+                {
+                    "type": "text",
+                    "text": "Long document",
+                    "cache_control": { "type": "ephemeral" }
+                },
+                { "type": "text", "text": "Question" }
+            ])
+        );
+        assert!(body["tools"][0].get("cache_control").is_none());
+        assert_eq!(
+            body["tools"][1]["cache_control"],
+            json!({ "type": "ephemeral", "ttl": "1h" })
+        );
+    }
+
+    /// Replays computer tool calls and answers them with screenshot blocks.
+    #[test]
+    fn map_computer_tool_calls_and_screenshot_results() {
+        let screenshot = ImageContent {
+            source: ImageSource::Base64 {
+                data: "iVBORw0".to_string(),
+                mime_type: Some("image/png".to_string()),
+            },
+            detail: None,
+            metadata: None,
+        };
+        let request = ChatRequest {
+            messages: vec![
+                Message {
+                    role: Role::user(),
+                    name: None,
+                    content: vec![ContentPart::Text(TextContent {
+                        text: "Open the menu".to_string(),
+                    })],
+                    metadata: None,
+                },
+                Message {
+                    role: Role::assistant(),
+                    name: None,
+                    content: vec![ContentPart::ToolCall(ToolCall {
+                        id: Some("toolu_1".to_string()),
+                        name: "computer".to_string(),
+                        arguments: json!({ "action": "left_click", "coordinate": [4, 2] }),
+                        kind: crate::types::ToolCallKind::ComputerUse,
+                    })],
+                    metadata: None,
+                },
+                Message {
+                    role: Role("tool".to_string()),
+                    name: None,
+                    content: vec![ContentPart::ToolResult(ToolResult {
+                        call_id: Some("toolu_1".to_string()),
+                        output: Value::Null,
+                        is_error: false,
+                        content: vec![ContentPart::Image(screenshot)],
+                        metadata: None,
+                    })],
+                    metadata: None,
+                },
+            ],
+            options: ChatOptions {
+                max_output_tokens: Some(128),
+                ..ChatOptions::default()
+            },
+            tools: vec![
+                crate::computer::ComputerDisplay {
+                    width: 1024,
+                    height: 768,
+                    environment: "browser".to_string(),
+                }
+                .tool_definition(),
+            ],
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        };
+
+        let body = build_anthropic_body(&request, "claude-sonnet-4-5", false).expect("build");
+
+        assert_eq!(
+            body["tools"][0],
+            json!({
+                "type": "computer_20250124",
+                "name": "computer",
+                "display_width_px": 1024,
+                "display_height_px": 768
+            })
+        );
+        assert_eq!(
+            body["messages"][1]["content"][0],
+            json!({
+                "type": "tool_use",
+                "id": "toolu_1",
+                "name": "computer",
+                "input": { "action": "left_click", "coordinate": [4, 2] }
+            })
+        );
+        assert_eq!(body["messages"][2]["role"], json!("user"));
+        assert_eq!(
+            body["messages"][2]["content"][0]["content"],
+            json!([{
+                "type": "image",
+                "source": { "type": "base64", "media_type": "image/png", "data": "iVBORw0" }
+            }])
+        );
+    }
 }
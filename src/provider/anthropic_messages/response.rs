@@ -1,11 +1,13 @@
 use std::collections::HashMap;
 
-use serde_json::json;
+use serde_json::{Value, json};
 
 use crate::error::LLMError;
 use crate::types::{
-    ChatResponse, ContentPart, FinishReason, ImageContent, ImageSource, Message, OutputItem,
-    ProviderMetadata, Role, TextContent, TokenUsage, ToolCall, ToolCallKind,
+    ChatResponse, Citation, CitationSource, ContentPart, FinishReason, HostedToolAction,
+    HostedToolCall, HostedToolOutput, HostedToolResult, ImageContent, ImageSource, Message,
+    OutputItem, ProviderMetadata, Role, TextContent, TokenUsage, ToolCall, ToolCallKind,
+    WebSearchResult,
 };
 
 use super::types::{
@@ -21,12 +23,31 @@ pub(crate) fn map_response(
 
     let mut outputs = Vec::new();
     let mut message_parts = Vec::new();
+    let mut citations = Vec::new();
+    let mut hosted = Vec::new();
     let mut tool_calls = Vec::new();
 
     for block in &resp.content {
         match convert_content_block(block)? {
-            ConvertedBlock::MessagePart(part) => message_parts.push(part),
+            ConvertedBlock::MessagePart(part) => {
+                // Citations on a text block cover that whole block.
+                if let Some(Value::Array(entries)) = block.extra.get("citations") {
+                    let part_index = message_parts.len();
+                    citations.extend(
+                        entries
+                            .iter()
+                            .filter_map(|entry| convert_citation(entry, part_index)),
+                    );
+                }
+                message_parts.push(part);
+            }
             ConvertedBlock::ToolCall(call) => tool_calls.push(call),
+            ConvertedBlock::HostedToolCall(call) => {
+                hosted.push(OutputItem::HostedToolCall { call, index: 0 })
+            }
+            ConvertedBlock::HostedToolResult(result) => {
+                hosted.push(OutputItem::HostedToolResult { result, index: 0 })
+            }
         }
     }
 
@@ -40,9 +61,12 @@ pub(crate) fn map_response(
                 metadata: None,
             },
             index: 0,
+            logprobs: Vec::new(),
+            citations,
         });
     }
 
+    outputs.extend(hosted);
     for call in tool_calls {
         outputs.push(OutputItem::ToolCall { call, index: 0 });
     }
@@ -54,6 +78,8 @@ pub(crate) fn map_response(
         outputs,
         usage,
         finish_reason,
+        candidate_finish_reasons: Vec::new(),
+        prompt_safety: None,
         model: Some(resp.model),
         provider: ProviderMetadata {
             provider: provider.to_string(),
@@ -67,6 +93,8 @@ pub(crate) fn map_response(
 enum ConvertedBlock {
     MessagePart(ContentPart),
     ToolCall(ToolCall),
+    HostedToolCall(HostedToolCall),
+    HostedToolResult(HostedToolResult),
 }
 
 fn convert_content_block(block: &AnthropicContentBlock) -> Result<ConvertedBlock, LLMError> {
@@ -93,14 +121,32 @@ fn convert_content_block(block: &AnthropicContentBlock) -> Result<ConvertedBlock
             let id = block.id.clone();
             let name = block.name.clone().unwrap_or_default();
             let input = block.input.clone().unwrap_or_else(|| json!({}));
+            // The computer tool must be named `computer`; everything else is a function call.
+            let kind = if name == "computer" {
+                ToolCallKind::ComputerUse
+            } else {
+                ToolCallKind::Function
+            };
             Ok(ConvertedBlock::ToolCall(ToolCall {
                 id,
                 name,
                 arguments: input,
-                // Anthropic tool calls are effectively function calls, so treat them uniformly.
-                kind: ToolCallKind::Function,
+                kind,
             }))
         }
+        // Server tools (web search, code execution, ...) run on Anthropic's side.
+        "server_tool_use" => Ok(ConvertedBlock::HostedToolCall(convert_server_tool_use(
+            block.id.clone(),
+            block.name.as_deref().unwrap_or_default(),
+            block.input.as_ref().unwrap_or(&Value::Null),
+        ))),
+        kind if kind.ends_with("_tool_result") && kind != "tool_result" => Ok(
+            ConvertedBlock::HostedToolResult(convert_server_tool_result(
+                kind,
+                block.tool_use_id.clone(),
+                block.content.as_ref().unwrap_or(&Value::Null),
+            )),
+        ),
         // Tool results and documents are forwarded as Data to let callers parse them.
         "tool_result" | "document" => {
             let value = serde_json::to_value(block).unwrap_or_else(|_| json!({}));
@@ -117,6 +163,131 @@ fn convert_content_block(block: &AnthropicContentBlock) -> Result<ConvertedBlock
     }
 }
 
+/// Converts a `server_tool_use` block; tools without a typed action keep their input.
+pub(crate) fn convert_server_tool_use(
+    id: Option<String>,
+    name: &str,
+    input: &Value,
+) -> HostedToolCall {
+    let string = |key: &str| input.get(key).and_then(Value::as_str).map(str::to_string);
+    let action = match name {
+        "web_search" => HostedToolAction::WebSearch {
+            query: string("query"),
+        },
+        "code_execution" => HostedToolAction::CodeExecution {
+            language: Some("python".to_string()),
+            code: string("code").unwrap_or_default(),
+        },
+        _ => HostedToolAction::Other {
+            name: name.to_string(),
+            input: input.clone(),
+        },
+    };
+    HostedToolCall {
+        id,
+        action,
+        status: None,
+    }
+}
+
+/// Converts a server tool result block such as `web_search_tool_result`; failures carry
+/// the `error_code` of the `*_tool_result_error` content.
+pub(crate) fn convert_server_tool_result(
+    kind: &str,
+    tool_use_id: Option<String>,
+    content: &Value,
+) -> HostedToolResult {
+    let string =
+        |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    let mut error = string(content, "error_code");
+    let output = match kind {
+        "web_search_tool_result" => HostedToolOutput::WebSearch {
+            results: content
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|result| {
+                    Some(WebSearchResult {
+                        url: string(result, "url")?,
+                        title: string(result, "title"),
+                        page_age: string(result, "page_age"),
+                    })
+                })
+                .collect(),
+        },
+        "code_execution_tool_result" if error.is_none() => {
+            let return_code = content.get("return_code").and_then(Value::as_i64);
+            if let Some(code) = return_code.filter(|code| *code != 0) {
+                error = string(content, "stderr")
+                    .filter(|stderr| !stderr.is_empty())
+                    .or_else(|| Some(format!("exit code {code}")));
+            }
+            HostedToolOutput::CodeExecution {
+                outcome: return_code.map(|code| code.to_string()),
+                output: string(content, "stdout").unwrap_or_default(),
+            }
+        }
+        _ => HostedToolOutput::Other {
+            name: kind.trim_end_matches("_tool_result").to_string(),
+            content: content.clone(),
+        },
+    };
+    HostedToolResult {
+        call_id: tool_use_id,
+        output,
+        error,
+    }
+}
+
+/// Converts a text block citation (document, page, search result or web search location).
+pub(crate) fn convert_citation(value: &Value, part: usize) -> Option<Citation> {
+    let Value::Object(map) = value else {
+        return None;
+    };
+    let string = |key: &str| map.get(key).and_then(Value::as_str).map(str::to_string);
+    let index = |key: &str| map.get(key).and_then(Value::as_u64).map(|v| v as usize);
+    let (source, mapped): (CitationSource, &[&str]) = match map.get("type")?.as_str()? {
+        "web_search_result_location" => (
+            CitationSource::Url {
+                url: string("url")?,
+                title: string("title"),
+            },
+            &["url", "title"],
+        ),
+        "search_result_location" => (
+            CitationSource::Document {
+                index: index("search_result_index"),
+                id: string("source"),
+                title: string("title"),
+            },
+            &["search_result_index", "source", "title"],
+        ),
+        _ => (
+            CitationSource::Document {
+                index: index("document_index"),
+                id: string("file_id"),
+                title: string("document_title"),
+            },
+            &["document_index", "file_id", "document_title"],
+        ),
+    };
+    // Location fields such as `start_char_index` or `start_page_number` stay as metadata.
+    let metadata: HashMap<String, Value> = map
+        .iter()
+        .filter(|(key, value)| {
+            key.as_str() != "cited_text" && !mapped.contains(&key.as_str()) && !value.is_null()
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    Some(Citation {
+        part,
+        span: None,
+        source,
+        cited_text: string("cited_text"),
+        metadata: Some(metadata),
+    })
+}
+
 fn convert_image_source(source: &AnthropicImageSource) -> ImageContent {
     ImageContent {
         source: ImageSource::Base64 {
@@ -156,6 +327,8 @@ pub(crate) fn convert_usage(usage: &AnthropicUsage) -> TokenUsage {
             .input_tokens
             .zip(usage.output_tokens)
             .map(|(i, o)| i + o),
+        cached_input_tokens: usage.cache_read_input_tokens,
+        cache_creation_tokens: usage.cache_creation_input_tokens,
         details: if details.is_empty() {
             None
         } else {
@@ -218,7 +391,7 @@ mod tests {
 
         assert_eq!(mapped.outputs.len(), 1);
         match &mapped.outputs[0] {
-            OutputItem::Message { message, index } => {
+            OutputItem::Message { message, index, .. } => {
                 assert_eq!(*index, 0);
                 assert_eq!(message.role.0, "assistant");
                 assert_eq!(message.content.len(), 1);
@@ -284,4 +457,139 @@ mod tests {
             Some(FinishReason::ToolCalls)
         ));
     }
+
+    #[test]
+    fn map_text_block_citations() {
+        let resp: AnthropicMessageResponse = serde_json::from_value(json!({
+            "id": "msg_2",
+            "model": "claude-sonnet-4-5",
+            "content": [
+                { "type": "text", "text": "According to the report, " },
+                {
+                    "type": "text",
+                    "text": "revenue grew 12%.",
+                    "citations": [{
+                        "type": "char_location",
+                        "cited_text": "Revenue grew 12% year over year.",
+                        "document_index": 0,
+                        "document_title": "Annual report",
+                        "start_char_index": 120,
+                        "end_char_index": 152
+                    }, {
+                        "type": "web_search_result_location",
+                        "cited_text": "Revenue up 12%",
+                        "url": "https://example.com/news",
+                        "title": "News",
+                        "encrypted_index": "abc"
+                    }]
+                }
+            ],
+            "stop_reason": "end_turn"
+        }))
+        .expect("response");
+
+        let mapped = map_response(resp, "anthropic_messages", "endpoint".into()).expect("map");
+
+        let OutputItem::Message { citations, .. } = &mapped.outputs[0] else {
+            panic!("unexpected output item: {:?}", mapped.outputs[0]);
+        };
+        assert_eq!(citations.len(), 2);
+        assert!(citations.iter().all(|citation| citation.part == 1));
+        assert_eq!(
+            citations[0].source,
+            CitationSource::Document {
+                index: Some(0),
+                id: None,
+                title: Some("Annual report".to_string()),
+            }
+        );
+        let location = citations[0].metadata.as_ref().expect("metadata");
+        assert_eq!(location["start_char_index"], json!(120));
+        assert_eq!(location["type"], json!("char_location"));
+        assert!(matches!(
+            &citations[1].source,
+            CitationSource::Url { url, title: Some(title) }
+                if url == "https://example.com/news" && title == "News"
+        ));
+        assert_eq!(citations[1].cited_text.as_deref(), Some("Revenue up 12%"));
+    }
+
+    #[test]
+    fn map_server_tool_blocks_to_hosted_tool_outputs() {
+        let resp: AnthropicMessageResponse = serde_json::from_value(json!({
+            "id": "msg_3",
+            "model": "claude-sonnet-4-5",
+            "content": [
+                {
+                    "type": "server_tool_use",
+                    "id": "srvtoolu_1",
+                    "name": "web_search",
+                    "input": { "query": "rust release date" }
+                },
+                {
+                    "type": "web_search_tool_result",
+                    "tool_use_id": "srvtoolu_1",
+                    "content": [{
+                        "type": "web_search_result",
+                        "url": "https://blog.rust-lang.org",
+                        "title": "Rust Blog",
+                        "encrypted_content": "xyz",
+                        "page_age": "2 days ago"
+                    }]
+                },
+                {
+                    "type": "code_execution_tool_result",
+                    "tool_use_id": "srvtoolu_2",
+                    "content": {
+                        "type": "code_execution_result",
+                        "stdout": "",
+                        "stderr": "NameError: x",
+                        "return_code": 1
+                    }
+                },
+                { "type": "text", "text": "Rust 1.0 shipped in 2015." }
+            ],
+            "stop_reason": "end_turn"
+        }))
+        .expect("response");
+
+        let mapped = map_response(resp, "anthropic_messages", "endpoint".into()).expect("map");
+
+        assert_eq!(mapped.outputs.len(), 4);
+        assert!(matches!(&mapped.outputs[0], OutputItem::Message { .. }));
+        let OutputItem::HostedToolCall { call, index } = &mapped.outputs[1] else {
+            panic!("unexpected output item: {:?}", mapped.outputs[1]);
+        };
+        assert_eq!(*index, 0);
+        assert_eq!(call.id.as_deref(), Some("srvtoolu_1"));
+        assert_eq!(
+            call.action,
+            HostedToolAction::WebSearch {
+                query: Some("rust release date".to_string())
+            }
+        );
+        let OutputItem::HostedToolResult { result, .. } = &mapped.outputs[2] else {
+            panic!("unexpected output item: {:?}", mapped.outputs[2]);
+        };
+        assert_eq!(result.call_id.as_deref(), Some("srvtoolu_1"));
+        assert_eq!(
+            result.output,
+            HostedToolOutput::WebSearch {
+                results: vec![WebSearchResult {
+                    url: "https://blog.rust-lang.org".to_string(),
+                    title: Some("Rust Blog".to_string()),
+                    page_age: Some("2 days ago".to_string()),
+                }]
+            }
+        );
+        assert!(result.error.is_none());
+        let OutputItem::HostedToolResult { result, .. } = &mapped.outputs[3] else {
+            panic!("unexpected output item: {:?}", mapped.outputs[3]);
+        };
+        assert_eq!(result.error.as_deref(), Some("NameError: x"));
+        assert!(matches!(
+            &result.output,
+            HostedToolOutput::CodeExecution { outcome: Some(outcome), .. } if outcome == "1"
+        ));
+    }
 }
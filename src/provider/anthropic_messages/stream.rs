@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use futures_util::StreamExt;
 use serde_json::{Value, json};
 
@@ -9,13 +11,25 @@ use crate::types::{
     ChatChunk, ChatEvent, ContentDelta, MessageDelta, ProviderMetadata, Role, TokenUsage,
 };
 
-use super::response::{convert_finish_reason, convert_usage};
+use super::response::{
+    convert_citation, convert_finish_reason, convert_server_tool_result, convert_server_tool_use,
+    convert_usage,
+};
+
+/// `server_tool_use` block whose input is still streaming as `input_json_delta`s.
+struct PendingServerTool {
+    id: Option<String>,
+    name: String,
+    input_json: String,
+}
 
 pub(crate) fn create_stream(
     body: HttpBodyStream,
     provider: &'static str,
     endpoint: String,
 ) -> ChatStream {
+    // Server tool calls are reported once their input is complete, keyed by block index.
+    let mut server_tools = HashMap::new();
     let stream = StreamDecoder::new(body, provider).map(move |event| match event {
         Ok(StreamEvent::Data(data)) => {
             let value: Value = serde_json::from_str(&data).map_err(|err| LLMError::Provider {
@@ -28,7 +42,7 @@ pub(crate) fn create_stream(
                 .unwrap_or_default()
                 .to_string();
             let is_terminal = event_type == "message_stop";
-            convert_stream_event(value, provider, &endpoint, is_terminal)
+            convert_stream_event(value, &mut server_tools, provider, &endpoint, is_terminal)
         }
         Ok(StreamEvent::Done) => Ok(ChatChunk {
             events: Vec::new(),
@@ -62,6 +76,7 @@ pub(crate) async fn collect_stream_text(
 
 fn convert_stream_event(
     event: Value,
+    server_tools: &mut HashMap<usize, PendingServerTool>,
     provider: &'static str,
     endpoint: &str,
     is_terminal: bool,
@@ -70,19 +85,74 @@ fn convert_stream_event(
     let mut usage: Option<TokenUsage> = None;
 
     if let Some(kind) = event.get("type").and_then(|v| v.as_str()) {
+        let block = event.get("index").and_then(Value::as_u64).unwrap_or(0) as usize;
         match kind {
+            "content_block_start" => {
+                let content_block = event.get("content_block").unwrap_or(&Value::Null);
+                let string = |key: &str| content_block.get(key).and_then(Value::as_str);
+                match string("type").unwrap_or_default() {
+                    "server_tool_use" => {
+                        server_tools.insert(
+                            block,
+                            PendingServerTool {
+                                id: string("id").map(str::to_string),
+                                name: string("name").unwrap_or_default().to_string(),
+                                input_json: String::new(),
+                            },
+                        );
+                    }
+                    // Result blocks arrive complete in their start event.
+                    kind if kind.ends_with("_tool_result") && kind != "tool_result" => {
+                        events.push(ChatEvent::HostedToolResult {
+                            index: 0,
+                            result: convert_server_tool_result(
+                                kind,
+                                string("tool_use_id").map(str::to_string),
+                                content_block.get("content").unwrap_or(&Value::Null),
+                            ),
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            "content_block_stop" => {
+                if let Some(pending) = server_tools.remove(&block) {
+                    let input = serde_json::from_str(&pending.input_json).unwrap_or(json!({}));
+                    events.push(ChatEvent::HostedToolCall {
+                        index: 0,
+                        call: convert_server_tool_use(pending.id, &pending.name, &input),
+                    });
+                }
+            }
             "content_block_delta" => {
                 if let Some(delta) = event.get("delta") {
-                    if let Some(text) = delta.get("text").and_then(|v| v.as_str()) {
-                        let index =
-                            event.get("index").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+                    if let (Some(pending), Some(json)) = (
+                        server_tools.get_mut(&block),
+                        delta.get("partial_json").and_then(Value::as_str),
+                    ) {
+                        pending.input_json.push_str(json);
+                    } else if let Some(text) = delta.get("text").and_then(|v| v.as_str()) {
+                        // `index` counts content blocks; Anthropic has a single candidate.
                         events.push(ChatEvent::MessageDelta(MessageDelta {
-                            index,
+                            index: 0,
                             role: Some(Role::assistant()),
                             content: vec![ContentDelta::Text {
                                 text: text.to_string(),
                             }],
                             finish_reason: None,
+                            logprobs: Vec::new(),
+                        }));
+                    } else if let Some(citation) = delta
+                        .get("citation")
+                        .and_then(|citation| convert_citation(citation, block))
+                    {
+                        // `citations_delta` attaches a source to the text block being streamed.
+                        events.push(ChatEvent::MessageDelta(MessageDelta {
+                            index: 0,
+                            role: Some(Role::assistant()),
+                            content: vec![ContentDelta::Citation { citation }],
+                            finish_reason: None,
+                            logprobs: Vec::new(),
                         }));
                     }
                 }
@@ -109,6 +179,7 @@ fn convert_stream_event(
                             role: Some(Role::assistant()),
                             content: Vec::new(),
                             finish_reason: Some(reason),
+                            logprobs: Vec::new(),
                         }));
                     }
                 }
@@ -141,6 +212,7 @@ fn convert_stream_event(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{HostedToolAction, HostedToolOutput};
     use serde_json::json;
 
     #[test]
@@ -150,8 +222,14 @@ mod tests {
             "index": 0,
             "delta": { "text": "Once upon a time" }
         });
-        let chunk =
-            convert_stream_event(event, "anthropic_messages", "endpoint", false).expect("convert");
+        let chunk = convert_stream_event(
+            event,
+            &mut HashMap::new(),
+            "anthropic_messages",
+            "endpoint",
+            false,
+        )
+        .expect("convert");
 
         assert!(!chunk.is_terminal);
         assert_eq!(chunk.provider.provider, "anthropic_messages");
@@ -172,8 +250,14 @@ mod tests {
                 }
             }
         });
-        let chunk =
-            convert_stream_event(event, "anthropic_messages", "endpoint", false).expect("convert");
+        let chunk = convert_stream_event(
+            event,
+            &mut HashMap::new(),
+            "anthropic_messages",
+            "endpoint",
+            false,
+        )
+        .expect("convert");
 
         assert!(chunk.usage.is_some());
         let usage = chunk.usage.as_ref().unwrap();
@@ -181,4 +265,119 @@ mod tests {
         assert_eq!(usage.completion_tokens, Some(5));
         assert_eq!(usage.total_tokens, Some(15));
     }
+
+    #[test]
+    fn convert_citations_delta_event() {
+        let event = json!({
+            "type": "content_block_delta",
+            "index": 2,
+            "delta": {
+                "type": "citations_delta",
+                "citation": {
+                    "type": "page_location",
+                    "cited_text": "The sky is blue.",
+                    "document_index": 1,
+                    "start_page_number": 3,
+                    "end_page_number": 4
+                }
+            }
+        });
+        let chunk = convert_stream_event(
+            event,
+            &mut HashMap::new(),
+            "anthropic_messages",
+            "endpoint",
+            false,
+        )
+        .expect("convert");
+
+        let ChatEvent::MessageDelta(delta) = &chunk.events[0] else {
+            panic!("unexpected event: {:?}", chunk.events);
+        };
+        match &delta.content[0] {
+            ContentDelta::Citation { citation } => {
+                assert_eq!(citation.part, 2);
+                assert_eq!(citation.cited_text.as_deref(), Some("The sky is blue."));
+                assert_eq!(
+                    citation.metadata.as_ref().unwrap()["start_page_number"],
+                    json!(3)
+                );
+            }
+            other => panic!("unexpected content delta: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn accumulate_server_tool_use_input_until_block_stop() {
+        let mut server_tools = HashMap::new();
+        let events = [
+            json!({
+                "type": "content_block_start",
+                "index": 1,
+                "content_block": {
+                    "type": "server_tool_use",
+                    "id": "srvtoolu_1",
+                    "name": "web_search",
+                    "input": {}
+                }
+            }),
+            json!({
+                "type": "content_block_delta",
+                "index": 1,
+                "delta": { "type": "input_json_delta", "partial_json": "{\"query\": \"ru" }
+            }),
+            json!({
+                "type": "content_block_delta",
+                "index": 1,
+                "delta": { "type": "input_json_delta", "partial_json": "st\"}" }
+            }),
+            json!({ "type": "content_block_stop", "index": 1 }),
+            json!({
+                "type": "content_block_start",
+                "index": 2,
+                "content_block": {
+                    "type": "web_search_tool_result",
+                    "tool_use_id": "srvtoolu_1",
+                    "content": [{ "type": "web_search_result", "url": "https://www.rust-lang.org" }]
+                }
+            }),
+        ];
+        let hosted: Vec<ChatEvent> = events
+            .into_iter()
+            .flat_map(|event| {
+                convert_stream_event(
+                    event,
+                    &mut server_tools,
+                    "anthropic_messages",
+                    "endpoint",
+                    false,
+                )
+                .expect("convert")
+                .events
+            })
+            .filter(|event| !matches!(event, ChatEvent::Custom { .. }))
+            .collect();
+
+        assert_eq!(hosted.len(), 2);
+        let ChatEvent::HostedToolCall { call, .. } = &hosted[0] else {
+            panic!("unexpected event: {:?}", hosted[0]);
+        };
+        assert_eq!(call.id.as_deref(), Some("srvtoolu_1"));
+        assert_eq!(
+            call.action,
+            HostedToolAction::WebSearch {
+                query: Some("rust".to_string())
+            }
+        );
+        let ChatEvent::HostedToolResult { result, .. } = &hosted[1] else {
+            panic!("unexpected event: {:?}", hosted[1]);
+        };
+        assert_eq!(result.call_id.as_deref(), Some("srvtoolu_1"));
+        assert!(matches!(
+            &result.output,
+            HostedToolOutput::WebSearch { results }
+                if results.len() == 1 && results[0].url == "https://www.rust-lang.org"
+        ));
+        assert!(server_tools.is_empty());
+    }
 }
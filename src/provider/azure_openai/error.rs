@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::LLMError;
+use crate::provider::openai_chat::error::parse_openai_error;
+use crate::provider::openai_chat::response::convert_content_filter_results;
+use crate::types::SafetyInfo;
+
+/// Parses Azure OpenAI error payloads.
+///
+/// Requests rejected by Azure's content management policy become
+/// [`LLMError::PromptBlocked`] with the per-category results; every other payload follows
+/// the OpenAI error schema and is parsed as such, re-attributed to `azure_openai`.
+pub(crate) fn parse_azure_error(
+    status: u16,
+    body: &str,
+    retry_after: Option<Duration>,
+) -> LLMError {
+    if let Some(error) = content_filter_error(body) {
+        return error;
+    }
+    match parse_openai_error(status, body, retry_after) {
+        LLMError::Provider { message, .. } => LLMError::Provider {
+            provider: "azure_openai",
+            message,
+        },
+        other => other,
+    }
+}
+
+fn content_filter_error(body: &str) -> Option<LLMError> {
+    #[derive(Deserialize)]
+    struct ErrorBody {
+        error: InnerError,
+    }
+    #[derive(Deserialize)]
+    struct InnerError {
+        message: Option<String>,
+        code: Option<String>,
+        innererror: Option<AzureInnerError>,
+    }
+    #[derive(Deserialize)]
+    struct AzureInnerError {
+        code: Option<String>,
+        content_filter_result: Option<Value>,
+    }
+
+    let error = serde_json::from_str::<ErrorBody>(body).ok()?.error;
+    let inner_code = error
+        .innererror
+        .as_ref()
+        .and_then(|inner| inner.code.as_deref());
+    let filtered = error.code.as_deref() == Some("content_filter")
+        || inner_code == Some("ResponsibleAIPolicyViolation");
+    if !filtered {
+        return None;
+    }
+    let ratings = error
+        .innererror
+        .and_then(|inner| inner.content_filter_result)
+        .map(|result| convert_content_filter_results(&result))
+        .unwrap_or_default();
+    Some(LLMError::PromptBlocked {
+        provider: "azure_openai",
+        safety: SafetyInfo {
+            ratings,
+            block_reason: Some("content_filter".to_string()),
+            message: error.message,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SafetyLevel;
+
+    #[test]
+    fn content_filter_errors_name_the_categories() {
+        let body = r#"{
+  "error": {
+    "message": "The response was filtered due to the prompt triggering Azure OpenAI's content management policy.",
+    "type": null,
+    "param": "prompt",
+    "code": "content_filter",
+    "status": 400,
+    "innererror": {
+      "code": "ResponsibleAIPolicyViolation",
+      "content_filter_result": {
+        "hate": { "filtered": false, "severity": "safe" },
+        "jailbreak": { "filtered": true, "detected": true },
+        "violence": { "filtered": true, "severity": "medium" }
+      }
+    }
+  }
+}"#;
+        let error = parse_azure_error(400, body, None);
+        let message = error.to_string();
+        assert!(message.contains("content management policy"), "{message}");
+        assert!(
+            message.ends_with("[jailbreak, violence: medium]"),
+            "{message}"
+        );
+        match error {
+            LLMError::PromptBlocked { provider, safety } => {
+                assert_eq!(provider, "azure_openai");
+                assert_eq!(safety.blocked_categories(), ["jailbreak", "violence"]);
+                assert_eq!(safety.ratings[0].category, "hate");
+                assert_eq!(safety.ratings[0].severity, Some(SafetyLevel::Negligible));
+                assert_eq!(
+                    safety.ratings[1].metadata.as_ref().unwrap()["detected"],
+                    serde_json::json!(true)
+                );
+            }
+            other => panic!("expected PromptBlocked error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn other_errors_follow_the_openai_schema() {
+        let body = r#"{"error":{"code":"DeploymentNotFound","message":"The API deployment for this resource does not exist."}}"#;
+        assert!(matches!(
+            parse_azure_error(404, body, None),
+            LLMError::ModelNotFound { .. }
+        ));
+
+        let body =
+            r#"{"error":{"code":"429","message":"Requests have exceeded the call rate limit."}}"#;
+        assert!(matches!(
+            parse_azure_error(429, body, Some(Duration::from_secs(7))),
+            LLMError::RateLimit { retry_after: Some(retry), .. } if retry == Duration::from_secs(7)
+        ));
+
+        match parse_azure_error(502, "bad gateway", None) {
+            LLMError::Provider { provider, .. } => assert_eq!(provider, "azure_openai"),
+            other => panic!("expected Provider error, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,4 @@
+mod error;
+mod provider;
+
+pub use provider::{AzureApi, AzureOpenAiProvider};
@@ -0,0 +1,696 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::config::{Credential, RequestPatch};
+use crate::credential::{DynCredentialProvider, StaticCredential, invalidate_on_unauthorized};
+use crate::error::LLMError;
+use crate::http::{
+    DynHttpTransport, HttpResponse, HttpStreamResponse, post_json_stream_with_headers,
+    post_json_with_headers,
+};
+use crate::provider::openai_chat::request::build_openai_body;
+use crate::provider::openai_chat::response::map_response;
+use crate::provider::openai_chat::stream as chat_stream;
+use crate::provider::openai_chat::types::OpenAiChatResponse;
+use crate::provider::openai_responses::request::build_openai_responses_body;
+use crate::provider::openai_responses::response::map_responses_response;
+use crate::provider::openai_responses::stream as responses_stream;
+use crate::provider::openai_responses::types::OpenAiResponsesResponse;
+use crate::provider::{ChatStream, LLMProvider, PreparedRequest, retry::retry_after_from_headers};
+use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
+
+use super::error::parse_azure_error;
+
+const DEFAULT_CHAT_API_VERSION: &str = "2024-10-21";
+const DEFAULT_RESPONSES_API_VERSION: &str = "2025-04-01-preview";
+
+/// Azure OpenAI API surface a provider talks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AzureApi {
+    /// `/openai/deployments/{deployment}/chat/completions`, mapped like `openai_chat`.
+    #[default]
+    ChatCompletions,
+    /// `/openai/responses`, mapped like `openai_responses`.
+    Responses,
+}
+
+/// How the credential is presented to Azure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AzureAuth {
+    /// Resource key sent in the `api-key` header.
+    ApiKey,
+    /// Microsoft Entra ID token sent as `Authorization: Bearer`.
+    Entra,
+}
+
+/// Azure OpenAI provider implementation.
+///
+/// Reuses the OpenAI Chat Completions and Responses mappers but addresses models through
+/// deployment-scoped URLs on an Azure resource endpoint and appends the `api-version`
+/// query parameter. The deployment is derived from the request's model, so switching
+/// models never requires rewriting URLs by hand.
+pub struct AzureOpenAiProvider {
+    pub(crate) transport: DynHttpTransport,
+    pub(crate) base_url: String,
+    pub(crate) credential: DynCredentialProvider,
+    auth: AzureAuth,
+    pub(crate) api: AzureApi,
+    pub(crate) api_version: Option<String>,
+    pub(crate) deployments: HashMap<String, String>,
+    pub(crate) default_model: Option<String>,
+    pub(crate) request_patch: Option<RequestPatch>,
+}
+
+impl AzureOpenAiProvider {
+    /// `ModelConfig.extra` keys read by [`Self::from_model_config`].
+    pub const SUPPORTED_EXTRA_KEYS: &'static [&'static str] =
+        &["api", "api_version", "auth", "deployments"];
+
+    /// Creates a provider for the resource at `endpoint` (e.g.
+    /// `https://my-resource.openai.azure.com`) that authenticates with an `api-key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use kotoba_llm::provider::azure_openai::AzureOpenAiProvider;
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let provider =
+    ///     AzureOpenAiProvider::new(transport, "https://my-resource.openai.azure.com", "azure-key");
+    /// assert_eq!(provider.name(), "azure_openai");
+    /// # }
+    /// ```
+    pub fn new(
+        transport: DynHttpTransport,
+        endpoint: impl Into<String>,
+        api_key: impl Into<String>,
+    ) -> Self {
+        Self::from_credential_provider(
+            transport,
+            endpoint,
+            Arc::new(StaticCredential::new(api_key)),
+        )
+    }
+
+    /// Creates a provider that asks `credential` for its secret before every request.
+    ///
+    /// The secret is sent as an `api-key` unless [`Self::with_entra_auth`] is called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::credential::{CachedCredential, CommandCredential};
+    /// # use kotoba_llm::provider::azure_openai::AzureOpenAiProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let token = CommandCredential::new("az").with_args([
+    ///     "account", "get-access-token", "--resource", "https://cognitiveservices.azure.com",
+    ///     "--query", "accessToken", "-o", "tsv",
+    /// ]);
+    /// let provider = AzureOpenAiProvider::from_credential_provider(
+    ///     transport,
+    ///     "https://my-resource.openai.azure.com",
+    ///     Arc::new(CachedCredential::new(token)),
+    /// )
+    /// .with_entra_auth();
+    /// # let _ = provider;
+    /// # }
+    /// ```
+    pub fn from_credential_provider(
+        transport: DynHttpTransport,
+        endpoint: impl Into<String>,
+        credential: DynCredentialProvider,
+    ) -> Self {
+        Self {
+            transport,
+            base_url: endpoint.into(),
+            credential,
+            auth: AzureAuth::ApiKey,
+            api: AzureApi::default(),
+            api_version: None,
+            deployments: HashMap::new(),
+            default_model: None,
+            request_patch: None,
+        }
+    }
+
+    /// Sends the credential as a Microsoft Entra ID bearer token instead of an `api-key`.
+    pub fn with_entra_auth(mut self) -> Self {
+        self.auth = AzureAuth::Entra;
+        self
+    }
+
+    /// Selects the Chat Completions or Responses API.
+    pub fn with_api(mut self, api: AzureApi) -> Self {
+        self.api = api;
+        self
+    }
+
+    /// Overrides the `api-version` query parameter.
+    ///
+    /// Defaults to `2024-10-21` for Chat Completions and `2025-04-01-preview` for Responses.
+    pub fn with_api_version(mut self, api_version: impl Into<String>) -> Self {
+        self.api_version = Some(api_version.into());
+        self
+    }
+
+    /// Routes `model` to a deployment with a different name.
+    ///
+    /// Models without a mapping are sent to the deployment of the same name.
+    pub fn with_deployment(
+        mut self,
+        model: impl Into<String>,
+        deployment: impl Into<String>,
+    ) -> Self {
+        self.deployments.insert(model.into(), deployment.into());
+        self
+    }
+
+    /// Configures a default model when the [`ChatRequest`] omits one.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.default_model = Some(model.into());
+        self
+    }
+
+    /// Constructs a provider from a [`crate::config::ModelConfig`].
+    ///
+    /// `base_url` is the resource endpoint and is required. `Credential::ApiKey` uses the
+    /// `api-key` header and `Credential::Bearer` an Entra token; `extra.auth` (`api_key` or
+    /// `entra`) overrides this, which is how `Credential::Source` tokens are marked as
+    /// Entra tokens. `extra.api` selects `chat_completions` (default) or `responses`,
+    /// `extra.api_version` overrides the API version and `extra.deployments` maps model
+    /// names to deployment names.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Auth`] when credentials are missing or invalid, and
+    /// [`LLMError::InvalidConfig`] when `base_url` is missing or an `extra` value is malformed.
+    pub fn from_model_config(
+        config: &crate::config::ModelConfig,
+        transport: DynHttpTransport,
+    ) -> Result<Self, LLMError> {
+        let credential = config.credential.credential_provider("azure_openai")?;
+        let base_url = config
+            .base_url
+            .clone()
+            .ok_or_else(|| LLMError::InvalidConfig {
+                field: "base_url".to_string(),
+                reason: "Azure OpenAI requires the resource endpoint, e.g. https://my-resource.openai.azure.com".to_string(),
+            })?;
+        let mut provider = Self::from_credential_provider(transport, base_url, credential);
+
+        let entra = match config.extra.get("auth") {
+            None => matches!(config.credential, Credential::Bearer { .. }),
+            Some(Value::String(auth)) if auth == "api_key" => false,
+            Some(Value::String(auth)) if auth == "entra" => true,
+            Some(other) => {
+                return Err(invalid_extra(
+                    "auth",
+                    format!("expected \"api_key\" or \"entra\", got {other}"),
+                ));
+            }
+        };
+        if entra {
+            provider = provider.with_entra_auth();
+        }
+
+        match config.extra.get("api") {
+            None => {}
+            Some(Value::String(api)) if api == "chat_completions" => {}
+            Some(Value::String(api)) if api == "responses" => {
+                provider = provider.with_api(AzureApi::Responses);
+            }
+            Some(other) => {
+                return Err(invalid_extra(
+                    "api",
+                    format!("expected \"chat_completions\" or \"responses\", got {other}"),
+                ));
+            }
+        }
+
+        match config.extra.get("api_version") {
+            None => {}
+            Some(Value::String(version)) => {
+                provider = provider.with_api_version(version.clone());
+            }
+            Some(other) => {
+                return Err(invalid_extra(
+                    "api_version",
+                    format!("expected a string, got {other}"),
+                ));
+            }
+        }
+
+        if let Some(deployments) = config.extra.get("deployments") {
+            let Some(deployments) = deployments.as_object() else {
+                return Err(invalid_extra(
+                    "deployments",
+                    "expected a table of model name to deployment name".to_string(),
+                ));
+            };
+            for (model, deployment) in deployments {
+                let Some(deployment) = deployment.as_str() else {
+                    return Err(invalid_extra(
+                        "deployments",
+                        format!("deployment for `{model}` must be a string"),
+                    ));
+                };
+                provider = provider.with_deployment(model.clone(), deployment);
+            }
+        }
+
+        if let Some(model) = &config.default_model {
+            provider = provider.with_default_model(model.clone());
+        }
+
+        provider.request_patch = config.patch.clone();
+
+        Ok(provider)
+    }
+
+    /// Builds the URL for `deployment`, including the `api-version` query parameter.
+    pub(crate) fn endpoint(&self, deployment: &str) -> String {
+        let base = self.base_url.trim_end_matches('/');
+        let base = base.strip_suffix("/openai").unwrap_or(base);
+        match self.api {
+            AzureApi::ChatCompletions => format!(
+                "{base}/openai/deployments/{deployment}/chat/completions?api-version={}",
+                self.api_version
+                    .as_deref()
+                    .unwrap_or(DEFAULT_CHAT_API_VERSION)
+            ),
+            AzureApi::Responses => format!(
+                "{base}/openai/responses?api-version={}",
+                self.api_version
+                    .as_deref()
+                    .unwrap_or(DEFAULT_RESPONSES_API_VERSION)
+            ),
+        }
+    }
+
+    fn build_headers(&self, secret: &str) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        match self.auth {
+            AzureAuth::ApiKey => {
+                headers.insert("api-key".to_string(), secret.to_string());
+            }
+            AzureAuth::Entra => {
+                headers.insert("Authorization".to_string(), format!("Bearer {secret}"));
+            }
+        }
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        headers.insert("Accept".to_string(), "application/json".to_string());
+        headers
+    }
+
+    /// Resolves the deployment addressed by `request`.
+    fn resolve_deployment(&self, request: &ChatRequest) -> Result<String, LLMError> {
+        let model = request
+            .options
+            .model
+            .clone()
+            .or_else(|| self.default_model.clone())
+            .ok_or_else(|| LLMError::Validation {
+                message: "model is required for Azure OpenAI".to_string(),
+            })?;
+        Ok(self.deployments.get(&model).cloned().unwrap_or(model))
+    }
+
+    async fn prepare(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let deployment = self.resolve_deployment(request)?;
+        let body = match self.api {
+            AzureApi::ChatCompletions => build_openai_body(request, &deployment, stream)?,
+            AzureApi::Responses => build_openai_responses_body(request, &deployment, stream)?,
+        };
+        let token = self.credential.token().await?;
+        let mut prepared = PreparedRequest {
+            url: self.endpoint(&deployment),
+            headers: self.build_headers(&token.value),
+            body,
+        };
+        self.apply_patch(&mut prepared.url, &mut prepared.headers, &mut prepared.body);
+        Ok(prepared)
+    }
+
+    async fn send_request(&self, prepared: PreparedRequest) -> Result<HttpResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
+        post_json_with_headers(self.transport.as_ref(), url, headers, &body).await
+    }
+
+    async fn send_stream_request(
+        &self,
+        prepared: PreparedRequest,
+    ) -> Result<HttpStreamResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
+        post_json_stream_with_headers(self.transport.as_ref(), url, headers, &body).await
+    }
+
+    fn ensure_success(&self, response: HttpResponse) -> Result<String, LLMError> {
+        let HttpResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        let text = String::from_utf8(body).map_err(|err| LLMError::transport(err.to_string()))?;
+        if (200..300).contains(&status) {
+            Ok(text)
+        } else {
+            invalidate_on_unauthorized(&self.credential, status);
+            Err(parse_azure_error(
+                status,
+                &text,
+                retry_after_from_headers(&headers),
+            ))
+        }
+    }
+
+    fn try_parse<T: DeserializeOwned>(&self, text: &str) -> Result<T, LLMError> {
+        serde_json::from_str(text).map_err(|err| LLMError::Provider {
+            provider: self.name(),
+            message: format!("failed to parse Azure OpenAI response: {err}"),
+        })
+    }
+
+    fn apply_patch(
+        &self,
+        url: &mut String,
+        headers: &mut HashMap<String, String>,
+        body: &mut Value,
+    ) {
+        if let Some(patch) = &self.request_patch {
+            patch.apply(url, headers, body);
+        }
+    }
+}
+
+fn invalid_extra(key: &str, reason: String) -> LLMError {
+    LLMError::InvalidConfig {
+        field: format!("extra.{key}"),
+        reason,
+    }
+}
+
+#[async_trait]
+impl LLMProvider for AzureOpenAiProvider {
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LLMError> {
+        let prepared = self.prepare(&request, false).await?;
+        let endpoint = prepared.url.clone();
+        let response = self.send_request(prepared).await?;
+        let text = self.ensure_success(response)?;
+        match self.api {
+            AzureApi::ChatCompletions => {
+                let parsed: OpenAiChatResponse = self.try_parse(&text)?;
+                map_response(parsed, self.name(), endpoint)
+            }
+            AzureApi::Responses => {
+                let parsed: OpenAiResponsesResponse = self.try_parse(&text)?;
+                map_responses_response(parsed, self.name(), endpoint)
+            }
+        }
+    }
+
+    async fn stream_chat(&self, request: ChatRequest) -> Result<ChatStream, LLMError> {
+        let prepared = self.prepare(&request, true).await?;
+        let endpoint = prepared.url.clone();
+        let response = self.send_stream_request(prepared).await?;
+        let HttpStreamResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        if !(200..300).contains(&status) {
+            invalidate_on_unauthorized(&self.credential, status);
+            let text = match self.api {
+                AzureApi::ChatCompletions => {
+                    chat_stream::collect_stream_text(body, self.name()).await?
+                }
+                AzureApi::Responses => {
+                    responses_stream::collect_stream_text(body, self.name()).await?
+                }
+            };
+            return Err(parse_azure_error(
+                status,
+                &text,
+                retry_after_from_headers(&headers),
+            ));
+        }
+        Ok(match self.api {
+            AzureApi::ChatCompletions => chat_stream::create_stream(body, self.name(), endpoint),
+            AzureApi::Responses => responses_stream::create_stream(body, self.name(), endpoint),
+        })
+    }
+
+    async fn prepare_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        self.prepare(request, stream).await
+    }
+
+    fn capabilities(&self) -> CapabilityDescriptor {
+        CapabilityDescriptor {
+            supports_stream: true,
+            supports_image_input: true,
+            supports_audio_input: self.api == AzureApi::ChatCompletions,
+            supports_video_input: false,
+            supports_tools: true,
+            supports_structured_output: true,
+            supports_parallel_tool_calls: true,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "azure_openai"
+    }
+
+    fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::config::{ModelConfig, ProviderKind};
+    use crate::http::test_support::PanicTransport;
+    use crate::http::{HttpRequest, HttpTransport};
+    use crate::types::test_support::text_request;
+    use crate::types::{ChatOptions, OutputItem};
+
+    /// Records the request body and answers with a canned JSON response.
+    struct CannedTransport {
+        response: Value,
+        sent: Mutex<Option<Value>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for CannedTransport {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, LLMError> {
+            let body = serde_json::from_slice(&request.body.unwrap_or_default()).expect("json");
+            *self.sent.lock().unwrap() = Some(body);
+            Ok(HttpResponse {
+                status: 200,
+                headers: HashMap::new(),
+                body: serde_json::to_vec(&self.response).expect("serialize"),
+            })
+        }
+
+        async fn send_stream(&self, _request: HttpRequest) -> Result<HttpStreamResponse, LLMError> {
+            panic!("send_stream should not be called");
+        }
+    }
+
+    /// Chat Completions deployments request and return logprobs like OpenAI.
+    #[tokio::test]
+    async fn chat_completions_map_logprobs() {
+        let transport = Arc::new(CannedTransport {
+            response: json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": { "role": "assistant", "content": "Yes" },
+                    "logprobs": { "content": [{
+                        "token": "Yes",
+                        "logprob": -0.01,
+                        "bytes": [89, 101, 115],
+                        "top_logprobs": [
+                            { "token": "Yes", "logprob": -0.01, "bytes": [89, 101, 115] },
+                            { "token": "No", "logprob": -4.7, "bytes": [78, 111] }
+                        ]
+                    }] }
+                }]
+            }),
+            sent: Mutex::new(None),
+        });
+        let provider = AzureOpenAiProvider::new(
+            transport.clone(),
+            "https://my-resource.openai.azure.com",
+            "azure-key",
+        )
+        .with_default_model("gpt-4o");
+        let request = ChatRequest {
+            options: ChatOptions {
+                top_logprobs: Some(2),
+                ..ChatOptions::default()
+            },
+            ..text_request("Is the sky blue?")
+        };
+
+        let response = provider.chat(request).await.expect("chat");
+
+        let sent = transport.sent.lock().unwrap().take().expect("request body");
+        assert_eq!(sent["logprobs"], json!(true));
+        assert_eq!(sent["top_logprobs"], json!(2));
+        let OutputItem::Message { logprobs, .. } = &response.outputs[0] else {
+            panic!("unexpected output: {:?}", response.outputs[0]);
+        };
+        assert_eq!(logprobs[0].token, "Yes");
+        assert_eq!(logprobs[0].bytes.as_deref(), Some("Yes".as_bytes()));
+        assert_eq!(logprobs[0].top_logprobs.len(), 2);
+        assert_eq!(logprobs[0].top_logprobs[1].token, "No");
+        assert_eq!(logprobs[0].top_logprobs[1].logprob, -4.7);
+    }
+
+    fn config(credential: Credential, extra: Value) -> ModelConfig {
+        ModelConfig {
+            handle: "azure".to_string(),
+            provider: ProviderKind::AzureOpenAi,
+            credential,
+            default_model: Some("gpt-4o".to_string()),
+            base_url: Some("https://my-resource.openai.azure.com/".to_string()),
+            extra: serde_json::from_value(extra).expect("extra"),
+            patch: None,
+            catalog: Vec::new(),
+        }
+    }
+
+    /// Deployment URLs derive from the requested model; Entra tokens use bearer auth.
+    #[tokio::test]
+    async fn routes_models_to_deployments() {
+        let api_key = Credential::ApiKey {
+            header: None,
+            key: "azure-key".to_string(),
+        };
+        let provider = AzureOpenAiProvider::from_model_config(
+            &config(
+                api_key,
+                json!({ "deployments": { "gpt-4o-mini": "mini-prod" } }),
+            ),
+            Arc::new(PanicTransport),
+        )
+        .expect("provider");
+        let mut request = text_request("ping");
+
+        let prepared = provider
+            .prepare_request(&request, false)
+            .await
+            .expect("prepared request");
+        assert_eq!(
+            prepared.url,
+            "https://my-resource.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-10-21"
+        );
+        assert_eq!(
+            prepared.headers.get("api-key"),
+            Some(&"azure-key".to_string())
+        );
+        assert!(!prepared.headers.contains_key("Authorization"));
+
+        request.options.model = Some("gpt-4o-mini".to_string());
+        let prepared = provider
+            .prepare_request(&request, false)
+            .await
+            .expect("prepared request");
+        assert!(
+            prepared.url.contains("/deployments/mini-prod/"),
+            "{}",
+            prepared.url
+        );
+
+        let entra = Credential::Bearer {
+            token: "entra-token".to_string(),
+        };
+        let provider = AzureOpenAiProvider::from_model_config(
+            &ModelConfig {
+                default_model: Some("gpt-4.1".to_string()),
+                ..config(
+                    entra,
+                    json!({ "api": "responses", "api_version": "preview" }),
+                )
+            },
+            Arc::new(PanicTransport),
+        )
+        .expect("provider");
+        request.options.model = None;
+        let prepared = provider
+            .prepare_request(&request, false)
+            .await
+            .expect("prepared request");
+        assert_eq!(
+            prepared.url,
+            "https://my-resource.openai.azure.com/openai/responses?api-version=preview"
+        );
+        assert_eq!(prepared.body["model"], json!("gpt-4.1"));
+        assert_eq!(
+            prepared.headers.get("Authorization"),
+            Some(&"Bearer entra-token".to_string())
+        );
+    }
+
+    /// Azure handles cannot guess the resource endpoint.
+    #[test]
+    fn requires_base_url() {
+        let config = ModelConfig {
+            base_url: None,
+            ..config(
+                Credential::ApiKey {
+                    header: None,
+                    key: "azure-key".to_string(),
+                },
+                json!({}),
+            )
+        };
+        match AzureOpenAiProvider::from_model_config(&config, Arc::new(PanicTransport)) {
+            Err(LLMError::InvalidConfig { field, .. }) => assert_eq!(field, "base_url"),
+            Err(other) => panic!("unexpected error: {other:?}"),
+            Ok(_) => panic!("expected invalid config"),
+        }
+    }
+
+    /// Malformed `extra` values are reported instead of silently ignored.
+    #[test]
+    fn rejects_non_string_api_version() {
+        let config = config(
+            Credential::ApiKey {
+                header: None,
+                key: "azure-key".to_string(),
+            },
+            json!({ "api_version": 20241021 }),
+        );
+        match AzureOpenAiProvider::from_model_config(&config, Arc::new(PanicTransport)) {
+            Err(LLMError::InvalidConfig { field, .. }) => assert_eq!(field, "extra.api_version"),
+            Err(other) => panic!("unexpected error: {other:?}"),
+            Ok(_) => panic!("expected invalid config"),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::error::{LLMError, extract_model_identifier, looks_like_token_limit_error};
+
+/// Reads the AWS error code from the `x-amzn-ErrorType` response header.
+///
+/// The header may carry a suffix such as `ThrottlingException:http://internal.amazon.com/`.
+pub(crate) fn error_type_from_headers(headers: &HashMap<String, String>) -> Option<String> {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("x-amzn-errortype"))
+        .map(|(_, value)| value.split(':').next().unwrap_or_default().to_string())
+}
+
+/// Returns whether an error response reports expired temporary credentials.
+///
+/// `error_type` is the `x-amzn-ErrorType` header value; the body's `__type` is used when
+/// it is absent.
+pub(crate) fn is_expired_token_error(error_type: Option<&str>, body: &str) -> bool {
+    let body_type = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|body| body.get("__type")?.as_str().map(str::to_string));
+    exception_name(error_type, body_type.as_deref()).eq_ignore_ascii_case("ExpiredTokenException")
+}
+
+/// Normalizes the AWS exception name, dropping any `namespace#` prefix.
+fn exception_name(error_type: Option<&str>, body_type: Option<&str>) -> String {
+    error_type
+        .or(body_type)
+        .map(|kind| kind.rsplit('#').next().unwrap_or_default().to_string())
+        .unwrap_or_default()
+}
+
+/// Parses error responses and stream exceptions returned by Bedrock Runtime.
+///
+/// `error_type` is the AWS exception name, taken from the `x-amzn-ErrorType` header or the
+/// `:exception-type` eventstream header; the body's `__type` is used when it is absent.
+pub(crate) fn parse_bedrock_error(
+    status: u16,
+    error_type: Option<&str>,
+    body: &str,
+    retry_after: Option<Duration>,
+) -> LLMError {
+    #[derive(Deserialize)]
+    struct ErrorBody {
+        #[serde(alias = "Message")]
+        message: Option<String>,
+        #[serde(rename = "__type")]
+        kind: Option<String>,
+    }
+
+    let parsed = serde_json::from_str::<ErrorBody>(body).ok();
+    let kind = exception_name(
+        error_type,
+        parsed.as_ref().and_then(|parsed| parsed.kind.as_deref()),
+    );
+    let Some(message) = parsed.and_then(|parsed| parsed.message) else {
+        return LLMError::Provider {
+            provider: "bedrock_converse",
+            message: format!("status {status}: {body}"),
+        };
+    };
+    let message = if kind.is_empty() {
+        message
+    } else {
+        format!("{message} ({kind})")
+    };
+
+    match kind.to_ascii_lowercase().as_str() {
+        "validationexception" if looks_like_token_limit_error(None, &message) => {
+            LLMError::TokenLimitExceeded {
+                message,
+                estimated: None,
+                limit: None,
+            }
+        }
+        "validationexception" => LLMError::Validation { message },
+        "accessdeniedexception" | "unrecognizedclientexception" => LLMError::Auth { message },
+        "throttlingexception" | "servicequotaexceededexception" => LLMError::RateLimit {
+            message,
+            retry_after,
+        },
+        "resourcenotfoundexception" => LLMError::ModelNotFound {
+            model: extract_model_identifier(&message),
+            message,
+        },
+        _ => match status {
+            401 | 403 => LLMError::Auth { message },
+            404 => LLMError::ModelNotFound {
+                model: extract_model_identifier(&message),
+                message,
+            },
+            429 => LLMError::RateLimit {
+                message,
+                retry_after,
+            },
+            400 => LLMError::Validation { message },
+            _ => LLMError::Provider {
+                provider: "bedrock_converse",
+                message,
+            },
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_aws_exception_types() {
+        let headers = HashMap::from([(
+            "x-amzn-errortype".to_string(),
+            "ThrottlingException:http://internal.amazon.com/coral/com.amazon.bedrock/".to_string(),
+        )]);
+        let kind = error_type_from_headers(&headers);
+        assert_eq!(kind.as_deref(), Some("ThrottlingException"));
+        assert!(matches!(
+            parse_bedrock_error(
+                429,
+                kind.as_deref(),
+                r#"{"message":"Too many requests, please wait before trying again."}"#,
+                Some(Duration::from_secs(2)),
+            ),
+            LLMError::RateLimit { retry_after: Some(retry), .. } if retry == Duration::from_secs(2)
+        ));
+
+        assert!(matches!(
+            parse_bedrock_error(
+                403,
+                Some("AccessDeniedException"),
+                r#"{"Message":"You don't have access to the model with the specified model ID."}"#,
+                None,
+            ),
+            LLMError::Auth { .. }
+        ));
+
+        // Stream exceptions use camelCase names and no HTTP status of their own.
+        assert!(matches!(
+            parse_bedrock_error(
+                200,
+                Some("validationException"),
+                r#"{"message":"Input is too long for requested model."}"#,
+                None,
+            ),
+            LLMError::TokenLimitExceeded { .. }
+        ));
+    }
+
+    #[test]
+    fn falls_back_to_body_type_and_status() {
+        assert!(matches!(
+            parse_bedrock_error(
+                404,
+                None,
+                r#"{"__type":"com.amazon.bedrock#ResourceNotFoundException","message":"Could not resolve the foundation model from the provided model identifier."}"#,
+                None,
+            ),
+            LLMError::ModelNotFound { .. }
+        ));
+        match parse_bedrock_error(503, None, "Service Unavailable", None) {
+            LLMError::Provider { provider, message } => {
+                assert_eq!(provider, "bedrock_converse");
+                assert!(message.contains("503"));
+            }
+            other => panic!("expected Provider error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn detects_expired_session_tokens() {
+        assert!(is_expired_token_error(Some("ExpiredTokenException"), ""));
+        assert!(is_expired_token_error(
+            None,
+            r#"{"__type":"com.amazon.coral.service#ExpiredTokenException","message":"expired"}"#,
+        ));
+        assert!(!is_expired_token_error(
+            Some("AccessDeniedException"),
+            r#"{"__type":"ExpiredTokenException"}"#,
+        ));
+        assert!(!is_expired_token_error(None, "not json"));
+    }
+}
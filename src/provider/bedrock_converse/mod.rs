@@ -0,0 +1,8 @@
+mod error;
+mod provider;
+mod request;
+mod response;
+mod stream;
+mod types;
+
+pub use provider::BedrockConverseProvider;
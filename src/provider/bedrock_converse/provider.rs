@@ -0,0 +1,627 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::config::{Credential, RequestPatch};
+use crate::credential::{
+    AwsCredentials, DynAwsCredentialsProvider, DynCredentialProvider, SigV4Signer,
+    SourcedAwsCredentials, invalidate_on_unauthorized, uri_encode,
+};
+use crate::error::LLMError;
+use crate::http::{
+    DynHttpTransport, HttpResponse, HttpStreamResponse, post_json_stream_with_headers,
+    post_json_with_headers,
+};
+use crate::provider::{ChatStream, LLMProvider, PreparedRequest, retry::retry_after_from_headers};
+use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
+
+use super::error::{error_type_from_headers, is_expired_token_error, parse_bedrock_error};
+use super::request::build_converse_body;
+use super::response::map_response;
+use super::stream::{collect_stream_text, create_stream};
+use super::types::ConverseResponse;
+
+/// Signing name of the Bedrock Runtime service.
+const SIGNING_SERVICE: &str = "bedrock";
+
+/// How requests are authenticated.
+enum BedrockAuth {
+    /// IAM credentials signed with SigV4.
+    SigV4(DynAwsCredentialsProvider),
+    /// Bedrock API key sent as `Authorization: Bearer`.
+    Bearer(DynCredentialProvider),
+}
+
+/// AWS Bedrock provider built on the model-agnostic Converse and ConverseStream APIs.
+///
+/// The model ID (or inference profile / provisioned throughput ARN) is part of the URL, so
+/// the same provider serves every model enabled in the account. Requests are signed with
+/// AWS Signature Version 4, or carry a Bedrock API key as a bearer token. Streams use the
+/// binary `application/vnd.amazon.eventstream` framing instead of SSE.
+pub struct BedrockConverseProvider {
+    pub(crate) transport: DynHttpTransport,
+    pub(crate) base_url: String,
+    signer: SigV4Signer,
+    auth: BedrockAuth,
+    pub(crate) default_model: Option<String>,
+    pub(crate) request_patch: Option<RequestPatch>,
+}
+
+impl BedrockConverseProvider {
+    /// `ModelConfig.extra` keys read by [`Self::from_model_config`].
+    pub const SUPPORTED_EXTRA_KEYS: &'static [&'static str] = &["region"];
+
+    /// Creates a provider for `region` that signs requests with `credentials`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use kotoba_llm::credential::AwsCredentials;
+    /// # use kotoba_llm::provider::bedrock_converse::BedrockConverseProvider;
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let credentials = AwsCredentials::new("AKIAEXAMPLE", "secret");
+    /// let provider = BedrockConverseProvider::new(transport, "us-east-1", credentials);
+    /// assert_eq!(provider.name(), "bedrock_converse");
+    /// # }
+    /// ```
+    pub fn new(
+        transport: DynHttpTransport,
+        region: impl Into<String>,
+        credentials: AwsCredentials,
+    ) -> Self {
+        Self::from_aws_credentials_provider(transport, region, Arc::new(credentials))
+    }
+
+    /// Creates a provider for `region` that signs each request with the credentials
+    /// `credentials` returns at that moment.
+    ///
+    /// Use this for temporary STS credentials: they are invalidated when AWS answers
+    /// `403 ExpiredTokenException`, so the next request signs with fresh ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::credential::{CachedCredential, FileCredential, SourcedAwsCredentials};
+    /// # use kotoba_llm::provider::bedrock_converse::BedrockConverseProvider;
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let part = |path: &str| Arc::new(CachedCredential::new(FileCredential::new(path)));
+    /// let credentials = SourcedAwsCredentials::new(
+    ///     part("/run/secrets/aws/access-key-id"),
+    ///     part("/run/secrets/aws/secret-access-key"),
+    /// )
+    /// .with_session_token(part("/run/secrets/aws/session-token"));
+    /// let provider = BedrockConverseProvider::from_aws_credentials_provider(
+    ///     transport,
+    ///     "us-east-1",
+    ///     Arc::new(credentials),
+    /// );
+    /// assert_eq!(provider.name(), "bedrock_converse");
+    /// # }
+    /// ```
+    pub fn from_aws_credentials_provider(
+        transport: DynHttpTransport,
+        region: impl Into<String>,
+        credentials: DynAwsCredentialsProvider,
+    ) -> Self {
+        Self::with_auth(transport, region.into(), BedrockAuth::SigV4(credentials))
+    }
+
+    /// Creates a provider for `region` that sends a Bedrock API key from `credential` as a
+    /// bearer token instead of signing requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::credential::EnvCredential;
+    /// # use kotoba_llm::provider::bedrock_converse::BedrockConverseProvider;
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let credential = Arc::new(EnvCredential::new("AWS_BEARER_TOKEN_BEDROCK"));
+    /// let provider =
+    ///     BedrockConverseProvider::from_credential_provider(transport, "us-west-2", credential);
+    /// assert_eq!(provider.name(), "bedrock_converse");
+    /// # }
+    /// ```
+    pub fn from_credential_provider(
+        transport: DynHttpTransport,
+        region: impl Into<String>,
+        credential: DynCredentialProvider,
+    ) -> Self {
+        Self::with_auth(transport, region.into(), BedrockAuth::Bearer(credential))
+    }
+
+    fn with_auth(transport: DynHttpTransport, region: String, auth: BedrockAuth) -> Self {
+        Self {
+            transport,
+            base_url: format!("https://bedrock-runtime.{region}.amazonaws.com"),
+            signer: SigV4Signer::new(region, SIGNING_SERVICE),
+            auth,
+            default_model: None,
+            request_patch: None,
+        }
+    }
+
+    /// Overrides the Bedrock Runtime endpoint, e.g. a VPC interface endpoint.
+    ///
+    /// Requests are still signed for the provider's region.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Configures a default model ID when the [`ChatRequest`] omits one.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.default_model = Some(model.into());
+        self
+    }
+
+    /// Constructs a provider from a [`crate::config::ModelConfig`].
+    ///
+    /// `extra.region` is required. [`Credential::AwsSigV4`] and
+    /// [`Credential::AwsSigV4Source`] sign requests; any other credential is sent as a
+    /// Bedrock API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Auth`] when credentials are missing or invalid, and
+    /// [`LLMError::InvalidConfig`] when `extra.region` is missing.
+    pub fn from_model_config(
+        config: &crate::config::ModelConfig,
+        transport: DynHttpTransport,
+    ) -> Result<Self, LLMError> {
+        let Some(Value::String(region)) = config.extra.get("region") else {
+            return Err(LLMError::InvalidConfig {
+                field: "extra.region".to_string(),
+                reason: "Bedrock Converse requires the AWS region, e.g. us-east-1".to_string(),
+            });
+        };
+        let mut provider = match &config.credential {
+            Credential::AwsSigV4 {
+                access_key_id,
+                secret_access_key,
+                session_token,
+            } => {
+                let mut credentials =
+                    AwsCredentials::new(access_key_id.clone(), secret_access_key.clone());
+                credentials.session_token = session_token.clone();
+                Self::new(transport, region.clone(), credentials)
+            }
+            Credential::AwsSigV4Source {
+                access_key_id,
+                secret_access_key,
+                session_token,
+                cache_ttl_secs,
+            } => {
+                let ttl = cache_ttl_secs.map(Duration::from_secs);
+                let mut credentials = SourcedAwsCredentials::new(
+                    access_key_id.clone().into_provider(ttl),
+                    secret_access_key.clone().into_provider(ttl),
+                );
+                if let Some(session_token) = session_token {
+                    credentials =
+                        credentials.with_session_token(session_token.clone().into_provider(ttl));
+                }
+                Self::from_aws_credentials_provider(
+                    transport,
+                    region.clone(),
+                    Arc::new(credentials),
+                )
+            }
+            other => Self::from_credential_provider(
+                transport,
+                region.clone(),
+                other.credential_provider("bedrock_converse")?,
+            ),
+        };
+
+        if let Some(base_url) = &config.base_url {
+            provider = provider.with_base_url(base_url.clone());
+        }
+
+        if let Some(model) = &config.default_model {
+            provider = provider.with_default_model(model.clone());
+        }
+
+        provider.request_patch = config.patch.clone();
+
+        Ok(provider)
+    }
+
+    /// Builds the Converse or ConverseStream URL for `model`.
+    pub(crate) fn endpoint(&self, model: &str, stream: bool) -> String {
+        let operation = if stream {
+            "converse-stream"
+        } else {
+            "converse"
+        };
+        format!(
+            "{}/model/{}/{operation}",
+            self.base_url.trim_end_matches('/'),
+            uri_encode(model)
+        )
+    }
+
+    fn resolve_model(&self, request: &ChatRequest) -> Result<String, LLMError> {
+        request
+            .options
+            .model
+            .clone()
+            .or_else(|| self.default_model.clone())
+            .ok_or_else(|| LLMError::Validation {
+                message: "model is required for Bedrock Converse".to_string(),
+            })
+    }
+
+    async fn prepare(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let model = self.resolve_model(request)?;
+        let body = build_converse_body(request)?;
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        let accept = if stream {
+            "application/vnd.amazon.eventstream"
+        } else {
+            "application/json"
+        };
+        headers.insert("Accept".to_string(), accept.to_string());
+        if let BedrockAuth::Bearer(credential) = &self.auth {
+            let token = credential.token().await?;
+            headers.insert(
+                "Authorization".to_string(),
+                format!("Bearer {}", token.value),
+            );
+        }
+        let mut prepared = PreparedRequest {
+            url: self.endpoint(&model, stream),
+            headers,
+            body,
+        };
+        self.apply_patch(&mut prepared.url, &mut prepared.headers, &mut prepared.body);
+
+        // Sign last so the signature covers the patched URL and body exactly as sent.
+        if let BedrockAuth::SigV4(provider) = &self.auth {
+            let credentials = provider.credentials().await?;
+            let payload =
+                serde_json::to_vec(&prepared.body).map_err(|err| LLMError::Validation {
+                    message: format!("failed to serialize request: {err}"),
+                })?;
+            self.signer.sign(
+                &credentials,
+                "POST",
+                &prepared.url,
+                &mut prepared.headers,
+                &payload,
+                SystemTime::now(),
+            )?;
+        }
+        Ok(prepared)
+    }
+
+    async fn send_request(&self, prepared: PreparedRequest) -> Result<HttpResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
+        post_json_with_headers(self.transport.as_ref(), url, headers, &body).await
+    }
+
+    async fn send_stream_request(
+        &self,
+        prepared: PreparedRequest,
+    ) -> Result<HttpStreamResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
+        post_json_stream_with_headers(self.transport.as_ref(), url, headers, &body).await
+    }
+
+    fn ensure_success(&self, response: HttpResponse) -> Result<String, LLMError> {
+        let HttpResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        let text = String::from_utf8(body).map_err(|err| LLMError::transport(err.to_string()))?;
+        if (200..300).contains(&status) {
+            Ok(text)
+        } else {
+            let error_type = error_type_from_headers(&headers);
+            self.invalidate_rejected_credentials(status, error_type.as_deref(), &text);
+            Err(parse_bedrock_error(
+                status,
+                error_type.as_deref(),
+                &text,
+                retry_after_from_headers(&headers),
+            ))
+        }
+    }
+
+    /// Drops cached credentials that the API rejected, so the next request fetches fresh
+    /// ones: bearer keys on `401`, SigV4 credentials once the session token has expired.
+    fn invalidate_rejected_credentials(&self, status: u16, error_type: Option<&str>, body: &str) {
+        match &self.auth {
+            BedrockAuth::Bearer(credential) => invalidate_on_unauthorized(credential, status),
+            BedrockAuth::SigV4(credentials) => {
+                if status == 403 && is_expired_token_error(error_type, body) {
+                    credentials.invalidate();
+                }
+            }
+        }
+    }
+
+    fn try_parse<T: DeserializeOwned>(&self, text: &str) -> Result<T, LLMError> {
+        serde_json::from_str(text).map_err(|err| LLMError::Provider {
+            provider: self.name(),
+            message: format!("failed to parse Bedrock Converse response: {err}"),
+        })
+    }
+
+    fn apply_patch(
+        &self,
+        url: &mut String,
+        headers: &mut HashMap<String, String>,
+        body: &mut Value,
+    ) {
+        if let Some(patch) = &self.request_patch {
+            patch.apply(url, headers, body);
+        }
+    }
+}
+
+/// Reads the `x-amzn-RequestId` response header.
+fn request_id(headers: &HashMap<String, String>) -> Option<String> {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("x-amzn-requestid"))
+        .map(|(_, value)| value.clone())
+}
+
+#[async_trait]
+impl LLMProvider for BedrockConverseProvider {
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LLMError> {
+        let model = self.resolve_model(&request)?;
+        let prepared = self.prepare(&request, false).await?;
+        let endpoint = prepared.url.clone();
+        let response = self.send_request(prepared).await?;
+        let request_id = request_id(&response.headers);
+        let text = self.ensure_success(response)?;
+        let parsed: ConverseResponse = self.try_parse(&text)?;
+        map_response(parsed, self.name(), endpoint, model, request_id)
+    }
+
+    async fn stream_chat(&self, request: ChatRequest) -> Result<ChatStream, LLMError> {
+        let prepared = self.prepare(&request, true).await?;
+        let endpoint = prepared.url.clone();
+        let response = self.send_stream_request(prepared).await?;
+        let HttpStreamResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        if !(200..300).contains(&status) {
+            let text = collect_stream_text(body, self.name()).await?;
+            let error_type = error_type_from_headers(&headers);
+            self.invalidate_rejected_credentials(status, error_type.as_deref(), &text);
+            return Err(parse_bedrock_error(
+                status,
+                error_type.as_deref(),
+                &text,
+                retry_after_from_headers(&headers),
+            ));
+        }
+        Ok(create_stream(body, self.name(), endpoint))
+    }
+
+    async fn prepare_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        self.prepare(request, stream).await
+    }
+
+    fn capabilities(&self) -> CapabilityDescriptor {
+        CapabilityDescriptor {
+            supports_stream: true,
+            supports_image_input: true,
+            supports_audio_input: false,
+            supports_video_input: false,
+            supports_tools: true,
+            // Converse has no JSON mode; structured output relies on forced tool use.
+            supports_structured_output: false,
+            supports_parallel_tool_calls: true,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "bedrock_converse"
+    }
+
+    fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::config::{ModelConfig, ProviderKind};
+    use crate::credential::CredentialSource;
+    use crate::http::test_support::PanicTransport;
+    use crate::http::{HttpRequest, HttpTransport};
+    use crate::types::test_support::text_request;
+
+    /// Rejects the first request as signed with an expired session token, then succeeds,
+    /// recording the signing headers of every request.
+    struct ExpiringTransport {
+        signed: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for ExpiringTransport {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, LLMError> {
+            let mut signed = self.signed.lock().unwrap();
+            signed.push((
+                request.headers["Authorization"].clone(),
+                request.headers["X-Amz-Security-Token"].clone(),
+            ));
+            let (status, body) = if signed.len() == 1 {
+                (
+                    403,
+                    json!({
+                        "__type": "com.amazon.coral.service#ExpiredTokenException",
+                        "message": "The security token included in the request is expired"
+                    }),
+                )
+            } else {
+                (
+                    200,
+                    json!({
+                        "output": { "message": { "role": "assistant", "content": [{ "text": "pong" }] } },
+                        "stopReason": "end_turn",
+                        "usage": { "inputTokens": 1, "outputTokens": 1, "totalTokens": 2 }
+                    }),
+                )
+            };
+            Ok(HttpResponse {
+                status,
+                headers: HashMap::new(),
+                body: serde_json::to_vec(&body).expect("serialize"),
+            })
+        }
+
+        async fn send_stream(&self, _request: HttpRequest) -> Result<HttpStreamResponse, LLMError> {
+            panic!("send_stream should not be called");
+        }
+    }
+
+    fn config() -> ModelConfig {
+        ModelConfig {
+            handle: "bedrock".to_string(),
+            provider: ProviderKind::BedrockConverse,
+            credential: Credential::AwsSigV4 {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+                session_token: Some("session".to_string()),
+            },
+            default_model: Some("anthropic.claude-3-haiku-20240307-v1:0".to_string()),
+            base_url: None,
+            extra: HashMap::from([("region".to_string(), json!("eu-west-1"))]),
+            patch: None,
+            catalog: Vec::new(),
+        }
+    }
+
+    /// The final model URL and body are signed with SigV4.
+    #[tokio::test]
+    async fn signs_model_urls() {
+        let provider =
+            BedrockConverseProvider::from_model_config(&config(), Arc::new(PanicTransport))
+                .expect("provider");
+
+        let prepared = provider
+            .prepare_request(&text_request("ping"), true)
+            .await
+            .expect("prepared request");
+        assert_eq!(
+            prepared.url,
+            "https://bedrock-runtime.eu-west-1.amazonaws.com/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse-stream"
+        );
+        assert_eq!(
+            prepared.headers.get("Accept"),
+            Some(&"application/vnd.amazon.eventstream".to_string())
+        );
+        assert_eq!(
+            prepared.headers.get("X-Amz-Security-Token"),
+            Some(&"session".to_string())
+        );
+        let authorization = &prepared.headers["Authorization"];
+        assert!(
+            authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/")
+                && authorization.contains("/eu-west-1/bedrock/aws4_request"),
+            "{authorization}"
+        );
+    }
+
+    /// SigV4 signing needs a region to build the endpoint and scope.
+    #[test]
+    fn requires_region() {
+        let config = ModelConfig {
+            extra: HashMap::new(),
+            ..config()
+        };
+        match BedrockConverseProvider::from_model_config(&config, Arc::new(PanicTransport)) {
+            Err(LLMError::InvalidConfig { field, .. }) => assert_eq!(field, "extra.region"),
+            Err(other) => panic!("unexpected error: {other:?}"),
+            Ok(_) => panic!("expected invalid config"),
+        }
+    }
+
+    /// Sourced STS credentials are re-read once AWS reports the session token as expired,
+    /// even while their cache TTL has not elapsed.
+    #[tokio::test]
+    async fn refreshes_sourced_credentials_after_expired_token() {
+        let dir = std::env::temp_dir().join(format!("kotoba-sts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let write = |key: &str, token: &str| {
+            std::fs::write(dir.join("key"), key).expect("write key");
+            std::fs::write(dir.join("secret"), "secret").expect("write secret");
+            std::fs::write(dir.join("token"), token).expect("write token");
+        };
+        let source = |name: &str| CredentialSource::File {
+            path: dir.join(name),
+        };
+        write("ASIAFIRST", "session-1");
+        let config = ModelConfig {
+            credential: Credential::AwsSigV4Source {
+                access_key_id: source("key"),
+                secret_access_key: source("secret"),
+                session_token: Some(source("token")),
+                cache_ttl_secs: Some(3600),
+            },
+            ..config()
+        };
+        let transport = Arc::new(ExpiringTransport {
+            signed: Mutex::new(Vec::new()),
+        });
+        let provider = BedrockConverseProvider::from_model_config(&config, transport.clone())
+            .expect("provider");
+
+        let expired = provider.chat(text_request("ping")).await;
+        write("ASIASECOND", "session-2");
+        let response = provider.chat(text_request("ping")).await;
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(matches!(expired, Err(LLMError::Auth { .. })), "{expired:?}");
+        response.expect("chat after refresh");
+        let signed = transport.signed.lock().unwrap();
+        assert!(
+            signed[0].0.contains("Credential=ASIAFIRST/"),
+            "{}",
+            signed[0].0
+        );
+        assert_eq!(signed[0].1, "session-1");
+        assert!(
+            signed[1].0.contains("Credential=ASIASECOND/"),
+            "{}",
+            signed[1].0
+        );
+        assert_eq!(signed[1].1, "session-2");
+    }
+}
@@ -0,0 +1,579 @@
+use serde_json::{Map, Value, json};
+
+use crate::error::LLMError;
+use crate::types::{
+    CacheControl, CacheTtl, ChatRequest, ContentPart, ImageContent, ImageSource, Message,
+    ReasoningOptions, TextContent, ToolCall, ToolChoice, ToolDefinition, ToolKind, ToolResult,
+};
+
+/// Builds the request body shared by Bedrock `Converse` and `ConverseStream`.
+///
+/// The model ID travels in the URL, so unlike other providers it is not part of the body.
+pub(crate) fn build_converse_body(request: &ChatRequest) -> Result<Value, LLMError> {
+    let mut body = Map::new();
+
+    // 1. System/developer roles become `system` blocks; everything else goes to `messages`.
+    let mut system = Vec::new();
+    let mut messages = Vec::new();
+    for message in &request.messages {
+        match message.role.0.as_str() {
+            "system" | "developer" => {
+                for part in &message.content {
+                    match part {
+                        ContentPart::Text(TextContent { text }) => {
+                            system.push(json!({ "text": text }));
+                        }
+                        ContentPart::CacheBreakpoint(cache) => system.push(cache_point(cache)?),
+                        _ => {}
+                    }
+                }
+            }
+            _ => messages.push(convert_message(message)?),
+        }
+    }
+
+    if messages.is_empty() {
+        return Err(LLMError::Validation {
+            message: "Bedrock Converse request requires at least one user/assistant message"
+                .to_string(),
+        });
+    }
+    body.insert("messages".to_string(), Value::Array(messages));
+    if !system.is_empty() {
+        body.insert("system".to_string(), Value::Array(system));
+    }
+
+    // 2. Sampling and generation controls.
+    let mut inference = Map::new();
+    if let Some(max_tokens) = request.options.max_output_tokens {
+        inference.insert("maxTokens".to_string(), Value::from(max_tokens));
+    }
+    if let Some(temperature) = request.options.temperature {
+        inference.insert("temperature".to_string(), Value::from(temperature));
+    }
+    if let Some(top_p) = request.options.top_p {
+        inference.insert("topP".to_string(), Value::from(top_p));
+    }
+    if let Some(stop) = &request.options.stop_sequences {
+        inference.insert("stopSequences".to_string(), json!(stop));
+    }
+    if !inference.is_empty() {
+        body.insert("inferenceConfig".to_string(), Value::Object(inference));
+    }
+    if request.options.seed.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "bedrock_converse_seed",
+        });
+    }
+    if request.options.candidate_count.is_some_and(|n| n > 1) {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "bedrock_converse_candidate_count",
+        });
+    }
+    if request.options.logprobs == Some(true) || request.options.top_logprobs.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "bedrock_converse_logprobs",
+        });
+    }
+    if request.options.cached_content.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "bedrock_converse_cached_content",
+        });
+    }
+    if request.options.user.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "bedrock_converse_user",
+        });
+    }
+
+    // 3. Model-specific fields: Anthropic extended thinking and top-k, which Converse
+    // does not model in `inferenceConfig`.
+    let mut additional = Map::new();
+    if let Some(reasoning) = &request.options.reasoning {
+        if let Some(thinking) = build_thinking(reasoning) {
+            additional.insert("thinking".to_string(), thinking);
+        }
+    }
+    if let Some(top_k) = request.options.top_k {
+        additional.insert("top_k".to_string(), Value::from(top_k));
+    }
+    if !additional.is_empty() {
+        body.insert(
+            "additionalModelRequestFields".to_string(),
+            Value::Object(additional),
+        );
+    }
+
+    // 4. Tool definitions and tool choice.
+    let tool_choice = match &request.tool_choice {
+        Some(choice) => convert_tool_choice(choice),
+        None => None,
+    };
+    if !request.tools.is_empty() && !matches!(request.tool_choice, Some(ToolChoice::None)) {
+        let mut tool_config = Map::new();
+        tool_config.insert(
+            "tools".to_string(),
+            Value::Array(convert_tools(&request.tools)?),
+        );
+        if let Some(choice) = tool_choice {
+            tool_config.insert("toolChoice".to_string(), choice);
+        }
+        body.insert("toolConfig".to_string(), Value::Object(tool_config));
+    }
+
+    // 5. Metadata passthrough; Bedrock only accepts string values.
+    if let Some(metadata) = &request.metadata {
+        let meta: Map<String, Value> = metadata
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                (key.clone(), Value::String(value))
+            })
+            .collect();
+        body.insert("requestMetadata".to_string(), Value::Object(meta));
+    }
+
+    // 6. Provider-specific extras such as `guardrailConfig` or `performanceConfig`.
+    for (k, v) in &request.options.extra {
+        body.insert(k.clone(), v.clone());
+    }
+
+    Ok(Value::Object(body))
+}
+
+fn convert_message(message: &Message) -> Result<Value, LLMError> {
+    // Converse only knows `user` and `assistant`; tool results travel in user turns.
+    let role = match message.role.0.as_str() {
+        "assistant" => "assistant",
+        _ => "user",
+    };
+
+    let mut content = Vec::new();
+    for part in &message.content {
+        content.push(convert_content_part(part)?);
+    }
+    if content.is_empty() {
+        return Err(LLMError::Validation {
+            message: "message must contain at least one content part".to_string(),
+        });
+    }
+
+    Ok(json!({ "role": role, "content": content }))
+}
+
+fn convert_content_part(part: &ContentPart) -> Result<Value, LLMError> {
+    match part {
+        ContentPart::Text(TextContent { text }) => Ok(json!({ "text": text })),
+        ContentPart::Image(ImageContent { source, .. }) => match source {
+            ImageSource::Base64 { data, mime_type } => {
+                let format = mime_type
+                    .as_deref()
+                    .and_then(|mime| mime.strip_prefix("image/"))
+                    .unwrap_or("png");
+                Ok(json!({
+                    "image": {
+                        "format": format,
+                        "source": { "bytes": data }
+                    }
+                }))
+            }
+            _ => Err(LLMError::UnsupportedFeature {
+                feature: "image_source_non_base64",
+            }),
+        },
+        ContentPart::ToolCall(ToolCall {
+            id,
+            name,
+            arguments,
+            ..
+        }) => {
+            let tool_use_id = id.clone().ok_or_else(|| LLMError::Validation {
+                message: "tool_call content requires id (mapped to toolUseId)".to_string(),
+            })?;
+            Ok(json!({
+                "toolUse": {
+                    "toolUseId": tool_use_id,
+                    "name": name,
+                    "input": arguments
+                }
+            }))
+        }
+        ContentPart::ToolResult(ToolResult {
+            call_id,
+            output,
+            is_error,
+            content: parts,
+            ..
+        }) => {
+            let tool_use_id = call_id.clone().ok_or_else(|| LLMError::Validation {
+                message: "tool_result content requires call_id (mapped to toolUseId)".to_string(),
+            })?;
+            let mut content = vec![match output {
+                Value::String(text) => json!({ "text": text }),
+                other => json!({ "json": other }),
+            }];
+            // Images and text parts share the block list; other parts are rejected.
+            for part in parts {
+                match part {
+                    ContentPart::Text(_) | ContentPart::Image(_) => {
+                        content.push(convert_content_part(part)?)
+                    }
+                    _ => {
+                        return Err(LLMError::UnsupportedFeature {
+                            feature: "bedrock_converse_tool_result_content",
+                        });
+                    }
+                }
+            }
+            let mut result = json!({
+                "toolUseId": tool_use_id,
+                "content": content
+            });
+            if *is_error {
+                result["status"] = Value::String("error".to_string());
+            }
+            Ok(json!({ "toolResult": result }))
+        }
+        // Documents, audio and video need S3 locations or formats we do not model; use Data.
+        ContentPart::Audio(_) | ContentPart::Video(_) | ContentPart::File(_) => {
+            Err(LLMError::UnsupportedFeature {
+                feature: "bedrock_converse_content_type",
+            })
+        }
+        ContentPart::Data { data } => Ok(data.clone()),
+        ContentPart::CacheBreakpoint(cache) => cache_point(cache),
+    }
+}
+
+/// Converse marks cache breakpoints with a standalone `cachePoint` block.
+fn cache_point(cache: &CacheControl) -> Result<Value, LLMError> {
+    if cache.ttl == Some(CacheTtl::OneHour) {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "bedrock_converse_cache_ttl",
+        });
+    }
+    Ok(json!({ "cachePoint": { "type": "default" } }))
+}
+
+fn build_thinking(reasoning: &ReasoningOptions) -> Option<Value> {
+    if let Some(explicit) = reasoning.extra.get("thinking") {
+        return Some(explicit.clone());
+    }
+    reasoning.budget_tokens.map(|budget| {
+        json!({
+            "type": "enabled",
+            "budget_tokens": budget
+        })
+    })
+}
+
+fn convert_tools(tools: &[ToolDefinition]) -> Result<Vec<Value>, LLMError> {
+    let mut result = Vec::new();
+    for tool in tools {
+        match &tool.kind {
+            ToolKind::Function => {
+                let mut spec = Map::new();
+                spec.insert("name".to_string(), Value::String(tool.name.clone()));
+                if let Some(description) = &tool.description {
+                    spec.insert(
+                        "description".to_string(),
+                        Value::String(description.clone()),
+                    );
+                }
+                let schema = tool
+                    .input_schema
+                    .clone()
+                    .unwrap_or_else(|| json!({ "type": "object", "properties": {} }));
+                spec.insert("inputSchema".to_string(), json!({ "json": schema }));
+                result.push(json!({ "toolSpec": spec }));
+            }
+            ToolKind::Custom {
+                config: Some(config),
+                ..
+            } => result.push(config.clone()),
+            _ => {
+                return Err(LLMError::Validation {
+                    message: "Bedrock Converse tools only support function tools or custom configs"
+                        .to_string(),
+                });
+            }
+        }
+        if let Some(cache) = &tool.cache_control {
+            result.push(cache_point(cache)?);
+        }
+    }
+    Ok(result)
+}
+
+fn convert_tool_choice(choice: &ToolChoice) -> Option<Value> {
+    match choice {
+        ToolChoice::Auto => Some(json!({ "auto": {} })),
+        ToolChoice::Any => Some(json!({ "any": {} })),
+        ToolChoice::Tool { name } => Some(json!({ "tool": { "name": name } })),
+        // Converse has no "none" choice; the tool config is dropped instead.
+        ToolChoice::None => None,
+        ToolChoice::Custom(value) => Some(value.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ChatOptions, Role, ToolCallKind};
+
+    fn text(role: Role, text: &str) -> Message {
+        Message {
+            role,
+            name: None,
+            content: vec![ContentPart::Text(TextContent {
+                text: text.to_string(),
+            })],
+            metadata: None,
+        }
+    }
+
+    /// Maps system prompts, sampling options and tools into the Converse layout.
+    #[test]
+    fn build_body_with_system_tools_and_inference_config() {
+        let request = ChatRequest {
+            messages: vec![
+                text(Role::system(), "Be terse."),
+                text(Role::user(), "Weather in Paris?"),
+            ],
+            options: ChatOptions {
+                max_output_tokens: Some(512),
+                temperature: Some(0.5),
+                top_k: Some(50),
+                stop_sequences: Some(vec!["END".to_string()]),
+                ..ChatOptions::default()
+            },
+            tools: vec![ToolDefinition {
+                name: "get_weather".to_string(),
+                description: Some("Looks up the weather".to_string()),
+                input_schema: Some(json!({
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } }
+                })),
+                kind: ToolKind::Function,
+                metadata: None,
+                cache_control: None,
+            }],
+            tool_choice: Some(ToolChoice::Tool {
+                name: "get_weather".to_string(),
+            }),
+            response_format: None,
+            metadata: None,
+        };
+
+        let body = build_converse_body(&request).expect("build");
+
+        assert_eq!(body["system"], json!([{ "text": "Be terse." }]));
+        assert_eq!(
+            body["messages"],
+            json!([{ "role": "user", "content": [{ "text": "Weather in Paris?" }] }])
+        );
+        assert_eq!(
+            body["inferenceConfig"],
+            json!({ "maxTokens": 512, "temperature": 0.5, "stopSequences": ["END"] })
+        );
+        assert_eq!(body["additionalModelRequestFields"], json!({ "top_k": 50 }));
+        assert_eq!(
+            body["toolConfig"]["tools"][0]["toolSpec"]["inputSchema"]["json"]["properties"]["city"],
+            json!({ "type": "string" })
+        );
+        assert_eq!(
+            body["toolConfig"]["toolChoice"],
+            json!({ "tool": { "name": "get_weather" } })
+        );
+        assert!(body.get("model").is_none());
+    }
+
+    /// Breakpoints become standalone `cachePoint` blocks right where they appear.
+    #[test]
+    fn cache_points_follow_system_messages_and_tools() {
+        let tool = |name: &str, cache_control: Option<CacheControl>| ToolDefinition {
+            name: name.to_string(),
+            description: None,
+            input_schema: None,
+            kind: ToolKind::Function,
+            metadata: None,
+            cache_control,
+        };
+        let mut request = ChatRequest {
+            messages: vec![
+                Message {
+                    role: Role::system(),
+                    name: None,
+                    content: vec![
+                        ContentPart::Text(TextContent {
+                            text: "Reference manual".to_string(),
+                        }),
+                        ContentPart::CacheBreakpoint(CacheControl::default()),
+                    ],
+                    metadata: None,
+                },
+                Message {
+                    role: Role::user(),
+                    name: None,
+                    content: vec![
+                        ContentPart::Text(TextContent {
+                            text: "Long document".to_string(),
+                        }),
+                        ContentPart::CacheBreakpoint(CacheControl::default()),
+                        ContentPart::Text(TextContent {
+                            text: "Question".to_string(),
+                        }),
+                    ],
+                    metadata: None,
+                },
+            ],
+            options: ChatOptions::default(),
+            tools: vec![
+                tool("lookup", Some(CacheControl::default())),
+                tool("search", None),
+            ],
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        };
+
+        let body = build_converse_body(&request).expect("build");
+
+        assert_eq!(
+            body["system"],
+            json!([
+                { "text": "Reference manual" },
+                { "cachePoint": { "type": "default" } }
+            ])
+        );
+        assert_eq!(
+            body["messages"][0]["content"],
+            json!([
+                { "text": "Long document" },
+                { "cachePoint": { "type": "default" } },
+                { "text": "Question" }
+            ])
+        );
+        let tools = body["toolConfig"]["tools"].as_array().expect("tools");
+        assert_eq!(tools.len(), 3);
+        assert_eq!(tools[0]["toolSpec"]["name"], json!("lookup"));
+        assert_eq!(tools[1], json!({ "cachePoint": { "type": "default" } }));
+        assert_eq!(tools[2]["toolSpec"]["name"], json!("search"));
+
+        request.tools[0].cache_control = Some(CacheControl {
+            ttl: Some(CacheTtl::OneHour),
+        });
+        assert!(matches!(
+            build_converse_body(&request),
+            Err(LLMError::UnsupportedFeature {
+                feature: "bedrock_converse_cache_ttl"
+            })
+        ));
+    }
+
+    /// Seeds, several candidates and end-user ids have no Converse field.
+    #[test]
+    fn rejects_options_converse_cannot_express() {
+        let cases = [
+            (
+                ChatOptions {
+                    seed: Some(7),
+                    ..ChatOptions::default()
+                },
+                "bedrock_converse_seed",
+            ),
+            (
+                ChatOptions {
+                    candidate_count: Some(2),
+                    ..ChatOptions::default()
+                },
+                "bedrock_converse_candidate_count",
+            ),
+            (
+                ChatOptions {
+                    user: Some("user-42".to_string()),
+                    ..ChatOptions::default()
+                },
+                "bedrock_converse_user",
+            ),
+        ];
+        for (options, expected) in cases {
+            let mut request = ChatRequest {
+                messages: vec![text(Role::user(), "Hi")],
+                options: ChatOptions {
+                    candidate_count: Some(1),
+                    ..ChatOptions::default()
+                },
+                tools: Vec::new(),
+                tool_choice: None,
+                response_format: None,
+                metadata: None,
+            };
+            build_converse_body(&request).expect("a single candidate is the default");
+
+            request.options = options;
+            match build_converse_body(&request) {
+                Err(LLMError::UnsupportedFeature { feature }) => assert_eq!(feature, expected),
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+    }
+
+    /// Round-trips a tool call and its error result through toolUse/toolResult blocks.
+    #[test]
+    fn tool_calls_and_results_use_tool_use_blocks() {
+        let request = ChatRequest {
+            messages: vec![
+                text(Role::user(), "Weather in Paris?"),
+                Message {
+                    role: Role::assistant(),
+                    name: None,
+                    content: vec![ContentPart::ToolCall(ToolCall {
+                        id: Some("tooluse_1".to_string()),
+                        name: "get_weather".to_string(),
+                        arguments: json!({ "city": "Paris" }),
+                        kind: ToolCallKind::Function,
+                    })],
+                    metadata: None,
+                },
+                Message {
+                    role: Role("tool".to_string()),
+                    name: None,
+                    content: vec![ContentPart::ToolResult(ToolResult {
+                        call_id: Some("tooluse_1".to_string()),
+                        output: json!({ "error": "upstream timeout" }),
+                        is_error: true,
+                        content: Vec::new(),
+                        metadata: None,
+                    })],
+                    metadata: None,
+                },
+            ],
+            options: ChatOptions::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        };
+
+        let body = build_converse_body(&request).expect("build");
+        let messages = body["messages"].as_array().expect("messages");
+
+        assert_eq!(
+            messages[1]["content"][0]["toolUse"],
+            json!({ "toolUseId": "tooluse_1", "name": "get_weather", "input": { "city": "Paris" } })
+        );
+        assert_eq!(messages[2]["role"], json!("user"));
+        assert_eq!(
+            messages[2]["content"][0]["toolResult"],
+            json!({
+                "toolUseId": "tooluse_1",
+                "content": [{ "json": { "error": "upstream timeout" } }],
+                "status": "error"
+            })
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+
+use crate::error::LLMError;
+use crate::types::{
+    ChatResponse, ContentPart, FinishReason, Message, OutputItem, ProviderMetadata, Role,
+    TextContent, TokenUsage, ToolCall, ToolCallKind,
+};
+
+use super::types::{ConverseResponse, ConverseUsage};
+
+pub(crate) fn map_response(
+    resp: ConverseResponse,
+    provider: &'static str,
+    endpoint: String,
+    model: String,
+    request_id: Option<String>,
+) -> Result<ChatResponse, LLMError> {
+    let raw = serde_json::to_value(&resp).ok();
+
+    let mut outputs = Vec::new();
+    let mut message_parts = Vec::new();
+    let mut tool_calls = Vec::new();
+    let mut reasoning = Vec::new();
+
+    let blocks = resp
+        .output
+        .and_then(|output| output.message)
+        .map(|message| message.content)
+        .unwrap_or_default();
+    for block in blocks {
+        if let Some(text) = block.get("text").and_then(Value::as_str) {
+            message_parts.push(ContentPart::Text(TextContent {
+                text: text.to_string(),
+            }));
+        } else if let Some(tool_use) = block.get("toolUse") {
+            tool_calls.push(ToolCall {
+                id: tool_use
+                    .get("toolUseId")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                name: tool_use
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                arguments: tool_use.get("input").cloned().unwrap_or_else(|| json!({})),
+                kind: ToolCallKind::Function,
+            });
+        } else if let Some(text) = block
+            .pointer("/reasoningContent/reasoningText/text")
+            .and_then(Value::as_str)
+        {
+            reasoning.push(text.to_string());
+        } else {
+            // Images, documents and guard content are forwarded untouched.
+            message_parts.push(ContentPart::Data { data: block });
+        }
+    }
+
+    for text in reasoning {
+        outputs.push(OutputItem::Reasoning { text, index: 0 });
+    }
+    if !message_parts.is_empty() {
+        outputs.push(OutputItem::Message {
+            message: Message {
+                role: Role::assistant(),
+                name: None,
+                content: message_parts,
+                metadata: None,
+            },
+            index: 0,
+            logprobs: Vec::new(),
+            citations: Vec::new(),
+        });
+    }
+    for call in tool_calls {
+        outputs.push(OutputItem::ToolCall { call, index: 0 });
+    }
+
+    Ok(ChatResponse {
+        outputs,
+        usage: resp.usage.as_ref().map(convert_usage),
+        finish_reason: resp.stop_reason.as_deref().map(convert_finish_reason),
+        candidate_finish_reasons: Vec::new(),
+        prompt_safety: None,
+        model: Some(model),
+        provider: ProviderMetadata {
+            provider: provider.to_string(),
+            request_id,
+            endpoint: Some(endpoint),
+            raw,
+        },
+    })
+}
+
+pub(crate) fn convert_finish_reason(reason: &str) -> FinishReason {
+    match reason {
+        "end_turn" | "stop_sequence" => FinishReason::Stop,
+        "max_tokens" => FinishReason::Length,
+        "tool_use" => FinishReason::ToolCalls,
+        "guardrail_intervened" | "content_filtered" => FinishReason::ContentFilter,
+        other => FinishReason::Other(other.to_string()),
+    }
+}
+
+pub(crate) fn convert_usage(usage: &ConverseUsage) -> TokenUsage {
+    let mut details = HashMap::new();
+    if let Some(v) = usage.cache_read_input_tokens {
+        details.insert("cache_read_input_tokens".to_string(), json!(v));
+    }
+    if let Some(v) = usage.cache_write_input_tokens {
+        details.insert("cache_write_input_tokens".to_string(), json!(v));
+    }
+    let total_tokens = usage
+        .total_tokens
+        .or(match (usage.input_tokens, usage.output_tokens) {
+            (Some(input), Some(output)) => Some(input + output),
+            _ => None,
+        });
+    TokenUsage {
+        prompt_tokens: usage.input_tokens,
+        completion_tokens: usage.output_tokens,
+        reasoning_tokens: None,
+        total_tokens,
+        cached_input_tokens: usage.cache_read_input_tokens,
+        cache_creation_tokens: usage.cache_write_input_tokens,
+        details: if details.is_empty() {
+            None
+        } else {
+            Some(details)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_text_reasoning_and_tool_use_blocks() {
+        let resp: ConverseResponse = serde_json::from_value(json!({
+            "output": {
+                "message": {
+                    "role": "assistant",
+                    "content": [
+                        { "reasoningContent": { "reasoningText": { "text": "Need the weather tool.", "signature": "sig" } } },
+                        { "text": "Let me check." },
+                        { "toolUse": { "toolUseId": "tooluse_1", "name": "get_weather", "input": { "city": "Paris" } } }
+                    ]
+                }
+            },
+            "stopReason": "tool_use",
+            "usage": { "inputTokens": 30, "outputTokens": 12, "totalTokens": 42, "cacheReadInputTokens": 8 },
+            "metrics": { "latencyMs": 321 }
+        }))
+        .expect("parse");
+
+        let response = map_response(
+            resp,
+            "bedrock_converse",
+            "endpoint".to_string(),
+            "anthropic.claude-3-haiku-20240307-v1:0".to_string(),
+            Some("req-1".to_string()),
+        )
+        .expect("map");
+
+        assert!(matches!(
+            response.finish_reason,
+            Some(FinishReason::ToolCalls)
+        ));
+        assert!(matches!(
+            &response.outputs[0],
+            OutputItem::Reasoning { text, .. } if text == "Need the weather tool."
+        ));
+        assert!(matches!(&response.outputs[1], OutputItem::Message { .. }));
+        match &response.outputs[2] {
+            OutputItem::ToolCall { call, .. } => {
+                assert_eq!(call.id.as_deref(), Some("tooluse_1"));
+                assert_eq!(call.arguments, json!({ "city": "Paris" }));
+            }
+            other => panic!("expected tool call, got {other:?}"),
+        }
+        let usage = response.usage.expect("usage");
+        assert_eq!(usage.total_tokens, Some(42));
+        assert_eq!(usage.cached_input_tokens, Some(8));
+        assert_eq!(
+            usage.details.expect("details")["cache_read_input_tokens"],
+            json!(8)
+        );
+        assert_eq!(response.provider.request_id.as_deref(), Some("req-1"));
+        assert_eq!(
+            response.provider.raw.expect("raw")["metrics"]["latencyMs"],
+            json!(321)
+        );
+    }
+
+    #[test]
+    fn guardrail_stops_map_to_content_filter() {
+        assert!(matches!(
+            convert_finish_reason("guardrail_intervened"),
+            FinishReason::ContentFilter
+        ));
+        assert!(matches!(
+            convert_finish_reason("max_tokens"),
+            FinishReason::Length
+        ));
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::HashSet;
+
+use futures_util::StreamExt;
+use serde_json::Value;
+
+use crate::error::LLMError;
+use crate::http::HttpBodyStream;
+use crate::provider::ChatStream;
+use crate::stream::eventstream::{EventStreamDecoder, EventStreamMessage};
+use crate::types::{
+    ChatChunk, ChatEvent, ContentDelta, MessageDelta, ProviderMetadata, Role, TokenUsage,
+    ToolCallDelta, ToolCallKind,
+};
+
+use super::error::parse_bedrock_error;
+use super::response::{convert_finish_reason, convert_usage};
+use super::types::ConverseUsage;
+
+pub(crate) fn create_stream(
+    body: HttpBodyStream,
+    provider: &'static str,
+    endpoint: String,
+) -> ChatStream {
+    let mut state = StreamState::default();
+    let stream = EventStreamDecoder::new(body, provider).map(move |message| {
+        let message = message?;
+        state.convert(message, provider, &endpoint)
+    });
+    Box::pin(stream)
+}
+
+pub(crate) async fn collect_stream_text(
+    mut body: HttpBodyStream,
+    provider: &'static str,
+) -> Result<String, LLMError> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.next().await {
+        bytes.extend_from_slice(&chunk?);
+    }
+    String::from_utf8(bytes).map_err(|err| LLMError::Provider {
+        provider,
+        message: format!("failed to decode stream error body: {err}"),
+    })
+}
+
+/// Remembers which content blocks are tool calls so `contentBlockStop` can close them.
+#[derive(Default)]
+struct StreamState {
+    tool_blocks: HashSet<usize>,
+}
+
+impl StreamState {
+    fn convert(
+        &mut self,
+        message: EventStreamMessage,
+        provider: &'static str,
+        endpoint: &str,
+    ) -> Result<ChatChunk, LLMError> {
+        let payload = String::from_utf8_lossy(&message.payload);
+        match message.header_str(":message-type") {
+            Some("exception") => {
+                return Err(parse_bedrock_error(
+                    200,
+                    message.header_str(":exception-type"),
+                    &payload,
+                    None,
+                ));
+            }
+            Some("error") => {
+                return Err(LLMError::Provider {
+                    provider,
+                    message: format!(
+                        "{} ({})",
+                        message
+                            .header_str(":error-message")
+                            .unwrap_or("stream error"),
+                        message.header_str(":error-code").unwrap_or("unknown")
+                    ),
+                });
+            }
+            _ => {}
+        }
+
+        let event_type = message.header_str(":event-type").unwrap_or_default();
+        let value: Value = serde_json::from_str(&payload).map_err(|err| LLMError::Provider {
+            provider,
+            message: format!("failed to parse stream event: {err}"),
+        })?;
+        let index = value
+            .get("contentBlockIndex")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as usize;
+
+        let mut events = Vec::new();
+        let mut usage: Option<TokenUsage> = None;
+        let mut is_terminal = false;
+        match event_type {
+            "contentBlockStart" => {
+                if let Some(tool_use) = value.pointer("/start/toolUse") {
+                    self.tool_blocks.insert(index);
+                    events.push(ChatEvent::ToolCallDelta(ToolCallDelta {
+                        index,
+                        id: tool_use
+                            .get("toolUseId")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                        name: tool_use
+                            .get("name")
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                        arguments_delta: None,
+                        kind: Some(ToolCallKind::Function),
+                        is_finished: false,
+                    }));
+                }
+            }
+            "contentBlockDelta" => {
+                if let Some(text) = value.pointer("/delta/text").and_then(Value::as_str) {
+                    events.push(ChatEvent::MessageDelta(MessageDelta {
+                        index: 0,
+                        role: Some(Role::assistant()),
+                        content: vec![ContentDelta::Text {
+                            text: text.to_string(),
+                        }],
+                        finish_reason: None,
+                        logprobs: Vec::new(),
+                    }));
+                } else if let Some(input) = value
+                    .pointer("/delta/toolUse/input")
+                    .and_then(Value::as_str)
+                {
+                    events.push(ChatEvent::ToolCallDelta(ToolCallDelta {
+                        index,
+                        id: None,
+                        name: None,
+                        arguments_delta: Some(input.to_string()),
+                        kind: Some(ToolCallKind::Function),
+                        is_finished: false,
+                    }));
+                }
+            }
+            "contentBlockStop" if self.tool_blocks.remove(&index) => {
+                events.push(ChatEvent::ToolCallDelta(ToolCallDelta {
+                    index,
+                    id: None,
+                    name: None,
+                    arguments_delta: None,
+                    kind: Some(ToolCallKind::Function),
+                    is_finished: true,
+                }));
+            }
+            "messageStop" => {
+                if let Some(reason) = value.get("stopReason").and_then(Value::as_str) {
+                    events.push(ChatEvent::MessageDelta(MessageDelta {
+                        index: 0,
+                        role: Some(Role::assistant()),
+                        content: Vec::new(),
+                        finish_reason: Some(convert_finish_reason(reason)),
+                        logprobs: Vec::new(),
+                    }));
+                }
+            }
+            "metadata" => {
+                // ConverseStream reports usage after `messageStop`, so this is the last event.
+                usage = value
+                    .get("usage")
+                    .and_then(|v| serde_json::from_value::<ConverseUsage>(v.clone()).ok())
+                    .map(|u| convert_usage(&u));
+                is_terminal = true;
+            }
+            _ => {}
+        }
+
+        // Attach the raw event, keyed by its eventstream type, for debugging and extensions.
+        let mut raw = serde_json::Map::new();
+        raw.insert(event_type.to_string(), value);
+        events.push(ChatEvent::Custom {
+            data: Value::Object(raw),
+        });
+
+        Ok(ChatChunk {
+            events,
+            usage,
+            is_terminal,
+            provider: ProviderMetadata {
+                provider: provider.to_string(),
+                request_id: None,
+                endpoint: Some(endpoint.to_string()),
+                raw: None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+    use crate::stream::eventstream::encode_message;
+    use crate::types::FinishReason;
+
+    fn event(event_type: &str, payload: &str) -> Vec<u8> {
+        encode_message(
+            &[
+                (":event-type", event_type),
+                (":content-type", "application/json"),
+                (":message-type", "event"),
+            ],
+            payload.as_bytes(),
+        )
+    }
+
+    fn body(frames: Vec<Vec<u8>>) -> HttpBodyStream {
+        let bytes: Vec<u8> = frames.concat();
+        // Split at odd offsets so frames straddle chunk boundaries.
+        let chunks: Vec<Vec<u8>> = bytes.chunks(13).map(<[u8]>::to_vec).collect();
+        Box::pin(stream::iter(chunks.into_iter().map(Ok)))
+    }
+
+    #[tokio::test]
+    async fn converts_text_tool_and_metadata_events() {
+        let frames = vec![
+            event("messageStart", r#"{"role":"assistant"}"#),
+            event(
+                "contentBlockDelta",
+                r#"{"contentBlockIndex":0,"delta":{"text":"Checking."}}"#,
+            ),
+            event("contentBlockStop", r#"{"contentBlockIndex":0}"#),
+            event(
+                "contentBlockStart",
+                r#"{"contentBlockIndex":1,"start":{"toolUse":{"toolUseId":"tooluse_1","name":"get_weather"}}}"#,
+            ),
+            event(
+                "contentBlockDelta",
+                r#"{"contentBlockIndex":1,"delta":{"toolUse":{"input":"{\"city\":\"Paris\"}"}}}"#,
+            ),
+            event("contentBlockStop", r#"{"contentBlockIndex":1}"#),
+            event("messageStop", r#"{"stopReason":"tool_use"}"#),
+            event(
+                "metadata",
+                r#"{"usage":{"inputTokens":20,"outputTokens":9,"totalTokens":29},"metrics":{"latencyMs":100}}"#,
+            ),
+        ];
+        let chunks: Vec<ChatChunk> = create_stream(body(frames), "bedrock_converse", "e".into())
+            .map(|chunk| chunk.expect("chunk"))
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 8);
+
+        assert!(matches!(
+            &chunks[1].events[0],
+            ChatEvent::MessageDelta(delta)
+                if matches!(&delta.content[0], ContentDelta::Text { text } if text == "Checking.")
+        ));
+        // The text block's stop only carries the raw event.
+        assert_eq!(chunks[2].events.len(), 1);
+        assert!(matches!(
+            &chunks[3].events[0],
+            ChatEvent::ToolCallDelta(delta)
+                if delta.index == 1 && delta.name.as_deref() == Some("get_weather")
+        ));
+        assert!(matches!(
+            &chunks[4].events[0],
+            ChatEvent::ToolCallDelta(delta)
+                if delta.arguments_delta.as_deref() == Some(r#"{"city":"Paris"}"#)
+        ));
+        assert!(matches!(
+            &chunks[5].events[0],
+            ChatEvent::ToolCallDelta(delta) if delta.is_finished
+        ));
+        assert!(matches!(
+            &chunks[6].events[0],
+            ChatEvent::MessageDelta(MessageDelta {
+                finish_reason: Some(FinishReason::ToolCalls),
+                ..
+            })
+        ));
+        assert!(chunks[7].is_terminal);
+        assert_eq!(
+            chunks[7]
+                .usage
+                .as_ref()
+                .and_then(|usage| usage.total_tokens),
+            Some(29)
+        );
+    }
+
+    #[tokio::test]
+    async fn exception_frames_become_errors() {
+        let frames = vec![
+            event(
+                "contentBlockDelta",
+                r#"{"contentBlockIndex":0,"delta":{"text":"Hi"}}"#,
+            ),
+            encode_message(
+                &[
+                    (":exception-type", "throttlingException"),
+                    (":content-type", "application/json"),
+                    (":message-type", "exception"),
+                ],
+                br#"{"message":"Too many tokens, please wait before trying again."}"#,
+            ),
+        ];
+        let results: Vec<_> = create_stream(body(frames), "bedrock_converse", "e".into())
+            .collect()
+            .await;
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(LLMError::RateLimit { .. })));
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Non-streaming response payload returned by Bedrock `Converse`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConverseResponse {
+    #[serde(default)]
+    pub(crate) output: Option<ConverseOutput>,
+    /// Stop reason such as `end_turn`, `tool_use` or `guardrail_intervened`.
+    #[serde(default)]
+    pub(crate) stop_reason: Option<String>,
+    #[serde(default)]
+    pub(crate) usage: Option<ConverseUsage>,
+    /// Additional fields such as `metrics` or `trace`, forwarded to `ProviderMetadata::raw`.
+    #[serde(flatten)]
+    pub(crate) extra: HashMap<String, Value>,
+}
+
+/// Union wrapper around the generated message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ConverseOutput {
+    #[serde(default)]
+    pub(crate) message: Option<ConverseMessage>,
+}
+
+/// Assistant message with its ordered content blocks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ConverseMessage {
+    #[serde(default)]
+    pub(crate) role: String,
+    /// Each block is a single-key union (`text`, `toolUse`, `reasoningContent`, ...).
+    #[serde(default)]
+    pub(crate) content: Vec<Value>,
+}
+
+/// Token accounting reported by Converse and the stream `metadata` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConverseUsage {
+    #[serde(default)]
+    pub(crate) input_tokens: Option<u64>,
+    #[serde(default)]
+    pub(crate) output_tokens: Option<u64>,
+    #[serde(default)]
+    pub(crate) total_tokens: Option<u64>,
+    #[serde(default)]
+    pub(crate) cache_read_input_tokens: Option<u64>,
+    #[serde(default)]
+    pub(crate) cache_write_input_tokens: Option<u64>,
+}
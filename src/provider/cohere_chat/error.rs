@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::error::{LLMError, extract_model_identifier, looks_like_token_limit_error};
+
+/// Parses Cohere error payloads of the form `{"id": "...", "message": "..."}`.
+pub(crate) fn parse_cohere_error(
+    status: u16,
+    body: &str,
+    retry_after: Option<Duration>,
+) -> LLMError {
+    #[derive(Deserialize)]
+    struct ErrorBody {
+        message: String,
+    }
+
+    let Ok(ErrorBody { message }) = serde_json::from_str::<ErrorBody>(body) else {
+        return LLMError::Provider {
+            provider: "cohere_chat",
+            message: format!("status {status}: {body}"),
+        };
+    };
+
+    // Oversized prompts come back as 400 `too many tokens: ...`.
+    if looks_like_token_limit_error(None, &message) {
+        return LLMError::TokenLimitExceeded {
+            message,
+            estimated: None,
+            limit: None,
+        };
+    }
+
+    match status {
+        401 | 403 => LLMError::Auth { message },
+        404 => LLMError::ModelNotFound {
+            model: extract_model_identifier(&message),
+            message,
+        },
+        429 => LLMError::RateLimit {
+            message,
+            retry_after,
+        },
+        400 | 422 => LLMError::Validation { message },
+        _ => LLMError::Provider {
+            provider: "cohere_chat",
+            message,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_status_and_message_to_errors() {
+        assert!(matches!(
+            parse_cohere_error(
+                400,
+                r#"{"id":"e1","message":"too many tokens: total number of tokens in the prompt cannot exceed 128000"}"#,
+                None
+            ),
+            LLMError::TokenLimitExceeded { .. }
+        ));
+        match parse_cohere_error(404, r#"{"message":"model 'command-z' not found"}"#, None) {
+            LLMError::ModelNotFound { model, .. } => {
+                assert_eq!(model.as_deref(), Some("command-z"))
+            }
+            other => panic!("expected ModelNotFound, got {other:?}"),
+        }
+        assert!(matches!(
+            parse_cohere_error(
+                429,
+                r#"{"message":"trial key rate limit"}"#,
+                Some(Duration::from_secs(3))
+            ),
+            LLMError::RateLimit {
+                retry_after: Some(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            parse_cohere_error(401, r#"{"message":"invalid api token"}"#, None),
+            LLMError::Auth { .. }
+        ));
+        assert!(matches!(
+            parse_cohere_error(503, "upstream unavailable", None),
+            LLMError::Provider {
+                provider: "cohere_chat",
+                ..
+            }
+        ));
+    }
+}
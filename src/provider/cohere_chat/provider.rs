@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::config::RequestPatch;
+use crate::credential::{DynCredentialProvider, StaticCredential, invalidate_on_unauthorized};
+use crate::error::LLMError;
+use crate::http::{
+    DynHttpTransport, HttpResponse, HttpStreamResponse, post_json_stream_with_headers,
+    post_json_with_headers,
+};
+use crate::provider::{ChatStream, LLMProvider, PreparedRequest, retry::retry_after_from_headers};
+use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
+
+use super::error::parse_cohere_error;
+use super::request::build_cohere_body;
+use super::response::map_response;
+use super::stream::{collect_stream_text, create_stream};
+use super::types::CohereChatResponse;
+
+const DEFAULT_BASE_URL: &str = "https://api.cohere.com";
+const CITATION_MODES: [&str; 3] = ["FAST", "ACCURATE", "OFF"];
+const SAFETY_MODES: [&str; 3] = ["CONTEXTUAL", "STRICT", "OFF"];
+
+/// Provider for Cohere's v2 `/chat` API.
+///
+/// `document` data parts are sent as grounding `documents`, and the citations Cohere
+/// returns become typed [`Citation`](crate::types::Citation)s, one per source. Streams use the v2 typed
+/// events (`content-delta`, `tool-call-delta`, `message-end`, ...).
+pub struct CohereChatProvider {
+    pub(crate) transport: DynHttpTransport,
+    pub(crate) base_url: String,
+    pub(crate) credential: DynCredentialProvider,
+    pub(crate) defaults: Map<String, Value>,
+    pub(crate) default_model: Option<String>,
+    pub(crate) request_patch: Option<RequestPatch>,
+}
+
+impl CohereChatProvider {
+    /// `ModelConfig.extra` keys read by [`Self::from_model_config`].
+    pub const SUPPORTED_EXTRA_KEYS: &'static [&'static str] = &["citation_mode", "safety_mode"];
+
+    /// Creates a provider that authenticates with a Cohere API key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use kotoba_llm::provider::cohere_chat::CohereChatProvider;
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let provider = CohereChatProvider::new(transport, "co-test")
+    ///     .with_default_model("command-a-03-2025");
+    /// assert_eq!(provider.name(), "cohere_chat");
+    /// # }
+    /// ```
+    pub fn new(transport: DynHttpTransport, api_key: impl Into<String>) -> Self {
+        Self::from_credential_provider(transport, Arc::new(StaticCredential::new(api_key)))
+    }
+
+    /// Creates a provider that asks `credential` for its API key before every request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::credential::EnvCredential;
+    /// # use kotoba_llm::provider::cohere_chat::CohereChatProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let credential = Arc::new(EnvCredential::new("COHERE_API_KEY"));
+    /// let provider = CohereChatProvider::from_credential_provider(transport, credential);
+    /// # let _ = provider;
+    /// # }
+    /// ```
+    pub fn from_credential_provider(
+        transport: DynHttpTransport,
+        credential: DynCredentialProvider,
+    ) -> Self {
+        Self {
+            transport,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            credential,
+            defaults: Map::new(),
+            default_model: None,
+            request_patch: None,
+        }
+    }
+
+    /// Overrides the API address; a trailing `/v2` is optional.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Configures a default model when the [`ChatRequest`] omits one.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.default_model = Some(model.into());
+        self
+    }
+
+    /// Sets the default `citation_options.mode`: `FAST`, `ACCURATE` or `OFF`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use kotoba_llm::provider::cohere_chat::CohereChatProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let provider = CohereChatProvider::new(transport, "co-test")
+    ///     .with_citation_mode("ACCURATE")
+    ///     .with_safety_mode("CONTEXTUAL");
+    /// # let _ = provider;
+    /// # }
+    /// ```
+    pub fn with_citation_mode(mut self, mode: impl Into<String>) -> Self {
+        self.defaults.insert(
+            "citation_options".to_string(),
+            json!({ "mode": mode.into() }),
+        );
+        self
+    }
+
+    /// Sets the default `safety_mode`: `CONTEXTUAL`, `STRICT` or `OFF`.
+    pub fn with_safety_mode(mut self, mode: impl Into<String>) -> Self {
+        self.defaults
+            .insert("safety_mode".to_string(), Value::String(mode.into()));
+        self
+    }
+
+    /// Constructs a provider from a [`crate::config::ModelConfig`].
+    ///
+    /// `extra.citation_mode` and `extra.safety_mode` set the request defaults; both are
+    /// case-insensitive.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Auth`] when credentials are missing or invalid, and
+    /// [`LLMError::InvalidConfig`] for an unknown mode.
+    pub fn from_model_config(
+        config: &crate::config::ModelConfig,
+        transport: DynHttpTransport,
+    ) -> Result<Self, LLMError> {
+        let credential = config.credential.credential_provider("cohere_chat")?;
+        let mut provider = Self::from_credential_provider(transport, credential);
+
+        if let Some(base_url) = &config.base_url {
+            provider = provider.with_base_url(base_url.clone());
+        }
+
+        if let Some(model) = &config.default_model {
+            provider = provider.with_default_model(model.clone());
+        }
+
+        if let Some(mode) = read_mode(config, "citation_mode", &CITATION_MODES)? {
+            provider = provider.with_citation_mode(mode);
+        }
+        if let Some(mode) = read_mode(config, "safety_mode", &SAFETY_MODES)? {
+            provider = provider.with_safety_mode(mode);
+        }
+
+        provider.request_patch = config.patch.clone();
+
+        Ok(provider)
+    }
+
+    pub(crate) fn endpoint(&self) -> String {
+        let base = self.base_url.trim_end_matches('/');
+        if base.ends_with("/v2") {
+            format!("{base}/chat")
+        } else {
+            format!("{base}/v2/chat")
+        }
+    }
+
+    fn build_headers(&self, api_key: &str) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        headers.insert("Authorization".to_string(), format!("Bearer {api_key}"));
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        headers.insert("Accept".to_string(), "application/json".to_string());
+        headers
+    }
+
+    fn resolve_model(&self, request: &ChatRequest) -> Result<String, LLMError> {
+        request
+            .options
+            .model
+            .clone()
+            .or_else(|| self.default_model.clone())
+            .ok_or_else(|| LLMError::Validation {
+                message: "model is required for Cohere".to_string(),
+            })
+    }
+
+    async fn prepare(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let model = self.resolve_model(request)?;
+        let body = build_cohere_body(request, &model, stream, &self.defaults)?;
+        let token = self.credential.token().await?;
+        let mut prepared = PreparedRequest {
+            url: self.endpoint(),
+            headers: self.build_headers(&token.value),
+            body,
+        };
+        self.apply_patch(&mut prepared.url, &mut prepared.headers, &mut prepared.body);
+        Ok(prepared)
+    }
+
+    async fn send_request(&self, prepared: PreparedRequest) -> Result<HttpResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
+        post_json_with_headers(self.transport.as_ref(), url, headers, &body).await
+    }
+
+    async fn send_stream_request(
+        &self,
+        prepared: PreparedRequest,
+    ) -> Result<HttpStreamResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
+        post_json_stream_with_headers(self.transport.as_ref(), url, headers, &body).await
+    }
+
+    fn ensure_success(&self, response: HttpResponse) -> Result<String, LLMError> {
+        let HttpResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        let text = String::from_utf8(body).map_err(|err| LLMError::transport(err.to_string()))?;
+        if (200..300).contains(&status) {
+            Ok(text)
+        } else {
+            invalidate_on_unauthorized(&self.credential, status);
+            Err(parse_cohere_error(
+                status,
+                &text,
+                retry_after_from_headers(&headers),
+            ))
+        }
+    }
+
+    fn try_parse<T: DeserializeOwned>(&self, text: &str) -> Result<T, LLMError> {
+        serde_json::from_str(text).map_err(|err| LLMError::Provider {
+            provider: self.name(),
+            message: format!("failed to parse Cohere response: {err}"),
+        })
+    }
+
+    fn apply_patch(
+        &self,
+        url: &mut String,
+        headers: &mut HashMap<String, String>,
+        body: &mut Value,
+    ) {
+        if let Some(patch) = &self.request_patch {
+            patch.apply(url, headers, body);
+        }
+    }
+}
+
+fn read_mode(
+    config: &crate::config::ModelConfig,
+    key: &str,
+    allowed: &[&str],
+) -> Result<Option<String>, LLMError> {
+    let Some(value) = config.extra.get(key) else {
+        return Ok(None);
+    };
+    let mode = value
+        .as_str()
+        .map(str::to_ascii_uppercase)
+        .filter(|mode| allowed.contains(&mode.as_str()));
+    match mode {
+        Some(mode) => Ok(Some(mode)),
+        None => Err(LLMError::InvalidConfig {
+            field: format!("extra.{key}"),
+            reason: format!("expected one of {}, got {value}", allowed.join(", ")),
+        }),
+    }
+}
+
+#[async_trait]
+impl LLMProvider for CohereChatProvider {
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LLMError> {
+        let prepared = self.prepare(&request, false).await?;
+        let response = self.send_request(prepared).await?;
+        let text = self.ensure_success(response)?;
+        let parsed: CohereChatResponse = self.try_parse(&text)?;
+        map_response(parsed, self.name(), self.endpoint())
+    }
+
+    async fn stream_chat(&self, request: ChatRequest) -> Result<ChatStream, LLMError> {
+        let prepared = self.prepare(&request, true).await?;
+        let response = self.send_stream_request(prepared).await?;
+        let HttpStreamResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        if !(200..300).contains(&status) {
+            invalidate_on_unauthorized(&self.credential, status);
+            let text = collect_stream_text(body, self.name()).await?;
+            return Err(parse_cohere_error(
+                status,
+                &text,
+                retry_after_from_headers(&headers),
+            ));
+        }
+        Ok(create_stream(body, self.name(), self.endpoint()))
+    }
+
+    async fn prepare_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        self.prepare(request, stream).await
+    }
+
+    fn capabilities(&self) -> CapabilityDescriptor {
+        CapabilityDescriptor {
+            supports_stream: true,
+            supports_image_input: true,
+            supports_audio_input: false,
+            supports_video_input: false,
+            supports_tools: true,
+            supports_structured_output: true,
+            supports_parallel_tool_calls: true,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "cohere_chat"
+    }
+
+    fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Credential, ModelConfig, ProviderKind};
+    use crate::http::test_support::PanicTransport;
+    use crate::types::test_support::text_request;
+
+    fn config(extra: Value) -> ModelConfig {
+        ModelConfig {
+            handle: "cohere".to_string(),
+            provider: ProviderKind::CohereChat,
+            credential: Credential::ApiKey {
+                header: None,
+                key: "co-test".to_string(),
+            },
+            default_model: Some("command-a-03-2025".to_string()),
+            base_url: None,
+            extra: serde_json::from_value(extra).expect("extra"),
+            patch: None,
+            catalog: Vec::new(),
+        }
+    }
+
+    /// Requests target `/v2/chat` and carry the configured citation and safety modes.
+    #[tokio::test]
+    async fn applies_citation_and_safety_modes() {
+        let provider = CohereChatProvider::from_model_config(
+            &config(json!({ "citation_mode": "accurate", "safety_mode": "CONTEXTUAL" })),
+            Arc::new(PanicTransport),
+        )
+        .expect("provider");
+
+        let prepared = provider
+            .prepare_request(&text_request("ping"), true)
+            .await
+            .expect("prepared request");
+        assert_eq!(prepared.url, "https://api.cohere.com/v2/chat");
+        assert_eq!(
+            prepared.headers.get("Authorization"),
+            Some(&"Bearer co-test".to_string())
+        );
+        assert_eq!(
+            prepared.body["citation_options"],
+            json!({ "mode": "ACCURATE" })
+        );
+        assert_eq!(prepared.body["safety_mode"], json!("CONTEXTUAL"));
+        assert_eq!(prepared.body["stream"], json!(true));
+
+        match CohereChatProvider::from_model_config(
+            &config(json!({ "citation_mode": "verbose" })),
+            Arc::new(PanicTransport),
+        ) {
+            Err(LLMError::InvalidConfig { field, .. }) => {
+                assert_eq!(field, "extra.citation_mode")
+            }
+            Err(other) => panic!("unexpected error: {other:?}"),
+            Ok(_) => panic!("expected invalid config"),
+        }
+    }
+}
@@ -0,0 +1,631 @@
+use serde_json::{Map, Value, json};
+
+use crate::error::LLMError;
+use crate::types::{
+    ChatRequest, ContentPart, ImageContent, ImageDetail, ImageSource, Message, ResponseFormat,
+    TextContent, ToolCall, ToolChoice, ToolDefinition, ToolKind, ToolResult,
+};
+
+/// Builds the request body for Cohere `POST /v2/chat`.
+///
+/// `ContentPart::Data` parts of the form `{"type": "document", ...}` in system or user
+/// messages are hoisted into the top-level `documents` list used for grounded answers;
+/// inside tool messages they stay in place as tool result documents.
+pub(crate) fn build_cohere_body(
+    request: &ChatRequest,
+    model: &str,
+    stream: bool,
+    defaults: &Map<String, Value>,
+) -> Result<Value, LLMError> {
+    let mut body = Map::new();
+    body.insert("model".to_string(), Value::String(model.to_string()));
+
+    // 1. Messages, collecting grounding documents along the way.
+    let mut messages = Vec::new();
+    let mut documents = Vec::new();
+    for message in &request.messages {
+        messages.push(convert_message(message, &mut documents)?);
+    }
+    if messages.is_empty() {
+        return Err(LLMError::Validation {
+            message: "Cohere chat request requires at least one message".to_string(),
+        });
+    }
+    body.insert("messages".to_string(), Value::Array(messages));
+    if !documents.is_empty() {
+        body.insert("documents".to_string(), Value::Array(documents));
+    }
+
+    // 2. Sampling controls; Cohere names nucleus sampling `p` and top-k sampling `k`.
+    if let Some(max_tokens) = request.options.max_output_tokens {
+        body.insert("max_tokens".to_string(), Value::from(max_tokens));
+    }
+    if let Some(temperature) = request.options.temperature {
+        body.insert("temperature".to_string(), Value::from(temperature));
+    }
+    if let Some(top_p) = request.options.top_p {
+        body.insert("p".to_string(), Value::from(top_p));
+    }
+    if let Some(penalty) = request.options.presence_penalty {
+        body.insert("presence_penalty".to_string(), Value::from(penalty));
+    }
+    if let Some(penalty) = request.options.frequency_penalty {
+        body.insert("frequency_penalty".to_string(), Value::from(penalty));
+    }
+    if let Some(top_k) = request.options.top_k {
+        body.insert("k".to_string(), Value::from(top_k));
+    }
+    if let Some(stop) = &request.options.stop_sequences {
+        body.insert("stop_sequences".to_string(), json!(stop));
+    }
+    if let Some(seed) = request.options.seed {
+        body.insert("seed".to_string(), Value::from(seed));
+    }
+    if request.options.candidate_count.is_some_and(|n| n > 1) {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "cohere_chat_candidate_count",
+        });
+    }
+    if request.options.logprobs == Some(true) || request.options.top_logprobs.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "cohere_chat_logprobs",
+        });
+    }
+    if request.options.cached_content.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "cohere_chat_cached_content",
+        });
+    }
+    if request.options.user.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "cohere_chat_user",
+        });
+    }
+
+    // 3. Reasoning models take a `thinking` object with an optional token budget.
+    if let Some(reasoning) = &request.options.reasoning {
+        let mut thinking = Map::new();
+        thinking.insert("type".to_string(), Value::String("enabled".to_string()));
+        if let Some(budget) = reasoning.budget_tokens {
+            thinking.insert("token_budget".to_string(), Value::from(budget));
+        }
+        for (k, v) in &reasoning.extra {
+            thinking.insert(k.clone(), v.clone());
+        }
+        body.insert("thinking".to_string(), Value::Object(thinking));
+    }
+
+    // 4. Tools. Cohere cannot force one named tool, so the list is narrowed instead.
+    let mut tools = request.tools.iter().collect::<Vec<_>>();
+    match &request.tool_choice {
+        None | Some(ToolChoice::Auto) => {}
+        Some(ToolChoice::None) => {
+            body.insert("tool_choice".to_string(), Value::String("NONE".to_string()));
+        }
+        Some(ToolChoice::Any) => {
+            body.insert(
+                "tool_choice".to_string(),
+                Value::String("REQUIRED".to_string()),
+            );
+        }
+        Some(ToolChoice::Tool { name }) => {
+            tools.retain(|tool| &tool.name == name);
+            if tools.is_empty() {
+                return Err(LLMError::Validation {
+                    message: format!("tool_choice names unknown tool `{name}`"),
+                });
+            }
+            body.insert(
+                "tool_choice".to_string(),
+                Value::String("REQUIRED".to_string()),
+            );
+        }
+        Some(ToolChoice::Custom(value)) => {
+            body.insert("tool_choice".to_string(), value.clone());
+        }
+    }
+    if !tools.is_empty() {
+        body.insert("tools".to_string(), Value::Array(convert_tools(&tools)?));
+    }
+
+    // 5. Structured output: JSON mode, optionally constrained by a schema.
+    if let Some(format) = &request.response_format {
+        let value = match format {
+            ResponseFormat::Text => json!({ "type": "text" }),
+            ResponseFormat::JsonObject => json!({ "type": "json_object" }),
+            ResponseFormat::JsonSchema { schema } => {
+                json!({ "type": "json_object", "json_schema": schema })
+            }
+            ResponseFormat::Custom(value) => value.clone(),
+        };
+        body.insert("response_format".to_string(), value);
+    }
+
+    // 6. Provider defaults (`citation_options`, `safety_mode`), then request extras.
+    for (k, v) in defaults {
+        body.insert(k.clone(), v.clone());
+    }
+    for (k, v) in &request.options.extra {
+        if k == "documents" {
+            // Extra documents are appended to those collected from messages.
+            let list = body
+                .entry("documents")
+                .or_insert_with(|| Value::Array(Vec::new()));
+            match (list.as_array_mut(), v) {
+                (Some(list), Value::Array(extra)) => list.extend(extra.iter().cloned()),
+                _ => {
+                    return Err(LLMError::Validation {
+                        message: "options.extra.documents must be an array".to_string(),
+                    });
+                }
+            }
+        } else {
+            body.insert(k.clone(), v.clone());
+        }
+    }
+
+    body.insert("stream".to_string(), Value::Bool(stream));
+    Ok(Value::Object(body))
+}
+
+fn convert_message(message: &Message, documents: &mut Vec<Value>) -> Result<Value, LLMError> {
+    let role = match message.role.0.as_str() {
+        "developer" => "system",
+        other => other,
+    };
+    match role {
+        "tool" => convert_tool_message(message),
+        "assistant" => convert_assistant_message(message),
+        _ => {
+            let mut content = Vec::new();
+            for part in &message.content {
+                match part {
+                    ContentPart::Text(TextContent { text }) => {
+                        content.push(json!({ "type": "text", "text": text }));
+                    }
+                    ContentPart::Image(image) => content.push(convert_image(image)?),
+                    ContentPart::Data { data } if is_document(data) => {
+                        documents.push(document_entry(data));
+                    }
+                    ContentPart::Data { data } => content.push(data.clone()),
+                    // Cohere caches prompt prefixes automatically.
+                    ContentPart::CacheBreakpoint(_) => {}
+                    _ => {
+                        return Err(LLMError::UnsupportedFeature {
+                            feature: "cohere_content_type",
+                        });
+                    }
+                }
+            }
+            Ok(json!({ "role": role, "content": content }))
+        }
+    }
+}
+
+fn convert_assistant_message(message: &Message) -> Result<Value, LLMError> {
+    let mut obj = Map::new();
+    obj.insert("role".to_string(), Value::String("assistant".to_string()));
+    let mut content = Vec::new();
+    let mut tool_calls = Vec::new();
+    for part in &message.content {
+        match part {
+            ContentPart::Text(TextContent { text }) => {
+                content.push(json!({ "type": "text", "text": text }));
+            }
+            ContentPart::ToolCall(ToolCall {
+                id,
+                name,
+                arguments,
+                ..
+            }) => {
+                let id = id.clone().ok_or_else(|| LLMError::Validation {
+                    message: "tool_call content requires id for Cohere".to_string(),
+                })?;
+                let arguments = match arguments {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                tool_calls.push(json!({
+                    "id": id,
+                    "type": "function",
+                    "function": { "name": name, "arguments": arguments }
+                }));
+            }
+            ContentPart::Data { data } => content.push(data.clone()),
+            ContentPart::CacheBreakpoint(_) => {}
+            _ => {
+                return Err(LLMError::UnsupportedFeature {
+                    feature: "cohere_content_type",
+                });
+            }
+        }
+    }
+    if !tool_calls.is_empty() {
+        obj.insert("tool_calls".to_string(), Value::Array(tool_calls));
+        // Cohere expects the plan that preceded the calls; replay it when provided.
+        if let Some(plan) = message
+            .metadata
+            .as_ref()
+            .and_then(|meta| meta.get("tool_plan"))
+        {
+            obj.insert("tool_plan".to_string(), plan.clone());
+        }
+    }
+    if !content.is_empty() {
+        obj.insert("content".to_string(), Value::Array(content));
+    }
+    Ok(Value::Object(obj))
+}
+
+/// Tool results become `tool` messages; JSON objects are sent as citable documents.
+fn convert_tool_message(message: &Message) -> Result<Value, LLMError> {
+    let mut call_id = None;
+    let mut content = Vec::new();
+    for part in &message.content {
+        match part {
+            ContentPart::ToolResult(ToolResult {
+                call_id: id,
+                output,
+                content: parts,
+                ..
+            }) => {
+                if !parts.is_empty() {
+                    return Err(LLMError::UnsupportedFeature {
+                        feature: "cohere_chat_tool_result_content",
+                    });
+                }
+                if call_id.is_none() {
+                    call_id = id.clone();
+                }
+                match output {
+                    Value::String(text) => content.push(json!({ "type": "text", "text": text })),
+                    Value::Array(items) => {
+                        for item in items {
+                            content
+                                .push(json!({ "type": "document", "document": { "data": item } }));
+                        }
+                    }
+                    other => {
+                        content.push(json!({ "type": "document", "document": { "data": other } }))
+                    }
+                }
+            }
+            ContentPart::Text(TextContent { text }) => {
+                content.push(json!({ "type": "text", "text": text }));
+            }
+            ContentPart::Data { data } if is_document(data) => {
+                content.push(json!({ "type": "document", "document": document_entry(data) }));
+            }
+            ContentPart::Data { data } => content.push(data.clone()),
+            ContentPart::CacheBreakpoint(_) => {}
+            _ => {
+                return Err(LLMError::UnsupportedFeature {
+                    feature: "cohere_content_type",
+                });
+            }
+        }
+    }
+    let call_id = call_id.ok_or_else(|| LLMError::Validation {
+        message: "tool message requires a tool_result with call_id for Cohere".to_string(),
+    })?;
+    Ok(json!({ "role": "tool", "tool_call_id": call_id, "content": content }))
+}
+
+fn convert_image(image: &ImageContent) -> Result<Value, LLMError> {
+    let url = match &image.source {
+        ImageSource::Url { url } => url.clone(),
+        ImageSource::Base64 { data, mime_type } => {
+            let mime = mime_type.as_deref().unwrap_or("image/png");
+            format!("data:{mime};base64,{data}")
+        }
+        ImageSource::FileId { .. } => {
+            return Err(LLMError::UnsupportedFeature {
+                feature: "image_source_file_id",
+            });
+        }
+    };
+    let mut image_url = json!({ "url": url });
+    if let Some(detail) = &image.detail {
+        image_url["detail"] = Value::String(
+            match detail {
+                ImageDetail::Low => "low",
+                ImageDetail::High => "high",
+                ImageDetail::Auto => "auto",
+            }
+            .to_string(),
+        );
+    }
+    Ok(json!({ "type": "image_url", "image_url": image_url }))
+}
+
+fn is_document(data: &Value) -> bool {
+    data.get("type").and_then(Value::as_str) == Some("document")
+}
+
+/// Converts `{"type": "document", "id"?, "data"}` into Cohere's `{ id?, data }` shape.
+///
+/// A missing `data` key treats the remaining fields as the document itself.
+fn document_entry(data: &Value) -> Value {
+    let mut fields = data.as_object().cloned().unwrap_or_default();
+    fields.remove("type");
+    let id = fields.remove("id");
+    let data = fields.remove("data").unwrap_or(Value::Object(fields));
+    let mut entry = Map::new();
+    if let Some(id) = id {
+        entry.insert("id".to_string(), id);
+    }
+    entry.insert("data".to_string(), data);
+    Value::Object(entry)
+}
+
+fn convert_tools(tools: &[&ToolDefinition]) -> Result<Vec<Value>, LLMError> {
+    let mut result = Vec::new();
+    for tool in tools {
+        match &tool.kind {
+            ToolKind::Function => {
+                let parameters = tool
+                    .input_schema
+                    .clone()
+                    .unwrap_or_else(|| json!({ "type": "object", "properties": {} }));
+                let mut function = Map::new();
+                function.insert("name".to_string(), Value::String(tool.name.clone()));
+                if let Some(description) = &tool.description {
+                    function.insert(
+                        "description".to_string(),
+                        Value::String(description.clone()),
+                    );
+                }
+                function.insert("parameters".to_string(), parameters);
+                result.push(json!({ "type": "function", "function": function }));
+            }
+            ToolKind::Custom {
+                config: Some(config),
+                ..
+            } => result.push(config.clone()),
+            _ => {
+                return Err(LLMError::Validation {
+                    message: "Cohere tools only support function tools or custom configs"
+                        .to_string(),
+                });
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::test_support::request_with_cache_breakpoints;
+    use crate::types::{ChatOptions, Role, ToolCallKind};
+
+    fn request(messages: Vec<Message>) -> ChatRequest {
+        ChatRequest {
+            messages,
+            options: ChatOptions::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        }
+    }
+
+    /// Document parts are hoisted into `documents`; schema output uses JSON mode.
+    #[test]
+    fn build_body_with_documents_and_schema() {
+        let mut request = request(vec![Message {
+            role: Role::user(),
+            name: None,
+            content: vec![
+                ContentPart::Data {
+                    data: json!({
+                        "type": "document",
+                        "id": "doc-1",
+                        "data": { "title": "Tall penguins", "snippet": "Emperor penguins are the tallest." }
+                    }),
+                },
+                ContentPart::Text(TextContent {
+                    text: "Which penguins are the tallest?".to_string(),
+                }),
+            ],
+            metadata: None,
+        }]);
+        request.options.top_p = Some(0.9);
+        request.options.top_k = Some(40);
+        request.options.stop_sequences = Some(vec!["END".to_string()]);
+        request.options.seed = Some(7);
+        request.response_format = Some(ResponseFormat::JsonSchema {
+            schema: json!({ "type": "object", "properties": { "answer": { "type": "string" } } }),
+        });
+        request.options.extra.insert(
+            "documents".to_string(),
+            json!([{ "data": { "snippet": "Penguins live in the southern hemisphere." } }]),
+        );
+        let defaults = Map::from_iter([(
+            "citation_options".to_string(),
+            json!({ "mode": "ACCURATE" }),
+        )]);
+
+        let body =
+            build_cohere_body(&request, "command-a-03-2025", true, &defaults).expect("build");
+
+        assert_eq!(
+            body["messages"],
+            json!([{ "role": "user", "content": [{ "type": "text", "text": "Which penguins are the tallest?" }] }])
+        );
+        assert_eq!(body["documents"][0]["id"], json!("doc-1"));
+        assert_eq!(
+            body["documents"][1]["data"]["snippet"],
+            json!("Penguins live in the southern hemisphere.")
+        );
+        assert_eq!(body["response_format"]["type"], json!("json_object"));
+        assert!(body["response_format"]["json_schema"].is_object());
+        assert_eq!(body["p"], json!(0.9f32));
+        assert_eq!(body["k"], json!(40));
+        assert_eq!(body["stop_sequences"], json!(["END"]));
+        assert_eq!(body["seed"], json!(7));
+        assert_eq!(body["citation_options"], json!({ "mode": "ACCURATE" }));
+        assert_eq!(body["stream"], json!(true));
+    }
+
+    /// Several candidates and end-user ids have no v2 `/chat` field.
+    #[test]
+    fn rejects_candidates_and_end_user_ids() {
+        let cases = [
+            (
+                ChatOptions {
+                    candidate_count: Some(2),
+                    ..ChatOptions::default()
+                },
+                "cohere_chat_candidate_count",
+            ),
+            (
+                ChatOptions {
+                    user: Some("user-42".to_string()),
+                    ..ChatOptions::default()
+                },
+                "cohere_chat_user",
+            ),
+        ];
+        for (options, expected) in cases {
+            let mut request = request(vec![Message {
+                role: Role::user(),
+                name: None,
+                content: vec![ContentPart::Text(TextContent {
+                    text: "Hi".to_string(),
+                })],
+                metadata: None,
+            }]);
+            request.options.candidate_count = Some(1);
+            build_cohere_body(&request, "command-a-03-2025", false, &Map::new())
+                .expect("a single candidate is the default");
+
+            request.options = options;
+            match build_cohere_body(&request, "command-a-03-2025", false, &Map::new()) {
+                Err(LLMError::UnsupportedFeature { feature }) => assert_eq!(feature, expected),
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+    }
+
+    /// `/chat` returns no token log probabilities, so requesting them is an error.
+    #[test]
+    fn rejects_logprobs() {
+        for options in [
+            ChatOptions {
+                logprobs: Some(true),
+                ..ChatOptions::default()
+            },
+            ChatOptions {
+                top_logprobs: Some(3),
+                ..ChatOptions::default()
+            },
+        ] {
+            let mut request = request(vec![Message {
+                role: Role::user(),
+                name: None,
+                content: vec![ContentPart::Text(TextContent {
+                    text: "Hi".to_string(),
+                })],
+                metadata: None,
+            }]);
+            request.options = options;
+            match build_cohere_body(&request, "command-a-03-2025", false, &Map::new()) {
+                Err(LLMError::UnsupportedFeature { feature }) => {
+                    assert_eq!(feature, "cohere_chat_logprobs")
+                }
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+    }
+
+    /// Tool calls carry string arguments and JSON tool output becomes documents.
+    #[test]
+    fn tool_calls_results_and_named_tool_choice() {
+        let mut request = request(vec![
+            Message {
+                role: Role::assistant(),
+                name: None,
+                content: vec![ContentPart::ToolCall(ToolCall {
+                    id: Some("call_1".to_string()),
+                    name: "search".to_string(),
+                    arguments: json!({ "query": "penguins" }),
+                    kind: ToolCallKind::Function,
+                })],
+                metadata: Some(
+                    [(
+                        "tool_plan".to_string(),
+                        json!("I will search for penguins."),
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+            },
+            Message {
+                role: Role("tool".to_string()),
+                name: None,
+                content: vec![ContentPart::ToolResult(ToolResult {
+                    call_id: Some("call_1".to_string()),
+                    output: json!([{ "title": "Emperor penguin" }]),
+                    is_error: false,
+                    content: Vec::new(),
+                    metadata: None,
+                })],
+                metadata: None,
+            },
+        ]);
+        let tool = |name: &str| ToolDefinition {
+            name: name.to_string(),
+            description: None,
+            input_schema: None,
+            kind: ToolKind::Function,
+            metadata: None,
+            cache_control: None,
+        };
+        request.tools = vec![tool("search"), tool("calculator")];
+        request.tool_choice = Some(ToolChoice::Tool {
+            name: "search".to_string(),
+        });
+
+        let body =
+            build_cohere_body(&request, "command-a-03-2025", false, &Map::new()).expect("build");
+
+        assert_eq!(
+            body["messages"][0],
+            json!({
+                "role": "assistant",
+                "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": { "name": "search", "arguments": "{\"query\":\"penguins\"}" }
+                }],
+                "tool_plan": "I will search for penguins."
+            })
+        );
+        assert_eq!(
+            body["messages"][1],
+            json!({
+                "role": "tool",
+                "tool_call_id": "call_1",
+                "content": [{ "type": "document", "document": { "data": { "title": "Emperor penguin" } } }]
+            })
+        );
+        assert_eq!(body["tools"].as_array().map(Vec::len), Some(1));
+        assert_eq!(body["tool_choice"], json!("REQUIRED"));
+    }
+
+    /// Cohere has no cache markers; breakpoints and tool cache settings are dropped.
+    #[test]
+    fn cache_breakpoints_are_ignored() {
+        let (request, plain) = request_with_cache_breakpoints();
+
+        let body =
+            build_cohere_body(&request, "command-a-03-2025", false, &Map::new()).expect("build");
+
+        assert_eq!(
+            body,
+            build_cohere_body(&plain, "command-a-03-2025", false, &Map::new()).expect("build")
+        );
+        assert!(!body.to_string().contains("cache"));
+    }
+}
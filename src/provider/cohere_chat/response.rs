@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+
+use crate::error::LLMError;
+use crate::types::{
+    ChatResponse, Citation, CitationSource, ContentPart, FinishReason, Message, OutputItem,
+    ProviderMetadata, Role, TextContent, TextSpan, TokenUsage, ToolCall, ToolCallKind,
+};
+
+use super::types::{CohereChatResponse, CohereToolCall, CohereUsage};
+
+pub(crate) fn map_response(
+    resp: CohereChatResponse,
+    provider: &'static str,
+    endpoint: String,
+) -> Result<ChatResponse, LLMError> {
+    let raw = serde_json::to_value(&resp).ok();
+    let mut outputs = Vec::new();
+
+    if let Some(message) = &resp.message {
+        let mut content = Vec::new();
+        // Citations point at response content blocks; thinking blocks are not message parts.
+        let mut parts = Vec::with_capacity(message.content.len());
+        for block in &message.content {
+            parts.push(content.len());
+            match block.kind.as_str() {
+                "text" => content.push(ContentPart::Text(TextContent {
+                    text: block.text.clone().unwrap_or_default(),
+                })),
+                "thinking" => outputs.push(OutputItem::Reasoning {
+                    text: block.thinking.clone().unwrap_or_default(),
+                    index: 0,
+                }),
+                _ => content.push(ContentPart::Data {
+                    data: serde_json::to_value(block).unwrap_or_else(|_| json!({})),
+                }),
+            }
+        }
+
+        let citations: Vec<Citation> = message
+            .citations
+            .iter()
+            .flat_map(|citation| {
+                let part = citation["content_index"]
+                    .as_u64()
+                    .and_then(|index| parts.get(index as usize).copied())
+                    .unwrap_or(0);
+                convert_citation(citation, part)
+            })
+            .collect();
+
+        // The tool plan has no unified field yet; keep it on the message so callers can
+        // replay it with the tool results.
+        let mut metadata = HashMap::new();
+        if let Some(plan) = &message.tool_plan {
+            metadata.insert("tool_plan".to_string(), Value::String(plan.clone()));
+        }
+
+        if !content.is_empty() || !metadata.is_empty() {
+            outputs.push(OutputItem::Message {
+                message: Message {
+                    role: Role::assistant(),
+                    name: None,
+                    content,
+                    metadata: (!metadata.is_empty()).then_some(metadata),
+                },
+                index: 0,
+                logprobs: Vec::new(),
+                citations,
+            });
+        }
+        for call in &message.tool_calls {
+            outputs.push(OutputItem::ToolCall {
+                call: convert_tool_call(call),
+                index: 0,
+            });
+        }
+    }
+
+    Ok(ChatResponse {
+        outputs,
+        usage: resp.usage.as_ref().map(convert_usage),
+        finish_reason: resp.finish_reason.as_deref().map(convert_finish_reason),
+        candidate_finish_reasons: Vec::new(),
+        prompt_safety: None,
+        // Cohere does not echo the model name.
+        model: None,
+        provider: ProviderMetadata {
+            provider: provider.to_string(),
+            request_id: resp.id.clone(),
+            endpoint: Some(endpoint),
+            raw,
+        },
+    })
+}
+
+/// Expands a Cohere citation into one [`Citation`] per source; `start`/`end` are
+/// character offsets into the cited text block.
+pub(crate) fn convert_citation(citation: &Value, part: usize) -> Vec<Citation> {
+    let span = match (citation["start"].as_u64(), citation["end"].as_u64()) {
+        (Some(start), Some(end)) => Some(TextSpan {
+            start: start as usize,
+            end: end as usize,
+        }),
+        _ => None,
+    };
+    let Some(sources) = citation["sources"].as_array() else {
+        return Vec::new();
+    };
+    sources
+        .iter()
+        .map(|source| {
+            let kind = source["type"].as_str().unwrap_or("document");
+            let payload = match kind {
+                "tool" => &source["tool_output"],
+                _ => &source["document"],
+            };
+            let mut metadata = HashMap::new();
+            if kind != "document" {
+                metadata.insert("source_type".to_string(), json!(kind));
+            }
+            if !payload.is_null() {
+                metadata.insert(
+                    if kind == "tool" {
+                        "tool_output"
+                    } else {
+                        "document"
+                    }
+                    .to_string(),
+                    payload.clone(),
+                );
+            }
+            Citation {
+                part,
+                span,
+                source: CitationSource::Document {
+                    index: None,
+                    id: source["id"].as_str().map(str::to_string),
+                    title: payload["title"].as_str().map(str::to_string),
+                },
+                cited_text: citation["text"].as_str().map(str::to_string),
+                metadata: (!metadata.is_empty()).then_some(metadata),
+            }
+        })
+        .collect()
+}
+
+fn convert_tool_call(call: &CohereToolCall) -> ToolCall {
+    let (name, arguments) = call
+        .function
+        .as_ref()
+        .map(|function| (function.name.clone(), function.arguments.clone()))
+        .unwrap_or((None, None));
+    let arguments = match arguments {
+        Some(text) => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+        None => Value::Null,
+    };
+    ToolCall {
+        id: call.id.clone(),
+        name: name.unwrap_or_default(),
+        arguments,
+        kind: ToolCallKind::Function,
+    }
+}
+
+pub(crate) fn convert_finish_reason(reason: &str) -> FinishReason {
+    match reason {
+        "COMPLETE" | "STOP_SEQUENCE" => FinishReason::Stop,
+        "MAX_TOKENS" => FinishReason::Length,
+        "TOOL_CALL" => FinishReason::ToolCalls,
+        other => FinishReason::Other(other.to_string()),
+    }
+}
+
+/// Uses the model's token counts; billed units and cache hits go into `details`.
+pub(crate) fn convert_usage(usage: &CohereUsage) -> TokenUsage {
+    let input = usage.tokens.as_ref().and_then(|tokens| tokens.input_tokens);
+    let output = usage
+        .tokens
+        .as_ref()
+        .and_then(|tokens| tokens.output_tokens);
+    let mut details = HashMap::new();
+    if let Some(billed) = &usage.billed_units {
+        details.insert("billed_units".to_string(), billed.clone());
+    }
+    if let Some(cached) = usage.cached_tokens {
+        details.insert("cached_tokens".to_string(), Value::from(cached));
+    }
+    TokenUsage {
+        prompt_tokens: input,
+        completion_tokens: output,
+        reasoning_tokens: None,
+        total_tokens: match (input, output) {
+            (Some(input), Some(output)) => Some(input + output),
+            _ => None,
+        },
+        cached_input_tokens: usage.cached_tokens,
+        cache_creation_tokens: None,
+        details: (!details.is_empty()).then_some(details),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Maps text, citations, tool plans and tool calls from a v2 response.
+    #[test]
+    fn maps_text_citations_and_tool_calls() {
+        let resp: CohereChatResponse = serde_json::from_value(json!({
+            "id": "c14c80c3",
+            "finish_reason": "TOOL_CALL",
+            "message": {
+                "role": "assistant",
+                "content": [{ "type": "text", "text": "Emperor penguins are the tallest." }],
+                "tool_plan": "I will look up penguin heights.",
+                "tool_calls": [{
+                    "id": "search_1",
+                    "type": "function",
+                    "function": { "name": "search", "arguments": "{\"query\":\"penguin height\"}" }
+                }],
+                "citations": [{
+                    "start": 0,
+                    "end": 16,
+                    "text": "Emperor penguins",
+                    "sources": [{ "type": "document", "id": "doc-1", "document": { "id": "doc-1" } }]
+                }]
+            },
+            "usage": {
+                "billed_units": { "input_tokens": 10, "output_tokens": 7 },
+                "tokens": { "input_tokens": 120, "output_tokens": 30 }
+            }
+        }))
+        .expect("response");
+
+        let response = map_response(resp, "cohere_chat", "e".into()).expect("map");
+
+        match &response.outputs[0] {
+            OutputItem::Message {
+                message, citations, ..
+            } => {
+                assert_eq!(citations.len(), 1);
+                assert_eq!(citations[0].span, Some(TextSpan { start: 0, end: 16 }));
+                assert_eq!(citations[0].cited_text.as_deref(), Some("Emperor penguins"));
+                assert_eq!(
+                    citations[0].source,
+                    CitationSource::Document {
+                        index: None,
+                        id: Some("doc-1".to_string()),
+                        title: None,
+                    }
+                );
+                let metadata = message.metadata.as_ref().expect("metadata");
+                assert_eq!(
+                    metadata["tool_plan"],
+                    json!("I will look up penguin heights.")
+                );
+            }
+            other => panic!("unexpected output: {other:?}"),
+        }
+        match &response.outputs[1] {
+            OutputItem::ToolCall { call, .. } => {
+                assert_eq!(call.id.as_deref(), Some("search_1"));
+                assert_eq!(call.arguments, json!({ "query": "penguin height" }));
+            }
+            other => panic!("unexpected output: {other:?}"),
+        }
+        assert!(matches!(
+            response.finish_reason,
+            Some(FinishReason::ToolCalls)
+        ));
+        let usage = response.usage.expect("usage");
+        assert_eq!(usage.total_tokens, Some(150));
+        assert!(usage.details.expect("details").contains_key("billed_units"));
+        assert_eq!(response.provider.request_id.as_deref(), Some("c14c80c3"));
+    }
+}
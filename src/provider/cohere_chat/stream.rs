@@ -0,0 +1,303 @@
+use futures_util::StreamExt;
+use serde_json::{Value, json};
+
+use crate::error::LLMError;
+use crate::http::HttpBodyStream;
+use crate::provider::ChatStream;
+use crate::stream::{StreamDecoder, StreamEvent};
+use crate::types::{
+    ChatChunk, ChatEvent, ContentDelta, MessageDelta, ProviderMetadata, Role, ToolCallDelta,
+    ToolCallKind,
+};
+
+use super::response::{convert_citation, convert_finish_reason, convert_usage};
+use super::types::CohereStreamEvent;
+
+pub(crate) fn create_stream(
+    body: HttpBodyStream,
+    provider: &'static str,
+    endpoint: String,
+) -> ChatStream {
+    // `message-start` carries the generation id; repeat it on every later chunk.
+    let mut request_id: Option<String> = None;
+    let stream = StreamDecoder::new(body, provider).map(move |event| match event {
+        Ok(StreamEvent::Data(data)) => {
+            let raw: Value = serde_json::from_str(&data).map_err(|err| LLMError::Provider {
+                provider,
+                message: format!("failed to parse stream event: {err}"),
+            })?;
+            let event: CohereStreamEvent =
+                serde_json::from_value(raw.clone()).map_err(|err| LLMError::Provider {
+                    provider,
+                    message: format!("failed to parse stream event: {err}"),
+                })?;
+            if event.kind == "message-start" {
+                request_id = event.id.clone();
+            }
+            convert_stream_event(event, raw, request_id.clone(), provider, &endpoint)
+        }
+        // Cohere ends with `message-end` rather than `[DONE]`; accept it from proxies anyway.
+        Ok(StreamEvent::Done) => Ok(ChatChunk {
+            events: Vec::new(),
+            usage: None,
+            is_terminal: true,
+            provider: ProviderMetadata {
+                provider: provider.to_string(),
+                request_id: request_id.clone(),
+                endpoint: Some(endpoint.clone()),
+                raw: Some(json!({"event": "[DONE]"})),
+            },
+        }),
+        Err(err) => Err(err),
+    });
+    Box::pin(stream)
+}
+
+pub(crate) async fn collect_stream_text(
+    mut body: HttpBodyStream,
+    provider: &'static str,
+) -> Result<String, LLMError> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.next().await {
+        bytes.extend_from_slice(&chunk?);
+    }
+    String::from_utf8(bytes).map_err(|err| LLMError::Provider {
+        provider,
+        message: format!("failed to decode stream error body: {err}"),
+    })
+}
+
+fn convert_stream_event(
+    event: CohereStreamEvent,
+    raw: Value,
+    request_id: Option<String>,
+    provider: &'static str,
+    endpoint: &str,
+) -> Result<ChatChunk, LLMError> {
+    let delta = event.delta.unwrap_or_default();
+    let message = delta.message.unwrap_or(Value::Null);
+    let index = event.index.unwrap_or(0);
+    let mut events = Vec::new();
+    let mut usage = None;
+    let mut is_terminal = false;
+
+    match event.kind.as_str() {
+        "message-start" => events.push(ChatEvent::MessageDelta(MessageDelta {
+            index: 0,
+            role: Some(Role::assistant()),
+            content: Vec::new(),
+            finish_reason: None,
+            logprobs: Vec::new(),
+        })),
+        "content-start" | "content-delta" => {
+            let content = &message["content"];
+            let delta = if let Some(text) = content["text"].as_str() {
+                (!text.is_empty()).then(|| ContentDelta::Text {
+                    text: text.to_string(),
+                })
+            } else {
+                content["thinking"]
+                    .as_str()
+                    .filter(|text| !text.is_empty())
+                    .map(|text| ContentDelta::Json {
+                        value: json!({ "type": "reasoning", "text": text }),
+                    })
+            };
+            if let Some(delta) = delta {
+                events.push(ChatEvent::MessageDelta(MessageDelta {
+                    index: 0,
+                    role: None,
+                    content: vec![delta],
+                    finish_reason: None,
+                    logprobs: Vec::new(),
+                }));
+            }
+        }
+        "tool-plan-delta" => {
+            if let Some(plan) = message["tool_plan"].as_str() {
+                events.push(ChatEvent::MessageDelta(MessageDelta {
+                    index: 0,
+                    role: None,
+                    content: vec![ContentDelta::Json {
+                        value: json!({ "type": "tool_plan", "text": plan }),
+                    }],
+                    finish_reason: None,
+                    logprobs: Vec::new(),
+                }));
+            }
+        }
+        "tool-call-start" | "tool-call-delta" => {
+            let call = &message["tool_calls"];
+            events.push(ChatEvent::ToolCallDelta(ToolCallDelta {
+                index,
+                id: call["id"].as_str().map(str::to_string),
+                name: call["function"]["name"].as_str().map(str::to_string),
+                arguments_delta: call["function"]["arguments"]
+                    .as_str()
+                    .filter(|arguments| !arguments.is_empty())
+                    .map(str::to_string),
+                kind: (event.kind == "tool-call-start").then_some(ToolCallKind::Function),
+                is_finished: false,
+            }));
+        }
+        "tool-call-end" => events.push(ChatEvent::ToolCallDelta(ToolCallDelta {
+            index,
+            id: None,
+            name: None,
+            arguments_delta: None,
+            kind: None,
+            is_finished: true,
+        })),
+        "citation-start" => {
+            // Streamed text deltas all extend the first part.
+            let citations = convert_citation(&message["citations"], 0);
+            if !citations.is_empty() {
+                events.push(ChatEvent::MessageDelta(MessageDelta {
+                    index: 0,
+                    role: None,
+                    content: citations
+                        .into_iter()
+                        .map(|citation| ContentDelta::Citation { citation })
+                        .collect(),
+                    finish_reason: None,
+                    logprobs: Vec::new(),
+                }));
+            }
+        }
+        "message-end" => {
+            if let Some(error) = delta.error {
+                return Err(LLMError::Provider {
+                    provider,
+                    message: error,
+                });
+            }
+            events.push(ChatEvent::MessageDelta(MessageDelta {
+                index: 0,
+                role: None,
+                content: Vec::new(),
+                finish_reason: delta.finish_reason.as_deref().map(convert_finish_reason),
+                logprobs: Vec::new(),
+            }));
+            usage = delta.usage.as_ref().map(convert_usage);
+            is_terminal = true;
+        }
+        // `content-end`, `citation-end` and `debug` only carry bookkeeping.
+        _ => {}
+    }
+
+    Ok(ChatChunk {
+        events,
+        usage,
+        is_terminal,
+        provider: ProviderMetadata {
+            provider: provider.to_string(),
+            request_id,
+            endpoint: Some(endpoint.to_string()),
+            raw: Some(raw),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+    use crate::types::FinishReason;
+
+    fn body(events: &[Value]) -> HttpBodyStream {
+        let text: String = events
+            .iter()
+            .map(|event| {
+                format!(
+                    "event: {}\ndata: {event}\n\n",
+                    event["type"].as_str().unwrap()
+                )
+            })
+            .collect();
+        Box::pin(stream::iter(vec![Ok(text.into_bytes())]))
+    }
+
+    /// Typed v2 events become text, tool-call and terminal chunks.
+    #[tokio::test]
+    async fn converts_typed_v2_events() {
+        let events = [
+            json!({ "type": "message-start", "id": "gen-1", "delta": { "message": { "role": "assistant" } } }),
+            json!({ "type": "content-delta", "index": 0, "delta": { "message": { "content": { "text": "Hello" } } } }),
+            json!({ "type": "tool-plan-delta", "delta": { "message": { "tool_plan": "I will search." } } }),
+            json!({ "type": "tool-call-start", "index": 0, "delta": { "message": { "tool_calls": {
+                "id": "search_1", "type": "function", "function": { "name": "search", "arguments": "" } } } } }),
+            json!({ "type": "tool-call-delta", "index": 0, "delta": { "message": { "tool_calls": {
+                "function": { "arguments": "{\"query\":\"x\"}" } } } } }),
+            json!({ "type": "tool-call-end", "index": 0 }),
+            json!({ "type": "message-end", "delta": { "finish_reason": "TOOL_CALL", "usage": {
+                "tokens": { "input_tokens": 3, "output_tokens": 4 } } } }),
+        ];
+        let chunks: Vec<ChatChunk> = create_stream(body(&events), "cohere_chat", "e".into())
+            .map(|chunk| chunk.expect("chunk"))
+            .collect()
+            .await;
+
+        assert_eq!(chunks.len(), 7);
+        assert_eq!(chunks[1].provider.request_id.as_deref(), Some("gen-1"));
+        assert!(matches!(
+            &chunks[1].events[0],
+            ChatEvent::MessageDelta(delta)
+                if matches!(&delta.content[0], ContentDelta::Text { text } if text == "Hello")
+        ));
+        assert!(matches!(
+            &chunks[3].events[0],
+            ChatEvent::ToolCallDelta(delta)
+                if delta.id.as_deref() == Some("search_1") && delta.name.as_deref() == Some("search")
+        ));
+        assert!(matches!(
+            &chunks[4].events[0],
+            ChatEvent::ToolCallDelta(delta) if delta.arguments_delta.as_deref() == Some("{\"query\":\"x\"}")
+        ));
+        assert!(matches!(
+            &chunks[5].events[0],
+            ChatEvent::ToolCallDelta(delta) if delta.is_finished
+        ));
+        let last = &chunks[6];
+        assert!(last.is_terminal);
+        assert!(matches!(
+            &last.events[0],
+            ChatEvent::MessageDelta(MessageDelta {
+                finish_reason: Some(FinishReason::ToolCalls),
+                ..
+            })
+        ));
+        assert_eq!(
+            last.usage.as_ref().and_then(|usage| usage.total_tokens),
+            Some(7)
+        );
+    }
+
+    /// `citation-start` events become typed citation deltas.
+    #[tokio::test]
+    async fn converts_citation_start_events() {
+        let events = [
+            json!({ "type": "citation-start", "index": 0, "delta": { "message": { "citations": {
+            "start": 0, "end": 5, "text": "Hello",
+            "sources": [{ "type": "document", "id": "doc-1", "document": { "title": "Greetings" } }]
+        } } } }),
+        ];
+        let chunks: Vec<ChatChunk> = create_stream(body(&events), "cohere_chat", "e".into())
+            .map(|chunk| chunk.expect("chunk"))
+            .collect()
+            .await;
+
+        let ChatEvent::MessageDelta(delta) = &chunks[0].events[0] else {
+            panic!("unexpected event: {:?}", chunks[0].events);
+        };
+        let ContentDelta::Citation { citation } = &delta.content[0] else {
+            panic!("unexpected delta: {:?}", delta.content);
+        };
+        assert_eq!(citation.cited_text.as_deref(), Some("Hello"));
+        assert!(matches!(
+            &citation.source,
+            crate::types::CitationSource::Document { id: Some(id), title: Some(title), .. }
+                if id == "doc-1" && title == "Greetings"
+        ));
+    }
+}
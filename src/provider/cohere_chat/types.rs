@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Non-streaming response returned by Cohere `POST /v2/chat`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CohereChatResponse {
+    #[serde(default)]
+    pub(crate) id: Option<String>,
+    #[serde(default)]
+    pub(crate) finish_reason: Option<String>,
+    #[serde(default)]
+    pub(crate) message: Option<CohereResponseMessage>,
+    #[serde(default)]
+    pub(crate) usage: Option<CohereUsage>,
+    /// Additional fields such as `logprobs`, kept for `ProviderMetadata::raw`.
+    #[serde(flatten)]
+    pub(crate) extra: HashMap<String, Value>,
+}
+
+/// Assistant message in a response or in the `message-start` stream event.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct CohereResponseMessage {
+    #[serde(default)]
+    pub(crate) role: Option<String>,
+    #[serde(default)]
+    pub(crate) content: Vec<CohereContentBlock>,
+    #[serde(default)]
+    pub(crate) tool_plan: Option<String>,
+    #[serde(default)]
+    pub(crate) tool_calls: Vec<CohereToolCall>,
+    /// Citation objects, mapped to typed citations on the output message.
+    #[serde(default)]
+    pub(crate) citations: Vec<Value>,
+}
+
+/// Response content block; `text` and `thinking` are the documented types.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CohereContentBlock {
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    #[serde(default)]
+    pub(crate) text: Option<String>,
+    #[serde(default)]
+    pub(crate) thinking: Option<String>,
+    #[serde(flatten)]
+    pub(crate) extra: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CohereToolCall {
+    #[serde(default)]
+    pub(crate) id: Option<String>,
+    #[serde(default)]
+    pub(crate) function: Option<CohereToolFunction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CohereToolFunction {
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    /// JSON-encoded arguments string.
+    #[serde(default)]
+    pub(crate) arguments: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CohereUsage {
+    #[serde(default)]
+    pub(crate) billed_units: Option<Value>,
+    #[serde(default)]
+    pub(crate) tokens: Option<CohereTokens>,
+    #[serde(default)]
+    pub(crate) cached_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CohereTokens {
+    #[serde(default)]
+    pub(crate) input_tokens: Option<u64>,
+    #[serde(default)]
+    pub(crate) output_tokens: Option<u64>,
+}
+
+/// Typed v2 stream event; `type` is e.g. `content-delta` or `message-end`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CohereStreamEvent {
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    #[serde(default)]
+    pub(crate) id: Option<String>,
+    #[serde(default)]
+    pub(crate) index: Option<usize>,
+    #[serde(default)]
+    pub(crate) delta: Option<CohereStreamDelta>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct CohereStreamDelta {
+    /// Partial message; its shape depends on the event type, so keep it as JSON.
+    #[serde(default)]
+    pub(crate) message: Option<Value>,
+    #[serde(default)]
+    pub(crate) finish_reason: Option<String>,
+    #[serde(default)]
+    pub(crate) usage: Option<CohereUsage>,
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
@@ -0,0 +1,432 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::LLMError;
+use crate::http::{HttpMethod, HttpRequest};
+use crate::types::ChatRequest;
+
+use super::provider::{GoogleGeminiProvider, VertexTarget};
+use super::request::build_cached_content_body;
+
+/// A Gemini `cachedContents` resource: a stored prompt prefix that chat requests reference
+/// through [`crate::types::ChatOptions::cached_content`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedContent {
+    /// Resource name, e.g. `cachedContents/abc123`.
+    pub name: String,
+    /// Human-readable label set at creation.
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Model resource the cache was created for; requests must use the same model.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Creation time (RFC 3339).
+    #[serde(default)]
+    pub create_time: Option<String>,
+    /// Last update time (RFC 3339).
+    #[serde(default)]
+    pub update_time: Option<String>,
+    /// Expiry time (RFC 3339); the cache is deleted afterwards.
+    #[serde(default)]
+    pub expire_time: Option<String>,
+    /// Size of the cached prefix.
+    #[serde(default)]
+    pub usage_metadata: Option<CachedContentUsage>,
+}
+
+/// Token accounting of a [`CachedContent`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedContentUsage {
+    /// Tokens stored in the cache, billed as cached input on every reference.
+    #[serde(default)]
+    pub total_token_count: Option<u64>,
+}
+
+/// One page of [`GoogleGeminiProvider::list_cached_contents`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedContentList {
+    /// Caches on this page.
+    #[serde(default)]
+    pub cached_contents: Vec<CachedContent>,
+    /// Token for the next page; `None` on the last page.
+    #[serde(default)]
+    pub next_page_token: Option<String>,
+}
+
+/// Settings for [`GoogleGeminiProvider::create_cached_content`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CachedContentOptions {
+    /// Human-readable label.
+    pub display_name: Option<String>,
+    /// Lifetime of the cache; Gemini defaults to one hour.
+    pub ttl: Option<Duration>,
+}
+
+impl GoogleGeminiProvider {
+    /// Stores the prefix of `prefix` as a cache: its system messages, tools, `tool_choice`
+    /// and messages. The model comes from `prefix.options.model` or the default model.
+    ///
+    /// Reference the returned [`CachedContent::name`] from later requests through
+    /// `ChatOptions::cached_content`; those requests carry only the new messages, since
+    /// Gemini rejects system instructions or tools next to a cache.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use kotoba_llm::provider::google_gemini::{CachedContentOptions, GoogleGeminiProvider};
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::types::{ChatRequest, Message};
+    /// # async fn run(provider: GoogleGeminiProvider, prefix: ChatRequest, question: Message) {
+    /// let cache = provider
+    ///     .create_cached_content(
+    ///         &prefix,
+    ///         CachedContentOptions {
+    ///             ttl: Some(Duration::from_secs(600)),
+    ///             ..CachedContentOptions::default()
+    ///         },
+    ///     )
+    ///     .await
+    ///     .expect("cache created");
+    ///
+    /// let mut request = ChatRequest {
+    ///     messages: vec![question],
+    ///     ..prefix
+    /// };
+    /// request.tools.clear();
+    /// request.tool_choice = None;
+    /// request.options.cached_content = Some(cache.name);
+    /// let _response = provider.chat(request).await;
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Validation`] when no model is set or the prefix is empty, and
+    /// the mapped Gemini error when the API rejects it (e.g. a prefix below the model's
+    /// minimum cache size).
+    pub async fn create_cached_content(
+        &self,
+        prefix: &ChatRequest,
+        options: CachedContentOptions,
+    ) -> Result<CachedContent, LLMError> {
+        let model = self.resolve_model(prefix)?;
+        let mut body = build_cached_content_body(prefix, &self.model_resource(&model))?;
+        if let Some(display_name) = options.display_name {
+            body.insert("displayName".to_string(), Value::String(display_name));
+        }
+        if let Some(ttl) = options.ttl {
+            body.insert("ttl".to_string(), Value::String(format_ttl(ttl)));
+        }
+        let url = self.cached_contents_url();
+        let text = self
+            .send_cache_request(HttpMethod::Post, url, Some(Value::Object(body)))
+            .await?;
+        self.try_parse(&text)
+    }
+
+    /// Fetches the metadata of a cache; the cached prompt itself is never returned.
+    pub async fn get_cached_content(&self, name: &str) -> Result<CachedContent, LLMError> {
+        let url = self.cached_content_url(name);
+        let text = self.send_cache_request(HttpMethod::Get, url, None).await?;
+        self.try_parse(&text)
+    }
+
+    /// Lists caches owned by the project, `page_size` at a time.
+    pub async fn list_cached_contents(
+        &self,
+        page_size: Option<u32>,
+        page_token: Option<&str>,
+    ) -> Result<CachedContentList, LLMError> {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(page_size) = page_size {
+            query.append_pair("pageSize", &page_size.to_string());
+        }
+        if let Some(page_token) = page_token {
+            query.append_pair("pageToken", page_token);
+        }
+        let query = query.finish();
+        let mut url = self.cached_contents_url();
+        if !query.is_empty() {
+            url = format!("{url}?{query}");
+        }
+        let text = self.send_cache_request(HttpMethod::Get, url, None).await?;
+        self.try_parse(&text)
+    }
+
+    /// Extends or shortens a cache's lifetime to `ttl` from now.
+    pub async fn update_cached_content_ttl(
+        &self,
+        name: &str,
+        ttl: Duration,
+    ) -> Result<CachedContent, LLMError> {
+        let url = format!("{}?updateMask=ttl", self.cached_content_url(name));
+        let body = serde_json::json!({ "ttl": format_ttl(ttl) });
+        let text = self
+            .send_cache_request(HttpMethod::Patch, url, Some(body))
+            .await?;
+        self.try_parse(&text)
+    }
+
+    /// Deletes a cache before it expires.
+    pub async fn delete_cached_content(&self, name: &str) -> Result<(), LLMError> {
+        let url = self.cached_content_url(name);
+        self.send_cache_request(HttpMethod::Delete, url, None)
+            .await
+            .map(|_| ())
+    }
+
+    fn cached_contents_url(&self) -> String {
+        match &self.vertex {
+            Some(VertexTarget { project, location }) => format!(
+                "{}/projects/{project}/locations/{location}/cachedContents",
+                self.api_root()
+            ),
+            None => format!("{}/cachedContents", self.api_root()),
+        }
+    }
+
+    /// Accepts full resource names as well as bare cache ids.
+    fn cached_content_url(&self, name: &str) -> String {
+        if name.starts_with("cachedContents/") || name.starts_with("projects/") {
+            format!("{}/{name}", self.api_root())
+        } else {
+            format!("{}/{name}", self.cached_contents_url())
+        }
+    }
+
+    /// Sends a cache API call; [`crate::config::RequestPatch`] only applies to chat calls.
+    async fn send_cache_request(
+        &self,
+        method: HttpMethod,
+        url: String,
+        body: Option<Value>,
+    ) -> Result<String, LLMError> {
+        let token = self.credential.token().await?;
+        let request = HttpRequest {
+            method,
+            url,
+            headers: self.build_headers(&token.value),
+            body: body.map(|body| body.to_string().into_bytes()),
+            timeout: None,
+        };
+        let response = self.transport.send(request).await?;
+        self.ensure_success(response)
+    }
+}
+
+/// Formats a protobuf `Duration` string such as `600s` or `1.5s`.
+fn format_ttl(ttl: Duration) -> String {
+    format!("{}s", ttl.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use futures_util::stream;
+    use serde_json::json;
+
+    use super::*;
+    use crate::http::{HttpResponse, HttpStreamResponse, HttpTransport};
+    use crate::types::{ChatOptions, ContentPart, Message, Role, TextContent};
+
+    /// Records every request and answers with a fixed JSON body.
+    struct RecordingTransport {
+        requests: Mutex<Vec<HttpRequest>>,
+        response: Value,
+    }
+
+    #[async_trait]
+    impl HttpTransport for RecordingTransport {
+        async fn send(&self, request: HttpRequest) -> Result<HttpResponse, LLMError> {
+            self.requests.lock().unwrap().push(request);
+            Ok(HttpResponse {
+                status: 200,
+                headers: HashMap::new(),
+                body: self.response.to_string().into_bytes(),
+            })
+        }
+
+        async fn send_stream(&self, _request: HttpRequest) -> Result<HttpStreamResponse, LLMError> {
+            Ok(HttpStreamResponse {
+                status: 200,
+                headers: HashMap::new(),
+                body: Box::pin(stream::empty()),
+            })
+        }
+    }
+
+    fn provider(response: Value) -> (GoogleGeminiProvider, Arc<RecordingTransport>) {
+        let transport = Arc::new(RecordingTransport {
+            requests: Mutex::new(Vec::new()),
+            response,
+        });
+        let provider = GoogleGeminiProvider::new(transport.clone(), "key")
+            .with_default_model("gemini-2.0-flash");
+        (provider, transport)
+    }
+
+    fn text_message(role: Role, text: &str) -> Message {
+        Message {
+            role,
+            name: None,
+            content: vec![ContentPart::Text(TextContent {
+                text: text.to_string(),
+            })],
+            metadata: None,
+        }
+    }
+
+    fn sent_body(request: &HttpRequest) -> Value {
+        serde_json::from_slice(request.body.as_deref().expect("body")).expect("json body")
+    }
+
+    /// Creating a cache sends the prefix, model resource, TTL and display name.
+    #[tokio::test]
+    async fn create_cached_content_sends_prefix() {
+        let (provider, transport) = provider(json!({
+            "name": "cachedContents/abc",
+            "model": "models/gemini-2.0-flash",
+            "expireTime": "2026-01-01T00:10:00Z",
+            "usageMetadata": { "totalTokenCount": 4096 }
+        }));
+        let prefix = ChatRequest {
+            messages: vec![
+                text_message(Role::system(), "Answer from the manual."),
+                text_message(Role::user(), "<manual>"),
+            ],
+            options: ChatOptions::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        };
+
+        let cache = provider
+            .create_cached_content(
+                &prefix,
+                CachedContentOptions {
+                    display_name: Some("manual".to_string()),
+                    ttl: Some(Duration::from_secs(600)),
+                },
+            )
+            .await
+            .expect("create");
+        assert_eq!(cache.name, "cachedContents/abc");
+        assert_eq!(
+            cache
+                .usage_metadata
+                .and_then(|usage| usage.total_token_count),
+            Some(4096)
+        );
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(requests[0].method, HttpMethod::Post);
+        assert_eq!(
+            requests[0].url,
+            "https://generativelanguage.googleapis.com/v1beta/cachedContents"
+        );
+        let body = sent_body(&requests[0]);
+        assert_eq!(body["model"], json!("models/gemini-2.0-flash"));
+        assert_eq!(body["ttl"], json!("600s"));
+        assert_eq!(body["displayName"], json!("manual"));
+        assert_eq!(
+            body["systemInstruction"]["parts"][0]["text"],
+            json!("Answer from the manual.")
+        );
+        assert_eq!(body["contents"][0]["parts"][0]["text"], json!("<manual>"));
+        assert!(body.get("generationConfig").is_none());
+    }
+
+    /// Get, list, TTL updates and deletes address caches by name with the right verbs.
+    #[tokio::test]
+    async fn manage_cached_content_lifecycle() {
+        let (provider, transport) = provider(json!({ "name": "cachedContents/abc" }));
+
+        provider.get_cached_content("abc").await.expect("get");
+        provider
+            .list_cached_contents(Some(10), Some("next"))
+            .await
+            .expect("list");
+        provider
+            .update_cached_content_ttl("cachedContents/abc", Duration::from_millis(1500))
+            .await
+            .expect("update");
+        provider
+            .delete_cached_content("cachedContents/abc")
+            .await
+            .expect("delete");
+
+        let requests = transport.requests.lock().unwrap();
+        let base = "https://generativelanguage.googleapis.com/v1beta";
+        let calls: Vec<_> = requests
+            .iter()
+            .map(|request| (request.method, request.url.as_str()))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                (HttpMethod::Get, &*format!("{base}/cachedContents/abc")),
+                (
+                    HttpMethod::Get,
+                    &*format!("{base}/cachedContents?pageSize=10&pageToken=next")
+                ),
+                (
+                    HttpMethod::Patch,
+                    &*format!("{base}/cachedContents/abc?updateMask=ttl")
+                ),
+                (HttpMethod::Delete, &*format!("{base}/cachedContents/abc")),
+            ]
+        );
+        assert_eq!(sent_body(&requests[2]), json!({ "ttl": "1.5s" }));
+        assert!(requests[0].body.is_none());
+    }
+
+    /// Vertex AI caches live under the project location and name full model resources.
+    #[tokio::test]
+    async fn vertex_caches_use_project_paths() {
+        let (provider, transport) =
+            provider(json!({ "name": "projects/p/locations/l/cachedContents/abc" }));
+        let provider = provider.with_vertex("p", "europe-west4");
+        let prefix = ChatRequest {
+            messages: vec![text_message(Role::user(), "<manual>")],
+            options: ChatOptions::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        };
+
+        provider
+            .create_cached_content(&prefix, CachedContentOptions::default())
+            .await
+            .expect("create");
+        provider
+            .get_cached_content("projects/p/locations/europe-west4/cachedContents/abc")
+            .await
+            .expect("get");
+
+        let requests = transport.requests.lock().unwrap();
+        let base = "https://europe-west4-aiplatform.googleapis.com/v1";
+        assert_eq!(
+            requests[0].url,
+            format!("{base}/projects/p/locations/europe-west4/cachedContents")
+        );
+        assert_eq!(
+            sent_body(&requests[0])["model"],
+            json!("projects/p/locations/europe-west4/publishers/google/models/gemini-2.0-flash")
+        );
+        assert_eq!(
+            requests[1].url,
+            format!("{base}/projects/p/locations/europe-west4/cachedContents/abc")
+        );
+    }
+}
@@ -1,3 +1,4 @@
+mod cache;
 mod error;
 mod provider;
 mod request;
@@ -5,4 +6,5 @@ mod response;
 mod stream;
 mod types;
 
+pub use cache::{CachedContent, CachedContentList, CachedContentOptions, CachedContentUsage};
 pub use provider::GoogleGeminiProvider;
@@ -1,16 +1,20 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 
-use crate::config::RequestPatch;
+use crate::config::{Credential, RequestPatch};
+#[cfg(feature = "gemini-service-account")]
+use crate::credential::{CachedCredential, ServiceAccountCredential};
+use crate::credential::{DynCredentialProvider, StaticCredential, invalidate_on_unauthorized};
 use crate::error::LLMError;
 use crate::http::{
     DynHttpTransport, HttpResponse, HttpStreamResponse, post_json_stream_with_headers,
     post_json_with_headers,
 };
-use crate::provider::{ChatStream, LLMProvider, retry::retry_after_from_headers};
+use crate::provider::{ChatStream, LLMProvider, PreparedRequest, retry::retry_after_from_headers};
 use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
 
 use super::error::parse_gemini_error;
@@ -20,23 +24,65 @@ use super::stream::{collect_stream_text, create_stream};
 use super::types::GeminiGenerateContentResponse;
 
 const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+const DEFAULT_VERTEX_LOCATION: &str = "us-central1";
+
+/// Vertex AI project and region that requests are routed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VertexTarget {
+    pub(crate) project: String,
+    pub(crate) location: String,
+}
 
 /// Google Gemini GenerateContent provider implementation.
 pub struct GoogleGeminiProvider {
     pub(crate) transport: DynHttpTransport,
     pub(crate) base_url: String,
-    pub(crate) api_key: String,
+    pub(crate) credential: DynCredentialProvider,
+    pub(crate) bearer_auth: bool,
+    pub(crate) vertex: Option<VertexTarget>,
     pub(crate) default_model: Option<String>,
     pub(crate) request_patch: Option<RequestPatch>,
 }
 
 impl GoogleGeminiProvider {
+    /// `ModelConfig.extra` keys read by [`Self::from_model_config`].
+    pub const SUPPORTED_EXTRA_KEYS: &'static [&'static str] =
+        &["vertex_project", "vertex_location"];
+
     /// Creates a provider that targets the default Google Generative Language endpoint.
     pub fn new(transport: DynHttpTransport, api_key: impl Into<String>) -> Self {
+        Self::from_credential_provider(transport, Arc::new(StaticCredential::new(api_key)))
+    }
+
+    /// Creates a provider that asks `credential` for its API key before every request.
+    ///
+    /// Use this with rotating secrets, e.g. a [`crate::credential::CachedCredential`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::credential::EnvCredential;
+    /// # use kotoba_llm::provider::google_gemini::GoogleGeminiProvider;
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let credential = Arc::new(EnvCredential::new("API_KEY"));
+    /// let provider = GoogleGeminiProvider::from_credential_provider(transport, credential);
+    /// assert_eq!(provider.name(), "google_gemini");
+    /// # }
+    /// ```
+    pub fn from_credential_provider(
+        transport: DynHttpTransport,
+        credential: DynCredentialProvider,
+    ) -> Self {
         Self {
             transport,
             base_url: DEFAULT_BASE_URL.to_string(),
-            api_key: api_key.into(),
+            credential,
+            bearer_auth: false,
+            vertex: None,
             default_model: None,
             request_patch: None,
         }
@@ -48,6 +94,44 @@ impl GoogleGeminiProvider {
         self
     }
 
+    /// Sends the credential as an OAuth2 `Authorization: Bearer` token instead of
+    /// `x-goog-api-key`.
+    pub fn with_bearer_auth(mut self) -> Self {
+        self.bearer_auth = true;
+        self
+    }
+
+    /// Routes requests to the Vertex AI `publishers/google/models` endpoints of `project`
+    /// in `location` (e.g. `us-central1`).
+    ///
+    /// Vertex AI only accepts OAuth2 tokens, so this also enables bearer auth. Unless a
+    /// base URL was set explicitly, requests go to `https://{location}-aiplatform.googleapis.com`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::credential::StaticCredential;
+    /// # use kotoba_llm::provider::google_gemini::GoogleGeminiProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let token = Arc::new(StaticCredential::new("ya29.token"));
+    /// let provider = GoogleGeminiProvider::from_credential_provider(transport, token)
+    ///     .with_vertex("my-project", "europe-west4")
+    ///     .with_default_model("gemini-2.0-flash");
+    /// # let _ = provider;
+    /// # }
+    /// ```
+    pub fn with_vertex(mut self, project: impl Into<String>, location: impl Into<String>) -> Self {
+        self.vertex = Some(VertexTarget {
+            project: project.into(),
+            location: location.into(),
+        });
+        self.bearer_auth = true;
+        self
+    }
+
     /// Sets a default model such as `gemini-2.0-flash` when the request omits one.
     pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
         self.default_model = Some(model.into());
@@ -59,6 +143,11 @@ impl GoogleGeminiProvider {
     /// This method is used by the macro-driven provider registration system to build
     /// providers from declarative configuration.
     ///
+    /// [`Credential::ServiceAccount`] keys are exchanged for cached OAuth2 tokens through
+    /// `transport`. Setting `extra.vertex_project` or `extra.vertex_location` switches to
+    /// Vertex AI endpoints; the project defaults to the key's `project_id` and the
+    /// location to `us-central1`.
+    ///
     /// # Arguments
     ///
     /// * `config` - The model configuration containing credentials and settings
@@ -66,30 +155,49 @@ impl GoogleGeminiProvider {
     ///
     /// # Errors
     ///
-    /// Returns [`LLMError::Auth`] when credentials are missing or invalid.
+    /// Returns [`LLMError::Auth`] when credentials are missing or invalid, and
+    /// [`LLMError::InvalidConfig`] when Vertex AI is requested without a resolvable project.
     pub fn from_model_config(
         config: &crate::config::ModelConfig,
         transport: DynHttpTransport,
     ) -> Result<Self, LLMError> {
-        use crate::config::Credential;
-
-        let api_key = match &config.credential {
-            Credential::ApiKey { key, .. } => key.clone(),
-            Credential::Bearer { token } => token.clone(),
-            Credential::ServiceAccount { .. } => {
-                return Err(LLMError::Auth {
-                    message: "provider google_gemini does not support service account credential"
-                        .to_string(),
-                });
+        let (credential, key_project): (DynCredentialProvider, _) = match &config.credential {
+            #[cfg(feature = "gemini-service-account")]
+            Credential::ServiceAccount { json } => {
+                let account = ServiceAccountCredential::from_json(json, transport.clone())
+                    .map_err(|err| LLMError::Auth {
+                        message: format!("provider google_gemini: {err}"),
+                    })?;
+                let project = account.project_id().map(str::to_string);
+                (Arc::new(CachedCredential::new(account)), project)
             }
-            Credential::None => {
+            #[cfg(not(feature = "gemini-service-account"))]
+            Credential::ServiceAccount { .. } => {
                 return Err(LLMError::Auth {
-                    message: "provider google_gemini requires credential".to_string(),
+                    message: "provider google_gemini: service account credential requires the `gemini-service-account` cargo feature of kotoba-llm".to_string(),
                 });
             }
+            other => (other.credential_provider("google_gemini")?, None),
         };
+        let mut provider = Self::from_credential_provider(transport, credential);
+        if matches!(config.credential, Credential::ServiceAccount { .. }) {
+            provider = provider.with_bearer_auth();
+        }
 
-        let mut provider = Self::new(transport, api_key);
+        let extra_string = |key: &str| config.extra.get(key).and_then(Value::as_str);
+        let vertex_project = extra_string("vertex_project");
+        let vertex_location = extra_string("vertex_location");
+        if vertex_project.is_some() || vertex_location.is_some() {
+            let project = vertex_project
+                .map(str::to_string)
+                .or(key_project)
+                .ok_or_else(|| LLMError::InvalidConfig {
+                    field: "extra.vertex_project".to_string(),
+                    reason: "Vertex AI needs a project; set vertex_project or use a service account key with project_id".to_string(),
+                })?;
+            provider =
+                provider.with_vertex(project, vertex_location.unwrap_or(DEFAULT_VERTEX_LOCATION));
+        }
 
         if let Some(base_url) = &config.base_url {
             provider = provider.with_base_url(base_url.clone());
@@ -106,35 +214,65 @@ impl GoogleGeminiProvider {
 
     /// Builds the non-streaming endpoint URL for GenerateContent.
     pub(crate) fn endpoint(&self, model: &str) -> String {
-        let base = self.base_url.trim_end_matches('/');
-        let model_path = normalize_model(model);
-        if base.ends_with("/v1beta") {
-            format!("{base}/{model_path}:generateContent")
-        } else {
-            format!("{base}/v1beta/{model_path}:generateContent")
-        }
+        format!("{}:generateContent", self.model_url(model))
     }
 
     /// Builds the streaming endpoint URL (SSE) for GenerateContent.
     pub(crate) fn stream_endpoint(&self, model: &str) -> String {
-        let base = self.base_url.trim_end_matches('/');
-        let model_path = normalize_model(model);
-        if base.ends_with("/v1beta") {
-            format!("{base}/{model_path}:streamGenerateContent?alt=sse")
-        } else {
-            format!("{base}/v1beta/{model_path}:streamGenerateContent?alt=sse")
+        format!("{}:streamGenerateContent?alt=sse", self.model_url(model))
+    }
+
+    fn model_url(&self, model: &str) -> String {
+        format!("{}/{}", self.api_root(), self.model_resource(model))
+    }
+
+    /// Versioned API root: `{base}/v1beta`, or `{base}/v1` on Vertex AI.
+    pub(super) fn api_root(&self) -> String {
+        match &self.vertex {
+            Some(VertexTarget { location, .. }) => {
+                let base = if self.base_url == DEFAULT_BASE_URL {
+                    format!("https://{location}-aiplatform.googleapis.com")
+                } else {
+                    self.base_url.trim_end_matches('/').to_string()
+                };
+                let base = base.strip_suffix("/v1").unwrap_or(&base);
+                format!("{base}/v1")
+            }
+            None => {
+                let base = self.base_url.trim_end_matches('/');
+                if base.ends_with("/v1beta") {
+                    base.to_string()
+                } else {
+                    format!("{base}/v1beta")
+                }
+            }
+        }
+    }
+
+    /// Resource name of `model`, as used in URLs and in `cachedContents.model`.
+    pub(super) fn model_resource(&self, model: &str) -> String {
+        match &self.vertex {
+            Some(VertexTarget { project, location }) => {
+                let model = model.strip_prefix("models/").unwrap_or(model);
+                format!("projects/{project}/locations/{location}/publishers/google/models/{model}")
+            }
+            None => normalize_model(model),
         }
     }
 
-    fn build_headers(&self) -> HashMap<String, String> {
+    pub(super) fn build_headers(&self, api_key: &str) -> HashMap<String, String> {
         let mut headers = HashMap::new();
-        headers.insert("x-goog-api-key".to_string(), self.api_key.clone());
+        if self.bearer_auth {
+            headers.insert("Authorization".to_string(), format!("Bearer {api_key}"));
+        } else {
+            headers.insert("x-goog-api-key".to_string(), api_key.to_string());
+        }
         headers.insert("Content-Type".to_string(), "application/json".to_string());
         headers.insert("Accept".to_string(), "application/json".to_string());
         headers
     }
 
-    fn resolve_model(&self, request: &ChatRequest) -> Result<String, LLMError> {
+    pub(super) fn resolve_model(&self, request: &ChatRequest) -> Result<String, LLMError> {
         request
             .options
             .model
@@ -145,36 +283,42 @@ impl GoogleGeminiProvider {
             })
     }
 
-    fn build_request_body(
+    async fn prepare(
         &self,
         request: &ChatRequest,
         model: &str,
         stream: bool,
-    ) -> Result<Value, LLMError> {
-        build_gemini_body(request, model, stream)
+    ) -> Result<PreparedRequest, LLMError> {
+        let url = if stream {
+            self.stream_endpoint(model)
+        } else {
+            self.endpoint(model)
+        };
+        let body = build_gemini_body(request, model, stream)?;
+        let token = self.credential.token().await?;
+        let mut prepared = PreparedRequest {
+            url,
+            headers: self.build_headers(&token.value),
+            body,
+        };
+        self.apply_patch(&mut prepared.url, &mut prepared.headers, &mut prepared.body);
+        Ok(prepared)
     }
 
-    async fn send_request(&self, url: String, body: Value) -> Result<HttpResponse, LLMError> {
-        let mut url = url;
-        let mut headers = self.build_headers();
-        let mut body = body;
-        self.apply_patch(&mut url, &mut headers, &mut body);
+    async fn send_request(&self, prepared: PreparedRequest) -> Result<HttpResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
         post_json_with_headers(self.transport.as_ref(), url, headers, &body).await
     }
 
     async fn send_stream_request(
         &self,
-        url: String,
-        body: Value,
+        prepared: PreparedRequest,
     ) -> Result<HttpStreamResponse, LLMError> {
-        let mut url = url;
-        let mut headers = self.build_headers();
-        let mut body = body;
-        self.apply_patch(&mut url, &mut headers, &mut body);
+        let PreparedRequest { url, headers, body } = prepared;
         post_json_stream_with_headers(self.transport.as_ref(), url, headers, &body).await
     }
 
-    fn ensure_success(&self, response: HttpResponse) -> Result<String, LLMError> {
+    pub(super) fn ensure_success(&self, response: HttpResponse) -> Result<String, LLMError> {
         let HttpResponse {
             status,
             headers,
@@ -184,6 +328,7 @@ impl GoogleGeminiProvider {
         if (200..300).contains(&status) {
             Ok(text)
         } else {
+            invalidate_on_unauthorized(&self.credential, status);
             Err(parse_gemini_error(
                 status,
                 &text,
@@ -192,7 +337,7 @@ impl GoogleGeminiProvider {
         }
     }
 
-    fn try_parse<T: DeserializeOwned>(&self, text: &str) -> Result<T, LLMError> {
+    pub(super) fn try_parse<T: DeserializeOwned>(&self, text: &str) -> Result<T, LLMError> {
         serde_json::from_str(text).map_err(|err| LLMError::Provider {
             provider: self.name(),
             message: format!("failed to parse Gemini response: {err}"),
@@ -224,8 +369,8 @@ impl LLMProvider for GoogleGeminiProvider {
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LLMError> {
         let model = self.resolve_model(&request)?;
         let endpoint = self.endpoint(&model);
-        let body = self.build_request_body(&request, &model, false)?;
-        let response = self.send_request(endpoint.clone(), body).await?;
+        let prepared = self.prepare(&request, &model, false).await?;
+        let response = self.send_request(prepared).await?;
         let text = self.ensure_success(response)?;
         let parsed: GeminiGenerateContentResponse = self.try_parse(&text)?;
         map_response(parsed, self.name(), endpoint)
@@ -234,14 +379,15 @@ impl LLMProvider for GoogleGeminiProvider {
     async fn stream_chat(&self, request: ChatRequest) -> Result<ChatStream, LLMError> {
         let model = self.resolve_model(&request)?;
         let endpoint = self.stream_endpoint(&model);
-        let body = self.build_request_body(&request, &model, true)?;
-        let response = self.send_stream_request(endpoint.clone(), body).await?;
+        let prepared = self.prepare(&request, &model, true).await?;
+        let response = self.send_stream_request(prepared).await?;
         let HttpStreamResponse {
             status,
             headers,
             body,
         } = response;
         if !(200..300).contains(&status) {
+            invalidate_on_unauthorized(&self.credential, status);
             let text = collect_stream_text(body, self.name()).await?;
             return Err(parse_gemini_error(
                 status,
@@ -252,6 +398,15 @@ impl LLMProvider for GoogleGeminiProvider {
         Ok(create_stream(body, self.name(), endpoint))
     }
 
+    async fn prepare_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let model = self.resolve_model(request)?;
+        self.prepare(request, &model, stream).await
+    }
+
     fn capabilities(&self) -> CapabilityDescriptor {
         CapabilityDescriptor {
             supports_stream: true,
@@ -267,4 +422,8 @@ impl LLMProvider for GoogleGeminiProvider {
     fn name(&self) -> &'static str {
         "google_gemini"
     }
+
+    fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
 }
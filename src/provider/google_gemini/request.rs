@@ -19,21 +19,7 @@ pub(crate) fn build_gemini_body(
     let mut body = Map::new();
 
     // 1. Fold system/developer roles into `system_instruction`; add the rest to `contents`.
-    let mut system_texts = Vec::new();
-    let mut contents = Vec::new();
-    for message in &request.messages {
-        match message.role.0.as_str() {
-            "system" | "developer" => {
-                if let Some(text) = extract_text_from_message(message) {
-                    system_texts.push(text);
-                }
-            }
-            _ => {
-                contents.push(convert_message(message)?);
-            }
-        }
-    }
-
+    let (system_instruction, contents) = convert_messages(&request.messages)?;
     if contents.is_empty() {
         return Err(LLMError::Validation {
             message: "Gemini GenerateContent request requires at least one content message"
@@ -41,17 +27,8 @@ pub(crate) fn build_gemini_body(
         });
     }
     body.insert("contents".to_string(), Value::Array(contents));
-
-    if !system_texts.is_empty() {
-        // Gemini's `system_instruction` currently expects text, so concatenate system/dev messages.
-        let system_text = system_texts.join("\n\n");
-        body.insert(
-            "system_instruction".to_string(),
-            json!({
-                "role": "system",
-                "parts": [ { "text": system_text } ]
-            }),
-        );
+    if let Some(system_instruction) = system_instruction {
+        body.insert("system_instruction".to_string(), system_instruction);
     }
 
     // 2. Sampling and generation configuration maps to `generationConfig`.
@@ -72,13 +49,29 @@ pub(crate) fn build_gemini_body(
         }
     }
 
-    // 4. Pass metadata through verbatim.
+    // 4. Reference a prompt prefix stored through the `cachedContents` API. The cache
+    // already holds the system instruction and tools, and Gemini rejects repeating them.
+    if let Some(name) = &request.options.cached_content {
+        if ["system_instruction", "tools", "toolConfig"]
+            .iter()
+            .any(|key| body.contains_key(*key))
+        {
+            return Err(LLMError::Validation {
+                message: "requests that reference cached content must not set system \
+                          instructions, tools or tool_choice; they come from the cache"
+                    .to_string(),
+            });
+        }
+        body.insert("cachedContent".to_string(), Value::String(name.clone()));
+    }
+
+    // 5. Pass metadata through verbatim.
     if let Some(metadata) = &request.metadata {
         let meta: Map<String, Value> = metadata.clone().into_iter().collect();
         body.insert("metadata".to_string(), Value::Object(meta));
     }
 
-    // 5. Forward provider-specific extras (safetySettings, cachedContent, etc.).
+    // 6. Forward provider-specific extras (safetySettings, etc.).
     for (k, v) in &request.options.extra {
         body.insert(k.clone(), v.clone());
     }
@@ -86,6 +79,71 @@ pub(crate) fn build_gemini_body(
     Ok(Value::Object(body))
 }
 
+/// Builds the body of a `cachedContents.create` call from the prefix of a chat request.
+///
+/// System messages become `systemInstruction`, the remaining messages `contents`, and
+/// tools plus `tool_choice` map as in [`build_gemini_body`]; sampling options are ignored
+/// because a cache only stores prompt input. `model` must already be a resource name.
+pub(crate) fn build_cached_content_body(
+    request: &ChatRequest,
+    model: &str,
+) -> Result<Map<String, Value>, LLMError> {
+    let mut body = Map::new();
+    body.insert("model".to_string(), Value::String(model.to_string()));
+
+    let (system_instruction, contents) = convert_messages(&request.messages)?;
+    if system_instruction.is_none() && contents.is_empty() && request.tools.is_empty() {
+        return Err(LLMError::Validation {
+            message: "Gemini cached content requires messages or tools to cache".to_string(),
+        });
+    }
+    if !contents.is_empty() {
+        body.insert("contents".to_string(), Value::Array(contents));
+    }
+    if let Some(system_instruction) = system_instruction {
+        body.insert("systemInstruction".to_string(), system_instruction);
+    }
+    if !request.tools.is_empty() {
+        body.insert(
+            "tools".to_string(),
+            Value::Array(convert_tools(&request.tools)?),
+        );
+    }
+    if let Some(choice) = &request.tool_choice {
+        if let Some(config) = convert_tool_choice(choice)? {
+            body.insert("toolConfig".to_string(), config);
+        }
+    }
+    Ok(body)
+}
+
+/// Splits messages into the system instruction and Gemini `contents`.
+fn convert_messages(messages: &[Message]) -> Result<(Option<Value>, Vec<Value>), LLMError> {
+    let mut system_texts = Vec::new();
+    let mut contents = Vec::new();
+    for message in messages {
+        match message.role.0.as_str() {
+            "system" | "developer" => {
+                if let Some(text) = extract_text_from_message(message) {
+                    system_texts.push(text);
+                }
+            }
+            _ => {
+                contents.push(convert_message(message)?);
+            }
+        }
+    }
+
+    // Gemini's system instruction currently expects text, so concatenate system/dev messages.
+    let system_instruction = (!system_texts.is_empty()).then(|| {
+        json!({
+            "role": "system",
+            "parts": [ { "text": system_texts.join("\n\n") } ]
+        })
+    });
+    Ok((system_instruction, contents))
+}
+
 /// Converts a unified [`Message`] into Gemini `Content`.
 fn convert_message(message: &Message) -> Result<Value, LLMError> {
     let mut obj = Map::new();
@@ -99,7 +157,10 @@ fn convert_message(message: &Message) -> Result<Value, LLMError> {
 
     let mut parts = Vec::new();
     for part in &message.content {
-        parts.push(convert_content_part(part)?);
+        // Gemini caches implicitly or through `cachedContents`, not per-part markers.
+        if !matches!(part, ContentPart::CacheBreakpoint(_)) {
+            parts.push(convert_content_part(part)?);
+        }
     }
     obj.insert("parts".to_string(), Value::Array(parts));
 
@@ -212,6 +273,9 @@ fn convert_content_part(part: &ContentPart) -> Result<Value, LLMError> {
                 "Gemini request messages do not support embedding ToolCall/ToolResult directly"
                     .to_string(),
         }),
+        ContentPart::CacheBreakpoint(_) => Err(LLMError::Validation {
+            message: "cache breakpoints carry no content".to_string(),
+        }),
     }
 }
 
@@ -243,6 +307,29 @@ fn build_generation_config(request: &ChatRequest) -> Result<Option<Value>, LLMEr
     if let Some(penalty) = request.options.frequency_penalty {
         ensure_map(&mut cfg).insert("frequencyPenalty".to_string(), Value::from(penalty));
     }
+    if let Some(top_k) = request.options.top_k {
+        ensure_map(&mut cfg).insert("topK".to_string(), Value::from(top_k));
+    }
+    if let Some(stop) = &request.options.stop_sequences {
+        ensure_map(&mut cfg).insert("stopSequences".to_string(), json!(stop));
+    }
+    if let Some(seed) = request.options.seed {
+        ensure_map(&mut cfg).insert("seed".to_string(), Value::from(seed));
+    }
+    if let Some(count) = request.options.candidate_count {
+        ensure_map(&mut cfg).insert("candidateCount".to_string(), Value::from(count));
+    }
+    if request.options.logprobs == Some(true) || request.options.top_logprobs.is_some() {
+        ensure_map(&mut cfg).insert("responseLogprobs".to_string(), Value::Bool(true));
+    }
+    if let Some(top_logprobs) = request.options.top_logprobs {
+        ensure_map(&mut cfg).insert("logprobs".to_string(), Value::from(top_logprobs));
+    }
+    if request.options.user.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "google_gemini_user",
+        });
+    }
 
     // Response format mapping driven by `response_format` (JSON mode / JSON Schema).
     if let Some(format) = &request.response_format {
@@ -339,6 +426,7 @@ fn convert_tool_choice(choice: &ToolChoice) -> Result<Option<Value>, LLMError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::test_support::request_with_cache_breakpoints;
     use crate::types::{
         ChatOptions, ChatRequest, ContentPart, ImageDetail, ImageSource, Message, Role,
     };
@@ -439,6 +527,12 @@ mod tests {
             temperature: Some(0.5),
             top_p: Some(0.9),
             max_output_tokens: Some(256),
+            top_k: Some(32),
+            stop_sequences: Some(vec!["END".to_string()]),
+            seed: Some(7),
+            candidate_count: Some(2),
+            top_logprobs: Some(5),
+            cached_content: Some("cachedContents/abc123".to_string()),
             ..ChatOptions::default()
         };
 
@@ -481,6 +575,64 @@ mod tests {
         assert_eq!(gen_cfg["maxOutputTokens"], json!(256));
         assert_eq!(gen_cfg["response_mime_type"], json!("application/json"));
         assert_eq!(gen_cfg["response_schema"], schema);
+        assert_eq!(gen_cfg["topK"], json!(32));
+        assert_eq!(gen_cfg["stopSequences"], json!(["END"]));
+        assert_eq!(gen_cfg["seed"], json!(7));
+        assert_eq!(gen_cfg["candidateCount"], json!(2));
+        assert_eq!(gen_cfg["responseLogprobs"], json!(true));
+        assert_eq!(gen_cfg["logprobs"], json!(5));
+        assert_eq!(body["cachedContent"], json!("cachedContents/abc123"));
+
+        let mut request = request;
+        request.options.user = Some("user-42".to_string());
+        let err = build_gemini_body(&request, "models/gemini-2.0-flash", false)
+            .expect_err("Gemini has no end-user field");
+        assert!(matches!(
+            err,
+            LLMError::UnsupportedFeature {
+                feature: "google_gemini_user"
+            }
+        ));
+    }
+
+    /// Requests that reference a cache cannot repeat the cached system instruction.
+    #[test]
+    fn cached_content_requests_reject_system_instructions() {
+        let mut request = ChatRequest {
+            messages: vec![Message {
+                role: Role::user(),
+                name: None,
+                content: vec![ContentPart::Text(TextContent {
+                    text: "What does chapter 3 say?".to_string(),
+                })],
+                metadata: None,
+            }],
+            options: ChatOptions {
+                cached_content: Some("cachedContents/abc123".to_string()),
+                ..ChatOptions::default()
+            },
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        };
+        let body = build_gemini_body(&request, "models/gemini-2.0-flash", false).expect("build");
+        assert_eq!(body["cachedContent"], json!("cachedContents/abc123"));
+
+        request.messages.insert(
+            0,
+            Message {
+                role: Role::system(),
+                name: None,
+                content: vec![ContentPart::Text(TextContent {
+                    text: "Answer from the manual.".to_string(),
+                })],
+                metadata: None,
+            },
+        );
+        let err = build_gemini_body(&request, "models/gemini-2.0-flash", false)
+            .expect_err("system instructions live in the cache");
+        assert!(matches!(err, LLMError::Validation { .. }));
     }
 
     /// Validates image content mapping for inline data and file references.
@@ -523,4 +675,18 @@ mod tests {
             })
         );
     }
+
+    /// Gemini caches through `cachedContents`, so per-part breakpoints and tool markers are dropped.
+    #[test]
+    fn cache_breakpoints_are_ignored() {
+        let (request, plain) = request_with_cache_breakpoints();
+
+        let body = build_gemini_body(&request, "models/gemini-2.0-flash", false).expect("build");
+
+        assert_eq!(
+            body,
+            build_gemini_body(&plain, "models/gemini-2.0-flash", false).expect("build")
+        );
+        assert!(!body.to_string().contains("cache"));
+    }
 }
@@ -4,11 +4,16 @@ use serde_json::Value;
 
 use crate::error::LLMError;
 use crate::types::{
-    ChatResponse, ContentPart, FinishReason, Message, OutputItem, ProviderMetadata, Role,
-    TextContent, TokenUsage, ToolCall, ToolCallKind, ToolResult,
+    ChatResponse, Citation, CitationSource, ContentPart, FinishReason, Grounding, HostedToolAction,
+    HostedToolCall, HostedToolOutput, HostedToolResult, Message, OutputItem, ProviderMetadata,
+    Role, SafetyInfo, SafetyLevel, SafetyRating, TextContent, TextSpan, TokenLogprob, TokenUsage,
+    ToolCall, ToolCallKind, ToolResult, TopLogprob,
 };
 
-use super::types::{GeminiContent, GeminiGenerateContentResponse, GeminiUsageMetadata};
+use super::types::{
+    GeminiCandidate, GeminiContent, GeminiGenerateContentResponse, GeminiLogprobsResult,
+    GeminiPart, GeminiUsageMetadata,
+};
 
 /// Maps `GeminiGenerateContentResponse` to the unified [`ChatResponse`].
 pub(crate) fn map_response(
@@ -16,20 +21,79 @@ pub(crate) fn map_response(
     provider: &'static str,
     endpoint: String,
 ) -> Result<ChatResponse, LLMError> {
+    let prompt_safety = resp
+        .prompt_feedback
+        .as_ref()
+        .and_then(convert_prompt_feedback);
+    if let Some(safety) = prompt_safety
+        .as_ref()
+        .filter(|safety| safety.block_reason.is_some())
+    {
+        return Err(LLMError::PromptBlocked {
+            provider,
+            safety: safety.clone(),
+        });
+    }
     let raw = serde_json::to_value(&resp).ok();
 
     let mut outputs = Vec::new();
     for (default_index, candidate) in resp.candidates.iter().enumerate() {
         let index = candidate.index.unwrap_or(default_index);
         if let Some(content) = &candidate.content {
-            let (message, tool_calls, tool_results) = convert_candidate_content(content, provider)?;
-            outputs.push(OutputItem::Message { message, index });
+            let (message, tool_calls, tool_results, hosted) =
+                convert_candidate_content(content, index, provider)?;
+            // Grounding segments index Gemini parts; only parts kept in the message count.
+            let mut next_part = 0;
+            let part_slots: Vec<Option<usize>> = content
+                .parts
+                .iter()
+                .map(|part| {
+                    if !is_message_part(part) {
+                        None
+                    } else {
+                        next_part += 1;
+                        Some(next_part - 1)
+                    }
+                })
+                .collect();
+            let part_text = |part_index: Option<usize>| {
+                let slot = match part_index {
+                    Some(part_index) => (*part_slots.get(part_index)?)?,
+                    None => message
+                        .content
+                        .iter()
+                        .position(|part| matches!(part, ContentPart::Text(_)))?,
+                };
+                match message.content.get(slot)? {
+                    ContentPart::Text(TextContent { text }) => Some((slot, text.as_str())),
+                    _ => None,
+                }
+            };
+            let (citations, grounding) = convert_grounding(&candidate.extra, part_text);
+            outputs.push(OutputItem::Message {
+                index,
+                logprobs: candidate
+                    .logprobs_result
+                    .as_ref()
+                    .map(convert_logprobs)
+                    .unwrap_or_default(),
+                citations,
+                message,
+            });
+            if let Some(grounding) = grounding {
+                outputs.push(OutputItem::Grounding { grounding, index });
+            }
             for call in tool_calls {
                 outputs.push(OutputItem::ToolCall { call, index });
             }
             for result in tool_results {
                 outputs.push(OutputItem::ToolResult { result, index });
             }
+            outputs.extend(hosted);
+        }
+        // Blocked candidates carry ratings but no content.
+        if let Some(safety) = candidate_safety(candidate) {
+            outputs.push(OutputItem::Safety { safety, index });
         }
     }
 
@@ -37,6 +101,19 @@ pub(crate) fn map_response(
         .candidates
         .iter()
         .find_map(|c| c.finish_reason.as_deref().map(convert_finish_reason));
+    let mut candidate_finish_reasons = Vec::new();
+    if resp.candidates.len() > 1 {
+        for (default_index, candidate) in resp.candidates.iter().enumerate() {
+            let index = candidate.index.unwrap_or(default_index);
+            if candidate_finish_reasons.len() <= index {
+                candidate_finish_reasons.resize(index + 1, None);
+            }
+            candidate_finish_reasons[index] = candidate
+                .finish_reason
+                .as_deref()
+                .map(convert_finish_reason);
+        }
+    }
     let usage = resp.usage_metadata.as_ref().map(convert_usage);
     let model = resp.model_version.clone();
 
@@ -44,6 +121,8 @@ pub(crate) fn map_response(
         outputs,
         usage,
         finish_reason,
+        candidate_finish_reasons,
+        prompt_safety,
         model,
         provider: ProviderMetadata {
             provider: provider.to_string(),
@@ -54,10 +133,16 @@ pub(crate) fn map_response(
     })
 }
 
+/// Message, function calls, function results and hosted code execution items of a candidate.
+type CandidateContent = (Message, Vec<ToolCall>, Vec<ToolResult>, Vec<OutputItem>);
+
+/// Splits candidate content into the message, function calls and results, and the
+/// hosted code execution items for candidate `index`.
 fn convert_candidate_content(
     content: &GeminiContent,
+    index: usize,
     provider: &'static str,
-) -> Result<(Message, Vec<ToolCall>, Vec<ToolResult>), LLMError> {
+) -> Result<CandidateContent, LLMError> {
     let role = content
         .role
         .as_deref()
@@ -71,8 +156,19 @@ fn convert_candidate_content(
     let mut msg_parts = Vec::new();
     let mut tool_calls = Vec::new();
     let mut tool_results = Vec::new();
+    let mut hosted = Vec::new();
 
     for part in &content.parts {
+        // Code execution runs on Gemini's side; keep it out of message content.
+        if let Some(call) = convert_hosted_call(part) {
+            hosted.push(OutputItem::HostedToolCall { call, index });
+            continue;
+        }
+        if let Some(result) = convert_hosted_result(part) {
+            hosted.push(OutputItem::HostedToolResult { result, index });
+            continue;
+        }
+
         // Function calls become [`OutputItem::ToolCall`] and stay out of message content.
         if let Some(call) = &part.function_call {
             let tool_call = ToolCall {
@@ -91,6 +187,7 @@ fn convert_candidate_content(
                 call_id: None,
                 output: resp.response.clone(),
                 is_error: false,
+                content: Vec::new(),
                 metadata: None,
             };
             tool_results.push(result);
@@ -119,7 +216,215 @@ fn convert_candidate_content(
         content: msg_parts,
         metadata: None,
     };
-    Ok((message, tool_calls, tool_results))
+    Ok((message, tool_calls, tool_results, hosted))
+}
+
+/// Parts mapped into message content rather than into separate outputs.
+fn is_message_part(part: &GeminiPart) -> bool {
+    part.function_call.is_none()
+        && part.function_response.is_none()
+        && part.executable_code.is_none()
+        && part.code_execution_result.is_none()
+}
+
+/// `executableCode` part; Gemini does not assign call ids.
+pub(crate) fn convert_hosted_call(part: &GeminiPart) -> Option<HostedToolCall> {
+    let code = part.executable_code.as_ref()?;
+    Some(HostedToolCall {
+        id: None,
+        action: HostedToolAction::CodeExecution {
+            language: Some(code.language.to_ascii_lowercase()),
+            code: code.code.clone(),
+        },
+        status: None,
+    })
+}
+
+/// `codeExecutionResult` part; any outcome but `OUTCOME_OK` is reported as the error.
+pub(crate) fn convert_hosted_result(part: &GeminiPart) -> Option<HostedToolResult> {
+    let result = part.code_execution_result.as_ref()?;
+    Some(HostedToolResult {
+        call_id: None,
+        output: HostedToolOutput::CodeExecution {
+            outcome: Some(result.outcome.clone()),
+            output: result.output.clone().unwrap_or_default(),
+        },
+        error: (result.outcome != "OUTCOME_OK").then(|| result.outcome.clone()),
+    })
+}
+
+/// Reads a candidate's `groundingMetadata` and `citationMetadata`.
+///
+/// Gemini reports UTF-8 byte offsets; `part_text` resolves a Gemini part index (or the
+/// first text part when the index is absent) to the cited message part and its text so
+/// the offsets can be converted into a character [`TextSpan`].
+pub(crate) fn convert_grounding<'a>(
+    extra: &HashMap<String, Value>,
+    part_text: impl Fn(Option<usize>) -> Option<(usize, &'a str)>,
+) -> (Vec<Citation>, Option<Grounding>) {
+    let span_of = |segment: &Value| {
+        let part_index = segment["partIndex"].as_u64().map(|index| index as usize);
+        let (part, text) = part_text(part_index)?;
+        let start = segment["startIndex"].as_u64().unwrap_or(0) as usize;
+        let end = segment["endIndex"].as_u64()? as usize;
+        Some((part, TextSpan::from_byte_range(text, start, end)))
+    };
+
+    let mut citations = Vec::new();
+    let grounding_metadata = extra.get("groundingMetadata").unwrap_or(&Value::Null);
+    let chunks = grounding_metadata["groundingChunks"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for support in grounding_metadata["groundingSupports"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        let segment = &support["segment"];
+        let (part, span) = span_of(segment).unwrap_or((0, None));
+        let scores = support["confidenceScores"].as_array();
+        for (position, chunk_index) in support["groundingChunkIndices"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let Some(source) = chunk_index
+                .as_u64()
+                .and_then(|chunk_index| chunks.get(chunk_index as usize))
+                .and_then(convert_grounding_chunk)
+            else {
+                continue;
+            };
+            let metadata = scores
+                .and_then(|scores| scores.get(position))
+                .map(|score| HashMap::from([("confidence_score".to_string(), score.clone())]));
+            citations.push(Citation {
+                part,
+                span,
+                source,
+                cited_text: segment["text"].as_str().map(str::to_string),
+                metadata,
+            });
+        }
+    }
+
+    // Recitation sources: passages the answer reproduces from the web or licensed code.
+    let sources = extra.get("citationMetadata").unwrap_or(&Value::Null);
+    for source in sources["citationSources"].as_array().into_iter().flatten() {
+        let Some(url) = source["uri"].as_str() else {
+            continue;
+        };
+        let (part, span) = span_of(source).unwrap_or((0, None));
+        let metadata = source["license"]
+            .as_str()
+            .filter(|license| !license.is_empty())
+            .map(|license| HashMap::from([("license".to_string(), Value::from(license))]));
+        citations.push(Citation {
+            part,
+            span,
+            source: CitationSource::Url {
+                url: url.to_string(),
+                title: None,
+            },
+            cited_text: None,
+            metadata,
+        });
+    }
+
+    let grounding = Grounding {
+        search_queries: grounding_metadata["webSearchQueries"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|query| query.as_str().map(str::to_string))
+            .collect(),
+        search_entry_point: grounding_metadata["searchEntryPoint"]["renderedContent"]
+            .as_str()
+            .map(str::to_string),
+    };
+    let has_grounding =
+        !grounding.search_queries.is_empty() || grounding.search_entry_point.is_some();
+    (citations, has_grounding.then_some(grounding))
+}
+
+/// `web` and `maps` chunks are pages; `retrievedContext` chunks come from a retrieval store.
+fn convert_grounding_chunk(chunk: &Value) -> Option<CitationSource> {
+    let title = |value: &Value| value["title"].as_str().map(str::to_string);
+    if let Some(page) = chunk.get("web").or_else(|| chunk.get("maps")) {
+        return Some(CitationSource::Url {
+            url: page["uri"].as_str()?.to_string(),
+            title: title(page),
+        });
+    }
+    let context = chunk.get("retrievedContext")?;
+    Some(CitationSource::Document {
+        index: None,
+        id: context["uri"].as_str().map(str::to_string),
+        title: title(context),
+    })
+}
+
+/// `promptFeedback`; `blockReason` is only present when the prompt was rejected.
+pub(crate) fn convert_prompt_feedback(feedback: &Value) -> Option<SafetyInfo> {
+    let safety = SafetyInfo {
+        ratings: convert_safety_ratings(&feedback["safetyRatings"]),
+        block_reason: feedback["blockReason"].as_str().map(str::to_string),
+        message: feedback["blockReasonMessage"].as_str().map(str::to_string),
+    };
+    (!safety.ratings.is_empty() || safety.block_reason.is_some()).then_some(safety)
+}
+
+/// Candidate `safetyRatings`, with the finish reason as block reason when a safety
+/// filter stopped the candidate.
+pub(crate) fn candidate_safety(candidate: &GeminiCandidate) -> Option<SafetyInfo> {
+    let ratings = candidate
+        .extra
+        .get("safetyRatings")
+        .map(convert_safety_ratings)
+        .unwrap_or_default();
+    let block_reason = candidate
+        .finish_reason
+        .as_deref()
+        .filter(|reason| matches!(convert_finish_reason(reason), FinishReason::ContentFilter))
+        .map(str::to_string);
+    if ratings.is_empty() && block_reason.is_none() {
+        return None;
+    }
+    Some(SafetyInfo {
+        ratings,
+        message: block_reason.as_ref().and_then(|_| {
+            candidate
+                .extra
+                .get("finishMessage")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        }),
+        block_reason,
+    })
+}
+
+fn convert_safety_ratings(ratings: &Value) -> Vec<SafetyRating> {
+    let level = |rating: &Value, key: &str| rating[key].as_str().and_then(SafetyLevel::parse);
+    ratings
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|rating| {
+            let metadata: HashMap<String, Value> = ["probabilityScore", "severityScore"]
+                .into_iter()
+                .filter_map(|key| Some((key.to_string(), rating.get(key)?.clone())))
+                .collect();
+            Some(SafetyRating {
+                category: rating["category"].as_str()?.to_string(),
+                probability: level(rating, "probability"),
+                severity: level(rating, "severity"),
+                blocked: rating["blocked"].as_bool().unwrap_or(false),
+                metadata: (!metadata.is_empty()).then_some(metadata),
+            })
+        })
+        .collect()
 }
 
 /// Maps Gemini finish reasons into the common [`FinishReason`].
@@ -135,6 +440,34 @@ pub(crate) fn convert_finish_reason(reason: &str) -> FinishReason {
     }
 }
 
+/// logprobsResult -> TokenLogprob, pairing each chosen token with the alternatives at its step.
+pub(crate) fn convert_logprobs(result: &GeminiLogprobsResult) -> Vec<TokenLogprob> {
+    result
+        .chosen_candidates
+        .iter()
+        .enumerate()
+        .map(|(step, chosen)| TokenLogprob {
+            token: chosen.token.clone(),
+            logprob: chosen.log_probability,
+            bytes: None,
+            top_logprobs: result
+                .top_candidates
+                .get(step)
+                .map(|top| {
+                    top.candidates
+                        .iter()
+                        .map(|alt| TopLogprob {
+                            token: alt.token.clone(),
+                            logprob: alt.log_probability,
+                            bytes: None,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
 /// UsageMetadata -> TokenUsage
 pub(crate) fn convert_usage(usage: &GeminiUsageMetadata) -> TokenUsage {
     let mut details = HashMap::new();
@@ -158,6 +491,8 @@ pub(crate) fn convert_usage(usage: &GeminiUsageMetadata) -> TokenUsage {
         completion_tokens: usage.candidates_token_count,
         reasoning_tokens: usage.thoughts_token_count,
         total_tokens: usage.total_token_count,
+        cached_input_tokens: usage.cached_content_token_count,
+        cache_creation_tokens: None,
         details: if details.is_empty() {
             None
         } else {
@@ -168,7 +503,10 @@ pub(crate) fn convert_usage(usage: &GeminiUsageMetadata) -> TokenUsage {
 
 #[cfg(test)]
 mod tests {
-    use super::super::types::{GeminiCandidate, GeminiPart, GeminiUsageMetadata};
+    use super::super::types::{
+        GeminiCandidate, GeminiLogprobCandidate, GeminiPart, GeminiTopCandidates,
+        GeminiUsageMetadata,
+    };
     use super::*;
 
     /// Maps a basic non-streaming response.
@@ -191,6 +529,24 @@ mod tests {
             }),
             finish_reason: Some("STOP".to_string()),
             index: Some(0),
+            logprobs_result: Some(GeminiLogprobsResult {
+                top_candidates: vec![GeminiTopCandidates {
+                    candidates: vec![
+                        GeminiLogprobCandidate {
+                            token: "Hello".to_string(),
+                            log_probability: -0.1,
+                        },
+                        GeminiLogprobCandidate {
+                            token: "Hi".to_string(),
+                            log_probability: -2.5,
+                        },
+                    ],
+                }],
+                chosen_candidates: vec![GeminiLogprobCandidate {
+                    token: "Hello".to_string(),
+                    log_probability: -0.1,
+                }],
+            }),
             extra: HashMap::new(),
         };
 
@@ -221,8 +577,17 @@ mod tests {
 
         assert_eq!(mapped.outputs.len(), 1);
         match &mapped.outputs[0] {
-            OutputItem::Message { message, index } => {
+            OutputItem::Message {
+                message,
+                index,
+                logprobs,
+                ..
+            } => {
                 assert_eq!(*index, 0);
+                assert_eq!(logprobs.len(), 1);
+                assert_eq!(logprobs[0].token, "Hello");
+                assert_eq!(logprobs[0].top_logprobs.len(), 2);
+                assert_eq!(logprobs[0].top_logprobs[1].token, "Hi");
                 assert_eq!(message.role.0, "assistant");
                 assert_eq!(message.content.len(), 1);
                 match &message.content[0] {
@@ -242,6 +607,55 @@ mod tests {
     }
 
     /// Tests finish-reason mapping.
+    /// Pairs each chosen token with the alternatives reported at the same step.
+    #[test]
+    fn convert_logprobs_result_by_step() {
+        let result: GeminiLogprobsResult = serde_json::from_value(serde_json::json!({
+            "topCandidates": [
+                { "candidates": [
+                    { "token": "Yes", "logProbability": -0.02 },
+                    { "token": "No", "logProbability": -3.9 }
+                ] }
+            ],
+            "chosenCandidates": [
+                { "token": "Yes", "logProbability": -0.02 },
+                { "token": ".", "logProbability": -0.5 }
+            ]
+        }))
+        .expect("logprobs result");
+
+        let logprobs = convert_logprobs(&result);
+
+        assert_eq!(
+            logprobs,
+            [
+                TokenLogprob {
+                    token: "Yes".to_string(),
+                    logprob: -0.02,
+                    bytes: None,
+                    top_logprobs: vec![
+                        TopLogprob {
+                            token: "Yes".to_string(),
+                            logprob: -0.02,
+                            bytes: None,
+                        },
+                        TopLogprob {
+                            token: "No".to_string(),
+                            logprob: -3.9,
+                            bytes: None,
+                        },
+                    ],
+                },
+                TokenLogprob {
+                    token: ".".to_string(),
+                    logprob: -0.5,
+                    bytes: None,
+                    top_logprobs: Vec::new(),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn convert_finish_reason_variants() {
         assert!(matches!(convert_finish_reason("STOP"), FinishReason::Stop));
@@ -266,4 +680,209 @@ mod tests {
             FinishReason::Other(_)
         ));
     }
+
+    /// Grounding supports become citations with character spans, plus a grounding item.
+    #[test]
+    fn maps_grounding_metadata() {
+        let resp: GeminiGenerateContentResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [{ "text": "Café Zürich opened in 1911." }] },
+                "finishReason": "STOP",
+                "groundingMetadata": {
+                    "webSearchQueries": ["Café Zürich opening year"],
+                    "searchEntryPoint": { "renderedContent": "<div>chip</div>" },
+                    "groundingChunks": [{ "web": { "uri": "https://example.com/zurich", "title": "example.com" } }],
+                    "groundingSupports": [{
+                        "segment": { "startIndex": 14, "endIndex": 28, "text": "opened in 1911" },
+                        "groundingChunkIndices": [0],
+                        "confidenceScores": [0.93]
+                    }]
+                },
+                "citationMetadata": {
+                    "citationSources": [{ "startIndex": 0, "endIndex": 13, "uri": "https://example.com/cafe", "license": "" }]
+                }
+            }]
+        }))
+        .expect("response");
+
+        let response = map_response(resp, "google_gemini", "e".into()).expect("map");
+
+        let OutputItem::Message {
+            message, citations, ..
+        } = &response.outputs[0]
+        else {
+            panic!("unexpected output: {:?}", response.outputs[0]);
+        };
+        let ContentPart::Text(TextContent { text }) = &message.content[0] else {
+            panic!("unexpected part: {:?}", message.content[0]);
+        };
+        assert_eq!(citations.len(), 2);
+        assert_eq!(citations[0].span, Some(TextSpan { start: 12, end: 26 }));
+        assert_eq!(
+            citations[0].span.and_then(|span| span.slice(text)),
+            Some("opened in 1911")
+        );
+        assert_eq!(
+            citations[0].source,
+            CitationSource::Url {
+                url: "https://example.com/zurich".to_string(),
+                title: Some("example.com".to_string()),
+            }
+        );
+        assert_eq!(
+            citations[0].metadata.as_ref().unwrap()["confidence_score"],
+            serde_json::json!(0.93)
+        );
+        assert_eq!(
+            citations[1].span.and_then(|span| span.slice(text)),
+            Some("Café Zürich")
+        );
+        assert!(citations[1].metadata.is_none());
+
+        match &response.outputs[1] {
+            OutputItem::Grounding { grounding, index } => {
+                assert_eq!(*index, 0);
+                assert_eq!(grounding.search_queries, vec!["Café Zürich opening year"]);
+                assert_eq!(
+                    grounding.search_entry_point.as_deref(),
+                    Some("<div>chip</div>")
+                );
+            }
+            other => panic!("unexpected output: {other:?}"),
+        }
+    }
+
+    /// A prompt with `blockReason` fails with `PromptBlocked` carrying the ratings.
+    #[test]
+    fn blocked_prompts_become_errors() {
+        let resp: GeminiGenerateContentResponse = serde_json::from_value(serde_json::json!({
+            "promptFeedback": {
+                "blockReason": "SAFETY",
+                "safetyRatings": [
+                    { "category": "HARM_CATEGORY_HARASSMENT", "probability": "NEGLIGIBLE" },
+                    { "category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "HIGH", "blocked": true }
+                ]
+            }
+        }))
+        .expect("response");
+
+        match map_response(resp, "google_gemini", "e".into()) {
+            Err(LLMError::PromptBlocked { provider, safety }) => {
+                assert_eq!(provider, "google_gemini");
+                assert_eq!(safety.block_reason.as_deref(), Some("SAFETY"));
+                assert_eq!(
+                    safety.blocked_categories(),
+                    ["HARM_CATEGORY_DANGEROUS_CONTENT"]
+                );
+                assert_eq!(safety.ratings[1].probability, Some(SafetyLevel::High));
+            }
+            other => panic!("expected PromptBlocked, got {other:?}"),
+        }
+    }
+
+    /// Prompt and candidate ratings are kept; a safety stop names the block reason.
+    #[test]
+    fn maps_prompt_and_candidate_safety_ratings() {
+        let resp: GeminiGenerateContentResponse = serde_json::from_value(serde_json::json!({
+            "promptFeedback": {
+                "safetyRatings": [{ "category": "HARM_CATEGORY_HATE_SPEECH", "probability": "NEGLIGIBLE" }]
+            },
+            "candidates": [{
+                "finishReason": "SAFETY",
+                "finishMessage": "Response blocked.",
+                "safetyRatings": [{
+                    "category": "HARM_CATEGORY_SEXUALLY_EXPLICIT",
+                    "probability": "MEDIUM",
+                    "probabilityScore": 0.61,
+                    "severity": "HARM_SEVERITY_HIGH",
+                    "severityScore": 0.82,
+                    "blocked": true
+                }]
+            }]
+        }))
+        .expect("response");
+
+        let response = map_response(resp, "google_gemini", "e".into()).expect("map");
+
+        let prompt = response.prompt_safety.expect("prompt safety");
+        assert!(!prompt.is_blocked());
+        assert_eq!(prompt.ratings[0].probability, Some(SafetyLevel::Negligible));
+        match &response.outputs[..] {
+            [OutputItem::Safety { safety, index: 0 }] => {
+                assert_eq!(safety.block_reason.as_deref(), Some("SAFETY"));
+                assert_eq!(safety.message.as_deref(), Some("Response blocked."));
+                let rating = &safety.ratings[0];
+                assert_eq!(rating.probability, Some(SafetyLevel::Medium));
+                assert_eq!(rating.severity, Some(SafetyLevel::High));
+                assert!(rating.blocked);
+                assert_eq!(
+                    rating.metadata.as_ref().unwrap()["severityScore"],
+                    serde_json::json!(0.82)
+                );
+            }
+            other => panic!("unexpected outputs: {other:?}"),
+        }
+        assert!(matches!(
+            response.finish_reason,
+            Some(FinishReason::ContentFilter)
+        ));
+    }
+
+    /// Code execution parts become hosted tool outputs around the message.
+    #[test]
+    fn maps_code_execution_parts_to_hosted_tools() {
+        let resp: GeminiGenerateContentResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        { "text": "Let me compute that." },
+                        { "executableCode": { "language": "PYTHON", "code": "print(2 ** 10)" } },
+                        { "codeExecutionResult": { "outcome": "OUTCOME_OK", "output": "1024\n" } },
+                        { "text": "The answer is 1024." }
+                    ]
+                },
+                "finishReason": "STOP"
+            }]
+        }))
+        .expect("response");
+
+        let mapped = map_response(resp, "google_gemini", "e".into()).expect("map");
+
+        let OutputItem::Message { message, .. } = &mapped.outputs[0] else {
+            panic!("unexpected output: {:?}", mapped.outputs[0]);
+        };
+        assert_eq!(message.content.len(), 2);
+        let call = mapped
+            .outputs
+            .iter()
+            .find_map(|item| match item {
+                OutputItem::HostedToolCall { call, .. } => Some(call),
+                _ => None,
+            })
+            .expect("hosted call");
+        assert_eq!(
+            call.action,
+            HostedToolAction::CodeExecution {
+                language: Some("python".to_string()),
+                code: "print(2 ** 10)".to_string(),
+            }
+        );
+        let result = mapped
+            .outputs
+            .iter()
+            .find_map(|item| match item {
+                OutputItem::HostedToolResult { result, .. } => Some(result),
+                _ => None,
+            })
+            .expect("hosted result");
+        assert!(result.error.is_none());
+        assert_eq!(
+            result.output,
+            HostedToolOutput::CodeExecution {
+                outcome: Some("OUTCOME_OK".to_string()),
+                output: "1024\n".to_string(),
+            }
+        );
+    }
 }
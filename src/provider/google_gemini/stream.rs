@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use futures_util::StreamExt;
 use serde_json::json;
 
@@ -9,7 +11,10 @@ use crate::types::{
     ChatChunk, ChatEvent, ContentDelta, MessageDelta, ProviderMetadata, ToolCallDelta, ToolCallKind,
 };
 
-use super::response::{convert_finish_reason, convert_usage};
+use super::response::{
+    candidate_safety, convert_finish_reason, convert_grounding, convert_hosted_call,
+    convert_hosted_result, convert_logprobs, convert_prompt_feedback, convert_usage,
+};
 use super::types::GeminiGenerateContentResponse;
 
 /// Wraps the raw HTTP stream into a [`ChatStream`].
@@ -18,6 +23,8 @@ pub(crate) fn create_stream(
     provider: &'static str,
     endpoint: String,
 ) -> ChatStream {
+    // Grounding offsets refer to the whole streamed answer, so keep each candidate's text.
+    let mut texts = HashMap::new();
     let stream = StreamDecoder::new(body, provider).map(move |event| match event {
         Ok(StreamEvent::Data(data)) => {
             let chunk: GeminiGenerateContentResponse =
@@ -25,7 +32,7 @@ pub(crate) fn create_stream(
                     provider,
                     message: format!("failed to parse stream chunk: {err}"),
                 })?;
-            convert_stream_chunk(chunk, provider, &endpoint)
+            convert_stream_chunk(chunk, &mut texts, provider, &endpoint)
         }
         Ok(StreamEvent::Done) => Ok(ChatChunk {
             events: Vec::new(),
@@ -60,15 +67,32 @@ pub(crate) async fn collect_stream_text(
 
 fn convert_stream_chunk(
     chunk: GeminiGenerateContentResponse,
+    texts: &mut HashMap<usize, String>,
     provider: &'static str,
     endpoint: &str,
 ) -> Result<ChatChunk, LLMError> {
     use crate::types::Role;
 
     let mut events = Vec::new();
+    if let Some(safety) = chunk
+        .prompt_feedback
+        .as_ref()
+        .and_then(convert_prompt_feedback)
+    {
+        if safety.block_reason.is_some() {
+            return Err(LLMError::PromptBlocked { provider, safety });
+        }
+        events.push(ChatEvent::PromptSafety { safety });
+    }
 
     for (default_index, candidate) in chunk.candidates.iter().enumerate() {
         let index = candidate.index.unwrap_or(default_index);
+        // Every chunk repeats the ratings so far; report them once, with the finish reason.
+        let safety = candidate
+            .finish_reason
+            .is_some()
+            .then(|| candidate_safety(candidate))
+            .flatten();
         if let Some(content) = &candidate.content {
             // Map Gemini roles to unified roles (`model` -> assistant).
             let role = content
@@ -83,6 +107,16 @@ fn convert_stream_chunk(
             let mut content_deltas = Vec::new();
 
             for part in &content.parts {
+                // Code execution parts arrive whole, so they map to complete hosted events.
+                if let Some(call) = convert_hosted_call(part) {
+                    events.push(ChatEvent::HostedToolCall { index, call });
+                    continue;
+                }
+                if let Some(result) = convert_hosted_result(part) {
+                    events.push(ChatEvent::HostedToolResult { index, result });
+                    continue;
+                }
+
                 // Function-call delta translates into a [`ToolCallDelta`].
                 if let Some(call) = &part.function_call {
                     let args_str = match serde_json::to_string(&call.args) {
@@ -105,6 +139,7 @@ fn convert_stream_chunk(
                 // Text fragments become [`ContentDelta::Text`].
                 if let Some(text) = &part.text {
                     if !text.is_empty() {
+                        texts.entry(index).or_default().push_str(text);
                         content_deltas.push(ContentDelta::Text { text: text.clone() });
                         continue;
                     }
@@ -115,7 +150,31 @@ fn convert_stream_chunk(
                 content_deltas.push(ContentDelta::Json { value });
             }
 
-            if !content_deltas.is_empty() || candidate.finish_reason.is_some() {
+            // Streamed text is one growing part, so every citation points at part 0.
+            let streamed = texts.get(&index).map(String::as_str).unwrap_or_default();
+            let (citations, grounding) =
+                convert_grounding(&candidate.extra, |_| Some((0, streamed)));
+            content_deltas.extend(
+                citations
+                    .into_iter()
+                    .map(|citation| ContentDelta::Citation { citation }),
+            );
+            if let Some(grounding) = grounding {
+                content_deltas.push(ContentDelta::Grounding { grounding });
+            }
+            if let Some(safety) = safety {
+                content_deltas.push(ContentDelta::Safety { safety });
+            }
+
+            let logprobs = candidate
+                .logprobs_result
+                .as_ref()
+                .map(convert_logprobs)
+                .unwrap_or_default();
+            if !content_deltas.is_empty()
+                || candidate.finish_reason.is_some()
+                || !logprobs.is_empty()
+            {
                 let message_delta = MessageDelta {
                     index,
                     role: Some(role),
@@ -124,9 +183,22 @@ fn convert_stream_chunk(
                         .finish_reason
                         .as_deref()
                         .map(convert_finish_reason),
+                    logprobs,
                 };
                 events.push(ChatEvent::MessageDelta(message_delta));
             }
+        } else if let Some(safety) = safety {
+            // Candidates blocked by a safety filter come without content.
+            events.push(ChatEvent::MessageDelta(MessageDelta {
+                index,
+                role: Some(Role::assistant()),
+                content: vec![ContentDelta::Safety { safety }],
+                finish_reason: candidate
+                    .finish_reason
+                    .as_deref()
+                    .map(convert_finish_reason),
+                logprobs: Vec::new(),
+            }));
         }
     }
 
@@ -172,6 +244,7 @@ mod tests {
                 }),
                 finish_reason: Some("STOP".to_string()),
                 index: Some(0),
+                logprobs_result: None,
                 extra: Default::default(),
             }],
             prompt_feedback: None,
@@ -190,7 +263,8 @@ mod tests {
         };
 
         let chat_chunk =
-            convert_stream_chunk(chunk, "google_gemini", "endpoint").expect("convert succeeds");
+            convert_stream_chunk(chunk, &mut HashMap::new(), "google_gemini", "endpoint")
+                .expect("convert succeeds");
 
         assert!(!chat_chunk.is_terminal);
         assert_eq!(chat_chunk.provider.provider, "google_gemini");
@@ -219,4 +293,70 @@ mod tests {
             other => panic!("unexpected chat event: {other:?}"),
         }
     }
+
+    /// Grounding on the final chunk resolves offsets against all streamed text.
+    #[test]
+    fn grounding_offsets_span_streamed_text() {
+        let chunk = |value: serde_json::Value| -> GeminiGenerateContentResponse {
+            serde_json::from_value(value).expect("chunk")
+        };
+        let mut texts = HashMap::new();
+        convert_stream_chunk(
+            chunk(json!({ "candidates": [{ "content": { "role": "model", "parts": [{ "text": "Zürich " }] } }] })),
+            &mut texts,
+            "google_gemini",
+            "endpoint",
+        )
+        .expect("first chunk");
+        let last = convert_stream_chunk(
+            chunk(json!({ "candidates": [{
+                "content": { "role": "model", "parts": [{ "text": "is in Switzerland." }] },
+                "finishReason": "STOP",
+                "groundingMetadata": {
+                    "webSearchQueries": ["where is Zürich"],
+                    "groundingChunks": [{ "web": { "uri": "https://example.com", "title": "example.com" } }],
+                    "groundingSupports": [{
+                        "segment": { "startIndex": 8, "endIndex": 25, "text": "is in Switzerland" },
+                        "groundingChunkIndices": [0]
+                    }]
+                }
+            }] })),
+            &mut texts,
+            "google_gemini",
+            "endpoint",
+        )
+        .expect("last chunk");
+
+        let ChatEvent::MessageDelta(delta) = &last.events[0] else {
+            panic!("unexpected event: {:?}", last.events);
+        };
+        match &delta.content[1] {
+            ContentDelta::Citation { citation } => {
+                let span = citation.span.expect("span");
+                assert_eq!(span.slice(&texts[&0]), Some("is in Switzerland"));
+            }
+            other => panic!("unexpected content delta: {other:?}"),
+        }
+        assert!(matches!(
+            &delta.content[2],
+            ContentDelta::Grounding { grounding } if grounding.search_queries == ["where is Zürich"]
+        ));
+    }
+
+    /// A blocked prompt ends the stream with `PromptBlocked`.
+    #[test]
+    fn blocked_prompt_chunk_is_an_error() {
+        let chunk: GeminiGenerateContentResponse = serde_json::from_value(json!({
+            "promptFeedback": { "blockReason": "PROHIBITED_CONTENT" }
+        }))
+        .expect("chunk");
+
+        let err = convert_stream_chunk(chunk, &mut HashMap::new(), "google_gemini", "endpoint")
+            .expect_err("blocked");
+        assert!(matches!(
+            err,
+            LLMError::PromptBlocked { safety, .. }
+                if safety.block_reason.as_deref() == Some("PROHIBITED_CONTENT")
+        ));
+    }
 }
@@ -35,11 +35,43 @@ pub(crate) struct GeminiCandidate {
     pub(crate) finish_reason: Option<String>,
     #[serde(default)]
     pub(crate) index: Option<usize>,
+    /// Token log probabilities requested via `generationConfig.responseLogprobs`.
+    #[serde(
+        default,
+        rename = "logprobsResult",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) logprobs_result: Option<GeminiLogprobsResult>,
     /// Additional fields (safety ratings, citation metadata, etc.) are forwarded.
     #[serde(flatten)]
     pub(crate) extra: HashMap<String, Value>,
 }
 
+/// `logprobsResult`: one chosen token per step plus the top alternatives at that step.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct GeminiLogprobsResult {
+    #[serde(default, rename = "topCandidates")]
+    pub(crate) top_candidates: Vec<GeminiTopCandidates>,
+    #[serde(default, rename = "chosenCandidates")]
+    pub(crate) chosen_candidates: Vec<GeminiLogprobCandidate>,
+}
+
+/// Alternatives considered at one decoding step.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct GeminiTopCandidates {
+    #[serde(default)]
+    pub(crate) candidates: Vec<GeminiLogprobCandidate>,
+}
+
+/// Single token with its log probability.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct GeminiLogprobCandidate {
+    #[serde(default)]
+    pub(crate) token: String,
+    #[serde(default, rename = "logProbability")]
+    pub(crate) log_probability: f64,
+}
+
 /// Candidate content payload.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct GeminiContent {
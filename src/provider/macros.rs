@@ -14,7 +14,7 @@
 ///
 /// ```rust,ignore
 /// register_providers!(
-///     (module_name, "serde_name", ProviderType, EnumVariant),
+///     (module_name, "serde_name", ProviderType, EnumVariant, "cargo-feature"),
 ///     // ... more providers
 /// );
 /// ```
@@ -24,21 +24,27 @@
 /// - `"serde_name"`: The serde serialization name (e.g., `"openai_chat"`)
 /// - `ProviderType`: The provider struct name (e.g., `OpenAiChatProvider`)
 /// - `EnumVariant`: The `ProviderKind` enum variant name (e.g., `OpenAiChat`)
+/// - `"cargo-feature"`: The cargo feature that compiles the module (e.g., `"openai"`); the
+///   variant and its match arms only exist when the feature is enabled
 ///
 /// # Generated Code
 ///
 /// This macro generates:
 ///
-/// 1. **`ProviderKind` enum**: A serde-compatible enum with one variant per provider
+/// 1. **`ProviderKind` enum**: A serde-compatible enum with one variant per provider, plus a
+///    `Custom` variant for names resolved through [`crate::config::ProviderRegistry`]
 /// 2. **`build_provider_from_config()` function**: Constructs a provider from `ModelConfig`
 /// 3. **`list_provider_types()` function**: Returns a list of all registered provider names
 ///
 /// # Requirements
 ///
-/// Each registered provider must implement a static method:
+/// Each registered provider must implement a static method and declare the `extra` keys it
+/// reads from [`crate::config::ModelConfig`]:
 ///
 /// ```rust,ignore
 /// impl ProviderType {
+///     pub const SUPPORTED_EXTRA_KEYS: &'static [&'static str] = &["organization"];
+///
 ///     pub fn from_model_config(
 ///         config: &crate::config::ModelConfig,
 ///         transport: crate::http::DynHttpTransport,
@@ -57,43 +63,83 @@
 /// use crate::provider::macros::register_providers;
 ///
 /// register_providers!(
-///     (openai_chat, "openai_chat", OpenAiChatProvider, OpenAiChat),
-///     (anthropic_messages, "anthropic_messages", AnthropicMessagesProvider, AnthropicMessages),
+///     (openai_chat, "openai_chat", OpenAiChatProvider, OpenAiChat, "openai"),
+///     (anthropic_messages, "anthropic_messages", AnthropicMessagesProvider, AnthropicMessages, "anthropic"),
 /// );
 /// ```
 ///
 /// This generates a `ProviderKind` enum with `OpenAiChat` and `AnthropicMessages` variants.
 #[macro_export]
 macro_rules! register_providers {
-    ($(($module:ident, $name:literal, $provider:ident, $variant:ident),)+) => {
+    ($(($module:ident, $name:literal, $provider:ident, $variant:ident, $feature:literal),)+) => {
         /// Enumerates the provider kinds supported by the configuration loader.
         ///
         /// This enum is automatically generated by the `register_providers!` macro.
         /// Each variant corresponds to a registered provider implementation.
+        ///
+        /// Names that match no built-in provider deserialize into [`ProviderKind::Custom`].
         #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
         #[serde(rename_all = "snake_case")]
         pub enum ProviderKind {
             $(
                 #[doc = concat!("The ", stringify!($provider), " provider.")]
+                #[cfg(feature = $feature)]
                 #[serde(rename = $name)]
                 $variant,
             )+
+            /// A provider registered at runtime on a [`crate::config::ProviderRegistry`].
+            #[serde(untagged)]
+            Custom(String),
         }
 
         impl ProviderKind {
-            /// Returns a list of all registered provider type names.
+            /// Returns the names of all built-in provider types enabled in this build.
             ///
             /// This is useful for validation, CLI help messages, and error reporting.
             pub fn list_provider_types() -> Vec<&'static str> {
-                vec![$($name,)+]
+                [$((cfg!(feature = $feature), $name),)+]
+                    .into_iter()
+                    .filter_map(|(enabled, name)| enabled.then_some(name))
+                    .collect()
+            }
+
+            /// Returns the cargo feature that compiles the built-in provider `name`, whether
+            /// or not it is enabled, or `None` when `name` is not a built-in provider.
+            pub fn required_feature(name: &str) -> Option<&'static str> {
+                [$(($name, $feature),)+]
+                    .into_iter()
+                    .find_map(|(known, feature)| (known == name).then_some(feature))
             }
 
             /// Returns the string representation of this provider kind.
-            pub fn as_str(&self) -> &'static str {
+            pub fn as_str(&self) -> &str {
                 match self {
                     $(
+                        #[cfg(feature = $feature)]
                         ProviderKind::$variant => $name,
                     )+
+                    ProviderKind::Custom(name) => name,
+                }
+            }
+
+            /// Returns `true` for [`ProviderKind::Custom`] names.
+            pub fn is_custom(&self) -> bool {
+                matches!(self, ProviderKind::Custom(_))
+            }
+
+            /// Returns the `ModelConfig.extra` keys understood by this provider kind.
+            ///
+            /// The configuration loader rejects any other key so typos surface at startup.
+            /// Custom providers validate their own keys, so this is empty for them.
+            pub fn supported_extra_keys(&self) -> &'static [&'static str] {
+                match self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        ProviderKind::$variant => {
+                            $crate::provider::$module::$provider::SUPPORTED_EXTRA_KEYS
+                        }
+                    )+
+                    ProviderKind::Custom(_) => &[],
                 }
             }
         }
@@ -116,26 +162,47 @@ macro_rules! register_providers {
         ///
         /// Returns [`LLMError::Auth`] when credentials are invalid or missing,
         /// [`LLMError::Validation`] for invalid configuration, or any provider-specific
-        /// error raised during construction.
+        /// error raised during construction. [`ProviderKind::Custom`] kinds return
+        /// [`LLMError::InvalidConfig`]; build them through a
+        /// [`crate::config::ProviderRegistry`] instead.
+        // Only the `Custom` arm remains when every provider feature is disabled.
+        #[allow(unused_variables, unreachable_code)]
         pub fn build_provider_from_config(
             config: &$crate::config::ModelConfig,
             transport: $crate::http::DynHttpTransport,
         ) -> Result<$crate::provider::DynProvider, $crate::error::LLMError> {
-            use std::sync::Arc;
             use $crate::config::ProviderKind;
-            $(
-                use $crate::provider::$module::$provider;
-            )+
 
             let provider: $crate::provider::DynProvider = match &config.provider {
                 $(
-                    ProviderKind::$variant => {
-                        Arc::new($provider::from_model_config(config, transport)?)
-                    }
+                    #[cfg(feature = $feature)]
+                    ProviderKind::$variant => std::sync::Arc::new(
+                        $crate::provider::$module::$provider::from_model_config(config, transport)?,
+                    ),
                 )+
+                ProviderKind::Custom(name) => {
+                    return Err($crate::error::LLMError::InvalidConfig {
+                        field: "provider".to_string(),
+                        reason: unknown_provider_reason(
+                            name,
+                            &ProviderKind::list_provider_types().join(", "),
+                        ),
+                    });
+                }
             };
 
             Ok(provider)
         }
+
+        /// Explains why `name` resolves to no provider, naming the cargo feature to enable
+        /// when it is a built-in provider left out of this build.
+        fn unknown_provider_reason(name: &str, known: &str) -> String {
+            match ProviderKind::required_feature(name) {
+                Some(feature) => format!(
+                    "provider `{name}` requires the `{feature}` cargo feature of kotoba-llm"
+                ),
+                None => format!("unknown provider `{name}`; expected one of {known}"),
+            }
+        }
     };
 }
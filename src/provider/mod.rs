@@ -1,16 +1,32 @@
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use futures_core::Stream;
+use serde_json::Value;
 
 use crate::error::LLMError;
 use crate::types::{CapabilityDescriptor, ChatChunk, ChatRequest, ChatResponse};
 
+#[cfg(feature = "anthropic")]
 pub mod anthropic_messages;
+#[cfg(feature = "azure")]
+pub mod azure_openai;
+#[cfg(feature = "bedrock")]
+pub mod bedrock_converse;
+#[cfg(feature = "cohere")]
+pub mod cohere_chat;
+#[cfg(feature = "gemini")]
 pub mod google_gemini;
 pub mod macros;
+#[cfg(feature = "ollama")]
+pub mod ollama;
+#[cfg(feature = "openai")]
 pub mod openai_chat;
+#[cfg(feature = "openai-compatible")]
+pub mod openai_compatible;
+#[cfg(feature = "openai")]
 pub mod openai_responses;
 pub(crate) mod retry;
 
@@ -19,6 +35,21 @@ pub use retry::{RetryConfig, RetryableLLMProvider};
 /// Stream alias returned by provider implementations for incremental responses.
 pub type ChatStream = Pin<Box<dyn Stream<Item = Result<ChatChunk, LLMError>> + Send>>;
 
+/// Vendor HTTP call assembled by a provider but not yet dispatched.
+///
+/// Providers build this after applying any configured [`crate::config::RequestPatch`], so
+/// it mirrors exactly what would go over the wire. Debugging tools use it to inspect the
+/// vendor JSON without paying for a round trip.
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    /// Fully resolved endpoint URL.
+    pub url: String,
+    /// Request headers, including credentials.
+    pub headers: HashMap<String, String>,
+    /// Vendor-specific JSON body.
+    pub body: Value,
+}
+
 /// Trait implemented by every provider integration.
 ///
 /// Providers translate the unified [`ChatRequest`] into a vendor-specific HTTP call and
@@ -89,11 +120,39 @@ pub trait LLMProvider: Send + Sync {
     /// vendor-specific errors through [`LLMError::Provider`].
     async fn stream_chat(&self, request: ChatRequest) -> Result<ChatStream, LLMError>;
 
+    /// Builds the vendor HTTP request for `request` without sending it.
+    ///
+    /// The default implementation reports [`LLMError::NotImplemented`], so custom providers
+    /// only need to override it when they want to support request inspection.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same validation errors as [`LLMProvider::chat`] would raise before
+    /// issuing the HTTP call.
+    async fn prepare_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let _ = (request, stream);
+        Err(LLMError::NotImplemented {
+            feature: "prepare_request",
+        })
+    }
+
     /// Returns the provider's capability descriptor.
     fn capabilities(&self) -> CapabilityDescriptor;
 
     /// Returns the provider identifier used in logs and error reporting.
     fn name(&self) -> &'static str;
+
+    /// Returns the model used when a request leaves [`crate::types::ChatOptions::model`] unset.
+    ///
+    /// [`crate::client::LLMClient::capabilities`] uses it to look the model up in the
+    /// [`crate::catalog::ModelCatalog`].
+    fn default_model(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// Thread-safe handle to a provider implementation.
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::error::{LLMError, extract_model_identifier, looks_like_token_limit_error};
+
+/// Parses Ollama error payloads of the form `{"error": "..."}`.
+///
+/// Also used for error objects that arrive as a line of an NDJSON stream.
+pub(crate) fn parse_ollama_error(
+    status: u16,
+    body: &str,
+    retry_after: Option<Duration>,
+) -> LLMError {
+    #[derive(Deserialize)]
+    struct ErrorBody {
+        error: String,
+    }
+
+    let Ok(ErrorBody { error: message }) = serde_json::from_str::<ErrorBody>(body) else {
+        return LLMError::Provider {
+            provider: "ollama",
+            message: format!("status {status}: {body}"),
+        };
+    };
+
+    // A missing model is reported as `model "llama3" not found, try pulling it first`.
+    if status == 404 || message.contains("not found") {
+        return LLMError::ModelNotFound {
+            model: extract_model_identifier(&message),
+            message,
+        };
+    }
+
+    if looks_like_token_limit_error(None, &message) {
+        return LLMError::TokenLimitExceeded {
+            message,
+            estimated: None,
+            limit: None,
+        };
+    }
+
+    match status {
+        401 | 403 => LLMError::Auth { message },
+        429 => LLMError::RateLimit {
+            message,
+            retry_after,
+        },
+        400 => LLMError::Validation { message },
+        _ => LLMError::Provider {
+            provider: "ollama",
+            message,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_models_map_to_model_not_found() {
+        match parse_ollama_error(
+            404,
+            r#"{"error":"model \"llama9\" not found, try pulling it first"}"#,
+            None,
+        ) {
+            LLMError::ModelNotFound { model, .. } => assert_eq!(model.as_deref(), Some("llama9")),
+            other => panic!("expected ModelNotFound, got {other:?}"),
+        }
+        assert!(matches!(
+            parse_ollama_error(
+                400,
+                r#"{"error":"invalid format: expected \"json\""}"#,
+                None
+            ),
+            LLMError::Validation { .. }
+        ));
+        assert!(matches!(
+            parse_ollama_error(502, "Bad Gateway", None),
+            LLMError::Provider {
+                provider: "ollama",
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::config::{Credential, RequestPatch};
+use crate::credential::{DynCredentialProvider, invalidate_on_unauthorized};
+use crate::error::LLMError;
+use crate::http::{
+    DynHttpTransport, HttpResponse, HttpStreamResponse, post_json_stream_with_headers,
+    post_json_with_headers,
+};
+use crate::provider::{ChatStream, LLMProvider, PreparedRequest, retry::retry_after_from_headers};
+use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
+
+use super::error::parse_ollama_error;
+use super::request::build_ollama_body;
+use super::response::map_response;
+use super::stream::{collect_stream_text, create_stream};
+use super::types::OllamaChatResponse;
+
+const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+/// Native Ollama provider for the `/api/chat` endpoint.
+///
+/// Unlike Ollama's OpenAI-compatible shim, the native API exposes runtime `options` such
+/// as `num_ctx`, `keep_alive`, JSON-schema `format` and thinking traces. Streams are
+/// newline-delimited JSON and are decoded with [`crate::stream::ndjson::NdjsonDecoder`].
+/// Local servers need no credential; a bearer token can be configured for servers behind
+/// an authenticating proxy.
+pub struct OllamaProvider {
+    pub(crate) transport: DynHttpTransport,
+    pub(crate) base_url: String,
+    pub(crate) credential: Option<DynCredentialProvider>,
+    pub(crate) keep_alive: Option<Value>,
+    pub(crate) options: Map<String, Value>,
+    pub(crate) default_model: Option<String>,
+    pub(crate) request_patch: Option<RequestPatch>,
+}
+
+impl OllamaProvider {
+    /// `ModelConfig.extra` keys read by [`Self::from_model_config`].
+    pub const SUPPORTED_EXTRA_KEYS: &'static [&'static str] = &["keep_alive", "options"];
+
+    /// Creates a provider for an unauthenticated server at `http://localhost:11434`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use kotoba_llm::provider::ollama::OllamaProvider;
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let provider = OllamaProvider::new(transport).with_default_model("llama3.1");
+    /// assert_eq!(provider.name(), "ollama");
+    /// # }
+    /// ```
+    pub fn new(transport: DynHttpTransport) -> Self {
+        Self {
+            transport,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            credential: None,
+            keep_alive: None,
+            options: Map::new(),
+            default_model: None,
+            request_patch: None,
+        }
+    }
+
+    /// Creates a provider that sends a bearer token from `credential` with every request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::credential::EnvCredential;
+    /// # use kotoba_llm::provider::ollama::OllamaProvider;
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let credential = Arc::new(EnvCredential::new("OLLAMA_PROXY_TOKEN"));
+    /// let provider = OllamaProvider::from_credential_provider(transport, credential)
+    ///     .with_base_url("https://ollama.internal");
+    /// assert_eq!(provider.name(), "ollama");
+    /// # }
+    /// ```
+    pub fn from_credential_provider(
+        transport: DynHttpTransport,
+        credential: DynCredentialProvider,
+    ) -> Self {
+        Self {
+            credential: Some(credential),
+            ..Self::new(transport)
+        }
+    }
+
+    /// Overrides the server address.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Sets how long the model stays loaded after a request, e.g. `"10m"`, `3600` or `-1`.
+    pub fn with_keep_alive(mut self, keep_alive: impl Into<Value>) -> Self {
+        self.keep_alive = Some(keep_alive.into());
+        self
+    }
+
+    /// Sets a default runtime option such as `num_ctx` or `seed`.
+    ///
+    /// Per-request values in `ChatOptions` or `options.extra["options"]` take precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use kotoba_llm::provider::ollama::OllamaProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let provider = OllamaProvider::new(transport)
+    ///     .with_option("num_ctx", 16384)
+    ///     .with_keep_alive("30m");
+    /// # let _ = provider;
+    /// # }
+    /// ```
+    pub fn with_option(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+
+    /// Configures a default model when the [`ChatRequest`] omits one.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.default_model = Some(model.into());
+        self
+    }
+
+    /// Constructs a provider from a [`crate::config::ModelConfig`].
+    ///
+    /// [`Credential::None`] talks to the server without authentication; any other
+    /// credential is sent as a bearer token. `extra.keep_alive` and `extra.options` set the
+    /// defaults applied to every request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Auth`] for unsupported credentials and
+    /// [`LLMError::InvalidConfig`] when an `extra` value has the wrong type.
+    pub fn from_model_config(
+        config: &crate::config::ModelConfig,
+        transport: DynHttpTransport,
+    ) -> Result<Self, LLMError> {
+        let mut provider = match &config.credential {
+            Credential::None => Self::new(transport),
+            other => {
+                Self::from_credential_provider(transport, other.credential_provider("ollama")?)
+            }
+        };
+
+        if let Some(base_url) = &config.base_url {
+            provider = provider.with_base_url(base_url.clone());
+        }
+
+        if let Some(model) = &config.default_model {
+            provider = provider.with_default_model(model.clone());
+        }
+
+        match config.extra.get("keep_alive") {
+            None => {}
+            Some(value @ (Value::String(_) | Value::Number(_))) => {
+                provider = provider.with_keep_alive(value.clone());
+            }
+            Some(other) => {
+                return Err(LLMError::InvalidConfig {
+                    field: "extra.keep_alive".to_string(),
+                    reason: format!("expected a duration string or seconds, got {other}"),
+                });
+            }
+        }
+
+        match config.extra.get("options") {
+            None => {}
+            Some(Value::Object(options)) => provider.options = options.clone(),
+            Some(other) => {
+                return Err(LLMError::InvalidConfig {
+                    field: "extra.options".to_string(),
+                    reason: format!("expected a table of runtime options, got {other}"),
+                });
+            }
+        }
+
+        provider.request_patch = config.patch.clone();
+
+        Ok(provider)
+    }
+
+    pub(crate) fn endpoint(&self) -> String {
+        let base = self.base_url.trim_end_matches('/');
+        if base.ends_with("/api") {
+            format!("{base}/chat")
+        } else {
+            format!("{base}/api/chat")
+        }
+    }
+
+    async fn build_headers(&self) -> Result<HashMap<String, String>, LLMError> {
+        let mut headers = HashMap::new();
+        if let Some(credential) = &self.credential {
+            let token = credential.token().await?;
+            headers.insert(
+                "Authorization".to_string(),
+                format!("Bearer {}", token.value),
+            );
+        }
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        headers.insert("Accept".to_string(), "application/json".to_string());
+        Ok(headers)
+    }
+
+    fn resolve_model(&self, request: &ChatRequest) -> Result<String, LLMError> {
+        request
+            .options
+            .model
+            .clone()
+            .or_else(|| self.default_model.clone())
+            .ok_or_else(|| LLMError::Validation {
+                message: "model is required for Ollama".to_string(),
+            })
+    }
+
+    async fn prepare(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let model = self.resolve_model(request)?;
+        let body = build_ollama_body(
+            request,
+            &model,
+            stream,
+            &self.options,
+            self.keep_alive.as_ref(),
+        )?;
+        let mut prepared = PreparedRequest {
+            url: self.endpoint(),
+            headers: self.build_headers().await?,
+            body,
+        };
+        self.apply_patch(&mut prepared.url, &mut prepared.headers, &mut prepared.body);
+        Ok(prepared)
+    }
+
+    async fn send_request(&self, prepared: PreparedRequest) -> Result<HttpResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
+        post_json_with_headers(self.transport.as_ref(), url, headers, &body).await
+    }
+
+    async fn send_stream_request(
+        &self,
+        prepared: PreparedRequest,
+    ) -> Result<HttpStreamResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
+        post_json_stream_with_headers(self.transport.as_ref(), url, headers, &body).await
+    }
+
+    fn ensure_success(&self, response: HttpResponse) -> Result<String, LLMError> {
+        let HttpResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        let text = String::from_utf8(body).map_err(|err| LLMError::transport(err.to_string()))?;
+        if (200..300).contains(&status) {
+            Ok(text)
+        } else {
+            if let Some(credential) = &self.credential {
+                invalidate_on_unauthorized(credential, status);
+            }
+            Err(parse_ollama_error(
+                status,
+                &text,
+                retry_after_from_headers(&headers),
+            ))
+        }
+    }
+
+    fn try_parse<T: DeserializeOwned>(&self, text: &str) -> Result<T, LLMError> {
+        serde_json::from_str(text).map_err(|err| LLMError::Provider {
+            provider: self.name(),
+            message: format!("failed to parse Ollama response: {err}"),
+        })
+    }
+
+    fn apply_patch(
+        &self,
+        url: &mut String,
+        headers: &mut HashMap<String, String>,
+        body: &mut Value,
+    ) {
+        if let Some(patch) = &self.request_patch {
+            patch.apply(url, headers, body);
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for OllamaProvider {
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LLMError> {
+        let prepared = self.prepare(&request, false).await?;
+        let response = self.send_request(prepared).await?;
+        let text = self.ensure_success(response)?;
+        let parsed: OllamaChatResponse = self.try_parse(&text)?;
+        map_response(parsed, self.name(), self.endpoint())
+    }
+
+    async fn stream_chat(&self, request: ChatRequest) -> Result<ChatStream, LLMError> {
+        let prepared = self.prepare(&request, true).await?;
+        let response = self.send_stream_request(prepared).await?;
+        let HttpStreamResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        if !(200..300).contains(&status) {
+            if let Some(credential) = &self.credential {
+                invalidate_on_unauthorized(credential, status);
+            }
+            let text = collect_stream_text(body, self.name()).await?;
+            return Err(parse_ollama_error(
+                status,
+                &text,
+                retry_after_from_headers(&headers),
+            ));
+        }
+        Ok(create_stream(body, self.name(), self.endpoint()))
+    }
+
+    async fn prepare_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        self.prepare(request, stream).await
+    }
+
+    fn capabilities(&self) -> CapabilityDescriptor {
+        CapabilityDescriptor {
+            supports_stream: true,
+            supports_image_input: true,
+            supports_audio_input: false,
+            supports_video_input: false,
+            supports_tools: true,
+            supports_structured_output: true,
+            // Models may emit several tool calls per turn; Ollama has no switch to prevent it.
+            supports_parallel_tool_calls: true,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::config::{ModelConfig, ProviderKind};
+    use crate::http::test_support::PanicTransport;
+    use crate::types::test_support::text_request;
+
+    fn config(extra: Value) -> ModelConfig {
+        ModelConfig {
+            handle: "local".to_string(),
+            provider: ProviderKind::Ollama,
+            credential: Credential::None,
+            default_model: Some("llama3.1".to_string()),
+            base_url: None,
+            extra: serde_json::from_value(extra).expect("extra"),
+            patch: None,
+            catalog: Vec::new(),
+        }
+    }
+
+    /// `Credential::None` sends no auth header and configured runtime defaults are applied.
+    #[tokio::test]
+    async fn accepts_no_credential_and_applies_defaults() {
+        let provider = OllamaProvider::from_model_config(
+            &config(json!({ "keep_alive": "30m", "options": { "num_ctx": 16384 } })),
+            Arc::new(PanicTransport),
+        )
+        .expect("provider");
+
+        let prepared = provider
+            .prepare_request(&text_request("ping"), true)
+            .await
+            .expect("prepared request");
+        assert_eq!(prepared.url, "http://localhost:11434/api/chat");
+        assert!(!prepared.headers.contains_key("Authorization"));
+        assert_eq!(prepared.body["keep_alive"], json!("30m"));
+        assert_eq!(prepared.body["options"], json!({ "num_ctx": 16384 }));
+        assert_eq!(prepared.body["stream"], json!(true));
+
+        match OllamaProvider::from_model_config(
+            &config(json!({ "options": "num_ctx=4096" })),
+            Arc::new(PanicTransport),
+        ) {
+            Err(LLMError::InvalidConfig { field, .. }) => assert_eq!(field, "extra.options"),
+            Err(other) => panic!("unexpected error: {other:?}"),
+            Ok(_) => panic!("expected invalid config"),
+        }
+    }
+}
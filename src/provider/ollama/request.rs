@@ -0,0 +1,513 @@
+use serde_json::{Map, Value, json};
+
+use crate::error::LLMError;
+use crate::types::{
+    ChatRequest, ContentPart, ImageContent, ImageSource, Message, ReasoningEffort, ResponseFormat,
+    TextContent, ToolCall, ToolChoice, ToolDefinition, ToolKind, ToolResult,
+};
+
+/// Builds the request body for Ollama `/api/chat`.
+///
+/// `default_options` and `default_keep_alive` come from the provider configuration; the
+/// request's `options.extra["options"]` and `options.extra["keep_alive"]` override them.
+pub(crate) fn build_ollama_body(
+    request: &ChatRequest,
+    model: &str,
+    stream: bool,
+    default_options: &Map<String, Value>,
+    default_keep_alive: Option<&Value>,
+) -> Result<Value, LLMError> {
+    let mut body = Map::new();
+    body.insert("model".to_string(), Value::String(model.to_string()));
+
+    // 1. Messages; Ollama accepts system, user, assistant and tool roles directly.
+    let mut messages = Vec::new();
+    for message in &request.messages {
+        messages.extend(convert_message(message)?);
+    }
+    if messages.is_empty() {
+        return Err(LLMError::Validation {
+            message: "Ollama chat request requires at least one message".to_string(),
+        });
+    }
+    body.insert("messages".to_string(), Value::Array(messages));
+
+    // 2. Sampling controls live in the runtime `options` table next to `num_ctx` & co.
+    let mut options = default_options.clone();
+    if let Some(temperature) = request.options.temperature {
+        options.insert("temperature".to_string(), Value::from(temperature));
+    }
+    if let Some(top_p) = request.options.top_p {
+        options.insert("top_p".to_string(), Value::from(top_p));
+    }
+    if let Some(max_tokens) = request.options.max_output_tokens {
+        options.insert("num_predict".to_string(), Value::from(max_tokens));
+    }
+    if let Some(presence) = request.options.presence_penalty {
+        options.insert("presence_penalty".to_string(), Value::from(presence));
+    }
+    if let Some(frequency) = request.options.frequency_penalty {
+        options.insert("frequency_penalty".to_string(), Value::from(frequency));
+    }
+    if let Some(top_k) = request.options.top_k {
+        options.insert("top_k".to_string(), Value::from(top_k));
+    }
+    if let Some(stop) = &request.options.stop_sequences {
+        options.insert("stop".to_string(), json!(stop));
+    }
+    if let Some(seed) = request.options.seed {
+        options.insert("seed".to_string(), Value::from(seed));
+    }
+    if request.options.candidate_count.is_some_and(|n| n > 1) {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "ollama_candidate_count",
+        });
+    }
+    if request.options.logprobs == Some(true) || request.options.top_logprobs.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "ollama_logprobs",
+        });
+    }
+    if request.options.cached_content.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "ollama_cached_content",
+        });
+    }
+    if request.options.user.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "ollama_user",
+        });
+    }
+    if let Some(Value::Object(overrides)) = request.options.extra.get("options") {
+        for (k, v) in overrides {
+            options.insert(k.clone(), v.clone());
+        }
+    }
+    if !options.is_empty() {
+        body.insert("options".to_string(), Value::Object(options));
+    }
+
+    // 3. Thinking models accept `think` as a boolean or, for some models, an effort level.
+    if let Some(reasoning) = &request.options.reasoning {
+        let think = match &reasoning.effort {
+            Some(ReasoningEffort::Low) => json!("low"),
+            Some(ReasoningEffort::Medium) => json!("medium"),
+            Some(ReasoningEffort::High) => json!("high"),
+            Some(ReasoningEffort::Custom(value)) => json!(value),
+            None => Value::Bool(true),
+        };
+        body.insert("think".to_string(), think);
+    }
+
+    // 4. Tools; Ollama has no tool_choice, so only "none" (drop tools) can be honoured.
+    match &request.tool_choice {
+        None | Some(ToolChoice::Auto) => {
+            if !request.tools.is_empty() {
+                body.insert(
+                    "tools".to_string(),
+                    Value::Array(convert_tools(&request.tools)?),
+                );
+            }
+        }
+        Some(ToolChoice::None) => {}
+        Some(_) => {
+            return Err(LLMError::UnsupportedFeature {
+                feature: "ollama_tool_choice",
+            });
+        }
+    }
+
+    // 5. Structured output: `"json"` or a JSON schema.
+    if let Some(format) = &request.response_format {
+        match format {
+            ResponseFormat::Text => {}
+            ResponseFormat::JsonObject => {
+                body.insert("format".to_string(), Value::String("json".to_string()));
+            }
+            ResponseFormat::JsonSchema { schema } => {
+                body.insert("format".to_string(), schema.clone());
+            }
+            ResponseFormat::Custom(value) => {
+                body.insert("format".to_string(), value.clone());
+            }
+        }
+    }
+
+    if let Some(keep_alive) = default_keep_alive {
+        body.insert("keep_alive".to_string(), keep_alive.clone());
+    }
+
+    // 6. Remaining extras (`keep_alive`, `think`, ...) are forwarded as top-level fields.
+    for (k, v) in &request.options.extra {
+        if k != "options" {
+            body.insert(k.clone(), v.clone());
+        }
+    }
+
+    body.insert("stream".to_string(), Value::Bool(stream));
+
+    Ok(Value::Object(body))
+}
+
+/// Converts one message; each tool result becomes its own `tool` message.
+fn convert_message(message: &Message) -> Result<Vec<Value>, LLMError> {
+    let role = match message.role.0.as_str() {
+        "developer" => "system",
+        other => other,
+    };
+
+    let mut obj = Map::new();
+    let mut text = String::new();
+    let mut images = Vec::new();
+    let mut tool_calls = Vec::new();
+    let mut tool_results = Vec::new();
+
+    for part in &message.content {
+        match part {
+            ContentPart::Text(TextContent { text: chunk }) => {
+                if !text.is_empty() {
+                    text.push('\n');
+                }
+                text.push_str(chunk);
+            }
+            ContentPart::Image(ImageContent { source, .. }) => match source {
+                ImageSource::Base64 { data, .. } => images.push(Value::String(data.clone())),
+                _ => {
+                    return Err(LLMError::UnsupportedFeature {
+                        feature: "image_source_non_base64",
+                    });
+                }
+            },
+            ContentPart::ToolCall(ToolCall {
+                name, arguments, ..
+            }) => {
+                tool_calls.push(json!({
+                    "function": { "name": name, "arguments": arguments }
+                }));
+            }
+            ContentPart::ToolResult(ToolResult {
+                output, content, ..
+            }) => {
+                if !content.is_empty() {
+                    return Err(LLMError::UnsupportedFeature {
+                        feature: "ollama_tool_result_content",
+                    });
+                }
+                let content = match output {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                let mut result = Map::new();
+                result.insert("role".to_string(), Value::String("tool".to_string()));
+                result.insert("content".to_string(), Value::String(content));
+                if let Some(name) = &message.name {
+                    result.insert("tool_name".to_string(), Value::String(name.clone()));
+                }
+                tool_results.push(Value::Object(result));
+            }
+            // Raw fields such as a precomputed `images` list are merged into the message.
+            ContentPart::Data { data } => {
+                if let Value::Object(fields) = data {
+                    for (k, v) in fields {
+                        obj.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+            ContentPart::Audio(_) | ContentPart::Video(_) | ContentPart::File(_) => {
+                return Err(LLMError::UnsupportedFeature {
+                    feature: "ollama_content_type",
+                });
+            }
+            // Ollama reuses its KV cache for repeated prefixes on its own.
+            ContentPart::CacheBreakpoint(_) => {}
+        }
+    }
+
+    let has_body = !text.is_empty() || !images.is_empty() || !tool_calls.is_empty();
+    if !has_body && !tool_results.is_empty() && obj.is_empty() {
+        return Ok(tool_results);
+    }
+
+    obj.insert("role".to_string(), Value::String(role.to_string()));
+    obj.insert("content".to_string(), Value::String(text));
+    if !images.is_empty() {
+        obj.insert("images".to_string(), Value::Array(images));
+    }
+    if !tool_calls.is_empty() {
+        obj.insert("tool_calls".to_string(), Value::Array(tool_calls));
+    }
+    let mut messages = vec![Value::Object(obj)];
+    messages.extend(tool_results);
+    Ok(messages)
+}
+
+fn convert_tools(tools: &[ToolDefinition]) -> Result<Vec<Value>, LLMError> {
+    let mut result = Vec::new();
+    for tool in tools {
+        match &tool.kind {
+            ToolKind::Function => {
+                let parameters = tool
+                    .input_schema
+                    .clone()
+                    .unwrap_or_else(|| json!({ "type": "object", "properties": {} }));
+                result.push(json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": parameters
+                    }
+                }));
+            }
+            ToolKind::Custom {
+                config: Some(config),
+                ..
+            } => result.push(config.clone()),
+            _ => {
+                return Err(LLMError::Validation {
+                    message: "Ollama tools only support function tools or custom configs"
+                        .to_string(),
+                });
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::types::test_support::request_with_cache_breakpoints;
+    use crate::types::{ChatOptions, Role, ToolCallKind};
+
+    fn request(messages: Vec<Message>) -> ChatRequest {
+        ChatRequest {
+            messages,
+            options: ChatOptions::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        }
+    }
+
+    /// Images, schema formats and runtime options land where `/api/chat` expects them.
+    #[test]
+    fn build_body_with_images_format_and_options() {
+        let mut request = request(vec![Message {
+            role: Role::user(),
+            name: None,
+            content: vec![
+                ContentPart::Text(TextContent {
+                    text: "What is in this picture?".to_string(),
+                }),
+                ContentPart::Image(ImageContent {
+                    source: ImageSource::Base64 {
+                        data: "iVBORw0KGgo=".to_string(),
+                        mime_type: Some("image/png".to_string()),
+                    },
+                    detail: None,
+                    metadata: None,
+                }),
+            ],
+            metadata: None,
+        }]);
+        request.options.temperature = Some(0.2);
+        request.options.max_output_tokens = Some(128);
+        request.options.top_k = Some(20);
+        request.options.stop_sequences = Some(vec!["<|end|>".to_string()]);
+        request.options.extra = HashMap::from([
+            ("options".to_string(), json!({ "num_ctx": 8192 })),
+            ("keep_alive".to_string(), json!("10m")),
+        ]);
+        request.response_format = Some(ResponseFormat::JsonSchema {
+            schema: json!({ "type": "object", "properties": { "label": { "type": "string" } } }),
+        });
+
+        let defaults = Map::from_iter([
+            ("num_ctx".to_string(), json!(4096)),
+            ("seed".to_string(), json!(7)),
+        ]);
+        let body = build_ollama_body(&request, "llava", true, &defaults, Some(&json!("5m")))
+            .expect("build");
+
+        assert_eq!(
+            body["messages"][0],
+            json!({
+                "role": "user",
+                "content": "What is in this picture?",
+                "images": ["iVBORw0KGgo="]
+            })
+        );
+        assert_eq!(
+            body["options"],
+            json!({
+                "num_ctx": 8192,
+                "seed": 7,
+                "temperature": 0.2f32,
+                "num_predict": 128,
+                "top_k": 20,
+                "stop": ["<|end|>"]
+            })
+        );
+        assert_eq!(
+            body["format"]["properties"]["label"],
+            json!({ "type": "string" })
+        );
+        assert_eq!(body["keep_alive"], json!("10m"));
+        assert_eq!(body["stream"], json!(true));
+    }
+
+    /// A typed seed overrides the configured default; candidates and end-user ids are rejected.
+    #[test]
+    fn map_seed_and_reject_candidates_and_end_user_ids() {
+        let mut request = request(vec![Message {
+            role: Role::user(),
+            name: None,
+            content: vec![ContentPart::Text(TextContent {
+                text: "Hi".to_string(),
+            })],
+            metadata: None,
+        }]);
+        request.options.seed = Some(42);
+        request.options.candidate_count = Some(1);
+        let defaults = Map::from_iter([("seed".to_string(), json!(7))]);
+
+        let body = build_ollama_body(&request, "llama3.2", false, &defaults, None).expect("build");
+        assert_eq!(body["options"], json!({ "seed": 42 }));
+
+        let cases = [
+            (
+                ChatOptions {
+                    candidate_count: Some(2),
+                    ..ChatOptions::default()
+                },
+                "ollama_candidate_count",
+            ),
+            (
+                ChatOptions {
+                    user: Some("user-42".to_string()),
+                    ..ChatOptions::default()
+                },
+                "ollama_user",
+            ),
+        ];
+        for (options, expected) in cases {
+            let mut request = request.clone();
+            request.options = options;
+            match build_ollama_body(&request, "llama3.2", false, &defaults, None) {
+                Err(LLMError::UnsupportedFeature { feature }) => assert_eq!(feature, expected),
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+    }
+
+    /// `/api/chat` returns no token log probabilities, so requesting them is an error.
+    #[test]
+    fn rejects_logprobs() {
+        for options in [
+            ChatOptions {
+                logprobs: Some(true),
+                ..ChatOptions::default()
+            },
+            ChatOptions {
+                top_logprobs: Some(3),
+                ..ChatOptions::default()
+            },
+        ] {
+            let mut request = request(vec![Message {
+                role: Role::user(),
+                name: None,
+                content: vec![ContentPart::Text(TextContent {
+                    text: "Hi".to_string(),
+                })],
+                metadata: None,
+            }]);
+            request.options = options;
+            match build_ollama_body(&request, "llama3.2", false, &Map::new(), None) {
+                Err(LLMError::UnsupportedFeature { feature }) => {
+                    assert_eq!(feature, "ollama_logprobs")
+                }
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+    }
+
+    /// Tool calls stay on the assistant message and results become `tool` messages.
+    #[test]
+    fn tool_calls_and_results_round_trip() {
+        let mut request = request(vec![
+            Message {
+                role: Role::assistant(),
+                name: None,
+                content: vec![ContentPart::ToolCall(ToolCall {
+                    id: None,
+                    name: "get_weather".to_string(),
+                    arguments: json!({ "city": "Tokyo" }),
+                    kind: ToolCallKind::Function,
+                })],
+                metadata: None,
+            },
+            Message {
+                role: Role("tool".to_string()),
+                name: Some("get_weather".to_string()),
+                content: vec![ContentPart::ToolResult(ToolResult {
+                    call_id: None,
+                    output: json!({ "temperature": 21 }),
+                    is_error: false,
+                    content: Vec::new(),
+                    metadata: None,
+                })],
+                metadata: None,
+            },
+        ]);
+        request.tools = vec![ToolDefinition {
+            name: "get_weather".to_string(),
+            description: Some("Looks up the weather".to_string()),
+            input_schema: None,
+            kind: ToolKind::Function,
+            metadata: None,
+            cache_control: None,
+        }];
+
+        let body =
+            build_ollama_body(&request, "llama3.1", false, &Map::new(), None).expect("build");
+
+        assert_eq!(
+            body["messages"],
+            json!([
+                {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{ "function": { "name": "get_weather", "arguments": { "city": "Tokyo" } } }]
+                },
+                { "role": "tool", "content": "{\"temperature\":21}", "tool_name": "get_weather" }
+            ])
+        );
+        assert_eq!(body["tools"][0]["function"]["name"], json!("get_weather"));
+        assert!(body.get("options").is_none());
+
+        request.tool_choice = Some(ToolChoice::Any);
+        assert!(matches!(
+            build_ollama_body(&request, "llama3.1", false, &Map::new(), None),
+            Err(LLMError::UnsupportedFeature { .. })
+        ));
+    }
+
+    /// Ollama reuses its KV cache by itself, so breakpoints and tool cache markers are dropped.
+    #[test]
+    fn cache_breakpoints_are_ignored() {
+        let (request, plain) = request_with_cache_breakpoints();
+
+        let body =
+            build_ollama_body(&request, "llama3.2", false, &Map::new(), None).expect("build");
+
+        assert_eq!(
+            body,
+            build_ollama_body(&plain, "llama3.2", false, &Map::new(), None).expect("build")
+        );
+        assert!(!body.to_string().contains("cache"));
+    }
+}
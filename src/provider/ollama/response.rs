@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::error::LLMError;
+use crate::types::{
+    ChatResponse, ContentPart, FinishReason, Message, OutputItem, ProviderMetadata, Role,
+    TextContent, TokenUsage, ToolCall, ToolCallKind,
+};
+
+use super::types::OllamaChatResponse;
+
+/// Timing fields (nanoseconds) copied into `TokenUsage::details`.
+const DURATION_FIELDS: [&str; 4] = [
+    "total_duration",
+    "load_duration",
+    "prompt_eval_duration",
+    "eval_duration",
+];
+
+pub(crate) fn map_response(
+    resp: OllamaChatResponse,
+    provider: &'static str,
+    endpoint: String,
+) -> Result<ChatResponse, LLMError> {
+    let raw = serde_json::to_value(&resp).ok();
+
+    let mut outputs = Vec::new();
+    let mut has_tool_calls = false;
+    if let Some(message) = &resp.message {
+        if let Some(thinking) = message.thinking.as_ref().filter(|t| !t.is_empty()) {
+            outputs.push(OutputItem::Reasoning {
+                text: thinking.clone(),
+                index: 0,
+            });
+        }
+        if !message.content.is_empty() {
+            outputs.push(OutputItem::Message {
+                message: Message {
+                    role: Role::assistant(),
+                    name: None,
+                    content: vec![ContentPart::Text(TextContent {
+                        text: message.content.clone(),
+                    })],
+                    metadata: None,
+                },
+                index: 0,
+                logprobs: Vec::new(),
+                citations: Vec::new(),
+            });
+        }
+        for call in &message.tool_calls {
+            has_tool_calls = true;
+            outputs.push(OutputItem::ToolCall {
+                call: ToolCall {
+                    // Ollama does not assign call IDs.
+                    id: None,
+                    name: call.function.name.clone(),
+                    arguments: call.function.arguments.clone(),
+                    kind: ToolCallKind::Function,
+                },
+                index: 0,
+            });
+        }
+    }
+
+    Ok(ChatResponse {
+        outputs,
+        usage: convert_usage(&resp),
+        finish_reason: convert_finish_reason(resp.done_reason.as_deref(), has_tool_calls),
+        candidate_finish_reasons: Vec::new(),
+        prompt_safety: None,
+        model: resp.model.clone(),
+        provider: ProviderMetadata {
+            provider: provider.to_string(),
+            request_id: None,
+            endpoint: Some(endpoint),
+            raw,
+        },
+    })
+}
+
+/// Ollama reports `stop` even when the turn ended with tool calls.
+pub(crate) fn convert_finish_reason(
+    reason: Option<&str>,
+    has_tool_calls: bool,
+) -> Option<FinishReason> {
+    match reason {
+        Some("stop") if has_tool_calls => Some(FinishReason::ToolCalls),
+        Some("stop") => Some(FinishReason::Stop),
+        Some("length") => Some(FinishReason::Length),
+        Some(other) => Some(FinishReason::Other(other.to_string())),
+        None if has_tool_calls => Some(FinishReason::ToolCalls),
+        None => None,
+    }
+}
+
+pub(crate) fn convert_usage(resp: &OllamaChatResponse) -> Option<TokenUsage> {
+    if resp.prompt_eval_count.is_none() && resp.eval_count.is_none() {
+        return None;
+    }
+    let details: HashMap<String, Value> = DURATION_FIELDS
+        .iter()
+        .filter_map(|field| {
+            resp.extra
+                .get(*field)
+                .map(|value| (field.to_string(), value.clone()))
+        })
+        .collect();
+    Some(TokenUsage {
+        prompt_tokens: resp.prompt_eval_count,
+        completion_tokens: resp.eval_count,
+        reasoning_tokens: None,
+        total_tokens: Some(resp.prompt_eval_count.unwrap_or(0) + resp.eval_count.unwrap_or(0)),
+        cached_input_tokens: None,
+        cache_creation_tokens: None,
+        details: if details.is_empty() {
+            None
+        } else {
+            Some(details)
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn maps_thinking_tool_calls_and_usage() {
+        let resp: OllamaChatResponse = serde_json::from_value(json!({
+            "model": "qwen3:8b",
+            "created_at": "2025-05-01T10:00:00Z",
+            "message": {
+                "role": "assistant",
+                "content": "",
+                "thinking": "The user wants the weather.",
+                "tool_calls": [
+                    { "function": { "name": "get_weather", "arguments": { "city": "Tokyo" } } }
+                ]
+            },
+            "done": true,
+            "done_reason": "stop",
+            "total_duration": 5_000_000_000u64,
+            "load_duration": 1_000_000u64,
+            "prompt_eval_count": 26,
+            "eval_count": 40
+        }))
+        .expect("parse");
+
+        let response = map_response(resp, "ollama", "endpoint".to_string()).expect("map");
+
+        assert!(matches!(
+            response.finish_reason,
+            Some(FinishReason::ToolCalls)
+        ));
+        assert!(matches!(&response.outputs[0], OutputItem::Reasoning { .. }));
+        match &response.outputs[1] {
+            OutputItem::ToolCall { call, .. } => {
+                assert_eq!(call.name, "get_weather");
+                assert_eq!(call.arguments, json!({ "city": "Tokyo" }));
+            }
+            other => panic!("expected tool call, got {other:?}"),
+        }
+        let usage = response.usage.expect("usage");
+        assert_eq!(usage.total_tokens, Some(66));
+        assert_eq!(
+            usage.details.expect("details")["total_duration"],
+            json!(5_000_000_000u64)
+        );
+        assert_eq!(response.model.as_deref(), Some("qwen3:8b"));
+    }
+}
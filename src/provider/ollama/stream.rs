@@ -0,0 +1,205 @@
+use futures_util::StreamExt;
+use serde_json::Value;
+
+use crate::error::LLMError;
+use crate::http::HttpBodyStream;
+use crate::provider::ChatStream;
+use crate::stream::ndjson::NdjsonDecoder;
+use crate::types::{
+    ChatChunk, ChatEvent, ContentDelta, MessageDelta, ProviderMetadata, Role, ToolCallDelta,
+    ToolCallKind,
+};
+
+use super::error::parse_ollama_error;
+use super::response::{convert_finish_reason, convert_usage};
+use super::types::OllamaChatResponse;
+
+pub(crate) fn create_stream(
+    body: HttpBodyStream,
+    provider: &'static str,
+    endpoint: String,
+) -> ChatStream {
+    // Tool calls arrive whole, one or more per line; number them across the stream.
+    let mut tool_calls = 0usize;
+    let stream = NdjsonDecoder::new(body, provider).map(move |line| {
+        let line = line?;
+        let value: Value = serde_json::from_str(&line).map_err(|err| LLMError::Provider {
+            provider,
+            message: format!("failed to parse stream line: {err}"),
+        })?;
+        // Failures after the response started (e.g. the runner crashing) arrive in-band.
+        if value.get("error").is_some() {
+            return Err(parse_ollama_error(500, &line, None));
+        }
+        let chunk: OllamaChatResponse =
+            serde_json::from_value(value.clone()).map_err(|err| LLMError::Provider {
+                provider,
+                message: format!("failed to parse stream line: {err}"),
+            })?;
+        Ok(convert_stream_line(
+            chunk,
+            value,
+            &mut tool_calls,
+            provider,
+            &endpoint,
+        ))
+    });
+    Box::pin(stream)
+}
+
+pub(crate) async fn collect_stream_text(
+    mut body: HttpBodyStream,
+    provider: &'static str,
+) -> Result<String, LLMError> {
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.next().await {
+        bytes.extend_from_slice(&chunk?);
+    }
+    String::from_utf8(bytes).map_err(|err| LLMError::Provider {
+        provider,
+        message: format!("failed to decode stream error body: {err}"),
+    })
+}
+
+fn convert_stream_line(
+    chunk: OllamaChatResponse,
+    raw: Value,
+    tool_calls: &mut usize,
+    provider: &'static str,
+    endpoint: &str,
+) -> ChatChunk {
+    let mut events = Vec::new();
+    if let Some(message) = &chunk.message {
+        if !message.content.is_empty() {
+            events.push(ChatEvent::MessageDelta(MessageDelta {
+                index: 0,
+                role: Some(Role::assistant()),
+                content: vec![ContentDelta::Text {
+                    text: message.content.clone(),
+                }],
+                finish_reason: None,
+                logprobs: Vec::new(),
+            }));
+        }
+        for call in &message.tool_calls {
+            events.push(ChatEvent::ToolCallDelta(ToolCallDelta {
+                index: *tool_calls,
+                id: None,
+                name: Some(call.function.name.clone()),
+                arguments_delta: Some(call.function.arguments.to_string()),
+                kind: Some(ToolCallKind::Function),
+                is_finished: true,
+            }));
+            *tool_calls += 1;
+        }
+    }
+
+    let usage = if chunk.done {
+        if let Some(reason) = convert_finish_reason(chunk.done_reason.as_deref(), *tool_calls > 0) {
+            events.push(ChatEvent::MessageDelta(MessageDelta {
+                index: 0,
+                role: Some(Role::assistant()),
+                content: Vec::new(),
+                finish_reason: Some(reason),
+                logprobs: Vec::new(),
+            }));
+        }
+        convert_usage(&chunk)
+    } else {
+        None
+    };
+
+    // Always attach the raw line; it also carries `thinking` deltas.
+    events.push(ChatEvent::Custom { data: raw });
+
+    ChatChunk {
+        events,
+        usage,
+        is_terminal: chunk.done,
+        provider: ProviderMetadata {
+            provider: provider.to_string(),
+            request_id: None,
+            endpoint: Some(endpoint.to_string()),
+            raw: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+    use crate::types::FinishReason;
+
+    fn body(text: &str) -> HttpBodyStream {
+        let chunks: Vec<Result<Vec<u8>, LLMError>> = text
+            .as_bytes()
+            .chunks(17)
+            .map(|chunk| Ok(chunk.to_vec()))
+            .collect();
+        Box::pin(stream::iter(chunks))
+    }
+
+    #[tokio::test]
+    async fn converts_ndjson_lines_into_chunks() {
+        let text = concat!(
+            r#"{"model":"llama3.1","message":{"role":"assistant","content":"Let me "},"done":false}"#,
+            "\n",
+            r#"{"model":"llama3.1","message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"get_weather","arguments":{"city":"Tokyo"}}}]},"done":false}"#,
+            "\n",
+            r#"{"model":"llama3.1","message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":12,"eval_count":8,"eval_duration":1000}"#,
+            "\n",
+        );
+        let chunks: Vec<ChatChunk> = create_stream(body(text), "ollama", "e".into())
+            .map(|chunk| chunk.expect("chunk"))
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 3);
+
+        assert!(matches!(
+            &chunks[0].events[0],
+            ChatEvent::MessageDelta(delta)
+                if matches!(&delta.content[0], ContentDelta::Text { text } if text == "Let me ")
+        ));
+        assert!(matches!(
+            &chunks[1].events[0],
+            ChatEvent::ToolCallDelta(delta)
+                if delta.is_finished
+                    && delta.arguments_delta.as_deref() == Some(r#"{"city":"Tokyo"}"#)
+        ));
+        let last = &chunks[2];
+        assert!(last.is_terminal);
+        assert!(matches!(
+            &last.events[0],
+            ChatEvent::MessageDelta(MessageDelta {
+                finish_reason: Some(FinishReason::ToolCalls),
+                ..
+            })
+        ));
+        assert_eq!(
+            last.usage.as_ref().and_then(|usage| usage.total_tokens),
+            Some(20)
+        );
+    }
+
+    #[tokio::test]
+    async fn in_band_errors_end_the_stream() {
+        let text = concat!(
+            r#"{"message":{"role":"assistant","content":"Hi"},"done":false}"#,
+            "\n",
+            r#"{"error":"llama runner process has terminated: signal: killed"}"#,
+            "\n",
+        );
+        let results: Vec<_> = create_stream(body(text), "ollama", "e".into())
+            .collect()
+            .await;
+        assert!(results[0].is_ok());
+        match &results[1] {
+            Err(LLMError::Provider { message, .. }) => {
+                assert!(message.contains("runner process"), "{message}")
+            }
+            other => panic!("expected Provider error, got {other:?}"),
+        }
+    }
+}
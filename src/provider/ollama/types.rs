@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Response object returned by `/api/chat`, both as the full body and as each NDJSON line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct OllamaChatResponse {
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) created_at: Option<String>,
+    #[serde(default)]
+    pub(crate) message: Option<OllamaMessage>,
+    /// `true` on the final object; streaming lines before it carry partial content.
+    #[serde(default)]
+    pub(crate) done: bool,
+    /// Why generation ended, e.g. `stop` or `length`.
+    #[serde(default)]
+    pub(crate) done_reason: Option<String>,
+    /// Prompt tokens evaluated, reported on the final object.
+    #[serde(default)]
+    pub(crate) prompt_eval_count: Option<u64>,
+    /// Generated tokens, reported on the final object.
+    #[serde(default)]
+    pub(crate) eval_count: Option<u64>,
+    /// Timings in nanoseconds (`total_duration`, `load_duration`, ...).
+    #[serde(flatten)]
+    pub(crate) extra: HashMap<String, Value>,
+}
+
+/// Assistant message, or the delta carried by one streaming line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct OllamaMessage {
+    #[serde(default)]
+    pub(crate) role: String,
+    #[serde(default)]
+    pub(crate) content: String,
+    /// Reasoning trace emitted when `think` is enabled.
+    #[serde(default)]
+    pub(crate) thinking: Option<String>,
+    #[serde(default)]
+    pub(crate) tool_calls: Vec<OllamaToolCall>,
+}
+
+/// Tool call; Ollama always returns complete calls with object arguments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct OllamaToolCall {
+    pub(crate) function: OllamaFunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct OllamaFunctionCall {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) arguments: Value,
+}
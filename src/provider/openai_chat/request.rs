@@ -36,6 +36,38 @@ pub(crate) fn build_openai_body(
     if let Some(penalty) = request.options.frequency_penalty {
         body.insert("frequency_penalty".to_string(), Value::from(penalty));
     }
+    if request.options.top_k.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "openai_chat_top_k",
+        });
+    }
+    if let Some(stop) = &request.options.stop_sequences {
+        body.insert("stop".to_string(), json!(stop));
+    }
+    if let Some(seed) = request.options.seed {
+        body.insert("seed".to_string(), Value::from(seed));
+    }
+    if let Some(n) = request.options.candidate_count {
+        body.insert("n".to_string(), Value::from(n));
+    }
+    if let Some(user) = &request.options.user {
+        body.insert("user".to_string(), Value::String(user.clone()));
+    }
+    if let Some(key) = &request.options.prompt_cache_key {
+        body.insert("prompt_cache_key".to_string(), Value::String(key.clone()));
+    }
+    if request.options.cached_content.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "openai_chat_cached_content",
+        });
+    }
+    // `top_logprobs` is rejected upstream unless `logprobs` is also true.
+    if let Some(top_logprobs) = request.options.top_logprobs {
+        body.insert("logprobs".to_string(), Value::Bool(true));
+        body.insert("top_logprobs".to_string(), Value::from(top_logprobs));
+    } else if let Some(logprobs) = request.options.logprobs {
+        body.insert("logprobs".to_string(), Value::Bool(logprobs));
+    }
     if let Some(parallel) = request.options.parallel_tool_calls {
         body.insert("parallel_tool_calls".to_string(), Value::from(parallel));
     }
@@ -104,6 +136,8 @@ fn convert_message(message: &Message) -> Result<Value, LLMError> {
             ContentPart::ToolResult(result) => {
                 tool_results.push(result);
             }
+            // OpenAI caches prompt prefixes automatically; see `prompt_cache_key`.
+            ContentPart::CacheBreakpoint(_) => {}
             _ => {
                 content_parts.push(convert_content_part(part)?);
             }
@@ -117,6 +151,12 @@ fn convert_message(message: &Message) -> Result<Value, LLMError> {
             });
         }
         if let Some(result) = tool_results.first() {
+            // Tool messages only carry text.
+            if !result.content.is_empty() {
+                return Err(LLMError::UnsupportedFeature {
+                    feature: "openai_chat_tool_result_content",
+                });
+            }
             let content_string = match &result.output {
                 Value::String(text) => text.clone(),
                 other => other.to_string(),
@@ -205,9 +245,11 @@ fn convert_content_part(part: &ContentPart) -> Result<Value, LLMError> {
             }
         })),
         ContentPart::Data { data } => Ok(data.clone()),
-        ContentPart::ToolCall(_) | ContentPart::ToolResult(_) => Err(LLMError::Validation {
-            message: "tool content must use dedicated structs".to_string(),
-        }),
+        ContentPart::ToolCall(_) | ContentPart::ToolResult(_) | ContentPart::CacheBreakpoint(_) => {
+            Err(LLMError::Validation {
+                message: "tool content must use dedicated structs".to_string(),
+            })
+        }
     }
 }
 
@@ -298,6 +340,7 @@ fn format_image_detail(detail: &ImageDetail) -> &'static str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::test_support::request_with_cache_breakpoints;
     use crate::types::{
         AudioContent, ChatOptions, ChatRequest, ContentPart, FileContent, ImageContent,
         ImageDetail, ImageSource, MediaSource, Message, ReasoningOptions, Role, TextContent,
@@ -352,6 +395,12 @@ mod tests {
             max_output_tokens: Some(256),
             presence_penalty: Some(0.5),
             frequency_penalty: Some(-0.2),
+            stop_sequences: Some(vec!["END".to_string()]),
+            seed: Some(7),
+            candidate_count: Some(2),
+            user: Some("user-42".to_string()),
+            top_logprobs: Some(2),
+            prompt_cache_key: Some("tenant-7".to_string()),
             parallel_tool_calls: Some(true),
             reasoning: Some(ReasoningOptions {
                 effort: Some(ReasoningEffort::High),
@@ -398,6 +447,13 @@ mod tests {
         assert!((presence_penalty - 0.5).abs() < 1e-6);
         let frequency_penalty = body["frequency_penalty"].as_f64().unwrap();
         assert!((frequency_penalty - (-0.2_f64)).abs() < 1e-6);
+        assert_eq!(body["stop"], json!(["END"]));
+        assert_eq!(body["seed"], json!(7));
+        assert_eq!(body["n"], json!(2));
+        assert_eq!(body["user"], json!("user-42"));
+        assert_eq!(body["logprobs"], json!(true));
+        assert_eq!(body["top_logprobs"], json!(2));
+        assert_eq!(body["prompt_cache_key"], json!("tenant-7"));
         assert_eq!(body["parallel_tool_calls"], json!(true));
         assert_eq!(body["reasoning_effort"], json!("high"));
         assert_eq!(body["max_reasoning_tokens"], json!(1024));
@@ -407,6 +463,16 @@ mod tests {
 
         // Metadata is serialized into an object.
         assert_eq!(body["metadata"]["trace_id"], json!("abc123"));
+
+        let mut request = request;
+        request.options.top_k = Some(40);
+        let err = build_openai_body(&request, "gpt-4.1", false).expect_err("top_k is unsupported");
+        assert!(matches!(
+            err,
+            LLMError::UnsupportedFeature {
+                feature: "openai_chat_top_k"
+            }
+        ));
     }
 
     /// Maps multimodal content (images, audio, video, files, and custom data).
@@ -537,6 +603,7 @@ mod tests {
             input_schema: Some(json!({"type": "object"})),
             kind: ToolKind::Function,
             metadata: None,
+            cache_control: None,
         }];
         let tools_json = convert_tools(&tools).expect("tools should convert");
         assert_eq!(
@@ -587,6 +654,7 @@ mod tests {
             call_id: Some("call_1".to_string()),
             output: json!({"ok": true}),
             is_error: false,
+            content: Vec::new(),
             metadata: None,
         };
         let message = Message {
@@ -613,6 +681,7 @@ mod tests {
             call_id: Some("id".to_string()),
             output: json!(1),
             is_error: false,
+            content: Vec::new(),
             metadata: None,
         };
         let msg_many = Message {
@@ -640,6 +709,7 @@ mod tests {
                 call_id: None,
                 output: json!(1),
                 is_error: false,
+                content: Vec::new(),
                 metadata: None,
             })],
             metadata: None,
@@ -652,4 +722,18 @@ mod tests {
             other => panic!("unexpected error: {other:?}"),
         }
     }
+
+    /// OpenAI caches prompt prefixes automatically, so breakpoints and tool cache markers are dropped.
+    #[test]
+    fn cache_breakpoints_are_ignored() {
+        let (request, plain) = request_with_cache_breakpoints();
+
+        let body = build_openai_body(&request, "gpt-4.1", false).expect("build");
+
+        assert_eq!(
+            body,
+            build_openai_body(&plain, "gpt-4.1", false).expect("build")
+        );
+        assert!(!body.to_string().contains("cache"));
+    }
 }
@@ -1,9 +1,12 @@
+use std::collections::HashMap;
+
 use serde_json::{Value, json};
 
 use crate::error::LLMError;
 use crate::types::{
-    ChatResponse, ContentPart, FinishReason, Message, OutputItem, ProviderMetadata, Role,
-    TextContent, TokenUsage, ToolCall, ToolCallKind,
+    ChatResponse, Citation, CitationSource, ContentPart, FinishReason, Message, OutputItem,
+    ProviderMetadata, Role, SafetyInfo, SafetyLevel, SafetyRating, TextContent, TextSpan,
+    TokenUsage, ToolCall, ToolCallKind,
 };
 
 use super::types::{
@@ -24,7 +27,24 @@ pub(crate) fn map_response(
             outputs.push(OutputItem::Message {
                 message: msg,
                 index: choice.index,
+                logprobs: choice
+                    .logprobs
+                    .clone()
+                    .and_then(|logprobs| logprobs.content)
+                    .unwrap_or_default(),
+                citations: message
+                    .annotations
+                    .iter()
+                    .flatten()
+                    .filter_map(convert_annotation)
+                    .collect(),
             });
+            if let Some(text) = &message.refusal {
+                outputs.push(OutputItem::Refusal {
+                    text: text.clone(),
+                    index: choice.index,
+                });
+            }
             for call in tool_calls {
                 outputs.push(OutputItem::ToolCall {
                     call,
@@ -32,17 +52,42 @@ pub(crate) fn map_response(
                 });
             }
         }
+        if let Some(safety) = choice
+            .content_filter_results
+            .as_ref()
+            .and_then(|results| completion_safety(results, choice.finish_reason.as_deref()))
+        {
+            outputs.push(OutputItem::Safety {
+                safety,
+                index: choice.index,
+            });
+        }
     }
     let finish_reason = resp
         .choices
         .iter()
         .find_map(|choice| choice.finish_reason.as_deref().map(convert_finish_reason));
+    let mut candidate_finish_reasons = Vec::new();
+    if resp.choices.len() > 1 {
+        for choice in &resp.choices {
+            if candidate_finish_reasons.len() <= choice.index {
+                candidate_finish_reasons.resize(choice.index + 1, None);
+            }
+            candidate_finish_reasons[choice.index] =
+                choice.finish_reason.as_deref().map(convert_finish_reason);
+        }
+    }
     let usage = resp.usage.clone().map(convert_usage);
     let model = resp.model.clone();
     Ok(ChatResponse {
         outputs,
         usage,
         finish_reason,
+        candidate_finish_reasons,
+        prompt_safety: resp
+            .prompt_filter_results
+            .as_deref()
+            .and_then(prompt_safety),
         model: Some(model),
         provider: ProviderMetadata {
             provider: provider.to_string(),
@@ -90,6 +135,92 @@ fn convert_response_message(
     ))
 }
 
+/// Azure `prompt_filter_results`; ratings of every prompt are combined.
+pub(crate) fn prompt_safety(results: &[Value]) -> Option<SafetyInfo> {
+    let ratings: Vec<SafetyRating> = results
+        .iter()
+        .flat_map(|result| convert_content_filter_results(&result["content_filter_results"]))
+        .collect();
+    (!ratings.is_empty()).then(|| SafetyInfo {
+        ratings,
+        ..SafetyInfo::default()
+    })
+}
+
+/// Azure `choices[].content_filter_results`, marked blocked when the choice was filtered.
+pub(crate) fn completion_safety(
+    results: &Value,
+    finish_reason: Option<&str>,
+) -> Option<SafetyInfo> {
+    let ratings = convert_content_filter_results(results);
+    let block_reason = (finish_reason == Some("content_filter")).then(|| "content_filter".into());
+    (!ratings.is_empty() || block_reason.is_some()).then_some(SafetyInfo {
+        ratings,
+        block_reason,
+        message: None,
+    })
+}
+
+/// Maps Azure `content_filter_results` entries (`{"hate": {"filtered", "severity"}}`)
+/// to ratings sorted by category; `detected` flags and other fields stay as metadata.
+pub(crate) fn convert_content_filter_results(results: &Value) -> Vec<SafetyRating> {
+    let Some(object) = results.as_object() else {
+        return Vec::new();
+    };
+    let mut ratings: Vec<SafetyRating> = object
+        .iter()
+        .filter_map(|(category, detail)| {
+            let detail = detail.as_object()?;
+            let metadata: HashMap<String, Value> = detail
+                .iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "filtered" | "severity"))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            Some(SafetyRating {
+                category: category.clone(),
+                probability: None,
+                severity: detail
+                    .get("severity")
+                    .and_then(Value::as_str)
+                    .and_then(SafetyLevel::parse),
+                blocked: detail.get("filtered").and_then(Value::as_bool)?,
+                metadata: (!metadata.is_empty()).then_some(metadata),
+            })
+        })
+        .collect();
+    ratings.sort_by(|a, b| a.category.cmp(&b.category));
+    ratings
+}
+
+/// Converts a `url_citation` annotation; spans index the single text part.
+fn convert_annotation(annotation: &Value) -> Option<Citation> {
+    if annotation.get("type")?.as_str()? != "url_citation" {
+        return None;
+    }
+    let citation = annotation.get("url_citation")?;
+    let offset = |key: &str| {
+        citation
+            .get(key)
+            .and_then(Value::as_u64)
+            .map(|v| v as usize)
+    };
+    Some(Citation {
+        part: 0,
+        span: offset("start_index")
+            .zip(offset("end_index"))
+            .map(|(start, end)| TextSpan { start, end }),
+        source: CitationSource::Url {
+            url: citation.get("url")?.as_str()?.to_string(),
+            title: citation
+                .get("title")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        },
+        cited_text: None,
+        metadata: None,
+    })
+}
+
 pub(crate) fn convert_finish_reason(reason: &str) -> FinishReason {
     match reason {
         "stop" => FinishReason::Stop,
@@ -102,11 +233,18 @@ pub(crate) fn convert_finish_reason(reason: &str) -> FinishReason {
 }
 
 pub(crate) fn convert_usage(usage: OpenAiUsage) -> TokenUsage {
+    let cached_input_tokens = usage
+        .prompt_tokens_details
+        .as_ref()
+        .and_then(|details| details.get("cached_tokens"))
+        .and_then(Value::as_u64);
     TokenUsage {
         prompt_tokens: usage.prompt_tokens,
         completion_tokens: usage.completion_tokens,
         reasoning_tokens: usage.reasoning_tokens,
         total_tokens: usage.total_tokens,
+        cached_input_tokens,
+        cache_creation_tokens: None,
         details: None,
     }
 }
@@ -170,7 +308,7 @@ fn convert_content_part_response(part: OpenAiMessagePart) -> Result<ContentPart,
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{ContentPart, ImageDetail, ImageSource};
+    use crate::types::{ContentPart, ImageDetail, ImageSource, SafetyLevel};
 
     fn sample_response_text() -> OpenAiChatResponse {
         OpenAiChatResponse {
@@ -185,17 +323,23 @@ mod tests {
                     content: Some(OpenAiMessageContent::Text("hello world".to_string())),
                     name: None,
                     tool_calls: None,
+                    annotations: None,
+                    refusal: None,
                 }),
                 finish_reason: Some("stop".to_string()),
+                logprobs: None,
+                content_filter_results: None,
             }],
             usage: Some(OpenAiUsage {
                 prompt_tokens: Some(10),
                 completion_tokens: Some(5),
                 total_tokens: Some(15),
                 reasoning_tokens: Some(0),
+                prompt_tokens_details: None,
             }),
             service_tier: Some("default".to_string()),
             system_fingerprint: None,
+            prompt_filter_results: None,
         }
     }
 
@@ -220,7 +364,7 @@ mod tests {
         // Expect a single Message output.
         assert_eq!(mapped.outputs.len(), 1);
         match &mapped.outputs[0] {
-            OutputItem::Message { message, index } => {
+            OutputItem::Message { message, index, .. } => {
                 assert_eq!(*index, 0);
                 assert_eq!(message.role.0, "assistant");
                 assert_eq!(message.name, None);
@@ -244,6 +388,76 @@ mod tests {
         assert_eq!(usage.reasoning_tokens, Some(0));
     }
 
+    /// `n > 1` keeps every choice under its own candidate index and finish reason.
+    #[test]
+    fn map_response_with_multiple_choices() {
+        let mut resp = sample_response_text();
+        let mut second = resp.choices[0].clone();
+        second.index = 1;
+        second.finish_reason = Some("length".to_string());
+        resp.choices.push(second);
+
+        let mapped = map_response(resp, "openai_chat", "endpoint".into()).expect("map");
+        assert!(matches!(
+            mapped.candidate_finish_reasons.as_slice(),
+            [Some(FinishReason::Stop), Some(FinishReason::Length)]
+        ));
+        let candidates = mapped.into_candidates();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[1].index, 1);
+        assert_eq!(candidates[1].text(), "hello world");
+        assert!(matches!(
+            candidates[1].finish_reason,
+            Some(FinishReason::Length)
+        ));
+    }
+
+    /// `choices[].logprobs.content` becomes the message's token logprobs with alternatives.
+    #[test]
+    fn map_response_with_logprobs() {
+        let resp: OpenAiChatResponse = serde_json::from_value(json!({
+            "id": "chatcmpl-3",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4.1",
+            "choices": [{
+                "index": 0,
+                "finish_reason": "stop",
+                "message": { "role": "assistant", "content": "Yes" },
+                "logprobs": {
+                    "content": [{
+                        "token": "Yes",
+                        "logprob": -0.01,
+                        "bytes": [89, 101, 115],
+                        "top_logprobs": [
+                            { "token": "Yes", "logprob": -0.01, "bytes": [89, 101, 115] },
+                            { "token": "No", "logprob": -4.7, "bytes": null }
+                        ]
+                    }],
+                    "refusal": null
+                }
+            }]
+        }))
+        .expect("response");
+
+        let mapped = map_response(resp, "openai_chat", "endpoint".into()).expect("map");
+
+        let OutputItem::Message { logprobs, .. } = &mapped.outputs[0] else {
+            panic!("unexpected output: {:?}", mapped.outputs[0]);
+        };
+        assert_eq!(logprobs.len(), 1);
+        assert_eq!(logprobs[0].token, "Yes");
+        assert_eq!(logprobs[0].logprob, -0.01);
+        assert_eq!(logprobs[0].bytes.as_deref(), Some("Yes".as_bytes()));
+        let alternatives: Vec<(&str, f64)> = logprobs[0]
+            .top_logprobs
+            .iter()
+            .map(|top| (top.token.as_str(), top.logprob))
+            .collect();
+        assert_eq!(alternatives, [("Yes", -0.01), ("No", -4.7)]);
+        assert_eq!(logprobs[0].top_logprobs[1].bytes, None);
+    }
+
     #[test]
     fn map_response_with_tool_calls() {
         use super::super::types::{OpenAiResponseChoice, OpenAiToolCallResponse};
@@ -272,12 +486,17 @@ mod tests {
                             arguments: Some(r#"{"location":"Boston, MA"}"#.to_string()),
                         }),
                     }]),
+                    annotations: None,
+                    refusal: None,
                 }),
                 finish_reason: Some("tool_calls".to_string()),
+                logprobs: None,
+                content_filter_results: None,
             }],
             usage: None,
             service_tier: None,
             system_fingerprint: None,
+            prompt_filter_results: None,
         };
 
         let mapped =
@@ -398,6 +617,7 @@ mod tests {
             completion_tokens: Some(2),
             total_tokens: Some(3),
             reasoning_tokens: Some(4),
+            prompt_tokens_details: None,
         };
         let mapped = convert_usage(usage);
         assert_eq!(mapped.prompt_tokens, Some(1));
@@ -405,4 +625,50 @@ mod tests {
         assert_eq!(mapped.total_tokens, Some(3));
         assert_eq!(mapped.reasoning_tokens, Some(4));
     }
+
+    #[test]
+    fn map_refusal_and_azure_filter_results() {
+        let resp: OpenAiChatResponse = serde_json::from_value(json!({
+            "id": "chatcmpl-2",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4o",
+            "prompt_filter_results": [{
+                "prompt_index": 0,
+                "content_filter_results": {
+                    "hate": { "filtered": false, "severity": "safe" },
+                    "jailbreak": { "filtered": false, "detected": false }
+                }
+            }],
+            "choices": [{
+                "index": 0,
+                "finish_reason": "content_filter",
+                "message": { "role": "assistant", "content": null, "refusal": "I can't help with that." },
+                "content_filter_results": {
+                    "violence": { "filtered": true, "severity": "high" },
+                    "self_harm": { "filtered": false, "severity": "low" }
+                }
+            }]
+        }))
+        .expect("response");
+
+        let mapped = map_response(resp, "azure_openai", "endpoint".into()).expect("map");
+
+        let prompt = mapped.prompt_safety.expect("prompt safety");
+        assert_eq!(prompt.ratings.len(), 2);
+        assert_eq!(prompt.ratings[0].severity, Some(SafetyLevel::Negligible));
+        assert!(!prompt.is_blocked());
+        assert!(matches!(
+            &mapped.outputs[1],
+            OutputItem::Refusal { text, index: 0 } if text == "I can't help with that."
+        ));
+        match &mapped.outputs[2] {
+            OutputItem::Safety { safety, .. } => {
+                assert_eq!(safety.block_reason.as_deref(), Some("content_filter"));
+                assert_eq!(safety.blocked_categories(), ["violence"]);
+                assert_eq!(safety.ratings[0].severity, Some(SafetyLevel::Low));
+            }
+            other => panic!("unexpected output: {other:?}"),
+        }
+    }
 }
@@ -9,7 +9,7 @@ use crate::types::{
     ChatChunk, ChatEvent, ContentDelta, MessageDelta, ProviderMetadata, ToolCallDelta, ToolCallKind,
 };
 
-use super::response::{convert_finish_reason, convert_usage};
+use super::response::{completion_safety, convert_finish_reason, convert_usage, prompt_safety};
 use super::types::{OpenAiDeltaContent, OpenAiMessagePart, OpenAiStreamChunk, OpenAiToolCallDelta};
 
 pub(crate) fn create_stream(
@@ -56,16 +56,38 @@ pub(crate) async fn collect_stream_text(
     })
 }
 
-fn convert_stream_chunk(
+pub(crate) fn convert_stream_chunk(
     chunk: OpenAiStreamChunk,
     provider: &'static str,
     endpoint: &str,
 ) -> Result<ChatChunk, LLMError> {
     let mut events = Vec::new();
+    if let Some(safety) = chunk
+        .prompt_filter_results
+        .as_deref()
+        .and_then(prompt_safety)
+    {
+        events.push(ChatEvent::PromptSafety { safety });
+    }
     for choice in &chunk.choices {
+        let safety = choice
+            .content_filter_results
+            .as_ref()
+            .and_then(|results| completion_safety(results, choice.finish_reason.as_deref()));
         if let Some(delta) = &choice.delta {
-            if delta.role.is_some() || delta.content.is_some() || choice.finish_reason.is_some() {
-                let content_updates = match &delta.content {
+            let logprobs = choice
+                .logprobs
+                .clone()
+                .and_then(|logprobs| logprobs.content)
+                .unwrap_or_default();
+            if delta.role.is_some()
+                || delta.content.is_some()
+                || delta.refusal.is_some()
+                || choice.finish_reason.is_some()
+                || !logprobs.is_empty()
+                || safety.is_some()
+            {
+                let mut content_updates = match &delta.content {
                     Some(OpenAiDeltaContent::Parts(parts)) => convert_content_delta(parts)?,
                     Some(OpenAiDeltaContent::Text(text)) => {
                         if text.is_empty() {
@@ -76,15 +98,23 @@ fn convert_stream_chunk(
                     }
                     None => Vec::new(),
                 };
+                if let Some(text) = delta.refusal.as_ref().filter(|text| !text.is_empty()) {
+                    content_updates.push(ContentDelta::Refusal { text: text.clone() });
+                }
+                if let Some(safety) = safety {
+                    content_updates.push(ContentDelta::Safety { safety });
+                }
                 let message_delta = MessageDelta {
                     index: choice.index,
                     role: delta.role.clone().map(crate::types::Role),
                     content: content_updates,
                     finish_reason: choice.finish_reason.as_deref().map(convert_finish_reason),
+                    logprobs,
                 };
                 if message_delta.role.is_some()
                     || !message_delta.content.is_empty()
                     || message_delta.finish_reason.is_some()
+                    || !message_delta.logprobs.is_empty()
                 {
                     events.push(ChatEvent::MessageDelta(message_delta));
                 }
@@ -99,6 +129,15 @@ fn convert_stream_chunk(
                     events.push(ChatEvent::ToolCallDelta(delta));
                 }
             }
+        } else if let Some(safety) = safety {
+            // Azure may send filter results for earlier text on a chunk without a delta.
+            events.push(ChatEvent::MessageDelta(MessageDelta {
+                index: choice.index,
+                role: None,
+                content: vec![ContentDelta::Safety { safety }],
+                finish_reason: choice.finish_reason.as_deref().map(convert_finish_reason),
+                logprobs: Vec::new(),
+            }));
         }
     }
     let usage = chunk.usage.clone().map(convert_usage);
@@ -181,15 +220,20 @@ mod tests {
                     role: Some("assistant".to_string()),
                     content: Some(OpenAiDeltaContent::Text("hello".to_string())),
                     tool_calls: None,
+                    refusal: None,
                 }),
                 finish_reason: Some("stop".to_string()),
+                logprobs: None,
+                content_filter_results: None,
             }],
             usage: Some(OpenAiUsage {
                 prompt_tokens: Some(1),
                 completion_tokens: Some(2),
                 total_tokens: Some(3),
                 reasoning_tokens: Some(0),
+                prompt_tokens_details: None,
             }),
+            prompt_filter_results: None,
         };
 
         let chat_chunk =
@@ -224,6 +268,37 @@ mod tests {
         }
     }
 
+    /// Chunks carrying only logprobs still surface as a [`MessageDelta`].
+    #[test]
+    fn convert_stream_chunk_with_logprobs() {
+        let chunk: OpenAiStreamChunk = serde_json::from_value(serde_json::json!({
+            "choices": [{
+                "index": 0,
+                "delta": {},
+                "logprobs": {"content": [{
+                    "token": "Hi",
+                    "logprob": -0.25,
+                    "bytes": [72, 105],
+                    "top_logprobs": [{"token": "Hello", "logprob": -1.5, "bytes": null}]
+                }]}
+            }]
+        }))
+        .expect("chunk parses");
+
+        let chat_chunk =
+            convert_stream_chunk(chunk, "openai_chat", "endpoint").expect("convert should succeed");
+        assert_eq!(chat_chunk.events.len(), 1);
+        match &chat_chunk.events[0] {
+            ChatEvent::MessageDelta(delta) => {
+                assert!(delta.content.is_empty());
+                assert_eq!(delta.logprobs.len(), 1);
+                assert_eq!(delta.logprobs[0].token, "Hi");
+                assert_eq!(delta.logprobs[0].top_logprobs[0].token, "Hello");
+            }
+            other => panic!("unexpected chat event: {other:?}"),
+        }
+    }
+
     /// Converts content delta events that arrive as message parts.
     #[test]
     fn convert_content_delta_from_parts() {
@@ -285,4 +360,35 @@ mod tests {
         assert!(matches!(mapped.kind, Some(ToolCallKind::Function)));
         assert!(mapped.is_finished);
     }
+
+    /// Azure prompt filter results and refusal deltas become typed events.
+    #[test]
+    fn convert_prompt_filter_results_and_refusal_delta() {
+        let chunk: OpenAiStreamChunk = serde_json::from_value(json!({
+            "choices": [],
+            "prompt_filter_results": [{
+                "prompt_index": 0,
+                "content_filter_results": { "sexual": { "filtered": false, "severity": "safe" } }
+            }]
+        }))
+        .expect("chunk");
+        let chat_chunk = convert_stream_chunk(chunk, "azure_openai", "endpoint").expect("convert");
+        assert!(matches!(
+            &chat_chunk.events[..],
+            [ChatEvent::PromptSafety { safety }] if safety.ratings[0].category == "sexual"
+        ));
+
+        let chunk: OpenAiStreamChunk = serde_json::from_value(json!({
+            "choices": [{ "index": 0, "delta": { "refusal": "I can't" } }]
+        }))
+        .expect("chunk");
+        let chat_chunk = convert_stream_chunk(chunk, "openai_chat", "endpoint").expect("convert");
+        match &chat_chunk.events[0] {
+            ChatEvent::MessageDelta(delta) => assert!(matches!(
+                &delta.content[..],
+                [ContentDelta::Refusal { text }] if text == "I can't"
+            )),
+            other => panic!("unexpected chat event: {other:?}"),
+        }
+    }
 }
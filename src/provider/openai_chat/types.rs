@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::types::TokenLogprob;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub(crate) struct OpenAiChatResponse {
     pub(crate) id: String,
@@ -13,6 +15,9 @@ pub(crate) struct OpenAiChatResponse {
     pub(crate) usage: Option<OpenAiUsage>,
     pub(crate) service_tier: Option<String>,
     pub(crate) system_fingerprint: Option<String>,
+    /// Azure content filter results for each prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) prompt_filter_results: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -20,6 +25,18 @@ pub(crate) struct OpenAiResponseChoice {
     pub(crate) index: usize,
     pub(crate) message: Option<OpenAiResponseMessage>,
     pub(crate) finish_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) logprobs: Option<OpenAiLogprobs>,
+    /// Azure content filter results for the completion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content_filter_results: Option<Value>,
+}
+
+/// `choices[].logprobs`; entries already follow the shape of [`TokenLogprob`].
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub(crate) struct OpenAiLogprobs {
+    #[serde(default)]
+    pub(crate) content: Option<Vec<TokenLogprob>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -31,6 +48,12 @@ pub(crate) struct OpenAiResponseMessage {
     pub(crate) name: Option<String>,
     #[serde(default)]
     pub(crate) tool_calls: Option<Vec<OpenAiToolCallResponse>>,
+    /// `url_citation` annotations returned by search-enabled models.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) annotations: Option<Vec<Value>>,
+    /// Refusal message returned instead of content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) refusal: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -83,6 +106,8 @@ pub(crate) struct OpenAiUsage {
     pub(crate) total_tokens: Option<u64>,
     #[serde(default)]
     pub(crate) reasoning_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) prompt_tokens_details: Option<Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -91,6 +116,9 @@ pub(crate) struct OpenAiStreamChunk {
     pub(crate) choices: Vec<OpenAiStreamChoice>,
     #[serde(default)]
     pub(crate) usage: Option<OpenAiUsage>,
+    /// Azure content filter results for each prompt, sent on the first chunk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) prompt_filter_results: Option<Vec<Value>>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -100,6 +128,11 @@ pub(crate) struct OpenAiStreamChoice {
     pub(crate) delta: Option<OpenAiStreamDelta>,
     #[serde(default)]
     pub(crate) finish_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) logprobs: Option<OpenAiLogprobs>,
+    /// Azure content filter results for the text streamed so far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content_filter_results: Option<Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -110,6 +143,8 @@ pub(crate) struct OpenAiStreamDelta {
     pub(crate) content: Option<OpenAiDeltaContent>,
     #[serde(default)]
     pub(crate) tool_calls: Option<Vec<OpenAiToolCallDelta>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) refusal: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use serde_json::{Value, json};
+
+use crate::error::LLMError;
+use crate::provider::openai_chat::error::parse_openai_error;
+
+/// Parses errors from OpenAI-compatible servers.
+///
+/// Most vendors use the OpenAI `{"error": {...}}` envelope. Mistral and older vLLM
+/// releases put `message`, `type` and `code` at the top level instead, so those bodies are
+/// wrapped into the OpenAI shape first. Generic provider errors are re-attributed to
+/// `openai_compatible`.
+pub(crate) fn parse_compatible_error(
+    status: u16,
+    body: &str,
+    retry_after: Option<Duration>,
+) -> LLMError {
+    let normalized = match serde_json::from_str::<Value>(body) {
+        Ok(Value::Object(map)) if !map.contains_key("error") && map.contains_key("message") => {
+            let message = match &map["message"] {
+                Value::String(text) => Value::String(text.clone()),
+                // Mistral validation errors carry a structured `message`.
+                other => Value::String(other.to_string()),
+            };
+            json!({
+                "error": {
+                    "message": message,
+                    "type": map.get("type").cloned().unwrap_or(Value::Null),
+                    "code": map.get("code").cloned().unwrap_or(Value::Null),
+                }
+            })
+            .to_string()
+        }
+        _ => body.to_string(),
+    };
+    match parse_openai_error(status, &normalized, retry_after) {
+        LLMError::Provider { message, .. } => LLMError::Provider {
+            provider: "openai_compatible",
+            message,
+        },
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Top-level Mistral errors and OpenAI envelopes map to the same variants.
+    #[test]
+    fn parses_top_level_and_enveloped_errors() {
+        let mistral = r#"{"object":"error","message":"Invalid model: mistral-huge","type":"invalid_model","code":"1500"}"#;
+        match parse_compatible_error(400, mistral, None) {
+            LLMError::Validation { message } => {
+                assert!(message.contains("Invalid model"), "{message}")
+            }
+            other => panic!("expected Validation error, got {other:?}"),
+        }
+
+        let deepseek = r#"{"error":{"message":"Authentication Fails","type":"authentication_error","code":"invalid_request_error"}}"#;
+        assert!(matches!(
+            parse_compatible_error(401, deepseek, None),
+            LLMError::Auth { .. }
+        ));
+
+        assert!(matches!(
+            parse_compatible_error(502, "Bad Gateway", None),
+            LLMError::Provider {
+                provider: "openai_compatible",
+                ..
+            }
+        ));
+    }
+}
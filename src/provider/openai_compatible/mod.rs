@@ -0,0 +1,8 @@
+mod error;
+mod profile;
+mod provider;
+mod response;
+mod stream;
+
+pub use profile::OpenAiCompatibleProfile;
+pub use provider::OpenAiCompatibleProvider;
@@ -0,0 +1,201 @@
+use serde_json::{Map, Value};
+
+use crate::types::CapabilityDescriptor;
+
+/// Vendor quirks applied on top of the OpenAI Chat Completions mapping.
+///
+/// Each profile knows the vendor's default endpoint, which request fields it rejects,
+/// where reasoning text appears in responses and what the vendor can actually accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpenAiCompatibleProfile {
+    /// Any OpenAI-compatible server; nothing is stripped and both `reasoning_content`
+    /// and `reasoning` are read. Requires an explicit base URL.
+    #[default]
+    Generic,
+    /// DeepSeek (`https://api.deepseek.com`).
+    DeepSeek,
+    /// Mistral La Plateforme (`https://api.mistral.ai`).
+    Mistral,
+    /// Groq (`https://api.groq.com/openai`).
+    Groq,
+    /// OpenRouter (`https://openrouter.ai/api`).
+    OpenRouter,
+    /// Self-hosted vLLM OpenAI-compatible server. Requires an explicit base URL.
+    Vllm,
+}
+
+impl OpenAiCompatibleProfile {
+    /// Every profile, in the order they are documented.
+    pub const ALL: &'static [OpenAiCompatibleProfile] = &[
+        Self::Generic,
+        Self::DeepSeek,
+        Self::Mistral,
+        Self::Groq,
+        Self::OpenRouter,
+        Self::Vllm,
+    ];
+
+    /// Looks up a profile by its configuration name, e.g. `"deepseek"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use kotoba_llm::provider::openai_compatible::OpenAiCompatibleProfile;
+    /// assert_eq!(
+    ///     OpenAiCompatibleProfile::from_name("openrouter"),
+    ///     Some(OpenAiCompatibleProfile::OpenRouter)
+    /// );
+    /// assert_eq!(OpenAiCompatibleProfile::from_name("unknown"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|profile| profile.name() == name)
+    }
+
+    /// Configuration name of the profile.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Generic => "generic",
+            Self::DeepSeek => "deepseek",
+            Self::Mistral => "mistral",
+            Self::Groq => "groq",
+            Self::OpenRouter => "openrouter",
+            Self::Vllm => "vllm",
+        }
+    }
+
+    /// Hosted endpoint used when no base URL is configured.
+    pub fn default_base_url(&self) -> Option<&'static str> {
+        match self {
+            Self::Generic | Self::Vllm => None,
+            Self::DeepSeek => Some("https://api.deepseek.com"),
+            Self::Mistral => Some("https://api.mistral.ai"),
+            Self::Groq => Some("https://api.groq.com/openai"),
+            Self::OpenRouter => Some("https://openrouter.ai/api"),
+        }
+    }
+
+    /// Top-level request fields the vendor rejects or silently misinterprets.
+    pub(crate) fn unsupported_fields(&self) -> &'static [&'static str] {
+        match self {
+            Self::Generic | Self::OpenRouter => &[],
+            Self::DeepSeek => &[
+                "metadata",
+                "parallel_tool_calls",
+                "reasoning_effort",
+                "max_reasoning_tokens",
+                "prompt_cache_key",
+            ],
+            // Mistral answers unknown fields with 422.
+            Self::Mistral => &[
+                "metadata",
+                "reasoning_effort",
+                "max_reasoning_tokens",
+                "prompt_cache_key",
+            ],
+            Self::Groq | Self::Vllm => &["metadata", "max_reasoning_tokens", "prompt_cache_key"],
+        }
+    }
+
+    /// Whether the vendor accepts the non-standard top-level `top_k` sampling field.
+    pub(crate) fn supports_top_k(&self) -> bool {
+        matches!(self, Self::Generic | Self::OpenRouter | Self::Vllm)
+    }
+
+    /// Message or delta fields that carry reasoning text, checked in order.
+    pub(crate) fn reasoning_fields(&self) -> &'static [&'static str] {
+        match self {
+            Self::Generic => &["reasoning_content", "reasoning"],
+            Self::DeepSeek | Self::Vllm => &["reasoning_content"],
+            Self::Groq | Self::OpenRouter => &["reasoning"],
+            // Magistral returns `thinking` content parts, which already surface as data parts.
+            Self::Mistral => &[],
+        }
+    }
+
+    /// Adapts a Chat Completions body to the vendor's dialect.
+    pub(crate) fn adapt_body(&self, body: &mut Value) {
+        let Value::Object(map) = body else {
+            return;
+        };
+        if *self == Self::OpenRouter {
+            // OpenRouter normalises reasoning controls into a single `reasoning` object.
+            let mut reasoning = Map::new();
+            if let Some(effort) = map.remove("reasoning_effort") {
+                reasoning.insert("effort".to_string(), effort);
+            }
+            if let Some(budget) = map.remove("max_reasoning_tokens") {
+                reasoning.insert("max_tokens".to_string(), budget);
+            }
+            if !reasoning.is_empty() && !map.contains_key("reasoning") {
+                map.insert("reasoning".to_string(), Value::Object(reasoning));
+            }
+        }
+        if *self == Self::Mistral {
+            if let Some(seed) = map.remove("seed") {
+                map.insert("random_seed".to_string(), seed);
+            }
+        }
+        for field in self.unsupported_fields() {
+            map.remove(*field);
+        }
+    }
+
+    pub(crate) fn capabilities(&self) -> CapabilityDescriptor {
+        CapabilityDescriptor {
+            supports_stream: true,
+            // DeepSeek's chat models are text-only.
+            supports_image_input: *self != Self::DeepSeek,
+            supports_audio_input: false,
+            supports_video_input: false,
+            supports_tools: true,
+            // DeepSeek only offers `json_object`, not schema-constrained output.
+            supports_structured_output: *self != Self::DeepSeek,
+            supports_parallel_tool_calls: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Profiles strip fields their vendor rejects and rewrite reasoning controls.
+    #[test]
+    fn adapt_body_strips_and_rewrites_fields() {
+        let body = json!({
+            "model": "m",
+            "metadata": { "trace": "1" },
+            "parallel_tool_calls": true,
+            "reasoning_effort": "high",
+            "max_reasoning_tokens": 2048,
+            "seed": 7
+        });
+
+        let mut deepseek = body.clone();
+        OpenAiCompatibleProfile::DeepSeek.adapt_body(&mut deepseek);
+        assert_eq!(deepseek, json!({ "model": "m", "seed": 7 }));
+
+        let mut mistral = body.clone();
+        OpenAiCompatibleProfile::Mistral.adapt_body(&mut mistral);
+        assert_eq!(mistral["random_seed"], json!(7));
+        assert!(mistral.get("seed").is_none());
+
+        let mut openrouter = body.clone();
+        OpenAiCompatibleProfile::OpenRouter.adapt_body(&mut openrouter);
+        assert_eq!(
+            openrouter["reasoning"],
+            json!({ "effort": "high", "max_tokens": 2048 })
+        );
+        assert!(openrouter.get("reasoning_effort").is_none());
+        assert_eq!(openrouter["metadata"], json!({ "trace": "1" }));
+
+        let mut generic = body.clone();
+        OpenAiCompatibleProfile::Generic.adapt_body(&mut generic);
+        assert_eq!(generic, body);
+    }
+}
@@ -0,0 +1,572 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::config::{RequestPatch, remove_json_field};
+use crate::credential::{DynCredentialProvider, StaticCredential, invalidate_on_unauthorized};
+use crate::error::LLMError;
+use crate::http::{
+    DynHttpTransport, HttpResponse, HttpStreamResponse, post_json_stream_with_headers,
+    post_json_with_headers,
+};
+use crate::provider::openai_chat::request::build_openai_body;
+use crate::provider::openai_chat::stream::collect_stream_text;
+use crate::provider::{ChatStream, LLMProvider, PreparedRequest, retry::retry_after_from_headers};
+use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
+
+use super::error::parse_compatible_error;
+use super::profile::OpenAiCompatibleProfile;
+use super::response::map_compatible_response;
+use super::stream::create_stream;
+
+/// Provider for vendors that speak "almost OpenAI Chat Completions".
+///
+/// Requests are built by the `openai_chat` mapper and then adapted by an
+/// [`OpenAiCompatibleProfile`], which strips fields the vendor rejects and reads vendor
+/// fields such as `reasoning_content` back out of responses. Additional fields to strip
+/// and extra headers can be layered on top for vendors without a dedicated profile.
+pub struct OpenAiCompatibleProvider {
+    pub(crate) transport: DynHttpTransport,
+    pub(crate) profile: OpenAiCompatibleProfile,
+    pub(crate) base_url: Option<String>,
+    pub(crate) credential: DynCredentialProvider,
+    pub(crate) headers: HashMap<String, String>,
+    pub(crate) remove_fields: Vec<String>,
+    pub(crate) default_model: Option<String>,
+    pub(crate) request_patch: Option<RequestPatch>,
+}
+
+impl OpenAiCompatibleProvider {
+    /// `ModelConfig.extra` keys read by [`Self::from_model_config`].
+    pub const SUPPORTED_EXTRA_KEYS: &'static [&'static str] =
+        &["profile", "headers", "remove_fields"];
+
+    /// Creates a provider for `profile` that authenticates with a bearer API key.
+    ///
+    /// Profiles for hosted vendors default to the vendor's endpoint; `Generic` and `Vllm`
+    /// need [`Self::with_base_url`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use kotoba_llm::provider::openai_compatible::{OpenAiCompatibleProfile, OpenAiCompatibleProvider};
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let provider =
+    ///     OpenAiCompatibleProvider::new(transport, OpenAiCompatibleProfile::DeepSeek, "sk-test")
+    ///         .with_default_model("deepseek-reasoner");
+    /// assert_eq!(provider.name(), "openai_compatible");
+    /// # }
+    /// ```
+    pub fn new(
+        transport: DynHttpTransport,
+        profile: OpenAiCompatibleProfile,
+        api_key: impl Into<String>,
+    ) -> Self {
+        Self::from_credential_provider(transport, profile, Arc::new(StaticCredential::new(api_key)))
+    }
+
+    /// Creates a provider that asks `credential` for its API key before every request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::credential::EnvCredential;
+    /// # use kotoba_llm::provider::openai_compatible::{OpenAiCompatibleProfile, OpenAiCompatibleProvider};
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let credential = Arc::new(EnvCredential::new("VLLM_API_KEY"));
+    /// let provider = OpenAiCompatibleProvider::from_credential_provider(
+    ///     transport,
+    ///     OpenAiCompatibleProfile::Vllm,
+    ///     credential,
+    /// )
+    /// .with_base_url("http://gpu-node:8000/v1");
+    /// # let _ = provider;
+    /// # }
+    /// ```
+    pub fn from_credential_provider(
+        transport: DynHttpTransport,
+        profile: OpenAiCompatibleProfile,
+        credential: DynCredentialProvider,
+    ) -> Self {
+        Self {
+            transport,
+            profile,
+            base_url: profile.default_base_url().map(str::to_string),
+            credential,
+            headers: HashMap::new(),
+            remove_fields: Vec::new(),
+            default_model: None,
+            request_patch: None,
+        }
+    }
+
+    /// Overrides the server address; a trailing `/v1` is optional.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Sends an extra header with every request, e.g. OpenRouter's `HTTP-Referer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use kotoba_llm::provider::openai_compatible::{OpenAiCompatibleProfile, OpenAiCompatibleProvider};
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let provider =
+    ///     OpenAiCompatibleProvider::new(transport, OpenAiCompatibleProfile::OpenRouter, "sk-or")
+    ///         .with_header("HTTP-Referer", "https://example.com")
+    ///         .with_header("X-Title", "Example App");
+    /// # let _ = provider;
+    /// # }
+    /// ```
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Removes a body field (dotted path, like `RequestPatch::remove_fields`) after the
+    /// profile has been applied.
+    pub fn with_removed_field(mut self, path: impl Into<String>) -> Self {
+        self.remove_fields.push(path.into());
+        self
+    }
+
+    /// Configures a default model when the [`ChatRequest`] omits one.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.default_model = Some(model.into());
+        self
+    }
+
+    /// Constructs a provider from a [`crate::config::ModelConfig`].
+    ///
+    /// `extra.profile` selects the quirk profile (default `generic`), `extra.headers` adds
+    /// request headers and `extra.remove_fields` lists further body fields to strip.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LLMError::Auth`] when credentials are missing or invalid, and
+    /// [`LLMError::InvalidConfig`] for an unknown profile, a malformed `extra` value or a
+    /// missing `base_url` on profiles without a default endpoint.
+    pub fn from_model_config(
+        config: &crate::config::ModelConfig,
+        transport: DynHttpTransport,
+    ) -> Result<Self, LLMError> {
+        let profile = match config.extra.get("profile") {
+            None => OpenAiCompatibleProfile::Generic,
+            Some(Value::String(name)) => {
+                OpenAiCompatibleProfile::from_name(name).ok_or_else(|| {
+                    let known: Vec<&str> = OpenAiCompatibleProfile::ALL
+                        .iter()
+                        .map(OpenAiCompatibleProfile::name)
+                        .collect();
+                    invalid_extra(
+                        "profile",
+                        format!(
+                            "unknown profile `{name}`, expected one of {}",
+                            known.join(", ")
+                        ),
+                    )
+                })?
+            }
+            Some(other) => {
+                return Err(invalid_extra(
+                    "profile",
+                    format!("expected a profile name, got {other}"),
+                ));
+            }
+        };
+
+        let credential = config.credential.credential_provider("openai_compatible")?;
+        let mut provider = Self::from_credential_provider(transport, profile, credential);
+
+        if let Some(base_url) = &config.base_url {
+            provider = provider.with_base_url(base_url.clone());
+        }
+        if provider.base_url.is_none() {
+            return Err(LLMError::InvalidConfig {
+                field: "base_url".to_string(),
+                reason: format!(
+                    "the `{}` profile has no default endpoint; set base_url",
+                    profile.name()
+                ),
+            });
+        }
+
+        if let Some(headers) = config.extra.get("headers") {
+            let Some(headers) = headers.as_object() else {
+                return Err(invalid_extra(
+                    "headers",
+                    "expected a table of header name to value".to_string(),
+                ));
+            };
+            for (name, value) in headers {
+                let Some(value) = value.as_str() else {
+                    return Err(invalid_extra(
+                        "headers",
+                        format!("header `{name}` must be a string"),
+                    ));
+                };
+                provider = provider.with_header(name.clone(), value);
+            }
+        }
+
+        if let Some(fields) = config.extra.get("remove_fields") {
+            let Some(fields) = fields.as_array() else {
+                return Err(invalid_extra(
+                    "remove_fields",
+                    "expected a list of dotted field paths".to_string(),
+                ));
+            };
+            for field in fields {
+                let Some(field) = field.as_str() else {
+                    return Err(invalid_extra(
+                        "remove_fields",
+                        format!("expected a field path, got {field}"),
+                    ));
+                };
+                provider = provider.with_removed_field(field);
+            }
+        }
+
+        if let Some(model) = &config.default_model {
+            provider = provider.with_default_model(model.clone());
+        }
+
+        provider.request_patch = config.patch.clone();
+
+        Ok(provider)
+    }
+
+    /// Returns the quirk profile in use.
+    pub fn profile(&self) -> OpenAiCompatibleProfile {
+        self.profile
+    }
+
+    pub(crate) fn endpoint(&self) -> Result<String, LLMError> {
+        let base = self
+            .base_url
+            .as_deref()
+            .ok_or_else(|| LLMError::InvalidConfig {
+                field: "base_url".to_string(),
+                reason: format!(
+                    "the `{}` profile has no default endpoint; call with_base_url",
+                    self.profile.name()
+                ),
+            })?
+            .trim_end_matches('/');
+        if base.ends_with("/v1") {
+            Ok(format!("{base}/chat/completions"))
+        } else {
+            Ok(format!("{base}/v1/chat/completions"))
+        }
+    }
+
+    fn build_headers(&self, api_key: &str) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        headers.insert("Authorization".to_string(), format!("Bearer {api_key}"));
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        headers.insert("Accept".to_string(), "application/json".to_string());
+        for (name, value) in &self.headers {
+            headers.insert(name.clone(), value.clone());
+        }
+        headers
+    }
+
+    fn resolve_model(&self, request: &ChatRequest) -> Result<String, LLMError> {
+        request
+            .options
+            .model
+            .clone()
+            .or_else(|| self.default_model.clone())
+            .ok_or_else(|| LLMError::Validation {
+                message: "model is required for OpenAI-compatible providers".to_string(),
+            })
+    }
+
+    async fn prepare(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let model = self.resolve_model(request)?;
+        let url = self.endpoint()?;
+        let mut body = match request.options.top_k {
+            // The Chat Completions mapper rejects `top_k`; some vendors accept it as an extension.
+            Some(top_k) if self.profile.supports_top_k() => {
+                let mut request = request.clone();
+                request.options.top_k = None;
+                let mut body = build_openai_body(&request, &model, stream)?;
+                body["top_k"] = Value::from(top_k);
+                body
+            }
+            _ => build_openai_body(request, &model, stream)?,
+        };
+        self.profile.adapt_body(&mut body);
+        for path in &self.remove_fields {
+            remove_json_field(&mut body, path);
+        }
+        let token = self.credential.token().await?;
+        let mut prepared = PreparedRequest {
+            url,
+            headers: self.build_headers(&token.value),
+            body,
+        };
+        self.apply_patch(&mut prepared.url, &mut prepared.headers, &mut prepared.body);
+        Ok(prepared)
+    }
+
+    async fn send_request(&self, prepared: PreparedRequest) -> Result<HttpResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
+        post_json_with_headers(self.transport.as_ref(), url, headers, &body).await
+    }
+
+    async fn send_stream_request(
+        &self,
+        prepared: PreparedRequest,
+    ) -> Result<HttpStreamResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
+        post_json_stream_with_headers(self.transport.as_ref(), url, headers, &body).await
+    }
+
+    fn ensure_success(&self, response: HttpResponse) -> Result<String, LLMError> {
+        let HttpResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        let text = String::from_utf8(body).map_err(|err| LLMError::transport(err.to_string()))?;
+        if (200..300).contains(&status) {
+            Ok(text)
+        } else {
+            invalidate_on_unauthorized(&self.credential, status);
+            Err(parse_compatible_error(
+                status,
+                &text,
+                retry_after_from_headers(&headers),
+            ))
+        }
+    }
+
+    fn apply_patch(
+        &self,
+        url: &mut String,
+        headers: &mut HashMap<String, String>,
+        body: &mut Value,
+    ) {
+        if let Some(patch) = &self.request_patch {
+            patch.apply(url, headers, body);
+        }
+    }
+}
+
+fn invalid_extra(key: &str, reason: String) -> LLMError {
+    LLMError::InvalidConfig {
+        field: format!("extra.{key}"),
+        reason,
+    }
+}
+
+#[async_trait]
+impl LLMProvider for OpenAiCompatibleProvider {
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LLMError> {
+        let prepared = self.prepare(&request, false).await?;
+        let endpoint = prepared.url.clone();
+        let response = self.send_request(prepared).await?;
+        let text = self.ensure_success(response)?;
+        let raw: Value = serde_json::from_str(&text).map_err(|err| LLMError::Provider {
+            provider: self.name(),
+            message: format!("failed to parse OpenAI-compatible response: {err}"),
+        })?;
+        map_compatible_response(raw, self.profile, self.name(), endpoint)
+    }
+
+    async fn stream_chat(&self, request: ChatRequest) -> Result<ChatStream, LLMError> {
+        let prepared = self.prepare(&request, true).await?;
+        let endpoint = prepared.url.clone();
+        let response = self.send_stream_request(prepared).await?;
+        let HttpStreamResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        if !(200..300).contains(&status) {
+            invalidate_on_unauthorized(&self.credential, status);
+            let text = collect_stream_text(body, self.name()).await?;
+            return Err(parse_compatible_error(
+                status,
+                &text,
+                retry_after_from_headers(&headers),
+            ));
+        }
+        Ok(create_stream(body, self.profile, self.name(), endpoint))
+    }
+
+    async fn prepare_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        self.prepare(request, stream).await
+    }
+
+    fn capabilities(&self) -> CapabilityDescriptor {
+        self.profile.capabilities()
+    }
+
+    fn name(&self) -> &'static str {
+        "openai_compatible"
+    }
+
+    fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::config::{Credential, ModelConfig, ProviderKind};
+    use crate::http::test_support::PanicTransport;
+    use crate::types::test_support::text_request;
+
+    fn provider(profile: OpenAiCompatibleProfile) -> OpenAiCompatibleProvider {
+        OpenAiCompatibleProvider::new(Arc::new(PanicTransport), profile, "sk-test")
+            .with_base_url("http://localhost:8000/v1")
+            .with_default_model("test-model")
+    }
+
+    fn config(extra: Value, base_url: Option<&str>) -> ModelConfig {
+        ModelConfig {
+            handle: "compatible".to_string(),
+            provider: ProviderKind::OpenAiCompatible,
+            credential: Credential::Bearer {
+                token: "sk-test".to_string(),
+            },
+            default_model: Some("test-model".to_string()),
+            base_url: base_url.map(str::to_string),
+            extra: serde_json::from_value(extra).expect("extra"),
+            patch: None,
+            catalog: Vec::new(),
+        }
+    }
+
+    /// `top_k` is sent as a top-level field only to vendors that accept it.
+    #[tokio::test]
+    async fn top_k_passes_through_for_profiles_that_accept_it() {
+        let mut request = text_request("Hi");
+        request.options.top_k = Some(40);
+        request.options.seed = Some(7);
+
+        for profile in [
+            OpenAiCompatibleProfile::Generic,
+            OpenAiCompatibleProfile::OpenRouter,
+            OpenAiCompatibleProfile::Vllm,
+        ] {
+            let prepared = provider(profile)
+                .prepare_request(&request, false)
+                .await
+                .expect("prepare");
+            assert_eq!(prepared.body["top_k"], json!(40), "{profile:?}");
+            assert_eq!(prepared.body["seed"], json!(7), "{profile:?}");
+        }
+
+        match provider(OpenAiCompatibleProfile::DeepSeek)
+            .prepare_request(&request, false)
+            .await
+        {
+            Err(LLMError::UnsupportedFeature { feature }) => {
+                assert_eq!(feature, "openai_chat_top_k")
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        request.options.top_k = None;
+        let prepared = provider(OpenAiCompatibleProfile::Mistral)
+            .prepare_request(&request, false)
+            .await
+            .expect("prepare");
+        assert_eq!(prepared.body["random_seed"], json!(7));
+        assert!(prepared.body.get("seed").is_none());
+    }
+
+    /// Profiles, extra headers and removed fields from the config shape the request.
+    #[tokio::test]
+    async fn config_profiles_adapt_requests() {
+        let deepseek = OpenAiCompatibleProvider::from_model_config(
+            &config(
+                json!({
+                    "profile": "deepseek",
+                    "headers": { "X-Request-Source": "kotoba" },
+                    "remove_fields": ["temperature"]
+                }),
+                None,
+            ),
+            Arc::new(PanicTransport),
+        )
+        .expect("provider");
+        let vllm = OpenAiCompatibleProvider::from_model_config(
+            &config(
+                json!({ "profile": "vllm" }),
+                Some("http://gpu-node:8000/v1"),
+            ),
+            Arc::new(PanicTransport),
+        )
+        .expect("provider");
+        let mut request = text_request("ping");
+        request.metadata = Some(HashMap::from([("trace".to_string(), json!("1"))]));
+        request.options.temperature = Some(0.3);
+        request.options.parallel_tool_calls = Some(true);
+
+        let prepared = deepseek
+            .prepare_request(&request, false)
+            .await
+            .expect("prepared request");
+        assert_eq!(prepared.url, "https://api.deepseek.com/v1/chat/completions");
+        assert_eq!(
+            prepared.headers.get("X-Request-Source"),
+            Some(&"kotoba".to_string())
+        );
+        assert!(prepared.body.get("metadata").is_none());
+        assert!(prepared.body.get("parallel_tool_calls").is_none());
+        assert!(prepared.body.get("temperature").is_none());
+
+        let prepared = vllm
+            .prepare_request(&request, true)
+            .await
+            .expect("prepared request");
+        assert_eq!(prepared.url, "http://gpu-node:8000/v1/chat/completions");
+        assert_eq!(prepared.body["temperature"], json!(0.3f32));
+        assert!(prepared.body.get("metadata").is_none());
+
+        for (extra, base_url, field) in [
+            (
+                json!({ "profile": "unknown" }),
+                Some("http://x"),
+                "extra.profile",
+            ),
+            (json!({ "profile": "generic" }), None, "base_url"),
+        ] {
+            match OpenAiCompatibleProvider::from_model_config(
+                &config(extra, base_url),
+                Arc::new(PanicTransport),
+            ) {
+                Err(LLMError::InvalidConfig { field: actual, .. }) => assert_eq!(actual, field),
+                Err(other) => panic!("unexpected error: {other:?}"),
+                Ok(_) => panic!("expected invalid config"),
+            }
+        }
+    }
+}
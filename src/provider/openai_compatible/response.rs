@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::error::LLMError;
+use crate::provider::openai_chat::response::map_response;
+use crate::provider::openai_chat::types::OpenAiChatResponse;
+use crate::types::{ChatResponse, OutputItem, TokenUsage};
+
+use super::profile::OpenAiCompatibleProfile;
+
+/// Usage fields already mapped onto [`TokenUsage`]; everything else goes into `details`.
+const STANDARD_USAGE_FIELDS: &[&str] = &[
+    "prompt_tokens",
+    "completion_tokens",
+    "total_tokens",
+    "reasoning_tokens",
+];
+
+/// Maps a Chat Completions response and layers the profile's quirks on top.
+pub(crate) fn map_compatible_response(
+    raw: Value,
+    profile: OpenAiCompatibleProfile,
+    provider: &'static str,
+    endpoint: String,
+) -> Result<ChatResponse, LLMError> {
+    let parsed: OpenAiChatResponse =
+        serde_json::from_value(raw.clone()).map_err(|err| LLMError::Provider {
+            provider,
+            message: format!("failed to parse OpenAI-compatible response: {err}"),
+        })?;
+    let mut response = map_response(parsed, provider, endpoint)?;
+
+    // Reasoning text precedes the message of the same choice.
+    let choices = raw["choices"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    for choice in choices.iter().rev() {
+        let index = choice["index"].as_u64().unwrap_or(0) as usize;
+        if let Some(text) = reasoning_text(&choice["message"], profile) {
+            let position = response
+                .outputs
+                .iter()
+                .position(
+                    |item| matches!(item, OutputItem::Message { index: i, .. } if *i == index),
+                )
+                .unwrap_or(response.outputs.len());
+            response
+                .outputs
+                .insert(position, OutputItem::Reasoning { text, index });
+        }
+    }
+
+    if let Some(usage) = raw.get("usage") {
+        response.usage = convert_usage(usage);
+    }
+    // Keep the vendor fields that the typed OpenAI structs drop.
+    response.provider.raw = Some(raw);
+    Ok(response)
+}
+
+/// Returns the reasoning text carried by a message or delta object, if any.
+pub(crate) fn reasoning_text(message: &Value, profile: OpenAiCompatibleProfile) -> Option<String> {
+    profile
+        .reasoning_fields()
+        .iter()
+        .filter_map(|field| message.get(*field).and_then(Value::as_str))
+        .find(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+/// Converts a usage object, reading reasoning tokens from the nested
+/// `completion_tokens_details`, cache hits from `prompt_tokens_details.cached_tokens` or
+/// DeepSeek's `prompt_cache_hit_tokens`, and keeping vendor counters such as OpenRouter's
+/// `cost` in `details`.
+pub(crate) fn convert_usage(usage: &Value) -> Option<TokenUsage> {
+    let Value::Object(map) = usage else {
+        return None;
+    };
+    let count = |value: Option<&Value>| value.and_then(Value::as_u64);
+    let reasoning_tokens = count(map.get("reasoning_tokens")).or_else(|| {
+        count(
+            map.get("completion_tokens_details")
+                .and_then(|details| details.get("reasoning_tokens")),
+        )
+    });
+    let cached_input_tokens = count(
+        map.get("prompt_tokens_details")
+            .and_then(|details| details.get("cached_tokens")),
+    )
+    .or_else(|| count(map.get("prompt_cache_hit_tokens")));
+    let details: HashMap<String, Value> = map
+        .iter()
+        .filter(|(key, value)| !STANDARD_USAGE_FIELDS.contains(&key.as_str()) && !value.is_null())
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    Some(TokenUsage {
+        prompt_tokens: count(map.get("prompt_tokens")),
+        completion_tokens: count(map.get("completion_tokens")),
+        reasoning_tokens,
+        total_tokens: count(map.get("total_tokens")),
+        cached_input_tokens,
+        cache_creation_tokens: None,
+        details: (!details.is_empty()).then_some(details),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::ContentPart;
+
+    /// DeepSeek reasoner responses expose `reasoning_content` and cache counters.
+    #[test]
+    fn maps_reasoning_content_and_vendor_usage() {
+        let raw = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "deepseek-reasoner",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "42",
+                    "reasoning_content": "6 times 7"
+                },
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": 10,
+                "completion_tokens": 20,
+                "total_tokens": 30,
+                "prompt_cache_hit_tokens": 8,
+                "completion_tokens_details": { "reasoning_tokens": 15 }
+            }
+        });
+
+        let response =
+            map_compatible_response(raw, OpenAiCompatibleProfile::DeepSeek, "p", "e".into())
+                .expect("map");
+
+        assert!(matches!(
+            &response.outputs[0],
+            OutputItem::Reasoning { text, index: 0 } if text == "6 times 7"
+        ));
+        assert!(matches!(
+            &response.outputs[1],
+            OutputItem::Message { message, .. }
+                if matches!(&message.content[0], ContentPart::Text(text) if text.text == "42")
+        ));
+        let usage = response.usage.expect("usage");
+        assert_eq!(usage.reasoning_tokens, Some(15));
+        assert_eq!(usage.total_tokens, Some(30));
+        assert_eq!(usage.cached_input_tokens, Some(8));
+        let details = usage.details.expect("details");
+        assert_eq!(details["prompt_cache_hit_tokens"], json!(8));
+        assert!(
+            response.provider.raw.expect("raw")["choices"][0]["message"]
+                .get("reasoning_content")
+                .is_some()
+        );
+    }
+
+    /// Profiles only read the reasoning fields their vendor uses.
+    #[test]
+    fn reasoning_fields_follow_the_profile() {
+        let message = json!({ "reasoning": "thinking out loud" });
+        assert_eq!(
+            reasoning_text(&message, OpenAiCompatibleProfile::Groq).as_deref(),
+            Some("thinking out loud")
+        );
+        assert_eq!(
+            reasoning_text(&message, OpenAiCompatibleProfile::DeepSeek),
+            None
+        );
+        assert!(reasoning_text(&message, OpenAiCompatibleProfile::Generic).is_some());
+    }
+}
@@ -0,0 +1,128 @@
+use futures_util::StreamExt;
+use serde_json::{Value, json};
+
+use crate::error::LLMError;
+use crate::http::HttpBodyStream;
+use crate::provider::ChatStream;
+use crate::provider::openai_chat::stream::convert_stream_chunk;
+use crate::provider::openai_chat::types::OpenAiStreamChunk;
+use crate::stream::{StreamDecoder, StreamEvent};
+use crate::types::{ChatChunk, ChatEvent, ContentDelta, MessageDelta, ProviderMetadata};
+
+use super::profile::OpenAiCompatibleProfile;
+use super::response::{convert_usage, reasoning_text};
+
+pub(crate) fn create_stream(
+    body: HttpBodyStream,
+    profile: OpenAiCompatibleProfile,
+    provider: &'static str,
+    endpoint: String,
+) -> ChatStream {
+    let stream = StreamDecoder::new(body, provider).map(move |event| match event {
+        Ok(StreamEvent::Data(data)) => {
+            let raw: Value = serde_json::from_str(&data).map_err(|err| LLMError::Provider {
+                provider,
+                message: format!("failed to parse stream chunk: {err}"),
+            })?;
+            convert_compatible_chunk(raw, profile, provider, &endpoint)
+        }
+        Ok(StreamEvent::Done) => Ok(ChatChunk {
+            events: Vec::new(),
+            usage: None,
+            is_terminal: true,
+            provider: ProviderMetadata {
+                provider: provider.to_string(),
+                request_id: None,
+                endpoint: Some(endpoint.clone()),
+                raw: Some(json!({"event": "[DONE]"})),
+            },
+        }),
+        Err(err) => Err(err),
+    });
+    Box::pin(stream)
+}
+
+fn convert_compatible_chunk(
+    raw: Value,
+    profile: OpenAiCompatibleProfile,
+    provider: &'static str,
+    endpoint: &str,
+) -> Result<ChatChunk, LLMError> {
+    let chunk: OpenAiStreamChunk =
+        serde_json::from_value(raw.clone()).map_err(|err| LLMError::Provider {
+            provider,
+            message: format!("failed to parse stream chunk: {err}"),
+        })?;
+    let mut converted = convert_stream_chunk(chunk, provider, endpoint)?;
+
+    // Reasoning deltas come first so consumers see them before the answer text.
+    let mut reasoning = Vec::new();
+    for choice in raw["choices"].as_array().map(Vec::as_slice).unwrap_or(&[]) {
+        if let Some(text) = reasoning_text(&choice["delta"], profile) {
+            reasoning.push(ChatEvent::MessageDelta(MessageDelta {
+                index: choice["index"].as_u64().unwrap_or(0) as usize,
+                role: None,
+                content: vec![ContentDelta::Json {
+                    value: json!({ "type": "reasoning", "text": text }),
+                }],
+                finish_reason: None,
+                logprobs: Vec::new(),
+            }));
+        }
+    }
+    converted.events.splice(0..0, reasoning);
+
+    // Groq reports streaming usage under `x_groq.usage` on the final chunk.
+    let usage = raw
+        .get("usage")
+        .filter(|usage| !usage.is_null())
+        .or_else(|| raw.get("x_groq").and_then(|x_groq| x_groq.get("usage")));
+    if let Some(usage) = usage {
+        converted.usage = convert_usage(usage);
+    }
+    converted.provider.raw = Some(raw);
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reasoning deltas and Groq's `x_groq.usage` are surfaced on stream chunks.
+    #[test]
+    fn converts_reasoning_deltas_and_groq_usage() {
+        let raw = json!({
+            "id": "chatcmpl-1",
+            "choices": [{
+                "index": 0,
+                "delta": { "role": "assistant", "content": "", "reasoning": "Let me think" },
+                "finish_reason": null
+            }]
+        });
+        let chunk =
+            convert_compatible_chunk(raw, OpenAiCompatibleProfile::Groq, "p", "e").expect("chunk");
+        match &chunk.events[0] {
+            ChatEvent::MessageDelta(delta) => match &delta.content[0] {
+                ContentDelta::Json { value } => {
+                    assert_eq!(
+                        value,
+                        &json!({ "type": "reasoning", "text": "Let me think" })
+                    )
+                }
+                other => panic!("unexpected content delta: {other:?}"),
+            },
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        let raw = json!({
+            "id": "chatcmpl-1",
+            "choices": [{ "index": 0, "delta": {}, "finish_reason": "stop" }],
+            "x_groq": { "usage": { "prompt_tokens": 5, "completion_tokens": 7, "total_tokens": 12, "queue_time": 0.01 } }
+        });
+        let chunk =
+            convert_compatible_chunk(raw, OpenAiCompatibleProfile::Groq, "p", "e").expect("chunk");
+        let usage = chunk.usage.expect("usage");
+        assert_eq!(usage.total_tokens, Some(12));
+        assert!(usage.details.expect("details").contains_key("queue_time"));
+    }
+}
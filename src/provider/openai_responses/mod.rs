@@ -1,8 +1,8 @@
-mod error;
+pub(crate) mod error;
 mod provider;
-mod request;
-mod response;
-mod stream;
-mod types;
+pub(crate) mod request;
+pub(crate) mod response;
+pub(crate) mod stream;
+pub(crate) mod types;
 
 pub use provider::OpenAiResponsesProvider;
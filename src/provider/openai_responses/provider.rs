@@ -1,16 +1,18 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 
 use crate::config::RequestPatch;
+use crate::credential::{DynCredentialProvider, StaticCredential, invalidate_on_unauthorized};
 use crate::error::LLMError;
 use crate::http::{
     DynHttpTransport, HttpResponse, HttpStreamResponse, post_json_stream_with_headers,
     post_json_with_headers,
 };
-use crate::provider::{ChatStream, LLMProvider, retry::retry_after_from_headers};
+use crate::provider::{ChatStream, LLMProvider, PreparedRequest, retry::retry_after_from_headers};
 use crate::types::{CapabilityDescriptor, ChatRequest, ChatResponse};
 
 use super::error::parse_openai_responses_error;
@@ -28,7 +30,7 @@ const DEFAULT_BASE_URL: &str = "https://api.openai.com";
 pub struct OpenAiResponsesProvider {
     pub(crate) transport: DynHttpTransport,
     pub(crate) base_url: String,
-    pub(crate) api_key: String,
+    pub(crate) credential: DynCredentialProvider,
     pub(crate) organization: Option<String>,
     pub(crate) project: Option<String>,
     pub(crate) default_model: Option<String>,
@@ -36,23 +38,54 @@ pub struct OpenAiResponsesProvider {
 }
 
 impl OpenAiResponsesProvider {
+    /// `ModelConfig.extra` keys read by [`Self::from_model_config`].
+    pub const SUPPORTED_EXTRA_KEYS: &'static [&'static str] = &["organization", "project"];
+
     /// Creates a provider targeting the default `https://api.openai.com` endpoint.
     ///
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
     /// # use kotoba_llm::provider::openai_responses::OpenAiResponsesProvider;
     /// # use kotoba_llm::provider::LLMProvider;
     /// # use kotoba_llm::http::reqwest::default_dyn_transport;
     /// let transport = default_dyn_transport().expect("transport");
     /// let provider = OpenAiResponsesProvider::new(transport, "key");
     /// assert_eq!(provider.name(), "openai_responses");
+    /// # }
     /// ```
     pub fn new(transport: DynHttpTransport, api_key: impl Into<String>) -> Self {
+        Self::from_credential_provider(transport, Arc::new(StaticCredential::new(api_key)))
+    }
+
+    /// Creates a provider that asks `credential` for its API key before every request.
+    ///
+    /// Use this with rotating secrets, e.g. a [`crate::credential::CachedCredential`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
+    /// # use std::sync::Arc;
+    /// # use kotoba_llm::credential::EnvCredential;
+    /// # use kotoba_llm::provider::openai_responses::OpenAiResponsesProvider;
+    /// # use kotoba_llm::provider::LLMProvider;
+    /// # use kotoba_llm::http::reqwest::default_dyn_transport;
+    /// let transport = default_dyn_transport().expect("transport");
+    /// let credential = Arc::new(EnvCredential::new("API_KEY"));
+    /// let provider = OpenAiResponsesProvider::from_credential_provider(transport, credential);
+    /// assert_eq!(provider.name(), "openai_responses");
+    /// # }
+    /// ```
+    pub fn from_credential_provider(
+        transport: DynHttpTransport,
+        credential: DynCredentialProvider,
+    ) -> Self {
         Self {
             transport,
             base_url: DEFAULT_BASE_URL.to_string(),
-            api_key: api_key.into(),
+            credential,
             organization: None,
             project: None,
             default_model: None,
@@ -65,6 +98,7 @@ impl OpenAiResponsesProvider {
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
     /// # use kotoba_llm::provider::openai_responses::OpenAiResponsesProvider;
     /// # use kotoba_llm::provider::LLMProvider;
     /// # use kotoba_llm::http::reqwest::default_dyn_transport;
@@ -72,6 +106,7 @@ impl OpenAiResponsesProvider {
     /// let provider = OpenAiResponsesProvider::new(transport, "key")
     ///     .with_base_url("https://proxy.local");
     /// assert_eq!(provider.name(), "openai_responses");
+    /// # }
     /// ```
     pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
         self.base_url = base_url.into();
@@ -83,12 +118,14 @@ impl OpenAiResponsesProvider {
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
     /// # use kotoba_llm::provider::openai_responses::OpenAiResponsesProvider;
     /// # use kotoba_llm::provider::LLMProvider;
     /// # use kotoba_llm::http::reqwest::default_dyn_transport;
     /// let transport = default_dyn_transport().expect("transport");
     /// let provider = OpenAiResponsesProvider::new(transport, "key").with_organization("org_123");
     /// assert_eq!(provider.name(), "openai_responses");
+    /// # }
     /// ```
     pub fn with_organization(mut self, organization: impl Into<String>) -> Self {
         self.organization = Some(organization.into());
@@ -100,12 +137,14 @@ impl OpenAiResponsesProvider {
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
     /// # use kotoba_llm::provider::openai_responses::OpenAiResponsesProvider;
     /// # use kotoba_llm::provider::LLMProvider;
     /// # use kotoba_llm::http::reqwest::default_dyn_transport;
     /// let transport = default_dyn_transport().expect("transport");
     /// let provider = OpenAiResponsesProvider::new(transport, "key").with_project("proj_alpha");
     /// assert_eq!(provider.name(), "openai_responses");
+    /// # }
     /// ```
     pub fn with_project(mut self, project: impl Into<String>) -> Self {
         self.project = Some(project.into());
@@ -117,6 +156,7 @@ impl OpenAiResponsesProvider {
     /// # Examples
     ///
     /// ```
+    /// # #[cfg(feature = "reqwest-transport")] {
     /// # use kotoba_llm::provider::openai_responses::OpenAiResponsesProvider;
     /// # use kotoba_llm::provider::LLMProvider;
     /// # use kotoba_llm::http::reqwest::default_dyn_transport;
@@ -124,6 +164,7 @@ impl OpenAiResponsesProvider {
     /// let provider = OpenAiResponsesProvider::new(transport, "key")
     ///     .with_default_model("gpt-4.1-mini");
     /// assert_eq!(provider.name(), "openai_responses");
+    /// # }
     /// ```
     pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
         self.default_model = Some(model.into());
@@ -147,26 +188,8 @@ impl OpenAiResponsesProvider {
         config: &crate::config::ModelConfig,
         transport: DynHttpTransport,
     ) -> Result<Self, LLMError> {
-        use crate::config::Credential;
-
-        let api_key = match &config.credential {
-            Credential::ApiKey { key, .. } => key.clone(),
-            Credential::Bearer { token } => token.clone(),
-            Credential::ServiceAccount { .. } => {
-                return Err(LLMError::Auth {
-                    message:
-                        "provider openai_responses does not support service account credential"
-                            .to_string(),
-                });
-            }
-            Credential::None => {
-                return Err(LLMError::Auth {
-                    message: "provider openai_responses requires credential".to_string(),
-                });
-            }
-        };
-
-        let mut provider = Self::new(transport, api_key);
+        let credential = config.credential.credential_provider("openai_responses")?;
+        let mut provider = Self::from_credential_provider(transport, credential);
 
         if let Some(base_url) = &config.base_url {
             provider = provider.with_base_url(base_url.clone());
@@ -198,12 +221,9 @@ impl OpenAiResponsesProvider {
         }
     }
 
-    fn build_headers(&self) -> HashMap<String, String> {
+    fn build_headers(&self, api_key: &str) -> HashMap<String, String> {
         let mut headers = HashMap::new();
-        headers.insert(
-            "Authorization".to_string(),
-            format!("Bearer {}", self.api_key),
-        );
+        headers.insert("Authorization".to_string(), format!("Bearer {api_key}"));
         headers.insert("Content-Type".to_string(), "application/json".to_string());
         headers.insert("Accept".to_string(), "application/json".to_string());
         if let Some(org) = &self.organization {
@@ -231,19 +251,32 @@ impl OpenAiResponsesProvider {
         build_openai_responses_body(request, &model, stream)
     }
 
-    async fn send_request(&self, body: Value) -> Result<HttpResponse, LLMError> {
-        let mut url = self.endpoint();
-        let mut headers = self.build_headers();
-        let mut body = body;
-        self.apply_patch(&mut url, &mut headers, &mut body);
+    async fn prepare(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        let body = self.build_request_body(request, stream)?;
+        let token = self.credential.token().await?;
+        let mut prepared = PreparedRequest {
+            url: self.endpoint(),
+            headers: self.build_headers(&token.value),
+            body,
+        };
+        self.apply_patch(&mut prepared.url, &mut prepared.headers, &mut prepared.body);
+        Ok(prepared)
+    }
+
+    async fn send_request(&self, prepared: PreparedRequest) -> Result<HttpResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
         post_json_with_headers(self.transport.as_ref(), url, headers, &body).await
     }
 
-    async fn send_stream_request(&self, body: Value) -> Result<HttpStreamResponse, LLMError> {
-        let mut url = self.endpoint();
-        let mut headers = self.build_headers();
-        let mut body = body;
-        self.apply_patch(&mut url, &mut headers, &mut body);
+    async fn send_stream_request(
+        &self,
+        prepared: PreparedRequest,
+    ) -> Result<HttpStreamResponse, LLMError> {
+        let PreparedRequest { url, headers, body } = prepared;
         post_json_stream_with_headers(self.transport.as_ref(), url, headers, &body).await
     }
 
@@ -257,6 +290,7 @@ impl OpenAiResponsesProvider {
         if (200..300).contains(&status) {
             Ok(text)
         } else {
+            invalidate_on_unauthorized(&self.credential, status);
             Err(parse_openai_responses_error(
                 status,
                 &text,
@@ -287,22 +321,23 @@ impl OpenAiResponsesProvider {
 #[async_trait]
 impl LLMProvider for OpenAiResponsesProvider {
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse, LLMError> {
-        let body = self.build_request_body(&request, false)?;
-        let response = self.send_request(body).await?;
+        let prepared = self.prepare(&request, false).await?;
+        let response = self.send_request(prepared).await?;
         let text = self.ensure_success(response)?;
         let parsed: OpenAiResponsesResponse = self.try_parse(&text)?;
         map_responses_response(parsed, self.name(), self.endpoint())
     }
 
     async fn stream_chat(&self, request: ChatRequest) -> Result<ChatStream, LLMError> {
-        let body = self.build_request_body(&request, true)?;
-        let response = self.send_stream_request(body).await?;
+        let prepared = self.prepare(&request, true).await?;
+        let response = self.send_stream_request(prepared).await?;
         let HttpStreamResponse {
             status,
             headers,
             body,
         } = response;
         if !(200..300).contains(&status) {
+            invalidate_on_unauthorized(&self.credential, status);
             let text = collect_stream_text(body, self.name()).await?;
             return Err(parse_openai_responses_error(
                 status,
@@ -313,6 +348,14 @@ impl LLMProvider for OpenAiResponsesProvider {
         Ok(create_stream(body, self.name(), self.endpoint()))
     }
 
+    async fn prepare_request(
+        &self,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<PreparedRequest, LLMError> {
+        self.prepare(request, stream).await
+    }
+
     fn capabilities(&self) -> CapabilityDescriptor {
         CapabilityDescriptor {
             supports_stream: true,
@@ -330,4 +373,8 @@ impl LLMProvider for OpenAiResponsesProvider {
     fn name(&self) -> &'static str {
         "openai_responses"
     }
+
+    fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
 }
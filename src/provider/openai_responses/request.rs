@@ -1,10 +1,12 @@
+use std::collections::HashSet;
+
 use serde_json::{Map, Value, json};
 
 use crate::error::LLMError;
 use crate::types::{
     AudioContent, ChatRequest, ContentPart, FileContent, ImageContent, ImageDetail, ImageSource,
-    MediaSource, Message, ReasoningEffort, ResponseFormat, TextContent, ToolChoice, ToolDefinition,
-    ToolKind, VideoContent,
+    MediaSource, Message, ReasoningEffort, ResponseFormat, TextContent, ToolCall, ToolCallKind,
+    ToolChoice, ToolDefinition, ToolKind, ToolResult, VideoContent,
 };
 
 /// Builds the request body expected by the OpenAI Responses API.
@@ -19,6 +21,18 @@ pub(crate) fn build_openai_responses_body(
     // Fold system/developer messages into `instructions` and treat the rest as `input`.
     let mut instructions_parts = Vec::new();
     let mut input_messages = Vec::new();
+    // Results of computer calls are sent as `computer_call_output` items.
+    let computer_calls: HashSet<&str> = request
+        .messages
+        .iter()
+        .flat_map(|message| &message.content)
+        .filter_map(|part| match part {
+            ContentPart::ToolCall(call) if call.kind == ToolCallKind::ComputerUse => {
+                call.id.as_deref()
+            }
+            _ => None,
+        })
+        .collect();
     for message in &request.messages {
         match message.role.0.as_str() {
             "system" | "developer" => {
@@ -27,7 +41,7 @@ pub(crate) fn build_openai_responses_body(
                 }
             }
             _ => {
-                input_messages.push(convert_input_message(message)?);
+                input_messages.extend(convert_input_message(message, &computer_calls)?);
             }
         }
     }
@@ -54,6 +68,49 @@ pub(crate) fn build_openai_responses_body(
     if let Some(parallel) = request.options.parallel_tool_calls {
         body.insert("parallel_tool_calls".to_string(), Value::from(parallel));
     }
+    if let Some(user) = &request.options.user {
+        body.insert("user".to_string(), Value::String(user.clone()));
+    }
+    if let Some(key) = &request.options.prompt_cache_key {
+        body.insert("prompt_cache_key".to_string(), Value::String(key.clone()));
+    }
+    // Responses has no stop sequences, seed, top-k or multiple candidates.
+    let options = &request.options;
+    if options.stop_sequences.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "openai_responses_stop_sequences",
+        });
+    }
+    if options.seed.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "openai_responses_seed",
+        });
+    }
+    if options.top_k.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "openai_responses_top_k",
+        });
+    }
+    if options.candidate_count.is_some_and(|n| n > 1) {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "openai_responses_candidate_count",
+        });
+    }
+    if options.cached_content.is_some() {
+        return Err(LLMError::UnsupportedFeature {
+            feature: "openai_responses_cached_content",
+        });
+    }
+    // Logprobs are opt-in output fields; `top_logprobs` alone also enables them.
+    if options.logprobs == Some(true) || options.top_logprobs.is_some() {
+        body.insert(
+            "include".to_string(),
+            json!(["message.output_text.logprobs"]),
+        );
+    }
+    if let Some(top_logprobs) = options.top_logprobs {
+        body.insert("top_logprobs".to_string(), Value::from(top_logprobs));
+    }
 
     // Map reasoning options; Responses officially documents effort plus a few extras.
     if let Some(reasoning) = &request.options.reasoning {
@@ -127,19 +184,29 @@ fn extract_text_from_message(message: &Message) -> Option<String> {
     }
 }
 
-fn convert_input_message(message: &Message) -> Result<Value, LLMError> {
+/// Converts a message into input items: a `message` item for its content, followed by
+/// one item per tool call or tool result.
+fn convert_input_message(
+    message: &Message,
+    computer_calls: &HashSet<&str>,
+) -> Result<Vec<Value>, LLMError> {
     let mut obj = Map::new();
     obj.insert("type".to_string(), Value::String("message".to_string()));
     obj.insert("role".to_string(), Value::String(message.role.0.clone()));
 
     let mut content_items = Vec::new();
+    let mut tool_items = Vec::new();
     for part in &message.content {
         match part {
-            ContentPart::ToolCall(_) | ContentPart::ToolResult(_) => {
-                return Err(LLMError::Validation {
-                    message: "tool contents are not allowed in Responses input messages"
-                        .to_string(),
-                });
+            ContentPart::ToolCall(call) => tool_items.extend(convert_tool_call(call)?),
+            ContentPart::ToolResult(result) => {
+                tool_items.push(convert_tool_result(result, computer_calls)?)
+            }
+            // Responses caches prompt prefixes automatically; see `prompt_cache_key`.
+            ContentPart::CacheBreakpoint(_) => {}
+            // Replayed assistant text is model output, which Responses types as `output_text`.
+            ContentPart::Text(TextContent { text }) if message.role.0 == "assistant" => {
+                content_items.push(json!({ "type": "output_text", "text": text }));
             }
             _ => {
                 content_items.push(convert_content_part(part)?);
@@ -147,10 +214,113 @@ fn convert_input_message(message: &Message) -> Result<Value, LLMError> {
         }
     }
 
-    // Responses accepts strings or arrays for content; always use arrays to support multimodal input.
-    obj.insert("content".to_string(), Value::Array(content_items));
+    let mut items = Vec::new();
+    if !content_items.is_empty() || tool_items.is_empty() {
+        // Responses accepts strings or arrays for content; always use arrays to support multimodal input.
+        obj.insert("content".to_string(), Value::Array(content_items));
+        items.push(Value::Object(obj));
+    }
+    items.extend(tool_items);
+    Ok(items)
+}
+
+/// Replays a tool call as a `function_call` or `computer_call` item.
+///
+/// Reasoning items kept with a computer call are replayed right before it.
+fn convert_tool_call(call: &ToolCall) -> Result<Vec<Value>, LLMError> {
+    let call_id = call.id.clone().ok_or_else(|| LLMError::Validation {
+        message: "tool_call content requires id (mapped to call_id)".to_string(),
+    })?;
+    match call.kind {
+        // Computer call arguments keep the item `id`, `action` and `pending_safety_checks`.
+        ToolCallKind::ComputerUse => {
+            let mut obj = match &call.arguments {
+                Value::Object(arguments) => arguments.clone(),
+                _ => Map::new(),
+            };
+            let mut items = match obj.remove("reasoning") {
+                Some(Value::Array(reasoning)) => reasoning,
+                _ => Vec::new(),
+            };
+            obj.insert(
+                "type".to_string(),
+                Value::String("computer_call".to_string()),
+            );
+            obj.insert("call_id".to_string(), Value::String(call_id));
+            obj.insert("status".to_string(), Value::String("completed".to_string()));
+            items.push(Value::Object(obj));
+            Ok(items)
+        }
+        _ => Ok(vec![json!({
+            "type": "function_call",
+            "call_id": call_id,
+            "name": call.name,
+            "arguments": match &call.arguments {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            }
+        })]),
+    }
+}
+
+/// Sends a tool result as `function_call_output`, or as `computer_call_output` carrying
+/// the first image of `content` as the screenshot when it answers a computer call.
+fn convert_tool_result(
+    result: &ToolResult,
+    computer_calls: &HashSet<&str>,
+) -> Result<Value, LLMError> {
+    let call_id = result.call_id.clone().ok_or_else(|| LLMError::Validation {
+        message: "tool_result content requires call_id".to_string(),
+    })?;
+    if !computer_calls.contains(call_id.as_str()) {
+        if !result.content.is_empty() {
+            return Err(LLMError::UnsupportedFeature {
+                feature: "openai_responses_tool_result_content",
+            });
+        }
+        return Ok(json!({
+            "type": "function_call_output",
+            "call_id": call_id,
+            "output": match &result.output {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            }
+        }));
+    }
 
-    Ok(Value::Object(obj))
+    let screenshot = result
+        .content
+        .iter()
+        .find_map(|part| match part {
+            ContentPart::Image(image) => Some(image),
+            _ => None,
+        })
+        .ok_or_else(|| LLMError::Validation {
+            message: "computer_call_output requires a screenshot image in the tool result content"
+                .to_string(),
+        })?;
+    let mut output = json!({ "type": "computer_screenshot" });
+    match &screenshot.source {
+        ImageSource::Url { url } => output["image_url"] = Value::String(url.clone()),
+        ImageSource::Base64 { data, mime_type } => {
+            let mime = mime_type.as_deref().unwrap_or("image/png");
+            output["image_url"] = Value::String(format!("data:{mime};base64,{data}"));
+        }
+        ImageSource::FileId { file_id } => output["file_id"] = Value::String(file_id.clone()),
+    }
+    let mut obj = json!({
+        "type": "computer_call_output",
+        "call_id": call_id,
+        "output": output
+    });
+    if let Some(checks) = result
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.get("acknowledged_safety_checks"))
+    {
+        obj["acknowledged_safety_checks"] = checks.clone();
+    }
+    Ok(obj)
 }
 
 fn convert_content_part(part: &ContentPart) -> Result<Value, LLMError> {
@@ -213,9 +383,11 @@ fn convert_content_part(part: &ContentPart) -> Result<Value, LLMError> {
             "file_id": file_id
         })),
         ContentPart::Data { data } => Ok(data.clone()),
-        ContentPart::ToolCall(_) | ContentPart::ToolResult(_) => Err(LLMError::Validation {
-            message: "tool content must use dedicated structs".to_string(),
-        }),
+        ContentPart::ToolCall(_) | ContentPart::ToolResult(_) | ContentPart::CacheBreakpoint(_) => {
+            Err(LLMError::Validation {
+                message: "tool content must use dedicated structs".to_string(),
+            })
+        }
     }
 }
 
@@ -330,6 +502,7 @@ fn format_image_detail(detail: &ImageDetail) -> &'static str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::test_support::request_with_cache_breakpoints;
     use crate::types::{
         ChatOptions, ChatRequest, ContentPart, ImageContent, ImageDetail, ImageSource, Message,
         ReasoningOptions, Role,
@@ -378,6 +551,8 @@ mod tests {
             temperature: Some(0.3),
             top_p: Some(0.9),
             max_output_tokens: Some(256),
+            candidate_count: Some(1),
+            user: Some("user-42".to_string()),
             parallel_tool_calls: Some(true),
             reasoning: Some(ReasoningOptions {
                 effort: Some(ReasoningEffort::High),
@@ -461,6 +636,77 @@ mod tests {
         // text.format
         let text_cfg = body["text"].as_object().expect("text should be object");
         assert_eq!(text_cfg["format"]["type"], json!("text"));
+        assert_eq!(body["user"], json!("user-42"));
+        assert!(body.get("n").is_none());
+        assert!(body.get("include").is_none());
+
+        let mut logprobs_request = request.clone();
+        logprobs_request.options.top_logprobs = Some(3);
+        let body = build_openai_responses_body(&logprobs_request, "gpt-4.1", false)
+            .expect("logprobs are supported");
+        assert_eq!(body["include"], json!(["message.output_text.logprobs"]));
+        assert_eq!(body["top_logprobs"], json!(3));
+
+        let mut request = request;
+        request.options.stop_sequences = Some(vec!["END".to_string()]);
+        let err = build_openai_responses_body(&request, "gpt-4.1", false)
+            .expect_err("stop sequences are unsupported");
+        assert!(matches!(
+            err,
+            LLMError::UnsupportedFeature {
+                feature: "openai_responses_stop_sequences"
+            }
+        ));
+    }
+
+    /// Seeds, top-k and several candidates have no Responses field.
+    #[test]
+    fn reject_seed_top_k_and_candidates() {
+        let cases = [
+            (
+                ChatOptions {
+                    seed: Some(7),
+                    ..ChatOptions::default()
+                },
+                "openai_responses_seed",
+            ),
+            (
+                ChatOptions {
+                    top_k: Some(40),
+                    ..ChatOptions::default()
+                },
+                "openai_responses_top_k",
+            ),
+            (
+                ChatOptions {
+                    candidate_count: Some(2),
+                    ..ChatOptions::default()
+                },
+                "openai_responses_candidate_count",
+            ),
+        ];
+        for (options, expected) in cases {
+            let mut request = ChatRequest {
+                messages: vec![Message {
+                    role: Role::user(),
+                    name: None,
+                    content: vec![ContentPart::Text(TextContent {
+                        text: "hello".to_string(),
+                    })],
+                    metadata: None,
+                }],
+                options: ChatOptions::default(),
+                tools: Vec::new(),
+                tool_choice: None,
+                response_format: None,
+                metadata: None,
+            };
+            request.options = options;
+            match build_openai_responses_body(&request, "gpt-4.1", false) {
+                Err(LLMError::UnsupportedFeature { feature }) => assert_eq!(feature, expected),
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
     }
 
     /// Verifies that image input is mapped to `input_image` entries.
@@ -501,4 +747,134 @@ mod tests {
             })
         );
     }
+
+    /// Tool calls and results become standalone input items after the message content.
+    #[test]
+    fn convert_tool_calls_and_results_to_input_items() {
+        let call = |id: &str, name: &str, arguments: Value, kind| {
+            ContentPart::ToolCall(ToolCall {
+                id: Some(id.to_string()),
+                name: name.to_string(),
+                arguments,
+                kind,
+            })
+        };
+        let result = |id: &str, output: Value, content: Vec<ContentPart>| {
+            ContentPart::ToolResult(ToolResult {
+                call_id: Some(id.to_string()),
+                output,
+                is_error: false,
+                content,
+                metadata: None,
+            })
+        };
+        let screenshot = ImageContent {
+            source: ImageSource::Base64 {
+                data: "iVBORw0".to_string(),
+                mime_type: Some("image/png".to_string()),
+            },
+            detail: None,
+            metadata: None,
+        };
+        let request = ChatRequest {
+            messages: vec![
+                Message {
+                    role: Role::assistant(),
+                    name: None,
+                    content: vec![
+                        ContentPart::Text(TextContent {
+                            text: "Checking.".to_string(),
+                        }),
+                        call(
+                            "call_1",
+                            "lookup",
+                            json!({ "q": "x" }),
+                            ToolCallKind::Function,
+                        ),
+                        call(
+                            "call_2",
+                            "computer",
+                            json!({
+                                "id": "cu_1",
+                                "action": { "type": "screenshot" },
+                                "pending_safety_checks": [],
+                                "reasoning": [{ "type": "reasoning", "id": "rs_1", "summary": [] }]
+                            }),
+                            ToolCallKind::ComputerUse,
+                        ),
+                    ],
+                    metadata: None,
+                },
+                Message {
+                    role: Role("tool".to_string()),
+                    name: None,
+                    content: vec![
+                        result("call_1", json!({ "hits": 1 }), Vec::new()),
+                        result("call_2", Value::Null, vec![ContentPart::Image(screenshot)]),
+                    ],
+                    metadata: None,
+                },
+            ],
+            options: ChatOptions::default(),
+            tools: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            metadata: None,
+        };
+
+        let body = build_openai_responses_body(&request, "computer-use-preview", false)
+            .expect("body should be built");
+        let input = body["input"].as_array().expect("input should be array");
+        assert_eq!(input.len(), 6);
+        assert_eq!(input[0]["type"], json!("message"));
+        assert_eq!(
+            input[0]["content"],
+            json!([{ "type": "output_text", "text": "Checking." }])
+        );
+        assert_eq!(
+            input[1],
+            json!({ "type": "function_call", "call_id": "call_1", "name": "lookup", "arguments": "{\"q\":\"x\"}" })
+        );
+        assert_eq!(
+            input[2],
+            json!({ "type": "reasoning", "id": "rs_1", "summary": [] })
+        );
+        assert_eq!(
+            input[3],
+            json!({
+                "type": "computer_call",
+                "id": "cu_1",
+                "call_id": "call_2",
+                "action": { "type": "screenshot" },
+                "pending_safety_checks": [],
+                "status": "completed"
+            })
+        );
+        assert_eq!(
+            input[4],
+            json!({ "type": "function_call_output", "call_id": "call_1", "output": "{\"hits\":1}" })
+        );
+        assert_eq!(
+            input[5],
+            json!({
+                "type": "computer_call_output",
+                "call_id": "call_2",
+                "output": { "type": "computer_screenshot", "image_url": "data:image/png;base64,iVBORw0" }
+            })
+        );
+    }
+
+    /// Responses caches prefixes on its own; breakpoints and tool cache markers are dropped.
+    #[test]
+    fn cache_breakpoints_are_ignored() {
+        let (request, plain) = request_with_cache_breakpoints();
+
+        let body = build_openai_responses_body(&request, "gpt-4.1", false).expect("build");
+
+        assert_eq!(
+            body,
+            build_openai_responses_body(&plain, "gpt-4.1", false).expect("build")
+        );
+        assert!(!body.to_string().contains("cache"));
+    }
 }
@@ -1,9 +1,13 @@
-use serde_json::Value;
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
 
 use crate::error::LLMError;
 use crate::types::{
-    ChatResponse, ContentPart, FinishReason, Message, OutputItem, ProviderMetadata, Role,
-    TextContent, TokenUsage, ToolCall, ToolCallKind, ToolResult,
+    ChatResponse, Citation, CitationSource, ContentPart, FileSearchResult, FinishReason,
+    HostedToolAction, HostedToolCall, HostedToolOutput, HostedToolResult, Message, OutputItem,
+    ProviderMetadata, Role, TextContent, TextSpan, TokenLogprob, TokenUsage, ToolCall,
+    ToolCallKind, ToolResult, WebSearchResult,
 };
 
 use super::types::{OpenAiResponsesResponse, OpenAiResponsesUsage};
@@ -16,25 +20,52 @@ pub(crate) fn map_responses_response(
     let raw = serde_json::to_value(&resp).ok();
     let mut outputs = Vec::new();
 
-    for (index, item) in resp.output.iter().enumerate() {
+    // Responses always produces a single candidate; output position is not a candidate.
+    let index = 0;
+    // Reasoning items that precede a computer call; replaying the call requires them.
+    let mut reasoning = Vec::new();
+    for item in &resp.output {
         let kind = item
             .get("type")
             .and_then(|v| v.as_str())
             .unwrap_or_default();
+        if kind != "reasoning" && kind != "computer_call" {
+            reasoning.clear();
+        }
         match kind {
             "message" => {
-                let message = convert_message_output(item)?;
-                outputs.push(OutputItem::Message { message, index });
+                let (message, logprobs, citations) = convert_message_output(item)?;
+                outputs.push(OutputItem::Message {
+                    message,
+                    index,
+                    logprobs,
+                    citations,
+                });
+                outputs.extend(refusals(item).map(|text| OutputItem::Refusal { text, index }));
+            }
+            "web_search_call" | "file_search_call" | "code_interpreter_call" => {
+                let (call, result) = convert_hosted_item(item);
+                outputs.push(OutputItem::HostedToolCall { call, index });
+                if let Some(result) = result {
+                    outputs.push(OutputItem::HostedToolResult { result, index });
+                }
             }
             "function_call" => {
                 let call = convert_function_call_output(item)?;
                 outputs.push(OutputItem::ToolCall { call, index });
             }
+            "computer_call" => {
+                outputs.push(OutputItem::ToolCall {
+                    call: convert_computer_call(item, std::mem::take(&mut reasoning)),
+                    index,
+                });
+            }
             "function_call_output" => {
                 let result = convert_function_call_result(item)?;
                 outputs.push(OutputItem::ToolResult { result, index });
             }
             "reasoning" => {
+                reasoning.push(item.clone());
                 if let Some(text) = extract_reasoning_text(item) {
                     outputs.push(OutputItem::Reasoning { text, index });
                 } else {
@@ -54,12 +85,18 @@ pub(crate) fn map_responses_response(
     }
 
     let usage = resp.usage.map(convert_usage);
-    let finish_reason = convert_finish_reason(resp.status.as_deref(), &resp.error);
+    let finish_reason = convert_finish_reason(
+        resp.status.as_deref(),
+        &resp.error,
+        resp.incomplete_details.as_ref(),
+    );
 
     Ok(ChatResponse {
         outputs,
         usage,
         finish_reason,
+        candidate_finish_reasons: Vec::new(),
+        prompt_safety: None,
         model: Some(resp.model),
         provider: ProviderMetadata {
             provider: provider.to_string(),
@@ -70,7 +107,9 @@ pub(crate) fn map_responses_response(
     })
 }
 
-fn convert_message_output(item: &Value) -> Result<Message, LLMError> {
+fn convert_message_output(
+    item: &Value,
+) -> Result<(Message, Vec<TokenLogprob>, Vec<Citation>), LLMError> {
     let role = item
         .get("role")
         .and_then(|v| v.as_str())
@@ -83,6 +122,8 @@ fn convert_message_output(item: &Value) -> Result<Message, LLMError> {
         .map(|s| s.to_string());
 
     let mut content_parts = Vec::new();
+    let mut logprobs = Vec::new();
+    let mut citations = Vec::new();
     match item.get("content") {
         Some(Value::Array(parts)) => {
             for part in parts {
@@ -91,15 +132,24 @@ fn convert_message_output(item: &Value) -> Result<Message, LLMError> {
                     .and_then(|v| v.as_str())
                     .unwrap_or_default();
                 match part_type {
+                    // Refusals become [`OutputItem::Refusal`] next to the message.
+                    "refusal" => {}
                     "output_text" => {
+                        if let Some(Value::Array(annotations)) = part.get("annotations") {
+                            let part_index = content_parts.len();
+                            citations.extend(annotations.iter().filter_map(|annotation| {
+                                convert_annotation(annotation, part_index)
+                            }));
+                        }
                         if let Some(text) = part.get("text").and_then(|v| v.as_str()) {
                             content_parts.push(ContentPart::Text(TextContent {
                                 text: text.to_string(),
                             }));
                         }
+                        logprobs.extend(convert_logprobs(part.get("logprobs")));
                     }
                     _ => {
-                        // Forward refusal/file_citation/url_citation blocks as Data for callers.
+                        // Forward other blocks as Data for callers.
                         content_parts.push(ContentPart::Data { data: part.clone() });
                     }
                 }
@@ -113,14 +163,202 @@ fn convert_message_output(item: &Value) -> Result<Message, LLMError> {
         _ => {}
     }
 
-    Ok(Message {
-        role,
-        name,
-        content: content_parts,
-        metadata: None,
+    Ok((
+        Message {
+            role,
+            name,
+            content: content_parts,
+            metadata: None,
+        },
+        logprobs,
+        citations,
+    ))
+}
+
+/// Maps a hosted tool item to its call and, when the item carries them, its results.
+///
+/// `web_search_call` only lists sources when requested through
+/// `include: ["web_search_call.action.sources"]`, and `file_search_call` only lists
+/// results with `include: ["file_search_call.results"]`.
+pub(crate) fn convert_hosted_item(item: &Value) -> (HostedToolCall, Option<HostedToolResult>) {
+    let string =
+        |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    let id = string(item, "id");
+    let status = string(item, "status");
+    let kind = item.get("type").and_then(Value::as_str).unwrap_or_default();
+    let (action, output) = match kind {
+        "web_search_call" => {
+            let action = item.get("action").unwrap_or(&Value::Null);
+            let sources = action
+                .get("sources")
+                .and_then(Value::as_array)
+                .map(|sources| HostedToolOutput::WebSearch {
+                    results: sources
+                        .iter()
+                        .filter_map(|source| {
+                            Some(WebSearchResult {
+                                url: string(source, "url")?,
+                                title: string(source, "title"),
+                                page_age: None,
+                            })
+                        })
+                        .collect(),
+                });
+            (
+                HostedToolAction::WebSearch {
+                    query: string(action, "query"),
+                },
+                sources,
+            )
+        }
+        "file_search_call" => {
+            let results = item
+                .get("results")
+                .and_then(Value::as_array)
+                .map(|results| HostedToolOutput::FileSearch {
+                    results: results
+                        .iter()
+                        .filter_map(|result| {
+                            Some(FileSearchResult {
+                                file_id: string(result, "file_id")?,
+                                filename: string(result, "filename"),
+                                score: result.get("score").and_then(Value::as_f64),
+                                text: string(result, "text"),
+                            })
+                        })
+                        .collect(),
+                });
+            (
+                HostedToolAction::FileSearch {
+                    queries: item
+                        .get("queries")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|query| query.as_str().map(str::to_string))
+                        .collect(),
+                },
+                results,
+            )
+        }
+        _ => {
+            // `code_interpreter_call`: log outputs are joined; image outputs stay raw.
+            let outputs = item.get("outputs").and_then(Value::as_array);
+            let output = outputs.map(|outputs| {
+                let logs: Vec<&str> = outputs
+                    .iter()
+                    .filter_map(|output| output.get("logs").and_then(Value::as_str))
+                    .collect();
+                if logs.len() == outputs.len() {
+                    HostedToolOutput::CodeExecution {
+                        outcome: status.clone(),
+                        output: logs.join("\n"),
+                    }
+                } else {
+                    HostedToolOutput::Other {
+                        name: "code_interpreter".to_string(),
+                        content: Value::Array(outputs.clone()),
+                    }
+                }
+            });
+            (
+                HostedToolAction::CodeExecution {
+                    language: Some("python".to_string()),
+                    code: string(item, "code").unwrap_or_default(),
+                },
+                output,
+            )
+        }
+    };
+    let result = output.map(|output| HostedToolResult {
+        call_id: id.clone(),
+        output,
+        error: (status.as_deref() == Some("failed")).then(|| "failed".to_string()),
+    });
+    (HostedToolCall { id, action, status }, result)
+}
+
+/// Texts of the `refusal` content parts of a message item.
+fn refusals(item: &Value) -> impl Iterator<Item = String> + '_ {
+    item.get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|part| part.get("type").and_then(Value::as_str) == Some("refusal"))
+        .filter_map(|part| part.get("refusal").and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+/// Converts an `output_text` annotation; `file_path` annotations are not citations.
+pub(crate) fn convert_annotation(annotation: &Value, part: usize) -> Option<Citation> {
+    let string = |key: &str| {
+        annotation
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let offset = |key: &str| {
+        annotation
+            .get(key)
+            .and_then(Value::as_u64)
+            .map(|v| v as usize)
+    };
+    let range = offset("start_index")
+        .zip(offset("end_index"))
+        .map(|(start, end)| TextSpan { start, end });
+    // File citations mark a single position rather than a range.
+    let position = offset("index").map(|at| TextSpan { start: at, end: at });
+    let (source, span, metadata) = match annotation.get("type")?.as_str()? {
+        "url_citation" => (
+            CitationSource::Url {
+                url: string("url")?,
+                title: string("title"),
+            },
+            range,
+            None,
+        ),
+        "file_citation" => (
+            CitationSource::File {
+                file_id: string("file_id")?,
+                filename: string("filename"),
+            },
+            position,
+            None,
+        ),
+        "container_file_citation" => (
+            CitationSource::File {
+                file_id: string("file_id")?,
+                filename: string("filename"),
+            },
+            range,
+            annotation
+                .get("container_id")
+                .map(|id| HashMap::from([("container_id".to_string(), id.clone())])),
+        ),
+        _ => return None,
+    };
+    Some(Citation {
+        part,
+        span,
+        source,
+        cited_text: None,
+        metadata,
     })
 }
 
+/// Parses an `output_text.logprobs` array; malformed entries are skipped.
+pub(crate) fn convert_logprobs(value: Option<&Value>) -> Vec<TokenLogprob> {
+    value
+        .and_then(Value::as_array)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| serde_json::from_value(entry.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn convert_function_call_output(item: &Value) -> Result<ToolCall, LLMError> {
     let call_id = item
         .get("call_id")
@@ -147,6 +385,34 @@ fn convert_function_call_output(item: &Value) -> Result<ToolCall, LLMError> {
     })
 }
 
+/// Maps a `computer_call` item to a [`ToolCallKind::ComputerUse`] call named `computer`.
+///
+/// The arguments keep the item `id`, `action` and `pending_safety_checks` so the call can
+/// be decoded with [`crate::computer::ComputerCall::from_tool_call`] and replayed as input.
+/// The `reasoning` items the model emitted right before the call are kept under
+/// `reasoning`, because Responses rejects a replayed `computer_call` without them.
+fn convert_computer_call(item: &Value, reasoning: Vec<Value>) -> ToolCall {
+    let mut arguments: Map<String, Value> = item
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| matches!(key.as_str(), "id" | "action" | "pending_safety_checks"))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    if !reasoning.is_empty() {
+        arguments.insert("reasoning".to_string(), Value::Array(reasoning));
+    }
+    ToolCall {
+        id: item
+            .get("call_id")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        name: "computer".to_string(),
+        arguments: Value::Object(arguments),
+        kind: ToolCallKind::ComputerUse,
+    }
+}
+
 fn convert_function_call_result(item: &Value) -> Result<ToolResult, LLMError> {
     let call_id = item
         .get("call_id")
@@ -163,6 +429,7 @@ fn convert_function_call_result(item: &Value) -> Result<ToolResult, LLMError> {
         call_id,
         output,
         is_error: false,
+        content: Vec::new(),
         metadata: None,
     })
 }
@@ -186,7 +453,11 @@ fn extract_reasoning_text(item: &Value) -> Option<String> {
     }
 }
 
-fn convert_finish_reason(status: Option<&str>, error: &Option<Value>) -> Option<FinishReason> {
+fn convert_finish_reason(
+    status: Option<&str>,
+    error: &Option<Value>,
+    incomplete_details: Option<&Value>,
+) -> Option<FinishReason> {
     if let Some(err) = error {
         if !err.is_null() {
             return Some(FinishReason::Error);
@@ -194,6 +465,16 @@ fn convert_finish_reason(status: Option<&str>, error: &Option<Value>) -> Option<
     }
     match status {
         Some("completed") => Some(FinishReason::Stop),
+        Some("incomplete") => {
+            match incomplete_details
+                .and_then(|details| details.get("reason"))
+                .and_then(Value::as_str)
+            {
+                Some("content_filter") => Some(FinishReason::ContentFilter),
+                Some("max_output_tokens") => Some(FinishReason::Length),
+                _ => Some(FinishReason::Other("incomplete".to_string())),
+            }
+        }
         Some(other) => Some(FinishReason::Other(other.to_string())),
         None => None,
     }
@@ -205,12 +486,19 @@ pub(crate) fn convert_usage(usage: OpenAiResponsesUsage) -> TokenUsage {
         .as_ref()
         .and_then(|details| details.get("reasoning_tokens"))
         .and_then(|v| v.as_u64());
+    let cached_input_tokens = usage
+        .input_tokens_details
+        .as_ref()
+        .and_then(|details| details.get("cached_tokens"))
+        .and_then(|v| v.as_u64());
 
     TokenUsage {
         prompt_tokens: usage.input_tokens,
         completion_tokens: usage.output_tokens,
         reasoning_tokens,
         total_tokens: usage.total_tokens,
+        cached_input_tokens,
+        cache_creation_tokens: None,
         details: None,
     }
 }
@@ -241,7 +529,15 @@ mod tests {
                     {
                         "type": "output_text",
                         "text": "hello responses",
-                        "annotations": []
+                        "annotations": [],
+                        "logprobs": [
+                            {
+                                "token": "hello",
+                                "logprob": -0.01,
+                                "bytes": [104, 101, 108, 108, 111],
+                                "top_logprobs": [{"token": "hi", "logprob": -4.6, "bytes": null}]
+                            }
+                        ]
                     }
                 ]
             })],
@@ -277,8 +573,17 @@ mod tests {
         // Expect a single Message output.
         assert_eq!(mapped.outputs.len(), 1);
         match &mapped.outputs[0] {
-            OutputItem::Message { message, index } => {
+            OutputItem::Message {
+                message,
+                index,
+                logprobs,
+                ..
+            } => {
                 assert_eq!(*index, 0);
+                assert_eq!(logprobs.len(), 1);
+                assert_eq!(logprobs[0].token, "hello");
+                assert_eq!(logprobs[0].bytes.as_deref(), Some("hello".as_bytes()));
+                assert_eq!(logprobs[0].top_logprobs[0].token, "hi");
                 assert_eq!(message.role.0, "assistant");
                 assert_eq!(message.content.len(), 1);
                 match &message.content[0] {
@@ -360,7 +665,7 @@ mod tests {
                 }
                 OutputItem::ToolResult { result, index } => {
                     saw_result = true;
-                    assert_eq!(*index, 1);
+                    assert_eq!(*index, 0);
                     assert_eq!(result.call_id.as_deref(), Some("call_1"));
                     assert_eq!(result.output["temperature"], json!(25));
                 }
@@ -369,4 +674,174 @@ mod tests {
         }
         assert!(saw_call && saw_result);
     }
+
+    #[test]
+    fn incomplete_responses_report_their_reason() {
+        let content_filter = json!({ "reason": "content_filter" });
+        assert!(matches!(
+            convert_finish_reason(Some("incomplete"), &None, Some(&content_filter)),
+            Some(FinishReason::ContentFilter)
+        ));
+        let truncated = json!({ "reason": "max_output_tokens" });
+        assert!(matches!(
+            convert_finish_reason(Some("incomplete"), &None, Some(&truncated)),
+            Some(FinishReason::Length)
+        ));
+        assert!(matches!(
+            convert_finish_reason(Some("incomplete"), &None, None),
+            Some(FinishReason::Other(reason)) if reason == "incomplete"
+        ));
+    }
+
+    #[test]
+    fn output_text_annotations_become_citations() {
+        let item = json!({
+            "type": "message",
+            "role": "assistant",
+            "content": [{
+                "type": "output_text",
+                "text": "See the docs and the spec.",
+                "annotations": [
+                    { "type": "url_citation", "start_index": 4, "end_index": 12, "url": "https://example.com/docs", "title": "Docs" },
+                    { "type": "file_citation", "index": 25, "file_id": "file-1", "filename": "spec.pdf" },
+                    { "type": "file_path", "index": 0, "file_id": "file-2" }
+                ]
+            }]
+        });
+
+        let (message, _, citations) = convert_message_output(&item).expect("convert");
+
+        let ContentPart::Text(TextContent { text }) = &message.content[0] else {
+            panic!("unexpected part: {:?}", message.content[0]);
+        };
+        assert_eq!(citations.len(), 2);
+        assert_eq!(
+            citations[0].span.and_then(|span| span.slice(text)),
+            Some("the docs")
+        );
+        assert_eq!(
+            citations[1].source,
+            CitationSource::File {
+                file_id: "file-1".to_string(),
+                filename: Some("spec.pdf".to_string()),
+            }
+        );
+        assert_eq!(citations[1].span, Some(TextSpan { start: 25, end: 25 }));
+    }
+
+    #[test]
+    fn refusal_parts_become_refusal_outputs() {
+        let item = json!({
+            "type": "message",
+            "role": "assistant",
+            "content": [{ "type": "refusal", "refusal": "I can't help with that." }]
+        });
+        let (message, _, _) = convert_message_output(&item).expect("convert");
+        assert!(message.content.is_empty());
+        assert_eq!(
+            refusals(&item).collect::<Vec<_>>(),
+            ["I can't help with that."]
+        );
+    }
+
+    #[test]
+    fn hosted_tool_items_become_typed_calls_and_results() {
+        let web_search = json!({
+            "type": "web_search_call",
+            "id": "ws_1",
+            "status": "completed",
+            "action": {
+                "type": "search",
+                "query": "rust 2024 edition",
+                "sources": [{ "type": "url", "url": "https://doc.rust-lang.org/edition-guide" }]
+            }
+        });
+        let (call, result) = convert_hosted_item(&web_search);
+        assert_eq!(call.id.as_deref(), Some("ws_1"));
+        assert_eq!(call.status.as_deref(), Some("completed"));
+        assert_eq!(
+            call.action,
+            HostedToolAction::WebSearch {
+                query: Some("rust 2024 edition".to_string())
+            }
+        );
+        let result = result.expect("sources");
+        assert_eq!(result.call_id.as_deref(), Some("ws_1"));
+        assert!(matches!(
+            &result.output,
+            HostedToolOutput::WebSearch { results }
+                if results[0].url == "https://doc.rust-lang.org/edition-guide"
+        ));
+
+        let file_search = json!({
+            "type": "file_search_call",
+            "id": "fs_1",
+            "status": "completed",
+            "queries": ["refund policy"],
+            "results": [{ "file_id": "file-1", "filename": "policy.pdf", "score": 0.92, "text": "Refunds within 30 days." }]
+        });
+        let (call, result) = convert_hosted_item(&file_search);
+        assert_eq!(
+            call.action,
+            HostedToolAction::FileSearch {
+                queries: vec!["refund policy".to_string()]
+            }
+        );
+        assert_eq!(
+            result.expect("results").output,
+            HostedToolOutput::FileSearch {
+                results: vec![FileSearchResult {
+                    file_id: "file-1".to_string(),
+                    filename: Some("policy.pdf".to_string()),
+                    score: Some(0.92),
+                    text: Some("Refunds within 30 days.".to_string()),
+                }]
+            }
+        );
+
+        let code = json!({
+            "type": "code_interpreter_call",
+            "id": "ci_1",
+            "status": "failed",
+            "code": "1 / 0",
+            "outputs": [{ "type": "logs", "logs": "ZeroDivisionError" }]
+        });
+        let (call, result) = convert_hosted_item(&code);
+        assert!(matches!(
+            &call.action,
+            HostedToolAction::CodeExecution { code, .. } if code == "1 / 0"
+        ));
+        let result = result.expect("outputs");
+        assert_eq!(result.error.as_deref(), Some("failed"));
+        assert!(matches!(
+            &result.output,
+            HostedToolOutput::CodeExecution { output, .. } if output == "ZeroDivisionError"
+        ));
+    }
+
+    #[test]
+    fn computer_call_items_become_computer_use_tool_calls() {
+        let item = json!({
+            "type": "computer_call",
+            "id": "cu_1",
+            "call_id": "call_1",
+            "status": "completed",
+            "action": { "type": "click", "button": "left", "x": 10, "y": 20 },
+            "pending_safety_checks": []
+        });
+
+        let call = convert_computer_call(&item, Vec::new());
+
+        assert_eq!(call.id.as_deref(), Some("call_1"));
+        assert_eq!(call.name, "computer");
+        assert_eq!(call.kind, ToolCallKind::ComputerUse);
+        assert_eq!(
+            call.arguments,
+            json!({
+                "id": "cu_1",
+                "action": { "type": "click", "button": "left", "x": 10, "y": 20 },
+                "pending_safety_checks": []
+            })
+        );
+    }
 }
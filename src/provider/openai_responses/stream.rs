@@ -10,7 +10,7 @@ use crate::provider::ChatStream;
 use crate::stream::{StreamDecoder, StreamEvent};
 use crate::types::{ChatChunk, ChatEvent, ContentDelta, MessageDelta, ProviderMetadata, Role};
 
-use super::response::convert_usage;
+use super::response::{convert_annotation, convert_hosted_item, convert_logprobs, convert_usage};
 use super::types::OpenAiResponsesStreamEvent;
 
 pub(crate) fn create_stream(
@@ -89,18 +89,24 @@ fn convert_stream_event(
     endpoint: &str,
 ) -> Result<Option<ChatChunk>, LLMError> {
     match event.event_type.as_str() {
-        "response.output_text.delta" => {
-            let index = event.output_index.unwrap_or(0);
+        "response.output_text.delta" | "response.refusal.delta" => {
             let delta = event.delta.as_deref().unwrap_or("").to_string();
             if delta.is_empty() {
                 return Ok(None);
             }
+            let content = if event.event_type == "response.refusal.delta" {
+                ContentDelta::Refusal { text: delta }
+            } else {
+                ContentDelta::Text { text: delta }
+            };
             let message_delta = MessageDelta {
-                index,
+                // `output_index` locates the output item; Responses has a single candidate.
+                index: 0,
                 // Streaming text events originate from the assistant role.
                 role: Some(Role::assistant()),
-                content: vec![ContentDelta::Text { text: delta }],
+                content: vec![content],
                 finish_reason: None,
+                logprobs: convert_logprobs(event.logprobs.as_ref()),
             };
             let raw = serde_json::to_value(event).ok();
             Ok(Some(ChatChunk {
@@ -115,6 +121,59 @@ fn convert_stream_event(
                 },
             }))
         }
+        "response.output_text.annotation.added" => {
+            let Some(citation) = event.annotation.as_ref().and_then(|annotation| {
+                convert_annotation(annotation, event.content_index.unwrap_or(0))
+            }) else {
+                return Ok(None);
+            };
+            let message_delta = MessageDelta {
+                index: 0,
+                role: Some(Role::assistant()),
+                content: vec![ContentDelta::Citation { citation }],
+                finish_reason: None,
+                logprobs: Vec::new(),
+            };
+            let raw = serde_json::to_value(event).ok();
+            Ok(Some(ChatChunk {
+                events: vec![ChatEvent::MessageDelta(message_delta)],
+                usage: None,
+                is_terminal: false,
+                provider: ProviderMetadata {
+                    provider: provider.to_string(),
+                    request_id: None,
+                    endpoint: Some(endpoint.to_string()),
+                    raw,
+                },
+            }))
+        }
+        "response.output_item.done" => {
+            let Some(item) = event.item.as_ref().filter(|item| {
+                matches!(
+                    item.get("type").and_then(|kind| kind.as_str()),
+                    Some("web_search_call" | "file_search_call" | "code_interpreter_call")
+                )
+            }) else {
+                return Ok(None);
+            };
+            let (call, result) = convert_hosted_item(item);
+            let mut events = vec![ChatEvent::HostedToolCall { index: 0, call }];
+            if let Some(result) = result {
+                events.push(ChatEvent::HostedToolResult { index: 0, result });
+            }
+            let raw = serde_json::to_value(event).ok();
+            Ok(Some(ChatChunk {
+                events,
+                usage: None,
+                is_terminal: false,
+                provider: ProviderMetadata {
+                    provider: provider.to_string(),
+                    request_id: None,
+                    endpoint: Some(endpoint.to_string()),
+                    raw,
+                },
+            }))
+        }
         "response.completed" => {
             let response = event.response.ok_or_else(|| LLMError::Provider {
                 provider,
@@ -134,7 +193,8 @@ fn convert_stream_event(
                 },
             }))
         }
-        // Ignore other events (response.created, response.in_progress, output_item.*, etc.).
+        // Ignore other events (response.created, response.in_progress, the
+        // `response.web_search_call.*` progress events, etc.).
         _ => Ok(None),
     }
 }
@@ -156,6 +216,10 @@ mod tests {
             item_id: Some("msg_1".to_string()),
             item: None,
             part: None,
+            annotation: None,
+            logprobs: Some(serde_json::json!([
+                {"token": "hello", "logprob": -0.5, "top_logprobs": []}
+            ])),
         };
 
         let chunk = convert_stream_event(event, "openai_responses", "endpoint")
@@ -171,6 +235,8 @@ mod tests {
                 assert_eq!(delta.index, 0);
                 assert_eq!(delta.role.as_ref().map(|r| r.0.as_str()), Some("assistant"));
                 assert_eq!(delta.content.len(), 1);
+                assert_eq!(delta.logprobs.len(), 1);
+                assert_eq!(delta.logprobs[0].logprob, -0.5);
                 match &delta.content[0] {
                     ContentDelta::Text { text } => assert_eq!(text, "hello"),
                     other => panic!("unexpected content delta: {other:?}"),
@@ -221,6 +287,8 @@ mod tests {
             item_id: None,
             item: None,
             part: None,
+            logprobs: None,
+            annotation: None,
         };
 
         let chunk = convert_stream_event(event, "openai_responses", "endpoint")
@@ -233,4 +301,38 @@ mod tests {
         assert_eq!(usage.completion_tokens, Some(5));
         assert_eq!(usage.total_tokens, Some(15));
     }
+
+    #[test]
+    fn convert_hosted_output_item_done_event() {
+        let event = OpenAiResponsesStreamEvent {
+            event_type: "response.output_item.done".to_string(),
+            response: None,
+            output_index: Some(0),
+            content_index: None,
+            delta: None,
+            item_id: None,
+            item: Some(serde_json::json!({
+                "type": "web_search_call",
+                "id": "ws_1",
+                "status": "completed",
+                "action": { "type": "search", "query": "weather" }
+            })),
+            part: None,
+            logprobs: None,
+            annotation: None,
+        };
+
+        let chunk = convert_stream_event(event, "openai_responses", "endpoint")
+            .expect("convert should succeed")
+            .expect("chunk should be Some");
+        assert_eq!(chunk.events.len(), 1);
+        match &chunk.events[0] {
+            ChatEvent::HostedToolCall { index, call } => {
+                assert_eq!(*index, 0);
+                assert_eq!(call.id.as_deref(), Some("ws_1"));
+                assert_eq!(call.action.tool_name(), "web_search");
+            }
+            other => panic!("unexpected chat event: {other:?}"),
+        }
+    }
 }
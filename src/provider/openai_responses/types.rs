@@ -83,4 +83,9 @@ pub(crate) struct OpenAiResponsesStreamEvent {
     pub(crate) item: Option<Value>,
     #[serde(default)]
     pub(crate) part: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) logprobs: Option<Value>,
+    /// Payload of `response.output_text.annotation.added`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) annotation: Option<Value>,
 }
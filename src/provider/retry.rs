@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -102,7 +101,17 @@ where
 /// Providers occasionally instruct clients to wait before re-sending requests. When the
 /// header is numeric this helper parses it into a [`Duration`]. HTTP-date values are
 /// currently ignored because vendors primarily use the numeric form.
-pub(crate) fn retry_after_from_headers(headers: &HashMap<String, String>) -> Option<Duration> {
+#[cfg(any(
+    feature = "openai",
+    feature = "anthropic",
+    feature = "gemini",
+    feature = "bedrock",
+    feature = "cohere",
+    feature = "ollama"
+))]
+pub(crate) fn retry_after_from_headers(
+    headers: &std::collections::HashMap<String, String>,
+) -> Option<Duration> {
     headers
         .iter()
         .find(|(name, _)| name.eq_ignore_ascii_case("retry-after"))
@@ -183,6 +192,8 @@ mod tests {
                         outputs: Vec::new(),
                         usage: None,
                         finish_reason: None,
+                        candidate_finish_reasons: Vec::new(),
+                        prompt_safety: None,
                         model: Some("test".to_string()),
                         provider: ProviderMetadata {
                             provider: "test".to_string(),
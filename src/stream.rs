@@ -7,6 +7,9 @@ use futures_core::Stream;
 use crate::error::LLMError;
 use crate::http::HttpBodyStream;
 
+pub mod eventstream;
+pub mod ndjson;
+
 /// Standardized SSE event yielded by [`StreamDecoder`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StreamEvent {
@@ -116,6 +119,20 @@ impl Stream for StreamDecoder {
         }
 
         loop {
+            // A single chunk may carry several events; finish the buffered lines first.
+            while let Some(line) = Self::drain_line(&mut this.buffer) {
+                if line.is_empty() {
+                    if let Err(err) = this.flush_event() {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    if let Some(event) = this.pending.pop_front() {
+                        return Poll::Ready(Some(event));
+                    }
+                } else {
+                    this.handle_line(line);
+                }
+            }
+
             if this.stream_closed {
                 if !this.buffer.is_empty() {
                     let line = this.buffer.drain(..).collect::<Vec<u8>>();
@@ -134,21 +151,6 @@ impl Stream for StreamDecoder {
                 Poll::Ready(Some(chunk_result)) => match chunk_result {
                     Ok(bytes) => {
                         this.buffer.extend_from_slice(&bytes);
-                        while let Some(line) = Self::drain_line(&mut this.buffer) {
-                            if line.is_empty() {
-                                if let Err(err) = this.flush_event() {
-                                    return Poll::Ready(Some(Err(err)));
-                                }
-                                if let Some(event) = this.pending.pop_front() {
-                                    return Poll::Ready(Some(event));
-                                }
-                            } else {
-                                this.handle_line(line);
-                            }
-                        }
-                        if let Some(event) = this.pending.pop_front() {
-                            return Poll::Ready(Some(event));
-                        }
                     }
                     Err(err) => {
                         let mapped = match err {
@@ -210,6 +212,24 @@ mod tests {
         assert!(decoder.next().await.is_none());
     }
 
+    #[tokio::test]
+    async fn decoder_emits_every_event_in_a_single_chunk() {
+        let chunks = vec![Ok(b"data: one\n\ndata: two\n\ndata: [DONE]\n\n".to_vec())];
+        let mut decoder = StreamDecoder::new(build_body(chunks), "test_provider");
+        let events: Vec<StreamEvent> = (&mut decoder)
+            .map(|event| event.expect("ok"))
+            .collect()
+            .await;
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent::Data("one".to_string()),
+                StreamEvent::Data("two".to_string()),
+                StreamEvent::Done,
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn decoder_reports_utf8_errors() {
         let chunks = vec![Ok(b"data: \xff\n\n".to_vec())];
@@ -0,0 +1,411 @@
+//! Decoder for the `application/vnd.amazon.eventstream` binary framing.
+//!
+//! AWS streaming APIs such as Bedrock `ConverseStream` do not speak SSE. Each message is a
+//! length-prefixed frame: a 12-byte prelude (total length, headers length, prelude CRC),
+//! typed headers, the payload, and a CRC32 over everything before it.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::error::LLMError;
+use crate::http::HttpBodyStream;
+
+const PRELUDE_LEN: usize = 12;
+const CRC_LEN: usize = 4;
+/// Upper bound AWS places on a single message; larger lengths indicate corrupt framing.
+const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+
+/// Typed header value carried by an eventstream message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderValue {
+    Bool(bool),
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Bytes(Vec<u8>),
+    String(String),
+    /// Milliseconds since the Unix epoch.
+    Timestamp(i64),
+    Uuid([u8; 16]),
+}
+
+/// One decoded eventstream frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventStreamMessage {
+    /// Headers in wire order, e.g. `:event-type` or `:message-type`.
+    pub headers: Vec<(String, HeaderValue)>,
+    /// Raw payload, usually a JSON document.
+    pub payload: Vec<u8>,
+}
+
+impl EventStreamMessage {
+    /// Returns the value of a string header such as `:event-type`.
+    pub fn header_str(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find_map(|(key, value)| match value {
+            HeaderValue::String(text) if key == name => Some(text.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// Splits an HTTP body into [`EventStreamMessage`] frames, verifying both CRCs.
+pub struct EventStreamDecoder {
+    body: HttpBodyStream,
+    buffer: Vec<u8>,
+    pending: VecDeque<EventStreamMessage>,
+    provider: &'static str,
+    finished: bool,
+}
+
+impl EventStreamDecoder {
+    /// Wraps a raw HTTP body stream.
+    pub fn new(body: HttpBodyStream, provider: &'static str) -> Self {
+        Self {
+            body,
+            buffer: Vec::new(),
+            pending: VecDeque::new(),
+            provider,
+            finished: false,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> LLMError {
+        LLMError::Provider {
+            provider: self.provider,
+            message: format!("invalid eventstream frame: {}", message.into()),
+        }
+    }
+
+    /// Moves every complete frame out of the buffer.
+    fn drain_frames(&mut self) -> Result<(), LLMError> {
+        while self.buffer.len() >= PRELUDE_LEN {
+            let total_len = read_u32(&self.buffer[0..4]) as usize;
+            if !(PRELUDE_LEN + CRC_LEN..=MAX_MESSAGE_LEN).contains(&total_len) {
+                return Err(self.error(format!("message length {total_len} out of range")));
+            }
+            if self.buffer.len() < total_len {
+                break;
+            }
+            let frame: Vec<u8> = self.buffer.drain(..total_len).collect();
+            let message = decode_message(&frame).map_err(|reason| self.error(reason))?;
+            self.pending.push_back(message);
+        }
+        Ok(())
+    }
+}
+
+impl Stream for EventStreamDecoder {
+    type Item = Result<EventStreamMessage, LLMError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(message) = this.pending.pop_front() {
+                return Poll::Ready(Some(Ok(message)));
+            }
+            if this.finished {
+                return Poll::Ready(None);
+            }
+            match this.body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(bytes))) => {
+                    this.buffer.extend_from_slice(&bytes);
+                    if let Err(err) = this.drain_frames() {
+                        this.finished = true;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    let mapped = match err {
+                        LLMError::Transport { message } => LLMError::StreamClosed {
+                            message: format!("stream error from {}: {message}", this.provider),
+                        },
+                        other => other,
+                    };
+                    return Poll::Ready(Some(Err(mapped)));
+                }
+                Poll::Ready(None) => {
+                    this.finished = true;
+                    if !this.buffer.is_empty() {
+                        let leftover = this.buffer.len();
+                        this.buffer.clear();
+                        return Poll::Ready(Some(Err(LLMError::StreamClosed {
+                            message: format!(
+                                "stream from {} ended inside an eventstream frame ({leftover} bytes left)",
+                                this.provider
+                            ),
+                        })));
+                    }
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Decodes one complete frame whose length has already been checked.
+fn decode_message(frame: &[u8]) -> Result<EventStreamMessage, String> {
+    let total_len = frame.len();
+    let headers_len = read_u32(&frame[4..8]) as usize;
+    let prelude_crc = read_u32(&frame[8..12]);
+    if crc32(&frame[..8]) != prelude_crc {
+        return Err("prelude checksum mismatch".to_string());
+    }
+    let message_crc = read_u32(&frame[total_len - CRC_LEN..]);
+    if crc32(&frame[..total_len - CRC_LEN]) != message_crc {
+        return Err("message checksum mismatch".to_string());
+    }
+    let headers_end = PRELUDE_LEN + headers_len;
+    if headers_end > total_len - CRC_LEN {
+        return Err(format!("headers length {headers_len} exceeds message"));
+    }
+    let headers = decode_headers(&frame[PRELUDE_LEN..headers_end])?;
+    Ok(EventStreamMessage {
+        headers,
+        payload: frame[headers_end..total_len - CRC_LEN].to_vec(),
+    })
+}
+
+fn decode_headers(mut bytes: &[u8]) -> Result<Vec<(String, HeaderValue)>, String> {
+    fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+        if bytes.len() < len {
+            return Err("truncated header".to_string());
+        }
+        let (head, tail) = bytes.split_at(len);
+        *bytes = tail;
+        Ok(head)
+    }
+
+    let mut headers = Vec::new();
+    while !bytes.is_empty() {
+        let name_len = take(&mut bytes, 1)?[0] as usize;
+        let name = String::from_utf8(take(&mut bytes, name_len)?.to_vec())
+            .map_err(|_| "header name is not UTF-8".to_string())?;
+        let value = match take(&mut bytes, 1)?[0] {
+            0 => HeaderValue::Bool(true),
+            1 => HeaderValue::Bool(false),
+            2 => HeaderValue::Byte(take(&mut bytes, 1)?[0] as i8),
+            3 => HeaderValue::Short(i16::from_be_bytes(take(&mut bytes, 2)?.try_into().unwrap())),
+            4 => HeaderValue::Int(i32::from_be_bytes(take(&mut bytes, 4)?.try_into().unwrap())),
+            5 => HeaderValue::Long(i64::from_be_bytes(take(&mut bytes, 8)?.try_into().unwrap())),
+            kind @ (6 | 7) => {
+                let len = u16::from_be_bytes(take(&mut bytes, 2)?.try_into().unwrap()) as usize;
+                let value = take(&mut bytes, len)?.to_vec();
+                if kind == 6 {
+                    HeaderValue::Bytes(value)
+                } else {
+                    HeaderValue::String(
+                        String::from_utf8(value)
+                            .map_err(|_| format!("header {name} is not UTF-8"))?,
+                    )
+                }
+            }
+            8 => {
+                HeaderValue::Timestamp(i64::from_be_bytes(take(&mut bytes, 8)?.try_into().unwrap()))
+            }
+            9 => HeaderValue::Uuid(take(&mut bytes, 16)?.try_into().unwrap()),
+            other => return Err(format!("unknown header type {other} for {name}")),
+        };
+        headers.push((name, value));
+    }
+    Ok(headers)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// CRC-32 (IEEE 802.3) lookup table, as used by eventstream checksums.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc = CRC32_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// Encodes a frame with string headers, for tests that feed decoders synthetic streams.
+#[cfg(test)]
+pub(crate) fn encode_message(headers: &[(&str, &str)], payload: &[u8]) -> Vec<u8> {
+    let mut header_bytes = Vec::new();
+    for (name, value) in headers {
+        header_bytes.push(name.len() as u8);
+        header_bytes.extend_from_slice(name.as_bytes());
+        header_bytes.push(7);
+        header_bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        header_bytes.extend_from_slice(value.as_bytes());
+    }
+    let total_len = PRELUDE_LEN + header_bytes.len() + payload.len() + CRC_LEN;
+    let mut frame = Vec::with_capacity(total_len);
+    frame.extend_from_slice(&(total_len as u32).to_be_bytes());
+    frame.extend_from_slice(&(header_bytes.len() as u32).to_be_bytes());
+    let prelude_crc = crc32(&frame);
+    frame.extend_from_slice(&prelude_crc.to_be_bytes());
+    frame.extend_from_slice(&header_bytes);
+    frame.extend_from_slice(payload);
+    let message_crc = crc32(&frame);
+    frame.extend_from_slice(&message_crc.to_be_bytes());
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use futures_util::stream;
+
+    use super::*;
+
+    fn body(chunks: Vec<Vec<u8>>) -> HttpBodyStream {
+        Box::pin(stream::iter(chunks.into_iter().map(Ok)))
+    }
+
+    fn hex(text: &str) -> Vec<u8> {
+        let digits: Vec<u8> = text.bytes().filter(u8::is_ascii_hexdigit).collect();
+        digits
+            .chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+            .collect()
+    }
+
+    /// `empty_message` from the AWS eventstream test suite.
+    const EMPTY_MESSAGE: &str = "00000010 00000000 05c248eb 7d98c8ff";
+
+    /// Bedrock `contentBlockDelta` frame with `:event-type`, `:content-type` and
+    /// `:message-type` headers and payload `{"contentBlockIndex":0,"delta":{"text":"Hi"}}`.
+    const CONTENT_BLOCK_DELTA: &str = "
+        00000094 00000057 b4ab9aee
+        0b3a 6576656e742d74797065 07 0011 636f6e74656e74426c6f636b44656c7461
+        0d3a 636f6e74656e742d74797065 07 0010 6170706c69636174696f6e2f6a736f6e
+        0d3a 6d6573736167652d74797065 07 0005 6576656e74
+        7b22636f6e74656e74426c6f636b496e646578223a302c2264656c7461223a7b2274657874223a224869227d7d
+        1f944657";
+
+    #[test]
+    fn crc32_matches_the_standard_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[tokio::test]
+    async fn decodes_known_vectors_split_across_chunks() {
+        let delta = hex(CONTENT_BLOCK_DELTA);
+        let mut bytes = hex(EMPTY_MESSAGE);
+        bytes.extend_from_slice(&delta);
+        let chunks = bytes.chunks(7).map(<[u8]>::to_vec).collect();
+        let messages: Vec<_> = EventStreamDecoder::new(body(chunks), "test_provider")
+            .collect()
+            .await;
+
+        assert_eq!(messages.len(), 2);
+        let empty = messages[0].as_ref().expect("empty message");
+        assert!(empty.headers.is_empty());
+        assert!(empty.payload.is_empty());
+
+        let event = messages[1].as_ref().expect("delta message");
+        assert_eq!(event.header_str(":event-type"), Some("contentBlockDelta"));
+        assert_eq!(event.header_str(":message-type"), Some("event"));
+        assert_eq!(
+            std::str::from_utf8(&event.payload).unwrap(),
+            r#"{"contentBlockIndex":0,"delta":{"text":"Hi"}}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_corrupted_frames() {
+        let mut bytes = hex(CONTENT_BLOCK_DELTA);
+        let last = bytes.len() - 6;
+        bytes[last] ^= 0xFF;
+        let mut decoder = EventStreamDecoder::new(body(vec![bytes]), "test_provider");
+        match decoder.next().await.expect("item") {
+            Err(LLMError::Provider { message, .. }) => {
+                assert!(message.contains("message checksum"), "{message}")
+            }
+            other => panic!("unexpected item: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_truncated_streams() {
+        let bytes = hex(CONTENT_BLOCK_DELTA);
+        let mut decoder =
+            EventStreamDecoder::new(body(vec![bytes[..40].to_vec()]), "test_provider");
+        assert!(matches!(
+            decoder.next().await,
+            Some(Err(LLMError::StreamClosed { .. }))
+        ));
+        assert!(decoder.next().await.is_none());
+    }
+
+    #[test]
+    fn encoder_reproduces_the_known_vector() {
+        let frame = encode_message(
+            &[
+                (":event-type", "contentBlockDelta"),
+                (":content-type", "application/json"),
+                (":message-type", "event"),
+            ],
+            br#"{"contentBlockIndex":0,"delta":{"text":"Hi"}}"#,
+        );
+        assert_eq!(frame, hex(CONTENT_BLOCK_DELTA));
+    }
+
+    #[test]
+    fn decodes_every_header_type() {
+        let mut headers = Vec::new();
+        for (name, kind, value) in [
+            ("t", 0u8, vec![]),
+            ("f", 1, vec![]),
+            ("b", 2, vec![0xFF]),
+            ("s", 3, vec![0x01, 0x02]),
+            ("i", 4, vec![0, 0, 0, 42]),
+            ("l", 5, vec![0, 0, 0, 0, 0, 0, 1, 0]),
+            ("y", 6, vec![0, 2, 0xAB, 0xCD]),
+            ("d", 8, vec![0, 0, 1, 0x8F, 0x5C, 0x2B, 0x9E, 0x00]),
+            ("u", 9, (0u8..16).collect()),
+        ] {
+            headers.push(name.len() as u8);
+            headers.extend_from_slice(name.as_bytes());
+            headers.push(kind);
+            headers.extend_from_slice(&value);
+        }
+        let decoded = decode_headers(&headers).expect("headers");
+        let values: Vec<HeaderValue> = decoded.into_iter().map(|(_, value)| value).collect();
+        assert_eq!(
+            values,
+            vec![
+                HeaderValue::Bool(true),
+                HeaderValue::Bool(false),
+                HeaderValue::Byte(-1),
+                HeaderValue::Short(0x0102),
+                HeaderValue::Int(42),
+                HeaderValue::Long(256),
+                HeaderValue::Bytes(vec![0xAB, 0xCD]),
+                HeaderValue::Timestamp(0x018F_5C2B_9E00),
+                HeaderValue::Uuid((0u8..16).collect::<Vec<_>>().try_into().unwrap()),
+            ]
+        );
+        assert!(decode_headers(&[1, b'x', 4, 0]).is_err());
+    }
+}
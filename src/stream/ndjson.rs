@@ -0,0 +1,148 @@
+//! Decoder for newline-delimited JSON (NDJSON) bodies.
+//!
+//! Ollama and similar local runtimes stream one JSON document per line with no SSE
+//! `data:` prefix and no `[DONE]` marker; the final object carries `"done": true` instead.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::error::LLMError;
+use crate::http::HttpBodyStream;
+
+/// Splits an HTTP body into non-empty lines, each holding one JSON document.
+///
+/// Blank lines are skipped and a trailing line without a newline is still emitted when the
+/// body ends, so callers only need to parse each yielded string.
+pub struct NdjsonDecoder {
+    body: HttpBodyStream,
+    buffer: Vec<u8>,
+    pending: VecDeque<Result<String, LLMError>>,
+    provider: &'static str,
+    stream_closed: bool,
+}
+
+impl NdjsonDecoder {
+    /// Wraps a raw HTTP body stream.
+    pub fn new(body: HttpBodyStream, provider: &'static str) -> Self {
+        Self {
+            body,
+            buffer: Vec::new(),
+            pending: VecDeque::new(),
+            provider,
+            stream_closed: false,
+        }
+    }
+
+    fn push_line(&mut self, line: Vec<u8>) {
+        let line = match String::from_utf8(line) {
+            Ok(line) => line,
+            Err(err) => {
+                self.pending.push_back(Err(LLMError::Provider {
+                    provider: self.provider,
+                    message: format!("invalid UTF-8 in stream line: {err}"),
+                }));
+                return;
+            }
+        };
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            self.pending.push_back(Ok(trimmed.to_string()));
+        }
+    }
+
+    fn drain_lines(&mut self) {
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let mut line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            line.pop();
+            self.push_line(line);
+        }
+    }
+}
+
+impl Stream for NdjsonDecoder {
+    type Item = Result<String, LLMError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(line) = this.pending.pop_front() {
+                return Poll::Ready(Some(line));
+            }
+            if this.stream_closed {
+                return Poll::Ready(None);
+            }
+            match this.body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(bytes))) => {
+                    this.buffer.extend_from_slice(&bytes);
+                    this.drain_lines();
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    let mapped = match err {
+                        LLMError::Transport { message } => LLMError::StreamClosed {
+                            message: format!("stream error from {}: {message}", this.provider),
+                        },
+                        other => other,
+                    };
+                    return Poll::Ready(Some(Err(mapped)));
+                }
+                Poll::Ready(None) => {
+                    this.stream_closed = true;
+                    let rest = std::mem::take(&mut this.buffer);
+                    this.push_line(rest);
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use futures_util::stream;
+
+    use super::*;
+
+    fn body(chunks: Vec<Result<Vec<u8>, LLMError>>) -> HttpBodyStream {
+        Box::pin(stream::iter(chunks))
+    }
+
+    #[tokio::test]
+    async fn splits_lines_across_chunks() {
+        let chunks = vec![
+            Ok(b"{\"a\":1}\n{\"b\"".to_vec()),
+            Ok(b":2}\r\n\n".to_vec()),
+            Ok(b"{\"done\":true}".to_vec()),
+        ];
+        let lines: Vec<String> = NdjsonDecoder::new(body(chunks), "test_provider")
+            .map(|line| line.expect("line"))
+            .collect()
+            .await;
+        assert_eq!(lines, vec![r#"{"a":1}"#, r#"{"b":2}"#, r#"{"done":true}"#]);
+    }
+
+    #[tokio::test]
+    async fn reports_utf8_and_transport_errors() {
+        let chunks = vec![
+            Ok(b"\xff\n".to_vec()),
+            Err(LLMError::transport("connection reset".to_string())),
+        ];
+        let mut decoder = NdjsonDecoder::new(body(chunks), "test_provider");
+        assert!(matches!(
+            decoder.next().await,
+            Some(Err(LLMError::Provider {
+                provider: "test_provider",
+                ..
+            }))
+        ));
+        match decoder.next().await {
+            Some(Err(LLMError::StreamClosed { message })) => {
+                assert!(message.contains("connection reset"), "{message}");
+            }
+            other => panic!("expected StreamClosed, got {other:?}"),
+        }
+    }
+}
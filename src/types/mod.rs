@@ -8,6 +8,9 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+#[cfg(test)]
+pub(crate) mod test_support;
+
 /// Chat role string compatible with provider-specific semantics.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -90,6 +93,48 @@ pub enum ContentPart {
     ToolResult(ToolResult),
     /// Vendor-defined or opaque content payload.
     Data { data: Value },
+    /// Cache breakpoint: the prompt up to and including the preceding part is cacheable.
+    ///
+    /// Place one at the end of a message to cache the conversation through that
+    /// message. Anthropic maps it to `cache_control` on the preceding block and Bedrock
+    /// to a `cachePoint` block; providers with automatic prefix caching skip it.
+    CacheBreakpoint(CacheControl),
+}
+
+/// Cache marker for [`ContentPart::CacheBreakpoint`] and [`ToolDefinition::cache_control`].
+///
+/// # Examples
+///
+/// ```
+/// # use kotoba_llm::types::{CacheControl, CacheTtl, ContentPart, Message, Role, TextContent};
+/// let system = Message {
+///     role: Role::system(),
+///     name: None,
+///     content: vec![
+///         ContentPart::Text(TextContent { text: "<long reference manual>".into() }),
+///         ContentPart::CacheBreakpoint(CacheControl { ttl: Some(CacheTtl::OneHour) }),
+///     ],
+///     metadata: None,
+/// };
+/// assert_eq!(system.content.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheControl {
+    /// Lifetime of the cache entry; `None` keeps the provider default (5 minutes on
+    /// Anthropic).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<CacheTtl>,
+}
+
+/// Cache lifetimes accepted by providers with explicit cache breakpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CacheTtl {
+    /// Five minutes, refreshed on every hit.
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    /// One hour.
+    #[serde(rename = "1h")]
+    OneHour,
 }
 
 /// Textual content payload.
@@ -195,6 +240,10 @@ pub struct ToolDefinition {
     pub kind: ToolKind,
     /// Provider-specific metadata forwarded untouched.
     pub metadata: Option<HashMap<String, Value>>,
+    /// Cache breakpoint after this tool; tools are sent before messages, so this caches
+    /// every tool definition up to and including this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
 }
 
 /// Enumerates supported tool kinds.
@@ -242,6 +291,116 @@ pub enum ToolCallKind {
     Custom { name: String },
 }
 
+/// Invocation of a tool that the provider runs itself, such as web search, file search
+/// or code execution. Unlike [`ToolCall`], the caller does not execute it.
+///
+/// # Examples
+///
+/// ```
+/// # use kotoba_llm::types::{HostedToolAction, HostedToolCall};
+/// let call = HostedToolCall {
+///     id: Some("ws_1".to_string()),
+///     action: HostedToolAction::WebSearch {
+///         query: Some("rust 2024 edition".to_string()),
+///     },
+///     status: Some("completed".to_string()),
+/// };
+/// assert_eq!(call.action.tool_name(), "web_search");
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostedToolCall {
+    /// Provider-assigned identifier, referenced by the matching [`HostedToolResult`].
+    pub id: Option<String>,
+    /// What the tool was asked to do.
+    pub action: HostedToolAction,
+    /// Execution status reported by the provider, e.g. `completed`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+/// Input of a [`HostedToolCall`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "tool", rename_all = "snake_case")]
+pub enum HostedToolAction {
+    /// Web search; `query` is absent for page-open or find-in-page actions.
+    WebSearch { query: Option<String> },
+    /// Search over uploaded files or vector stores.
+    FileSearch { queries: Vec<String> },
+    /// Code written by the model and run in the provider's sandbox.
+    CodeExecution {
+        language: Option<String>,
+        code: String,
+    },
+    /// Any other hosted tool, with its raw input.
+    Other { name: String, input: Value },
+}
+
+impl HostedToolAction {
+    /// Tool name in snake case, or the provider's name for [`HostedToolAction::Other`].
+    pub fn tool_name(&self) -> &str {
+        match self {
+            Self::WebSearch { .. } => "web_search",
+            Self::FileSearch { .. } => "file_search",
+            Self::CodeExecution { .. } => "code_execution",
+            Self::Other { name, .. } => name,
+        }
+    }
+}
+
+/// Output of a hosted tool, as reported back by the provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostedToolResult {
+    /// Identifier of the [`HostedToolCall`] this result answers.
+    pub call_id: Option<String>,
+    /// What the tool returned.
+    pub output: HostedToolOutput,
+    /// Error code or failed outcome when the tool did not succeed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Typed content of a [`HostedToolResult`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "tool", rename_all = "snake_case")]
+pub enum HostedToolOutput {
+    /// Pages returned by a web search.
+    WebSearch { results: Vec<WebSearchResult> },
+    /// Chunks returned by a file search.
+    FileSearch { results: Vec<FileSearchResult> },
+    /// Outcome and captured output of executed code.
+    CodeExecution {
+        outcome: Option<String>,
+        output: String,
+    },
+    /// Any other hosted tool, with its raw content.
+    Other { name: String, content: Value },
+}
+
+/// One page found by a hosted web search.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebSearchResult {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Age of the page as reported by the search backend, e.g. `2 days ago`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_age: Option<String>,
+}
+
+/// One chunk found by a hosted file search.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileSearchResult {
+    pub file_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Relevance score between 0 and 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    /// Matched text, when results were requested with content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
 /// Result returned by a tool execution step.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResult {
@@ -252,6 +411,10 @@ pub struct ToolResult {
     /// Indicates whether the tool reported an error.
     #[serde(default)]
     pub is_error: bool,
+    /// Multimodal parts returned with the result, such as a screenshot; `output` stays
+    /// the primary payload.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub content: Vec<ContentPart>,
     /// Optional metadata such as captured stdio.
     pub metadata: Option<HashMap<String, Value>>,
 }
@@ -326,6 +489,26 @@ pub struct ChatOptions {
     pub presence_penalty: Option<f32>,
     /// Discourages repeating identical tokens (`-2.0..=2.0`).
     pub frequency_penalty: Option<f32>,
+    /// Top-k sampling: only the `k` most likely tokens are considered.
+    pub top_k: Option<u32>,
+    /// Sequences that stop generation when produced.
+    pub stop_sequences: Option<Vec<String>>,
+    /// Seed for best-effort deterministic sampling.
+    pub seed: Option<i64>,
+    /// Number of alternative completions to generate (`n` / `candidateCount`).
+    pub candidate_count: Option<u32>,
+    /// Stable identifier of the end user, forwarded for abuse monitoring.
+    pub user: Option<String>,
+    /// Return the log probability of each generated token.
+    pub logprobs: Option<bool>,
+    /// Number of most likely alternatives to return per token; implies `logprobs`.
+    pub top_logprobs: Option<u32>,
+    /// Routing key that groups requests sharing a long prefix (OpenAI `prompt_cache_key`);
+    /// a hint that providers without cache routing drop.
+    pub prompt_cache_key: Option<String>,
+    /// Name of a provider-side cache used as the prompt prefix (Gemini
+    /// `cachedContents/{id}`).
+    pub cached_content: Option<String>,
     /// Whether providers may execute tool calls in parallel.
     pub parallel_tool_calls: Option<bool>,
     /// Reasoning extensions for providers such as OpenAI or Anthropic.
@@ -399,6 +582,8 @@ pub enum ResponseFormat {
 /// let response = ChatResponse {
 ///     outputs: vec![OutputItem::Message {
 ///         index: 0,
+///         logprobs: Vec::new(),
+///         citations: Vec::new(),
 ///         message: Message {
 ///             role: Role::assistant(),
 ///             name: None,
@@ -408,6 +593,8 @@ pub enum ResponseFormat {
 ///     }],
 ///     usage: None,
 ///     finish_reason: None,
+///     candidate_finish_reasons: Vec::new(),
+///     prompt_safety: None,
 ///     model: Some("gpt-4o-mini".into()),
 ///     provider: ProviderMetadata { provider: "openai_chat".into(), ..Default::default() },
 /// };
@@ -419,33 +606,219 @@ pub struct ChatResponse {
     pub outputs: Vec<OutputItem>,
     /// Token usage accounting.
     pub usage: Option<TokenUsage>,
-    /// Why the response stopped.
+    /// Why the response stopped; the first candidate's reason when several were generated.
     pub finish_reason: Option<FinishReason>,
+    /// Finish reason of each candidate, by candidate index, when more than one candidate
+    /// was generated. Empty for single-candidate responses.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub candidate_finish_reasons: Vec<Option<FinishReason>>,
+    /// Safety assessment of the prompt, when the provider rates prompts (Gemini
+    /// `promptFeedback`, Azure `prompt_filter_results`). Blocked prompts surface as
+    /// [`LLMError::PromptBlocked`](crate::error::LLMError::PromptBlocked) instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_safety: Option<SafetyInfo>,
     /// Effective model identifier reported by the provider.
     pub model: Option<String>,
     /// Metadata about the provider invocation.
     pub provider: ProviderMetadata,
 }
 
+impl ChatResponse {
+    /// Splits the outputs per candidate, in candidate order.
+    ///
+    /// Always returns at least one candidate, so responses without outputs (for example
+    /// blocked by a content filter) still expose their finish reason.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use kotoba_llm::types::{ChatResponse, ContentPart, FinishReason, Message, OutputItem, ProviderMetadata, Role, TextContent};
+    /// let answer = |index: usize, text: &str| OutputItem::Message {
+    ///     index,
+    ///     logprobs: Vec::new(),
+    ///     citations: Vec::new(),
+    ///     message: Message {
+    ///         role: Role::assistant(),
+    ///         name: None,
+    ///         content: vec![ContentPart::Text(TextContent { text: text.into() })],
+    ///         metadata: None,
+    ///     },
+    /// };
+    /// let response = ChatResponse {
+    ///     outputs: vec![answer(0, "Paris"), answer(1, "Paris, France")],
+    ///     usage: None,
+    ///     finish_reason: Some(FinishReason::Stop),
+    ///     candidate_finish_reasons: vec![Some(FinishReason::Stop), Some(FinishReason::Length)],
+    ///     prompt_safety: None,
+    ///     model: None,
+    ///     provider: ProviderMetadata::default(),
+    /// };
+    /// let candidates = response.into_candidates();
+    /// assert_eq!(candidates.len(), 2);
+    /// assert_eq!(candidates[1].text(), "Paris, France");
+    /// assert!(matches!(candidates[1].finish_reason, Some(FinishReason::Length)));
+    /// ```
+    pub fn into_candidates(self) -> Vec<Candidate> {
+        let count = self
+            .outputs
+            .iter()
+            .map(|item| item.index() + 1)
+            .max()
+            .unwrap_or(0)
+            .max(self.candidate_finish_reasons.len())
+            .max(1);
+        let mut candidates: Vec<Candidate> = (0..count)
+            .map(|index| Candidate {
+                index,
+                outputs: Vec::new(),
+                finish_reason: match self.candidate_finish_reasons.get(index) {
+                    Some(reason) => reason.clone(),
+                    None if self.candidate_finish_reasons.is_empty() => self.finish_reason.clone(),
+                    None => None,
+                },
+            })
+            .collect();
+        for item in self.outputs {
+            candidates[item.index()].outputs.push(item);
+        }
+        candidates
+    }
+
+    /// Combines single-candidate responses, such as parallel calls emulating `n`, into one
+    /// response whose candidate 0 is `first` and candidate `i` is the `i`-th of `rest`.
+    ///
+    /// Usage is summed; the model, provider metadata and overall finish reason come from
+    /// `first`.
+    pub fn merge_candidates(
+        first: ChatResponse,
+        rest: impl IntoIterator<Item = ChatResponse>,
+    ) -> ChatResponse {
+        let mut merged = first;
+        merged.outputs = std::mem::take(&mut merged.outputs)
+            .into_iter()
+            .map(|item| item.with_index(0))
+            .collect();
+        merged.candidate_finish_reasons = vec![merged.finish_reason.clone()];
+        for (index, response) in rest.into_iter().enumerate() {
+            merged.outputs.extend(
+                response
+                    .outputs
+                    .into_iter()
+                    .map(|item| item.with_index(index + 1)),
+            );
+            merged.candidate_finish_reasons.push(response.finish_reason);
+            merged.usage = match (merged.usage.take(), response.usage) {
+                (Some(total), Some(usage)) => Some(total.combine(usage)),
+                (total, usage) => total.or(usage),
+            };
+        }
+        merged
+    }
+}
+
+/// Outputs of one candidate, produced by [`ChatResponse::into_candidates`].
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Candidate index, matching [`OutputItem`] and [`MessageDelta`] indices.
+    pub index: usize,
+    /// Outputs belonging to this candidate, in response order.
+    pub outputs: Vec<OutputItem>,
+    /// Why this candidate stopped.
+    pub finish_reason: Option<FinishReason>,
+}
+
+impl Candidate {
+    /// Concatenated text of the candidate's assistant messages.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for item in &self.outputs {
+            if let OutputItem::Message { message, .. } = item {
+                for part in &message.content {
+                    if let ContentPart::Text(TextContent { text: chunk }) = part {
+                        text.push_str(chunk);
+                    }
+                }
+            }
+        }
+        text
+    }
+}
+
 /// Individual output entry emitted by the provider.
 ///
-/// The `index` mirrors upstream array indices so streaming deltas can be merged
-/// deterministically.
+/// The `index` is the candidate the output belongs to: always `0` unless several
+/// candidates were requested through [`ChatOptions::candidate_count`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum OutputItem {
     /// Completed assistant message.
-    Message { message: Message, index: usize },
+    Message {
+        message: Message,
+        index: usize,
+        /// Per-token log probabilities when [`ChatOptions::logprobs`] was requested.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        logprobs: Vec<TokenLogprob>,
+        /// Sources backing the message text.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        citations: Vec<Citation>,
+    },
     /// Tool invocation emitted by the assistant.
     ToolCall { call: ToolCall, index: usize },
     /// Tool execution result authored by the tool role.
     ToolResult { result: ToolResult, index: usize },
+    /// Invocation of a tool the provider runs itself.
+    HostedToolCall { call: HostedToolCall, index: usize },
+    /// Output of a tool the provider ran itself.
+    HostedToolResult {
+        result: HostedToolResult,
+        index: usize,
+    },
     /// Reasoning trace text.
     Reasoning { text: String, index: usize },
+    /// Search queries and suggestions behind a grounded answer.
+    Grounding { grounding: Grounding, index: usize },
+    /// Safety ratings of the candidate, and why it was blocked if it was.
+    Safety { safety: SafetyInfo, index: usize },
+    /// Explanation the model gave instead of answering.
+    Refusal { text: String, index: usize },
     /// Provider-specific payload.
     Custom { data: Value, index: usize },
 }
 
+impl OutputItem {
+    /// Candidate index of the output.
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Message { index, .. }
+            | Self::ToolCall { index, .. }
+            | Self::ToolResult { index, .. }
+            | Self::HostedToolCall { index, .. }
+            | Self::HostedToolResult { index, .. }
+            | Self::Reasoning { index, .. }
+            | Self::Grounding { index, .. }
+            | Self::Safety { index, .. }
+            | Self::Refusal { index, .. }
+            | Self::Custom { index, .. } => *index,
+        }
+    }
+
+    fn with_index(mut self, candidate: usize) -> Self {
+        match &mut self {
+            Self::Message { index, .. }
+            | Self::ToolCall { index, .. }
+            | Self::ToolResult { index, .. }
+            | Self::HostedToolCall { index, .. }
+            | Self::HostedToolResult { index, .. }
+            | Self::Reasoning { index, .. }
+            | Self::Grounding { index, .. }
+            | Self::Safety { index, .. }
+            | Self::Refusal { index, .. }
+            | Self::Custom { index, .. } => *index = candidate,
+        }
+        self
+    }
+}
+
 /// Streaming chunk representing incremental response data.
 ///
 /// Streaming transports emit one or more chunks until `is_terminal` becomes
@@ -473,6 +846,15 @@ pub enum ChatEvent {
     ToolCallDelta(ToolCallDelta),
     /// Tool-result delta.
     ToolResultDelta(ToolResultDelta),
+    /// Safety assessment of the prompt, sent before the first content.
+    PromptSafety { safety: SafetyInfo },
+    /// Completed invocation of a hosted tool, for the candidate at `index`.
+    HostedToolCall { index: usize, call: HostedToolCall },
+    /// Output of a hosted tool, for the candidate at `index`.
+    HostedToolResult {
+        index: usize,
+        result: HostedToolResult,
+    },
     /// Provider-specific raw event.
     Custom { data: Value },
 }
@@ -480,7 +862,7 @@ pub enum ChatEvent {
 /// Delta describing textual content generated so far.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageDelta {
-    /// Target message index within the response.
+    /// Candidate the delta belongs to, as in [`OutputItem`] indices.
     pub index: usize,
     /// Optional role override.
     pub role: Option<Role>,
@@ -488,6 +870,280 @@ pub struct MessageDelta {
     pub content: Vec<ContentDelta>,
     /// Why the response stopped.
     pub finish_reason: Option<FinishReason>,
+    /// Log probabilities of the tokens in this delta.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logprobs: Vec<TokenLogprob>,
+}
+
+/// Log probability of one generated token.
+///
+/// The field layout matches OpenAI's `logprobs.content` entries.
+///
+/// # Examples
+///
+/// ```
+/// # use kotoba_llm::types::TokenLogprob;
+/// let token = TokenLogprob {
+///     token: "yes".into(),
+///     logprob: -0.105,
+///     bytes: None,
+///     top_logprobs: Vec::new(),
+/// };
+/// assert!((token.probability() - 0.9).abs() < 0.001);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenLogprob {
+    /// Token text.
+    pub token: String,
+    /// Natural log of the token probability.
+    pub logprob: f64,
+    /// UTF-8 bytes of the token, when the provider reports them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<Vec<u8>>,
+    /// Most likely alternatives at this position, requested via
+    /// [`ChatOptions::top_logprobs`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+impl TokenLogprob {
+    /// Probability of the token in `0.0..=1.0`.
+    pub fn probability(&self) -> f64 {
+        self.logprob.exp()
+    }
+}
+
+/// Alternative token considered at one position of a [`TokenLogprob`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopLogprob {
+    /// Token text.
+    pub token: String,
+    /// Natural log of the token probability.
+    pub logprob: f64,
+    /// UTF-8 bytes of the token, when the provider reports them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<Vec<u8>>,
+}
+
+/// Source attribution for generated text.
+///
+/// `part` and `span` locate the supported text. In responses `part` indexes
+/// [`Message::content`]; in [`ContentDelta::Citation`] it is the provider's content block
+/// index. `span` is a character range within that part's text, or `None` when the
+/// citation covers the whole part.
+///
+/// # Examples
+///
+/// ```
+/// # use kotoba_llm::types::{Citation, CitationSource, TextSpan};
+/// let citation = Citation {
+///     part: 0,
+///     span: Some(TextSpan { start: 0, end: 5 }),
+///     source: CitationSource::Url {
+///         url: "https://en.wikipedia.org/wiki/Paris".into(),
+///         title: Some("Paris".into()),
+///     },
+///     cited_text: None,
+///     metadata: None,
+/// };
+/// let span = citation.span.expect("span");
+/// assert_eq!(span.slice("Paris is the capital of France."), Some("Paris"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    /// Content part holding the cited text.
+    pub part: usize,
+    /// Character range of the cited text within the part.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<TextSpan>,
+    /// Where the supporting content comes from.
+    pub source: CitationSource,
+    /// Passage quoted from the source, when the provider returns it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cited_text: Option<String>,
+    /// Provider-specific details such as page ranges or confidence scores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, Value>>,
+}
+
+/// Half-open range of characters (Unicode scalar values) within a text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextSpan {
+    /// First character of the range.
+    pub start: usize,
+    /// One past the last character of the range.
+    pub end: usize,
+}
+
+impl TextSpan {
+    /// Returns the spanned part of `text`, or `None` when the span is out of range.
+    pub fn slice<'a>(&self, text: &'a str) -> Option<&'a str> {
+        if self.start > self.end {
+            return None;
+        }
+        let byte_offset = |chars: usize| {
+            text.char_indices()
+                .map(|(offset, _)| offset)
+                .chain(std::iter::once(text.len()))
+                .nth(chars)
+        };
+        Some(&text[byte_offset(self.start)?..byte_offset(self.end)?])
+    }
+
+    /// Converts UTF-8 byte offsets into `text`, as reported by Gemini, into a character span.
+    #[cfg(feature = "gemini")]
+    pub(crate) fn from_byte_range(text: &str, start: usize, end: usize) -> Option<Self> {
+        let chars_before = |offset: usize| text.get(..offset).map(|prefix| prefix.chars().count());
+        Some(Self {
+            start: chars_before(start)?,
+            end: chars_before(end)?,
+        })
+    }
+}
+
+/// Origin of a [`Citation`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CitationSource {
+    /// Web page, e.g. from a search tool.
+    Url { url: String, title: Option<String> },
+    /// Document supplied with the request or retrieved from a store; `index` is its
+    /// position among the request documents when the provider reports it.
+    Document {
+        index: Option<usize>,
+        id: Option<String>,
+        title: Option<String>,
+    },
+    /// Provider-managed file, e.g. from OpenAI file search.
+    File {
+        file_id: String,
+        filename: Option<String>,
+    },
+}
+
+/// Search grounding that accompanies a grounded answer.
+///
+/// Google requires apps that show Gemini Search-grounded answers to also render
+/// `search_entry_point`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grounding {
+    /// Queries the model issued to the search tool.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_queries: Vec<String>,
+    /// Rendered HTML of the search suggestions chip.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_entry_point: Option<String>,
+}
+
+/// Safety assessment of a prompt or a generated candidate.
+///
+/// # Examples
+///
+/// ```
+/// # use kotoba_llm::types::{SafetyInfo, SafetyLevel, SafetyRating};
+/// let safety = SafetyInfo {
+///     ratings: vec![SafetyRating {
+///         category: "violence".to_string(),
+///         probability: None,
+///         severity: Some(SafetyLevel::Medium),
+///         blocked: true,
+///         metadata: None,
+///     }],
+///     block_reason: Some("content_filter".to_string()),
+///     message: None,
+/// };
+/// assert!(safety.is_blocked());
+/// assert_eq!(safety.blocked_categories(), ["violence"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyInfo {
+    /// Per-category ratings, in provider order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ratings: Vec<SafetyRating>,
+    /// Provider reason for blocking, e.g. Gemini `SAFETY` or Azure `content_filter`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_reason: Option<String>,
+    /// Human-readable explanation accompanying the block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl SafetyInfo {
+    /// Whether the provider blocked the content, as a whole or for any category.
+    pub fn is_blocked(&self) -> bool {
+        self.block_reason.is_some() || self.ratings.iter().any(|rating| rating.blocked)
+    }
+
+    /// Categories whose rating caused the block.
+    pub fn blocked_categories(&self) -> Vec<&str> {
+        self.ratings
+            .iter()
+            .filter(|rating| rating.blocked)
+            .map(|rating| rating.category.as_str())
+            .collect()
+    }
+}
+
+/// Rating of one harm category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyRating {
+    /// Category as named by the provider, e.g. `HARM_CATEGORY_HATE_SPEECH` or `hate`.
+    pub category: String,
+    /// Likelihood that the content is harmful (Gemini `probability`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probability: Option<SafetyLevel>,
+    /// How harmful the content is (Gemini on Vertex AI and Azure `severity`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<SafetyLevel>,
+    /// Whether this category caused the content to be filtered.
+    #[serde(default)]
+    pub blocked: bool,
+    /// Provider-specific details such as numeric scores or Azure `detected` flags.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, Value>>,
+}
+
+/// Graded level used for both probability and severity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafetyLevel {
+    /// Gemini `NEGLIGIBLE`, Azure `safe`.
+    Negligible,
+    Low,
+    Medium,
+    High,
+    Other(String),
+}
+
+impl SafetyLevel {
+    /// Lower-case name of the level; the provider's value for [`SafetyLevel::Other`].
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Negligible => "negligible",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Other(raw) => raw,
+        }
+    }
+
+    /// Parses Gemini (`HIGH`, `HARM_SEVERITY_HIGH`) and Azure (`high`) levels;
+    /// unspecified levels yield `None`.
+    #[cfg(any(feature = "openai", feature = "gemini"))]
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        let level = raw
+            .trim_start_matches("HARM_SEVERITY_")
+            .trim_start_matches("HARM_PROBABILITY_")
+            .to_ascii_lowercase();
+        match level.as_str() {
+            "" | "unspecified" => None,
+            "negligible" | "safe" => Some(Self::Negligible),
+            "low" => Some(Self::Low),
+            "medium" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            _ => Some(Self::Other(raw.to_string())),
+        }
+    }
 }
 
 /// Variants for streamed content.
@@ -500,6 +1156,14 @@ pub enum ContentDelta {
     Json { value: Value },
     /// Embedded tool-call delta.
     ToolCall { delta: ToolCallDelta },
+    /// Source for text of this message, streamed by providers that cite incrementally.
+    Citation { citation: Citation },
+    /// Search grounding details, usually sent with the final delta.
+    Grounding { grounding: Grounding },
+    /// Safety ratings of the text generated so far.
+    Safety { safety: SafetyInfo },
+    /// Refusal text fragment, streamed instead of regular text.
+    Refusal { text: String },
 }
 
 /// Delta describing the ongoing tool call.
@@ -548,6 +1212,8 @@ pub struct ToolResultDelta {
 ///     completion_tokens: Some(200),
 ///     reasoning_tokens: None,
 ///     total_tokens: Some(1400),
+///     cached_input_tokens: Some(1024),
+///     cache_creation_tokens: None,
 ///     details: None,
 /// };
 /// assert_eq!(usage.total_tokens, Some(1400));
@@ -562,10 +1228,56 @@ pub struct TokenUsage {
     pub reasoning_tokens: Option<u64>,
     /// Total tokens across prompt, completion, and reasoning.
     pub total_tokens: Option<u64>,
+    /// Prompt tokens served from the provider's prompt cache.
+    ///
+    /// OpenAI, Gemini and Cohere count these inside `prompt_tokens`; Anthropic and Bedrock
+    /// report them separately, so `prompt_tokens` only covers uncached input there.
+    #[serde(default)]
+    pub cached_input_tokens: Option<u64>,
+    /// Prompt tokens written to the cache by this request (Anthropic and Bedrock).
+    #[serde(default)]
+    pub cache_creation_tokens: Option<u64>,
     /// Provider-specific accounting details.
     pub details: Option<HashMap<String, Value>>,
 }
 
+impl TokenUsage {
+    /// Adds the counts of two calls; numeric `details` entries are summed as well.
+    fn combine(self, other: TokenUsage) -> TokenUsage {
+        fn sum(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            }
+        }
+        let details = match (self.details, other.details) {
+            (Some(mut details), Some(other)) => {
+                for (key, value) in other {
+                    match (details.get(&key).and_then(Value::as_u64), value.as_u64()) {
+                        (Some(a), Some(b)) => {
+                            details.insert(key, Value::from(a + b));
+                        }
+                        _ => {
+                            details.entry(key).or_insert(value);
+                        }
+                    }
+                }
+                Some(details)
+            }
+            (details, other) => details.or(other),
+        };
+        TokenUsage {
+            prompt_tokens: sum(self.prompt_tokens, other.prompt_tokens),
+            completion_tokens: sum(self.completion_tokens, other.completion_tokens),
+            reasoning_tokens: sum(self.reasoning_tokens, other.reasoning_tokens),
+            total_tokens: sum(self.total_tokens, other.total_tokens),
+            cached_input_tokens: sum(self.cached_input_tokens, other.cached_input_tokens),
+            cache_creation_tokens: sum(self.cache_creation_tokens, other.cache_creation_tokens),
+            details,
+        }
+    }
+}
+
 /// Why a chat response stopped generating content.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -600,6 +1312,7 @@ pub struct ProviderMetadata {
 /// [`crate::client::LLMClient`] exposes capability lookups so applications can
 /// pick compatible providers before dispatching a request.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct CapabilityDescriptor {
     /// Whether the provider supports streaming outputs.
     pub supports_stream: bool,
@@ -617,6 +1330,23 @@ pub struct CapabilityDescriptor {
     pub supports_parallel_tool_calls: bool,
 }
 
+impl CapabilityDescriptor {
+    /// Returns the capabilities present in both `self` and `other`.
+    pub fn intersect(&self, other: &CapabilityDescriptor) -> CapabilityDescriptor {
+        CapabilityDescriptor {
+            supports_stream: self.supports_stream && other.supports_stream,
+            supports_image_input: self.supports_image_input && other.supports_image_input,
+            supports_audio_input: self.supports_audio_input && other.supports_audio_input,
+            supports_video_input: self.supports_video_input && other.supports_video_input,
+            supports_tools: self.supports_tools && other.supports_tools,
+            supports_structured_output: self.supports_structured_output
+                && other.supports_structured_output,
+            supports_parallel_tool_calls: self.supports_parallel_tool_calls
+                && other.supports_parallel_tool_calls,
+        }
+    }
+}
+
 /// Groups provider families that share similar tokenization characteristics.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProviderType {
@@ -757,6 +1487,7 @@ impl TokenEstimator {
                 self.estimate_text(&serde_json::to_string(result).unwrap_or_default())
             }
             ContentPart::Data { data } => self.estimate_text(&data.to_string()),
+            ContentPart::CacheBreakpoint(_) => 0,
         }
     }
 
@@ -875,4 +1606,73 @@ mod tests {
         assert!(estimate.by_role.contains_key("system"));
         assert!(estimate.by_role.contains_key("user"));
     }
+
+    #[test]
+    fn merge_candidates_reindexes_outputs_and_sums_usage() {
+        let single = |text: &str, reason: FinishReason, details: u64| ChatResponse {
+            outputs: vec![OutputItem::Message {
+                message: Message {
+                    role: Role::assistant(),
+                    name: None,
+                    content: vec![ContentPart::Text(TextContent {
+                        text: text.to_string(),
+                    })],
+                    metadata: None,
+                },
+                index: 0,
+                logprobs: Vec::new(),
+                citations: Vec::new(),
+            }],
+            usage: Some(TokenUsage {
+                prompt_tokens: Some(10),
+                completion_tokens: Some(2),
+                reasoning_tokens: None,
+                total_tokens: Some(12),
+                details: Some(HashMap::from([(
+                    "cache_read_input_tokens".to_string(),
+                    Value::from(details),
+                )])),
+                cached_input_tokens: None,
+                cache_creation_tokens: None,
+            }),
+            finish_reason: Some(reason),
+            candidate_finish_reasons: Vec::new(),
+            prompt_safety: None,
+            model: Some("m".to_string()),
+            provider: ProviderMetadata::default(),
+        };
+
+        let merged = ChatResponse::merge_candidates(
+            single("a", FinishReason::Stop, 1),
+            [single("b", FinishReason::Length, 2)],
+        );
+        let usage = merged.usage.clone().expect("usage");
+        assert_eq!(usage.prompt_tokens, Some(20));
+        assert_eq!(usage.total_tokens, Some(24));
+        assert_eq!(usage.reasoning_tokens, None);
+        assert_eq!(
+            usage.details.expect("details")["cache_read_input_tokens"],
+            Value::from(3)
+        );
+        assert!(matches!(merged.finish_reason, Some(FinishReason::Stop)));
+
+        let candidates = merged.into_candidates();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[1].text(), "b");
+        assert_eq!(candidates[1].outputs[0].index(), 1);
+        assert!(matches!(
+            candidates[1].finish_reason,
+            Some(FinishReason::Length)
+        ));
+
+        // A response without outputs still yields its finish reason.
+        let mut blocked = single("", FinishReason::ContentFilter, 0);
+        blocked.outputs.clear();
+        let candidates = blocked.into_candidates();
+        assert_eq!(candidates.len(), 1);
+        assert!(matches!(
+            candidates[0].finish_reason,
+            Some(FinishReason::ContentFilter)
+        ));
+    }
 }
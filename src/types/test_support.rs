@@ -0,0 +1,85 @@
+//! Request fixtures shared by provider tests.
+
+// Each fixture is only used by the providers enabled in a given feature set.
+#![allow(dead_code)]
+
+use serde_json::json;
+
+use super::{
+    CacheControl, ChatOptions, ChatRequest, ContentPart, Message, Role, TextContent,
+    ToolDefinition, ToolKind,
+};
+
+/// Returns a request holding one user message with `text` and default options.
+pub(crate) fn text_request(text: &str) -> ChatRequest {
+    ChatRequest {
+        messages: vec![Message {
+            role: Role::user(),
+            name: None,
+            content: vec![ContentPart::Text(TextContent {
+                text: text.to_string(),
+            })],
+            metadata: None,
+        }],
+        options: ChatOptions::default(),
+        tools: Vec::new(),
+        tool_choice: None,
+        response_format: None,
+        metadata: None,
+    }
+}
+
+/// Returns a request carrying cache breakpoints on the system prompt, mid-message and on a
+/// tool, paired with the same request stripped of every breakpoint.
+///
+/// Providers without explicit cache markers must map both to the same body.
+pub(crate) fn request_with_cache_breakpoints() -> (ChatRequest, ChatRequest) {
+    let text = |text: &str| {
+        ContentPart::Text(TextContent {
+            text: text.to_string(),
+        })
+    };
+    let request = ChatRequest {
+        messages: vec![
+            Message {
+                role: Role::system(),
+                name: None,
+                content: vec![
+                    text("Reference manual"),
+                    ContentPart::CacheBreakpoint(CacheControl::default()),
+                ],
+                metadata: None,
+            },
+            Message {
+                role: Role::user(),
+                name: None,
+                content: vec![
+                    text("Long document"),
+                    ContentPart::CacheBreakpoint(CacheControl::default()),
+                    text("Question"),
+                ],
+                metadata: None,
+            },
+        ],
+        options: ChatOptions::default(),
+        tools: vec![ToolDefinition {
+            name: "lookup".to_string(),
+            description: None,
+            input_schema: Some(json!({ "type": "object" })),
+            kind: ToolKind::Function,
+            metadata: None,
+            cache_control: Some(CacheControl::default()),
+        }],
+        tool_choice: None,
+        response_format: None,
+        metadata: None,
+    };
+    let mut plain = request.clone();
+    for message in &mut plain.messages {
+        message
+            .content
+            .retain(|part| !matches!(part, ContentPart::CacheBreakpoint(_)));
+    }
+    plain.tools[0].cache_control = None;
+    (request, plain)
+}
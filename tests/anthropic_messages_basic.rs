@@ -174,6 +174,7 @@ async fn anthropic_messages_basic_tool_call_dialog_live() {
             })),
             kind: ToolKind::Function,
             metadata: None,
+            cache_control: None,
         }],
         tool_choice: Some(ToolChoice::Tool {
             name: "get_weather".to_string(),
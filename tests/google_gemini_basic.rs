@@ -174,6 +174,7 @@ async fn google_gemini_basic_tool_call_dialog_live() {
             })),
             kind: ToolKind::Function,
             metadata: None,
+            cache_control: None,
         }],
         tool_choice: Some(ToolChoice::Tool {
             name: "get_current_weather".to_string(),
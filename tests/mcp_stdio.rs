@@ -0,0 +1,184 @@
+//! Drives the MCP client against a real stdio server process.
+//!
+//! The test binary doubles as the server: with `KOTOBA_MCP_FIXTURE` set it serves a few
+//! tools over stdin/stdout instead of running the checks.
+
+use std::env;
+use std::io::{self, BufRead, Write};
+
+use kotoba_llm::mcp::{McpClient, McpToolRouter};
+use kotoba_llm::types::{ContentPart, ImageSource, ToolCall, ToolCallKind};
+use serde_json::{Value, json};
+
+const FIXTURE_ENV: &str = "KOTOBA_MCP_FIXTURE";
+
+fn main() {
+    if env::var_os(FIXTURE_ENV).is_some() {
+        serve();
+        return;
+    }
+    let runtime = tokio::runtime::Runtime::new().expect("runtime");
+    runtime.block_on(stdio_server_tools_are_discovered_and_called());
+    println!("test stdio_server_tools_are_discovered_and_called ... ok");
+}
+
+async fn stdio_server_tools_are_discovered_and_called() {
+    let mut command = tokio::process::Command::new(env::current_exe().expect("current exe"));
+    command.env(FIXTURE_ENV, "1");
+    let client = McpClient::spawn("fixture", command).await.expect("spawn");
+    assert_eq!(client.server_info().protocol_version, "2025-06-18");
+    assert_eq!(client.server_info().server_info.name, "fixture-server");
+
+    let mut router = McpToolRouter::new();
+    router.add_server(client).await.expect("add server");
+    let names: Vec<&str> = router
+        .tools()
+        .iter()
+        .map(|tool| tool.name.as_str())
+        .collect();
+    assert_eq!(names, ["echo", "screenshot", "fail"]);
+    assert_eq!(
+        router.tools()[0].input_schema.as_ref().unwrap()["required"],
+        json!(["text"])
+    );
+
+    // The server interleaves a log notification and a ping before answering `echo`.
+    let echo = router
+        .call(&call("call_1", "echo", json!({ "text": "hello" })))
+        .await
+        .expect("echo");
+    assert_eq!(echo.call_id.as_deref(), Some("call_1"));
+    assert_eq!(echo.output, json!("hello"));
+    assert!(!echo.is_error);
+
+    let screenshot = router
+        .call(&call("call_2", "screenshot", json!("{}")))
+        .await
+        .expect("screenshot");
+    assert_eq!(screenshot.output, json!("captured"));
+    assert!(matches!(
+        &screenshot.content[..],
+        [ContentPart::Image(image)]
+            if matches!(&image.source, ImageSource::Base64 { data, mime_type: Some(mime) }
+                if data == "iVBORw0KGgo=" && mime == "image/png")
+    ));
+
+    let failed = router
+        .call(&call("call_3", "fail", json!({})))
+        .await
+        .expect("fail");
+    assert!(failed.is_error);
+    assert_eq!(failed.output, json!("disk is full"));
+
+    let message = router
+        .tool_message(&[
+            call("call_4", "echo", json!({ "text": "a" })),
+            call("call_5", "echo", json!({ "text": "b" })),
+        ])
+        .await
+        .expect("tool message");
+    let outputs: Vec<&Value> = message
+        .content
+        .iter()
+        .map(|part| match part {
+            ContentPart::ToolResult(result) => &result.output,
+            other => panic!("unexpected part: {other:?}"),
+        })
+        .collect();
+    assert_eq!(outputs, [&json!("a"), &json!("b")]);
+
+    let unknown = router.servers()[0]
+        .call_tool("nope", json!({}))
+        .await
+        .expect_err("unknown tool");
+    assert!(unknown.to_string().contains("Unknown tool: nope"));
+}
+
+fn call(id: &str, name: &str, arguments: Value) -> ToolCall {
+    ToolCall {
+        id: Some(id.to_string()),
+        name: name.to_string(),
+        arguments,
+        kind: ToolCallKind::Function,
+    }
+}
+
+/// Minimal MCP server speaking newline-delimited JSON-RPC on stdin/stdout.
+fn serve() {
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut stdout = io::stdout();
+    let mut send = |message: Value| {
+        writeln!(stdout, "{message}").expect("write");
+        stdout.flush().expect("flush");
+    };
+
+    while let Some(Ok(line)) = lines.next() {
+        let request: Value = serde_json::from_str(&line).expect("request");
+        let Some(id) = request.get("id").cloned() else {
+            continue;
+        };
+        let params = &request["params"];
+        let result = match request["method"].as_str().unwrap_or_default() {
+            "initialize" => json!({
+                "protocolVersion": params["protocolVersion"],
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "fixture-server", "version": "0.1.0" }
+            }),
+            "tools/list" if params.get("cursor").is_none() => json!({
+                "tools": [{
+                    "name": "echo",
+                    "description": "Echoes the text back",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": { "text": { "type": "string" } },
+                        "required": ["text"]
+                    }
+                }],
+                "nextCursor": "page-2"
+            }),
+            "tools/list" => json!({
+                "tools": [
+                    { "name": "screenshot", "inputSchema": { "type": "object" } },
+                    { "name": "fail", "inputSchema": { "type": "object" } }
+                ]
+            }),
+            "tools/call" => match params["name"].as_str().unwrap_or_default() {
+                "echo" => {
+                    send(json!({
+                        "jsonrpc": "2.0",
+                        "method": "notifications/message",
+                        "params": { "level": "info", "data": "echoing" }
+                    }));
+                    send(json!({ "jsonrpc": "2.0", "id": "ping-1", "method": "ping" }));
+                    let pong: Value =
+                        serde_json::from_str(&lines.next().expect("pong").expect("pong"))
+                            .expect("pong");
+                    assert_eq!(pong["id"], "ping-1");
+                    assert_eq!(pong["result"], json!({}));
+                    json!({ "content": [{ "type": "text", "text": params["arguments"]["text"] }] })
+                }
+                "screenshot" => json!({
+                    "content": [
+                        { "type": "text", "text": "captured" },
+                        { "type": "image", "data": "iVBORw0KGgo=", "mimeType": "image/png" }
+                    ]
+                }),
+                "fail" => json!({
+                    "content": [{ "type": "text", "text": "disk is full" }],
+                    "isError": true
+                }),
+                other => {
+                    send(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": -32602, "message": format!("Unknown tool: {other}") }
+                    }));
+                    continue;
+                }
+            },
+            other => panic!("unexpected method {other}"),
+        };
+        send(json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+    }
+}
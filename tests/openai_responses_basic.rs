@@ -207,6 +207,7 @@ async fn openai_responses_basic_tool_call_dialog_live() {
             })),
             kind: ToolKind::Function,
             metadata: None,
+            cache_control: None,
         }],
         tool_choice: Some(ToolChoice::Tool {
             name: "get_current_weather".to_string(),